@@ -8,12 +8,12 @@ client = SmartVaults.open("/home/user/.smartvaults", "test", "test", Network.TES
 policies = []
 
 class SyncNotifications(SyncHandler):
-    def handle(self, msg):
+    def handle(self, seq, msg):
         print("Refreshing...")
         global policies
         policies = client.get_policies()
 
-handle = client.handle_sync(SyncNotifications())
+handle = client.handle_sync(SyncNotifications(), 0)
 
 while True:
     time.sleep(5.0)
@@ -6,13 +6,13 @@ init_desktop_logger("/home/user/.smartvaults", Network.TESTNET)
 client = SmartVaults.restore("/home/user/.smartvaults", "name", "test", "mnemonic", None, Network.TESTNET)
 
 class SyncNotifications(SyncHandler):
-    def handle(self, msg):
+    def handle(self, seq, msg):
         print("Refreshing...")
         policies = client.get_policies()
         for p in policies:
             print(p.policy().name())
 
-handle = client.handle_sync(SyncNotifications())
+handle = client.handle_sync(SyncNotifications(), 0)
 
 while True:
     time.sleep(10.0)
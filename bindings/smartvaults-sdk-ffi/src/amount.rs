@@ -28,6 +28,14 @@ impl Amount {
         }
     }
 
+    /// Send `amount` minus the fee to the recipient
+    #[uniffi::constructor]
+    pub fn custom_subtract_fee(amount: u64) -> Self {
+        Self {
+            inner: core::Amount::CustomSubtractFee(amount),
+        }
+    }
+
     #[uniffi::constructor]
     pub fn max() -> Self {
         Self {
@@ -14,20 +14,22 @@ use nostr_ffi::{EventId, Keys, Metadata, NostrConnectURI, PublicKey};
 use nostr_sdk_ffi::profile::Profile;
 use nostr_sdk_ffi::Relay;
 use smartvaults_sdk::client;
-use smartvaults_sdk::core::bips::bip39::Mnemonic;
 use smartvaults_sdk::core::bitcoin::psbt::PartiallySignedTransaction;
 use smartvaults_sdk::core::bitcoin::{Address, Txid};
 use smartvaults_sdk::core::miniscript::Descriptor;
+use smartvaults_sdk::core::mnemonic;
 use smartvaults_sdk::core::types::{FeeRate, Priority};
 use smartvaults_sdk::nostr::block_on;
+use smartvaults_sdk::types::RelayRole;
 use uniffi::Object;
 
 use crate::error::Result;
 use crate::{
-    AbortHandle, AddressIndex, Amount, Balance, CompletedProposal, Config, GetAddress, GetApproval,
-    GetCompletedProposal, GetPolicy, GetProposal, GetSharedSigner, GetSigner, GetTransaction,
-    KeyAgent, Message, Network, NostrConnectRequest, NostrConnectSession, OutPoint, Period,
-    PolicyTemplate, Seed, Signer, SignerOffering, Utxo, WordCount,
+    AbortHandle, AddressIndex, Amount, Balance, CoinSelection, CompletedProposal, Config,
+    GetAddress, GetApproval, GetCompletedProposal, GetPolicy, GetProposal, GetSharedSigner,
+    GetSigner, GetTransaction, KeyAgent, Language, Message, Network, NostrConnectRequest,
+    NostrConnectSession, OutPoint, Period, PolicyTemplate, Seed, Signer, SignerOffering,
+    SyncProfile, Utxo, WordCount,
 };
 
 #[derive(Object)]
@@ -82,6 +84,7 @@ impl SmartVaults {
         password: String,
         confirm_password: String,
         word_count: WordCount,
+        language: Language,
         passphrase: Option<String>,
         network: Network,
     ) -> Result<Self> {
@@ -93,6 +96,7 @@ impl SmartVaults {
                     || Ok(password),
                     || Ok(confirm_password),
                     word_count.into(),
+                    language.into(),
                     || Ok(passphrase),
                     network.into(),
                 )
@@ -103,6 +107,8 @@ impl SmartVaults {
     }
 
     /// Restore keychain
+    ///
+    /// The mnemonic language is detected if `language` is not set.
     #[uniffi::constructor]
     pub fn restore(
         base_path: String,
@@ -110,11 +116,12 @@ impl SmartVaults {
         password: String,
         confirm_password: String,
         mnemonic: String,
+        language: Option<Language>,
         passphrase: Option<String>,
         network: Network,
     ) -> Result<Self> {
         block_on(async move {
-            let mnemonic = Mnemonic::from_str(&mnemonic)?;
+            let mnemonic = mnemonic::parse(mnemonic, language.map(|l| l.into()))?;
             Ok(Self {
                 inner: client::SmartVaults::restore(
                     base_path,
@@ -204,7 +211,22 @@ impl SmartVaults {
 
     /// Add new relay
     pub fn add_relay(&self, url: String) -> Result<()> {
-        block_on(async move { Ok(self.inner.add_relay(url, None).await?) })
+        block_on(async move {
+            Ok(self
+                .inner
+                .add_relay(url, None, RelayRole::default())
+                .await?)
+        })
+    }
+
+    /// Add new relay used only to read (`read`) or to publish (`write`)
+    pub fn add_relay_with_role(&self, url: String, read: bool, write: bool) -> Result<()> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .add_relay(url, None, RelayRole { read, write })
+                .await?)
+        })
     }
 
     /// Get list of default relays
@@ -239,6 +261,18 @@ impl SmartVaults {
         Arc::new(self.inner.config().into())
     }
 
+    /// Set sync profile
+    ///
+    /// Switching from light to full backfills the missing history in background.
+    pub fn set_sync_profile(&self, profile: SyncProfile) -> Result<()> {
+        block_on(async move { Ok(self.inner.set_sync_profile(profile.into()).await?) })
+    }
+
+    /// Fetch the policy history older than the light sync profile horizon
+    pub fn backfill_policy(&self, policy_id: Arc<EventId>) -> Result<u64> {
+        block_on(async move { Ok(self.inner.backfill_policy(**policy_id).await? as u64) })
+    }
+
     /// Get current block height
     pub fn block_height(&self) -> u32 {
         self.inner.block_height()
@@ -334,17 +368,18 @@ impl SmartVaults {
     pub fn delete_completed_proposal_by_id(
         &self,
         completed_proposal_id: Arc<EventId>,
+        delete_dependents: bool,
     ) -> Result<()> {
         block_on(async move {
-            Ok(self
-                .inner
-                .delete_completed_proposal_by_id(**completed_proposal_id)
-                .await?)
+            self.inner
+                .delete_completed_proposal_by_id(**completed_proposal_id, delete_dependents)
+                .await?;
+            Ok(())
         })
     }
 
-    pub fn delete_signer_by_id(&self, signer_id: Arc<EventId>) -> Result<()> {
-        block_on(async move { Ok(self.inner.delete_signer_by_id(**signer_id).await?) })
+    pub fn delete_signer_by_id(&self, signer_id: Arc<EventId>, force: bool) -> Result<()> {
+        block_on(async move { Ok(self.inner.delete_signer_by_id(**signer_id, force).await?) })
     }
 
     pub fn get_policies(&self) -> Result<Vec<Arc<GetPolicy>>> {
@@ -459,6 +494,7 @@ impl SmartVaults {
         utxos: Option<Vec<Arc<OutPoint>>>,
         policy_path: Option<HashMap<String, Vec<u64>>>,
         skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
     ) -> Result<Arc<GetProposal>> {
         block_on(async move {
             let to_address = Address::from_str(&to_address)?;
@@ -477,12 +513,58 @@ impl SmartVaults {
                             .collect()
                     }),
                     skip_frozen_utxos,
+                    coin_selection.map(|c| c.into()),
                 )
                 .await?;
             Ok(Arc::new(proposal.into()))
         })
     }
 
+    pub fn bump_fee(
+        &self,
+        policy_id: Arc<EventId>,
+        txid: String,
+        target_blocks: u8,
+        policy_path: Option<HashMap<String, Vec<u64>>>,
+    ) -> Result<Arc<GetProposal>> {
+        block_on(async move {
+            let txid = Txid::from_str(&txid)?;
+            let proposal = self
+                .inner
+                .bump_fee(
+                    **policy_id,
+                    txid,
+                    FeeRate::Priority(Priority::Custom(target_blocks)),
+                    policy_path.map(|pp| {
+                        pp.into_iter()
+                            .map(|(k, v)| (k, v.into_iter().map(|i| i as usize).collect()))
+                            .collect()
+                    }),
+                )
+                .await?;
+            Ok(Arc::new(proposal.into()))
+        })
+    }
+
+    /// Get the default coin selection strategy of a policy
+    pub fn default_coin_selection(&self, policy_id: Arc<EventId>) -> Result<CoinSelection> {
+        block_on(async move { Ok(self.inner.default_coin_selection(**policy_id).await?.into()) })
+    }
+
+    /// Set the default coin selection strategy of a policy
+    pub fn set_default_coin_selection(
+        &self,
+        policy_id: Arc<EventId>,
+        coin_selection: CoinSelection,
+    ) -> Result<()> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .set_default_coin_selection(**policy_id, coin_selection.into())
+                .await?)
+        })
+    }
+
     pub fn self_transfer(
         &self,
         from_policy_id: Arc<EventId>,
@@ -492,6 +574,7 @@ impl SmartVaults {
         utxos: Option<Vec<Arc<OutPoint>>>,
         policy_path: Option<HashMap<String, Vec<u64>>>,
         skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
     ) -> Result<Arc<GetProposal>> {
         block_on(async move {
             let proposal = self
@@ -508,15 +591,25 @@ impl SmartVaults {
                             .collect()
                     }),
                     skip_frozen_utxos,
+                    coin_selection.map(|c| c.into()),
                 )
                 .await?;
             Ok(Arc::new(proposal.into()))
         })
     }
 
-    pub fn approve(&self, password: String, proposal_id: Arc<EventId>) -> Result<Arc<EventId>> {
+    pub fn approve(
+        &self,
+        password: String,
+        proposal_id: Arc<EventId>,
+        acknowledge_fee: bool,
+        force: bool,
+    ) -> Result<Arc<EventId>> {
         block_on(async move {
-            let (approval_id, ..) = self.inner.approve(password, **proposal_id).await?;
+            let (approval_id, ..) = self
+                .inner
+                .approve(password, **proposal_id, acknowledge_fee, force)
+                .await?;
             Ok(Arc::new(approval_id.into()))
         })
     }
@@ -536,6 +629,30 @@ impl SmartVaults {
         })
     }
 
+    pub fn export_proposal_ur(
+        &self,
+        proposal_id: Arc<EventId>,
+        max_fragment_len: u64,
+    ) -> Result<Vec<String>> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .export_proposal_ur(**proposal_id, max_fragment_len as usize)
+                .await?)
+        })
+    }
+
+    pub fn approve_with_ur(
+        &self,
+        proposal_id: Arc<EventId>,
+        parts: Vec<String>,
+    ) -> Result<Arc<EventId>> {
+        block_on(async move {
+            let (approval_id, ..) = self.inner.approve_with_ur(**proposal_id, parts).await?;
+            Ok(Arc::new(approval_id.into()))
+        })
+    }
+
     pub fn revoke_approval(&self, approval_id: &EventId) -> Result<()> {
         block_on(async move { Ok(self.inner.revoke_approval(**approval_id).await?) })
     }
@@ -609,19 +726,27 @@ impl SmartVaults {
     ) -> Result<()> {
         block_on(async move {
             let public_keys: Vec<_> = public_keys.into_iter().map(|p| **p).collect();
-            Ok(self
-                .inner
+            self.inner
                 .share_signer_to_multiple_public_keys(**signer_id, public_keys)
-                .await?)
+                .await?;
+            Ok(())
         })
     }
 
     pub fn revoke_all_shared_signers(&self) -> Result<()> {
-        block_on(async move { Ok(self.inner.revoke_all_shared_signers().await?) })
+        block_on(async move {
+            self.inner.revoke_all_shared_signers().await?;
+            Ok(())
+        })
     }
 
-    pub fn revoke_shared_signer(&self, shared_signer_id: Arc<EventId>) -> Result<()> {
-        block_on(async move { Ok(self.inner.revoke_shared_signer(**shared_signer_id).await?) })
+    pub fn revoke_shared_signer(&self, shared_signer_id: Arc<EventId>, force: bool) -> Result<()> {
+        block_on(async move {
+            Ok(self
+                .inner
+                .revoke_shared_signer(**shared_signer_id, force)
+                .await?)
+        })
     }
 
     pub fn get_shared_signers(&self) -> Result<Vec<Arc<GetSharedSigner>>> {
@@ -699,6 +824,17 @@ impl SmartVaults {
         })
     }
 
+    /// Acknowledge an unexpected outgoing transaction
+    pub fn acknowledge_unexpected_tx(&self, policy_id: Arc<EventId>, txid: String) -> Result<()> {
+        block_on(async move {
+            let txid = Txid::from_str(&txid)?;
+            Ok(self
+                .inner
+                .acknowledge_unexpected_tx(**policy_id, txid)
+                .await?)
+        })
+    }
+
     pub fn get_utxos(&self, policy_id: Arc<EventId>) -> Result<Vec<Arc<Utxo>>> {
         block_on(async move {
             Ok(self
@@ -751,10 +887,10 @@ impl SmartVaults {
 
     pub fn republish_shared_key_for_policy(&self, policy_id: Arc<EventId>) -> Result<()> {
         block_on(async move {
-            Ok(self
-                .inner
+            self.inner
                 .republish_shared_key_for_policy(**policy_id)
-                .await?)
+                .await?;
+            Ok(())
         })
     }
 
@@ -884,6 +1020,7 @@ impl SmartVaults {
         utxos: Option<Vec<Arc<OutPoint>>>,
         policy_path: Option<HashMap<String, Vec<u64>>>,
         skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
     ) -> Result<Arc<GetProposal>> {
         block_on(async move {
             let to_address = Address::from_str(&to_address)?;
@@ -904,21 +1041,37 @@ impl SmartVaults {
                             .collect()
                     }),
                     skip_frozen_utxos,
+                    coin_selection.map(|c| c.into()),
                 )
                 .await?;
             Ok(Arc::new(proposal.into()))
         })
     }
 
-    pub fn handle_sync(self: Arc<Self>, handler: Box<dyn SyncHandler>) -> Result<Arc<AbortHandle>> {
+    /// Sequence number of the last sync notification
+    pub fn last_sync_notification_seq(&self) -> u64 {
+        self.inner.last_sync_notification_seq()
+    }
+
+    /// Handle sync notifications
+    ///
+    /// If `since` is set, the buffered notifications with sequence number `>= since` are replayed first.
+    pub fn handle_sync(
+        self: Arc<Self>,
+        handler: Box<dyn SyncHandler>,
+        since: Option<u64>,
+    ) -> Result<Arc<AbortHandle>> {
         tracing::info!("Spawning new `handle_sync` thread");
         let handle = async_utility::thread::abortable(async move {
-            let mut receiver = self.inner.sync_notifications();
+            let mut receiver = match since {
+                Some(since) => self.inner.sync_notifications_since(since),
+                None => self.inner.sync_notifications(),
+            };
             let handler = Arc::new(handler);
-            while let Ok(message) = receiver.recv().await {
+            while let Ok(notification) = receiver.recv().await {
                 let h = handler.clone();
                 let _ = tokio::task::spawn_blocking(move || {
-                    h.handle(message.into());
+                    h.handle(notification.seq, notification.message.into());
                 })
                 .await;
             }
@@ -930,5 +1083,5 @@ impl SmartVaults {
 
 #[uniffi::export(callback_interface)]
 pub trait SyncHandler: Send + Sync + Debug {
-    fn handle(&self, msg: Message);
+    fn handle(&self, seq: u64, msg: Message);
 }
@@ -0,0 +1,35 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use smartvaults_sdk::core::CoinSelection as CoinSelectionSdk;
+use uniffi::Enum;
+
+#[derive(Enum)]
+pub enum CoinSelection {
+    Default,
+    Economical,
+    Consolidate,
+    PrivacyFirst,
+}
+
+impl From<CoinSelectionSdk> for CoinSelection {
+    fn from(value: CoinSelectionSdk) -> Self {
+        match value {
+            CoinSelectionSdk::Default => Self::Default,
+            CoinSelectionSdk::Economical => Self::Economical,
+            CoinSelectionSdk::Consolidate => Self::Consolidate,
+            CoinSelectionSdk::PrivacyFirst => Self::PrivacyFirst,
+        }
+    }
+}
+
+impl From<CoinSelection> for CoinSelectionSdk {
+    fn from(value: CoinSelection) -> Self {
+        match value {
+            CoinSelection::Default => Self::Default,
+            CoinSelection::Economical => Self::Economical,
+            CoinSelection::Consolidate => Self::Consolidate,
+            CoinSelection::PrivacyFirst => Self::PrivacyFirst,
+        }
+    }
+}
@@ -3,10 +3,34 @@
 
 use smartvaults_sdk::config;
 use smartvaults_sdk::nostr::{block_on, Url};
-use uniffi::Object;
+use uniffi::{Enum, Object};
 
 use crate::error::Result;
 
+#[derive(Enum)]
+pub enum SyncProfile {
+    Full,
+    Light,
+}
+
+impl From<config::SyncProfile> for SyncProfile {
+    fn from(value: config::SyncProfile) -> Self {
+        match value {
+            config::SyncProfile::Full => Self::Full,
+            config::SyncProfile::Light => Self::Light,
+        }
+    }
+}
+
+impl From<SyncProfile> for config::SyncProfile {
+    fn from(value: SyncProfile) -> Self {
+        match value {
+            SyncProfile::Full => Self::Full,
+            SyncProfile::Light => Self::Light,
+        }
+    }
+}
+
 #[derive(Object)]
 pub struct Config {
     inner: config::Config,
@@ -32,6 +56,27 @@ impl Config {
         block_on(async move { Ok(self.inner.electrum_endpoint().await?.to_string()) })
     }
 
+    /// Set the electrum endpoints, in failover order
+    pub fn set_electrum_endpoints(&self, endpoints: Vec<String>) -> Result<()> {
+        block_on(async move { Ok(self.inner.set_electrum_endpoints(endpoints).await?) })
+    }
+
+    pub fn electrum_endpoints(&self) -> Vec<String> {
+        block_on(async move {
+            self.inner
+                .electrum_endpoints()
+                .await
+                .into_iter()
+                .map(|e| e.to_string())
+                .collect()
+        })
+    }
+
+    /// Get the electrum endpoint currently in use
+    pub fn active_electrum_endpoint(&self) -> Result<String> {
+        block_on(async move { Ok(self.inner.active_electrum_endpoint().await?.to_string()) })
+    }
+
     pub fn set_block_explorer(&self, url: String) -> Result<()> {
         block_on(async move {
             let url = Url::parse(&url)?;
@@ -43,4 +88,17 @@ impl Config {
     pub fn block_explorer(&self) -> Result<String> {
         block_on(async move { Ok(self.inner.block_explorer().await?.to_string()) })
     }
+
+    pub fn sync_profile(&self) -> SyncProfile {
+        block_on(async move { self.inner.sync_profile().await.into() })
+    }
+
+    /// History window (days) synced by the light profile (`None` to use the default one)
+    pub fn set_light_sync_horizon(&self, days: Option<u64>) {
+        block_on(async move { self.inner.set_light_sync_horizon(days).await })
+    }
+
+    pub fn light_sync_horizon(&self) -> u64 {
+        block_on(async move { self.inner.light_sync_horizon().await.as_secs() / (60 * 60 * 24) })
+    }
 }
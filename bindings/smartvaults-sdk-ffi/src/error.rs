@@ -108,6 +108,12 @@ impl From<smartvaults_sdk::core::bips::bip39::Error> for SmartVaultsError {
     }
 }
 
+impl From<smartvaults_sdk::core::mnemonic::Error> for SmartVaultsError {
+    fn from(e: smartvaults_sdk::core::mnemonic::Error) -> Self {
+        Self::Generic(e.to_string())
+    }
+}
+
 impl From<smartvaults_sdk::core::bitcoin::address::Error> for SmartVaultsError {
     fn from(e: smartvaults_sdk::core::bitcoin::address::Error) -> Self {
         Self::Generic(e.to_string())
@@ -13,6 +13,7 @@ mod address;
 mod amount;
 mod balance;
 mod client;
+mod coin_selection;
 mod config;
 mod descriptor;
 mod error;
@@ -31,7 +32,8 @@ pub use self::address::{AddressIndex, GetAddress};
 pub use self::amount::Amount;
 pub use self::balance::Balance;
 pub use self::client::{SmartVaults, SyncHandler};
-pub use self::config::Config;
+pub use self::coin_selection::CoinSelection;
+pub use self::config::{Config, SyncProfile};
 pub use self::descriptor::Descriptor;
 use self::error::Result;
 pub use self::error::SmartVaultsError;
@@ -47,7 +49,7 @@ pub use self::proposal::{
     ApprovedProposal, CompletedProposal, GetApproval, GetCompletedProposal, GetProposal, Period,
     Proposal,
 };
-pub use self::seed::{Seed, WordCount};
+pub use self::seed::{Language, Seed, WordCount};
 pub use self::signer::{GetSharedSigner, GetSigner, SharedSigner, Signer, SignerType};
 pub use self::transaction::{
     BlockTime, GetTransaction, OutPoint, Transaction, TransactionDetails, TxIn, TxOut, Utxo,
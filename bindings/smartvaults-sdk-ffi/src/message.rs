@@ -7,24 +7,58 @@ use nostr_ffi::{EventId, PublicKey};
 use smartvaults_sdk::{EventHandled as EventHandledSdk, Message as MessageSdk};
 use uniffi::Enum;
 
+/// What changed after handling an event (see the SDK `EventHandled` for details)
 #[derive(Enum)]
 pub enum EventHandled {
-    SharedKey { event_id: Arc<EventId> },
-    Policy { policy_id: Arc<EventId> },
-    Proposal { proposal_id: Arc<EventId> },
-    Approval { proposal_id: Arc<EventId> },
-    CompletedProposal { completed_proposal_id: Arc<EventId> },
-    Signer { signer_id: Arc<EventId> },
-    MySharedSigner { my_shared_signer_id: Arc<EventId> },
-    SharedSigner { shared_signer_id: Arc<EventId> },
+    SharedKey {
+        event_id: Arc<EventId>,
+    },
+    Policy {
+        policy_id: Arc<EventId>,
+    },
+    Proposal {
+        proposal_id: Arc<EventId>,
+    },
+    Approval {
+        proposal_id: Arc<EventId>,
+        approval_id: Arc<EventId>,
+    },
+    CompletedProposal {
+        completed_proposal_id: Arc<EventId>,
+    },
+    Signer {
+        signer_id: Arc<EventId>,
+    },
+    MySharedSigner {
+        my_shared_signer_id: Arc<EventId>,
+    },
+    SharedSigner {
+        shared_signer_id: Arc<EventId>,
+    },
     Contacts,
-    Metadata { public_key: Arc<PublicKey> },
-    NostrConnectRequest { request_id: Arc<EventId> },
-    Label,
-    EventDeletion,
+    Metadata {
+        public_key: Arc<PublicKey>,
+    },
+    NostrConnectRequest {
+        request_id: Arc<EventId>,
+    },
+    Label {
+        policy_id: Arc<EventId>,
+    },
+    EventDeletion {
+        event_ids: Vec<Arc<EventId>>,
+    },
     RelayList,
-    KeyAgentSignerOffering,
+    KeyAgentSignaling {
+        public_key: Arc<PublicKey>,
+    },
+    KeyAgentSignerOffering {
+        event_id: Arc<EventId>,
+    },
     VerifiedKeyAgents,
+    PeerCapabilities {
+        public_key: Arc<PublicKey>,
+    },
 }
 
 impl From<EventHandledSdk> for EventHandled {
@@ -39,8 +73,12 @@ impl From<EventHandledSdk> for EventHandled {
             EventHandledSdk::Proposal(id) => Self::Proposal {
                 proposal_id: Arc::new(id.into()),
             },
-            EventHandledSdk::Approval { proposal_id } => Self::Approval {
+            EventHandledSdk::Approval {
+                proposal_id,
+                approval_id,
+            } => Self::Approval {
                 proposal_id: Arc::new(proposal_id.into()),
+                approval_id: Arc::new(approval_id.into()),
             },
             EventHandledSdk::CompletedProposal(id) => Self::CompletedProposal {
                 completed_proposal_id: Arc::new(id.into()),
@@ -61,21 +99,102 @@ impl From<EventHandledSdk> for EventHandled {
             EventHandledSdk::NostrConnectRequest(id) => Self::NostrConnectRequest {
                 request_id: Arc::new(id.into()),
             },
-            EventHandledSdk::Label => Self::Label,
-            EventHandledSdk::EventDeletion => Self::EventDeletion,
+            EventHandledSdk::Label { policy_id } => Self::Label {
+                policy_id: Arc::new(policy_id.into()),
+            },
+            EventHandledSdk::EventDeletion(ids) => Self::EventDeletion {
+                event_ids: ids.into_iter().map(|id| Arc::new(id.into())).collect(),
+            },
             EventHandledSdk::RelayList => Self::RelayList,
-            EventHandledSdk::KeyAgentSignerOffering => Self::KeyAgentSignerOffering,
+            EventHandledSdk::KeyAgentSignaling(pk) => Self::KeyAgentSignaling {
+                public_key: Arc::new(pk.into()),
+            },
+            EventHandledSdk::KeyAgentSignerOffering(id) => Self::KeyAgentSignerOffering {
+                event_id: Arc::new(id.into()),
+            },
             EventHandledSdk::VerifiedKeyAgents => Self::VerifiedKeyAgents,
+            EventHandledSdk::PeerCapabilities(pk) => Self::PeerCapabilities {
+                public_key: Arc::new(pk.into()),
+            },
         }
     }
 }
 
 #[derive(Enum)]
 pub enum Message {
-    EventHandledMsg { event: EventHandled },
-    WalletSyncCompleted { policy_id: Arc<EventId> },
+    EventHandledMsg {
+        event: EventHandled,
+    },
+    WalletSyncCompleted {
+        policy_id: Arc<EventId>,
+    },
     BlockHeightUpdated,
     MempoolFeesUpdated,
+    PoisonEventQuarantined {
+        event_id: Arc<EventId>,
+    },
+    ProposalReady {
+        proposal_id: Arc<EventId>,
+    },
+    InsufficientRelayAcks {
+        event_id: Arc<EventId>,
+        got: u64,
+        want: u64,
+    },
+    OutboxEventDropped {
+        event_id: Arc<EventId>,
+        relay_url: String,
+    },
+    UnexpectedOutgoingTx {
+        policy_id: Arc<EventId>,
+        txid: String,
+        amount: u64,
+    },
+    ProofVerified {
+        completed_proposal_id: Arc<EventId>,
+        spendable: u64,
+    },
+    ProofInvalid {
+        completed_proposal_id: Arc<EventId>,
+    },
+    ExternalPsbtImported {
+        proposal_id: Arc<EventId>,
+    },
+    ExternalPsbtRejected {
+        proposal_id: Arc<EventId>,
+        reason: String,
+    },
+    MemberCannotReadProposal {
+        member: Arc<PublicKey>,
+        proposal_id: Arc<EventId>,
+        reason_code: String,
+    },
+    ApprovalReminder {
+        proposal_id: Arc<EventId>,
+        count: u16,
+    },
+    PolicyWalletMismatch {
+        policy_id: Arc<EventId>,
+    },
+    NewNostrConnectRequest {
+        app_public_key: Arc<PublicKey>,
+        request_id: Arc<EventId>,
+        method: String,
+    },
+    NostrConnectRequestAutoApproved {
+        app_public_key: Arc<PublicKey>,
+        request_id: Arc<EventId>,
+        method: String,
+    },
+    SyncProgress {
+        relay_url: String,
+        events_received: u64,
+        eose: bool,
+    },
+    InitialSyncComplete {
+        events: u64,
+        relays: u64,
+    },
 }
 
 impl From<MessageSdk> for Message {
@@ -89,6 +208,104 @@ impl From<MessageSdk> for Message {
             },
             MessageSdk::BlockHeightUpdated => Self::BlockHeightUpdated,
             MessageSdk::MempoolFeesUpdated(..) => Self::MempoolFeesUpdated,
+            MessageSdk::PoisonEventQuarantined(event_id) => Self::PoisonEventQuarantined {
+                event_id: Arc::new(event_id.into()),
+            },
+            MessageSdk::ProposalReady(proposal_id) => Self::ProposalReady {
+                proposal_id: Arc::new(proposal_id.into()),
+            },
+            MessageSdk::InsufficientRelayAcks {
+                event_id,
+                got,
+                want,
+            } => Self::InsufficientRelayAcks {
+                event_id: Arc::new(event_id.into()),
+                got: got as u64,
+                want: want as u64,
+            },
+            MessageSdk::OutboxEventDropped {
+                event_id,
+                relay_url,
+            } => Self::OutboxEventDropped {
+                event_id: Arc::new(event_id.into()),
+                relay_url: relay_url.to_string(),
+            },
+            MessageSdk::UnexpectedOutgoingTx {
+                policy_id,
+                txid,
+                amount,
+            } => Self::UnexpectedOutgoingTx {
+                policy_id: Arc::new(policy_id.into()),
+                txid: txid.to_string(),
+                amount,
+            },
+            MessageSdk::ProofVerified {
+                completed_proposal_id,
+                spendable,
+            } => Self::ProofVerified {
+                completed_proposal_id: Arc::new(completed_proposal_id.into()),
+                spendable,
+            },
+            MessageSdk::ProofInvalid(completed_proposal_id) => Self::ProofInvalid {
+                completed_proposal_id: Arc::new(completed_proposal_id.into()),
+            },
+            MessageSdk::ExternalPsbtImported(proposal_id) => Self::ExternalPsbtImported {
+                proposal_id: Arc::new(proposal_id.into()),
+            },
+            MessageSdk::ExternalPsbtRejected {
+                proposal_id,
+                reason,
+            } => Self::ExternalPsbtRejected {
+                proposal_id: Arc::new(proposal_id.into()),
+                reason,
+            },
+            MessageSdk::MemberCannotReadProposal {
+                member,
+                proposal_id,
+                reason_code,
+            } => Self::MemberCannotReadProposal {
+                member: Arc::new(member.into()),
+                proposal_id: Arc::new(proposal_id.into()),
+                reason_code: reason_code.to_string(),
+            },
+            MessageSdk::ApprovalReminder { proposal_id, count } => Self::ApprovalReminder {
+                proposal_id: Arc::new(proposal_id.into()),
+                count,
+            },
+            MessageSdk::PolicyWalletMismatch(policy_id) => Self::PolicyWalletMismatch {
+                policy_id: Arc::new(policy_id.into()),
+            },
+            MessageSdk::NewNostrConnectRequest {
+                app_public_key,
+                request_id,
+                method,
+            } => Self::NewNostrConnectRequest {
+                app_public_key: Arc::new(app_public_key.into()),
+                request_id: Arc::new(request_id.into()),
+                method,
+            },
+            MessageSdk::NostrConnectRequestAutoApproved {
+                app_public_key,
+                request_id,
+                method,
+            } => Self::NostrConnectRequestAutoApproved {
+                app_public_key: Arc::new(app_public_key.into()),
+                request_id: Arc::new(request_id.into()),
+                method,
+            },
+            MessageSdk::SyncProgress {
+                relay_url,
+                events_received,
+                eose,
+            } => Self::SyncProgress {
+                relay_url: relay_url.to_string(),
+                events_received: events_received as u64,
+                eose,
+            },
+            MessageSdk::InitialSyncComplete { events, relays } => Self::InitialSyncComplete {
+                events: events as u64,
+                relays: relays as u64,
+            },
         }
     }
 }
@@ -53,6 +53,10 @@ impl Policy {
         self.inner.as_descriptor().to_string()
     }
 
+    pub fn change_descriptor(&self) -> Option<String> {
+        self.inner.as_change_descriptor().map(|d| d.to_string())
+    }
+
     pub fn satisfiable_item(&self) -> Result<String> {
         Ok(self.inner.satisfiable_item()?.as_json())
     }
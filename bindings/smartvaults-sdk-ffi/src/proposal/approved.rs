@@ -60,4 +60,13 @@ impl GetApproval {
     pub fn timestamp(&self) -> Arc<Timestamp> {
         Arc::new(self.inner.timestamp.into())
     }
+
+    /// Timestamp clamped to the current time
+    pub fn display_timestamp(&self) -> Arc<Timestamp> {
+        Arc::new(self.inner.display_timestamp.into())
+    }
+
+    pub fn is_clock_skewed(&self) -> bool {
+        self.inner.is_clock_skewed()
+    }
 }
@@ -143,4 +143,9 @@ impl GetProposal {
     pub fn is_signed(&self) -> bool {
         self.inner.signed
     }
+
+    /// Suspicious fee description, if flagged
+    pub fn fee_warning(&self) -> Option<String> {
+        self.inner.fee_warning.map(|w| w.to_string())
+    }
 }
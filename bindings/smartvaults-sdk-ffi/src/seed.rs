@@ -1,6 +1,7 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use smartvaults_sdk::core::bips::bip39;
 use smartvaults_sdk::core::types::{self, seed};
 use uniffi::{Enum, Object};
 
@@ -21,6 +22,35 @@ impl From<WordCount> for types::WordCount {
     }
 }
 
+#[derive(Enum)]
+pub enum Language {
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Spanish,
+}
+
+impl From<Language> for bip39::Language {
+    fn from(value: Language) -> Self {
+        match value {
+            Language::English => Self::English,
+            Language::SimplifiedChinese => Self::SimplifiedChinese,
+            Language::TraditionalChinese => Self::TraditionalChinese,
+            Language::Czech => Self::Czech,
+            Language::French => Self::French,
+            Language::Italian => Self::Italian,
+            Language::Japanese => Self::Japanese,
+            Language::Korean => Self::Korean,
+            Language::Spanish => Self::Spanish,
+        }
+    }
+}
+
 #[derive(Object)]
 pub struct Seed {
     inner: seed::Seed,
@@ -286,4 +286,18 @@ impl GetTransaction {
     pub fn block_explorer(&self) -> Option<String> {
         self.inner.block_explorer.clone()
     }
+
+    /// Screening flag (`None` if clear)
+    pub fn screening_flag(&self) -> Option<String> {
+        if self.inner.screening.is_flagged() {
+            Some(self.inner.screening.to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Outgoing transaction without a matching completed proposal
+    pub fn unexpected(&self) -> bool {
+        self.inner.unexpected
+    }
 }
@@ -3,7 +3,7 @@
 
 use clap::Parser;
 
-use super::{AddCommand, Command, SetCommand};
+use super::{AddCommand, Command, SetCommand, SpendArgs};
 
 #[derive(Debug, Parser)]
 #[command(name = "")]
@@ -20,6 +20,12 @@ pub enum BatchCommand {
         #[command(subcommand)]
         command: SetCommand,
     },
+    /// Create a spending proposal
+    #[command(arg_required_else_help = true)]
+    Spend {
+        #[command(flatten)]
+        args: SpendArgs,
+    },
 }
 
 impl From<BatchCommand> for Command {
@@ -27,6 +33,7 @@ impl From<BatchCommand> for Command {
         match cmd {
             BatchCommand::Add { command } => Self::Add { command },
             BatchCommand::Set { command } => Self::Set { command },
+            BatchCommand::Spend { args } => Self::Spend { args },
         }
     }
 }
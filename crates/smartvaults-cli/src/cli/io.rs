@@ -4,8 +4,12 @@
 use std::env;
 
 use dialoguer::{Confirm, Input, Password};
+use owo_colors::OwoColorize;
 use smartvaults_sdk::core::Result;
 
+/// Text to type to confirm a mainnet action
+const MAINNET_CONFIRMATION: &str = "MAINNET";
+
 pub fn get_input<S>(prompt: S) -> Result<String>
 where
     S: Into<String>,
@@ -40,6 +44,20 @@ where
     }
 }
 
+/// Ask to type [`MAINNET_CONFIRMATION`] to confirm a value-moving or destructive action
+pub fn confirm_mainnet(action: &str) -> Result<bool> {
+    println!(
+        "{} You are about to {action} on {}",
+        "MAINNET".red().bold(),
+        "bitcoin mainnet".red()
+    );
+    let input: String = Input::new()
+        .with_prompt(format!("Type {MAINNET_CONFIRMATION} to confirm"))
+        .allow_empty(true)
+        .interact_text()?;
+    Ok(input.trim() == MAINNET_CONFIRMATION)
+}
+
 pub fn get_password_from_env() -> Option<String> {
     env::var("SMARTVAULTS_CLI_PASSWORD").ok()
 }
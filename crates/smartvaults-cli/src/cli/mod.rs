@@ -6,21 +6,32 @@
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use smartvaults_sdk::config::FeeTargets;
 use smartvaults_sdk::core::bips::bip32::Fingerprint;
 use smartvaults_sdk::core::bitcoin::address::NetworkUnchecked;
-use smartvaults_sdk::core::bitcoin::Address;
+use smartvaults_sdk::core::bitcoin::{Address, OutPoint, Txid};
 use smartvaults_sdk::core::miniscript::{Descriptor, DescriptorPublicKey};
+use smartvaults_sdk::core::signer::SharedSignerScope;
+use smartvaults_sdk::core::{
+    AbsoluteLockTime, Amount, CoinSelection, DecayingTime, FeeRate, Locktime, PolicyTemplate,
+    Priority, RecoveryTemplate, Sequence,
+};
 use smartvaults_sdk::nostr::prelude::NostrConnectURI;
-use smartvaults_sdk::nostr::{EventId, PublicKey, Url};
+use smartvaults_sdk::nostr::{EventId, PublicKey, Timestamp, Url};
 use smartvaults_sdk::protocol::v1::{BasisPoints, DeviceType, LabelData, Price, Temperature};
+use smartvaults_sdk::util::parse_public_key;
 
 pub mod batch;
 pub mod io;
 pub mod parser;
 mod types;
 
-use self::types::{CliNetwork, CliWordCount};
+pub use self::types::{parse_indexes, CliPolicyPath, CliPolicyTemplateType};
+use self::types::{
+    CliFiatCurrency, CliLanguage, CliNetwork, CliPriority, CliProxy, CliPublicKeyStyle,
+    CliScreeningAction, CliSyncProfile, CliTimestampStyle, CliUserRole, CliWordCount,
+};
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about, long_about)]
@@ -28,6 +39,12 @@ pub struct Cli {
     /// Network
     #[clap(short, long, value_enum, default_value_t = CliNetwork::Bitcoin)]
     pub network: CliNetwork,
+    /// Public keys display format
+    #[clap(long, value_enum, default_value_t = CliPublicKeyStyle::Hex)]
+    pub pubkey_style: CliPublicKeyStyle,
+    /// Timestamps time zone
+    #[clap(long, value_enum, default_value_t = CliTimestampStyle::Local)]
+    pub timestamp_style: CliTimestampStyle,
     #[command(subcommand)]
     pub command: CliCommand,
 }
@@ -43,6 +60,9 @@ pub enum CliCommand {
         /// Word count
         #[arg(value_enum, default_value_t = CliWordCount::W12)]
         word_count: CliWordCount,
+        /// Mnemonic language
+        #[arg(long, value_enum, default_value_t = CliLanguage::English)]
+        language: CliLanguage,
         /// Passphrase
         #[arg(long)]
         passphrase: Option<String>,
@@ -53,6 +73,12 @@ pub enum CliCommand {
         /// Keychain name
         #[arg(required = true)]
         name: String,
+        /// Settings payload exported from another device
+        #[arg(long)]
+        settings: Option<String>,
+        /// Mnemonic language (detected if not set)
+        #[arg(long, value_enum)]
+        language: Option<CliLanguage>,
     },
     /// Open keychain
     #[command(arg_required_else_help = true)]
@@ -60,6 +86,9 @@ pub enum CliCommand {
         /// Keychain name
         #[arg(required = true)]
         name: String,
+        /// Remove the lock left by a crashed process
+        #[arg(long)]
+        steal_lock: bool,
     },
     /// Batch
     #[command(arg_required_else_help = true)]
@@ -70,9 +99,39 @@ pub enum CliCommand {
         /// Batch file
         #[arg(required = true)]
         path: PathBuf,
+        /// Remove the lock left by a crashed process
+        #[arg(long)]
+        steal_lock: bool,
+    },
+    /// Export an encrypted backup of the keychain, policies, labels and contacts
+    #[command(arg_required_else_help = true)]
+    Backup {
+        /// Keychain name
+        #[arg(required = true)]
+        name: String,
+        /// Backup file
+        #[arg(required = true)]
+        path: PathBuf,
+    },
+    /// Restore keychain from a backup (see `backup`)
+    #[command(arg_required_else_help = true)]
+    RestoreBackup {
+        /// Backup file
+        #[arg(required = true)]
+        path: PathBuf,
+        /// Replace the keychain with the same name
+        #[arg(long)]
+        force: bool,
     },
     /// List keychains
     List,
+    /// Analyze a descriptor or a miniscript policy (nothing is saved)
+    #[command(arg_required_else_help = true)]
+    InspectDescriptor {
+        /// Descriptor or miniscript policy
+        #[arg(required = true)]
+        desc_or_policy: String,
+    },
     /// Config
     Config {
         #[command(subcommand)]
@@ -82,20 +141,75 @@ pub enum CliCommand {
 
 #[derive(Debug, Subcommand)]
 pub enum ConfigCommand {
-    /// View config
+    /// View config (active network profile and overridden fields)
     View,
     /// Set config
     #[command(arg_required_else_help = true)]
+    #[command(group(ArgGroup::new("bitcoind_auth").args(["bitcoind_cookie", "bitcoind_user"])))]
     Set {
-        /// Electrum server
+        /// Electrum server (repeat to set fallback servers, in failover order)
         #[clap(long)]
-        electrum_server: Option<String>,
+        electrum_server: Vec<String>,
+        /// Bitcoin Core RPC URL (used instead of electrum to sync the timechain)
+        #[clap(long, requires = "bitcoind_auth")]
+        bitcoind_url: Option<Url>,
+        /// Bitcoin Core RPC cookie file
+        #[clap(long, requires = "bitcoind_url")]
+        bitcoind_cookie: Option<PathBuf>,
+        /// Bitcoin Core RPC user
+        #[clap(long, requires_all = ["bitcoind_url", "bitcoind_pass"])]
+        bitcoind_user: Option<String>,
+        /// Bitcoin Core RPC password
+        #[clap(long, requires = "bitcoind_user")]
+        bitcoind_pass: Option<String>,
         /// Proxy
         #[clap(long)]
         proxy: Option<SocketAddr>,
         /// Block explorer
         #[clap(long)]
         block_explorer: Option<Url>,
+        /// Fee target blocks for high, medium and low priority (format: `<high>,<medium>,<low>`)
+        #[clap(long)]
+        fee_targets: Option<FeeTargets>,
+        /// Proposal fee cap: max fee as percentage of the spent amount
+        #[clap(long)]
+        fee_cap_percentage: Option<f64>,
+        /// Proposal fee cap: max absolute fee (sat)
+        #[clap(long)]
+        fee_cap_sats: Option<u64>,
+        /// Consecutive unused addresses after which the timechain scan stops (electrum)
+        #[clap(long)]
+        gap_limit: Option<usize>,
+        /// Screening list path (one address, descriptor or script per line)
+        #[clap(long)]
+        screening_list: Option<PathBuf>,
+        /// Action when approving a proposal that pays a flagged address
+        #[clap(long)]
+        screening_on_approve: Option<CliScreeningAction>,
+        /// Min number of relays that must acknowledge policies, proposals and approvals
+        #[clap(long)]
+        min_relay_acks: Option<usize>,
+        /// Max number of recipient relays used for invites and shared signers, in addition to ours
+        #[clap(long)]
+        max_recipient_relays: Option<usize>,
+        /// Send a DM to the co-signers when a spending proposal is created
+        #[clap(long)]
+        notify_cosigners: Option<bool>,
+        /// Sync profile (`light` for low-bandwidth devices)
+        #[clap(long)]
+        sync_profile: Option<CliSyncProfile>,
+        /// History window (days) synced by the light profile
+        #[clap(long)]
+        light_horizon_days: Option<u64>,
+        /// Directory watched by external signers (pending PSBTs exported, signed ones imported)
+        #[clap(long)]
+        psbt_dir: Option<PathBuf>,
+        /// Show the balances also in this currency
+        #[clap(long)]
+        fiat_currency: Option<CliFiatCurrency>,
+        /// Price endpoint (mempool.space or coingecko API)
+        #[clap(long)]
+        price_endpoint: Option<Url>,
     },
 
     /// Unset
@@ -104,12 +218,48 @@ pub enum ConfigCommand {
         /// Electrum server
         #[clap(long)]
         electrum_server: bool,
+        /// Bitcoin Core RPC (use electrum)
+        #[clap(long)]
+        bitcoind: bool,
         /// Proxy
         #[clap(long)]
         proxy: bool,
         /// Block explorer
         #[clap(long)]
         block_explorer: bool,
+        /// Fee targets (use the profile ones)
+        #[clap(long)]
+        fee_targets: bool,
+        /// Proposal fee cap (use the default one)
+        #[clap(long)]
+        fee_cap: bool,
+        /// Gap limit (use the default one)
+        #[clap(long)]
+        gap_limit: bool,
+        /// Screening list (disable screening)
+        #[clap(long)]
+        screening_list: bool,
+        /// Min relay acknowledgments (use the default one)
+        #[clap(long)]
+        min_relay_acks: bool,
+        /// Max recipient relays (use the default one)
+        #[clap(long)]
+        max_recipient_relays: bool,
+        /// Co-signers notification (disable it)
+        #[clap(long)]
+        notify_cosigners: bool,
+        /// Light profile history window (use the default one)
+        #[clap(long)]
+        light_horizon_days: bool,
+        /// External signers directory (disable the watcher)
+        #[clap(long)]
+        psbt_dir: bool,
+        /// Fiat currency (show only sats)
+        #[clap(long)]
+        fiat_currency: bool,
+        /// Price endpoint (use the default one)
+        #[clap(long)]
+        price_endpoint: bool,
     },
 }
 
@@ -124,6 +274,29 @@ pub enum SettingCommand {
     },
     /// Change keychain password
     ChangePassword,
+    /// Verify the mnemonic written down (asks some random words)
+    VerifySeed,
+    /// Switch identity by applying another BIP39 passphrase
+    Passphrase,
+    /// Export relays and settings (encrypted) to bootstrap another device
+    Export,
+    /// Import relays and settings exported from another device
+    Import {
+        /// Settings payload
+        #[arg(required = true)]
+        payload: String,
+    },
+    /// Set sync profile (switching to `full` backfills the missing history)
+    SyncProfile {
+        #[arg(required = true)]
+        profile: CliSyncProfile,
+    },
+    /// Enable the extra confirmation of the mainnet spends and deletions
+    MainnetGuard {
+        /// Disable the guard
+        #[arg(long)]
+        disable: bool,
+    },
 }
 
 #[derive(Debug, Parser)]
@@ -133,42 +306,78 @@ pub enum Command {
     Inspect,
     /// Create a spending proposal
     Spend {
+        #[command(flatten)]
+        args: SpendArgs,
+    },
+    /// Create a spending proposal (send all funds)
+    SpendAll {
         /// Policy id
         #[arg(required = true)]
         policy_id: EventId,
         /// To address
         #[arg(required = true)]
         to_address: Address<NetworkUnchecked>,
-        /// Amount in sat
-        #[arg(required = true)]
-        amount: u64,
         /// Description
         #[arg(required = true)]
         description: String,
-        /// Taget blocks
-        #[clap(short, long, default_value_t = 6)]
-        target_blocks: u8,
+        #[command(flatten)]
+        fee: FeeArgs,
+        /// Coin selection strategy (default, economical, consolidate or privacy-first)
+        ///
+        /// If not set, the default one of the policy is used
+        #[arg(long)]
+        coin_selection: Option<CoinSelection>,
+        /// UTXO to spend (txid:vout, repeatable)
+        ///
+        /// If set, only the selected UTXOs are used (see `get utxos`)
+        #[arg(long = "utxo")]
+        utxos: Vec<OutPoint>,
+        /// Policy path (<path_id>:<index,...>, repeatable)
+        ///
+        /// If not set and can't be selected automatically, it's asked interactively
+        #[arg(long = "path")]
+        paths: Vec<CliPolicyPath>,
     },
-    /// Create a spending proposal (send all funds)
-    SpendAll {
+    /// Bump the fee (RBF) of a pending spending proposal or of an unconfirmed transaction
+    SpendBump {
         /// Policy id
         #[arg(required = true)]
         policy_id: EventId,
-        /// To address
-        #[arg(required = true)]
-        to_address: Address<NetworkUnchecked>,
-        /// Description
+        /// TXID of the pending proposal or of the unconfirmed transaction
         #[arg(required = true)]
-        description: String,
+        txid: Txid,
         /// Taget blocks
-        #[clap(short, long, default_value_t = 6)]
+        #[arg(required = true)]
         target_blocks: u8,
+        /// Policy path used by the original transaction (<path_id>:<index,...>, repeatable)
+        ///
+        /// If not set and can't be selected automatically, it's asked interactively
+        #[arg(long = "path")]
+        paths: Vec<CliPolicyPath>,
     },
     /// Approve a spending proposal
     Approve {
         /// Proposal id
         #[arg(required = true)]
         proposal_id: EventId,
+        /// Approve even if the proposal fee is flagged as suspicious
+        #[arg(long)]
+        acknowledge_fee: bool,
+        /// Approve even if some output not match the vault descriptor
+        #[arg(long)]
+        force: bool,
+        /// Approve with a PSBT signed externally (base64 or binary file)
+        #[arg(long, conflicts_with = "hw")]
+        psbt: Option<PathBuf>,
+        /// Sign with a hardware device connected via HWI
+        #[arg(long, conflicts_with = "signer")]
+        hw: bool,
+        /// Sign only with the keys of this local signer (default: all the seed keys)
+        #[arg(long, conflicts_with = "psbt")]
+        signer: Option<EventId>,
+        /// Finalize and broadcast if the approval completes the proposal
+        #[arg(long, conflicts_with_all = ["psbt", "hw", "signer"])]
+        finalize: bool,
     },
     /// Finalize proposal
     Finalize {
@@ -176,6 +385,12 @@ pub enum Command {
         #[arg(required = true)]
         proposal_id: EventId,
     },
+    /// Send a DM with the proposal summary to the co-signers
+    NotifyCosigners {
+        /// Proposal id
+        #[arg(required = true)]
+        proposal_id: EventId,
+    },
     /// Proof of Reserve commands
     #[command(arg_required_else_help = true)]
     Proof {
@@ -230,12 +445,101 @@ pub enum Command {
         #[command(subcommand)]
         command: SettingCommand,
     },
+    /// Acknowledge an unexpected outgoing transaction
+    AckTx {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Txid
+        #[arg(required = true)]
+        txid: Txid,
+    },
+    /// Fetch the policy history older than the light sync profile horizon
+    Backfill {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+    },
+    /// Rotate the shared key of a policy (i.e. the nostr key of a member was compromised)
+    RotateSharedKey {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Remove a member (the new shared key is not sent to it)
+        #[arg(long)]
+        remove: Vec<PublicKey>,
+        /// Add a member (i.e. the new nostr key of a removed member)
+        #[arg(long)]
+        add: Vec<PublicKey>,
+    },
     /// Rebroadcast all events to connected relays
-    Rebroadcast,
+    Rebroadcast {
+        /// Rebroadcast only this event
+        #[arg(long, conflicts_with = "txs")]
+        event: Option<EventId>,
+        /// Re-submit the unconfirmed transactions of a policy to the backend
+        #[arg(long, value_name = "POLICY_ID")]
+        txs: Option<EventId>,
+    },
+    /// Rescan the timechain for every policy
+    Rescan,
+    /// Export the PSBTs of the pending proposals for external signers
+    ExportPsbts {
+        /// Directory
+        #[arg(required = true)]
+        dir: PathBuf,
+    },
+    /// Import the PSBTs signed by external signers (`<proposal_id>.signed.psbt`) as approvals
+    ImportPsbts {
+        /// Directory
+        #[arg(required = true)]
+        dir: PathBuf,
+    },
+    /// Verify the integrity of the signing audit log
+    VerifyAudit,
+    /// List the quarantined events (the app crashed while handling them) and the failed pending
+    /// events, and check the wallets against their policies
+    Doctor {
+        /// Try to handle again the quarantined events
+        #[arg(long)]
+        replay_quarantined: bool,
+        /// Quarantine the mismatching wallet states and rescan them from scratch
+        #[arg(long)]
+        repair_wallets: bool,
+    },
+    /// Retry now a pending event (i.e. received before its shared key), clearing its backoff
+    RetryEvent {
+        /// Event ID
+        #[arg(required = true)]
+        event_id: EventId,
+    },
     /// Exit
     Exit,
 }
 
+impl Command {
+    /// Value-moving or destructive action, that require the mainnet guard confirmation
+    pub fn mainnet_guarded_action(&self) -> Option<&'static str> {
+        match self {
+            Self::Spend { .. } | Self::SpendAll { .. } => Some("spend"),
+            Self::SpendBump { .. } => Some("bump the fee"),
+            Self::Finalize { .. } | Self::Approve { finalize: true, .. } => {
+                Some("finalize the proposal")
+            }
+            Self::Delete {
+                command: DeleteCommand::Policy { .. },
+            } => Some("delete the policy"),
+            Self::Delete {
+                command: DeleteCommand::Signer { .. },
+            } => Some("delete the signer"),
+            Self::Delete {
+                command: DeleteCommand::SharedSigner { .. },
+            } => Some("revoke the shared signer"),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum ProofCommand {
     /// New Proof Of Reserve
@@ -253,6 +557,8 @@ pub enum ProofCommand {
         #[arg(required = true)]
         proposal_id: EventId,
     },
+    /// Verify all the Proof Of Reserve and print the verdicts
+    Report,
 }
 
 #[derive(Debug, Subcommand)]
@@ -266,7 +572,7 @@ pub enum ConnectCommand {
     /// Disconnect session
     Disconnect {
         /// App Public Key
-        #[arg(required = true)]
+        #[arg(required = true, value_parser = parse_public_key)]
         app_public_key: PublicKey,
     },
     /// List sessions
@@ -286,7 +592,7 @@ pub enum ConnectCommand {
     /// Autoapprove
     Autoapprove {
         /// App Public Key
-        #[arg(required = true)]
+        #[arg(required = true, value_parser = parse_public_key)]
         app_public_key: PublicKey,
         /// Seconds
         #[arg(required = true)]
@@ -297,13 +603,17 @@ pub enum ConnectCommand {
     /// Revoke auto-approve
     Revoke {
         /// App Public Key
-        #[arg(required = true)]
+        #[arg(required = true, value_parser = parse_public_key)]
         app_public_key: PublicKey,
     },
 }
 
 #[derive(Debug, Subcommand)]
 pub enum KeyAgentCommand {
+    /// Announce as key agent (required to publish signer offerings)
+    Announce,
+    /// Stop announcing as key agent
+    Deannounce,
     /// Create or edit signer
     Signer {
         /// Signer ID
@@ -341,26 +651,46 @@ pub enum AddCommand {
         url: Url,
         /// Proxy
         proxy: Option<SocketAddr>,
+        /// Use the relay only to read (never publish to it)
+        #[arg(long, conflicts_with = "write_only")]
+        read_only: bool,
+        /// Use the relay only to publish (never subscribe to it)
+        #[arg(long)]
+        write_only: bool,
     },
     /// Add contact
     Contact {
         /// Public key
-        #[arg(required = true)]
+        #[arg(required = true, value_parser = parse_public_key)]
         public_key: PublicKey,
     },
     /// Add policy
     Policy {
         /// Policy name
-        #[arg(required = true)]
-        name: String,
+        #[arg(required_unless_present = "from_backup")]
+        name: Option<String>,
         /// Policy description
-        #[arg(required = true)]
-        description: String,
+        #[arg(required_unless_present = "from_backup")]
+        description: Option<String>,
         /// Policy descriptor
-        #[arg(required = true)]
-        descriptor: String,
+        #[arg(required_unless_present = "from_backup")]
+        descriptor: Option<String>,
         /// Nostr pubkeys
+        #[arg(value_parser = parse_public_key)]
         nostr_pubkeys: Vec<PublicKey>,
+        /// Restore the policy from a backup file (see `get policy --backup`)
+        #[arg(long, conflicts_with_all = ["name", "description", "descriptor", "nostr_pubkeys"])]
+        from_backup: Option<PathBuf>,
+        /// Save the policy even if it exceeds the complexity limits (max keys, script size and
+        /// selectable conditions)
+        #[arg(long, conflicts_with = "from_backup")]
+        override_limits: bool,
+    },
+    /// Add policy from a template
+    #[command(arg_required_else_help = true)]
+    PolicyTemplate {
+        #[command(subcommand)]
+        template: PolicyTemplateCommand,
     },
     /// Add SmartVaults Signer
     SmartVaultsSigner {
@@ -374,21 +704,256 @@ pub enum AddCommand {
         #[arg(required = true)]
         name: String,
         /// Signer fingerprint (master fingerprint)
-        #[arg(required = true)]
-        fingerprint: Fingerprint,
-        /// Signer name
-        #[arg(required = true)]
-        descriptor: Descriptor<DescriptorPublicKey>,
+        #[arg(required_unless_present_any = ["ur", "coldcard", "hw"])]
+        fingerprint: Option<Fingerprint>,
+        /// Signer descriptor
+        #[arg(required_unless_present_any = ["ur", "coldcard", "hw"])]
+        descriptor: Option<Descriptor<DescriptorPublicKey>>,
+        /// UR `crypto-account` exported by SeedSigner, Specter DIY, ... (repeat for multi-part URs)
+        #[arg(long, conflicts_with_all = ["fingerprint", "descriptor"])]
+        ur: Vec<String>,
+        /// Coldcard generic JSON export (Advanced/Tools -> Export Wallet -> Generic JSON)
+        #[arg(long, conflicts_with_all = ["fingerprint", "descriptor", "ur"])]
+        coldcard: Option<PathBuf>,
+        /// Pick a hardware device connected via USB (requires the `hwi` feature)
+        #[arg(long, conflicts_with_all = ["fingerprint", "descriptor", "ur", "coldcard"])]
+        hw: bool,
         /// Share with contacts
         #[arg(long)]
         share_with_contacts: bool,
     },
 }
 
+/// Spending proposal (also available in batch files)
+#[derive(Debug, Clone, Args)]
+pub struct SpendArgs {
+    /// Policy id
+    #[arg(required = true)]
+    pub policy_id: EventId,
+    /// To address
+    #[arg(required = true)]
+    pub to_address: Address<NetworkUnchecked>,
+    /// Amount: `0.015btc`, `1_500_000sat` or `all` (bare numbers are sats, up to 21,000,000)
+    #[arg(required = true)]
+    pub amount: Amount,
+    /// Description
+    #[arg(required = true)]
+    pub description: String,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    /// Coin selection strategy (default, economical, consolidate or privacy-first)
+    ///
+    /// If not set, the default one of the policy is used
+    #[arg(long)]
+    pub coin_selection: Option<CoinSelection>,
+    /// UTXO to spend (txid:vout, repeatable)
+    ///
+    /// If set, only the selected UTXOs are used (see `get utxos`)
+    #[arg(long = "utxo")]
+    pub utxos: Vec<OutPoint>,
+    /// Approve and finalize in one go if the destination is another local policy
+    /// and the local signers can satisfy the policy on their own
+    #[arg(long)]
+    pub auto_complete: bool,
+    /// Deduct the fee from the amount (the recipient receives amount minus fee)
+    #[arg(long)]
+    pub subtract_fee: bool,
+    /// Policy path (<path_id>:<index,...>, repeatable)
+    ///
+    /// If not set and can't be selected automatically, it's asked interactively
+    #[arg(long = "path")]
+    pub paths: Vec<CliPolicyPath>,
+    /// Print the fee, change and selected UTXOs without publishing the proposal
+    #[arg(long, conflicts_with = "auto_complete")]
+    pub dry_run: bool,
+    /// Send a DM with the proposal summary to the co-signers (see `config set --notify-cosigners`)
+    #[arg(long)]
+    pub notify: bool,
+}
+
+/// Fee of a transaction (`--target-blocks`, `--priority` or `--fee-rate`, default: 6 target blocks)
+#[derive(Debug, Clone, Args)]
+#[group(multiple = false)]
+pub struct FeeArgs {
+    /// Target blocks
+    #[arg(short, long)]
+    pub target_blocks: Option<u8>,
+    /// Priority (fee rate from the backend estimates, see `get fees`)
+    #[arg(long, value_enum)]
+    pub priority: Option<CliPriority>,
+    /// Fee rate (sat/vB)
+    #[arg(long)]
+    pub fee_rate: Option<f32>,
+}
+
+impl FeeArgs {
+    pub fn fee_rate(&self) -> FeeRate {
+        match (self.fee_rate, self.priority.clone()) {
+            (Some(rate), _) => FeeRate::Rate(rate),
+            (None, Some(priority)) => FeeRate::Priority(priority.into()),
+            (None, None) => FeeRate::Priority(Priority::Custom(self.target_blocks.unwrap_or(6))),
+        }
+    }
+}
+
+/// Name, description and participants of a policy created from a template
+#[derive(Debug, Clone, Args)]
+pub struct PolicyTemplateArgs {
+    /// Policy name
+    #[arg(required = true)]
+    pub name: String,
+    /// Policy description
+    #[arg(required = true)]
+    pub description: String,
+    /// Nostr pubkey of a participant (repeatable)
+    #[arg(long = "nostr-pubkey", required = true, value_parser = parse_public_key)]
+    pub nostr_pubkeys: Vec<PublicKey>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PolicyTemplateCommand {
+    /// N-of-M multisig
+    Multisig {
+        #[command(flatten)]
+        args: PolicyTemplateArgs,
+        /// Number of required signatures
+        #[arg(long, required = true)]
+        threshold: usize,
+        /// Key descriptor (repeatable)
+        #[arg(long = "xpub", required = true)]
+        keys: Vec<DescriptorPublicKey>,
+    },
+    /// Key locked until the timelock expires
+    Hold {
+        #[command(flatten)]
+        args: PolicyTemplateArgs,
+        /// Key descriptor
+        #[arg(long = "xpub", required = true)]
+        key: DescriptorPublicKey,
+        #[command(flatten)]
+        timelock: TimelockArgs,
+    },
+    /// Owner key or, after the timelock, the heirs
+    Inheritance {
+        #[command(flatten)]
+        args: PolicyTemplateArgs,
+        /// Owner key descriptor
+        #[arg(long, required = true)]
+        owner: DescriptorPublicKey,
+        /// Heir key descriptor (repeatable)
+        #[arg(long = "heir", required = true)]
+        heirs: Vec<DescriptorPublicKey>,
+        /// Number of heirs required to spend
+        #[arg(long, default_value_t = 1)]
+        threshold: usize,
+        #[command(flatten)]
+        timelock: TimelockArgs,
+    },
+    /// N-of-M multisig whose threshold decreases by one at each timelock
+    Decaying {
+        #[command(flatten)]
+        args: PolicyTemplateArgs,
+        /// Initial number of required signatures
+        #[arg(long, required = true)]
+        start_threshold: usize,
+        /// Key descriptor (repeatable)
+        #[arg(long = "xpub", required = true)]
+        keys: Vec<DescriptorPublicKey>,
+        /// Relative timelock in blocks (repeatable)
+        #[arg(long, conflicts_with = "after", required_unless_present = "after")]
+        older: Vec<u16>,
+        /// Absolute timelock, block height or unix timestamp (repeatable)
+        #[arg(long)]
+        after: Vec<u32>,
+    },
+}
+
+/// Timelock of a policy template (`--older` or `--after`)
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+pub struct TimelockArgs {
+    /// Relative timelock in blocks
+    #[arg(long)]
+    pub older: Option<u16>,
+    /// Absolute timelock, block height or unix timestamp
+    #[arg(long)]
+    pub after: Option<u32>,
+}
+
+impl TimelockArgs {
+    pub fn locktime(&self) -> Locktime {
+        match self.older {
+            Some(older) => Locktime::Older(Sequence::from_height(older)),
+            // One of the two is required by the arg group
+            None => Locktime::After(AbsoluteLockTime::from_consensus(
+                self.after.unwrap_or_default(),
+            )),
+        }
+    }
+}
+
+impl PolicyTemplateCommand {
+    /// Split into the common args and the [`PolicyTemplate`]
+    pub fn into_template(self) -> (PolicyTemplateArgs, PolicyTemplate) {
+        match self {
+            Self::Multisig {
+                args,
+                threshold,
+                keys,
+            } => (args, PolicyTemplate::multisig(threshold, keys)),
+            Self::Hold {
+                args,
+                key,
+                timelock,
+            } => (args, PolicyTemplate::hold(key, timelock.locktime())),
+            Self::Inheritance {
+                args,
+                owner,
+                heirs,
+                threshold,
+                timelock,
+            } => (
+                args,
+                PolicyTemplate::recovery(
+                    owner,
+                    RecoveryTemplate::new(threshold, heirs, timelock.locktime()),
+                ),
+            ),
+            Self::Decaying {
+                args,
+                start_threshold,
+                keys,
+                older,
+                after,
+            } => {
+                let mut timelocks: Vec<Locktime> = older
+                    .into_iter()
+                    .map(|older| Locktime::Older(Sequence::from_height(older)))
+                    .collect();
+                timelocks.extend(
+                    after
+                        .into_iter()
+                        .map(|after| Locktime::After(AbsoluteLockTime::from_consensus(after))),
+                );
+                let time: DecayingTime = if timelocks.len() == 1 {
+                    DecayingTime::Single(timelocks.remove(0))
+                } else {
+                    DecayingTime::Multiple(timelocks)
+                };
+                (args, PolicyTemplate::decaying(start_threshold, keys, time))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum GetCommand {
     /// Get contacts list
-    Contacts,
+    Contacts {
+        /// Filter by self-declared role
+        #[arg(long, value_enum)]
+        role: Option<CliUserRole>,
+    },
     /// Get policies list
     Policies,
     /// Get policy by id
@@ -399,29 +964,165 @@ pub enum GetCommand {
         /// Export descriptor
         #[arg(long)]
         export: bool,
+        /// Write a JSON backup (descriptor, name, description and nostr pubkeys)
+        #[arg(long)]
+        backup: Option<PathBuf>,
+        /// Show approver statistics
+        #[arg(long)]
+        stats: bool,
+        /// Show protocol compatibility of the members
+        #[arg(long)]
+        compat: bool,
+        /// Show the origin of the keys and the signers behind them
+        #[arg(long)]
+        keys: bool,
+        /// Export the spending policy as JSON for third-party analyzers
+        /// (schema: doc/policy-analysis.schema.json)
+        #[arg(long)]
+        analysis_json: bool,
     },
     /// Get proposals list
     Proposals {
         /// Get completed proposals
         #[arg(long)]
         completed: bool,
+        /// Get only the proposals awaiting my approval (of all the policies, oldest first)
+        #[arg(long, conflicts_with_all = ["completed", "policy", "limit"])]
+        mine: bool,
+        /// Filter by policy id
+        #[arg(long)]
+        policy: Option<EventId>,
+        /// Max number of proposals per page
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Page number (starts from 1)
+        #[arg(long, default_value_t = 1, requires = "limit")]
+        page: usize,
     },
     /// Get proposal by id
     Proposal {
         /// Proposal id
         #[arg(required = true)]
         proposal_id: EventId,
+        /// Export the unsigned PSBT to file (i.e. for airgapped signing)
+        ///
+        /// The policy descriptor is exported alongside, with `descriptor` extension
+        #[arg(long)]
+        export_psbt: Option<PathBuf>,
+        /// Export the PSBT as binary instead of base64
+        #[arg(long, requires = "export_psbt")]
+        binary: bool,
     },
     /// Get signers
     Signers,
+    /// Get contacts shared signers
+    SharedSigners {
+        /// Get the shared signers with an expired scope
+        #[arg(long)]
+        expired: bool,
+    },
     /// Get relays
-    Relays,
+    Relays {
+        /// Show health counters (events received, failed publications, send latency, ...)
+        #[arg(long)]
+        verbose: bool,
+        /// Measure the round-trip time to each relay
+        #[arg(long, requires = "verbose")]
+        ping: bool,
+    },
+    /// Get transaction details (inputs, outputs, fee and labels)
+    Tx {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Txid
+        #[arg(required = true)]
+        txid: Txid,
+    },
+    /// Get UTXOs
+    Utxos {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+    },
     /// Get addresses
     Addresses {
         /// Policy id
         #[arg(required = true)]
         policy_id: EventId,
+        /// Number of addresses to skip (with `--count`: index of the first address)
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+        /// Max number of addresses to show
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Derive this number of addresses, also beyond the gap limit
+        #[arg(long, conflicts_with = "limit")]
+        count: Option<usize>,
+    },
+    /// Screen address against the screening list
+    ScreeningStatus {
+        /// Address
+        #[arg(required = true)]
+        address: Address<NetworkUnchecked>,
+    },
+    /// Get signing audit log of a signer
+    Audit {
+        /// Signer id
+        #[arg(required = true)]
+        signer_id: EventId,
+        /// From (unix timestamp)
+        #[arg(long)]
+        from: Option<u64>,
+        /// To (unix timestamp)
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Get the miner fees paid by a policy
+    FeesPaid {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// From (unix timestamp)
+        #[arg(long)]
+        from: Option<u64>,
+        /// To (unix timestamp)
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Get the events waiting to be acknowledged by some relays
+    Outbox,
+    /// Get the relays a recipient-addressed event was sent to
+    EventDelivery {
+        /// Event ID
+        #[arg(required = true)]
+        event_id: EventId,
+    },
+    /// Get the outgoing transactions without a matching completed proposal
+    UnexpectedTxs {
+        /// Policy id
+        #[arg(long)]
+        policy_id: Option<EventId>,
     },
+    /// Get notifications
+    Notifications {
+        /// Show only the unseen notifications
+        #[arg(long)]
+        unseen_only: bool,
+        /// Approve or reject the pending Nostr Connect requests
+        #[arg(long)]
+        actions: bool,
+    },
+    /// Get the policies registered on hardware devices
+    HwRegistrations,
+    /// Get the hardware devices connected via USB (requires the `hwi` feature)
+    HwDevices,
+    /// Get the profile metadata versions published from this device
+    ProfileHistory,
+    /// Get the identities (fingerprints) used with the passphrases of this seed
+    Identities,
+    /// Get the current fee estimates of the backend
+    Fees,
 }
 
 #[derive(Debug, Subcommand)]
@@ -440,6 +1141,15 @@ pub enum SetCommand {
         /// Allow to set empty metadata
         #[arg(long)]
         empty: bool,
+        /// Show the changes against the published profile without publishing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Publish again an older version of the profile metadata
+    RevertProfile {
+        /// Version (see `get profile-history`)
+        #[arg(required = true)]
+        version: u64,
     },
     /// Set label
     Label {
@@ -453,6 +1163,87 @@ pub enum SetCommand {
         #[arg(required = true)]
         text: String,
     },
+    /// Set the default coin selection strategy of a policy
+    CoinSelection {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Strategy (default, economical, consolidate or privacy-first)
+        #[arg(required = true)]
+        strategy: CoinSelection,
+    },
+    /// Set the approval reminders of a policy: the pending approvers of my proposals are reminded
+    /// periodically
+    Reminders {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Remind every N hours
+        #[arg(long, required_unless_present = "disable")]
+        every: Option<u64>,
+        /// Max number of reminders for each proposal
+        #[arg(long, default_value_t = 3)]
+        max: u16,
+        /// Disable the reminders
+        #[arg(long, conflicts_with = "every")]
+        disable: bool,
+    },
+    /// Mute the approval reminders received for a proposal
+    MuteReminders {
+        /// Proposal id
+        #[arg(required = true)]
+        proposal_id: EventId,
+        /// Unmute
+        #[arg(long)]
+        unmute: bool,
+    },
+    /// Mark notification as seen
+    NotificationSeen {
+        /// ID of the policy, proposal, ... the notification refers to
+        #[arg(required_unless_present = "all")]
+        event_id: Option<EventId>,
+        /// Mark all the notifications as seen
+        #[arg(long, conflicts_with = "event_id")]
+        all: bool,
+    },
+    /// Set the read/write policy of a relay (read and write if no flag is passed)
+    RelayRole {
+        /// Url
+        #[arg(required = true)]
+        url: Url,
+        /// Use the relay only to read (never publish to it)
+        #[arg(long, conflicts_with = "write_only")]
+        read_only: bool,
+        /// Use the relay only to publish (never subscribe to it)
+        #[arg(long)]
+        write_only: bool,
+    },
+    /// Set the SOCKS5 proxy of a relay
+    RelayProxy {
+        /// Url
+        #[arg(required = true)]
+        url: Url,
+        /// Proxy address or `none` to connect directly
+        #[arg(required = true)]
+        proxy: CliProxy,
+    },
+    /// Set the policy birthday (blocks before it are skipped) and re-sync the policy
+    Birthday {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Block height (the policy creation is used if not set)
+        #[arg(long)]
+        height: Option<u32>,
+    },
+    /// Update the scope of a shared signer (no flags to remove it)
+    SharedSignerScope {
+        /// Shared Signer ID
+        #[arg(required = true)]
+        shared_signer_id: EventId,
+        #[command(flatten)]
+        scope: SharedSignerScopeArgs,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -463,11 +1254,44 @@ pub enum ShareCommand {
         #[arg(required = true)]
         signer_id: EventId,
         /// Public Key of the user with whom to share the signer
-        #[arg(required = true)]
+        #[arg(required = true, value_parser = parse_public_key)]
         public_key: PublicKey,
+        #[command(flatten)]
+        scope: SharedSignerScopeArgs,
     },
 }
 
+/// Advisory scope of a shared signer
+#[derive(Debug, Clone, Args)]
+pub struct SharedSignerScopeArgs {
+    /// Policy templates the signer is intended for (repeatable)
+    #[arg(long = "template", value_enum)]
+    pub templates: Vec<CliPolicyTemplateType>,
+    /// Days after which the signer should no longer be used
+    #[arg(long)]
+    pub expires_in_days: Option<u64>,
+    /// Max number of policies the signer is intended to be used in
+    #[arg(long)]
+    pub max_uses: Option<u32>,
+}
+
+impl SharedSignerScopeArgs {
+    /// `None` if no flag is set
+    pub fn into_scope(self) -> Option<SharedSignerScope> {
+        if self.templates.is_empty() && self.expires_in_days.is_none() && self.max_uses.is_none() {
+            return None;
+        }
+
+        Some(SharedSignerScope {
+            templates: self.templates.into_iter().map(|t| t.into()).collect(),
+            expires_at: self
+                .expires_in_days
+                .map(|days| Timestamp::now().as_u64() + days * 86_400),
+            max_uses: self.max_uses,
+        })
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum DeleteCommand {
     /// Remove relay
@@ -502,12 +1326,38 @@ pub enum DeleteCommand {
         /// Signer id
         #[arg(required = true)]
         signer_id: EventId,
+        /// Delete even if the signer is used by some policy
+        #[arg(long)]
+        force: bool,
     },
     /// Revoke shared signer by id
     SharedSigner {
         /// Signer id
         #[arg(required = true)]
         shared_signer_id: EventId,
+        /// Revoke even if the signer is used by some policy shared with the contact
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete notifications
+    Notifications {
+        /// Delete all the notifications
+        #[arg(long, required = true)]
+        all: bool,
+    },
+    /// Delete a policy registration of a hardware device
+    ///
+    /// The policy will be registered again at the next approval with the device
+    HwRegistration {
+        /// Policy id
+        #[arg(required = true)]
+        policy_id: EventId,
+        /// Device master fingerprint
+        #[arg(required = true)]
+        fingerprint: Fingerprint,
+        /// Device id
+        #[arg(required = true)]
+        device_id: String,
     },
     /// Clear cache
     Cache,
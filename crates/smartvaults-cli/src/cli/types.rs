@@ -1,9 +1,20 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
 use clap::ValueEnum;
+use smartvaults_sdk::config::SyncProfile;
+use smartvaults_sdk::core::bips::bip39::Language;
 use smartvaults_sdk::core::bitcoin::Network;
 use smartvaults_sdk::core::types::WordCount;
+use smartvaults_sdk::core::{PolicyTemplateType, Priority};
+use smartvaults_sdk::price::FiatCurrency;
+use smartvaults_sdk::screening::ScreeningAction;
+use smartvaults_sdk::types::UserRole;
+use smartvaults_sdk::util::{PublicKeyStyle, TimestampStyle};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum CliNetwork {
@@ -43,3 +54,222 @@ impl From<CliWordCount> for WordCount {
         }
     }
 }
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliLanguage {
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Spanish,
+}
+
+impl From<CliLanguage> for Language {
+    fn from(value: CliLanguage) -> Self {
+        match value {
+            CliLanguage::English => Self::English,
+            CliLanguage::SimplifiedChinese => Self::SimplifiedChinese,
+            CliLanguage::TraditionalChinese => Self::TraditionalChinese,
+            CliLanguage::Czech => Self::Czech,
+            CliLanguage::French => Self::French,
+            CliLanguage::Italian => Self::Italian,
+            CliLanguage::Japanese => Self::Japanese,
+            CliLanguage::Korean => Self::Korean,
+            CliLanguage::Spanish => Self::Spanish,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliPublicKeyStyle {
+    Hex,
+    Npub,
+}
+
+impl From<CliPublicKeyStyle> for PublicKeyStyle {
+    fn from(value: CliPublicKeyStyle) -> Self {
+        match value {
+            CliPublicKeyStyle::Hex => Self::Hex,
+            CliPublicKeyStyle::Npub => Self::Npub,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliTimestampStyle {
+    Local,
+    Utc,
+}
+
+impl From<CliTimestampStyle> for TimestampStyle {
+    fn from(value: CliTimestampStyle) -> Self {
+        match value {
+            CliTimestampStyle::Local => Self::Local,
+            CliTimestampStyle::Utc => Self::Utc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliScreeningAction {
+    Warn,
+    Block,
+}
+
+impl From<CliScreeningAction> for ScreeningAction {
+    fn from(value: CliScreeningAction) -> Self {
+        match value {
+            CliScreeningAction::Warn => Self::Warn,
+            CliScreeningAction::Block => Self::Block,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliFiatCurrency {
+    Usd,
+    Eur,
+}
+
+impl From<CliFiatCurrency> for FiatCurrency {
+    fn from(value: CliFiatCurrency) -> Self {
+        match value {
+            CliFiatCurrency::Usd => Self::Usd,
+            CliFiatCurrency::Eur => Self::Eur,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliPriority {
+    High,
+    Medium,
+    Low,
+}
+
+impl From<CliPriority> for Priority {
+    fn from(value: CliPriority) -> Self {
+        match value {
+            CliPriority::High => Self::High,
+            CliPriority::Medium => Self::Medium,
+            CliPriority::Low => Self::Low,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliSyncProfile {
+    Full,
+    Light,
+}
+
+impl From<CliSyncProfile> for SyncProfile {
+    fn from(value: CliSyncProfile) -> Self {
+        match value {
+            CliSyncProfile::Full => Self::Full,
+            CliSyncProfile::Light => Self::Light,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliUserRole {
+    User,
+    KeyAgent,
+}
+
+impl From<CliUserRole> for UserRole {
+    fn from(value: CliUserRole) -> Self {
+        match value {
+            CliUserRole::User => Self::User,
+            CliUserRole::KeyAgent => Self::KeyAgent,
+        }
+    }
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum CliPolicyTemplateType {
+    Singlesig,
+    Multisig,
+    Recovery,
+    Hold,
+    Decaying,
+}
+
+impl From<CliPolicyTemplateType> for PolicyTemplateType {
+    fn from(value: CliPolicyTemplateType) -> Self {
+        match value {
+            CliPolicyTemplateType::Singlesig => Self::Singlesig,
+            CliPolicyTemplateType::Multisig => Self::Multisig,
+            CliPolicyTemplateType::Recovery => Self::Recovery,
+            CliPolicyTemplateType::Hold => Self::Hold,
+            CliPolicyTemplateType::Decaying => Self::Decaying,
+        }
+    }
+}
+
+/// SOCKS5 proxy address or `none`
+#[derive(Debug, Clone, Copy)]
+pub struct CliProxy(pub Option<SocketAddr>);
+
+impl FromStr for CliProxy {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("none") {
+            Ok(Self(None))
+        } else {
+            Ok(Self(Some(SocketAddr::from_str(s)?)))
+        }
+    }
+}
+
+impl fmt::Display for CliProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(proxy) => write!(f, "{proxy}"),
+            None => write!(f, "none"),
+        }
+    }
+}
+
+/// Policy path selection: `<path_id>:<index,...>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliPolicyPath {
+    pub path: String,
+    pub indexes: Vec<usize>,
+}
+
+impl FromStr for CliPolicyPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, indexes) = s
+            .split_once(':')
+            .ok_or_else(|| String::from("expected <path_id>:<index,...>"))?;
+        if path.is_empty() {
+            return Err(String::from("empty path id"));
+        }
+        Ok(Self {
+            path: path.to_string(),
+            indexes: parse_indexes(indexes)?,
+        })
+    }
+}
+
+/// Parse comma separated indexes (ex. `0,2`)
+pub fn parse_indexes(s: &str) -> Result<Vec<usize>, String> {
+    let indexes: Vec<usize> = s
+        .split(',')
+        .map(|i| i.trim().parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("invalid index: {e}"))?;
+    if indexes.is_empty() {
+        return Err(String::from("no index selected"));
+    }
+    Ok(indexes)
+}
@@ -3,35 +3,50 @@
 
 #![forbid(unsafe_code)]
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::time::Duration;
 
 use clap::Parser;
-use cli::{AddCommand, ConfigCommand, ConnectCommand, KeyAgentCommand, SetCommand};
+use cli::{
+    AddCommand, ConfigCommand, ConnectCommand, KeyAgentCommand, PolicyTemplateArgs, SetCommand,
+};
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
-use smartvaults_sdk::config::Config;
-use smartvaults_sdk::core::bips::bip39::Mnemonic;
-use smartvaults_sdk::core::bitcoin::Network;
+use smartvaults_sdk::config::{Backend, BitcoindAuth, Config};
+use smartvaults_sdk::core::bitcoin::address::NetworkUnchecked;
+use smartvaults_sdk::core::bitcoin::psbt::PartiallySignedTransaction;
+use smartvaults_sdk::core::bitcoin::{Address, Network, OutPoint};
+#[cfg(feature = "hwi")]
+use smartvaults_sdk::core::hwi::types::HWIDevice;
+use smartvaults_sdk::core::policy::{PolicyAnalysis, SelectableCondition};
 use smartvaults_sdk::core::signer::Signer;
 use smartvaults_sdk::core::types::Priority;
-use smartvaults_sdk::core::{Amount, CompletedProposal, FeeRate, Keychain, Result};
-use smartvaults_sdk::nostr::{EventId, Metadata};
+use smartvaults_sdk::core::{
+    mnemonic, Amount, ApprovedProposal, CoinSelection, CompletedProposal, FeeRate, Keychain,
+    OutputKind, Policy, PsbtUtility, Result,
+};
+use smartvaults_sdk::nostr::{EventId, Metadata, PublicKey, Timestamp};
 use smartvaults_sdk::protocol::v1::{Label, SignerOffering};
-use smartvaults_sdk::types::{GetPolicy, GetProposal};
+use smartvaults_sdk::types::{
+    ApproveAndFinalize, AuditLogIntegrity, DisplayPreferences, GetAddress, GetPolicy, GetProposal,
+    NotificationKind, Pagination, PolicyBackup, RelayRole, ReminderSettings, SpendPreview,
+    UserRole,
+};
 use smartvaults_sdk::util::format;
-use smartvaults_sdk::{logger, SmartVaults};
+use smartvaults_sdk::util::ur::CryptoAccount;
+use smartvaults_sdk::{logger, Error, Message, Notification, SmartVaults};
 
 mod cli;
+mod progress;
 mod util;
 
 use crate::cli::batch::BatchCommand;
 use crate::cli::{
-    io, Cli, CliCommand, Command, DeleteCommand, GetCommand, ProofCommand, SettingCommand,
-    ShareCommand,
+    io, parse_indexes, Cli, CliCommand, CliPolicyPath, Command, DeleteCommand, GetCommand,
+    ProofCommand, SettingCommand, ShareCommand, SpendArgs,
 };
 
 fn base_path() -> Result<PathBuf> {
@@ -57,12 +72,16 @@ async fn run() -> Result<()> {
     let network: Network = args.network.into();
     let base_path: PathBuf = base_path()?;
 
+    smartvaults_sdk::util::set_public_key_style(args.pubkey_style.into());
+    smartvaults_sdk::util::set_timestamp_style(args.timestamp_style.into());
+
     logger::init(base_path.clone(), network, false)?;
 
     match args.command {
         CliCommand::Generate {
             name,
             word_count,
+            language,
             passphrase,
         } => {
             let password_from_env: Option<String> = io::get_password_from_env();
@@ -86,6 +105,7 @@ async fn run() -> Result<()> {
                     }
                 },
                 word_count.into(),
+                language.into(),
                 || {
                     if let Some(passphrase) = passphrase {
                         Ok(Some(passphrase))
@@ -107,13 +127,22 @@ async fn run() -> Result<()> {
 
             Ok(())
         }
-        CliCommand::Restore { name } => {
-            SmartVaults::restore(
+        CliCommand::Restore {
+            name,
+            settings,
+            language,
+        } => {
+            let client = SmartVaults::restore(
                 base_path,
                 name,
                 io::get_password,
                 io::get_confirmation_password,
-                || Ok(Mnemonic::from_str(&io::get_input("Mnemonic")?)?),
+                || {
+                    Ok(mnemonic::parse(
+                        io::get_input("Mnemonic")?,
+                        language.map(|l| l.into()),
+                    )?)
+                },
                 || {
                     if io::ask("Do you want to use a passphrase?")? {
                         Ok(Some(io::get_input("Passphrase")?))
@@ -124,16 +153,49 @@ async fn run() -> Result<()> {
                 network,
             )
             .await?;
+
+            if let Some(payload) = settings {
+                println!("Enter the password used to export the settings");
+                let password: String = io::get_password()?;
+                client.import_settings_qr(payload, password).await?;
+                println!("Settings imported");
+            }
+
+            client.shutdown().await?;
+
             Ok(())
         }
-        CliCommand::Open { name } => {
+        CliCommand::Open { name, steal_lock } => {
             let password: String = io::get_password()?;
-            let client = SmartVaults::open(base_path, name, password, network).await?;
+            let mut client =
+                SmartVaults::open_with_opts(base_path, name, password, network, steal_lock).await?;
+
+            // One-time banner after an upgrade
+            let releases = client.whats_new().await?;
+            if !releases.is_empty() {
+                util::print_whats_new(releases);
+                client.mark_whats_new_seen().await?;
+            }
+
+            // Notify when the initial relays sync is completed
+            let mut notifications = client.sync_notifications_since(0);
+            tokio::spawn(async move {
+                while let Ok(Notification { message, .. }) = notifications.recv().await {
+                    if let Message::InitialSyncComplete { events, relays } = message {
+                        println!("Synced {events} events from {relays} relays");
+                        break;
+                    }
+                }
+            });
 
             let rl = &mut DefaultEditor::new()?;
+            let prompt: &str = match client.network() {
+                Network::Bitcoin => "smartvaults [MAINNET]> ",
+                _ => "smartvaults> ",
+            };
 
             loop {
-                let readline = rl.readline("smartvaults> ");
+                let readline = rl.readline(prompt);
                 match readline {
                     Ok(line) => {
                         let _ = rl.add_history_entry(line.as_str());
@@ -141,7 +203,7 @@ async fn run() -> Result<()> {
                         vec.insert(0, String::new());
                         match Command::try_parse_from(vec) {
                             Ok(command) => {
-                                if let Err(e) = handle_command(command, &client).await {
+                                if let Err(e) = handle_command(command, &mut client).await {
                                     eprintln!("Error: {e}");
                                 }
                             }
@@ -167,9 +229,14 @@ async fn run() -> Result<()> {
 
             Ok(())
         }
-        CliCommand::Batch { name, path } => {
+        CliCommand::Batch {
+            name,
+            path,
+            steal_lock,
+        } => {
             let password: String = io::get_password()?;
-            let client = SmartVaults::open(base_path, name, password, network).await?;
+            let mut client =
+                SmartVaults::open_with_opts(base_path, name, password, network, steal_lock).await?;
 
             let file = File::open(path)?;
             let reader = BufReader::new(file);
@@ -180,7 +247,7 @@ async fn run() -> Result<()> {
                 println!("{line}");
                 match BatchCommand::try_parse_from(vec) {
                     Ok(command) => {
-                        if let Err(e) = handle_command(command.into(), &client).await {
+                        if let Err(e) = handle_command(command.into(), &mut client).await {
                             eprintln!("Error: {e}");
                         }
                     }
@@ -195,6 +262,25 @@ async fn run() -> Result<()> {
 
             Ok(())
         }
+        CliCommand::Backup { name, path } => {
+            let password: String = io::get_password()?;
+            let client = SmartVaults::open(base_path, name, password.clone(), network).await?;
+            client.export_backup(&path, password).await?;
+            client.shutdown().await?;
+            println!("Backup saved to {}", path.display());
+            Ok(())
+        }
+        CliCommand::RestoreBackup { path, force } => {
+            println!("Enter the password of the backed up keychain");
+            let password: String = io::get_password()?;
+            let client =
+                SmartVaults::import_backup(base_path, path, password, network, force).await?;
+            if let Some(name) = client.name() {
+                println!("Keychain {name} restored");
+            }
+            client.shutdown().await?;
+            Ok(())
+        }
         CliCommand::List => {
             let names: Vec<String> = SmartVaults::list_keychains(base_path, network)?;
             for (index, name) in names.iter().enumerate() {
@@ -202,21 +288,54 @@ async fn run() -> Result<()> {
             }
             Ok(())
         }
+        CliCommand::InspectDescriptor { desc_or_policy } => {
+            let analysis = PolicyAnalysis::from_desc_or_policy(desc_or_policy, network)?;
+            util::print_policy_analysis(analysis);
+            Ok(())
+        }
         CliCommand::Config { command } => match command {
             ConfigCommand::View => {
                 let config = Config::try_from_file(base_path, network)?;
-                println!("{}", config.as_pretty_json().await?);
-                Ok(())
+                util::print_config(&config).await
             }
             ConfigCommand::Set {
                 electrum_server,
+                bitcoind_url,
+                bitcoind_cookie,
+                bitcoind_user,
+                bitcoind_pass,
                 proxy,
                 block_explorer,
+                fee_targets,
+                fee_cap_percentage,
+                fee_cap_sats,
+                gap_limit,
+                screening_list,
+                screening_on_approve,
+                min_relay_acks,
+                max_recipient_relays,
+                notify_cosigners,
+                sync_profile,
+                light_horizon_days,
+                psbt_dir,
+                fiat_currency,
+                price_endpoint,
             } => {
                 let config = Config::try_from_file(base_path, network)?;
 
-                if let Some(endpoint) = electrum_server {
-                    config.set_electrum_endpoint(Some(endpoint)).await?;
+                if !electrum_server.is_empty() {
+                    config.set_electrum_endpoints(electrum_server).await?;
+                }
+
+                if let Some(url) = bitcoind_url {
+                    let auth = match bitcoind_cookie {
+                        Some(path) => BitcoindAuth::Cookie(path),
+                        None => BitcoindAuth::UserPass {
+                            user: bitcoind_user.unwrap_or_default(),
+                            pass: bitcoind_pass.unwrap_or_default(),
+                        },
+                    };
+                    config.set_backend(Backend::BitcoinCore { url, auth }).await;
                 }
 
                 if let Some(proxy) = proxy {
@@ -227,14 +346,87 @@ async fn run() -> Result<()> {
                     config.set_block_explorer(Some(block_explorer)).await;
                 }
 
+                if let Some(fee_targets) = fee_targets {
+                    config.set_fee_targets(Some(fee_targets)).await;
+                }
+
+                if fee_cap_percentage.is_some() || fee_cap_sats.is_some() {
+                    let mut fee_cap = config.fee_cap().await;
+                    if let Some(percentage) = fee_cap_percentage {
+                        fee_cap.percentage = percentage;
+                    }
+                    if let Some(absolute) = fee_cap_sats {
+                        fee_cap.absolute = absolute;
+                    }
+                    config.set_fee_cap(Some(fee_cap)).await;
+                }
+
+                if let Some(gap_limit) = gap_limit {
+                    config.set_gap_limit(Some(gap_limit)).await;
+                }
+
+                if let Some(path) = screening_list {
+                    config.set_screening_list_path(Some(path)).await;
+                }
+
+                if let Some(action) = screening_on_approve {
+                    config.set_screening_on_approve(action.into()).await;
+                }
+
+                if let Some(min_relay_acks) = min_relay_acks {
+                    config.set_min_relay_acks(Some(min_relay_acks)).await;
+                }
+
+                if let Some(max_recipient_relays) = max_recipient_relays {
+                    config
+                        .set_max_recipient_relays(Some(max_recipient_relays))
+                        .await;
+                }
+
+                if let Some(notify_cosigners) = notify_cosigners {
+                    config.set_notify_cosigners(Some(notify_cosigners)).await;
+                }
+
+                if let Some(profile) = sync_profile {
+                    config.set_sync_profile(profile.into()).await;
+                }
+
+                if let Some(days) = light_horizon_days {
+                    config.set_light_sync_horizon(Some(days)).await;
+                }
+
+                if let Some(path) = psbt_dir {
+                    config.set_psbt_dir(Some(path)).await;
+                }
+
+                if let Some(currency) = fiat_currency {
+                    config.set_fiat_currency(Some(currency.into())).await;
+                }
+
+                if let Some(endpoint) = price_endpoint {
+                    config.set_price_endpoint(Some(endpoint)).await;
+                }
+
                 config.save().await?;
 
                 Ok(())
             }
             ConfigCommand::Unset {
                 electrum_server,
+                bitcoind,
                 proxy,
                 block_explorer,
+                fee_targets,
+                fee_cap,
+                gap_limit,
+                screening_list,
+                min_relay_acks,
+                max_recipient_relays,
+                notify_cosigners,
+                light_horizon_days,
+                psbt_dir,
+                fiat_currency,
+                price_endpoint,
             } => {
                 let config = Config::try_from_file(base_path, network)?;
 
@@ -242,6 +434,10 @@ async fn run() -> Result<()> {
                     config.set_electrum_endpoint::<String>(None).await?;
                 }
 
+                if bitcoind {
+                    config.set_backend(Backend::Electrum).await;
+                }
+
                 if proxy {
                     config.set_proxy(None).await;
                 }
@@ -250,6 +446,50 @@ async fn run() -> Result<()> {
                     config.set_block_explorer(None).await;
                 }
 
+                if fee_targets {
+                    config.set_fee_targets(None).await;
+                }
+
+                if fee_cap {
+                    config.set_fee_cap(None).await;
+                }
+
+                if gap_limit {
+                    config.set_gap_limit(None).await;
+                }
+
+                if screening_list {
+                    config.set_screening_list_path(None).await;
+                }
+
+                if min_relay_acks {
+                    config.set_min_relay_acks(None).await;
+                }
+
+                if max_recipient_relays {
+                    config.set_max_recipient_relays(None).await;
+                }
+
+                if notify_cosigners {
+                    config.set_notify_cosigners(None).await;
+                }
+
+                if light_horizon_days {
+                    config.set_light_sync_horizon(None).await;
+                }
+
+                if psbt_dir {
+                    config.set_psbt_dir(None).await;
+                }
+
+                if fiat_currency {
+                    config.set_fiat_currency(None).await;
+                }
+
+                if price_endpoint {
+                    config.set_price_endpoint(None).await;
+                }
+
                 config.save().await?;
 
                 Ok(())
@@ -258,7 +498,16 @@ async fn run() -> Result<()> {
     }
 }
 
-async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
+async fn handle_command(command: Command, client: &mut SmartVaults) -> Result<()> {
+    // Spends are confirmed after printing the preview
+    let is_spend: bool = matches!(command, Command::Spend { .. } | Command::SpendAll { .. });
+    if let Some(action) = command.mainnet_guarded_action().filter(|_| !is_spend) {
+        if client.mainnet_guard().await? && !io::confirm_mainnet(action)? {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
     match command {
         Command::Inspect => {
             let password: String = io::get_password()?;
@@ -266,95 +515,364 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
             util::print_secrets(keychain, client.network())
         }
         Command::Spend {
-            policy_id,
-            to_address,
-            amount,
-            description,
-            target_blocks,
-        } => {
-            let GetProposal { proposal_id, .. } = client
-                .spend(
+            args:
+                SpendArgs {
                     policy_id,
                     to_address,
-                    Amount::Custom(amount),
+                    amount,
                     description,
-                    FeeRate::Priority(Priority::Custom(target_blocks)),
-                    None,
-                    None,
-                    false,
-                )
+                    fee,
+                    coin_selection,
+                    utxos,
+                    auto_complete,
+                    subtract_fee,
+                    paths,
+                    dry_run,
+                    notify,
+                },
+        } => {
+            let coin_selection: CoinSelection = client
+                .resolve_coin_selection(policy_id, coin_selection)
                 .await?;
+            let utxos: Option<Vec<OutPoint>> = (!utxos.is_empty()).then_some(utxos);
+            let amount: Amount = match amount {
+                Amount::Custom(amount) if subtract_fee => Amount::CustomSubtractFee(amount),
+                amount => amount,
+            };
+            let fee_rate: FeeRate = fee.fee_rate();
+
+            let preview: SpendPreview = build_spend_preview(
+                client,
+                policy_id,
+                to_address,
+                amount,
+                &description,
+                fee_rate,
+                utxos,
+                policy_path_from_args(paths),
+                coin_selection,
+            )
+            .await?;
+            util::print_spend_preview(coin_selection, &preview);
+
+            if dry_run || !confirm_spend(client).await? {
+                return Ok(());
+            }
+
+            // Publish the previewed proposal, without rebuilding it
+            if auto_complete {
+                let password: String = io::get_password()?;
+                let completed_proposal: CompletedProposal = client
+                    .publish_spend_preview_with_auto_complete(password, preview)
+                    .await?;
+                if let CompletedProposal::Spending { tx, .. } = completed_proposal {
+                    println!("Transaction {} broadcasted", tx.txid());
+                }
+                return Ok(());
+            }
+
+            let GetProposal { proposal_id, .. } = client.publish_spend_preview(preview).await?;
             println!("Spending proposal {proposal_id} sent");
+
+            // Already notified if enabled in config
+            if notify && !client.config().notify_cosigners().await {
+                let notified = client.notify_cosigners(proposal_id).await?;
+                println!("{} co-signers notified", notified.len());
+            }
+
             Ok(())
         }
         Command::SpendAll {
             policy_id,
             to_address,
             description,
-            target_blocks,
+            fee,
+            coin_selection,
+            utxos,
+            paths,
         } => {
-            let GetProposal { proposal_id, .. } = client
-                .spend(
-                    policy_id,
-                    to_address,
-                    Amount::Max,
-                    description,
-                    FeeRate::Priority(Priority::Custom(target_blocks)),
-                    None,
-                    None,
-                    false,
-                )
+            let coin_selection: CoinSelection = client
+                .resolve_coin_selection(policy_id, coin_selection)
                 .await?;
+            let utxos: Option<Vec<OutPoint>> = (!utxos.is_empty()).then_some(utxos);
+            let fee_rate: FeeRate = fee.fee_rate();
+
+            let preview: SpendPreview = build_spend_preview(
+                client,
+                policy_id,
+                to_address,
+                Amount::Max,
+                &description,
+                fee_rate,
+                utxos,
+                policy_path_from_args(paths),
+                coin_selection,
+            )
+            .await?;
+            util::print_spend_preview(coin_selection, &preview);
+
+            if !confirm_spend(client).await? {
+                return Ok(());
+            }
+
+            let GetProposal { proposal_id, .. } = client.publish_spend_preview(preview).await?;
             println!("Spending proposal {proposal_id} sent");
             Ok(())
         }
-        Command::Approve { proposal_id } => {
-            let password: String = io::get_password()?;
-            let (event_id, _) = client.approve(password, proposal_id).await?;
-            println!("Proposal {proposal_id} approved: {event_id}");
+        Command::SpendBump {
+            policy_id,
+            txid,
+            target_blocks,
+            paths,
+        } => {
+            let fee_rate = FeeRate::Priority(Priority::Custom(target_blocks));
+            let res = client
+                .bump_fee(policy_id, txid, fee_rate, policy_path_from_args(paths))
+                .await;
+            let GetProposal { proposal_id, .. } = match res {
+                Err(Error::PolicyPathRequired(conditions)) => {
+                    let GetPolicy { policy, .. } = client.get_policy_by_id(policy_id).await?;
+                    let policy_path = select_policy_path(&policy, conditions)?;
+                    client
+                        .bump_fee(policy_id, txid, fee_rate, Some(policy_path))
+                        .await?
+                }
+                res => res?,
+            };
+            println!("Fee bump proposal {proposal_id} sent");
             Ok(())
         }
-        Command::Finalize { proposal_id } => {
-            let completed_proposal: CompletedProposal = client.finalize(proposal_id).await?;
-
-            match completed_proposal {
-                CompletedProposal::Spending { tx, .. } => {
-                    let txid = tx.txid();
+        Command::Approve {
+            proposal_id,
+            acknowledge_fee,
+            force,
+            psbt,
+            hw,
+            signer,
+            finalize,
+        } => {
+            let GetProposal { fee_warning, .. } = client.get_proposal_by_id(proposal_id).await?;
+            if let Some(warning) = fee_warning {
+                util::print_fee_warning(warning);
+                if !acknowledge_fee {
+                    println!("Use --acknowledge-fee to approve anyway");
+                    return Ok(());
+                }
+            }
 
-                    println!("Transaction {txid} broadcasted");
+            let outputs = client.verify_proposal_outputs(proposal_id).await?;
+            let screening = client.screen_proposal(proposal_id).await?;
+            let flagged: bool = screening.iter().any(|(_, r)| r.is_flagged());
+            if flagged || outputs.iter().any(|o| o.kind == OutputKind::Unverified) {
+                util::print_proposal_outputs(outputs.clone(), screening, client.network());
+            }
+            if outputs.iter().any(|o| o.kind == OutputKind::Unverified) {
+                if !force {
+                    println!("Use --force to approve anyway");
+                    return Ok(());
+                }
+            }
 
-                    match client.network() {
-                        Network::Bitcoin => {
-                            println!("\nExplorer: https://blockstream.info/tx/{txid} \n")
-                        }
-                        Network::Testnet => {
-                            println!("\nExplorer: https://blockstream.info/testnet/tx/{txid} \n")
+            if finalize {
+                let password: String = io::get_password()?;
+                match client
+                    .approve_and_finalize(password, proposal_id, acknowledge_fee, force)
+                    .await?
+                {
+                    ApproveAndFinalize::Finalized {
+                        approval_id,
+                        completed_proposal,
+                    } => {
+                        println!("Proposal {proposal_id} approved: {approval_id}");
+                        util::print_completed_proposal(completed_proposal, client.network());
+                    }
+                    ApproveAndFinalize::Approved {
+                        approval_id,
+                        missing_signatures,
+                        ..
+                    } => {
+                        println!("Proposal {proposal_id} approved: {approval_id}");
+                        match missing_signatures {
+                            Some(missing) => println!(
+                                "Not finalized: still missing {missing} signature{}",
+                                if missing == 1 { "" } else { "s" }
+                            ),
+                            None => println!("Not finalized: the policy is not satisfied yet"),
                         }
-                        _ => (),
-                    };
+                    }
                 }
-                CompletedProposal::KeyAgentPayment { tx, .. } => {
-                    let txid = tx.txid();
-
-                    println!("Key agent payment broadcasted: {txid}");
+                return Ok(());
+            }
 
-                    match client.network() {
-                        Network::Bitcoin => {
-                            println!("\nExplorer: https://blockstream.info/tx/{txid} \n")
+            let (event_id, _) = match psbt {
+                Some(path) => {
+                    let signed_psbt = PartiallySignedTransaction::from_file(path)?;
+                    client
+                        .approve_with_signed_psbt(proposal_id, signed_psbt)
+                        .await?
+                }
+                None if hw => approve_with_hw(client, proposal_id).await?,
+                None => {
+                    let password: String = io::get_password()?;
+                    match signer {
+                        Some(signer_id) => {
+                            client
+                                .approve_with_signer(
+                                    password,
+                                    proposal_id,
+                                    signer_id,
+                                    acknowledge_fee,
+                                    force,
+                                )
+                                .await?
                         }
-                        Network::Testnet => {
-                            println!("\nExplorer: https://blockstream.info/testnet/tx/{txid} \n")
+                        None => {
+                            client
+                                .approve(password, proposal_id, acknowledge_fee, force)
+                                .await?
                         }
-                        _ => (),
-                    };
+                    }
                 }
-                CompletedProposal::ProofOfReserve { .. } => println!("Proof of Reserve finalized"),
             };
+            println!("Proposal {proposal_id} approved: {event_id}");
+            Ok(())
+        }
+        Command::Finalize { proposal_id } => {
+            let completed_proposal: CompletedProposal = client.finalize(proposal_id).await?;
+            util::print_completed_proposal(completed_proposal, client.network());
+            Ok(())
+        }
+        Command::NotifyCosigners { proposal_id } => {
+            let notified = client.notify_cosigners(proposal_id).await?;
+            println!("{} co-signers notified", notified.len());
+            Ok(())
+        }
+        Command::AckTx { policy_id, txid } => {
+            client.acknowledge_unexpected_tx(policy_id, txid).await?;
+            Ok(())
+        }
+        Command::Backfill { policy_id } => {
+            let handled: usize = progress::run("Backfilling", |progress, token| {
+                client.backfill_policy_with_progress(policy_id, progress, token)
+            })
+            .await?;
+            println!("{handled} events backfilled");
+            Ok(())
+        }
+        Command::RotateSharedKey {
+            policy_id,
+            remove,
+            add,
+        } => {
+            let mut members: Vec<PublicKey> = client
+                .get_members_of_policy(policy_id)
+                .await?
+                .into_iter()
+                .map(|p| p.public_key())
+                .filter(|pk| !remove.contains(pk))
+                .collect();
+            for public_key in add.into_iter() {
+                if !members.contains(&public_key) {
+                    members.push(public_key);
+                }
+            }
 
+            let participants = client.check_policy_participants(&members).await?;
+            util::print_policy_participants(&participants);
+            println!("The policy will be republished with a new shared key: the old policy ID will be replaced.");
+            if !io::ask("Rotate the shared key?")? {
+                return Ok(());
+            }
+
+            let new_policy_id = client.rotate_shared_key(policy_id, members).await?;
+            println!("Shared key rotated: new policy {new_policy_id}");
             Ok(())
         }
-        Command::Rebroadcast => {
-            client.rebroadcast_all_events().await?;
+        Command::Rebroadcast { event, txs } => {
+            if let Some(event_id) = event {
+                let report = client.rebroadcast_event(event_id).await?;
+                for (url, res) in report.results.get(&event_id).into_iter().flatten() {
+                    match res {
+                        Some(e) => println!("{url}: {e}"),
+                        None => println!("{url}: sent"),
+                    }
+                }
+            } else if let Some(policy_id) = txs {
+                let txids = client.rebroadcast_unconfirmed_txs(policy_id).await?;
+                if txids.is_empty() {
+                    println!("No unconfirmed transactions");
+                }
+                for txid in txids.into_iter() {
+                    println!("Tx {txid} rebroadcasted");
+                }
+            } else {
+                progress::run("Rebroadcasting", |progress, token| {
+                    client.rebroadcast_all_events_with_progress(progress, token)
+                })
+                .await?;
+            }
+            Ok(())
+        }
+        Command::Rescan => {
+            progress::run("Rescanning", |progress, token| {
+                client.rescan_with_progress(progress, token)
+            })
+            .await?;
+            println!("Rescan completed");
+            Ok(())
+        }
+        Command::ExportPsbts { dir } => {
+            let exported = client.export_pending_proposals(&dir).await?;
+            println!("{} proposals exported to {}", exported.len(), dir.display());
+            Ok(())
+        }
+        Command::ImportPsbts { dir } => {
+            let approved = client.import_signed_psbt_dir(&dir).await?;
+            for proposal_id in approved.iter() {
+                println!("Proposal {proposal_id} approved");
+            }
+            println!("{} signed PSBTs imported", approved.len());
+            Ok(())
+        }
+        Command::VerifyAudit => {
+            match client.verify_signing_audit().await? {
+                AuditLogIntegrity::Valid { entries } => {
+                    println!("Signing audit log is valid ({entries} entries)")
+                }
+                AuditLogIntegrity::Tampered { reason } => {
+                    eprintln!("Signing audit log was tampered: {reason}")
+                }
+            }
+            Ok(())
+        }
+        Command::Doctor {
+            replay_quarantined,
+            repair_wallets,
+        } => {
+            if replay_quarantined {
+                let replayed = client.replay_quarantined_events().await?;
+                println!("Replayed {} quarantined events", replayed.len());
+            }
+            let events = client.quarantined_events().await?;
+            util::print_quarantined_events(events);
+
+            let failed = client.failed_events().await?;
+            if !failed.is_empty() {
+                println!("Failed pending events (use retry-event to retry them):");
+                util::print_failed_events(failed);
+            }
+
+            let mismatches = client.check_wallets_integrity(repair_wallets).await?;
+            if mismatches.is_empty() {
+                println!("All wallets match their policies");
+            }
+            for policy_id in mismatches.into_iter() {
+                if repair_wallets {
+                    println!("Wallet of policy {policy_id} quarantined: rescanning from scratch");
+                } else {
+                    println!("Wallet of policy {policy_id} doesn't match the policy (use --repair-wallets)");
+                }
+            }
             Ok(())
         }
         Command::Proof { command } => match command {
@@ -371,6 +889,14 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 );
                 Ok(())
             }
+            ProofCommand::Report => {
+                let proofs = progress::run("Verifying proofs", |progress, token| {
+                    client.proof_report_with_progress(progress, token)
+                })
+                .await?;
+                util::print_proof_report(proofs);
+                Ok(())
+            }
         },
         Command::Connect { command } => match command {
             ConnectCommand::New { uri } => {
@@ -422,6 +948,16 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
             }
         },
         Command::KeyAgent { command } => match command {
+            KeyAgentCommand::Announce => {
+                let event_id: EventId = client.announce_key_agent().await?;
+                println!("Announced as key agent: {event_id}");
+                Ok(())
+            }
+            KeyAgentCommand::Deannounce => {
+                client.deannounce_key_agent().await?;
+                println!("Key agent announcement deleted");
+                Ok(())
+            }
             KeyAgentCommand::Signer {
                 signer_id,
                 temperature,
@@ -455,22 +991,114 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
             }
         },
         Command::Add { command } => match command {
-            AddCommand::Relay { url, proxy } => {
-                client.add_relay(url, proxy).await?;
+            AddCommand::Relay {
+                url,
+                proxy,
+                read_only,
+                write_only,
+            } => {
+                let role: RelayRole = util::relay_role(read_only, write_only);
+                client.add_relay(url, proxy, role).await?;
                 Ok(())
             }
             AddCommand::Contact { public_key } => {
                 client.add_contact(public_key).await?;
                 Ok(())
             }
+            AddCommand::Policy {
+                from_backup: Some(path),
+                ..
+            } => {
+                let backup = PolicyBackup::open(path)?;
+                let participants = client
+                    .check_policy_participants(&backup.public_keys())
+                    .await?;
+                util::print_policy_participants(&participants);
+                if participants.iter().any(|p| !p.is_me && !p.is_contact)
+                    && !io::ask("Some public keys are not in your contacts: continue?")?
+                {
+                    return Ok(());
+                }
+
+                match client.restore_policy_backup(backup).await {
+                    Ok(policy_id) => println!("Policy restored: {policy_id}"),
+                    Err(Error::PolicyAlreadyExists(policy_id)) => {
+                        println!("Policy already exists: {policy_id}")
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+                Ok(())
+            }
             AddCommand::Policy {
                 name,
                 description,
                 descriptor,
                 nostr_pubkeys,
+                from_backup: None,
+                override_limits,
             } => {
+                let name: String = name.unwrap_or_default();
+                let description: String = description.unwrap_or_default();
+                let descriptor: String = descriptor.unwrap_or_default();
+                let participants = client.check_policy_participants(&nostr_pubkeys).await?;
+                util::print_policy_participants(&participants);
+                if participants.iter().any(|p| !p.is_me && !p.is_contact)
+                    && !io::ask("Some public keys are not in your contacts: continue?")?
+                {
+                    return Ok(());
+                }
+
                 let policy_id = client
-                    .save_policy(name, description, descriptor, nostr_pubkeys)
+                    .save_policy_with_limits(
+                        name,
+                        description,
+                        descriptor,
+                        nostr_pubkeys,
+                        !override_limits,
+                    )
+                    .await?;
+                if override_limits {
+                    let GetPolicy { policy, .. } = client.get_policy_by_id(policy_id).await?;
+                    util::print_limits_exceeded(policy.complexity().exceeded());
+                }
+                println!("Policy saved: {policy_id}");
+                Ok(())
+            }
+            AddCommand::PolicyTemplate { template } => {
+                let (
+                    PolicyTemplateArgs {
+                        name,
+                        description,
+                        nostr_pubkeys,
+                    },
+                    template,
+                ) = template.into_template();
+
+                let policy: Policy = client.policy_from_template(
+                    name.as_str(),
+                    description.as_str(),
+                    template.clone(),
+                )?;
+                println!("Descriptor: {}", policy.descriptor());
+                match policy.template_match()? {
+                    Some(template_type) => println!("Detected template: {template_type}"),
+                    None => println!("Detected template: none"),
+                }
+
+                let participants = client.check_policy_participants(&nostr_pubkeys).await?;
+                util::print_policy_participants(&participants);
+                if participants.iter().any(|p| !p.is_me && !p.is_contact)
+                    && !io::ask("Some public keys are not in your contacts: continue?")?
+                {
+                    return Ok(());
+                }
+
+                if !io::ask("Publish the policy?")? {
+                    return Ok(());
+                }
+
+                let policy_id = client
+                    .save_policy_from_template(name, description, template, nostr_pubkeys)
                     .await?;
                 println!("Policy saved: {policy_id}");
                 Ok(())
@@ -480,8 +1108,17 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
             } => {
                 let signer_id = client.save_smartvaults_signer().await?;
                 if share_with_contacts {
-                    for user in client.get_contacts().await? {
-                        client.share_signer(signer_id, user.public_key()).await?;
+                    let public_keys: Vec<PublicKey> = client
+                        .get_contacts()
+                        .await?
+                        .into_iter()
+                        .map(|user| user.public_key())
+                        .collect();
+                    if !public_keys.is_empty() {
+                        let report = client
+                            .share_signer_to_multiple_public_keys(signer_id, public_keys)
+                            .await?;
+                        println!("Signer shared with contacts: {report}");
                     }
                 }
                 Ok(())
@@ -490,21 +1127,60 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 name,
                 fingerprint,
                 descriptor,
+                ur,
+                coldcard,
+                hw,
                 share_with_contacts,
             } => {
-                let signer = Signer::airgap(name, None, fingerprint, descriptor, client.network())?;
-                let signer_id = client.save_signer(signer).await?;
+                let signer_id = match coldcard {
+                    None if hw => add_hw_signer(client, name).await?,
+                    Some(path) => {
+                        let signer_id = client.save_coldcard_signer(name, path).await?;
+                        let signer = client.get_signer_by_id(signer_id).await?;
+                        println!(
+                            "Coldcard signer saved: {signer_id} (fingerprint {})",
+                            signer.fingerprint()
+                        );
+                        signer_id
+                    }
+                    None => {
+                        let signer = match (fingerprint, descriptor) {
+                            (Some(fingerprint), Some(descriptor)) => Signer::airgap(
+                                name,
+                                None,
+                                fingerprint,
+                                descriptor,
+                                client.network(),
+                            )?,
+                            _ => CryptoAccount::from_ur(&ur)?.to_signer(name, client.network())?,
+                        };
+                        client.save_signer(signer).await?
+                    }
+                };
                 if share_with_contacts {
-                    for user in client.get_contacts().await? {
-                        client.share_signer(signer_id, user.public_key()).await?;
+                    let public_keys: Vec<PublicKey> = client
+                        .get_contacts()
+                        .await?
+                        .into_iter()
+                        .map(|user| user.public_key())
+                        .collect();
+                    if !public_keys.is_empty() {
+                        let report = client
+                            .share_signer_to_multiple_public_keys(signer_id, public_keys)
+                            .await?;
+                        println!("Signer shared with contacts: {report}");
                     }
                 }
                 Ok(())
             }
         },
         Command::Get { command } => match command {
-            GetCommand::Contacts => {
-                let contacts = client.get_contacts().await?;
+            GetCommand::Contacts { role } => {
+                let mut contacts = client.get_contacts_with_metadata().await?;
+                if let Some(role) = role {
+                    let role: UserRole = role.into();
+                    contacts.retain(|c| c.role == role);
+                }
                 util::print_contacts(contacts);
                 Ok(())
             }
@@ -513,36 +1189,121 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 util::print_policies(policies);
                 Ok(())
             }
-            GetCommand::Policy { policy_id, export } => {
+            GetCommand::Policy {
+                policy_id,
+                export,
+                backup,
+                stats,
+                compat,
+                keys,
+                analysis_json,
+            } => {
                 // Get policy
                 let policy: GetPolicy = client.get_policy_by_id(policy_id).await?;
 
                 // Print result
-                if export {
+                if let Some(path) = backup {
+                    client.save_policy_backup(policy_id, &path).await?;
+                    println!("Policy backup saved to {}", path.display());
+                    Ok(())
+                } else if export {
                     println!("\n{}\n", policy.as_descriptor());
                     Ok(())
+                } else if stats {
+                    let stats = client.get_approver_stats(policy_id).await?;
+                    util::print_approver_stats(stats);
+                    Ok(())
+                } else if compat {
+                    let report = client.policy_compatibility(policy_id).await?;
+                    util::print_policy_compatibility(report);
+                    Ok(())
+                } else if keys {
+                    let report = client.get_policy_key_report(policy_id).await?;
+                    util::print_policy_key_report(report);
+                    Ok(())
+                } else if analysis_json {
+                    println!("{}", policy.to_analysis_json(client.network())?);
+                    Ok(())
                 } else {
                     let item = policy.satisfiable_item()?.clone();
                     let address = client.get_last_unused_address(policy_id).await?;
                     let txs = client.get_txs(policy_id).await.unwrap_or_default();
                     let utxos = client.get_utxos(policy_id).await.unwrap_or_default();
-                    util::print_policy(policy, policy_id, item, address, txs, utxos);
+                    let fiat = match client.config().fiat_currency().await {
+                        Some(currency) => client
+                            .get_balance_with_fiat(policy_id, currency)
+                            .await
+                            .and_then(|b| b.fiat),
+                        None => None,
+                    };
+                    util::print_policy(policy, policy_id, item, address, txs, utxos, fiat);
                     Ok(())
                 }
             }
-            GetCommand::Proposals { completed } => {
-                if completed {
-                    let proposals = client.get_completed_proposals().await?;
-                    util::print_completed_proposals(proposals);
+            GetCommand::Proposals {
+                completed,
+                mine,
+                policy,
+                limit,
+                page,
+            } => {
+                if mine {
+                    let proposals = client.get_proposals_awaiting_my_approval().await?;
+                    util::print_proposals_awaiting_my_approval(proposals);
+                    return Ok(());
+                }
+
+                let mut opts = Pagination::new();
+                if let Some(policy_id) = policy {
+                    opts = opts.policy_id(policy_id);
+                }
+                if let Some(limit) = limit {
+                    opts = opts.page(page, limit);
+                }
+
+                let total: usize = if completed {
+                    let proposals = client.get_completed_proposals_paginated(opts).await?;
+                    let proofs = client.get_proof_verifications().await?;
+                    util::print_completed_proposals(proposals.items, proofs);
+                    proposals.total
                 } else {
-                    let proposals = client.get_proposals().await?;
-                    util::print_proposals(proposals);
+                    let proposals = client.get_proposals_paginated(opts).await?;
+                    util::print_proposals(proposals.items);
+                    proposals.total
+                };
+
+                if let Some(limit) = limit {
+                    println!(
+                        "Page {page} of {} ({total} proposals)",
+                        total.div_ceil(limit).max(1)
+                    );
                 }
+
                 Ok(())
             }
-            GetCommand::Proposal { proposal_id } => {
+            GetCommand::Proposal {
+                proposal_id,
+                export_psbt,
+                binary,
+            } => {
+                if let Some(path) = export_psbt {
+                    let descriptor_path = client
+                        .save_proposal_psbt(proposal_id, &path, binary)
+                        .await?;
+                    println!("PSBT exported to {}", path.display());
+                    println!("Descriptor exported to {}", descriptor_path.display());
+                    return Ok(());
+                }
+
                 let proposal = client.get_proposal_by_id(proposal_id).await?;
                 util::print_proposal(proposal);
+                let signers = client.get_proposal_signers_status(proposal_id).await?;
+                util::print_proposal_signers(signers);
+                let approvals = client.get_approvals_by_proposal_id(proposal_id).await?;
+                util::print_approvals(approvals);
+                let outputs = client.verify_proposal_outputs(proposal_id).await?;
+                let screening = client.screen_proposal(proposal_id).await?;
+                util::print_proposal_outputs(outputs, screening, client.network());
                 Ok(())
             }
             GetCommand::Signers => {
@@ -550,15 +1311,187 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 util::print_signers(signers);
                 Ok(())
             }
-            GetCommand::Relays => {
-                let relays = client.relays().await;
-                util::print_relays(relays).await;
+            GetCommand::SharedSigners { expired } => {
+                let shared_signers = if expired {
+                    client.get_expired_shared_signers().await?
+                } else {
+                    client.get_shared_signers().await?
+                };
+                util::print_shared_signers(shared_signers);
+                Ok(())
+            }
+            GetCommand::Relays { verbose, ping } => {
+                if verbose {
+                    let relays = client.relay_status().await;
+                    let mut pings = HashMap::new();
+                    if ping {
+                        for relay in relays.iter() {
+                            let res = client.ping_relay(relay.url.clone()).await;
+                            pings.insert(relay.url.clone(), res.map_err(|e| e.to_string()));
+                        }
+                    }
+                    util::print_relays_health(relays, pings);
+                } else {
+                    let relays = client.relays().await;
+                    let roles = client.relays_roles().await?;
+                    util::print_relays(relays, roles).await;
+                }
+                Ok(())
+            }
+            GetCommand::Tx { policy_id, txid } => {
+                let tx = client.get_tx_details(policy_id, txid).await?;
+                util::print_tx_details(tx);
+                Ok(())
+            }
+            GetCommand::Utxos { policy_id } => {
+                let utxos = client.get_utxos(policy_id).await?;
+                util::print_utxos(utxos, usize::MAX);
+                Ok(())
+            }
+            GetCommand::Addresses {
+                policy_id,
+                offset,
+                limit,
+                count,
+            } => {
+                let addresses: Vec<GetAddress> = match count {
+                    Some(count) => {
+                        client
+                            .get_addresses_range(policy_id, offset.try_into()?, count)
+                            .await?
+                    }
+                    None => client
+                        .get_addresses(policy_id)
+                        .await?
+                        .into_iter()
+                        .skip(offset)
+                        .take(limit.unwrap_or(usize::MAX))
+                        .collect(),
+                };
+                let boundary: u32 = client.gap_limit_boundary(policy_id).await?;
+                let beyond_gap: bool = addresses.iter().any(|a| a.index >= boundary);
+                util::print_addresses(addresses);
+                if beyond_gap {
+                    util::print_gap_limit_warning(boundary);
+                }
+                Ok(())
+            }
+            GetCommand::ScreeningStatus { address } => {
+                let address = address.require_network(client.network())?;
+                let result = client.screen_address(&address);
+                util::print_screening_status(&address, result);
+                Ok(())
+            }
+            GetCommand::Audit {
+                signer_id,
+                from,
+                to,
+            } => {
+                let entries = client
+                    .get_signing_audit(
+                        signer_id,
+                        from.map(Timestamp::from),
+                        to.map(Timestamp::from),
+                    )
+                    .await?;
+                util::print_signing_audit(entries);
+                Ok(())
+            }
+            GetCommand::FeesPaid {
+                policy_id,
+                from,
+                to,
+            } => {
+                let fees = client
+                    .get_fees_paid(
+                        policy_id,
+                        from.map(Timestamp::from),
+                        to.map(Timestamp::from),
+                    )
+                    .await?;
+                util::print_fees_paid(fees);
+                Ok(())
+            }
+            GetCommand::Outbox => {
+                let outbox = client.get_outbox().await?;
+                util::print_outbox(outbox);
+                Ok(())
+            }
+            GetCommand::EventDelivery { event_id } => {
+                let deliveries = client.get_event_delivery(event_id).await?;
+                util::print_event_deliveries(deliveries);
+                Ok(())
+            }
+            GetCommand::UnexpectedTxs { policy_id } => {
+                let txs = client.get_unexpected_txs(policy_id).await?;
+                util::print_unexpected_txs(txs);
+                Ok(())
+            }
+            GetCommand::Notifications {
+                unseen_only,
+                actions,
+            } => {
+                let notifications = client.get_notifications(unseen_only).await?;
+                util::print_notifications(notifications.clone());
+                let unseen = client.unseen_notifications_count().await?;
+                println!("Unseen: {unseen}");
+
+                if actions {
+                    let requests = client.get_nostr_connect_requests(false).await?;
+                    for notification in notifications
+                        .into_iter()
+                        .filter(|n| n.kind == NotificationKind::NostrConnectRequest && !n.seen)
+                    {
+                        let request_id: EventId = notification.event_id;
+                        let request = match requests.iter().find(|r| r.event_id == request_id) {
+                            Some(request) => request,
+                            None => continue,
+                        };
+                        println!(
+                            "\nRequest {request_id} from app {}: {}",
+                            request.app_public_key,
+                            request.message.to_request()?.method()
+                        );
+                        match io::get_input("Approve, reject or skip? [a/r/S]")?
+                            .trim()
+                            .to_lowercase()
+                            .as_str()
+                        {
+                            "a" | "approve" => {
+                                client.approve_nostr_connect_request(request_id).await?;
+                                println!("Request approved");
+                            }
+                            "r" | "reject" => {
+                                client.reject_nostr_connect_request(request_id).await?;
+                                println!("Request rejected");
+                            }
+                            _ => println!("Skipped"),
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            GetCommand::HwDevices => print_hw_devices(),
+            GetCommand::HwRegistrations => {
+                let registrations = client.get_hw_registrations().await?;
+                util::print_hw_registrations(registrations);
+                Ok(())
+            }
+            GetCommand::Identities => {
+                let identities = client.list_passphrase_identities().await?;
+                util::print_passphrase_identities(identities, client.fingerprint());
+                Ok(())
+            }
+            GetCommand::ProfileHistory => {
+                let history = client.get_profile_history().await?;
+                util::print_profile_history(history);
                 Ok(())
             }
-            GetCommand::Addresses { policy_id } => {
-                let addresses = client.get_addresses(policy_id).await?;
-                let balances = client.get_addresses_balances(policy_id).await?;
-                util::print_addresses(addresses, balances);
+            GetCommand::Fees => {
+                let estimates = client.estimate_fee_rates().await?;
+                let fee_targets = client.config().fee_targets().await;
+                util::print_fee_estimates(estimates, fee_targets);
                 Ok(())
             }
         },
@@ -568,13 +1501,17 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 display_name,
                 nip05,
                 empty,
+                dry_run,
             } => {
                 let mut metadata = Metadata::new();
                 metadata.name = name;
                 metadata.display_name = display_name;
                 metadata.nip05 = nip05;
 
-                if metadata != Metadata::default() || empty {
+                if dry_run {
+                    let changes = client.profile_diff(&metadata).await?;
+                    util::print_metadata_changes(changes);
+                } else if metadata != Metadata::default() || empty {
                     client.set_metadata(&metadata).await?;
                 } else {
                     println!("No metadata passed with args! If you want to set empty metadata, use --empty flag");
@@ -582,6 +1519,11 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
 
                 Ok(())
             }
+            SetCommand::RevertProfile { version } => {
+                client.revert_profile(version).await?;
+                println!("Profile version {version} published");
+                Ok(())
+            }
             SetCommand::Label {
                 policy_id,
                 data,
@@ -592,13 +1534,94 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 println!("Label saved at event {event_id}");
                 Ok(())
             }
+            SetCommand::CoinSelection {
+                policy_id,
+                strategy,
+            } => {
+                client
+                    .set_default_coin_selection(policy_id, strategy)
+                    .await?;
+                println!("Default coin selection strategy of policy {policy_id} set to {strategy}");
+                Ok(())
+            }
+            SetCommand::Reminders {
+                policy_id,
+                every,
+                max,
+                disable,
+            } => {
+                let settings: Option<ReminderSettings> = match every {
+                    Some(hours) if !disable => Some(ReminderSettings {
+                        interval: Duration::from_secs(hours * 60 * 60),
+                        max_reminders: max,
+                    }),
+                    _ => None,
+                };
+                client.set_reminder_settings(policy_id, settings).await?;
+                match settings {
+                    Some(..) => println!(
+                        "Pending approvers of policy {policy_id} reminded every {}h (max {max} times)",
+                        every.unwrap_or_default()
+                    ),
+                    None => println!("Approval reminders of policy {policy_id} disabled"),
+                }
+                Ok(())
+            }
+            SetCommand::MuteReminders {
+                proposal_id,
+                unmute,
+            } => {
+                client.mute_reminders(proposal_id, !unmute).await?;
+                Ok(())
+            }
+            SetCommand::NotificationSeen { event_id, all } => {
+                match event_id {
+                    Some(event_id) if !all => client.mark_notification_seen(event_id).await?,
+                    _ => client.mark_all_notifications_seen().await?,
+                }
+                Ok(())
+            }
+            SetCommand::RelayRole {
+                url,
+                read_only,
+                write_only,
+            } => {
+                let role: RelayRole = util::relay_role(read_only, write_only);
+                client.set_relay_role(url.clone(), role).await?;
+                println!("Relay {url} role set to {role}");
+                Ok(())
+            }
+            SetCommand::RelayProxy { url, proxy } => {
+                client.set_relay_proxy(url.clone(), proxy.0).await?;
+                println!("Relay {url} proxy set to {proxy}");
+                Ok(())
+            }
+            SetCommand::Birthday { policy_id, height } => {
+                client.set_policy_birthday(policy_id, height).await?;
+                println!("Policy birthday updated: re-syncing");
+                Ok(())
+            }
+            SetCommand::SharedSignerScope {
+                shared_signer_id,
+                scope,
+            } => {
+                let new_id = client
+                    .update_shared_signer_scope(shared_signer_id, scope.into_scope())
+                    .await?;
+                println!("Shared signer scope updated");
+                println!("Shared Signer ID: {new_id}");
+                Ok(())
+            }
         },
         Command::Share { command } => match command {
             ShareCommand::Signer {
                 signer_id,
                 public_key,
+                scope,
             } => {
-                let shared_signer_id = client.share_signer(signer_id, public_key).await?;
+                let shared_signer_id = client
+                    .share_signer_with_scope(signer_id, public_key, scope.into_scope())
+                    .await?;
                 println!(
                     "Signer {} shared with {}",
                     smartvaults_sdk::util::cut_event_id(signer_id),
@@ -621,7 +1644,19 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 completed,
             } => {
                 if completed {
-                    Ok(client.delete_completed_proposal_by_id(proposal_id).await?)
+                    let impact = client
+                        .completed_proposal_deletion_impact(proposal_id)
+                        .await?;
+                    let mut delete_dependents: bool = false;
+                    if impact.has_dependents() {
+                        util::print_deletion_impact(&impact);
+                        delete_dependents =
+                            io::ask("Delete also the labels and links of this proposal?")?;
+                    }
+                    client
+                        .delete_completed_proposal_by_id(proposal_id, delete_dependents)
+                        .await?;
+                    Ok(())
                 } else {
                     Ok(client.delete_proposal_by_id(proposal_id).await?)
                 }
@@ -630,12 +1665,31 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 client.revoke_approval(approval_id).await?;
                 Ok(())
             }
-            DeleteCommand::Signer { signer_id } => {
-                Ok(client.delete_signer_by_id(signer_id).await?)
+            DeleteCommand::Signer { signer_id, force } => {
+                let res = client.delete_signer_by_id(signer_id, force).await;
+                if let Err(Error::SignerInUse(policies)) = &res {
+                    util::print_policies_in_use(client, policies).await;
+                }
+                Ok(res?)
             }
-            DeleteCommand::SharedSigner { shared_signer_id } => {
-                Ok(client.revoke_shared_signer(shared_signer_id).await?)
+            DeleteCommand::SharedSigner {
+                shared_signer_id,
+                force,
+            } => {
+                let res = client.revoke_shared_signer(shared_signer_id, force).await;
+                if let Err(Error::SharedSignerInUse(policies)) = &res {
+                    util::print_policies_in_use(client, policies).await;
+                }
+                Ok(res?)
             }
+            DeleteCommand::Notifications { .. } => Ok(client.delete_all_notifications().await?),
+            DeleteCommand::HwRegistration {
+                policy_id,
+                fingerprint,
+                device_id,
+            } => Ok(client
+                .delete_hw_registration(policy_id, fingerprint, device_id)
+                .await?),
             DeleteCommand::Cache => Ok(client.clear_cache().await?),
         },
         Command::Setting { command } => match command {
@@ -645,7 +1699,235 @@ async fn handle_command(command: Command, client: &SmartVaults) -> Result<()> {
                 io::get_new_password,
                 io::get_confirmation_password,
             )?),
+            SettingCommand::VerifySeed => {
+                let password: String = io::get_password()?;
+                let positions: Vec<usize> = client.mnemonic_challenge(&password, 3)?;
+                let mut words: Vec<(usize, String)> = Vec::with_capacity(positions.len());
+                for position in positions.into_iter() {
+                    words.push((position, io::get_input(format!("Word #{position}"))?));
+                }
+                let passphrase: Option<String> = if io::ask("Do you use a passphrase?")? {
+                    Some(io::get_input("Passphrase")?)
+                } else {
+                    None
+                };
+                client.verify_mnemonic_words(password, &words, passphrase.as_deref())?;
+                println!("Mnemonic verified");
+                Ok(())
+            }
+            SettingCommand::Passphrase => {
+                let password: String = io::get_password()?;
+                let passphrase: Option<String> = if io::ask("Do you want to use a passphrase?")? {
+                    Some(io::get_input("Passphrase")?)
+                } else {
+                    None
+                };
+                *client = client
+                    .clone()
+                    .apply_passphrase(password, passphrase)
+                    .await?;
+                println!("Switched to identity {}", client.fingerprint());
+                Ok(())
+            }
+            SettingCommand::Export => {
+                println!("Choose a password to encrypt the settings");
+                let password: String = io::get_new_password()?;
+                let payload: String = client
+                    .export_settings_qr(password, DisplayPreferences::default())
+                    .await?;
+                println!("\n{payload}\n");
+                Ok(())
+            }
+            SettingCommand::Import { payload } => {
+                println!("Enter the password used to export the settings");
+                let password: String = io::get_password()?;
+                client.import_settings_qr(payload, password).await?;
+                println!("Settings imported");
+                Ok(())
+            }
+            SettingCommand::SyncProfile { profile } => {
+                Ok(client.set_sync_profile(profile.into()).await?)
+            }
+            SettingCommand::MainnetGuard { disable } => {
+                Ok(client.set_mainnet_guard(!disable).await?)
+            }
         },
+        Command::RetryEvent { event_id } => {
+            if client.retry_event(event_id).await? {
+                println!("Event {event_id} handled");
+            } else {
+                println!("Event {event_id} still pending");
+            }
+            Ok(())
+        }
         Command::Exit => std::process::exit(0x01),
     }
 }
+
+#[cfg(feature = "hwi")]
+fn print_hw_devices() -> Result<()> {
+    let devices = SmartVaults::list_hw_devices()?;
+    util::print_hw_devices(&devices);
+    Ok(())
+}
+
+#[cfg(not(feature = "hwi"))]
+fn print_hw_devices() -> Result<()> {
+    Err("hardware devices not supported: build with the `hwi` feature".into())
+}
+
+/// Build the preview of a spend, asking the policy path if required
+#[allow(clippy::too_many_arguments)]
+async fn build_spend_preview(
+    client: &SmartVaults,
+    policy_id: EventId,
+    to_address: Address<NetworkUnchecked>,
+    amount: Amount,
+    description: &str,
+    fee_rate: FeeRate,
+    utxos: Option<Vec<OutPoint>>,
+    policy_path: Option<BTreeMap<String, Vec<usize>>>,
+    coin_selection: CoinSelection,
+) -> Result<SpendPreview> {
+    let res = client
+        .build_spend_preview(
+            policy_id,
+            to_address.clone(),
+            amount,
+            description,
+            fee_rate,
+            utxos.clone(),
+            policy_path,
+            false,
+            Some(coin_selection),
+        )
+        .await;
+    match res {
+        Err(Error::PolicyPathRequired(conditions)) => {
+            let GetPolicy { policy, .. } = client.get_policy_by_id(policy_id).await?;
+            let policy_path = select_policy_path(&policy, conditions)?;
+            Ok(client
+                .build_spend_preview(
+                    policy_id,
+                    to_address,
+                    amount,
+                    description,
+                    fee_rate,
+                    utxos,
+                    Some(policy_path),
+                    false,
+                    Some(coin_selection),
+                )
+                .await?)
+        }
+        res => Ok(res?),
+    }
+}
+
+/// Ask to type the mainnet confirmation before publishing a spend, if the guard is enabled
+async fn confirm_spend(client: &SmartVaults) -> Result<bool> {
+    if client.mainnet_guard().await? && !io::confirm_mainnet("spend")? {
+        println!("Aborted");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn policy_path_from_args(paths: Vec<CliPolicyPath>) -> Option<BTreeMap<String, Vec<usize>>> {
+    if paths.is_empty() {
+        None
+    } else {
+        Some(paths.into_iter().map(|p| (p.path, p.indexes)).collect())
+    }
+}
+
+/// Ask which sub-paths to use for each selectable condition
+///
+/// Conditions of not selected branches are skipped.
+fn select_policy_path(
+    policy: &Policy,
+    conditions: Vec<SelectableCondition>,
+) -> Result<BTreeMap<String, Vec<usize>>> {
+    let mut policy_path: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    let mut sub_paths_seen: HashSet<String> = HashSet::new();
+    let mut sub_paths_selected: HashSet<String> = HashSet::new();
+
+    for SelectableCondition {
+        path,
+        thresh,
+        sub_paths,
+    } in conditions.into_iter()
+    {
+        if sub_paths_seen.contains(&path) && !sub_paths_selected.contains(&path) {
+            continue;
+        }
+
+        println!("Select {thresh} of:");
+        for (index, sub_path) in sub_paths.iter().enumerate() {
+            let description: String = policy
+                .describe_path(sub_path)?
+                .unwrap_or_else(|| sub_path.clone());
+            println!("  {index}) {description}");
+        }
+        let indexes: Vec<usize> = parse_indexes(&io::get_input(format!("Sub-paths of {path}"))?)?;
+        for index in indexes.iter() {
+            if let Some(sub_path) = sub_paths.get(*index) {
+                sub_paths_selected.insert(sub_path.clone());
+            }
+        }
+        sub_paths_seen.extend(sub_paths);
+        policy_path.insert(path, indexes);
+    }
+
+    policy.validate_policy_path(&policy_path)?;
+    Ok(policy_path)
+}
+
+#[cfg(feature = "hwi")]
+fn select_hw_device() -> Result<HWIDevice> {
+    let devices = SmartVaults::list_hw_devices()?;
+    if devices.is_empty() {
+        return Err("no hardware device found: connect and unlock it, then retry".into());
+    }
+    util::print_hw_devices(&devices);
+
+    let index: usize = io::get_input("Device #")?.trim().parse()?;
+    match devices.into_iter().nth(index.saturating_sub(1)) {
+        Some(Ok(device)) => Ok(device),
+        Some(Err(e)) => Err(e.into()),
+        None => Err(format!("device #{index} not found").into()),
+    }
+}
+
+#[cfg(feature = "hwi")]
+async fn add_hw_signer(client: &SmartVaults, name: String) -> Result<EventId> {
+    let device = select_hw_device()?;
+    let fingerprint = device.fingerprint;
+
+    let signer_id = client.save_hw_signer(name, device).await?;
+    println!("Hardware signer saved: {signer_id} (fingerprint {fingerprint})");
+    Ok(signer_id)
+}
+
+#[cfg(not(feature = "hwi"))]
+async fn add_hw_signer(_client: &SmartVaults, _name: String) -> Result<EventId> {
+    Err("hardware devices not supported: build with the `hwi` feature".into())
+}
+
+#[cfg(feature = "hwi")]
+async fn approve_with_hw(
+    client: &SmartVaults,
+    proposal_id: EventId,
+) -> Result<(EventId, ApprovedProposal)> {
+    let device = select_hw_device()?;
+    println!("Confirm the transaction on the device");
+    Ok(client.approve_with_hwi(proposal_id, device).await?)
+}
+
+#[cfg(not(feature = "hwi"))]
+async fn approve_with_hw(
+    _client: &SmartVaults,
+    _proposal_id: EventId,
+) -> Result<(EventId, ApprovedProposal)> {
+    Err("hardware devices not supported: build with the `hwi` feature".into())
+}
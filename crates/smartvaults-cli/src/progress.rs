@@ -0,0 +1,76 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::future::Future;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use smartvaults_sdk::util::progress::{CancellationToken, Progress, ProgressCallback};
+use smartvaults_sdk::Error;
+
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const TICK: Duration = Duration::from_millis(120);
+
+fn render(label: &str, frame: usize, progress: Option<Progress>, cancelling: bool) {
+    let spinner: char = SPINNER[frame % SPINNER.len()];
+    let status: String = if cancelling {
+        String::from("cancelling...")
+    } else {
+        match progress {
+            Some(progress) => progress.to_string(),
+            None => String::new(),
+        }
+    };
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "\r\x1b[2K{spinner} {label} {status}");
+    let _ = stderr.flush();
+}
+
+fn clear() {
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "\r\x1b[2K");
+    let _ = stderr.flush();
+}
+
+/// Run a long-running operation rendering a spinner/percentage line
+///
+/// Ctrl-C cancels only the running operation (that returns [`Error::Cancelled`]),
+/// not the whole session.
+pub async fn run<F, Fut, T>(label: &str, operation: F) -> Result<T, Error>
+where
+    F: FnOnce(Option<ProgressCallback>, CancellationToken) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let last: Arc<Mutex<Option<Progress>>> = Arc::new(Mutex::new(None));
+    let callback: ProgressCallback = {
+        let last = last.clone();
+        Arc::new(move |progress: Progress| {
+            if let Ok(mut last) = last.lock() {
+                *last = Some(progress);
+            }
+        })
+    };
+    let token = CancellationToken::new();
+
+    let operation = operation(Some(callback), token.clone());
+    tokio::pin!(operation);
+
+    let mut interval = tokio::time::interval(TICK);
+    let mut frame: usize = 0;
+    let res = loop {
+        tokio::select! {
+            res = &mut operation => break res,
+            _ = interval.tick() => {
+                let progress: Option<Progress> = last.lock().ok().and_then(|p| *p);
+                render(label, frame, progress, token.is_cancelled());
+                frame += 1;
+            }
+            // Wait the operation to stop at the next checkpoint
+            Ok(()) = tokio::signal::ctrl_c(), if !token.is_cancelled() => token.cancel(),
+        }
+    };
+
+    clear();
+    res
+}
@@ -2,26 +2,45 @@
 // Distributed under the MIT software license
 
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
 
 use owo_colors::colors::css::Lime;
 use owo_colors::colors::xterm::{BlazeOrange, BrightElectricViolet, Pistachio};
 use owo_colors::colors::{BrightCyan, Magenta};
 use owo_colors::OwoColorize;
 use prettytable::{row, Table};
+use smartvaults_sdk::changelog::Release;
+use smartvaults_sdk::config::{Backend, Config, FeeTargets, SyncProfile};
 use smartvaults_sdk::core::bdk::chain::ConfirmationTime;
 use smartvaults_sdk::core::bdk::descriptor::policy::{PkOrF, SatisfiableItem};
 use smartvaults_sdk::core::bips::bip32::Bip32;
-use smartvaults_sdk::core::bitcoin::bip32::ExtendedPubKey;
-use smartvaults_sdk::core::bitcoin::{Network, ScriptBuf};
+use smartvaults_sdk::core::bitcoin::bip32::{ExtendedPubKey, Fingerprint};
+use smartvaults_sdk::core::bitcoin::{Address, Network, ScriptBuf, Txid};
+#[cfg(feature = "hwi")]
+use smartvaults_sdk::core::hwi::types::HWIDevice;
+use smartvaults_sdk::core::policy::{LimitExceeded, PolicyAnalysis};
 use smartvaults_sdk::core::proposal::{CompletedProposal, Proposal};
-use smartvaults_sdk::core::{Keychain, Purpose, Result, SECP256K1};
+use smartvaults_sdk::core::{
+    CoinSelection, FeeWarning, Keychain, OutputKind, OutputVerification, Priority, Purpose, Result,
+    SECP256K1,
+};
+#[cfg(feature = "hwi")]
+use smartvaults_sdk::hw;
 use smartvaults_sdk::nostr::prelude::{FromMnemonic, NostrConnectURI, ToBech32};
-use smartvaults_sdk::nostr::{EventId, Keys, Profile, PublicKey, Relay, Timestamp, Url};
+use smartvaults_sdk::nostr::{EventId, Keys, PublicKey, Relay, Timestamp, Url};
+use smartvaults_sdk::price::FiatAmount;
+use smartvaults_sdk::screening::ScreeningResult;
 use smartvaults_sdk::types::{
-    GetAddress, GetCompletedProposal, GetPolicy, GetProposal, GetSigner, GetSignerOffering,
-    GetTransaction, GetUtxo, NostrConnectRequest,
+    ApproverStats, DeletionImpact, EventDelivery, FeeEstimates, FeesPaid, GetAddress, GetApproval,
+    GetCompletedProposal, GetContact, GetNotificationsResult, GetPolicy, GetProposal,
+    GetSharedSigner, GetSigner, GetSignerOffering, GetTransaction, GetUtxo, HwRegistration,
+    MetadataChange, NostrConnectRequest, NotificationKind, OutboxEvent, PassphraseIdentity,
+    PendingEventRetry, PolicyCompatibility, PolicyKeyReport, PolicyParticipant, ProfileVersion,
+    ProofVerification, QuarantinedEvent, RelayHealth, RelayRole, SigningAuditEntry, SpendPreview,
+    TxDetails, UnexpectedTx, UserRole,
 };
 use smartvaults_sdk::util::{self, format};
+use smartvaults_sdk::SmartVaults;
 use termtree::Tree;
 
 pub fn print_secrets(keychain: Keychain, network: Network) -> Result<()> {
@@ -65,7 +84,7 @@ pub fn print_secrets(keychain: Keychain, network: Network) -> Result<()> {
     Ok(())
 }
 
-pub fn print_contacts(contacts: BTreeSet<Profile>) {
+pub fn print_contacts(contacts: BTreeSet<GetContact>) {
     let mut table = Table::new();
 
     table.set_titles(row![
@@ -74,16 +93,64 @@ pub fn print_contacts(contacts: BTreeSet<Profile>) {
         "Username",
         "Display name",
         "NIP-05",
+        "Role",
+        "Updated",
     ]);
 
-    for (index, user) in contacts.into_iter().enumerate() {
-        let metadata = user.metadata();
+    let mut stale: usize = 0;
+    for (index, contact) in contacts.into_iter().enumerate() {
+        let metadata = contact.profile.metadata();
+        let updated: String = match contact.updated_at {
+            Some(updated_at) => format::relative_datetime(updated_at),
+            None => String::from("never"),
+        };
+        let updated: String = if contact.is_stale() {
+            stale += 1;
+            format!("{updated} (stale)")
+        } else {
+            updated
+        };
+        let role: String = match contact.role {
+            UserRole::KeyAgent if contact.verified => String::from("key agent (verified)"),
+            UserRole::KeyAgent => String::from("key agent (self-declared)"),
+            UserRole::User => String::from("user"),
+        };
         table.add_row(row![
             index + 1,
-            user.public_key(),
+            util::display_public_key(contact.profile.public_key()),
             metadata.name.unwrap_or_default(),
             metadata.display_name.unwrap_or_default(),
-            metadata.nip05.unwrap_or_default()
+            metadata.nip05.unwrap_or_default(),
+            role,
+            updated
+        ]);
+    }
+
+    table.printstd();
+
+    if stale > 0 {
+        println!("{stale} contacts with stale metadata: refresh requested in background");
+    }
+}
+
+pub fn print_policy_participants(participants: &[PolicyParticipant]) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Public key", "Name", "Note"]);
+
+    for (index, participant) in participants.iter().enumerate() {
+        let note: String = if participant.is_me {
+            String::from("me")
+        } else if !participant.is_contact {
+            format!("{}", "not in contacts".fg::<BlazeOrange>())
+        } else {
+            String::new()
+        };
+        table.add_row(row![
+            index + 1,
+            util::display_public_key(participant.public_key()),
+            participant.name(),
+            note
         ]);
     }
 
@@ -97,11 +164,23 @@ pub fn print_policy(
     address: GetAddress,
     txs: BTreeSet<GetTransaction>,
     utxos: Vec<GetUtxo>,
+    fiat: Option<FiatAmount>,
 ) {
     println!("{}", "\nPolicy".fg::<BlazeOrange>().underline());
     println!("- ID: {policy_id}");
     println!("- Name: {}", policy.name());
     println!("- Description: {}", policy.description());
+    if let Ok(script_type) = policy.script_type() {
+        println!("- Script type: {script_type}");
+    }
+    println!(
+        "- Change keychain: {}",
+        if policy.has_change_descriptor() {
+            "separate"
+        } else {
+            "same as receiving"
+        }
+    );
 
     let mut tree: Tree<String> = Tree::new("- Descriptor".to_string());
     tree.push(add_node(&item));
@@ -124,6 +203,12 @@ pub fn print_policy(
         "- Confirmed           	: {} sat",
         format::number(policy.balance.confirmed)
     );
+    if let Some(fiat) = fiat {
+        println!(
+            "- Total               	: {fiat} (1 BTC = {:.2} {})",
+            fiat.price, fiat.currency
+        );
+    }
 
     println!(
         "\n{}: {}\n",
@@ -160,7 +245,17 @@ pub fn print_txs(txs: BTreeSet<GetTransaction>, limit: usize) {
         "Date/Time"
     ]);
 
-    for (index, GetTransaction { tx, label, .. }) in txs.into_iter().take(limit).enumerate() {
+    for (
+        index,
+        GetTransaction {
+            tx,
+            label,
+            screening,
+            unexpected,
+            ..
+        },
+    ) in txs.into_iter().take(limit).enumerate()
+    {
         let (total, positive): (u64, bool) = {
             let received: i64 = tx.received as i64;
             let sent: i64 = tx.sent as i64;
@@ -178,10 +273,27 @@ pub fn print_txs(txs: BTreeSet<GetTransaction>, limit: usize) {
                 if positive { "+" } else { "-" },
                 format::number(total)
             ),
-            label.unwrap_or_else(|| String::from("-")),
+            if unexpected {
+                format!(
+                    "{} {}",
+                    label.unwrap_or_default(),
+                    "UNEXPECTED".red().bold()
+                )
+                .trim()
+                .to_string()
+            } else if screening.is_flagged() {
+                format!(
+                    "{} {}",
+                    label.unwrap_or_default(),
+                    screening.to_string().red().bold()
+                )
+                .trim()
+                .to_string()
+            } else {
+                label.unwrap_or_else(|| String::from("-"))
+            },
             match tx.confirmation_time {
-                ConfirmationTime::Confirmed { time, .. } =>
-                    Timestamp::from(time).to_human_datetime(),
+                ConfirmationTime::Confirmed { time, .. } => format::datetime(Timestamp::from(time)),
                 ConfirmationTime::Unconfirmed { .. } => String::from("Pending"),
             }
         ]);
@@ -190,6 +302,17 @@ pub fn print_txs(txs: BTreeSet<GetTransaction>, limit: usize) {
     table.printstd();
 }
 
+/// Print the names of the policies blocking a signer deletion/revocation
+pub async fn print_policies_in_use(client: &SmartVaults, policies: &[EventId]) {
+    println!("{}", "Used by policies:".fg::<BlazeOrange>());
+    for policy_id in policies.iter() {
+        match client.get_policy_by_id(*policy_id).await {
+            Ok(policy) => println!("- {} ({policy_id})", policy.name()),
+            Err(_) => println!("- {policy_id}"),
+        }
+    }
+}
+
 pub fn print_utxos(utxos: Vec<GetUtxo>, limit: usize) {
     let mut table = Table::new();
 
@@ -227,6 +350,85 @@ pub fn print_utxos(utxos: Vec<GetUtxo>, limit: usize) {
     table.printstd();
 }
 
+pub fn print_tx_details(tx: TxDetails) {
+    println!();
+    println!("- Txid: {}", tx.txid);
+    match tx.confirmation_time {
+        ConfirmationTime::Confirmed { height, time } => {
+            println!("- Block height: {}", format::number(height as u64));
+            println!(
+                "- Confirmed at: {}",
+                format::datetime(Timestamp::from(time))
+            );
+        }
+        ConfirmationTime::Unconfirmed { last_seen } => {
+            println!("- Status: {}", "Pending (mempool)".yellow());
+            if last_seen > 0 {
+                println!(
+                    "- Last seen: {}",
+                    format::relative_datetime(Timestamp::from(last_seen))
+                );
+            }
+        }
+    }
+    match tx.fee {
+        Some(fee) => println!("- Fee: {} sat", format::number(fee)),
+        None => println!("- Fee: unknown"),
+    }
+    if let Some(rate) = tx.fee_rate {
+        println!("- Fee rate: {:.2} sat/vB", rate.as_sat_per_vb());
+    }
+
+    let mut table = Table::new();
+    table.set_titles(row!["#", "Previous output", "Value", "Mine"]);
+    for (index, input) in tx.inputs.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            input.previous_output.to_string(),
+            match input.value {
+                Some(value) => format!("{} sat", format::number(value)),
+                None => String::from("-"),
+            },
+            input.is_mine
+        ]);
+    }
+    println!();
+    println!("{}", "Inputs".fg::<BlazeOrange>().underline());
+    table.printstd();
+
+    let mut table = Table::new();
+    table.set_titles(row!["#", "Address", "Value", "Mine", "Change"]);
+    for (index, output) in tx.outputs.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            output
+                .address
+                .map(|a| a.assume_checked().to_string())
+                .unwrap_or_else(|| String::from("-")),
+            format!("{} sat", format::number(output.value)),
+            output.is_mine,
+            output.is_change
+        ]);
+    }
+    println!();
+    println!("{}", "Outputs".fg::<BlazeOrange>().underline());
+    table.printstd();
+
+    if !tx.labels.is_empty() {
+        println!();
+        println!("- Labels:");
+        for label in tx.labels.values() {
+            println!("  - {} label: {}", label.kind(), label.text());
+        }
+    }
+
+    if let Some(url) = tx.block_explorer {
+        println!();
+        println!("- Block explorer: {url}");
+    }
+    println!();
+}
+
 fn display_key(key: &PkOrF) -> String {
     match key {
         PkOrF::Pubkey(pk) => format!("<pk:{pk}>"),
@@ -312,6 +514,71 @@ fn add_node(item: &SatisfiableItem) -> Tree<String> {
     si_tree
 }
 
+pub fn print_policy_analysis(analysis: PolicyAnalysis) {
+    println!("{}", "\nDescriptor".fg::<BlazeOrange>().underline());
+    println!("{}", analysis.descriptor);
+    println!(
+        "- Template: {}",
+        analysis
+            .template
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| String::from("custom"))
+    );
+
+    if let Some(item) = &analysis.tree {
+        let mut tree: Tree<String> = Tree::new("- Threshold structure".to_string());
+        tree.push(add_node(item));
+        println!("{tree}");
+    }
+
+    println!("{}", "Keys".fg::<BlazeOrange>().underline());
+    let mut table = Table::new();
+    table.set_titles(row!["#", "Fingerprint", "Origin", "Key"]);
+    for (index, key) in analysis.keys.iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            key.fingerprint,
+            key.origin
+                .as_ref()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| String::from("-")),
+            key.key
+        ]);
+    }
+    table.printstd();
+
+    println!("{}", "\nTimelocks".fg::<BlazeOrange>().underline());
+    if analysis.timelocks.is_empty() {
+        println!("- None");
+    }
+    for timelock in analysis.timelocks.iter() {
+        println!("- {timelock}");
+    }
+
+    println!("{}", "\nSpending paths".fg::<BlazeOrange>().underline());
+    let mut table = Table::new();
+    table.set_titles(row!["#", "Path", "Max satisfaction weight (WU)"]);
+    for (index, path) in analysis.spending_paths.iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            path.description,
+            path.max_satisfaction_weight
+                .map(|w| format::number(w as u64))
+                .unwrap_or_else(|| String::from("-"))
+        ]);
+    }
+    table.printstd();
+
+    if analysis.has_problems() {
+        println!("{}", "\nProblems".fg::<BlazeOrange>().underline());
+        for problem in analysis.problems.iter() {
+            println!("- ⚠️ {problem}");
+        }
+    } else {
+        println!("\nNo problems found");
+    }
+}
+
 pub fn print_policies(policies: Vec<GetPolicy>) {
     let mut table = Table::new();
 
@@ -341,11 +608,18 @@ pub fn print_proposal(proposal: GetProposal) {
         policy_id,
         proposal,
         signed,
+        fee_warning,
         ..
     } = proposal;
     println!();
+    if let Some(warning) = fee_warning {
+        print_fee_warning(warning);
+    }
     println!("- Proposal id: {proposal_id}");
     println!("- Policy id: {policy_id}");
+    if let Ok(fee) = proposal.fee() {
+        println!("- Fee: {} sat", format::number(fee));
+    }
     match proposal {
         Proposal::Spending {
             to_address,
@@ -363,12 +637,14 @@ pub fn print_proposal(proposal: GetProposal) {
             signer_descriptor,
             amount,
             description,
+            period,
             ..
         } => {
             println!("- Type: key-agent-payment");
             println!("- Description: {description}");
             println!("- Signer: {signer_descriptor}");
             println!("- Amount: {amount}");
+            println!("- Period: {}", format::period(period.from, period.to));
             println!("- Signed: {signed}");
         }
         Proposal::ProofOfReserve { message, .. } => {
@@ -379,6 +655,330 @@ pub fn print_proposal(proposal: GetProposal) {
     println!();
 }
 
+pub fn print_completed_proposal(completed_proposal: CompletedProposal, network: Network) {
+    let explorer = |txid: Txid| match network {
+        Network::Bitcoin => println!("\nExplorer: https://blockstream.info/tx/{txid} \n"),
+        Network::Testnet => println!("\nExplorer: https://blockstream.info/testnet/tx/{txid} \n"),
+        _ => (),
+    };
+
+    match completed_proposal {
+        CompletedProposal::Spending { tx, .. } => {
+            let txid = tx.txid();
+            println!("Transaction {txid} broadcasted");
+            explorer(txid);
+        }
+        CompletedProposal::KeyAgentPayment { tx, .. } => {
+            let txid = tx.txid();
+            println!("Key agent payment broadcasted: {txid}");
+            explorer(txid);
+        }
+        CompletedProposal::ProofOfReserve { .. } => println!("Proof of Reserve finalized"),
+    }
+}
+
+pub fn print_proposal_outputs(
+    outputs: Vec<OutputVerification>,
+    screening: Vec<(ScriptBuf, ScreeningResult)>,
+    network: Network,
+) {
+    let screening: HashMap<ScriptBuf, ScreeningResult> = screening.into_iter().collect();
+    let mut table = Table::new();
+
+    table.set_titles(row!["Vout", "Address", "Value", "Kind", "Screening"]);
+
+    for output in outputs.into_iter() {
+        let address: String = Address::from_script(&output.script_pubkey, network)
+            .map(|a| a.to_string())
+            .unwrap_or_else(|_| output.script_pubkey.to_string());
+        let kind: String = match output.kind {
+            OutputKind::Unverified => output.kind.to_string().red().bold().to_string(),
+            kind => kind.to_string(),
+        };
+        let screening: String = match screening.get(&output.script_pubkey) {
+            Some(result) if result.is_flagged() => result.to_string().red().bold().to_string(),
+            Some(result) => result.to_string(),
+            None => String::from("-"),
+        };
+        table.add_row(row![
+            output.vout,
+            address,
+            format!("{} sat", format::number(output.value)),
+            kind,
+            screening
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_approver_stats(stats: Vec<ApproverStats>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "Public key",
+        "Approved",
+        "Approval rate",
+        "Median time to approve",
+        "Finalized without"
+    ]);
+
+    for s in stats.into_iter() {
+        table.add_row(row![
+            util::cut_public_key(s.public_key),
+            format!("{}/{}", s.approved, s.proposals),
+            match s.approval_rate() {
+                Some(rate) => format!("{:.0}%", rate * 100.0),
+                None => String::from("-"),
+            },
+            match s.median_approval_time {
+                Some(secs) => format::duration(secs),
+                None => String::from("-"),
+            },
+            s.finalized_without
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_policy_key_report(report: Vec<PolicyKeyReport>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Fingerprint",
+        "Derivation path",
+        "Purpose",
+        "Owner",
+        "Signer match",
+        "Device",
+        "Ours"
+    ]);
+
+    let mut unverifiable: usize = 0;
+    for (index, key) in report.into_iter().enumerate() {
+        let origin = &key.origin;
+        let fingerprint: String = match origin.fingerprint {
+            Some(fingerprint) => fingerprint.to_string(),
+            None if origin.internal => String::from("internal key"),
+            None => {
+                unverifiable += 1;
+                "unverifiable".red().to_string()
+            }
+        };
+        let mut purpose: String = origin
+            .purpose
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| String::from("-"));
+        if origin.network_mismatch {
+            purpose.push_str(&" (wrong network)".red().to_string());
+        }
+        let signer_match: String = match (key.signer_id, key.matched_purpose) {
+            (Some(_), Some(purpose)) => format!("{purpose} descriptor"),
+            (Some(_), None) => "fingerprint only".yellow().to_string(),
+            (None, _) => String::from("-"),
+        };
+        table.add_row(row![
+            index + 1,
+            fingerprint,
+            origin
+                .derivation_path
+                .as_ref()
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| String::from("-")),
+            purpose,
+            key.owner.unwrap_or_else(|| String::from("-")),
+            signer_match,
+            key.device.unwrap_or_else(|| String::from("-")),
+            if key.ours { "yes" } else { "no" }
+        ]);
+    }
+
+    table.printstd();
+
+    if unverifiable > 0 {
+        println!();
+        println!("{unverifiable} key(s) without origin info: can't be matched to any signer");
+    }
+}
+
+pub fn print_policy_compatibility(report: PolicyCompatibility) {
+    println!();
+    println!(
+        "Protocol version: v{}",
+        report.protocol_version.fg::<BlazeOrange>()
+    );
+    println!();
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["Public key", "Versions", "Last seen", "Status"]);
+
+    for member in report.members.iter() {
+        table.add_row(row![
+            util::cut_public_key(member.public_key),
+            match &member.capabilities {
+                Some(c) => c
+                    .versions
+                    .iter()
+                    .map(|v| format!("v{v}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => String::from("v1 (legacy)"),
+            },
+            match member.last_seen {
+                Some(timestamp) => format::relative_datetime(timestamp),
+                None => String::from("-"),
+            },
+            if member.is_outdated() {
+                "Upgrade needed".red().to_string()
+            } else {
+                String::from("Up to date")
+            }
+        ]);
+    }
+
+    table.printstd();
+
+    let outdated = report.outdated_members();
+    if !outdated.is_empty() {
+        println!();
+        println!(
+            "{} member(s) stuck on v{}: ask them to upgrade to the latest Smart Vaults release",
+            outdated.len(),
+            report.protocol_version
+        );
+    }
+}
+
+pub fn print_screening_status(address: &Address, result: ScreeningResult) {
+    if result.is_flagged() {
+        println!("{address}: {}", result.to_string().red().bold());
+    } else {
+        println!("{address}: {result}");
+    }
+}
+
+pub fn print_deletion_impact(impact: &DeletionImpact) {
+    println!();
+    println!("- Completed proposal id: {}", impact.completed_proposal_id);
+    if let Some(txid) = impact.txid {
+        println!("- Txid: {txid}");
+    }
+    println!("- Labels: {}", impact.labels.len());
+    for label in impact.labels.values() {
+        println!("  - {} label: {}", label.kind(), label.text());
+    }
+    println!("- Fee bump links: {}", impact.superseded_links);
+    println!(
+        "- Signing audit entries: {} (always retained)",
+        impact.audit_entries
+    );
+    println!();
+}
+
+pub fn print_fee_estimates(estimates: FeeEstimates, fee_targets: FeeTargets) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["Estimate", "Target blocks", "Fee rate"]);
+
+    let rates = [
+        ("Fastest", estimates.fastest),
+        ("Half hour", estimates.half_hour),
+        ("Hour", estimates.hour),
+        ("Economy", estimates.economy),
+    ];
+    for ((name, rate), target_blocks) in rates.into_iter().zip(FeeEstimates::TARGET_BLOCKS) {
+        table.add_row(row![name, target_blocks, format!("{rate:.2} sat/vB")]);
+    }
+
+    table.printstd();
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["Priority", "Target blocks", "Fee rate"]);
+
+    for priority in [Priority::High, Priority::Medium, Priority::Low].into_iter() {
+        let target_blocks: u8 = fee_targets.target_blocks(priority);
+        table.add_row(row![
+            format!("{priority:?}"),
+            target_blocks,
+            format!("{:.2} sat/vB", estimates.fee_rate(target_blocks))
+        ]);
+    }
+
+    table.printstd();
+
+    if estimates.fallback {
+        println!(
+            "{}",
+            "WARNING: the backend can't estimate the fees, the fallback fee rates are used"
+                .red()
+                .bold()
+        );
+    }
+}
+
+pub fn print_fee_warning(warning: FeeWarning) {
+    println!(
+        "{}",
+        format!("WARNING: suspicious fee - {warning}").red().bold()
+    );
+}
+
+pub fn print_limits_exceeded(exceeded: Vec<LimitExceeded>) {
+    for LimitExceeded { metric, value, max } in exceeded.into_iter() {
+        println!(
+            "{}",
+            format!("WARNING: complexity limit exceeded - {value} {metric} (max {max})")
+                .red()
+                .bold()
+        );
+    }
+}
+
+pub fn print_spend_preview(coin_selection: CoinSelection, preview: &SpendPreview) {
+    println!(
+        "{}",
+        "Spend preview (not published)"
+            .fg::<BlazeOrange>()
+            .underline()
+    );
+    println!("Coin selection: {coin_selection}");
+    println!("Fee: {} sat", format::number(preview.fee));
+    println!("Fee rate: ~{:.2} sat/vB", preview.fee_rate);
+
+    match &preview.change {
+        Some(change) => println!(
+            "Change: {} sat to {}",
+            format::number(change.value),
+            change
+                .address
+                .clone()
+                .map(|a| a.assume_checked().to_string())
+                .unwrap_or_else(|| String::from("unknown"))
+        ),
+        None => println!("Change: none"),
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Selected UTXO", "Value"]);
+
+    for (index, utxo) in preview.selected_utxos.iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            utxo.previous_output,
+            utxo.value
+                .map(|v| format!("{} sat", format::number(v)))
+                .unwrap_or_else(|| String::from("-")),
+        ]);
+    }
+
+    table.printstd();
+}
+
 pub fn print_proposals(proposals: Vec<GetProposal>) {
     let mut table = Table::new();
 
@@ -457,32 +1057,71 @@ pub fn print_proposals(proposals: Vec<GetProposal>) {
     table.printstd();
 }
 
-pub fn print_completed_proposals(proposals: Vec<GetCompletedProposal>) {
+pub fn print_proposals_awaiting_my_approval(proposals: Vec<GetProposal>) {
     let mut table = Table::new();
 
-    table.set_titles(row!["#", "ID", "Policy ID", "Type", "Txid", "Description"]);
+    table.set_titles(row!["#", "ID", "Policy ID", "Type", "Desc/Msg", "Age"]);
 
-    for (
-        index,
-        GetCompletedProposal {
-            policy_id,
-            completed_proposal_id,
-            proposal,
-            ..
-        },
-    ) in proposals.into_iter().enumerate()
-    {
-        match proposal {
-            CompletedProposal::Spending {
-                tx, description, ..
-            } => {
-                table.add_row(row![
+    for (index, proposal) in proposals.into_iter().enumerate() {
+        let age: String = format::duration(proposal.age().as_secs());
+        let (t, description) = match &proposal.proposal {
+            Proposal::Spending { description, .. } => ("spending", description.clone()),
+            Proposal::KeyAgentPayment { description, .. } => {
+                ("key-agent-payment", description.clone())
+            }
+            Proposal::ProofOfReserve { message, .. } => ("proof-of-reserve", message.clone()),
+        };
+        table.add_row(row![
+            index + 1,
+            proposal.proposal_id,
+            util::cut_event_id(proposal.policy_id),
+            t,
+            description,
+            age
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_completed_proposals(
+    proposals: Vec<GetCompletedProposal>,
+    proofs: HashMap<EventId, ProofVerification>,
+) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "ID",
+        "Policy ID",
+        "Type",
+        "Txid",
+        "Description",
+        "Proof"
+    ]);
+
+    for (
+        index,
+        GetCompletedProposal {
+            policy_id,
+            completed_proposal_id,
+            proposal,
+            ..
+        },
+    ) in proposals.into_iter().enumerate()
+    {
+        match proposal {
+            CompletedProposal::Spending {
+                tx, description, ..
+            } => {
+                table.add_row(row![
                     index + 1,
                     completed_proposal_id,
                     util::cut_event_id(policy_id),
                     "spending",
                     tx.txid(),
                     description,
+                    "-",
                 ]);
             }
             CompletedProposal::KeyAgentPayment {
@@ -495,6 +1134,7 @@ pub fn print_completed_proposals(proposals: Vec<GetCompletedProposal>) {
                     "key-agent-payment",
                     tx.txid(),
                     description,
+                    "-",
                 ]);
             }
             CompletedProposal::ProofOfReserve { message, .. } => {
@@ -505,6 +1145,14 @@ pub fn print_completed_proposals(proposals: Vec<GetCompletedProposal>) {
                     "proof-of-reserve",
                     "-",
                     message,
+                    match proofs.get(&completed_proposal_id) {
+                        Some(ProofVerification {
+                            spendable: Some(spendable),
+                            ..
+                        }) => format!("valid ({} sat)", format::number(*spendable)),
+                        Some(..) => "invalid".red().to_string(),
+                        None => String::from("pending"),
+                    },
                 ]);
             }
         }
@@ -513,6 +1161,87 @@ pub fn print_completed_proposals(proposals: Vec<GetCompletedProposal>) {
     table.printstd();
 }
 
+pub fn print_proof_report(proofs: HashMap<EventId, ProofVerification>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Completed proposal ID",
+        "Policy ID",
+        "Verdict",
+        "Height",
+        "Verified at"
+    ]);
+
+    let mut proofs: Vec<ProofVerification> = proofs.into_values().collect();
+    proofs.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    for (index, proof) in proofs.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            proof.completed_proposal_id,
+            util::cut_event_id(proof.policy_id),
+            match (proof.spendable, proof.error) {
+                (Some(spendable), _) => format!("valid ({} sat)", format::number(spendable)),
+                (None, Some(error)) => format!("invalid: {error}").red().to_string(),
+                (None, None) => "invalid".red().to_string(),
+            },
+            format::number(proof.height as u64),
+            format::datetime(proof.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_proposal_signers(signers: Vec<(GetSigner, bool)>) {
+    if signers.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "ID", "Name", "Fingerprint", "Type", "Approved"]);
+
+    for (index, (GetSigner { signer_id, signer }, approved)) in signers.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            signer_id,
+            signer.name(),
+            signer.fingerprint(),
+            signer.signer_type(),
+            if approved { "yes" } else { "no" },
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_approvals(approvals: Vec<GetApproval>) {
+    if approvals.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "ID", "User", "Date/Time"]);
+
+    for (index, approval) in approvals.into_iter().enumerate() {
+        let mut datetime: String = format::datetime(approval.display_timestamp);
+        if approval.is_clock_skewed() {
+            datetime.push_str(" (clock skew)");
+        }
+        table.add_row(row![
+            index + 1,
+            approval.approval_id,
+            approval.user.name(),
+            datetime,
+        ]);
+    }
+
+    table.printstd();
+}
+
 pub fn print_signers(signers: Vec<GetSigner>) {
     let mut table = Table::new();
 
@@ -531,12 +1260,82 @@ pub fn print_signers(signers: Vec<GetSigner>) {
     table.printstd();
 }
 
-pub async fn print_relays(relays: BTreeMap<Url, Relay>) {
+pub fn print_shared_signers(shared_signers: Vec<GetSharedSigner>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "ID",
+        "Owner",
+        "Fingerprint",
+        "Templates",
+        "Expires",
+        "Max uses"
+    ]);
+
+    for (
+        index,
+        GetSharedSigner {
+            shared_signer_id,
+            owner,
+            shared_signer,
+        },
+    ) in shared_signers.into_iter().enumerate()
+    {
+        let (templates, expires, max_uses) = match shared_signer.scope() {
+            Some(scope) => (
+                if scope.templates.is_empty() {
+                    String::from("-")
+                } else {
+                    scope
+                        .templates
+                        .iter()
+                        .map(|t| t.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                },
+                scope
+                    .expires_at
+                    .map(|ts| format::datetime(Timestamp::from(ts)))
+                    .unwrap_or_else(|| String::from("-")),
+                scope
+                    .max_uses
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| String::from("-")),
+            ),
+            None => (String::from("-"), String::from("-"), String::from("-")),
+        };
+        table.add_row(row![
+            index + 1,
+            util::cut_event_id(shared_signer_id),
+            owner.name(),
+            shared_signer.fingerprint(),
+            templates,
+            expires,
+            max_uses,
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn relay_role(read_only: bool, write_only: bool) -> RelayRole {
+    if read_only {
+        RelayRole::read_only()
+    } else if write_only {
+        RelayRole::write_only()
+    } else {
+        RelayRole::default()
+    }
+}
+
+pub async fn print_relays(relays: BTreeMap<Url, Relay>, roles: HashMap<Url, RelayRole>) {
     let mut table = Table::new();
 
     table.set_titles(row![
         "#",
         "Url",
+        "Role",
         "Status",
         "Attemps",
         "Success",
@@ -549,9 +1348,11 @@ pub async fn print_relays(relays: BTreeMap<Url, Relay>) {
 
     for (index, (url, relay)) in relays.into_iter().enumerate() {
         let stats = relay.stats();
+        let role: RelayRole = roles.get(&url).copied().unwrap_or_default();
         table.add_row(row![
             index + 1,
             url,
+            role,
             relay.status().await,
             stats.attempts(),
             stats.success(),
@@ -565,7 +1366,7 @@ pub async fn print_relays(relays: BTreeMap<Url, Relay>) {
             if stats.connected_at() == Timestamp::from(0) {
                 String::from("-")
             } else {
-                stats.connected_at().to_human_datetime()
+                format::relative_datetime(stats.connected_at())
             }
         ]);
     }
@@ -573,31 +1374,92 @@ pub async fn print_relays(relays: BTreeMap<Url, Relay>) {
     table.printstd();
 }
 
-pub fn print_addresses(addresses: Vec<GetAddress>, balances: HashMap<ScriptBuf, u64>) {
+pub fn print_relays_health(
+    relays: Vec<RelayHealth>,
+    pings: HashMap<Url, Result<Duration, String>>,
+) {
     let mut table = Table::new();
 
-    table.set_titles(row!["#", "Address", "Label", "Balance"]);
+    table.set_titles(row![
+        "#",
+        "Url",
+        "Status",
+        "Connected at",
+        "Disconnected at",
+        "Events received",
+        "Failed publishes",
+        "Avg send latency",
+        "Ping"
+    ]);
+
+    let fmt_ts = |ts: Option<Timestamp>| match ts {
+        Some(ts) => format::relative_datetime(ts),
+        None => String::from("-"),
+    };
 
-    for (index, GetAddress { address, label }) in addresses.into_iter().enumerate() {
+    for (index, relay) in relays.into_iter().enumerate() {
+        let status: String = if relay.is_connected() {
+            relay.status.to_string().fg::<Lime>().to_string()
+        } else {
+            relay.status.to_string().red().to_string()
+        };
         table.add_row(row![
             index + 1,
-            address.clone().assume_checked().to_string(),
+            relay.url,
+            status,
+            fmt_ts(relay.connected_at),
+            fmt_ts(relay.disconnected_at),
+            format::number(relay.events_received),
+            format::number(relay.failed_publishes),
+            match relay.avg_send_latency {
+                Some(latency) => format!("{} ms", latency.as_millis()),
+                None => String::from("-"),
+            },
+            match pings.get(&relay.url) {
+                Some(Ok(rtt)) => format!("{} ms", rtt.as_millis()),
+                Some(Err(e)) => e.red().to_string(),
+                None => String::from("-"),
+            }
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_addresses(addresses: Vec<GetAddress>) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["Index", "Address", "Used", "Label", "Balance"]);
+
+    for GetAddress {
+        address,
+        index,
+        used,
+        balance,
+        label,
+    } in addresses.into_iter()
+    {
+        table.add_row(row![
+            index,
+            address.assume_checked().to_string(),
+            if used { "yes" } else { "no" },
             label.unwrap_or_else(|| String::from("-")),
-            format!(
-                "{} sat",
-                format::number(
-                    balances
-                        .get(&address.payload.script_pubkey())
-                        .copied()
-                        .unwrap_or_default()
-                )
-            )
+            format!("{} sat", format::number(balance))
         ]);
     }
 
     table.printstd();
 }
 
+pub fn print_gap_limit_warning(boundary: u32) {
+    println!(
+        "{}",
+        format!("WARNING: addresses from index {boundary} are beyond the gap limit - funds sent to them will not be detected until the gap limit is raised (`config set --gap-limit`)")
+            .red()
+            .bold()
+    );
+}
+
 pub fn print_sessions(sessions: Vec<(NostrConnectURI, Timestamp)>) {
     let mut table = Table::new();
 
@@ -613,9 +1475,9 @@ pub fn print_sessions(sessions: Vec<(NostrConnectURI, Timestamp)>) {
         table.add_row(row![
             index + 1,
             uri.metadata.name,
-            uri.public_key,
+            util::display_public_key(uri.public_key),
             uri.relay_url,
-            timestamp.to_human_datetime(),
+            format::datetime(timestamp),
         ]);
     }
 
@@ -639,7 +1501,7 @@ pub fn print_requests(requests: Vec<NostrConnectRequest>) -> Result<()> {
             req.event_id,
             util::cut_public_key(req.app_public_key),
             req.message.to_request()?.method(),
-            req.timestamp.to_human_datetime(),
+            format::datetime(req.timestamp),
         ]);
     }
 
@@ -648,13 +1510,486 @@ pub fn print_requests(requests: Vec<NostrConnectRequest>) -> Result<()> {
     Ok(())
 }
 
+pub async fn print_config(config: &Config) -> Result<()> {
+    let overridden: Vec<&str> = config.overridden_fields().await;
+
+    println!();
+    println!("Profile: {}", config.profile.name.fg::<BrightCyan>());
+
+    match config.backend().await {
+        Backend::Electrum => println!("Backend: electrum"),
+        Backend::BitcoinCore { url, .. } => {
+            println!(
+                "Backend: {}",
+                format!("bitcoind ({url})").fg::<BlazeOrange>()
+            )
+        }
+    }
+
+    let fee_targets = config.fee_targets().await;
+    let fee_targets = format!("{fee_targets} (high,medium,low)");
+    if overridden.contains(&"fee_targets") {
+        println!("Fee targets: {}", fee_targets.fg::<BlazeOrange>());
+    } else {
+        println!("Fee targets: {fee_targets}");
+    }
+
+    let fee_cap = format!("{} (proposals)", config.fee_cap().await);
+    if overridden.contains(&"fee_cap") {
+        println!("Fee cap: {}", fee_cap.fg::<BlazeOrange>());
+    } else {
+        println!("Fee cap: {fee_cap}");
+    }
+
+    let gap_limit = format!("{} (unused addresses)", config.gap_limit().await);
+    if overridden.contains(&"gap_limit") {
+        println!("Gap limit: {}", gap_limit.fg::<BlazeOrange>());
+    } else {
+        println!("Gap limit: {gap_limit}");
+    }
+
+    match config.screening_list_path().await {
+        Some(path) => println!(
+            "Screening: {} (on approve: {})",
+            path.display(),
+            config.screening_on_approve().await
+        ),
+        None => println!("Screening: disabled"),
+    }
+
+    let min_relay_acks = format!("{} (critical events)", config.min_relay_acks().await);
+    if overridden.contains(&"min_relay_acks") {
+        println!("Min relay acks: {}", min_relay_acks.fg::<BlazeOrange>());
+    } else {
+        println!("Min relay acks: {min_relay_acks}");
+    }
+
+    let max_recipient_relays = config.max_recipient_relays().await;
+    if overridden.contains(&"max_recipient_relays") {
+        println!(
+            "Max recipient relays: {}",
+            max_recipient_relays.fg::<BlazeOrange>()
+        );
+    } else {
+        println!("Max recipient relays: {max_recipient_relays}");
+    }
+
+    if overridden.contains(&"notify_cosigners") {
+        println!("Notify co-signers: {}", "enabled".fg::<BlazeOrange>());
+    } else {
+        println!("Notify co-signers: disabled");
+    }
+
+    match config.psbt_dir().await {
+        Some(path) => println!("External signers dir: {}", path.display()),
+        None => println!("External signers dir: disabled"),
+    }
+
+    match config.fiat_currency().await {
+        Some(currency) => println!(
+            "Fiat balances: {currency} (prices: {})",
+            config.price_endpoint().await?
+        ),
+        None => println!("Fiat balances: disabled"),
+    }
+
+    match config.sync_profile().await {
+        SyncProfile::Full => println!("Sync profile: full"),
+        SyncProfile::Light => println!(
+            "Sync profile: {} (history horizon: {} days)",
+            "light".fg::<BlazeOrange>(),
+            config.light_sync_horizon().await.as_secs() / (60 * 60 * 24)
+        ),
+    }
+
+    println!();
+
+    // Highlight the fields that differ from the profile
+    for line in config.as_pretty_json().await?.lines() {
+        let is_overridden: bool = overridden
+            .iter()
+            .any(|field| line.trim_start().starts_with(&format!("\"{field}\"")));
+        if is_overridden {
+            println!("{}", line.fg::<BlazeOrange>());
+        } else {
+            println!("{line}");
+        }
+    }
+
+    if !overridden.is_empty() {
+        println!();
+        println!("Overridden: {}", overridden.join(", "));
+    }
+
+    println!();
+
+    Ok(())
+}
+
+pub fn print_unexpected_txs(txs: Vec<UnexpectedTx>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Policy ID",
+        "Txid",
+        "Amount",
+        "Detected",
+        "Acknowledged"
+    ]);
+
+    for (index, tx) in txs.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            util::cut_event_id(tx.policy_id),
+            tx.txid,
+            format!("{} sat", format::number(tx.amount)),
+            format::relative_datetime(tx.timestamp),
+            tx.acknowledged
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_notifications(notifications: Vec<GetNotificationsResult>) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Kind", "Event ID", "Timestamp", "Seen", "Action"]);
+
+    for (index, notification) in notifications.into_iter().enumerate() {
+        let action: &str = if notification.kind.is_informational() {
+            "info"
+        } else if notification.kind == NotificationKind::NostrConnectRequest && !notification.seen {
+            "approve/reject"
+        } else {
+            ""
+        };
+        table.add_row(row![
+            index + 1,
+            notification.kind,
+            notification.event_id,
+            format::datetime(notification.timestamp),
+            notification.seen,
+            action
+        ]);
+    }
+
+    table.printstd();
+}
+
+#[cfg(feature = "hwi")]
+pub fn print_hw_devices(devices: &[Result<HWIDevice, hw::Error>]) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Type", "Model", "Fingerprint", "Status"]);
+
+    for (index, device) in devices.iter().enumerate() {
+        match device {
+            Ok(device) => {
+                let status: &str = if device.needs_pin_sent {
+                    "locked (enter PIN)"
+                } else if device.needs_passphrase_sent {
+                    "locked (enter passphrase)"
+                } else {
+                    "ready"
+                };
+                table.add_row(row![
+                    index + 1,
+                    format!("{:?}", device.device_type),
+                    device.model,
+                    device.fingerprint,
+                    status
+                ]);
+            }
+            Err(e) => {
+                table.add_row(row![index + 1, "-", "-", "-", e]);
+            }
+        }
+    }
+
+    table.printstd();
+}
+
+pub fn print_hw_registrations(registrations: Vec<HwRegistration>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Policy ID",
+        "Fingerprint",
+        "Device ID",
+        "Registered at"
+    ]);
+
+    for (index, registration) in registrations.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            registration.policy_id,
+            registration.fingerprint,
+            registration.device_id,
+            format::datetime(registration.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_passphrase_identities(identities: Vec<PassphraseIdentity>, current: Fingerprint) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Fingerprint",
+        "Public Key",
+        "Passphrase",
+        "Last use"
+    ]);
+
+    for (index, identity) in identities.into_iter().enumerate() {
+        let fingerprint: String = if identity.fingerprint == current {
+            format!("{} (current)", identity.fingerprint)
+        } else {
+            identity.fingerprint.to_string()
+        };
+        table.add_row(row![
+            index + 1,
+            fingerprint,
+            identity.public_key,
+            if identity.passphrase_hash.is_some() {
+                "yes"
+            } else {
+                "no"
+            },
+            format::datetime(identity.last_used),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_profile_history(history: Vec<ProfileVersion>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "Version",
+        "Name",
+        "Display name",
+        "NIP-05",
+        "Published at"
+    ]);
+
+    for ProfileVersion {
+        version,
+        metadata,
+        timestamp,
+        ..
+    } in history.into_iter()
+    {
+        table.add_row(row![
+            version,
+            metadata.name.unwrap_or_else(|| String::from("-")),
+            metadata.display_name.unwrap_or_else(|| String::from("-")),
+            metadata.nip05.unwrap_or_else(|| String::from("-")),
+            format::datetime(timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_metadata_changes(changes: Vec<MetadataChange>) {
+    if changes.is_empty() {
+        println!("No changes against the published profile");
+        return;
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["Field", "Published", "New"]);
+
+    for MetadataChange { field, old, new } in changes.into_iter() {
+        table.add_row(row![
+            field,
+            old.unwrap_or_else(|| String::from("-")).red(),
+            new.unwrap_or_else(|| String::from("-")).green()
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_outbox(outbox: BTreeMap<EventId, Vec<OutboxEvent>>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Event ID",
+        "Kind",
+        "Pending relays",
+        "Attempts",
+        "Since"
+    ]);
+
+    for (index, (event_id, pending)) in outbox.into_iter().enumerate() {
+        let kind = pending.first().map(|e| e.event.kind.to_string());
+        let since = pending.iter().map(|e| e.timestamp).min();
+        table.add_row(row![
+            index + 1,
+            event_id,
+            kind.unwrap_or_default(),
+            pending
+                .iter()
+                .map(|e| e.relay_url.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            pending
+                .iter()
+                .map(|e| e.attempts.to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            since.map(format::relative_datetime).unwrap_or_default(),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_event_deliveries(deliveries: Vec<EventDelivery>) {
+    if deliveries.is_empty() {
+        println!("No deliveries recorded for this event");
+        return;
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Recipient",
+        "Relay",
+        "Recipient relay",
+        "Status",
+        "Timestamp"
+    ]);
+
+    for (index, delivery) in deliveries.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            util::cut_public_key(delivery.recipient),
+            delivery.relay_url,
+            if delivery.recipient_relay {
+                "yes"
+            } else {
+                "no"
+            },
+            delivery.error.unwrap_or_else(|| String::from("sent")),
+            format::relative_datetime(delivery.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_quarantined_events(events: Vec<QuarantinedEvent>) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Event ID", "Kind", "Reason", "Quarantined at"]);
+
+    for (index, quarantined) in events.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            quarantined.event_id,
+            quarantined
+                .event
+                .map(|e| e.kind.to_string())
+                .unwrap_or_else(|| String::from("-")),
+            quarantined.reason,
+            format::datetime(quarantined.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_failed_events(events: Vec<PendingEventRetry>) {
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Event ID", "Kind", "Author", "Retries"]);
+
+    for (index, failed) in events.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            failed.event.id,
+            failed.event.kind,
+            util::cut_public_key(failed.event.pubkey),
+            failed.retries,
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_signing_audit(entries: Vec<SigningAuditEntry>) {
+    let mut table = Table::new();
+
+    table.set_titles(row![
+        "#",
+        "Fingerprint",
+        "Kind",
+        "Object ID",
+        "Digest",
+        "Timestamp"
+    ]);
+
+    for entry in entries.into_iter() {
+        table.add_row(row![
+            entry.id,
+            entry.fingerprint,
+            entry.kind,
+            entry.object_id,
+            entry.digest,
+            format::datetime(entry.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
+pub fn print_fees_paid(fees: FeesPaid) {
+    let prefix: &str = if fees.estimated { "~" } else { "" };
+    println!("Total: {prefix}{} sat", format::number(fees.total));
+    if fees.estimated {
+        println!("Some input values are unknown: the estimated fees are lower bounds");
+    }
+
+    let mut table = Table::new();
+
+    table.set_titles(row!["#", "Txid", "Fee", "Estimated", "Timestamp"]);
+
+    for (index, tx) in fees.txs.into_iter().enumerate() {
+        table.add_row(row![
+            index + 1,
+            tx.txid,
+            format!("{} sat", format::number(tx.fee)),
+            if tx.estimated { "yes" } else { "no" },
+            format::datetime(tx.timestamp),
+        ]);
+    }
+
+    table.printstd();
+}
+
 pub fn print_authorizations(authorizations: BTreeMap<PublicKey, Timestamp>) {
     let mut table = Table::new();
 
     table.set_titles(row!["#", "App Public Key", "Authorized until",]);
 
     for (index, (app_public_key, until)) in authorizations.into_iter().enumerate() {
-        table.add_row(row![index + 1, app_public_key, until.to_human_datetime(),]);
+        table.add_row(row![
+            index + 1,
+            util::display_public_key(app_public_key),
+            format::datetime(until),
+        ]);
     }
 
     table.printstd();
@@ -712,3 +2047,19 @@ where
 
     table.printstd();
 }
+
+pub fn print_whats_new(releases: Vec<&Release>) {
+    println!("{}", "What's new".fg::<BlazeOrange>().underline());
+
+    for release in releases.into_iter() {
+        println!("\n{}", format!("v{}", release.version).bold());
+        for feature in release.features.iter() {
+            println!("  * {}: {}", feature.title.bold(), feature.description);
+            if let Some(command) = feature.command {
+                println!("    Try: `{command}`");
+            }
+        }
+    }
+
+    println!();
+}
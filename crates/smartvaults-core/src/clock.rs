@@ -0,0 +1,101 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Clock
+//!
+//! Source of the current time and block height of the timelock checks: a [`MockClock`] allows
+//! to test them without waiting for the locks to expire.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use keechain_core::util::time;
+
+/// Clock
+pub trait Clock {
+    /// Current UNIX timestamp (seconds)
+    fn now_timestamp(&self) -> u64;
+
+    /// Current block height
+    ///
+    /// `tip` is the height of the latest block known by the wallet.
+    fn now_height(&self, tip: u32) -> u32 {
+        tip
+    }
+}
+
+/// System time and wallet tip
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_timestamp(&self) -> u64 {
+        time::timestamp()
+    }
+}
+
+/// Manually advanced clock, for tests
+///
+/// The height is the wallet tip until set with [`MockClock::set_height`].
+#[derive(Debug, Default)]
+pub struct MockClock {
+    timestamp: AtomicU64,
+    /// `0` means wallet tip
+    height: AtomicU32,
+}
+
+impl MockClock {
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            timestamp: AtomicU64::new(timestamp),
+            height: AtomicU32::new(0),
+        }
+    }
+
+    pub fn set_timestamp(&self, timestamp: u64) {
+        self.timestamp.store(timestamp, Ordering::SeqCst);
+    }
+
+    pub fn advance_secs(&self, secs: u64) {
+        self.timestamp.fetch_add(secs, Ordering::SeqCst);
+    }
+
+    pub fn set_height(&self, height: u32) {
+        self.height.store(height, Ordering::SeqCst);
+    }
+
+    /// Advance the height set with [`MockClock::set_height`]
+    pub fn advance_blocks(&self, blocks: u32) {
+        self.height.fetch_add(blocks, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_timestamp(&self) -> u64 {
+        self.timestamp.load(Ordering::SeqCst)
+    }
+
+    fn now_height(&self, tip: u32) -> u32 {
+        match self.height.load(Ordering::SeqCst) {
+            0 => tip,
+            height => height,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::new(1_700_000_000);
+        assert_eq!(clock.now_timestamp(), 1_700_000_000);
+        clock.advance_secs(600);
+        assert_eq!(clock.now_timestamp(), 1_700_000_600);
+
+        assert_eq!(clock.now_height(800_000), 800_000);
+        clock.set_height(800_100);
+        clock.advance_blocks(6);
+        assert_eq!(clock.now_height(800_000), 800_106);
+    }
+}
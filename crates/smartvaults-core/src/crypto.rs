@@ -0,0 +1,118 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Crypto
+
+pub use keechain_core::crypto::*;
+use sha2::Sha256;
+
+/// Rounds of [`derive_key`]
+pub const PBKDF2_ROUNDS: u32 = 100_000;
+/// Default scrypt cost parameter of [`derive_key_scrypt`] (`N = 2^17`, 128 MiB with `r = 8`)
+pub const SCRYPT_LOG_N: u8 = 17;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScryptError {
+    #[error("invalid scrypt params")]
+    InvalidParams,
+    #[error("invalid scrypt output len")]
+    InvalidOutputLen,
+}
+
+/// PBKDF2-HMAC-SHA256: fill `output` with the key derived from `password` and `salt`
+pub fn pbkdf2_hmac_sha256(password: &[u8], salt: &[u8], rounds: u32, output: &mut [u8]) {
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, rounds, output);
+}
+
+/// Derive a 32 bytes key from `password` and `salt` (PBKDF2-HMAC-SHA256, [`PBKDF2_ROUNDS`] rounds)
+pub fn derive_key(password: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut key: [u8; 32] = [0u8; 32];
+    pbkdf2_hmac_sha256(password, salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+/// scrypt: fill `output` with the key derived from `password` and `salt`
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+    output: &mut [u8],
+) -> Result<(), ScryptError> {
+    let params =
+        scrypt::Params::new(log_n, r, p, output.len()).map_err(|_| ScryptError::InvalidParams)?;
+    scrypt::scrypt(password, salt, &params, output).map_err(|_| ScryptError::InvalidOutputLen)
+}
+
+/// Derive a 32 bytes key from `password` and `salt` (memory-hard, scrypt with `N = 2^log_n`, `r = 8`, `p = 1`)
+///
+/// Use [`SCRYPT_LOG_N`] unless the cost is read from an existing payload.
+pub fn derive_key_scrypt(password: &[u8], salt: &[u8], log_n: u8) -> Result<[u8; 32], ScryptError> {
+    let mut key: [u8; 32] = [0u8; 32];
+    scrypt(password, salt, log_n, SCRYPT_R, SCRYPT_P, &mut key)?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use keechain_core::bitcoin::hashes::hex::FromHex;
+
+    use super::*;
+
+    fn pbkdf2(password: &str, salt: &str, rounds: u32) -> Vec<u8> {
+        let mut output: Vec<u8> = vec![0u8; 64];
+        pbkdf2_hmac_sha256(password.as_bytes(), salt.as_bytes(), rounds, &mut output);
+        output
+    }
+
+    /// RFC 7914, section 11
+    #[test]
+    fn test_pbkdf2_hmac_sha256_rfc7914() {
+        assert_eq!(
+            pbkdf2("passwd", "salt", 1),
+            Vec::<u8>::from_hex("55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783").unwrap()
+        );
+        assert_eq!(
+            pbkdf2("Password", "NaCl", 80000),
+            Vec::<u8>::from_hex("4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_derive_key() {
+        assert_eq!(
+            derive_key(b"test", &[0u8; 16]).to_vec(),
+            Vec::<u8>::from_hex("da3a2737bbf7ddc300befff667a52ed11f95f79a0ff52f24d70890d6c7523c3d")
+                .unwrap()
+        );
+    }
+
+    /// RFC 7914, section 12
+    #[test]
+    fn test_scrypt_rfc7914() {
+        let mut output = [0u8; 64];
+        scrypt(b"", b"", 4, 1, 1, &mut output).unwrap();
+        assert_eq!(
+            output.to_vec(),
+            Vec::<u8>::from_hex("77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906").unwrap()
+        );
+        scrypt(b"password", b"NaCl", 10, 8, 16, &mut output).unwrap();
+        assert_eq!(
+            output.to_vec(),
+            Vec::<u8>::from_hex("fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_derive_key_scrypt() {
+        assert_eq!(
+            derive_key_scrypt(b"test", &[0u8; 16], 10).unwrap().to_vec(),
+            Vec::<u8>::from_hex("3398c9b6adaafcde4aa89570e6d6f42726e4fcb1652ec2249ef2b391bd5fd95f")
+                .unwrap()
+        );
+        assert!(derive_key_scrypt(b"test", &[0u8; 16], 64).is_err());
+    }
+}
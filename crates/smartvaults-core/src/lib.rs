@@ -12,7 +12,10 @@ use keechain_core::secp256k1::{rand, All, Secp256k1};
 pub use keechain_core::*;
 use once_cell::sync::Lazy;
 
+pub mod clock;
 pub mod constants;
+pub mod crypto;
+pub mod mnemonic;
 pub mod policy;
 pub mod proposal;
 #[cfg(feature = "reserves")]
@@ -21,13 +24,17 @@ pub mod signer;
 pub mod types;
 pub mod util;
 
+pub use self::clock::{Clock, MockClock, SystemClock};
 pub use self::policy::{
     AbsoluteLockTime, DecayingTime, Locktime, Policy, PolicyTemplate, PolicyTemplateType,
-    RecoveryTemplate, SelectableCondition, Sequence,
+    RecoveryTemplate, ScriptType, SelectableCondition, Sequence,
+};
+pub use self::proposal::{
+    ApprovedProposal, CompletedProposal, FeeCap, FeeWarning, OutputKind, OutputVerification,
+    Proposal,
 };
-pub use self::proposal::{ApprovedProposal, CompletedProposal, Proposal};
 pub use self::signer::{SharedSigner, Signer, SignerType};
-pub use self::types::{Amount, FeeRate, Priority};
+pub use self::types::{Amount, CoinSelection, FeeRate, Priority};
 
 pub static SECP256K1: Lazy<Secp256k1<All>> = Lazy::new(|| {
     let mut ctx = Secp256k1::new();
@@ -45,6 +52,7 @@ mod tests {
     use keechain_core::bdk::{FeeRate, Wallet};
     use keechain_core::bips::bip39::Mnemonic;
     use keechain_core::bitcoin::absolute::Height;
+    use keechain_core::bitcoin::bip32::ChildNumber;
     use keechain_core::bitcoin::hashes::Hash;
     use keechain_core::bitcoin::{absolute, Address, BlockHash, Network, Transaction, TxOut};
     use keechain_core::descriptors::ToDescriptor;
@@ -53,7 +61,7 @@ mod tests {
 
     use super::*;
     use crate::constants::SMARTVAULTS_ACCOUNT_INDEX;
-    use crate::proposal::ProposalType;
+    use crate::proposal::{OutputKind, ProposalType};
     #[cfg(feature = "reserves")]
     use crate::reserves::ProofOfReserves;
 
@@ -62,9 +70,18 @@ mod tests {
         "possible suffer flavor boring essay zoo collect stairs day cabbage wasp tackle";
     const MNEMONIC_B: &str =
         "panther tree neglect narrow drip act visit position pass assault tennis long";
+    const MNEMONIC_C: &str =
+        "message scissors typical gravity patrol lunch about bacon person focus cry uncover";
 
     pub fn get_funded_wallet(descriptor: &str) -> Result<Wallet> {
-        let mut wallet = Wallet::new_no_persist(descriptor, None, NETWORK)?;
+        get_funded_wallet_with_change(descriptor, None)
+    }
+
+    pub fn get_funded_wallet_with_change(
+        descriptor: &str,
+        change_descriptor: Option<&str>,
+    ) -> Result<Wallet> {
+        let mut wallet = Wallet::new_no_persist(descriptor, change_descriptor, NETWORK)?;
 
         wallet
             .insert_checkpoint(BlockId {
@@ -167,8 +184,11 @@ mod tests {
             None,
             None,
             None,
+            CoinSelection::default(),
         )?;
 
+        assert!(!proposal.can_be_finalized_by(&seed_a, NETWORK)?);
+
         let approved_a: ApprovedProposal = proposal.approve(&seed_a, Vec::new(), NETWORK)?;
         let approved_b: ApprovedProposal = proposal.approve(&seed_b, Vec::new(), NETWORK)?;
 
@@ -220,6 +240,158 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_proposal_verify_outputs() -> Result<()> {
+        let mnemonic_a: Mnemonic = Mnemonic::from_str(MNEMONIC_A)?;
+        let seed_a: Seed = Seed::from_mnemonic(mnemonic_a);
+        let desc_a: DescriptorPublicKey =
+            seed_a.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let mnemonic_b: Mnemonic = Mnemonic::from_str(MNEMONIC_B)?;
+        let seed_b: Seed = Seed::from_mnemonic(mnemonic_b);
+        let desc_b: DescriptorPublicKey =
+            seed_b.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let template = PolicyTemplate::multisig(2, vec![desc_a, desc_b]);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?;
+        let descriptor: String = policy.as_descriptor().to_string();
+
+        let mut wallet = get_funded_wallet(&descriptor).unwrap();
+        let proposal: Proposal = policy.spend(
+            &mut wallet,
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+            Amount::Custom(1120),
+            "Testing",
+            FeeRate::from_sat_per_vb(1.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        )?;
+
+        let outputs = proposal.verify_outputs(policy.as_descriptor())?;
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(
+            outputs
+                .iter()
+                .filter(|o| o.kind == OutputKind::Recipient)
+                .count(),
+            1
+        );
+        assert_eq!(
+            outputs
+                .iter()
+                .filter(|o| o.kind == OutputKind::Change)
+                .count(),
+            1
+        );
+
+        // Tamper the key origins of the change output
+        let change_vout: usize = outputs
+            .iter()
+            .find(|o| o.kind == OutputKind::Change)
+            .map(|o| o.vout)
+            .unwrap();
+        let tampered: Proposal = match proposal.clone() {
+            Proposal::Spending {
+                descriptor,
+                to_address,
+                amount,
+                description,
+                mut psbt,
+                policy_path,
+            } => {
+                for (_, (_, path)) in psbt.outputs[change_vout].tap_key_origins.values_mut() {
+                    *path = path.child(ChildNumber::from_normal_idx(1000)?);
+                }
+                Proposal::Spending {
+                    descriptor,
+                    to_address,
+                    amount,
+                    description,
+                    psbt,
+                    policy_path,
+                }
+            }
+            _ => unreachable!(),
+        };
+        let outputs = tampered.verify_outputs(policy.as_descriptor())?;
+        assert_eq!(outputs[change_vout].kind, OutputKind::Unverified);
+
+        // Remove key origins of the change output
+        let stripped: Proposal = match proposal {
+            Proposal::Spending {
+                descriptor,
+                to_address,
+                amount,
+                description,
+                mut psbt,
+                policy_path,
+            } => {
+                psbt.outputs[change_vout].tap_key_origins.clear();
+                psbt.outputs[change_vout].bip32_derivation.clear();
+                Proposal::Spending {
+                    descriptor,
+                    to_address,
+                    amount,
+                    description,
+                    psbt,
+                    policy_path,
+                }
+            }
+            _ => unreachable!(),
+        };
+        let outputs = stripped.verify_outputs(policy.as_descriptor())?;
+        assert_eq!(outputs[change_vout].kind, OutputKind::Unverified);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_spend_with_change_descriptor() -> Result<()> {
+        let mnemonic_a: Mnemonic = Mnemonic::from_str(MNEMONIC_A)?;
+        let seed_a: Seed = Seed::from_mnemonic(mnemonic_a);
+        let desc_a: DescriptorPublicKey =
+            seed_a.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let mnemonic_b: Mnemonic = Mnemonic::from_str(MNEMONIC_B)?;
+        let seed_b: Seed = Seed::from_mnemonic(mnemonic_b);
+        let desc_b: DescriptorPublicKey =
+            seed_b.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let template = PolicyTemplate::multisig(2, vec![desc_a, desc_b]);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?
+            .with_derived_change_descriptor()?;
+        let descriptor: String = policy.as_descriptor().to_string();
+        let change_descriptor: String = policy.change_descriptor().unwrap().to_string();
+
+        let mut wallet = get_funded_wallet_with_change(&descriptor, Some(&change_descriptor))?;
+        let proposal: Proposal = policy.spend(
+            &mut wallet,
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+            Amount::Custom(1120),
+            "Testing",
+            FeeRate::from_sat_per_vb(1.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        )?;
+
+        // Change is derived from the internal keychain
+        let outputs = proposal
+            .verify_outputs_with_change(policy.as_descriptor(), policy.as_change_descriptor())?;
+        let change = outputs
+            .iter()
+            .find(|o| o.kind == OutputKind::Change)
+            .unwrap();
+        assert!(wallet.is_mine(&change.script_pubkey));
+        let outputs = proposal.verify_outputs(policy.as_descriptor())?;
+        assert_eq!(outputs[change.vout].kind, OutputKind::Unverified);
+
+        Ok(())
+    }
+
     #[test]
     fn test_policy_spend_1_of_2_multisig() -> Result<()> {
         // User A
@@ -258,8 +430,11 @@ mod tests {
             None,
             None,
             None,
+            CoinSelection::default(),
         )?;
 
+        assert!(proposal.can_be_finalized_by(&seed_a, NETWORK)?);
+
         let approved_a: ApprovedProposal = proposal.approve(&seed_a, Vec::new(), NETWORK)?;
 
         let completed_proposal: CompletedProposal = proposal.finalize(vec![approved_a], NETWORK)?;
@@ -269,6 +444,311 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_2_of_3_multisig_threshold() -> Result<()> {
+        let mut seeds: Vec<Seed> = Vec::new();
+        let mut descriptors: Vec<DescriptorPublicKey> = Vec::new();
+        for mnemonic in [MNEMONIC_A, MNEMONIC_B, MNEMONIC_C] {
+            let seed: Seed = Seed::from_mnemonic(Mnemonic::from_str(mnemonic)?);
+            descriptors.push(seed.to_descriptor(
+                Purpose::BIP86,
+                Some(7291640),
+                false,
+                NETWORK,
+                &SECP256K1,
+            )?);
+            seeds.push(seed);
+        }
+
+        let template = PolicyTemplate::multisig(2, descriptors);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?;
+        let descriptor: String = policy.as_descriptor().to_string();
+
+        let mut wallet = get_funded_wallet(&descriptor).unwrap();
+        let proposal: Proposal = policy.spend(
+            &mut wallet,
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+            Amount::Custom(1120),
+            "Testing",
+            FeeRate::from_sat_per_vb(1.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        )?;
+
+        let mut approvals: Vec<ApprovedProposal> = Vec::new();
+        let mut ready: Vec<bool> = Vec::new();
+        for seed in seeds.iter() {
+            approvals.push(proposal.approve(seed, Vec::new(), NETWORK)?);
+            ready.push(proposal.finalize(approvals.clone(), NETWORK).is_ok());
+        }
+
+        // Threshold reached with the second approval, still ready after the third one
+        assert_eq!(ready, vec![false, true, true]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_spend_coin_selection() -> Result<()> {
+        let mnemonic_a: Mnemonic = Mnemonic::from_str(MNEMONIC_A)?;
+        let seed_a: Seed = Seed::from_mnemonic(mnemonic_a);
+        let desc_a: DescriptorPublicKey =
+            seed_a.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let mnemonic_b: Mnemonic = Mnemonic::from_str(MNEMONIC_B)?;
+        let seed_b: Seed = Seed::from_mnemonic(mnemonic_b);
+        let desc_b: DescriptorPublicKey =
+            seed_b.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let template = PolicyTemplate::multisig(2, vec![desc_a, desc_b]);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?;
+        let descriptor: String = policy.as_descriptor().to_string();
+
+        let mut wallet = get_funded_wallet(&descriptor).unwrap();
+        let wallet_utxos: usize = wallet.list_unspent().count();
+
+        let mut spend = |coin_selection: CoinSelection| -> Result<usize> {
+            let proposal: Proposal = policy.spend(
+                &mut wallet,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+                Amount::Custom(1120),
+                "Testing",
+                FeeRate::from_sat_per_vb(1.0),
+                None,
+                None,
+                None,
+                coin_selection,
+            )?;
+            Ok(proposal.psbt().unsigned_tx.input.len())
+        };
+
+        assert_eq!(spend(CoinSelection::PrivacyFirst)?, 1);
+        assert_eq!(spend(CoinSelection::Consolidate)?, wallet_utxos);
+
+        // Covered only by two UTXOs: refused instead of linking them
+        let mut spend_5000 = |coin_selection: CoinSelection| {
+            policy.spend(
+                &mut wallet,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+                Amount::Custom(5000),
+                "Testing",
+                FeeRate::from_sat_per_vb(1.0),
+                None,
+                None,
+                None,
+                coin_selection,
+            )
+        };
+        assert!(spend_5000(CoinSelection::PrivacyFirst).is_err());
+        assert!(
+            spend_5000(CoinSelection::Default)?
+                .psbt()
+                .unsigned_tx
+                .input
+                .len()
+                > 1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_spend_subtract_fee() -> Result<()> {
+        let mnemonic_a: Mnemonic = Mnemonic::from_str(MNEMONIC_A)?;
+        let seed_a: Seed = Seed::from_mnemonic(mnemonic_a);
+        let desc_a: DescriptorPublicKey =
+            seed_a.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let mnemonic_b: Mnemonic = Mnemonic::from_str(MNEMONIC_B)?;
+        let seed_b: Seed = Seed::from_mnemonic(mnemonic_b);
+        let desc_b: DescriptorPublicKey =
+            seed_b.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let template = PolicyTemplate::multisig(2, vec![desc_a, desc_b]);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?
+            .with_derived_change_descriptor()?;
+        let descriptor: String = policy.as_descriptor().to_string();
+        let change_descriptor: String = policy.change_descriptor().unwrap().to_string();
+
+        let mut wallet = get_funded_wallet_with_change(&descriptor, Some(&change_descriptor))?;
+        let address = Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?;
+        let script = address.clone().assume_checked().script_pubkey();
+
+        let proposal: Proposal = policy.spend(
+            &mut wallet,
+            address.clone(),
+            Amount::CustomSubtractFee(3000),
+            "Testing",
+            FeeRate::from_sat_per_vb(2.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        )?;
+        let fee: u64 = proposal.fee()?;
+        let tx: Transaction = proposal.psbt().unsigned_tx;
+
+        // The recipient receives the amount minus the exact fee
+        let received: u64 = tx
+            .output
+            .iter()
+            .filter(|txout| txout.script_pubkey == script)
+            .map(|txout| txout.value)
+            .sum();
+        assert_eq!(received, 3000 - fee);
+        match proposal {
+            Proposal::Spending { amount, .. } => assert_eq!(amount, received),
+            _ => panic!("expected a spending proposal"),
+        }
+
+        // The change is what exceeds the amount: it doesn't pay any fee
+        let (sent, _) = wallet.sent_and_received(&tx);
+        let change: u64 = tx
+            .output
+            .iter()
+            .filter(|txout| txout.script_pubkey != script)
+            .map(|txout| txout.value)
+            .sum();
+        assert!(change > 0);
+        assert_eq!(change, sent - 3000);
+
+        // Amount minus fee is dust (or negative): refused, instead of paying it all to the miners
+        let res = policy.spend(
+            &mut wallet,
+            address,
+            Amount::CustomSubtractFee(400),
+            "Testing",
+            FeeRate::from_sat_per_vb(2.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        );
+        assert!(matches!(
+            res,
+            Err(policy::Error::FeeExceedsAmount { amount: 400, .. })
+                | Err(policy::Error::BdkCreateTx(..))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_policy_bump_fee() -> Result<()> {
+        let mnemonic_a: Mnemonic = Mnemonic::from_str(MNEMONIC_A)?;
+        let seed_a: Seed = Seed::from_mnemonic(mnemonic_a);
+        let desc_a: DescriptorPublicKey =
+            seed_a.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let mnemonic_b: Mnemonic = Mnemonic::from_str(MNEMONIC_B)?;
+        let seed_b: Seed = Seed::from_mnemonic(mnemonic_b);
+        let desc_b: DescriptorPublicKey =
+            seed_b.to_descriptor(Purpose::BIP86, Some(7291640), false, NETWORK, &SECP256K1)?;
+
+        let template = PolicyTemplate::multisig(2, vec![desc_a, desc_b]);
+        let policy: Policy = Policy::from_template("Name", "Description", template, NETWORK)?
+            .with_derived_change_descriptor()?;
+        let descriptor: String = policy.as_descriptor().to_string();
+        let change_descriptor: String = policy.change_descriptor().unwrap().to_string();
+
+        let mut wallet = get_funded_wallet_with_change(&descriptor, Some(&change_descriptor))?;
+        let original: Proposal = policy.spend(
+            &mut wallet,
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?,
+            Amount::Custom(1120),
+            "Testing",
+            FeeRate::from_sat_per_vb(1.0),
+            None,
+            None,
+            None,
+            CoinSelection::default(),
+        )?;
+        let original_tx: Transaction = original.psbt().unsigned_tx;
+        let original_fee: u64 = original.fee()?;
+        let txid = original_tx.txid();
+
+        // Unknown transaction
+        assert!(matches!(
+            policy.bump_fee(
+                &mut wallet,
+                txid,
+                "Bump",
+                FeeRate::from_sat_per_vb(2.0),
+                None
+            ),
+            Err(policy::Error::TxNotFound)
+        ));
+
+        wallet.insert_tx(
+            original_tx.clone(),
+            ConfirmationTime::Unconfirmed { last_seen: 0 },
+        )?;
+
+        // Same inputs and recipient, higher fee
+        let bumped: Proposal = policy.bump_fee(
+            &mut wallet,
+            txid,
+            "Bump",
+            FeeRate::from_sat_per_vb(2.0),
+            None,
+        )?;
+        let bumped_tx: Transaction = bumped.psbt().unsigned_tx;
+        for txin in original_tx.input.iter() {
+            assert!(bumped_tx
+                .input
+                .iter()
+                .any(|i| i.previous_output == txin.previous_output));
+        }
+        assert!(bumped.fee()? > original_fee);
+        match bumped {
+            Proposal::Spending {
+                to_address, amount, ..
+            } => {
+                assert_eq!(
+                    to_address,
+                    Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")?
+                );
+                assert_eq!(amount, 1120);
+            }
+            _ => panic!("expected a spending proposal"),
+        }
+
+        // The replacement must pay a higher rate
+        assert!(matches!(
+            policy.bump_fee(
+                &mut wallet,
+                txid,
+                "Bump",
+                FeeRate::from_sat_per_vb(1.0),
+                None
+            ),
+            Err(policy::Error::BdkCreateTx(..))
+        ));
+
+        // Confirmed transactions can't be replaced
+        wallet.insert_tx(
+            original_tx,
+            ConfirmationTime::Confirmed {
+                height: 2_520_450,
+                time: 1696282740,
+            },
+        )?;
+        assert!(matches!(
+            policy.bump_fee(
+                &mut wallet,
+                txid,
+                "Bump",
+                FeeRate::from_sat_per_vb(5.0),
+                None
+            ),
+            Err(policy::Error::TxAlreadyConfirmed)
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_1_of_3_multisig() {
         let network = Network::Testnet;
@@ -293,6 +773,7 @@ mod tests {
                 None,
                 None,
                 None,
+                CoinSelection::default(),
             )
             .unwrap();
 
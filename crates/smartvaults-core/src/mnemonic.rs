@@ -0,0 +1,124 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Multi-language BIP39 mnemonics
+
+use keechain_core::bips::bip39::{self, Language, Mnemonic};
+use keechain_core::types::WordCount;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    BIP39(#[from] bip39::Error),
+    #[error("mnemonic not valid in any supported language")]
+    UnknownLanguage,
+    #[error("mnemonic valid in multiple languages: {}", .0.iter().map(|l| language_name(*l)).collect::<Vec<_>>().join(", "))]
+    AmbiguousLanguage(Vec<Language>),
+}
+
+/// Supported wordlists
+pub fn languages() -> &'static [Language] {
+    Language::all()
+}
+
+pub fn language_name(language: Language) -> &'static str {
+    match language {
+        Language::English => "english",
+        Language::SimplifiedChinese => "simplified-chinese",
+        Language::TraditionalChinese => "traditional-chinese",
+        Language::Czech => "czech",
+        Language::French => "french",
+        Language::Italian => "italian",
+        Language::Japanese => "japanese",
+        Language::Korean => "korean",
+        Language::Spanish => "spanish",
+    }
+}
+
+/// Generate a new mnemonic using the wordlist of `language`
+pub fn generate(word_count: WordCount, language: Language) -> Result<Mnemonic, Error> {
+    let entropy: Vec<u8> = bip39::entropy(word_count, None);
+    Ok(Mnemonic::from_entropy_in(language, &entropy)?)
+}
+
+/// Parse a mnemonic
+///
+/// If `language` is `None`, the wordlist is detected by validating the whole phrase (checksum
+/// included) against every supported wordlist, so words shared by multiple wordlists don't
+/// lead to a wrong detection.
+pub fn parse<S>(mnemonic: S, language: Option<Language>) -> Result<Mnemonic, Error>
+where
+    S: AsRef<str>,
+{
+    let mnemonic: &str = mnemonic.as_ref();
+
+    if let Some(language) = language {
+        return Ok(Mnemonic::parse_in(language, mnemonic)?);
+    }
+
+    let mut candidates: Vec<Mnemonic> = languages()
+        .iter()
+        .filter_map(|language| Mnemonic::parse_in(*language, mnemonic).ok())
+        .collect();
+
+    match candidates.len() {
+        0 => Err(Error::UnknownLanguage),
+        1 => Ok(candidates.remove(0)),
+        _ => Err(Error::AmbiguousLanguage(
+            candidates.into_iter().map(|m| m.language()).collect(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPANISH: &str =
+        "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco abierto";
+    const JAPANESE: &str = "あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あいこくしん あおぞら";
+
+    #[test]
+    fn test_detect_language() {
+        let mnemonic = parse(SPANISH, None).unwrap();
+        assert_eq!(mnemonic.language(), Language::Spanish);
+
+        let mnemonic = parse(JAPANESE, None).unwrap();
+        assert_eq!(mnemonic.language(), Language::Japanese);
+
+        // Explicit language
+        assert!(parse(SPANISH, Some(Language::Spanish)).is_ok());
+        assert!(parse(SPANISH, Some(Language::English)).is_err());
+
+        // Invalid checksum
+        assert!(matches!(
+            parse(
+                "ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco ábaco",
+                None
+            ),
+            Err(Error::UnknownLanguage)
+        ));
+    }
+
+    #[test]
+    fn test_shared_words() {
+        // `abandon` is in both English and French wordlists
+        let mnemonic = parse(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            None,
+        )
+        .unwrap();
+        assert_eq!(mnemonic.language(), Language::English);
+    }
+
+    #[test]
+    fn test_generate() {
+        for language in languages() {
+            let mnemonic = generate(WordCount::W12, *language).unwrap();
+            assert_eq!(mnemonic.word_count(), 12);
+            let parsed = parse(mnemonic.to_string(), Some(*language)).unwrap();
+            assert_eq!(parsed, mnemonic);
+        }
+    }
+}
@@ -0,0 +1,350 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Descriptor analysis
+//!
+//! Review what a descriptor commits to (keys, timelocks, spending paths) before saving the policy.
+
+use core::fmt;
+use core::str::FromStr;
+use std::collections::{BTreeMap, BTreeSet};
+
+use bdk::descriptor::policy::SatisfiableItem;
+use keechain_core::bitcoin::bip32::{DerivationPath, ExtendedPubKey, Fingerprint};
+use keechain_core::bitcoin::{absolute, relative, Network, Sequence};
+use keechain_core::miniscript::descriptor::DescriptorType;
+use keechain_core::miniscript::{
+    Descriptor, DescriptorPublicKey, ForEachKey, Miniscript, MiniscriptKey, Tap,
+};
+
+use super::{extract_spending_policy, template_match, Error, Policy, PolicyTemplateType};
+
+/// Average block interval (seconds)
+const BLOCK_INTERVAL: u64 = 600;
+/// Weight of a taproot key path satisfaction (schnorr signature with sighash byte)
+const KEY_PATH_WEIGHT: usize = 1 + 65;
+/// Taproot control block size without the merkle path
+const TAPROOT_CONTROL_BASE_SIZE: usize = 33;
+const TAPROOT_CONTROL_NODE_SIZE: usize = 32;
+
+/// Key of the descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyzedKey {
+    pub key: DescriptorPublicKey,
+    pub fingerprint: Fingerprint,
+    /// Full derivation path from the master key (`None` for multi-path keys)
+    pub origin: Option<DerivationPath>,
+}
+
+/// Timelock of the descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timelock {
+    Absolute(absolute::LockTime),
+    Relative(Sequence),
+}
+
+impl fmt::Display for Timelock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(absolute::LockTime::Blocks(height)) => {
+                write!(f, "absolute: from block height {height}")
+            }
+            Self::Absolute(absolute::LockTime::Seconds(time)) => {
+                write!(f, "absolute: from unix time {time}")
+            }
+            Self::Relative(sequence) => match sequence.to_relative_lock_time() {
+                Some(relative::LockTime::Blocks(blocks)) => {
+                    let blocks: u64 = blocks.value() as u64;
+                    write!(
+                        f,
+                        "relative: {blocks} blocks after the coins confirmation (~{})",
+                        human_duration(blocks * BLOCK_INTERVAL)
+                    )
+                }
+                Some(relative::LockTime::Time(time)) => write!(
+                    f,
+                    "relative: {} after the coins confirmation",
+                    human_duration(time.value() as u64 * 512)
+                ),
+                None => write!(f, "relative: invalid sequence {sequence}"),
+            },
+        }
+    }
+}
+
+/// Spending path of the descriptor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendingPath {
+    pub description: String,
+    /// Maximum satisfaction weight (`None` if can't be computed)
+    pub max_satisfaction_weight: Option<usize>,
+}
+
+/// Problem found in the descriptor
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PolicyProblem {
+    /// Only taproot descriptors can be saved as policy
+    NotTaproot,
+    /// The same key appears more than once
+    DuplicateKey(DescriptorPublicKey),
+    /// Keys derived from the same extended key appear in different places
+    ReusedExtendedKey {
+        xpub: ExtendedPubKey,
+        keys: Vec<DescriptorPublicKey>,
+    },
+}
+
+impl fmt::Display for PolicyProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotTaproot => write!(f, "not a taproot descriptor"),
+            Self::DuplicateKey(key) => write!(f, "duplicate key {key}"),
+            Self::ReusedExtendedKey { xpub, keys } => write!(
+                f,
+                "{} keys derived from the same extended key {xpub}",
+                keys.len()
+            ),
+        }
+    }
+}
+
+/// Analysis of a descriptor
+#[derive(Debug, Clone)]
+pub struct PolicyAnalysis {
+    pub descriptor: Descriptor<String>,
+    pub keys: Vec<AnalyzedKey>,
+    pub timelocks: Vec<Timelock>,
+    /// Threshold structure (`None` if the spending policy can't be extracted)
+    pub tree: Option<SatisfiableItem>,
+    pub template: Option<PolicyTemplateType>,
+    pub spending_paths: Vec<SpendingPath>,
+    pub problems: Vec<PolicyProblem>,
+}
+
+impl PolicyAnalysis {
+    /// Analyze a descriptor of any type (nothing is saved)
+    pub fn new(descriptor: &Descriptor<String>, network: Network) -> Result<Self, Error> {
+        let desc: Descriptor<DescriptorPublicKey> = Descriptor::from_str(&descriptor.to_string())?;
+        let mut problems: Vec<PolicyProblem> = Vec::new();
+
+        if descriptor.desc_type() != DescriptorType::Tr {
+            problems.push(PolicyProblem::NotTaproot);
+        }
+
+        // Keys
+        let mut occurrences: Vec<DescriptorPublicKey> = Vec::new();
+        desc.for_each_key(|key| {
+            occurrences.push(key.clone());
+            true
+        });
+        let mut keys: Vec<AnalyzedKey> = Vec::new();
+        let mut duplicates: BTreeSet<DescriptorPublicKey> = BTreeSet::new();
+        let mut xpubs: BTreeMap<ExtendedPubKey, BTreeSet<DescriptorPublicKey>> = BTreeMap::new();
+        for key in occurrences.into_iter() {
+            if keys.iter().any(|k| k.key == key) {
+                duplicates.insert(key);
+                continue;
+            }
+            let xpub: Option<ExtendedPubKey> = match &key {
+                DescriptorPublicKey::XPub(xpub) => Some(xpub.xkey),
+                DescriptorPublicKey::MultiXPub(xpub) => Some(xpub.xkey),
+                DescriptorPublicKey::Single(..) => None,
+            };
+            if let Some(xpub) = xpub {
+                xpubs.entry(xpub).or_default().insert(key.clone());
+            }
+            keys.push(AnalyzedKey {
+                fingerprint: key.master_fingerprint(),
+                origin: key.full_derivation_path(),
+                key,
+            });
+        }
+        problems.extend(duplicates.into_iter().map(PolicyProblem::DuplicateKey));
+        problems.extend(xpubs.into_iter().filter(|(_, keys)| keys.len() > 1).map(
+            |(xpub, keys)| PolicyProblem::ReusedExtendedKey {
+                xpub,
+                keys: keys.into_iter().collect(),
+            },
+        ));
+
+        // Threshold structure, timelocks and template
+        let tree: Option<SatisfiableItem> =
+            extract_spending_policy(descriptor, network)?.map(|policy| policy.item);
+        let mut timelocks: Vec<Timelock> = Vec::new();
+        if let Some(item) = &tree {
+            collect_timelocks(item, &mut timelocks);
+        }
+        let template: Option<PolicyTemplateType> = tree.as_ref().and_then(template_match);
+
+        // Spending paths
+        let spending_paths: Vec<SpendingPath> = match &desc {
+            Descriptor::Tr(tr) => {
+                let mut paths: Vec<SpendingPath> = vec![SpendingPath {
+                    description: format!("key path: {}", tr.internal_key()),
+                    max_satisfaction_weight: Some(KEY_PATH_WEIGHT),
+                }];
+                for (depth, ms) in tr.iter_scripts() {
+                    paths.push(SpendingPath {
+                        description: format!("script path: {ms}"),
+                        max_satisfaction_weight: script_path_weight(depth, ms),
+                    });
+                }
+                paths
+            }
+            desc => vec![SpendingPath {
+                description: format!("{:?}", desc.desc_type()),
+                max_satisfaction_weight: desc.max_weight_to_satisfy().ok(),
+            }],
+        };
+
+        Ok(Self {
+            descriptor: descriptor.clone(),
+            keys,
+            timelocks,
+            tree,
+            template,
+            spending_paths,
+            problems,
+        })
+    }
+
+    /// Analyze a descriptor or a miniscript policy (compiled to taproot)
+    pub fn from_desc_or_policy<S>(desc_or_policy: S, network: Network) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let desc_or_policy: &str = desc_or_policy.as_ref();
+        match Descriptor::<String>::from_str(desc_or_policy) {
+            Ok(descriptor) => Self::new(&descriptor, network),
+            Err(desc_e) => match Policy::from_policy("", "", desc_or_policy, network) {
+                Ok(policy) => policy.analyze(network),
+                Err(policy_e) => Err(Error::DescOrPolicy(
+                    Box::new(desc_e.into()),
+                    Box::new(policy_e),
+                )),
+            },
+        }
+    }
+
+    /// Check if any problem was found
+    pub fn has_problems(&self) -> bool {
+        !self.problems.is_empty()
+    }
+}
+
+fn collect_timelocks(item: &SatisfiableItem, timelocks: &mut Vec<Timelock>) {
+    let timelock: Option<Timelock> = match item {
+        SatisfiableItem::AbsoluteTimelock { value } => Some(Timelock::Absolute(*value)),
+        SatisfiableItem::RelativeTimelock { value } => Some(Timelock::Relative(*value)),
+        SatisfiableItem::Thresh { items, .. } => {
+            for x in items.iter() {
+                collect_timelocks(&x.item, timelocks);
+            }
+            None
+        }
+        _ => None,
+    };
+    if let Some(timelock) = timelock {
+        if !timelocks.contains(&timelock) {
+            timelocks.push(timelock);
+        }
+    }
+}
+
+/// Maximum satisfaction weight of a taproot script path (witness stack, script and control block)
+fn script_path_weight<Pk>(depth: u8, ms: &Miniscript<Pk, Tap>) -> Option<usize>
+where
+    Pk: MiniscriptKey,
+{
+    let script_size: usize = ms.script_size();
+    let max_sat_elems: usize = ms.max_satisfaction_witness_elements().ok()?;
+    let max_sat_size: usize = ms.max_satisfaction_size().ok()?;
+    let control_block_size: usize =
+        TAPROOT_CONTROL_BASE_SIZE + TAPROOT_CONTROL_NODE_SIZE * depth as usize;
+    // +2 stack items: script and control block
+    Some(
+        varint_len(max_sat_elems + 2) - varint_len(0)
+            + varint_len(script_size)
+            + script_size
+            + varint_len(control_block_size)
+            + control_block_size
+            + max_sat_size,
+    )
+}
+
+fn varint_len(n: usize) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffffffff => 5,
+        _ => 9,
+    }
+}
+
+fn human_duration(seconds: u64) -> String {
+    let days: u64 = seconds / 86_400;
+    let hours: u64 = seconds % 86_400 / 3_600;
+    match (days, hours) {
+        (0, 0) => format!("{} minutes", seconds / 60),
+        (0, hours) => format!("{hours} hours"),
+        (days, 0) => format!("{days} days"),
+        (days, hours) => format!("{days} days {hours} hours"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETWORK: Network = Network::Testnet;
+
+    #[test]
+    fn test_analyze_decaying() {
+        let desc = "tr(56f05264c005e2a2f6e261996ed2cd904dfafbc6d75cc07a5a76d46df56e6ff9,thresh(2,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*),s:pk([4eb5d5a1/86'/1'/784923']tpubDCLskGdzStPPo1auRQygJUfbmLMwujWr7fmekdUMD7gqSpwEcRso4CfiP5GkRqfXFYkfqTujyvuehb7inymMhBJFdbJqFyHsHVRuwLKCSe9/0/*),s:pk([f3ab64d8/86'/1'/784923']tpubDCh4uyVDVretfgTNkazUarV9ESTh7DJy8yvMSuWn5PQFbTDEsJwHGSBvTrNF92kw3x5ZLFXw91gN5LYtuSCbr1Vo6mzQmD49sF2vGpReZp2/0/*),snl:after(1698947462)))#2263dhaf";
+        let analysis = PolicyAnalysis::from_desc_or_policy(desc, NETWORK).unwrap();
+
+        assert_eq!(analysis.keys.len(), 4);
+        assert!(analysis
+            .keys
+            .iter()
+            .any(|k| k.fingerprint == Fingerprint::from_str("7356e457").unwrap()));
+        assert_eq!(
+            analysis.timelocks,
+            vec![Timelock::Absolute(absolute::LockTime::from_consensus(
+                1698947462
+            ))]
+        );
+        assert_eq!(analysis.template, Some(PolicyTemplateType::Decaying));
+        assert_eq!(analysis.spending_paths.len(), 2);
+        assert!(analysis
+            .spending_paths
+            .iter()
+            .all(|p| p.max_satisfaction_weight.is_some()));
+        assert!(!analysis.has_problems());
+    }
+
+    #[test]
+    fn test_analyze_problems() {
+        // Keys derived from the same xpub
+        let desc = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/1/*))";
+        let analysis = PolicyAnalysis::from_desc_or_policy(desc, NETWORK).unwrap();
+        assert!(matches!(
+            analysis.problems.as_slice(),
+            [PolicyProblem::ReusedExtendedKey { keys, .. }] if keys.len() == 2
+        ));
+
+        // Not taproot
+        let desc = "wsh(multi(1,[9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,[7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))";
+        let analysis = PolicyAnalysis::from_desc_or_policy(desc, NETWORK).unwrap();
+        assert_eq!(analysis.problems, vec![PolicyProblem::NotTaproot]);
+        assert_eq!(analysis.keys.len(), 2);
+        assert_eq!(analysis.spending_paths.len(), 1);
+    }
+
+    #[test]
+    fn test_human_duration() {
+        assert_eq!(human_duration(144 * BLOCK_INTERVAL), "1 days");
+        assert_eq!(human_duration(3_600 * 30), "1 days 6 hours");
+        assert_eq!(human_duration(1_800), "30 minutes");
+    }
+}
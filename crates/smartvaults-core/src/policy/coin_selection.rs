@@ -0,0 +1,124 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Coin selection
+
+use bdk::chain::ConfirmationTime;
+use bdk::wallet::coin_selection::{
+    BranchAndBoundCoinSelection, CoinSelectionAlgorithm, CoinSelectionResult,
+    DefaultCoinSelectionAlgorithm, Error, LargestFirstCoinSelection,
+};
+use bdk::{FeeRate, Utxo, WeightedUtxo};
+use keechain_core::bitcoin::Script;
+
+use crate::CoinSelection;
+
+/// Coin selection algorithm used by [`Policy::spend`](super::Policy::spend)
+///
+/// Map the [`CoinSelection`] strategy to the BDK implementations and,
+/// if `min_confirmations` is set, exclude the UTXOs that not satisfy the relative timelock.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SmartVaultsCoinSelection {
+    strategy: CoinSelection,
+    current_height: u32,
+    min_confirmations: Option<u32>,
+}
+
+impl SmartVaultsCoinSelection {
+    pub fn new(strategy: CoinSelection, current_height: u32) -> Self {
+        Self {
+            strategy,
+            current_height,
+            min_confirmations: None,
+        }
+    }
+
+    pub fn min_confirmations(self, min_confirmations: Option<u32>) -> Self {
+        Self {
+            min_confirmations,
+            ..self
+        }
+    }
+
+    fn is_timelock_satisfied(&self, utxo: &WeightedUtxo) -> bool {
+        match (self.min_confirmations, &utxo.utxo) {
+            (Some(min_confirmations), Utxo::Local(local)) => is_relative_timelock_satisfied(
+                &local.confirmation_time,
+                self.current_height,
+                min_confirmations,
+            ),
+            _ => true,
+        }
+    }
+}
+
+/// Check if an UTXO has at least `min_confirmations`
+pub(crate) fn is_relative_timelock_satisfied(
+    confirmation_time: &ConfirmationTime,
+    current_height: u32,
+    min_confirmations: u32,
+) -> bool {
+    match confirmation_time {
+        ConfirmationTime::Confirmed { height, .. } => {
+            current_height.saturating_sub(*height) >= min_confirmations
+        }
+        ConfirmationTime::Unconfirmed { .. } => false,
+    }
+}
+
+impl CoinSelectionAlgorithm for SmartVaultsCoinSelection {
+    fn coin_select(
+        &self,
+        required_utxos: Vec<WeightedUtxo>,
+        optional_utxos: Vec<WeightedUtxo>,
+        fee_rate: FeeRate,
+        target_amount: u64,
+        drain_script: &Script,
+    ) -> Result<CoinSelectionResult, Error> {
+        let optional_utxos: Vec<WeightedUtxo> = optional_utxos
+            .into_iter()
+            .filter(|utxo| self.is_timelock_satisfied(utxo))
+            .collect();
+
+        match self.strategy {
+            CoinSelection::Default => DefaultCoinSelectionAlgorithm::default().coin_select(
+                required_utxos,
+                optional_utxos,
+                fee_rate,
+                target_amount,
+                drain_script,
+            ),
+            CoinSelection::Economical => BranchAndBoundCoinSelection::default().coin_select(
+                required_utxos,
+                optional_utxos,
+                fee_rate,
+                target_amount,
+                drain_script,
+            ),
+            CoinSelection::Consolidate => {
+                let mut required_utxos = required_utxos;
+                required_utxos.extend(optional_utxos);
+                LargestFirstCoinSelection.coin_select(
+                    required_utxos,
+                    Vec::new(),
+                    fee_rate,
+                    target_amount,
+                    drain_script,
+                )
+            }
+            CoinSelection::PrivacyFirst => {
+                // Never link UTXOs: only the largest one can be selected
+                let largest: Option<WeightedUtxo> = optional_utxos
+                    .into_iter()
+                    .max_by_key(|utxo| utxo.utxo.txout().value);
+                LargestFirstCoinSelection.coin_select(
+                    required_utxos,
+                    largest.into_iter().collect(),
+                    fee_rate,
+                    target_amount,
+                    drain_script,
+                )
+            }
+        }
+    }
+}
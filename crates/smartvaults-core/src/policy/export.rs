@@ -0,0 +1,451 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Policy export for third-party analyzers
+//!
+//! Stable JSON representation of the spending policy (the satisfiable-item tree), for the tools
+//! that can't link this crate. Unlike the BDK serde, it doesn't follow the BDK and miniscript
+//! internals: node IDs and satisfaction data are not exported and the node types are normalized
+//! (ex. a `multi_a` is a `threshold` of `signature` nodes).
+//!
+//! The schema is in `doc/policy-analysis.schema.json` (see [`analysis_json_schema`]): breaking
+//! changes must bump [`ANALYSIS_SCHEMA_VERSION`].
+
+use bdk::descriptor::policy::{PkOrF, SatisfiableItem};
+use keechain_core::bitcoin::bip32::ChildNumber;
+use keechain_core::bitcoin::{absolute, relative, Network};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::key_origin::KeyOriginInfo;
+use super::{Error, Policy};
+
+/// Version of the export schema
+pub const ANALYSIS_SCHEMA_VERSION: u32 = 1;
+
+/// Policy export
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyAnalysisExport {
+    /// Schema version
+    pub version: u32,
+    /// `bitcoin`, `testnet`, `signet` or `regtest`
+    pub network: String,
+    /// Descriptor, with checksum
+    pub descriptor: String,
+    /// Keys of the descriptor: the internal key first, then the script keys in order of
+    /// appearance (duplicates included)
+    pub keys: Vec<ExportedKey>,
+    /// Root of the spending policy
+    pub policy: PolicyNode,
+}
+
+/// Key of the descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedKey {
+    /// Descriptor key
+    pub key: String,
+    /// Master fingerprint (`None` if the key has no origin info)
+    pub fingerprint: Option<String>,
+    /// Derivation path from the master key (ex. `m/86'/1'/784923'`)
+    pub derivation_path: Option<String>,
+    /// BIP43 purpose (ex. `86`): `None` if not hardened or without origin info
+    pub purpose: Option<u32>,
+    /// Taproot internal key (key path spend)
+    pub internal: bool,
+    /// The coin type of the derivation path is not the one of the network
+    pub network_mismatch: bool,
+}
+
+impl From<KeyOriginInfo> for ExportedKey {
+    fn from(origin: KeyOriginInfo) -> Self {
+        let purpose: Option<u32> =
+            origin
+                .derivation_path
+                .as_ref()
+                .and_then(|path| match path.into_iter().next()? {
+                    ChildNumber::Hardened { index } => Some(*index),
+                    ChildNumber::Normal { .. } => None,
+                });
+        Self {
+            key: origin.key.to_string(),
+            fingerprint: origin.fingerprint.map(|f| f.to_string()),
+            derivation_path: origin.derivation_path.map(|p| p.to_string()),
+            purpose,
+            internal: origin.internal,
+            network_mismatch: origin.network_mismatch,
+        }
+    }
+}
+
+/// Key required by a `signature` node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRef {
+    /// Master fingerprint (the one of the extended key itself, if without origin info)
+    Fingerprint(String),
+    /// Public key (single keys)
+    PublicKey(String),
+}
+
+impl From<&PkOrF> for KeyRef {
+    fn from(key: &PkOrF) -> Self {
+        match key {
+            PkOrF::Pubkey(pk) => Self::PublicKey(pk.to_string()),
+            PkOrF::XOnlyPubkey(pk) => Self::PublicKey(pk.to_string()),
+            PkOrF::Fingerprint(f) => Self::Fingerprint(f.to_string()),
+        }
+    }
+}
+
+/// Semantic of a `threshold` node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdOperator {
+    /// All the items (`threshold` == number of items)
+    And,
+    /// Any of the items (`threshold` == 1)
+    Or,
+    /// Any `threshold` of the items
+    KOfN,
+}
+
+impl ThresholdOperator {
+    fn new(threshold: usize, items: usize) -> Self {
+        if threshold == items {
+            Self::And
+        } else if threshold == 1 {
+            Self::Or
+        } else {
+            Self::KOfN
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbsoluteTimelockUnit {
+    /// Block height
+    Height,
+    /// Unix timestamp (seconds), compared with the median time past
+    Timestamp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeTimelockUnit {
+    /// Blocks after the coins confirmation
+    Blocks,
+    /// Seconds after the coins confirmation (multiple of 512)
+    Seconds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    Sha256,
+    Hash256,
+    Ripemd160,
+    Hash160,
+}
+
+/// Node of the spending policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PolicyNode {
+    /// Signature of a key
+    Signature { key: KeyRef },
+    /// Satisfied by `threshold` of the `items`
+    Threshold {
+        operator: ThresholdOperator,
+        threshold: usize,
+        items: Vec<PolicyNode>,
+    },
+    /// Spendable from the block height or the timestamp
+    AbsoluteTimelock {
+        unit: AbsoluteTimelockUnit,
+        value: u32,
+    },
+    /// Spendable after the blocks or the seconds since the coins confirmation
+    RelativeTimelock {
+        unit: RelativeTimelockUnit,
+        value: u32,
+    },
+    /// Preimage of the hash
+    HashPreimage {
+        algorithm: HashAlgorithm,
+        hash: String,
+    },
+}
+
+impl From<&SatisfiableItem> for PolicyNode {
+    fn from(item: &SatisfiableItem) -> Self {
+        match item {
+            SatisfiableItem::EcdsaSignature(key) | SatisfiableItem::SchnorrSignature(key) => {
+                Self::Signature { key: key.into() }
+            }
+            SatisfiableItem::Sha256Preimage { hash } => Self::HashPreimage {
+                algorithm: HashAlgorithm::Sha256,
+                hash: hash.to_string(),
+            },
+            SatisfiableItem::Hash256Preimage { hash } => Self::HashPreimage {
+                algorithm: HashAlgorithm::Hash256,
+                hash: hash.to_string(),
+            },
+            SatisfiableItem::Ripemd160Preimage { hash } => Self::HashPreimage {
+                algorithm: HashAlgorithm::Ripemd160,
+                hash: hash.to_string(),
+            },
+            SatisfiableItem::Hash160Preimage { hash } => Self::HashPreimage {
+                algorithm: HashAlgorithm::Hash160,
+                hash: hash.to_string(),
+            },
+            SatisfiableItem::AbsoluteTimelock { value } => match value {
+                absolute::LockTime::Blocks(height) => Self::AbsoluteTimelock {
+                    unit: AbsoluteTimelockUnit::Height,
+                    value: height.to_consensus_u32(),
+                },
+                absolute::LockTime::Seconds(time) => Self::AbsoluteTimelock {
+                    unit: AbsoluteTimelockUnit::Timestamp,
+                    value: time.to_consensus_u32(),
+                },
+            },
+            SatisfiableItem::RelativeTimelock { value } => match value.to_relative_lock_time() {
+                Some(relative::LockTime::Time(time)) => Self::RelativeTimelock {
+                    unit: RelativeTimelockUnit::Seconds,
+                    value: time.value() as u32 * 512,
+                },
+                Some(relative::LockTime::Blocks(blocks)) => Self::RelativeTimelock {
+                    unit: RelativeTimelockUnit::Blocks,
+                    value: blocks.value() as u32,
+                },
+                None => Self::RelativeTimelock {
+                    unit: RelativeTimelockUnit::Blocks,
+                    value: value.to_consensus_u32(),
+                },
+            },
+            SatisfiableItem::Multisig { keys, threshold } => Self::Threshold {
+                operator: ThresholdOperator::new(*threshold, keys.len()),
+                threshold: *threshold,
+                items: keys
+                    .iter()
+                    .map(|key| Self::Signature { key: key.into() })
+                    .collect(),
+            },
+            SatisfiableItem::Thresh { items, threshold } => Self::Threshold {
+                operator: ThresholdOperator::new(*threshold, items.len()),
+                threshold: *threshold,
+                items: items.iter().map(|x| Self::from(&x.item)).collect(),
+            },
+        }
+    }
+}
+
+impl PolicyAnalysisExport {
+    pub(crate) fn new(policy: &Policy, network: Network) -> Result<Self, Error> {
+        let mut keys: Vec<ExportedKey> = policy
+            .key_origins(network)?
+            .into_iter()
+            .map(ExportedKey::from)
+            .collect();
+        // Stable: the script keys keep their order
+        keys.sort_by_key(|k| !k.internal);
+
+        Ok(Self {
+            version: ANALYSIS_SCHEMA_VERSION,
+            network: network.to_string(),
+            descriptor: policy.as_descriptor().to_string(),
+            keys,
+            policy: PolicyNode::from(policy.satisfiable_item()?),
+        })
+    }
+}
+
+/// JSON schema of [`PolicyAnalysisExport`]
+pub fn analysis_json_schema() -> Value {
+    let key: Value = json!({
+        "type": "object",
+        "required": ["key", "fingerprint", "derivation_path", "purpose", "internal", "network_mismatch"],
+        "additionalProperties": false,
+        "properties": {
+            "key": {
+                "description": "Descriptor key",
+                "type": "string"
+            },
+            "fingerprint": {
+                "description": "Master fingerprint (null if the key has no origin info)",
+                "type": ["string", "null"],
+                "pattern": "^[0-9a-f]{8}$"
+            },
+            "derivation_path": {
+                "description": "Derivation path from the master key (null if the key has no origin info)",
+                "type": ["string", "null"]
+            },
+            "purpose": {
+                "description": "BIP43 purpose (null if not hardened or without origin info)",
+                "type": ["integer", "null"],
+                "minimum": 0
+            },
+            "internal": {
+                "description": "Taproot internal key (key path spend)",
+                "type": "boolean"
+            },
+            "network_mismatch": {
+                "description": "The coin type of the derivation path is not the one of the network",
+                "type": "boolean"
+            }
+        }
+    });
+
+    let key_ref: Value = json!({
+        "description": "Key required by a signature: master fingerprint (the one of the extended key itself, if without origin info) or public key (single keys)",
+        "oneOf": [
+            {
+                "type": "object",
+                "required": ["fingerprint"],
+                "additionalProperties": false,
+                "properties": {
+                    "fingerprint": { "type": "string", "pattern": "^[0-9a-f]{8}$" }
+                }
+            },
+            {
+                "type": "object",
+                "required": ["public_key"],
+                "additionalProperties": false,
+                "properties": {
+                    "public_key": { "type": "string", "pattern": "^[0-9a-f]{64}([0-9a-f]{2})?$" }
+                }
+            }
+        ]
+    });
+
+    let signature: Value = json!({
+        "description": "Signature of a key",
+        "type": "object",
+        "required": ["type", "key"],
+        "additionalProperties": false,
+        "properties": {
+            "type": { "const": "signature" },
+            "key": { "$ref": "#/$defs/key_ref" }
+        }
+    });
+
+    let threshold: Value = json!({
+        "description": "Satisfied by `threshold` of the `items`: `and` if all of them are required, `or` if any of them, `k_of_n` otherwise",
+        "type": "object",
+        "required": ["type", "operator", "threshold", "items"],
+        "additionalProperties": false,
+        "properties": {
+            "type": { "const": "threshold" },
+            "operator": { "enum": ["and", "or", "k_of_n"] },
+            "threshold": { "type": "integer", "minimum": 1 },
+            "items": {
+                "type": "array",
+                "minItems": 1,
+                "items": { "$ref": "#/$defs/node" }
+            }
+        }
+    });
+
+    let absolute_timelock: Value = json!({
+        "description": "Spendable from the block height or the unix timestamp (compared with the median time past)",
+        "type": "object",
+        "required": ["type", "unit", "value"],
+        "additionalProperties": false,
+        "properties": {
+            "type": { "const": "absolute_timelock" },
+            "unit": { "enum": ["height", "timestamp"] },
+            "value": { "type": "integer", "minimum": 0 }
+        }
+    });
+
+    let relative_timelock: Value = json!({
+        "description": "Spendable after the blocks or the seconds (multiple of 512) since the coins confirmation",
+        "type": "object",
+        "required": ["type", "unit", "value"],
+        "additionalProperties": false,
+        "properties": {
+            "type": { "const": "relative_timelock" },
+            "unit": { "enum": ["blocks", "seconds"] },
+            "value": { "type": "integer", "minimum": 0 }
+        }
+    });
+
+    let hash_preimage: Value = json!({
+        "description": "Preimage of the hash",
+        "type": "object",
+        "required": ["type", "algorithm", "hash"],
+        "additionalProperties": false,
+        "properties": {
+            "type": { "const": "hash_preimage" },
+            "algorithm": { "enum": ["sha256", "hash256", "ripemd160", "hash160"] },
+            "hash": { "type": "string", "pattern": "^[0-9a-f]+$" }
+        }
+    });
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Smart Vaults policy analysis",
+        "description": "Spending policy of a Smart Vaults vault",
+        "type": "object",
+        "required": ["version", "network", "descriptor", "keys", "policy"],
+        "additionalProperties": false,
+        "properties": {
+            "version": {
+                "description": "Schema version",
+                "const": ANALYSIS_SCHEMA_VERSION
+            },
+            "network": {
+                "enum": ["bitcoin", "testnet", "signet", "regtest"]
+            },
+            "descriptor": {
+                "description": "Taproot descriptor, with checksum",
+                "type": "string"
+            },
+            "keys": {
+                "description": "Keys of the descriptor: the internal key first, then the script keys in order of appearance (duplicates included)",
+                "type": "array",
+                "items": { "$ref": "#/$defs/key" }
+            },
+            "policy": {
+                "description": "Root of the spending policy",
+                "$ref": "#/$defs/node"
+            }
+        },
+        "$defs": {
+            "key": key,
+            "key_ref": key_ref,
+            "node": {
+                "oneOf": [signature, threshold, absolute_timelock, relative_timelock, hash_preimage]
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analysis_json_schema_file() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../doc/policy-analysis.schema.json"
+        );
+        let schema: Value = analysis_json_schema();
+        // Regenerate with `UPDATE_GOLDEN=1 cargo test`
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            let json: String = serde_json::to_string_pretty(&schema).unwrap();
+            std::fs::write(path, format!("{json}\n")).unwrap();
+        }
+        let file: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(file, schema, "schema changed: run with UPDATE_GOLDEN=1");
+    }
+
+    #[test]
+    fn test_threshold_operator() {
+        assert_eq!(ThresholdOperator::new(2, 2), ThresholdOperator::And);
+        assert_eq!(ThresholdOperator::new(1, 1), ThresholdOperator::And);
+        assert_eq!(ThresholdOperator::new(1, 3), ThresholdOperator::Or);
+        assert_eq!(ThresholdOperator::new(2, 3), ThresholdOperator::KOfN);
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Key origins
+//!
+//! Master fingerprint, derivation path and purpose of the descriptor keys, to reconstruct which
+//! signer (and which of its accounts) is behind each key of a policy.
+//!
+//! The purpose tells also which [`ScriptType`] a key is meant for: using a key derived for another
+//! script type is valid, but devices following the BIPs refuse to register or sign for the vault.
+
+use core::fmt;
+use core::str::FromStr;
+
+use keechain_core::bitcoin::bip32::{ChildNumber, DerivationPath, Fingerprint};
+use keechain_core::bitcoin::Network;
+use keechain_core::miniscript::{Descriptor, DescriptorPublicKey, ForEachKey};
+
+use super::Error;
+
+/// Purpose of a key, from the first step of its derivation path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPurpose {
+    /// BIP44 (P2PKH)
+    Bip44,
+    /// BIP48 (multisig), with the script type
+    Bip48 { script_type: Option<u32> },
+    /// BIP49 (P2SH-P2WPKH)
+    Bip49,
+    /// BIP84 (P2WPKH)
+    Bip84,
+    /// BIP86 (P2TR)
+    Bip86,
+    /// Unknown purpose (`None` if not hardened)
+    Other(Option<u32>),
+}
+
+impl fmt::Display for KeyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bip44 => write!(f, "BIP44 (P2PKH)"),
+            Self::Bip48 { script_type } => match script_type {
+                Some(1) => write!(f, "BIP48 (P2SH-P2WSH)"),
+                Some(2) => write!(f, "BIP48 (P2WSH)"),
+                Some(3) => write!(f, "BIP48 (P2TR)"),
+                Some(t) => write!(f, "BIP48 (script type {t})"),
+                None => write!(f, "BIP48"),
+            },
+            Self::Bip49 => write!(f, "BIP49 (P2SH-P2WPKH)"),
+            Self::Bip84 => write!(f, "BIP84 (P2WPKH)"),
+            Self::Bip86 => write!(f, "BIP86 (P2TR)"),
+            Self::Other(Some(purpose)) => write!(f, "unknown ({purpose}')"),
+            Self::Other(None) => write!(f, "unknown"),
+        }
+    }
+}
+
+impl KeyPurpose {
+    /// Purpose from the origin of the key (`None` if the key has no origin info)
+    pub fn from_key(key: &DescriptorPublicKey) -> Option<Self> {
+        let path: &DerivationPath = match key {
+            DescriptorPublicKey::Single(single) => &single.origin.as_ref()?.1,
+            DescriptorPublicKey::XPub(xpub) => &xpub.origin.as_ref()?.1,
+            DescriptorPublicKey::MultiXPub(xpub) => &xpub.origin.as_ref()?.1,
+        };
+        Self::from_path(path)
+    }
+
+    fn from_path(path: &DerivationPath) -> Option<Self> {
+        let mut steps = path.into_iter();
+        let purpose: Option<u32> = match steps.next()? {
+            ChildNumber::Hardened { index } => Some(*index),
+            ChildNumber::Normal { .. } => None,
+        };
+        Some(match purpose {
+            Some(44) => Self::Bip44,
+            Some(48) => Self::Bip48 {
+                // m/48'/<coin>'/<account>'/<script type>'
+                script_type: match steps.nth(2) {
+                    Some(ChildNumber::Hardened { index }) => Some(*index),
+                    _ => None,
+                },
+            },
+            Some(49) => Self::Bip49,
+            Some(84) => Self::Bip84,
+            Some(86) => Self::Bip86,
+            other => Self::Other(other),
+        })
+    }
+}
+
+/// Script type of the policy addresses
+///
+/// Only taproot today: the policies are always `tr()` descriptors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScriptType {
+    /// Taproot (P2TR)
+    Tr,
+}
+
+impl fmt::Display for ScriptType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tr => write!(f, "P2TR (taproot)"),
+        }
+    }
+}
+
+impl ScriptType {
+    /// Check if a key derived for `purpose` is meant for this script type
+    ///
+    /// Unknown purposes (custom derivation paths) are accepted.
+    pub fn matches_purpose(&self, purpose: &KeyPurpose) -> bool {
+        match self {
+            Self::Tr => match purpose {
+                KeyPurpose::Bip44 | KeyPurpose::Bip49 | KeyPurpose::Bip84 => false,
+                KeyPurpose::Bip48 { script_type } => matches!(script_type, Some(3) | None),
+                KeyPurpose::Bip86 | KeyPurpose::Other(..) => true,
+            },
+        }
+    }
+
+    /// Check if the key is meant for this script type (keys without origin info are accepted)
+    pub fn matches_key(&self, key: &DescriptorPublicKey) -> bool {
+        match KeyPurpose::from_key(key) {
+            Some(purpose) => self.matches_purpose(&purpose),
+            None => true,
+        }
+    }
+}
+
+/// Origin of a descriptor key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyOriginInfo {
+    pub key: DescriptorPublicKey,
+    /// Master fingerprint (`None` if the key has no origin info)
+    pub fingerprint: Option<Fingerprint>,
+    /// Derivation path from the master key (`None` if the key has no origin info)
+    pub derivation_path: Option<DerivationPath>,
+    pub purpose: Option<KeyPurpose>,
+    /// Taproot internal key
+    pub internal: bool,
+    /// The coin type of the derivation path is not the one of the network
+    pub network_mismatch: bool,
+}
+
+impl KeyOriginInfo {
+    fn new(key: DescriptorPublicKey, internal: bool, network: Network) -> Self {
+        let origin: Option<(Fingerprint, DerivationPath)> = match &key {
+            DescriptorPublicKey::Single(single) => single.origin.clone(),
+            DescriptorPublicKey::XPub(xpub) => xpub.origin.clone(),
+            DescriptorPublicKey::MultiXPub(xpub) => xpub.origin.clone(),
+        };
+        let (fingerprint, derivation_path) = match origin {
+            Some((fingerprint, path)) => (Some(fingerprint), Some(path)),
+            None => (None, None),
+        };
+
+        // m/<purpose>'/<coin>'/...: 0' is mainnet, 1' all the test networks
+        let network_mismatch: bool = match derivation_path
+            .as_ref()
+            .and_then(|path| path.into_iter().nth(1).copied())
+        {
+            Some(ChildNumber::Hardened { index }) => match network {
+                Network::Bitcoin => index != 0,
+                _ => index != 1,
+            },
+            _ => false,
+        };
+
+        Self {
+            purpose: derivation_path.as_ref().and_then(KeyPurpose::from_path),
+            key,
+            fingerprint,
+            derivation_path,
+            internal,
+            network_mismatch,
+        }
+    }
+
+    /// The key can't be matched to a signer: no origin info
+    pub fn is_unverifiable(&self) -> bool {
+        self.fingerprint.is_none()
+    }
+}
+
+/// Extract the origin of every key of the descriptor (duplicates included)
+pub(crate) fn key_origins(
+    descriptor: &Descriptor<String>,
+    network: Network,
+) -> Result<Vec<KeyOriginInfo>, Error> {
+    let desc: Descriptor<DescriptorPublicKey> = Descriptor::from_str(&descriptor.to_string())?;
+    let internal_key: Option<DescriptorPublicKey> = match &desc {
+        Descriptor::Tr(tr) => Some(tr.internal_key().clone()),
+        _ => None,
+    };
+
+    let mut keys: Vec<DescriptorPublicKey> = Vec::new();
+    desc.for_each_key(|key| {
+        keys.push(key.clone());
+        true
+    });
+
+    let mut internal_found: bool = false;
+    Ok(keys
+        .into_iter()
+        .map(|key| {
+            let internal: bool = !internal_found && internal_key.as_ref() == Some(&key);
+            internal_found |= internal;
+            KeyOriginInfo::new(key, internal, network)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETWORK: Network = Network::Testnet;
+
+    #[test]
+    fn test_key_origins() {
+        let desc = Descriptor::from_str("tr(af2486c537bbb20285bb29e0dd7c05a875b684aee7d4a2501c5c1aea63eaff1c,multi_a(2,[7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*,[f3ab64d8/48'/1'/0'/2']tpubDCh4uyVDVretfgTNkazUarV9ESTh7DJy8yvMSuWn5PQFbTDEsJwHGSBvTrNF92kw3x5ZLFXw91gN5LYtuSCbr1Vo6mzQmD49sF2vGpReZp2/0/*,tpubDC45v32EZGP2U4qVTKayC3kkdKmFAFDxxA7wnCCVgUuPXRFNms1W1LZq2LiCUBk5XmNvTZcEtbexZUMtY4ubZGS74kQftEGibUxUpybMan7/0/*))").unwrap();
+        let origins = key_origins(&desc, NETWORK).unwrap();
+        assert_eq!(origins.len(), 4);
+        let by_fingerprint = |origins: &[KeyOriginInfo], fingerprint: &str| {
+            let fingerprint = Fingerprint::from_str(fingerprint).unwrap();
+            origins
+                .iter()
+                .find(|o| o.fingerprint == Some(fingerprint))
+                .cloned()
+                .unwrap()
+        };
+
+        let internal: Vec<&KeyOriginInfo> = origins.iter().filter(|o| o.internal).collect();
+        assert_eq!(internal.len(), 1);
+        assert!(internal[0].is_unverifiable());
+
+        let bip86 = by_fingerprint(&origins, "7356e457");
+        assert_eq!(bip86.purpose, Some(KeyPurpose::Bip86));
+        assert!(!bip86.network_mismatch);
+        let bip48 = by_fingerprint(&origins, "f3ab64d8");
+        assert_eq!(
+            bip48.purpose,
+            Some(KeyPurpose::Bip48 {
+                script_type: Some(2)
+            })
+        );
+
+        // No origin info
+        let unverifiable: Vec<&KeyOriginInfo> = origins
+            .iter()
+            .filter(|o| !o.internal && o.is_unverifiable())
+            .collect();
+        assert_eq!(unverifiable.len(), 1);
+        assert_eq!(unverifiable[0].purpose, None);
+
+        let origins = key_origins(&desc, Network::Bitcoin).unwrap();
+        assert!(by_fingerprint(&origins, "7356e457").network_mismatch);
+    }
+
+    #[test]
+    fn test_script_type_purpose() {
+        let tr = ScriptType::Tr;
+        assert!(tr.matches_purpose(&KeyPurpose::Bip86));
+        assert!(tr.matches_purpose(&KeyPurpose::Bip48 {
+            script_type: Some(3)
+        }));
+        assert!(!tr.matches_purpose(&KeyPurpose::Bip48 {
+            script_type: Some(2)
+        }));
+        assert!(!tr.matches_purpose(&KeyPurpose::Bip84));
+        assert!(tr.matches_purpose(&KeyPurpose::Other(Some(1234))));
+
+        let key = |s: &str| DescriptorPublicKey::from_str(s).unwrap();
+        let bip86 = key("[7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*");
+        assert_eq!(KeyPurpose::from_key(&bip86), Some(KeyPurpose::Bip86));
+        assert!(tr.matches_key(&bip86));
+        let p2wsh = key("[f3ab64d8/48'/1'/0'/2']tpubDCh4uyVDVretfgTNkazUarV9ESTh7DJy8yvMSuWn5PQFbTDEsJwHGSBvTrNF92kw3x5ZLFXw91gN5LYtuSCbr1Vo6mzQmD49sF2vGpReZp2/0/*");
+        assert!(!tr.matches_key(&p2wsh));
+        let no_origin = key("tpubDC45v32EZGP2U4qVTKayC3kkdKmFAFDxxA7wnCCVgUuPXRFNms1W1LZq2LiCUBk5XmNvTZcEtbexZUMtY4ubZGS74kQftEGibUxUpybMan7/0/*");
+        assert_eq!(KeyPurpose::from_key(&no_origin), None);
+        assert!(tr.matches_key(&no_origin));
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Policy complexity limits
+//!
+//! Pathologically large policies (ex. 80-key multisigs) make the policy path computation, the
+//! size of the policy event and the weight of the PSBTs blow up, surfacing as timeouts or relay
+//! rejections. The `bench_policy_limits_*` benches show how the costs grow with the number of keys.
+
+use core::fmt;
+
+use bdk::descriptor::policy::SatisfiableItem;
+use keechain_core::miniscript::{Descriptor, ForEachKey};
+
+use super::Error;
+
+/// Max number of keys (internal key included)
+///
+/// Compiling and extracting the spending policy grows super-linearly with the keys: above 20,
+/// creating a policy takes seconds on low-end devices and the policy event approaches the
+/// size limit of the most restrictive relays.
+pub const MAX_POLICY_KEYS: usize = 20;
+/// Max size (bytes) of a tapleaf script
+///
+/// The standardness limit of P2WSH scripts: bigger leaves make the PSBTs too heavy for most
+/// hardware signers.
+pub const MAX_POLICY_SCRIPT_SIZE: usize = 3_600;
+/// Max number of selectable conditions (thresholds with more items than required)
+///
+/// Each of them must be selected when spending (see [`Policy::selectable_conditions`](super::Policy::selectable_conditions)):
+/// the policy path combinations grow exponentially.
+pub const MAX_SELECTABLE_CONDITIONS: usize = 10;
+
+/// Policy complexity metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PolicyMetric {
+    Keys,
+    ScriptSize,
+    SelectableConditions,
+}
+
+impl fmt::Display for PolicyMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keys => write!(f, "keys"),
+            Self::ScriptSize => write!(f, "script size"),
+            Self::SelectableConditions => write!(f, "selectable conditions"),
+        }
+    }
+}
+
+/// Limit exceeded by a policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub metric: PolicyMetric,
+    pub value: usize,
+    pub max: usize,
+}
+
+impl From<LimitExceeded> for Error {
+    fn from(e: LimitExceeded) -> Self {
+        Self::PolicyTooComplex {
+            metric: e.metric,
+            value: e.value,
+            max: e.max,
+        }
+    }
+}
+
+/// Complexity of a policy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PolicyComplexity {
+    /// Number of keys (internal key included)
+    pub keys: usize,
+    /// Size of the largest tapleaf script
+    pub max_script_size: usize,
+    pub selectable_conditions: usize,
+}
+
+impl PolicyComplexity {
+    pub fn new(descriptor: &Descriptor<String>, item: Option<&SatisfiableItem>) -> Self {
+        let mut keys: usize = 0;
+        descriptor.for_each_key(|_| {
+            keys += 1;
+            true
+        });
+
+        let max_script_size: usize = match descriptor {
+            Descriptor::Tr(tr) => tr
+                .iter_scripts()
+                .map(|(_, ms)| ms.script_size())
+                .max()
+                .unwrap_or_default(),
+            _ => 0,
+        };
+
+        let mut selectable_conditions: usize = 0;
+        if let Some(item) = item {
+            count_selectable_conditions(item, &mut selectable_conditions);
+        }
+
+        Self {
+            keys,
+            max_script_size,
+            selectable_conditions,
+        }
+    }
+
+    /// Get the exceeded limits
+    pub fn exceeded(&self) -> Vec<LimitExceeded> {
+        [
+            (PolicyMetric::Keys, self.keys, MAX_POLICY_KEYS),
+            (
+                PolicyMetric::ScriptSize,
+                self.max_script_size,
+                MAX_POLICY_SCRIPT_SIZE,
+            ),
+            (
+                PolicyMetric::SelectableConditions,
+                self.selectable_conditions,
+                MAX_SELECTABLE_CONDITIONS,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, value, max)| value > max)
+        .map(|(metric, value, max)| LimitExceeded { metric, value, max })
+        .collect()
+    }
+
+    /// Error with the first exceeded limit, if any
+    pub fn check(&self) -> Result<(), Error> {
+        match self.exceeded().into_iter().next() {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Same logic of [`Policy::selectable_conditions`](super::Policy::selectable_conditions)
+fn count_selectable_conditions(item: &SatisfiableItem, count: &mut usize) {
+    if let SatisfiableItem::Thresh { items, threshold } = item {
+        if *threshold < items.len() {
+            *count += 1;
+        }
+
+        for x in items.iter() {
+            count_selectable_conditions(&x.item, count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complexity_boundaries() {
+        let complexity = PolicyComplexity {
+            keys: MAX_POLICY_KEYS,
+            max_script_size: MAX_POLICY_SCRIPT_SIZE,
+            selectable_conditions: MAX_SELECTABLE_CONDITIONS,
+        };
+        assert!(complexity.exceeded().is_empty());
+        assert!(complexity.check().is_ok());
+
+        let keys = PolicyComplexity {
+            keys: MAX_POLICY_KEYS + 1,
+            ..complexity
+        };
+        assert!(matches!(
+            keys.check(),
+            Err(Error::PolicyTooComplex {
+                metric: PolicyMetric::Keys,
+                value,
+                max: MAX_POLICY_KEYS,
+            }) if value == MAX_POLICY_KEYS + 1
+        ));
+
+        let script_size = PolicyComplexity {
+            max_script_size: MAX_POLICY_SCRIPT_SIZE + 1,
+            ..complexity
+        };
+        assert!(matches!(
+            script_size.check(),
+            Err(Error::PolicyTooComplex {
+                metric: PolicyMetric::ScriptSize,
+                ..
+            })
+        ));
+
+        let conditions = PolicyComplexity {
+            selectable_conditions: MAX_SELECTABLE_CONDITIONS + 1,
+            ..complexity
+        };
+        assert!(matches!(
+            conditions.check(),
+            Err(Error::PolicyTooComplex {
+                metric: PolicyMetric::SelectableConditions,
+                ..
+            })
+        ));
+
+        let all = PolicyComplexity {
+            keys: MAX_POLICY_KEYS + 1,
+            max_script_size: MAX_POLICY_SCRIPT_SIZE + 1,
+            selectable_conditions: MAX_SELECTABLE_CONDITIONS + 1,
+        };
+        assert_eq!(all.exceeded().len(), 3);
+    }
+}
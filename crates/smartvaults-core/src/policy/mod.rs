@@ -4,10 +4,10 @@
 use core::cmp::Ordering;
 use core::hash::{Hash, Hasher};
 use core::str::FromStr;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use bdk::chain::{ConfirmationTime, PersistBackend};
-use bdk::descriptor::policy::{BuildSatisfaction, PkOrF, SatisfiableItem};
+use bdk::descriptor::policy::{BuildSatisfaction, PkOrF, Satisfaction, SatisfiableItem};
 use bdk::descriptor::{ExtractPolicy, IntoWalletDescriptor, Policy as SpendingPolicy};
 use bdk::signer::SignersContainer;
 use bdk::wallet::tx_builder::AddUtxoError;
@@ -15,29 +15,38 @@ use bdk::wallet::ChangeSet;
 use bdk::{FeeRate, KeychainKind, LocalOutput, Wallet};
 use keechain_core::bitcoin::absolute::{self, Height, Time};
 use keechain_core::bitcoin::address::NetworkUnchecked;
-use keechain_core::bitcoin::bip32::Fingerprint;
-#[cfg(feature = "reserves")]
+use keechain_core::bitcoin::bip32::{ChildNumber, Fingerprint};
 use keechain_core::bitcoin::psbt::PartiallySignedTransaction;
-use keechain_core::bitcoin::{Address, Network, OutPoint};
+use keechain_core::bitcoin::{Address, Network, OutPoint, ScriptBuf, Transaction, Txid};
 use keechain_core::miniscript::descriptor::DescriptorType;
 use keechain_core::miniscript::policy::Concrete;
 use keechain_core::miniscript::Descriptor;
 use keechain_core::secp256k1::XOnlyPublicKey;
-use keechain_core::util::time;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+pub mod analysis;
+mod coin_selection;
+pub mod export;
+pub mod key_origin;
+pub mod limits;
 pub mod template;
 
+pub use self::analysis::{AnalyzedKey, PolicyAnalysis, PolicyProblem, SpendingPath, Timelock};
+use self::coin_selection::{is_relative_timelock_satisfied, SmartVaultsCoinSelection};
+pub use self::export::{PolicyAnalysisExport, ANALYSIS_SCHEMA_VERSION};
+pub use self::key_origin::{KeyOriginInfo, KeyPurpose, ScriptType};
+pub use self::limits::{LimitExceeded, PolicyComplexity, PolicyMetric};
 use self::template::PolicyTemplateResult;
 pub use self::template::{
     AbsoluteLockTime, DecayingTime, Locktime, PolicyTemplate, PolicyTemplateType, RecoveryTemplate,
     Sequence,
 };
+use crate::clock::{Clock, SystemClock};
 use crate::proposal::Proposal;
 #[cfg(feature = "reserves")]
 use crate::reserves::ProofOfReserves;
-use crate::util::{search_network_for_descriptor, Unspendable};
-use crate::{Amount, Signer, SECP256K1};
+use crate::util::{derive_change_descriptor, search_network_for_descriptor, Unspendable};
+use crate::{Amount, CoinSelection, Signer, SECP256K1};
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -53,6 +62,8 @@ pub enum Error {
     AbsoluteTimelock(#[from] absolute::Error),
     #[error(transparent)]
     Psbt(#[from] keechain_core::bitcoin::psbt::Error),
+    #[error(transparent)]
+    Address(#[from] keechain_core::bitcoin::address::Error),
     #[cfg(feature = "reserves")]
     #[error(transparent)]
     ProofOfReserves(#[from] crate::reserves::ProofError),
@@ -62,10 +73,16 @@ pub enum Error {
     Policy(#[from] keechain_core::miniscript::policy::compiler::CompilerError),
     #[error(transparent)]
     Template(#[from] template::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
     #[error("{0}, {1}")]
     DescOrPolicy(Box<Self>, Box<Self>),
     #[error("must be a taproot descriptor")]
     NotTaprootDescriptor,
+    #[error("change descriptor must be different from the external one")]
+    ChangeDescriptorEqualToExternal,
+    #[error("change descriptor spending policy not match the external one")]
+    ChangeDescriptorPolicyMismatch,
     #[error("spending policy not found")]
     SpendingPolicyNotFound,
     #[error("no utxos selected")]
@@ -76,6 +93,20 @@ pub enum Error {
     AbsoluteTimelockNotSatisfied,
     #[error("Relative timelock not satisfied")]
     RelativeTimelockNotSatisfied,
+    #[error("transaction not found")]
+    TxNotFound,
+    #[error("transaction already confirmed")]
+    TxAlreadyConfirmed,
+    #[error("amount of {amount} sat doesn't cover the fee of {fee} sat")]
+    FeeExceedsAmount { amount: u64, fee: u64 },
+    #[error("invalid policy path: {0}")]
+    InvalidPolicyPath(String),
+    #[error("policy too complex: {value} {metric} (max {max})")]
+    PolicyTooComplex {
+        metric: PolicyMetric,
+        value: usize,
+        max: usize,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -111,6 +142,9 @@ struct PolicyItermediate {
     name: String,
     description: String,
     descriptor: Descriptor<String>,
+    /// Policies created before the separate change descriptor support not have it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    change_descriptor: Option<Descriptor<String>>,
 }
 
 impl Serialize for Policy {
@@ -122,6 +156,7 @@ impl Serialize for Policy {
             name: self.name.clone(),
             description: self.description.clone(),
             descriptor: self.descriptor.clone(),
+            change_descriptor: self.change_descriptor.clone(),
         };
         intermediate.serialize(serializer)
     }
@@ -136,10 +171,19 @@ impl<'de> Deserialize<'de> for Policy {
             name,
             description,
             descriptor,
+            change_descriptor,
         } = PolicyItermediate::deserialize(deserializer)?;
         let network: Network = search_network_for_descriptor(&descriptor)
             .ok_or(serde::de::Error::custom("Network not found"))?;
-        Self::new(name, description, descriptor, network).map_err(serde::de::Error::custom)
+        // Already published: the complexity limits are not enforced
+        let policy = Self::new_with_limits(name, description, descriptor, network, false)
+            .map_err(serde::de::Error::custom)?;
+        match change_descriptor {
+            Some(change_descriptor) => policy
+                .with_change_descriptor(change_descriptor)
+                .map_err(serde::de::Error::custom),
+            None => Ok(policy),
+        }
     }
 }
 
@@ -149,6 +193,10 @@ pub struct Policy {
     description: String,
     /// Descriptor
     descriptor: Descriptor<String>,
+    /// Change descriptor
+    ///
+    /// If `None`, change outputs are derived from the external descriptor.
+    change_descriptor: Option<Descriptor<String>>,
     /// Spending policy
     spending_policy: Option<SpendingPolicy>,
     /// network
@@ -182,34 +230,47 @@ impl Hash for Policy {
 }
 
 impl Policy {
+    /// New policy
+    ///
+    /// Error if the policy exceeds the complexity limits (see [`limits`]).
     pub fn new<S>(
         name: S,
         description: S,
         descriptor: Descriptor<String>,
         network: Network,
     ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        Self::new_with_limits(name, description, descriptor, network, true)
+    }
+
+    /// New policy, optionally without enforcing the complexity limits (see [`limits`])
+    pub fn new_with_limits<S>(
+        name: S,
+        description: S,
+        descriptor: Descriptor<String>,
+        network: Network,
+        enforce_limits: bool,
+    ) -> Result<Self, Error>
     where
         S: Into<String>,
     {
         if let DescriptorType::Tr = descriptor.desc_type() {
-            // Check if descriptor match network
-            let desc: String = descriptor.to_string();
-            let (descriptor_public_key, keymap) =
-                desc.into_wallet_descriptor(&SECP256K1, network)?;
-
-            // Get spending policy
-            let signer = SignersContainer::build(keymap, &descriptor_public_key, &SECP256K1);
-            let spending_policy: Option<SpendingPolicy> = descriptor_public_key.extract_policy(
-                &signer,
-                BuildSatisfaction::None,
-                &SECP256K1,
-            )?;
+            let spending_policy: Option<SpendingPolicy> =
+                extract_spending_policy(&descriptor, network)?;
+
+            if enforce_limits {
+                PolicyComplexity::new(&descriptor, spending_policy.as_ref().map(|p| &p.item))
+                    .check()?;
+            }
 
             // Compose policy
             Ok(Self {
                 name: name.into(),
                 description: description.into(),
                 descriptor,
+                change_descriptor: None,
                 spending_policy,
                 network,
             })
@@ -242,9 +303,7 @@ impl Policy {
         S: Into<String>,
         P: AsRef<str>,
     {
-        let policy: Concrete<String> = Concrete::<String>::from_str(policy.as_ref())?;
-        let unspendable_pk: XOnlyPublicKey = XOnlyPublicKey::unspendable(&SECP256K1);
-        let descriptor: Descriptor<String> = policy.compile_tr(Some(unspendable_pk.to_string()))?;
+        let descriptor: Descriptor<String> = compile_policy(policy.as_ref())?;
         Self::new(name, description, descriptor, network)
     }
 
@@ -254,6 +313,23 @@ impl Policy {
         desc_or_policy: P,
         network: Network,
     ) -> Result<Self, Error>
+    where
+        N: AsRef<str>,
+        D: AsRef<str>,
+        P: AsRef<str>,
+    {
+        Self::from_desc_or_policy_with_limits(name, description, desc_or_policy, network, true)
+    }
+
+    /// Same as [`Policy::from_desc_or_policy`], optionally without enforcing the complexity
+    /// limits (see [`limits`])
+    pub fn from_desc_or_policy_with_limits<N, D, P>(
+        name: N,
+        description: D,
+        desc_or_policy: P,
+        network: Network,
+        enforce_limits: bool,
+    ) -> Result<Self, Error>
     where
         N: AsRef<str>,
         D: AsRef<str>,
@@ -262,13 +338,19 @@ impl Policy {
         let name: &str = name.as_ref();
         let description: &str = description.as_ref();
         let desc_or_policy: &str = desc_or_policy.as_ref();
-        match Self::from_descriptor(name, description, desc_or_policy, network) {
-            Ok(policy) => Ok(policy),
-            Err(desc_e) => match Self::from_policy(name, description, desc_or_policy, network) {
-                Ok(policy) => Ok(policy),
-                Err(policy_e) => Err(Error::DescOrPolicy(Box::new(desc_e), Box::new(policy_e))),
+        let descriptor: Descriptor<String> = match Descriptor::from_str(desc_or_policy) {
+            Ok(descriptor) => descriptor,
+            Err(desc_e) => match compile_policy(desc_or_policy) {
+                Ok(descriptor) => descriptor,
+                Err(policy_e) => {
+                    return Err(Error::DescOrPolicy(
+                        Box::new(desc_e.into()),
+                        Box::new(policy_e),
+                    ))
+                }
             },
-        }
+        };
+        Self::new_with_limits(name, description, descriptor, network, enforce_limits)
     }
 
     pub fn from_template<S>(
@@ -291,6 +373,44 @@ impl Policy {
         }
     }
 
+    /// Set a separate change descriptor
+    pub fn with_change_descriptor(
+        mut self,
+        change_descriptor: Descriptor<String>,
+    ) -> Result<Self, Error> {
+        if change_descriptor == self.descriptor {
+            return Err(Error::ChangeDescriptorEqualToExternal);
+        }
+
+        // Same structure of the external descriptor: limits already checked
+        let change: Self = Self::new_with_limits(
+            self.name.as_str(),
+            self.description.as_str(),
+            change_descriptor,
+            self.network,
+            false,
+        )?;
+
+        // Key origins are the same for both keychains, so also the policy IDs must match
+        let id = |policy: &Self| policy.spending_policy.as_ref().map(|p| p.id.clone());
+        if id(&change) != id(&self) {
+            return Err(Error::ChangeDescriptorPolicyMismatch);
+        }
+
+        self.change_descriptor = Some(change.descriptor);
+        Ok(self)
+    }
+
+    /// Set a change descriptor derived from the external one (`/0/*` -> `/1/*`)
+    ///
+    /// The policy is left unchanged if the change descriptor can't be derived.
+    pub fn with_derived_change_descriptor(self) -> Result<Self, Error> {
+        match derive_change_descriptor(&self.descriptor) {
+            Some(change_descriptor) => self.with_change_descriptor(change_descriptor),
+            None => Ok(self),
+        }
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -307,10 +427,32 @@ impl Policy {
         &self.descriptor
     }
 
+    pub fn change_descriptor(&self) -> Option<Descriptor<String>> {
+        self.change_descriptor.clone()
+    }
+
+    pub fn as_change_descriptor(&self) -> Option<&Descriptor<String>> {
+        self.change_descriptor.as_ref()
+    }
+
+    /// Check if [`Policy`] derive change outputs from a separate (internal) descriptor
+    #[inline]
+    pub fn has_change_descriptor(&self) -> bool {
+        self.change_descriptor.is_some()
+    }
+
     pub fn network(&self) -> Network {
         self.network
     }
 
+    /// Get the complexity of the policy (see [`limits`])
+    pub fn complexity(&self) -> PolicyComplexity {
+        PolicyComplexity::new(
+            &self.descriptor,
+            self.spending_policy.as_ref().map(|p| &p.item),
+        )
+    }
+
     /// Check if [`Policy`] has an `absolute` or `relative` timelock
     #[inline]
     pub fn has_timelock(&self) -> bool {
@@ -377,6 +519,99 @@ impl Policy {
         }
     }
 
+    /// Check that `policy_path` select existing conditions and enough sub-paths to satisfy their thresh
+    pub fn validate_policy_path(
+        &self,
+        policy_path: &BTreeMap<String, Vec<usize>>,
+    ) -> Result<(), Error> {
+        let conditions: Vec<SelectableCondition> =
+            self.selectable_conditions()?.unwrap_or_default();
+        for (path, indexes) in policy_path.iter() {
+            let condition: &SelectableCondition = conditions
+                .iter()
+                .find(|c| &c.path == path)
+                .ok_or_else(|| Error::InvalidPolicyPath(format!("condition {path} not found")))?;
+
+            let unique: HashSet<&usize> = indexes.iter().collect();
+            if unique.len() != indexes.len() {
+                return Err(Error::InvalidPolicyPath(format!(
+                    "duplicated sub-path for {path}"
+                )));
+            }
+
+            if let Some(index) = indexes.iter().find(|i| **i >= condition.sub_paths.len()) {
+                return Err(Error::InvalidPolicyPath(format!(
+                    "sub-path {index} not found for {path}"
+                )));
+            }
+
+            if indexes.len() < condition.thresh {
+                return Err(Error::InvalidPolicyPath(format!(
+                    "{path} requires {} sub-paths, {} selected",
+                    condition.thresh,
+                    indexes.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Human-readable description of a (sub-)path of the spending policy
+    ///
+    /// Return `None` if the path not exists.
+    pub fn describe_path<S>(&self, path: S) -> Result<Option<String>, Error>
+    where
+        S: AsRef<str>,
+    {
+        fn find<'a>(item: &'a SatisfiableItem, path: &str) -> Option<&'a SatisfiableItem> {
+            if item.id() == path {
+                return Some(item);
+            }
+            if let SatisfiableItem::Thresh { items, .. } = item {
+                for x in items.iter() {
+                    if let Some(i) = find(&x.item, path) {
+                        return Some(i);
+                    }
+                }
+            }
+            None
+        }
+
+        fn describe(item: &SatisfiableItem) -> String {
+            fn key(key: &PkOrF) -> String {
+                match key {
+                    PkOrF::Pubkey(pk) => pk.to_string(),
+                    PkOrF::XOnlyPubkey(pk) => pk.to_string(),
+                    PkOrF::Fingerprint(f) => f.to_string(),
+                }
+            }
+
+            match item {
+                SatisfiableItem::EcdsaSignature(k) | SatisfiableItem::SchnorrSignature(k) => {
+                    format!("key {}", key(k))
+                }
+                SatisfiableItem::AbsoluteTimelock { value } => format!("after {value}"),
+                SatisfiableItem::RelativeTimelock { value } => format!("older {value}"),
+                SatisfiableItem::Multisig { keys, threshold } => format!(
+                    "{threshold} of [{}]",
+                    keys.iter().map(key).collect::<Vec<_>>().join(", ")
+                ),
+                SatisfiableItem::Thresh { items, threshold } => format!(
+                    "{threshold} of [{}]",
+                    items
+                        .iter()
+                        .map(|x| describe(&x.item))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                _ => String::from("hash preimage"),
+            }
+        }
+
+        let item: &SatisfiableItem = self.satisfiable_item()?;
+        Ok(find(item, path.as_ref()).map(describe))
+    }
+
     /// Get [SatisfiableItem] by policy path
     fn satisfiable_item_by_path<S>(&self, path: S) -> Result<Option<SatisfiableItem>, Error>
     where
@@ -614,89 +849,116 @@ impl Policy {
 
     /// Check if [`Policy`] match any [`PolicyTemplateType`]
     pub fn template_match(&self) -> Result<Option<PolicyTemplateType>, Error> {
-        match self.satisfiable_item()? {
-            SatisfiableItem::SchnorrSignature(..) => {
-                return Ok(Some(PolicyTemplateType::Singlesig))
-            }
-            SatisfiableItem::Thresh { items, threshold } => {
-                if *threshold == 1 && items.len() == 2 {
-                    if let SatisfiableItem::SchnorrSignature(..) = items[0].item {
-                        match &items[1].item {
-                            // Multisig 1 of 2 or N of M templates
-                            SatisfiableItem::SchnorrSignature(..)
-                            | SatisfiableItem::Multisig { .. } => {
-                                return Ok(Some(PolicyTemplateType::Multisig))
-                            }
-                            SatisfiableItem::Thresh { items, threshold } => {
-                                if *threshold == 2 && items.len() == 2 {
-                                    match items[0].item {
-                                        // Hold template
-                                        SatisfiableItem::SchnorrSignature(..) => {
-                                            if let SatisfiableItem::RelativeTimelock { .. }
-                                            | SatisfiableItem::AbsoluteTimelock { .. } =
-                                                items[1].item
-                                            {
-                                                return Ok(Some(PolicyTemplateType::Hold));
-                                            }
-                                        }
-                                        // Recovery templates
-                                        SatisfiableItem::Multisig { .. } => {
-                                            // Social Recovery / Inheritance
-                                            if let SatisfiableItem::RelativeTimelock { .. }
-                                            | SatisfiableItem::AbsoluteTimelock { .. } =
-                                                items[1].item
-                                            {
-                                                return Ok(Some(PolicyTemplateType::Recovery));
-                                            }
-                                        }
-                                        _ => (),
-                                    }
-                                }
+        Ok(template_match(self.satisfiable_item()?))
+    }
 
-                                // Decaying template
-                                if threshold < &items.len() {
-                                    let keys_count: usize = items
-                                        .iter()
-                                        .filter(|i| {
-                                            matches!(i.item, SatisfiableItem::SchnorrSignature(_))
-                                        })
-                                        .count();
-                                    let absolute_timelock_count: usize = items
-                                        .iter()
-                                        .filter(|i| {
-                                            matches!(
-                                                i.item,
-                                                SatisfiableItem::AbsoluteTimelock { .. }
-                                            )
-                                        })
-                                        .count();
-                                    let relative_timelock_count: usize = items
-                                        .iter()
-                                        .filter(|i| {
-                                            matches!(
-                                                i.item,
-                                                SatisfiableItem::RelativeTimelock { .. }
-                                            )
-                                        })
-                                        .count();
-
-                                    if threshold <= &keys_count
-                                        && absolute_timelock_count + relative_timelock_count
-                                            == items.len() - keys_count
-                                    {
-                                        return Ok(Some(PolicyTemplateType::Decaying));
-                                    }
-                                }
-                            }
-                            _ => (),
-                        }
+    /// Minimum number of signatures still missing to satisfy the policy
+    ///
+    /// The signatures are taken from the PSBT and the keys of `assume_signed` are considered as
+    /// signed too (ex. to predict if an approval completes the quorum). The timelocks are
+    /// considered as satisfied.
+    /// Return `None` if the policy can't be satisfied with signatures only (i.e. hash preimages).
+    pub fn missing_signatures(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        assume_signed: &[Fingerprint],
+    ) -> Result<Option<usize>, Error> {
+        let desc: String = self.descriptor.to_string();
+        let (descriptor_public_key, keymap) =
+            desc.into_wallet_descriptor(&SECP256K1, self.network)?;
+        let signer = SignersContainer::build(keymap, &descriptor_public_key, &SECP256K1);
+        let policy: SpendingPolicy = descriptor_public_key
+            .extract_policy(&signer, BuildSatisfaction::Psbt(psbt), &SECP256K1)?
+            .ok_or(Error::SpendingPolicyNotFound)?;
+        Ok(count_missing_signatures(&policy, assume_signed))
+    }
+
+    /// Check if the input `index` of `psbt` spends a script of the policy (external or change descriptor)
+    ///
+    /// The scripts are derived at the indexes of the key origins of the input, so no synced wallet is needed.
+    pub fn is_psbt_input_mine(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        index: usize,
+    ) -> Result<bool, Error> {
+        let (input, txin) = match (psbt.inputs.get(index), psbt.unsigned_tx.input.get(index)) {
+            (Some(input), Some(txin)) => (input, txin),
+            _ => return Ok(false),
+        };
+
+        let script: &ScriptBuf = match (&input.witness_utxo, &input.non_witness_utxo) {
+            (Some(txout), _) => &txout.script_pubkey,
+            (None, Some(tx)) => match tx.output.get(txin.previous_output.vout as usize) {
+                Some(txout) => &txout.script_pubkey,
+                None => return Ok(false),
+            },
+            (None, None) => return Ok(false),
+        };
+
+        let indexes: BTreeSet<u32> = input
+            .bip32_derivation
+            .values()
+            .chain(
+                input
+                    .tap_key_origins
+                    .values()
+                    .map(|(_, key_source)| key_source),
+            )
+            .filter_map(|(_, path)| match path.as_ref().last() {
+                Some(ChildNumber::Normal { index }) => Some(*index),
+                _ => None,
+            })
+            .collect();
+
+        for descriptor in core::iter::once(&self.descriptor).chain(self.change_descriptor.iter()) {
+            let (descriptor, _) = descriptor
+                .to_string()
+                .into_wallet_descriptor(&SECP256K1, self.network)?;
+            let indexes: Vec<u32> = if descriptor.has_wildcard() {
+                indexes.iter().copied().collect()
+            } else {
+                vec![0]
+            };
+            for index in indexes.into_iter() {
+                if let Ok(derived) = descriptor.at_derivation_index(index) {
+                    if derived.script_pubkey() == *script {
+                        return Ok(true);
                     }
                 }
             }
-            _ => {}
-        };
+        }
+
+        Ok(false)
+    }
+
+    /// Analyze the descriptor (keys, timelocks, spending paths, ...)
+    pub fn analyze(&self, network: Network) -> Result<PolicyAnalysis, Error> {
+        PolicyAnalysis::new(&self.descriptor, network)
+    }
+
+    /// Script type of the addresses
+    pub fn script_type(&self) -> Result<ScriptType, Error> {
+        match self.descriptor.desc_type() {
+            DescriptorType::Tr => Ok(ScriptType::Tr),
+            _ => Err(Error::NotTaprootDescriptor),
+        }
+    }
+
+    /// Origin (fingerprint, derivation path and purpose) of every key of the descriptor
+    pub fn key_origins(&self, network: Network) -> Result<Vec<KeyOriginInfo>, Error> {
+        key_origin::key_origins(&self.descriptor, network)
+    }
 
-        Ok(None)
+    /// Export the spending policy for third-party analyzers (see [`export`])
+    pub fn analysis_export(&self, network: Network) -> Result<PolicyAnalysisExport, Error> {
+        PolicyAnalysisExport::new(self, network)
+    }
+
+    /// Export the spending policy for third-party analyzers, as pretty JSON (see [`export`])
+    pub fn to_analysis_json(&self, network: Network) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(
+            &self.analysis_export(network)?,
+        )?)
     }
 
     /// Estimate TX vsize
@@ -710,6 +972,7 @@ impl Policy {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
     ) -> Option<usize>
     where
         D: PersistBackend<ChangeSet>,
@@ -724,6 +987,7 @@ impl Policy {
                 utxos,
                 frozen_utxos,
                 policy_path,
+                coin_selection,
             )
             .ok()?;
         let psbt = proposal.psbt();
@@ -740,10 +1004,44 @@ impl Policy {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
+    ) -> Result<Proposal, Error>
+    where
+        D: PersistBackend<ChangeSet>,
+        S: Into<String>,
+    {
+        self.spend_with_clock(
+            wallet,
+            address,
+            amount,
+            description,
+            fee_rate,
+            utxos,
+            frozen_utxos,
+            policy_path,
+            coin_selection,
+            &SystemClock,
+        )
+    }
+
+    /// Same as [`Policy::spend`], checking the timelocks against `clock`
+    pub fn spend_with_clock<D, S, C>(
+        &self,
+        wallet: &mut Wallet<D>,
+        address: Address<NetworkUnchecked>,
+        amount: Amount,
+        description: S,
+        fee_rate: FeeRate,
+        utxos: Option<Vec<OutPoint>>,
+        frozen_utxos: Option<Vec<OutPoint>>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
+        clock: &C,
     ) -> Result<Proposal, Error>
     where
         D: PersistBackend<ChangeSet>,
         S: Into<String>,
+        C: Clock,
     {
         let wallet_utxos: HashMap<OutPoint, LocalOutput> = wallet
             .list_unspent()
@@ -757,10 +1055,8 @@ impl Policy {
             )));
         }
 
-        let checkpoint = wallet.latest_checkpoint();
-
-        let current_height: u32 = checkpoint.height();
-        let timestamp: u64 = time::timestamp();
+        let tip: u32 = wallet.latest_checkpoint().height();
+        let current_height: u32 = clock.now_height(tip);
 
         if let Some(frozen_utxos) = &frozen_utxos {
             if wallet
@@ -776,73 +1072,61 @@ impl Policy {
         }
 
         // Build the PSBT
-        let psbt = {
-            let mut builder = wallet.build_tx();
-
-            if let Some(frozen_utxos) = frozen_utxos {
-                for unspendable in frozen_utxos.into_iter() {
-                    builder.add_unspendable(unspendable);
-                }
-            }
-
-            if let Some(utxos) = utxos {
-                if utxos.is_empty() {
-                    return Err(Error::NoUtxosSelected);
+        let coin_selector = SmartVaultsCoinSelection::new(coin_selection, current_height);
+        let mut psbt: PartiallySignedTransaction = self.build_spending_psbt(
+            wallet,
+            &address,
+            amount,
+            fee_rate,
+            utxos.as_deref(),
+            frozen_utxos.as_deref(),
+            policy_path.clone(),
+            coin_selector,
+        )?;
+
+        // If some of the selected UTXOs not satisfy the relative timelock, exclude them and retry
+        if self.has_timelock() {
+            let min_confirmations: Option<u32> = psbt
+                .unsigned_tx
+                .input
+                .iter()
+                .map(|txin| txin.sequence)
+                .filter(|sequence| sequence.is_height_locked())
+                .map(|sequence| sequence.0)
+                .max();
+            if let Some(min_confirmations) = min_confirmations {
+                let satisfied: bool = psbt.unsigned_tx.input.iter().all(|txin| {
+                    wallet_utxos
+                        .get(&txin.previous_output)
+                        .map_or(true, |utxo| {
+                            is_relative_timelock_satisfied(
+                                &utxo.confirmation_time,
+                                current_height,
+                                min_confirmations,
+                            )
+                        })
+                });
+                if !satisfied {
+                    psbt = self.build_spending_psbt(
+                        wallet,
+                        &address,
+                        amount,
+                        fee_rate,
+                        utxos.as_deref(),
+                        frozen_utxos.as_deref(),
+                        policy_path.clone(),
+                        coin_selector.min_confirmations(Some(min_confirmations)),
+                    )?;
                 }
-                builder.manually_selected_only();
-                builder.add_utxos(&utxos)?;
-            }
-
-            if let Some(path) = policy_path.clone() {
-                builder.policy_path(path, KeychainKind::External);
             }
-
-            // TODO: add custom coin selection alorithm (to exclude UTXOs with timelock enabled)
-            builder
-                .fee_rate(fee_rate)
-                .enable_rbf()
-                .current_height(current_height);
-            match amount {
-                Amount::Max => builder
-                    .drain_wallet()
-                    .drain_to(address.payload.script_pubkey()),
-                Amount::Custom(amount) => {
-                    builder.add_recipient(address.payload.script_pubkey(), amount)
-                }
-            };
-            builder
-                .finish()
-                .map_err(|e| Error::BdkCreateTx(format!("{e:?}")))?
-        };
+        }
 
         if self.has_timelock() {
-            // Check if absolute timelock is satisfied
-            if !psbt.unsigned_tx.is_absolute_timelock_satisfied(
-                Height::from_consensus(current_height)?,
-                Time::from_consensus(timestamp as u32)?,
-            ) {
-                return Err(Error::AbsoluteTimelockNotSatisfied);
-            }
-
-            for txin in psbt.unsigned_tx.input.iter() {
-                let sequence: Sequence = txin.sequence;
-
-                // Check if relative timelock is satisfied
-                if sequence.is_height_locked() || sequence.is_time_locked() {
-                    if let Some(utxo) = wallet_utxos.get(&txin.previous_output) {
-                        match utxo.confirmation_time {
-                            ConfirmationTime::Confirmed { height, .. } => {
-                                if current_height.saturating_sub(height) < sequence.0 {
-                                    return Err(Error::RelativeTimelockNotSatisfied);
-                                }
-                            }
-                            ConfirmationTime::Unconfirmed { .. } => {
-                                return Err(Error::RelativeTimelockNotSatisfied);
-                            }
-                        }
-                    }
-                }
-            }
+            let confirmation_times: HashMap<OutPoint, &ConfirmationTime> = wallet_utxos
+                .values()
+                .map(|utxo| (utxo.outpoint, &utxo.confirmation_time))
+                .collect();
+            check_timelocks(&psbt.unsigned_tx, &confirmation_times, tip, clock)?;
         }
 
         let amount: u64 = match amount {
@@ -852,6 +1136,16 @@ impl Policy {
                 sent.saturating_sub(received).saturating_sub(fee)
             }
             Amount::Custom(amount) => amount,
+            Amount::CustomSubtractFee(..) => {
+                // Amount actually received by the recipient
+                let script = address.payload.script_pubkey();
+                psbt.unsigned_tx
+                    .output
+                    .iter()
+                    .filter(|txout| txout.script_pubkey == script)
+                    .map(|txout| txout.value)
+                    .sum()
+            }
         };
 
         Ok(Proposal::spending(
@@ -864,6 +1158,232 @@ impl Policy {
         ))
     }
 
+    /// Build a replacement (RBF) of an unconfirmed wallet transaction paying `fee_rate`
+    ///
+    /// The inputs and the recipient of the original transaction are kept: the additional fee is paid by the change.
+    pub fn bump_fee<D, S>(
+        &self,
+        wallet: &mut Wallet<D>,
+        txid: Txid,
+        description: S,
+        fee_rate: FeeRate,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+    ) -> Result<Proposal, Error>
+    where
+        D: PersistBackend<ChangeSet>,
+        S: Into<String>,
+    {
+        let canonical_tx = wallet.get_tx(txid).ok_or(Error::TxNotFound)?;
+        let confirmation_time: ConfirmationTime = canonical_tx.chain_position.cloned().into();
+        if let ConfirmationTime::Confirmed { .. } = confirmation_time {
+            return Err(Error::TxAlreadyConfirmed);
+        }
+
+        let network: Network = wallet.network();
+        let current_height: u32 = wallet.latest_checkpoint().height();
+        let mut builder = wallet
+            .build_fee_bump(txid)
+            .map_err(|e| Error::BdkCreateTx(format!("{e:?}")))?;
+
+        if let Some(path) = policy_path.clone() {
+            // Policy IDs are the same for both keychains
+            if self.has_change_descriptor() {
+                builder.policy_path(path.clone(), KeychainKind::Internal);
+            }
+            builder.policy_path(path, KeychainKind::External);
+        }
+
+        builder
+            .fee_rate(fee_rate)
+            .enable_rbf()
+            .current_height(current_height);
+        let psbt: PartiallySignedTransaction = builder
+            .finish()
+            .map_err(|e| Error::BdkCreateTx(format!("{e:?}")))?;
+
+        // Recipient: the first output not owned by the wallet or, if sending to self, the first one
+        // not derived by the change keychain (outputs are shuffled by BDK)
+        let outputs = &psbt.unsigned_tx.output;
+        let txout = outputs
+            .iter()
+            .find(|txout| !wallet.is_mine(&txout.script_pubkey))
+            .or_else(|| {
+                outputs.iter().find(|txout| {
+                    !matches!(
+                        wallet.derivation_of_spk(&txout.script_pubkey),
+                        Some((KeychainKind::Internal, _))
+                    )
+                })
+            })
+            .or_else(|| outputs.first())
+            .ok_or(Error::BdkCreateTx(String::from(
+                "transaction without outputs",
+            )))?;
+        let address: Address = Address::from_script(&txout.script_pubkey, network)?;
+        let amount: u64 = txout.value;
+
+        Ok(Proposal::spending(
+            self.descriptor.clone(),
+            address.as_unchecked().clone(),
+            amount,
+            description,
+            psbt,
+            policy_path,
+        ))
+    }
+
+    fn build_spending_psbt<D>(
+        &self,
+        wallet: &mut Wallet<D>,
+        address: &Address<NetworkUnchecked>,
+        amount: Amount,
+        fee_rate: FeeRate,
+        utxos: Option<&[OutPoint]>,
+        frozen_utxos: Option<&[OutPoint]>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selector: SmartVaultsCoinSelection,
+    ) -> Result<PartiallySignedTransaction, Error>
+    where
+        D: PersistBackend<ChangeSet>,
+    {
+        let script: ScriptBuf = address.payload.script_pubkey();
+        match amount {
+            Amount::Max => self.build_psbt(
+                wallet,
+                Vec::new(),
+                Some((script, true)),
+                fee_rate,
+                utxos,
+                frozen_utxos,
+                policy_path,
+                coin_selector,
+            ),
+            Amount::Custom(amount) => self.build_psbt(
+                wallet,
+                vec![(script, amount)],
+                None,
+                fee_rate,
+                utxos,
+                frozen_utxos,
+                policy_path,
+                coin_selector,
+            ),
+            Amount::CustomSubtractFee(amount) => {
+                // Select the inputs as if the fee was zero
+                let selection: PartiallySignedTransaction = self.build_psbt(
+                    wallet,
+                    vec![(script.clone(), amount)],
+                    None,
+                    FeeRate::from_sat_per_vb(0.0),
+                    utxos,
+                    frozen_utxos,
+                    policy_path.clone(),
+                    coin_selector,
+                )?;
+                let inputs: Vec<OutPoint> = selection
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|txin| txin.previous_output)
+                    .collect();
+                let change: Vec<(ScriptBuf, u64)> = selection
+                    .unsigned_tx
+                    .output
+                    .into_iter()
+                    .filter(|txout| txout.script_pubkey != script)
+                    .map(|txout| (txout.script_pubkey, txout.value))
+                    .collect();
+
+                // Keep the same inputs and change: the fee is paid by the recipient output
+                let psbt: PartiallySignedTransaction = self.build_psbt(
+                    wallet,
+                    change,
+                    Some((script.clone(), false)),
+                    fee_rate,
+                    Some(&inputs),
+                    frozen_utxos,
+                    policy_path,
+                    coin_selector,
+                )?;
+
+                // If `amount - fee` is dust, BDK drops the recipient output and the whole
+                // amount would go to the miners
+                let fee: u64 = psbt.fee()?.to_sat();
+                let received: u64 = psbt
+                    .unsigned_tx
+                    .output
+                    .iter()
+                    .filter(|txout| txout.script_pubkey == script)
+                    .map(|txout| txout.value)
+                    .sum();
+                if received == 0 || received != amount.saturating_sub(fee) {
+                    return Err(Error::FeeExceedsAmount { amount, fee });
+                }
+
+                Ok(psbt)
+            }
+        }
+    }
+
+    /// Build a PSBT paying the `recipients`
+    ///
+    /// If `drain_to` is set, the remaining value of the selected inputs (or of the whole wallet, if the flag is `true`)
+    /// is sent to that script, instead of to a change output.
+    fn build_psbt<D>(
+        &self,
+        wallet: &mut Wallet<D>,
+        recipients: Vec<(ScriptBuf, u64)>,
+        drain_to: Option<(ScriptBuf, bool)>,
+        fee_rate: FeeRate,
+        utxos: Option<&[OutPoint]>,
+        frozen_utxos: Option<&[OutPoint]>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selector: SmartVaultsCoinSelection,
+    ) -> Result<PartiallySignedTransaction, Error>
+    where
+        D: PersistBackend<ChangeSet>,
+    {
+        let current_height: u32 = wallet.latest_checkpoint().height();
+        let mut builder = wallet.build_tx().coin_selection(coin_selector);
+
+        if let Some(frozen_utxos) = frozen_utxos {
+            for unspendable in frozen_utxos.iter() {
+                builder.add_unspendable(*unspendable);
+            }
+        }
+
+        if let Some(utxos) = utxos {
+            if utxos.is_empty() {
+                return Err(Error::NoUtxosSelected);
+            }
+            builder.manually_selected_only();
+            builder.add_utxos(utxos)?;
+        }
+
+        if let Some(path) = policy_path {
+            // Policy IDs are the same for both keychains
+            if self.has_change_descriptor() {
+                builder.policy_path(path.clone(), KeychainKind::Internal);
+            }
+            builder.policy_path(path, KeychainKind::External);
+        }
+
+        builder
+            .fee_rate(fee_rate)
+            .enable_rbf()
+            .current_height(current_height);
+        builder.set_recipients(recipients);
+        if let Some((script, drain_wallet)) = drain_to {
+            if drain_wallet {
+                builder.drain_wallet();
+            }
+            builder.drain_to(script);
+        }
+        builder
+            .finish()
+            .map_err(|e| Error::BdkCreateTx(format!("{e:?}")))
+    }
+
     #[cfg(feature = "reserves")]
     pub fn proof_of_reserve<D, S>(
         &self,
@@ -893,6 +1413,149 @@ impl Policy {
     }
 }
 
+/// Extract the spending policy of the descriptor, checking that it match the network
+pub(crate) fn extract_spending_policy(
+    descriptor: &Descriptor<String>,
+    network: Network,
+) -> Result<Option<SpendingPolicy>, Error> {
+    let desc: String = descriptor.to_string();
+    let (descriptor_public_key, keymap) = desc.into_wallet_descriptor(&SECP256K1, network)?;
+    let signer = SignersContainer::build(keymap, &descriptor_public_key, &SECP256K1);
+    Ok(descriptor_public_key.extract_policy(&signer, BuildSatisfaction::None, &SECP256K1)?)
+}
+
+/// See [`Policy::missing_signatures`]
+fn count_missing_signatures(
+    policy: &SpendingPolicy,
+    assume_signed: &[Fingerprint],
+) -> Option<usize> {
+    let assumed = |key: &PkOrF| matches!(key, PkOrF::Fingerprint(f) if assume_signed.contains(f));
+
+    if let Satisfaction::Complete { .. } = policy.satisfaction {
+        return Some(0);
+    }
+
+    match &policy.item {
+        SatisfiableItem::EcdsaSignature(key) | SatisfiableItem::SchnorrSignature(key) => {
+            Some(usize::from(!assumed(key)))
+        }
+        SatisfiableItem::AbsoluteTimelock { .. } | SatisfiableItem::RelativeTimelock { .. } => {
+            Some(0)
+        }
+        SatisfiableItem::Multisig { keys, threshold } => {
+            let signed: &[usize] = match &policy.satisfaction {
+                Satisfaction::Partial { items, .. }
+                | Satisfaction::PartialComplete { items, .. } => items,
+                _ => &[],
+            };
+            let signed: usize = keys
+                .iter()
+                .enumerate()
+                .filter(|(index, key)| signed.contains(index) || assumed(key))
+                .count();
+            Some(threshold.saturating_sub(signed))
+        }
+        SatisfiableItem::Thresh { items, threshold } => {
+            let mut missing: Vec<usize> = items
+                .iter()
+                .filter_map(|x| count_missing_signatures(x, assume_signed))
+                .collect();
+            if missing.len() < *threshold {
+                return None;
+            }
+            missing.sort_unstable();
+            Some(missing.into_iter().take(*threshold).sum())
+        }
+        _ => None,
+    }
+}
+
+/// Match the spending policy with the [`PolicyTemplateType`]s
+pub(crate) fn template_match(item: &SatisfiableItem) -> Option<PolicyTemplateType> {
+    match item {
+        SatisfiableItem::SchnorrSignature(..) => return Some(PolicyTemplateType::Singlesig),
+        SatisfiableItem::Thresh { items, threshold } => {
+            if *threshold == 1 && items.len() == 2 {
+                if let SatisfiableItem::SchnorrSignature(..) = items[0].item {
+                    match &items[1].item {
+                        // Multisig 1 of 2 or N of M templates
+                        SatisfiableItem::SchnorrSignature(..)
+                        | SatisfiableItem::Multisig { .. } => {
+                            return Some(PolicyTemplateType::Multisig)
+                        }
+                        SatisfiableItem::Thresh { items, threshold } => {
+                            if *threshold == 2 && items.len() == 2 {
+                                match items[0].item {
+                                    // Hold template
+                                    SatisfiableItem::SchnorrSignature(..) => {
+                                        if let SatisfiableItem::RelativeTimelock { .. }
+                                        | SatisfiableItem::AbsoluteTimelock { .. } =
+                                            items[1].item
+                                        {
+                                            return Some(PolicyTemplateType::Hold);
+                                        }
+                                    }
+                                    // Recovery templates
+                                    SatisfiableItem::Multisig { .. } => {
+                                        // Social Recovery / Inheritance
+                                        if let SatisfiableItem::RelativeTimelock { .. }
+                                        | SatisfiableItem::AbsoluteTimelock { .. } =
+                                            items[1].item
+                                        {
+                                            return Some(PolicyTemplateType::Recovery);
+                                        }
+                                    }
+                                    _ => (),
+                                }
+                            }
+
+                            // Decaying template
+                            if threshold < &items.len() {
+                                let keys_count: usize = items
+                                    .iter()
+                                    .filter(|i| {
+                                        matches!(i.item, SatisfiableItem::SchnorrSignature(_))
+                                    })
+                                    .count();
+                                let absolute_timelock_count: usize = items
+                                    .iter()
+                                    .filter(|i| {
+                                        matches!(i.item, SatisfiableItem::AbsoluteTimelock { .. })
+                                    })
+                                    .count();
+                                let relative_timelock_count: usize = items
+                                    .iter()
+                                    .filter(|i| {
+                                        matches!(i.item, SatisfiableItem::RelativeTimelock { .. })
+                                    })
+                                    .count();
+
+                                if threshold <= &keys_count
+                                    && absolute_timelock_count + relative_timelock_count
+                                        == items.len() - keys_count
+                                {
+                                    return Some(PolicyTemplateType::Decaying);
+                                }
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        _ => {}
+    };
+
+    None
+}
+
+/// Compile a miniscript policy to a taproot descriptor (unspendable internal key)
+fn compile_policy(policy: &str) -> Result<Descriptor<String>, Error> {
+    let policy: Concrete<String> = Concrete::<String>::from_str(policy)?;
+    let unspendable_pk: XOnlyPublicKey = XOnlyPublicKey::unspendable(&SECP256K1);
+    Ok(policy.compile_tr(Some(unspendable_pk.to_string()))?)
+}
+
 /// Check if [SatisfiableItem] contains [Fingerprint]
 fn satisfiable_item_contains_fingerprint(
     item: &SatisfiableItem,
@@ -941,13 +1604,54 @@ fn satisfiable_item_contains_fingerprint(
     }
 }
 
+/// Check if the absolute and relative timelocks of `tx` are satisfied at the `clock` time
+///
+/// `confirmation_times` are the ones of the spent UTXOs: the inputs without one are not checked.
+fn check_timelocks<C>(
+    tx: &Transaction,
+    confirmation_times: &HashMap<OutPoint, &ConfirmationTime>,
+    tip: u32,
+    clock: &C,
+) -> Result<(), Error>
+where
+    C: Clock,
+{
+    let current_height: u32 = clock.now_height(tip);
+    let timestamp: u64 = clock.now_timestamp();
+
+    // Check if absolute timelock is satisfied
+    if !tx.is_absolute_timelock_satisfied(
+        Height::from_consensus(current_height)?,
+        Time::from_consensus(timestamp as u32)?,
+    ) {
+        return Err(Error::AbsoluteTimelockNotSatisfied);
+    }
+
+    for txin in tx.input.iter() {
+        let sequence: Sequence = txin.sequence;
+
+        // Check if relative timelock is satisfied
+        if sequence.is_height_locked() || sequence.is_time_locked() {
+            if let Some(confirmation_time) = confirmation_times.get(&txin.previous_output) {
+                if !is_relative_timelock_satisfied(confirmation_time, current_height, sequence.0) {
+                    return Err(Error::RelativeTimelockNotSatisfied);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use bdk::keys::DescriptorPublicKey;
     use keechain_core::bips::bip39::Mnemonic;
+    use keechain_core::secp256k1::SecretKey;
     use keechain_core::Seed;
 
     use super::*;
+    use crate::clock::MockClock;
     use crate::signer::smartvaults_signer;
 
     const NETWORK: Network = Network::Testnet;
@@ -973,6 +1677,87 @@ mod tests {
         assert!(Policy::from_descriptor("", "", descriptor, NETWORK).is_ok())
     }
 
+    #[test]
+    fn test_is_psbt_input_mine() {
+        use keechain_core::bitcoin::bip32::DerivationPath;
+        use keechain_core::bitcoin::psbt::Input;
+        use keechain_core::bitcoin::{TxIn, TxOut};
+
+        let descriptor = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+        let policy = Policy::from_descriptor("", "", descriptor, NETWORK)
+            .unwrap()
+            .with_derived_change_descriptor()
+            .unwrap();
+        let (desc, _) = descriptor
+            .into_wallet_descriptor(&SECP256K1, NETWORK)
+            .unwrap();
+        let (change_desc, _) = policy
+            .change_descriptor()
+            .unwrap()
+            .to_string()
+            .into_wallet_descriptor(&SECP256K1, NETWORK)
+            .unwrap();
+        let key = SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .x_only_public_key(&SECP256K1)
+            .0;
+
+        let psbt = |script_pubkey: ScriptBuf, path: &str| {
+            let mut input = Input {
+                witness_utxo: Some(TxOut {
+                    value: 10_000,
+                    script_pubkey,
+                }),
+                ..Default::default()
+            };
+            input.tap_key_origins.insert(
+                key,
+                (
+                    Vec::new(),
+                    (
+                        Fingerprint::from_str("9bf4354b").unwrap(),
+                        DerivationPath::from_str(path).unwrap(),
+                    ),
+                ),
+            );
+            let mut psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+                version: 2,
+                lock_time: absolute::LockTime::ZERO,
+                input: vec![TxIn::default()],
+                output: Vec::new(),
+            })
+            .unwrap();
+            psbt.inputs[0] = input;
+            psbt
+        };
+
+        let script = desc.at_derivation_index(5).unwrap().script_pubkey();
+        assert!(policy
+            .is_psbt_input_mine(&psbt(script.clone(), "m/86'/1'/784923'/0/5"), 0)
+            .unwrap());
+        // Key origin not matching the script
+        assert!(!policy
+            .is_psbt_input_mine(&psbt(script, "m/86'/1'/784923'/0/6"), 0)
+            .unwrap());
+
+        // Change
+        let script = change_desc.at_derivation_index(3).unwrap().script_pubkey();
+        assert!(policy
+            .is_psbt_input_mine(&psbt(script, "m/86'/1'/784923'/1/3"), 0)
+            .unwrap());
+
+        // Foreign script
+        let script = ScriptBuf::new_v1_p2tr(&SECP256K1, key, None);
+        assert!(!policy
+            .is_psbt_input_mine(&psbt(script, "m/86'/1'/784923'/0/5"), 0)
+            .unwrap());
+
+        // Unknown input
+        assert!(!policy
+            .is_psbt_input_mine(&psbt(ScriptBuf::new(), "m/0"), 1)
+            .unwrap());
+    }
+
     #[test]
     fn test_wrong_descriptor() {
         let descriptor = "tr(939742dc67dd3c5b5c9201df54ee8a92b053b2613770c8c26f2156cfd9514a0b,multi_a(2,[7c997e72/86'/0'/784923']xpub6DGQCZUmD4kdGDj8ttgba5Jc6pUSkFWaMwB1jedmzer1BtKDdef18k3cWwC9k7HfJGci7Q9S5KTRD9bBn4JZm3xPcDvidkSXvZ6pg4now57/0/,[87131a00/86'/1'/784923']tpubDDEaK5JwGiGDTRkML9YKh8AF4rHPhkpnXzVjVMDBtzayJpnsWKeiFPxtiyYeGHQj8pnjsei7N98winwZ3ivGoVVKArZVMsEYGig73XVqbSX/0/,[e157a520/86'/1'/784923']tpubDCCYFYCyDkxo1xAzDpoFNdtGcjD5BPLZbEJswjJmwqp67Weqd2C7fg6Jy1SBjgn3wYnKyUtoYKXG4VdQczjqb6FJnqHe3NmFdgy8vNBSty4/0/))#kdvl4ku3";
@@ -985,6 +1770,113 @@ mod tests {
         assert!(Policy::from_descriptor("", "", descriptor, Network::Bitcoin).is_err())
     }
 
+    #[test]
+    fn test_analysis_export_golden() {
+        fn check(name: &str, descriptor: &str) {
+            let policy = Policy::from_descriptor("", "", descriptor, NETWORK).unwrap();
+            let export = policy.analysis_export(NETWORK).unwrap();
+            let path = format!(
+                "{}/testdata/policy-analysis/{name}.json",
+                env!("CARGO_MANIFEST_DIR")
+            );
+
+            // Regenerate with `UPDATE_GOLDEN=1 cargo test`
+            if std::env::var("UPDATE_GOLDEN").is_ok() {
+                let json: String = policy.to_analysis_json(NETWORK).unwrap();
+                std::fs::write(&path, format!("{json}\n")).unwrap();
+            }
+
+            let golden: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(
+                serde_json::to_value(&export).unwrap(),
+                golden,
+                "{name}: export changed (bump ANALYSIS_SCHEMA_VERSION if it's a breaking change)"
+            );
+
+            // Round-trip
+            let parsed: PolicyAnalysisExport = serde_json::from_value(golden).unwrap();
+            assert_eq!(parsed, export);
+        }
+
+        check("single-leaf", "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg");
+        check("complex", COMPLEX_DESCRIPTOR);
+        check("complex-with-timelock", COMPLEX_DESCRIPTOR_WITH_TIMELOCK);
+    }
+
+    #[test]
+    fn test_count_missing_signatures() {
+        let fingerprint = |f: &str| Fingerprint::from_str(f).unwrap();
+
+        // Key path or 1 script key
+        let policy = Policy::from_descriptor("", "", "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg", NETWORK).unwrap();
+        let spending_policy = policy.spending_policy().unwrap();
+        assert_eq!(count_missing_signatures(spending_policy, &[]), Some(1));
+        assert_eq!(
+            count_missing_signatures(spending_policy, &[fingerprint("7356e457")]),
+            Some(0)
+        );
+
+        // Key path or f3ab64d8 and (f57a6b99 after the timelock or 2 of 2)
+        let policy = Policy::from_descriptor("", "", COMPLEX_DESCRIPTOR, NETWORK).unwrap();
+        let spending_policy = policy.spending_policy().unwrap();
+        assert_eq!(count_missing_signatures(spending_policy, &[]), Some(1));
+        assert_eq!(
+            count_missing_signatures(spending_policy, &[fingerprint("f3ab64d8")]),
+            Some(1)
+        );
+        assert_eq!(
+            count_missing_signatures(
+                spending_policy,
+                &[fingerprint("f3ab64d8"), fingerprint("4eb5d5a1")]
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            count_missing_signatures(
+                spending_policy,
+                &[fingerprint("f3ab64d8"), fingerprint("f57a6b99")]
+            ),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_change_descriptor() {
+        let policy = Policy::from_descriptor("", "", COMPLEX_DESCRIPTOR, NETWORK).unwrap();
+        assert!(!policy.has_change_descriptor());
+
+        // Legacy policies keep the single descriptor
+        let json = serde_json::to_string(&policy).unwrap();
+        assert!(!json.contains("change_descriptor"));
+        let policy: Policy = serde_json::from_str(&json).unwrap();
+        assert!(!policy.has_change_descriptor());
+
+        let policy = policy.with_derived_change_descriptor().unwrap();
+        let change_descriptor = policy.change_descriptor().unwrap();
+        assert_ne!(change_descriptor, policy.descriptor());
+        assert!(change_descriptor.to_string().contains("/1/*"));
+        assert_eq!(
+            policy.selectable_conditions().unwrap().map(|c| c.len()),
+            Some(2)
+        );
+
+        let json = serde_json::to_string(&policy).unwrap();
+        let deserialized: Policy = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.change_descriptor(), Some(change_descriptor));
+
+        // Change descriptor of another policy
+        let other = Descriptor::from_str(COMPLEX_DESCRIPTOR_WITH_TIMELOCK).unwrap();
+        assert!(matches!(
+            policy.clone().with_change_descriptor(other),
+            Err(Error::ChangeDescriptorPolicyMismatch)
+        ));
+        assert!(matches!(
+            policy.clone().with_change_descriptor(policy.descriptor()),
+            Err(Error::ChangeDescriptorEqualToExternal)
+        ));
+    }
+
     #[test]
     fn selectable_conditions() {
         let policy = Policy::from_descriptor("", "", COMPLEX_DESCRIPTOR, NETWORK).unwrap();
@@ -1127,6 +2019,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_decaying_policy_path_selection() {
+        // Decaying 2 of 4 (absolute)
+        let desc = "tr(56f05264c005e2a2f6e261996ed2cd904dfafbc6d75cc07a5a76d46df56e6ff9,thresh(2,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*),s:pk([4eb5d5a1/86'/1'/784923']tpubDCLskGdzStPPo1auRQygJUfbmLMwujWr7fmekdUMD7gqSpwEcRso4CfiP5GkRqfXFYkfqTujyvuehb7inymMhBJFdbJqFyHsHVRuwLKCSe9/0/*),s:pk([f3ab64d8/86'/1'/784923']tpubDCh4uyVDVretfgTNkazUarV9ESTh7DJy8yvMSuWn5PQFbTDEsJwHGSBvTrNF92kw3x5ZLFXw91gN5LYtuSCbr1Vo6mzQmD49sF2vGpReZp2/0/*),snl:after(1698947462)))#2263dhaf";
+        let policy = Policy::from_descriptor("", "", desc, NETWORK).unwrap();
+
+        let conditions = policy.selectable_conditions().unwrap().unwrap();
+        let decaying = conditions.iter().find(|c| c.path == "5p29qyl4").unwrap();
+        assert_eq!(decaying.thresh, 2);
+        assert_eq!(decaying.sub_paths.len(), 4);
+
+        // Human-readable sub-paths
+        let key = policy
+            .describe_path(&decaying.sub_paths[0])
+            .unwrap()
+            .unwrap();
+        assert!(key.contains("7356e457"));
+        let timelock = policy
+            .describe_path(&decaying.sub_paths[3])
+            .unwrap()
+            .unwrap();
+        assert!(timelock.contains("1698947462"));
+        assert!(policy.describe_path("unknown").unwrap().is_none());
+
+        // Script path: my key + timelock
+        let mut path: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        path.insert(String::from("n4vvscy5"), vec![1]);
+        path.insert(String::from("5p29qyl4"), vec![0, 3]);
+        assert!(policy.validate_policy_path(&path).is_ok());
+
+        // Thresh not satisfied
+        path.insert(String::from("5p29qyl4"), vec![0]);
+        assert!(matches!(
+            policy.validate_policy_path(&path),
+            Err(Error::InvalidPolicyPath(..))
+        ));
+
+        // Sub-path out of range
+        path.insert(String::from("5p29qyl4"), vec![0, 4]);
+        assert!(matches!(
+            policy.validate_policy_path(&path),
+            Err(Error::InvalidPolicyPath(..))
+        ));
+
+        // Unknown condition
+        let mut path: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        path.insert(String::from("unknown"), vec![0]);
+        assert!(matches!(
+            policy.validate_policy_path(&path),
+            Err(Error::InvalidPolicyPath(..))
+        ));
+    }
+
     #[test]
     fn test_get_policy_path_from_signers() {
         // Signer 1
@@ -1296,10 +2241,56 @@ mod tests {
             Some(PolicyTemplateType::Decaying)
         );
     }
+
+    /// `tr(<unspendable>,multi_a(1,<keys>))`
+    fn multisig_descriptor(keys: usize) -> String {
+        let keys: Vec<String> = (1..=keys as u8)
+            .map(|i| {
+                let secret_key = SecretKey::from_slice(&[i; 32]).unwrap();
+                secret_key.x_only_public_key(&SECP256K1).0.to_string()
+            })
+            .collect();
+        format!(
+            "tr({},multi_a(1,{}))",
+            XOnlyPublicKey::unspendable(&SECP256K1),
+            keys.join(",")
+        )
+    }
+
+    #[test]
+    fn test_policy_limits() {
+        // Internal key included
+        let desc = multisig_descriptor(limits::MAX_POLICY_KEYS - 1);
+        let policy = Policy::from_descriptor("", "", &desc, NETWORK).unwrap();
+        assert_eq!(policy.complexity().keys, limits::MAX_POLICY_KEYS);
+
+        let desc = multisig_descriptor(limits::MAX_POLICY_KEYS);
+        assert!(matches!(
+            Policy::from_descriptor("", "", &desc, NETWORK),
+            Err(Error::PolicyTooComplex {
+                metric: PolicyMetric::Keys,
+                value,
+                max: limits::MAX_POLICY_KEYS,
+            }) if value == limits::MAX_POLICY_KEYS + 1
+        ));
+
+        // Limits overridden
+        let policy =
+            Policy::from_desc_or_policy_with_limits("", "", &desc, NETWORK, false).unwrap();
+        assert_eq!(
+            policy.complexity().exceeded(),
+            vec![LimitExceeded {
+                metric: PolicyMetric::Keys,
+                value: limits::MAX_POLICY_KEYS + 1,
+                max: limits::MAX_POLICY_KEYS,
+            }]
+        );
+    }
 }
 
 #[cfg(bench)]
 mod benches {
+    use keechain_core::secp256k1::SecretKey;
     use test::{black_box, Bencher};
 
     use super::*;
@@ -1339,4 +2330,134 @@ mod benches {
             black_box(policy.template_match()).unwrap();
         });
     }
+
+    fn multisig_policy(keys: usize) -> String {
+        let keys: Vec<String> = (1..=keys as u8)
+            .map(|i| {
+                let secret_key = SecretKey::from_slice(&[i; 32]).unwrap();
+                format!("pk({})", secret_key.x_only_public_key(&SECP256K1).0)
+            })
+            .collect();
+        format!("thresh({},{})", keys.len() / 2 + 1, keys.join(","))
+    }
+
+    // Costs of a majority multisig growing with the number of keys (see `limits`)
+
+    #[bench]
+    pub fn bench_policy_limits_10_keys(bh: &mut Bencher) {
+        let policy = multisig_policy(10);
+        bh.iter(|| {
+            black_box(Policy::from_desc_or_policy_with_limits(
+                "", "", &policy, NETWORK, false,
+            ))
+            .unwrap();
+        });
+    }
+
+    #[bench]
+    pub fn bench_policy_limits_20_keys(bh: &mut Bencher) {
+        let policy = multisig_policy(20);
+        bh.iter(|| {
+            black_box(Policy::from_desc_or_policy_with_limits(
+                "", "", &policy, NETWORK, false,
+            ))
+            .unwrap();
+        });
+    }
+
+    #[bench]
+    pub fn bench_policy_limits_40_keys(bh: &mut Bencher) {
+        let policy = multisig_policy(40);
+        bh.iter(|| {
+            black_box(Policy::from_desc_or_policy_with_limits(
+                "", "", &policy, NETWORK, false,
+            ))
+            .unwrap();
+        });
+    }
+
+    #[bench]
+    pub fn bench_policy_limits_80_keys(bh: &mut Bencher) {
+        let policy = multisig_policy(80);
+        bh.iter(|| {
+            black_box(Policy::from_desc_or_policy_with_limits(
+                "", "", &policy, NETWORK, false,
+            ))
+            .unwrap();
+        });
+    }
+
+    fn timelocked_tx(lock_time: absolute::LockTime, sequence: Sequence) -> Transaction {
+        use keechain_core::bitcoin::{TxIn, Witness};
+
+        Transaction {
+            version: 2,
+            lock_time,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: ScriptBuf::new(),
+                sequence,
+                witness: Witness::new(),
+            }],
+            output: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_absolute_timelock_with_mock_clock() {
+        let confirmation_times = HashMap::new();
+
+        // Time based
+        let tx = timelocked_tx(
+            absolute::LockTime::from_time(1_709_133_311).unwrap(),
+            Sequence::ENABLE_RBF_NO_LOCKTIME,
+        );
+        let clock = MockClock::new(1_709_133_311 - 3600);
+        assert!(matches!(
+            check_timelocks(&tx, &confirmation_times, 800_000, &clock),
+            Err(Error::AbsoluteTimelockNotSatisfied)
+        ));
+        clock.advance_secs(3600);
+        assert!(check_timelocks(&tx, &confirmation_times, 800_000, &clock).is_ok());
+
+        // Height based
+        let tx = timelocked_tx(
+            absolute::LockTime::from_height(800_010).unwrap(),
+            Sequence::ENABLE_RBF_NO_LOCKTIME,
+        );
+        clock.set_height(800_000);
+        assert!(matches!(
+            check_timelocks(&tx, &confirmation_times, 800_000, &clock),
+            Err(Error::AbsoluteTimelockNotSatisfied)
+        ));
+        clock.advance_blocks(10);
+        assert!(check_timelocks(&tx, &confirmation_times, 800_000, &clock).is_ok());
+    }
+
+    #[test]
+    fn test_relative_timelock_with_mock_clock() {
+        let tx = timelocked_tx(absolute::LockTime::ZERO, Sequence(144));
+        let clock = MockClock::new(1_709_133_311);
+
+        let confirmed = ConfirmationTime::Confirmed {
+            height: 800_000,
+            time: 1_709_000_000,
+        };
+        let confirmation_times = HashMap::from([(OutPoint::null(), &confirmed)]);
+        clock.set_height(800_143);
+        assert!(matches!(
+            check_timelocks(&tx, &confirmation_times, 800_000, &clock),
+            Err(Error::RelativeTimelockNotSatisfied)
+        ));
+        clock.advance_blocks(1);
+        assert!(check_timelocks(&tx, &confirmation_times, 800_000, &clock).is_ok());
+
+        // Unconfirmed UTXOs never satisfy a relative timelock
+        let unconfirmed = ConfirmationTime::Unconfirmed { last_seen: 0 };
+        let confirmation_times = HashMap::from([(OutPoint::null(), &unconfirmed)]);
+        assert!(matches!(
+            check_timelocks(&tx, &confirmation_times, 900_000, &clock),
+            Err(Error::RelativeTimelockNotSatisfied)
+        ));
+    }
 }
@@ -7,6 +7,7 @@ pub use keechain_core::bitcoin::absolute::LockTime as AbsoluteLockTime;
 pub use keechain_core::bitcoin::Sequence;
 use keechain_core::miniscript::policy::concrete::Policy;
 use keechain_core::miniscript::DescriptorPublicKey;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -46,7 +47,7 @@ pub enum DecayingTime {
     Multiple(Vec<Locktime>),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum PolicyTemplateType {
     Singlesig,
     Multisig,
@@ -56,6 +57,18 @@ pub enum PolicyTemplateType {
     Decaying,
 }
 
+impl fmt::Display for PolicyTemplateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Singlesig => write!(f, "Singlesig"),
+            Self::Multisig => write!(f, "Multisig"),
+            Self::Recovery => write!(f, "Recovery"),
+            Self::Hold => write!(f, "Hold"),
+            Self::Decaying => write!(f, "Decaying"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Hash)]
 pub struct RecoveryTemplate {
     threshold: usize,
@@ -154,6 +167,20 @@ impl PolicyTemplate {
         }
     }
 
+    /// Get the keys used by the template
+    pub fn keys(&self) -> Vec<&DescriptorPublicKey> {
+        match self {
+            Self::Singlesig { key } => vec![key],
+            Self::Multisig { keys, .. } | Self::Decaying { keys, .. } => keys.iter().collect(),
+            Self::Recovery { my_key, recovery } => {
+                let mut keys: Vec<&DescriptorPublicKey> = vec![my_key];
+                keys.extend(recovery.keys.iter());
+                keys
+            }
+            Self::Hold { my_key, .. } => vec![my_key],
+        }
+    }
+
     pub fn build(self) -> Result<PolicyTemplateResult, Error> {
         match self {
             Self::Singlesig { key } => Ok(PolicyTemplateResult::Singlesig(key)),
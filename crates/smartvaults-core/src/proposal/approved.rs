@@ -4,7 +4,8 @@
 use keechain_core::bitcoin::psbt::PartiallySignedTransaction;
 use serde::{Deserialize, Serialize};
 
-use super::ProposalType;
+use super::{Error, ProposalType, SignerKeys};
+use crate::signer::Signer;
 use crate::util::{deserialize_psbt, serialize_psbt};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -60,4 +61,14 @@ impl ApprovedProposal {
             Self::KeyAgentPayment { psbt } => psbt.clone(),
         }
     }
+
+    /// Check if the PSBT contains signatures made by the keys of `signer`
+    pub fn is_signed_by(&self, signer: &Signer) -> Result<bool, Error> {
+        let keys = SignerKeys::new(signer)?;
+        Ok(self
+            .psbt()
+            .inputs
+            .iter()
+            .any(|input| keys.has_signatures(input)))
+    }
 }
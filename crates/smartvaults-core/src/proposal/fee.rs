@@ -0,0 +1,146 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Proposal fee sanity checks
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Error, Proposal};
+
+/// Default max fee: 5% of the spent amount
+const DEFAULT_FEE_CAP_PERCENTAGE: f64 = 5.0;
+/// Default max fee: 50 000 sat
+const DEFAULT_FEE_CAP_ABSOLUTE: u64 = 50_000;
+
+/// Absolute fee cap
+///
+/// The max allowed fee is the larger between `percentage` of the spent amount and `absolute` sats.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeCap {
+    pub percentage: f64,
+    pub absolute: u64,
+}
+
+impl Default for FeeCap {
+    fn default() -> Self {
+        Self {
+            percentage: DEFAULT_FEE_CAP_PERCENTAGE,
+            absolute: DEFAULT_FEE_CAP_ABSOLUTE,
+        }
+    }
+}
+
+impl FeeCap {
+    /// Max allowed fee for `amount`
+    pub fn max_fee(&self, amount: u64) -> u64 {
+        let relative: u64 = (amount as f64 * self.percentage / 100.0) as u64;
+        relative.max(self.absolute)
+    }
+}
+
+impl fmt::Display for FeeCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "max({}% of amount, {} sat)",
+            self.percentage, self.absolute
+        )
+    }
+}
+
+/// Suspicious fee in a [`Proposal`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeeWarning {
+    /// The fee is above the [`FeeCap`]
+    AboveCap { fee: u64, cap: u64 },
+    /// The fee rate (sat/vByte) is above the max rate allowed by the current estimates
+    AboveEstimate { fee_rate: u64, max_fee_rate: u64 },
+}
+
+impl fmt::Display for FeeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AboveCap { fee, cap } => {
+                write!(f, "fee of {fee} sat is above the cap of {cap} sat")
+            }
+            Self::AboveEstimate {
+                fee_rate,
+                max_fee_rate,
+            } => write!(
+                f,
+                "fee rate of {fee_rate} sat/vB is above the max of {max_fee_rate} sat/vB allowed by current estimates"
+            ),
+        }
+    }
+}
+
+impl Proposal {
+    /// Get the PSBT fee (sat)
+    pub fn fee(&self) -> Result<u64, Error> {
+        Ok(self.psbt().fee()?.to_sat())
+    }
+
+    /// Get the approximated PSBT fee rate (sat/vByte)
+    ///
+    /// Witnesses are not available in an unsigned PSBT so the vsize is underestimated (the fee rate overestimated).
+    pub fn fee_rate(&self) -> Result<f32, Error> {
+        let fee: u64 = self.fee()?;
+        let vsize: usize = self.psbt().unsigned_tx.vsize();
+        Ok(fee as f32 / vsize.max(1) as f32)
+    }
+
+    /// Check if the fee is sane
+    ///
+    /// `max_fee_rate` (sat/vByte) is usually derived from the current mempool estimates.
+    /// Proof of reserve proposals are never flagged.
+    pub fn check_fee(
+        &self,
+        cap: FeeCap,
+        max_fee_rate: Option<f32>,
+    ) -> Result<Option<FeeWarning>, Error> {
+        let amount: u64 = match self {
+            Self::Spending { amount, .. } => *amount,
+            Self::KeyAgentPayment { amount, .. } => *amount,
+            Self::ProofOfReserve { .. } => return Ok(None),
+        };
+
+        let fee: u64 = self.fee()?;
+        let cap: u64 = cap.max_fee(amount);
+        if fee > cap {
+            return Ok(Some(FeeWarning::AboveCap { fee, cap }));
+        }
+
+        if let Some(max_fee_rate) = max_fee_rate {
+            let fee_rate: f32 = self.fee_rate()?;
+            if fee_rate > max_fee_rate {
+                return Ok(Some(FeeWarning::AboveEstimate {
+                    fee_rate: fee_rate.ceil() as u64,
+                    max_fee_rate: max_fee_rate.ceil() as u64,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_cap() {
+        let cap = FeeCap::default();
+        assert_eq!(cap.max_fee(100_000), 50_000);
+        assert_eq!(cap.max_fee(10_000_000), 500_000);
+
+        let cap = FeeCap {
+            percentage: 1.0,
+            absolute: 1_000,
+        };
+        assert_eq!(cap.max_fee(50_000), 1_000);
+        assert_eq!(cap.max_fee(1_000_000), 10_000);
+    }
+}
@@ -8,11 +8,14 @@ use std::fmt;
 use keechain_core::bdk::signer::{SignerError, SignerWrapper};
 use keechain_core::bdk::{SignOptions, Wallet};
 use keechain_core::bitcoin::address::NetworkUnchecked;
+use keechain_core::bitcoin::bip32::{DerivationPath, Fingerprint, KeySource};
 use keechain_core::bitcoin::psbt::{
-    Error as PsbtError, PartiallySignedTransaction, PsbtParseError,
+    Error as PsbtError, Input, PartiallySignedTransaction, PsbtParseError,
 };
+use keechain_core::bitcoin::secp256k1::XOnlyPublicKey;
+use keechain_core::bitcoin::sighash::SighashCache;
 use keechain_core::bitcoin::{Address, Network, PrivateKey};
-use keechain_core::miniscript::psbt::PsbtExt;
+use keechain_core::miniscript::psbt::{PsbtExt, SighashError};
 use keechain_core::miniscript::Descriptor;
 use keechain_core::psbt::{Error as KPsbtError, PsbtUtility};
 use keechain_core::types::Seed;
@@ -20,10 +23,15 @@ use serde::{Deserialize, Serialize};
 
 mod approved;
 mod completed;
+mod fee;
+mod outputs;
 
 pub use self::approved::ApprovedProposal;
 pub use self::completed::CompletedProposal;
-use crate::util::{deserialize_psbt, serialize_psbt};
+pub use self::fee::{FeeCap, FeeWarning};
+pub use self::outputs::{OutputKind, OutputVerification};
+use crate::signer::Signer;
+use crate::util::{derive_change_descriptor, deserialize_psbt, serialize_psbt};
 use crate::SECP256K1;
 
 #[derive(Debug, thiserror::Error)]
@@ -38,8 +46,20 @@ pub enum Error {
     KPsbt(#[from] KPsbtError),
     #[error(transparent)]
     PsbtParse(#[from] PsbtParseError),
-    #[error("PSBT not signed (equal to base PSBT)")]
+    #[error(transparent)]
+    Miniscript(#[from] keechain_core::miniscript::Error),
+    #[error(transparent)]
+    Sighash(#[from] SighashError),
+    #[error(transparent)]
+    Signer(#[from] crate::signer::Error),
+    #[error("PSBT not signed (no new signatures)")]
     PsbtNotSigned,
+    #[error("the PSBT transaction doesn't match the proposal")]
+    PsbtTxMismatch,
+    #[error("invalid signature for input #{0}")]
+    InvalidPsbtSignature(usize),
+    #[error("signature with unexpected sighash type for input #{0}")]
+    PsbtSighashTypeMismatch(usize),
     #[error("approved proposals not proveded")]
     EmptyApprovedProposals,
     #[error("the provided approved proposals must have the same type")]
@@ -114,6 +134,75 @@ pub enum Proposal {
     },
 }
 
+/// Keys of a [`Signer`]: master fingerprint and derivation path of the account
+pub(crate) struct SignerKeys {
+    fingerprint: Fingerprint,
+    path: DerivationPath,
+}
+
+impl SignerKeys {
+    pub(crate) fn new(signer: &Signer) -> Result<Self, Error> {
+        let key = signer.descriptor_public_key()?;
+        Ok(Self {
+            fingerprint: key.master_fingerprint(),
+            path: key.full_derivation_path().unwrap_or_default(),
+        })
+    }
+
+    fn contains(&self, (fingerprint, path): &KeySource) -> bool {
+        *fingerprint == self.fingerprint && path.as_ref().starts_with(self.path.as_ref())
+    }
+
+    /// Remove the signatures of `input` made by other keys (the ones already in `base` are kept)
+    fn retain_signatures(&self, input: &mut Input, base: &Input) {
+        let bip32_derivation = input.bip32_derivation.clone();
+        input.partial_sigs.retain(|pk, _| {
+            base.partial_sigs.contains_key(pk)
+                || bip32_derivation
+                    .get(&pk.inner)
+                    .map_or(false, |ks| self.contains(ks))
+        });
+
+        let tap_key_origins = input.tap_key_origins.clone();
+        input.tap_script_sigs.retain(|key, _| {
+            base.tap_script_sigs.contains_key(key)
+                || tap_key_origins
+                    .get(&key.0)
+                    .map_or(false, |(_, ks)| self.contains(ks))
+        });
+
+        if base.tap_key_sig.is_none() {
+            let internal_key_is_mine: bool = input
+                .tap_internal_key
+                .and_then(|key| tap_key_origins.get(&key))
+                .map_or(false, |(_, ks)| self.contains(ks));
+            if !internal_key_is_mine {
+                input.tap_key_sig = None;
+            }
+        }
+    }
+
+    /// Check if `input` contains signatures of these keys
+    pub(crate) fn has_signatures(&self, input: &Input) -> bool {
+        let ecdsa: bool = input.partial_sigs.keys().any(|pk| {
+            input
+                .bip32_derivation
+                .get(&pk.inner)
+                .map_or(false, |ks| self.contains(ks))
+        });
+        let is_mine = |key: &XOnlyPublicKey| {
+            input
+                .tap_key_origins
+                .get(key)
+                .map_or(false, |(_, ks)| self.contains(ks))
+        };
+        let script: bool = input.tap_script_sigs.keys().any(|(key, _)| is_mine(key));
+        let key_spend: bool =
+            input.tap_key_sig.is_some() && input.tap_internal_key.as_ref().map_or(false, is_mine);
+        ecdsa || script || key_spend
+    }
+}
+
 impl PartialOrd for Proposal {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -240,23 +329,155 @@ impl Proposal {
         }
     }
 
+    /// Approve signing only with the keys of `signer` (derived from `seed`)
+    ///
+    /// The seed may hold other keys of the policy (i.e. another account), that unlock different paths:
+    /// their signatures are discarded.
+    pub fn approve_with_signer(
+        &self,
+        seed: &Seed,
+        signer: &Signer,
+        network: Network,
+    ) -> Result<ApprovedProposal, Error> {
+        let keys = SignerKeys::new(signer)?;
+        let base_psbt: PartiallySignedTransaction = self.psbt();
+        let mut psbt: PartiallySignedTransaction = self.approve(seed, Vec::new(), network)?.psbt();
+
+        for (input, base) in psbt.inputs.iter_mut().zip(base_psbt.inputs.iter()) {
+            keys.retain_signatures(input, base);
+        }
+
+        if psbt == base_psbt {
+            return Err(Error::PsbtNotSigned);
+        }
+
+        match self {
+            Proposal::Spending { .. } => Ok(ApprovedProposal::spending(psbt)),
+            Proposal::ProofOfReserve { .. } => Ok(ApprovedProposal::proof_of_reserve(psbt)),
+            Proposal::KeyAgentPayment { .. } => Ok(ApprovedProposal::key_agent_payment(psbt)),
+        }
+    }
+
+    /// Check if the proposal can be finalized with the signatures of `seed` only
+    pub fn can_be_finalized_by(&self, seed: &Seed, network: Network) -> Result<bool, Error> {
+        let approved: ApprovedProposal = self.approve(seed, Vec::new(), network)?;
+        Ok(self.finalize([approved], network).is_ok())
+    }
+
+    /// Approve with a PSBT signed externally (ex. airgapped device)
+    ///
+    /// The unsigned transaction must match the proposal one.
+    /// Only the new signatures made by keys of the descriptor are taken (after verifying them and
+    /// their sighash type against the one of the proposal input), any other change to the PSBT is discarded.
     pub fn approve_with_signed_psbt(
         &self,
         signed_psbt: PartiallySignedTransaction,
     ) -> Result<ApprovedProposal, Error> {
-        if signed_psbt != self.psbt() {
-            // TODO: check if psbt was signed with the correct signer
-            match self {
-                Proposal::Spending { .. } => Ok(ApprovedProposal::spending(signed_psbt)),
-                Proposal::ProofOfReserve { .. } => {
-                    Ok(ApprovedProposal::proof_of_reserve(signed_psbt))
+        let mut psbt: PartiallySignedTransaction = self.psbt();
+
+        if signed_psbt.unsigned_tx != psbt.unsigned_tx
+            || signed_psbt.inputs.len() != psbt.inputs.len()
+        {
+            return Err(Error::PsbtTxMismatch);
+        }
+
+        let base_psbt: PartiallySignedTransaction = psbt.clone();
+        let mut cache = SighashCache::new(&base_psbt.unsigned_tx);
+        let mut new_signatures: usize = 0;
+
+        for (index, (input, signed)) in psbt
+            .inputs
+            .iter_mut()
+            .zip(signed_psbt.inputs.into_iter())
+            .enumerate()
+        {
+            // ECDSA
+            for (public_key, signature) in signed.partial_sigs.into_iter() {
+                if input.partial_sigs.contains_key(&public_key) {
+                    continue;
                 }
-                Proposal::KeyAgentPayment { .. } => {
-                    Ok(ApprovedProposal::key_agent_payment(signed_psbt))
+
+                if !input.bip32_derivation.contains_key(&public_key.inner) {
+                    return Err(Error::InvalidPsbtSignature(index));
                 }
+
+                if input.ecdsa_hash_ty().ok() != Some(signature.hash_ty) {
+                    return Err(Error::PsbtSighashTypeMismatch(index));
+                }
+
+                let msg = base_psbt
+                    .sighash_msg(index, &mut cache, None)?
+                    .to_secp_msg();
+                SECP256K1
+                    .verify_ecdsa(&msg, &signature.sig, &public_key.inner)
+                    .map_err(|_| Error::InvalidPsbtSignature(index))?;
+
+                input.partial_sigs.insert(public_key, signature);
+                new_signatures += 1;
             }
-        } else {
-            Err(Error::PsbtNotSigned)
+
+            // Taproot key path
+            if let (None, Some(signature)) = (input.tap_key_sig, signed.tap_key_sig) {
+                let output_key: XOnlyPublicKey = input
+                    .witness_utxo
+                    .as_ref()
+                    .filter(|utxo| utxo.script_pubkey.is_v1_p2tr())
+                    .and_then(|utxo| {
+                        XOnlyPublicKey::from_slice(&utxo.script_pubkey.as_bytes()[2..]).ok()
+                    })
+                    .ok_or(Error::InvalidPsbtSignature(index))?;
+
+                if input.taproot_hash_ty().ok() != Some(signature.hash_ty) {
+                    return Err(Error::PsbtSighashTypeMismatch(index));
+                }
+
+                let msg = base_psbt
+                    .sighash_msg(index, &mut cache, None)?
+                    .to_secp_msg();
+                SECP256K1
+                    .verify_schnorr(&signature.sig, &msg, &output_key)
+                    .map_err(|_| Error::InvalidPsbtSignature(index))?;
+
+                input.tap_key_sig = Some(signature);
+                new_signatures += 1;
+            }
+
+            // Taproot script path
+            for ((public_key, leaf_hash), signature) in signed.tap_script_sigs.into_iter() {
+                if input.tap_script_sigs.contains_key(&(public_key, leaf_hash)) {
+                    continue;
+                }
+
+                if !input.tap_key_origins.contains_key(&public_key) {
+                    return Err(Error::InvalidPsbtSignature(index));
+                }
+
+                if input.taproot_hash_ty().ok() != Some(signature.hash_ty) {
+                    return Err(Error::PsbtSighashTypeMismatch(index));
+                }
+
+                let msg = base_psbt
+                    .sighash_msg(index, &mut cache, Some(leaf_hash))?
+                    .to_secp_msg();
+                SECP256K1
+                    .verify_schnorr(&signature.sig, &msg, &public_key)
+                    .map_err(|_| Error::InvalidPsbtSignature(index))?;
+
+                input
+                    .tap_script_sigs
+                    .insert((public_key, leaf_hash), signature);
+                new_signatures += 1;
+            }
+        }
+
+        if new_signatures == 0 {
+            return Err(Error::PsbtNotSigned);
+        }
+
+        match self {
+            Proposal::Spending { .. } => Ok(ApprovedProposal::spending(psbt)),
+            Proposal::ProofOfReserve { .. } => Ok(ApprovedProposal::proof_of_reserve(psbt)),
+            Proposal::KeyAgentPayment { .. } => Ok(ApprovedProposal::key_agent_payment(psbt)),
         }
     }
 
@@ -286,23 +507,35 @@ impl Proposal {
     // }
     // }
 
-    pub fn finalize<I>(
+    /// Combine the PSBT of the proposal with the approved ones
+    pub fn combine_approvals<I>(
         &self,
         approved_proposals: I,
-        network: Network,
-    ) -> Result<CompletedProposal, Error>
+    ) -> Result<PartiallySignedTransaction, Error>
     where
         I: IntoIterator<Item = ApprovedProposal>,
     {
         let mut base_psbt: PartiallySignedTransaction = self.psbt();
-
-        // Combine PSBTs
         for proposal in approved_proposals.into_iter() {
             if proposal.get_type() != self.get_type() {
                 return Err(Error::ApprovedProposalTypeMismatch);
             }
             base_psbt.combine(proposal.psbt())?;
         }
+        Ok(base_psbt)
+    }
+
+    pub fn finalize<I>(
+        &self,
+        approved_proposals: I,
+        network: Network,
+    ) -> Result<CompletedProposal, Error>
+    where
+        I: IntoIterator<Item = ApprovedProposal>,
+    {
+        // Combine PSBTs
+        let mut base_psbt: PartiallySignedTransaction =
+            self.combine_approvals(approved_proposals)?;
 
         // Finalize the proposal
         match self {
@@ -336,7 +569,14 @@ impl Proposal {
                 message,
                 ..
             } => {
-                let wallet = Wallet::new_no_persist(&descriptor.to_string(), None, network)?;
+                // Include the change keychain: the proof may contains UTXOs of policies with a separate change descriptor
+                let change_descriptor: Option<String> =
+                    derive_change_descriptor(descriptor).map(|d| d.to_string());
+                let wallet = Wallet::new_no_persist(
+                    &descriptor.to_string(),
+                    change_descriptor.as_deref(),
+                    network,
+                )?;
                 let signopts = SignOptions {
                     trust_witness_utxo: true,
                     remove_partial_sigs: false,
@@ -355,3 +595,319 @@ impl Proposal {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use keechain_core::bips::bip39::Mnemonic;
+    use keechain_core::bitcoin::bip32::ChildNumber;
+    use keechain_core::bitcoin::hashes::Hash;
+    use keechain_core::bitcoin::key::{KeyPair, TweakedPublicKey};
+    use keechain_core::bitcoin::secp256k1::{schnorr, Message, SecretKey};
+    use keechain_core::bitcoin::sighash::{EcdsaSighashType, TapSighashType};
+    use keechain_core::bitcoin::taproot::{self, TapLeafHash};
+    use keechain_core::bitcoin::{
+        absolute, ecdsa, OutPoint, PublicKey, ScriptBuf, Transaction, TxIn, TxOut, Txid,
+    };
+
+    use super::*;
+    use crate::SECP256K1;
+
+    const NETWORK: Network = Network::Testnet;
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+
+    fn x_only(byte: u8) -> XOnlyPublicKey {
+        let secret_key = SecretKey::from_slice(&[byte; 32]).unwrap();
+        KeyPair::from_secret_key(&SECP256K1, &secret_key)
+            .x_only_public_key()
+            .0
+    }
+
+    fn signature() -> taproot::Signature {
+        taproot::Signature {
+            sig: schnorr::Signature::from_slice(&[1; 64]).unwrap(),
+            hash_ty: TapSighashType::Default,
+        }
+    }
+
+    fn secret_key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn p2tr(key: XOnlyPublicKey) -> ScriptBuf {
+        ScriptBuf::new_v1_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(key))
+    }
+
+    /// Proposal spending a single input
+    fn proposal(input: Input) -> Proposal {
+        let unsigned_tx = Transaction {
+            version: 2,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), 0),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: 9_000,
+                script_pubkey: p2tr(x_only(9)),
+            }],
+        };
+        let mut psbt = PartiallySignedTransaction::from_unsigned_tx(unsigned_tx).unwrap();
+        psbt.inputs[0] = input;
+        Proposal::proof_of_reserve(Descriptor::from_str(DESCRIPTOR).unwrap(), "test", psbt)
+    }
+
+    /// P2WPKH input of `secret_key`
+    fn ecdsa_proposal(secret_key: &SecretKey) -> Proposal {
+        let public_key = PublicKey::new(secret_key.public_key(&SECP256K1));
+        let mut input = Input {
+            witness_utxo: Some(TxOut {
+                value: 10_000,
+                script_pubkey: ScriptBuf::new_v0_p2wpkh(&public_key.wpubkey_hash().unwrap()),
+            }),
+            ..Default::default()
+        };
+        input.bip32_derivation.insert(
+            public_key.inner,
+            (
+                Fingerprint::from_str("00000000").unwrap(),
+                DerivationPath::default(),
+            ),
+        );
+        proposal(input)
+    }
+
+    fn sighash(psbt: &PartiallySignedTransaction, leaf_hash: Option<TapLeafHash>) -> Message {
+        psbt.sighash_msg(0, &mut SighashCache::new(&psbt.unsigned_tx), leaf_hash)
+            .unwrap()
+            .to_secp_msg()
+    }
+
+    fn sign_ecdsa(
+        proposal: &Proposal,
+        secret_key: &SecretKey,
+        hash_ty: EcdsaSighashType,
+    ) -> PartiallySignedTransaction {
+        let mut psbt = proposal.psbt();
+        let msg = sighash(&psbt, None);
+        psbt.inputs[0].partial_sigs.insert(
+            PublicKey::new(secret_key.public_key(&SECP256K1)),
+            ecdsa::Signature {
+                sig: SECP256K1.sign_ecdsa(&msg, secret_key),
+                hash_ty,
+            },
+        );
+        psbt
+    }
+
+    #[test]
+    fn test_approve_with_signed_psbt_ecdsa() {
+        let secret_key = secret_key(1);
+        let proposal = ecdsa_proposal(&secret_key);
+
+        // Unchanged
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(proposal.psbt()),
+            Err(Error::PsbtNotSigned)
+        ));
+
+        // Different unsigned tx
+        let mut signed = sign_ecdsa(&proposal, &secret_key, EcdsaSighashType::All);
+        signed.unsigned_tx.output[0].value = 1_000;
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::PsbtTxMismatch)
+        ));
+
+        // Forged signature
+        let mut signed = proposal.psbt();
+        let msg = Message::from_slice(&[1; 32]).unwrap();
+        signed.inputs[0].partial_sigs.insert(
+            PublicKey::new(secret_key.public_key(&SECP256K1)),
+            ecdsa::Signature {
+                sig: SECP256K1.sign_ecdsa(&msg, &secret_key),
+                hash_ty: EcdsaSighashType::All,
+            },
+        );
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::InvalidPsbtSignature(0))
+        ));
+
+        // Valid signature of a key outside the `bip32_derivation`
+        let signed = sign_ecdsa(&proposal, &self::secret_key(2), EcdsaSighashType::All);
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::InvalidPsbtSignature(0))
+        ));
+
+        // Valid signature with a sighash type different from the input one
+        let signed = sign_ecdsa(
+            &proposal,
+            &secret_key,
+            EcdsaSighashType::SinglePlusAnyoneCanPay,
+        );
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::PsbtSighashTypeMismatch(0))
+        ));
+
+        // Valid signature, foreign fields dropped
+        let mut signed = sign_ecdsa(&proposal, &secret_key, EcdsaSighashType::All);
+        let mut expected = proposal.psbt();
+        expected.inputs[0].partial_sigs = signed.inputs[0].partial_sigs.clone();
+        signed.inputs[0].redeem_script = Some(ScriptBuf::from(vec![0x51]));
+        signed.outputs[0].redeem_script = Some(ScriptBuf::from(vec![0x51]));
+        let approved = proposal.approve_with_signed_psbt(signed).unwrap();
+        assert_eq!(approved.psbt(), expected);
+    }
+
+    #[test]
+    fn test_approve_with_signed_psbt_taproot_key_path() {
+        let keypair = KeyPair::from_secret_key(&SECP256K1, &secret_key(1));
+        let proposal = proposal(Input {
+            witness_utxo: Some(TxOut {
+                value: 10_000,
+                script_pubkey: p2tr(keypair.x_only_public_key().0),
+            }),
+            ..Default::default()
+        });
+
+        let mut signed = proposal.psbt();
+        let msg = sighash(&signed, None);
+        let sig = SECP256K1.sign_schnorr_no_aux_rand(&msg, &keypair);
+
+        // Sighash type different from the input one
+        signed.inputs[0].tap_key_sig = Some(taproot::Signature {
+            sig,
+            hash_ty: TapSighashType::All,
+        });
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed.clone()),
+            Err(Error::PsbtSighashTypeMismatch(0))
+        ));
+
+        signed.inputs[0].tap_key_sig = Some(taproot::Signature {
+            sig,
+            hash_ty: TapSighashType::Default,
+        });
+        let approved = proposal.approve_with_signed_psbt(signed.clone()).unwrap();
+        assert_eq!(approved.psbt(), signed);
+
+        // Signature of another key
+        let other = KeyPair::from_secret_key(&SECP256K1, &secret_key(2));
+        signed.inputs[0].tap_key_sig = Some(taproot::Signature {
+            sig: SECP256K1.sign_schnorr_no_aux_rand(&msg, &other),
+            hash_ty: TapSighashType::Default,
+        });
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::InvalidPsbtSignature(0))
+        ));
+    }
+
+    #[test]
+    fn test_approve_with_signed_psbt_taproot_script_path() {
+        let keypair = KeyPair::from_secret_key(&SECP256K1, &secret_key(1));
+        let public_key = keypair.x_only_public_key().0;
+        let leaf_hash = TapLeafHash::all_zeros();
+        let mut input = Input {
+            witness_utxo: Some(TxOut {
+                value: 10_000,
+                script_pubkey: p2tr(x_only(3)),
+            }),
+            ..Default::default()
+        };
+        input.tap_key_origins.insert(
+            public_key,
+            (
+                vec![leaf_hash],
+                (
+                    Fingerprint::from_str("00000000").unwrap(),
+                    DerivationPath::default(),
+                ),
+            ),
+        );
+        let proposal = proposal(input);
+
+        let mut signed = proposal.psbt();
+        let msg = sighash(&signed, Some(leaf_hash));
+        signed.inputs[0].tap_script_sigs.insert(
+            (public_key, leaf_hash),
+            taproot::Signature {
+                sig: SECP256K1.sign_schnorr_no_aux_rand(&msg, &keypair),
+                hash_ty: TapSighashType::Default,
+            },
+        );
+        let approved = proposal.approve_with_signed_psbt(signed.clone()).unwrap();
+        assert_eq!(approved.psbt(), signed);
+
+        // Key outside the `tap_key_origins`
+        let other = KeyPair::from_secret_key(&SECP256K1, &secret_key(2));
+        let mut signed = proposal.psbt();
+        signed.inputs[0].tap_script_sigs.insert(
+            (other.x_only_public_key().0, leaf_hash),
+            taproot::Signature {
+                sig: SECP256K1.sign_schnorr_no_aux_rand(&msg, &other),
+                hash_ty: TapSighashType::Default,
+            },
+        );
+        assert!(matches!(
+            proposal.approve_with_signed_psbt(signed),
+            Err(Error::InvalidPsbtSignature(0))
+        ));
+    }
+
+    #[test]
+    fn test_signer_keys() {
+        let mnemonic = Mnemonic::from_str(
+            "possible suffer flavor boring essay zoo collect stairs day cabbage wasp tackle",
+        )
+        .unwrap();
+        let signer =
+            Signer::from_seed("Signer", None, Seed::from_mnemonic(mnemonic), None, NETWORK)
+                .unwrap();
+        let fingerprint: Fingerprint = signer.fingerprint();
+        let keys = SignerKeys::new(&signer).unwrap();
+
+        // Same seed, different accounts
+        let mine = x_only(1);
+        let other_account = x_only(2);
+        let leaf = TapLeafHash::all_zeros();
+
+        let mut input = Input::default();
+        input.tap_key_origins.insert(
+            mine,
+            (
+                vec![leaf],
+                (
+                    fingerprint,
+                    keys.path.child(ChildNumber::from_normal_idx(3).unwrap()),
+                ),
+            ),
+        );
+        input.tap_key_origins.insert(
+            other_account,
+            (
+                vec![leaf],
+                (
+                    fingerprint,
+                    DerivationPath::from_str("m/86'/1'/784923'/0/3").unwrap(),
+                ),
+            ),
+        );
+        input
+            .tap_script_sigs
+            .insert((other_account, leaf), signature());
+        assert!(!keys.has_signatures(&input));
+
+        input.tap_script_sigs.insert((mine, leaf), signature());
+        assert!(keys.has_signatures(&input));
+
+        let base = Input::default();
+        keys.retain_signatures(&mut input, &base);
+        assert_eq!(input.tap_script_sigs.len(), 1);
+        assert!(input.tap_script_sigs.contains_key(&(mine, leaf)));
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Proposal outputs verification
+
+use std::fmt;
+use std::str::FromStr;
+
+use keechain_core::bitcoin::bip32::{ChildNumber, KeySource};
+use keechain_core::bitcoin::psbt::Output;
+use keechain_core::bitcoin::ScriptBuf;
+use keechain_core::miniscript::{Descriptor, DescriptorPublicKey};
+
+use super::{Error, Proposal};
+
+/// Proposal output classification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputKind {
+    /// The output pays to the proposal recipient
+    Recipient,
+    /// The output pays back to the vault descriptor
+    Change,
+    /// The output is not the recipient and not match the vault descriptor
+    Unverified,
+}
+
+impl fmt::Display for OutputKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Recipient => write!(f, "external recipient"),
+            Self::Change => write!(f, "change (verified)"),
+            Self::Unverified => write!(f, "UNVERIFIED - does not match vault descriptor"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutputVerification {
+    pub vout: usize,
+    pub script_pubkey: ScriptBuf,
+    pub value: u64,
+    pub kind: OutputKind,
+}
+
+/// Derive the vault script from the key origins of the PSBT output
+///
+/// Return `None` if the output not contains key origins or if they are inconsistent.
+fn derive_vault_script(
+    descriptor: &Descriptor<DescriptorPublicKey>,
+    output: &Output,
+) -> Option<ScriptBuf> {
+    let mut origins = output
+        .tap_key_origins
+        .values()
+        .map(|(_, key_source)| key_source)
+        .chain(output.bip32_derivation.values());
+
+    // Derivation index is the last child of the key origin path (wildcard)
+    let index = |(_, path): &KeySource| match path.as_ref().last() {
+        Some(ChildNumber::Normal { index }) => Some(*index),
+        _ => None,
+    };
+
+    let first: u32 = index(origins.next()?)?;
+    if origins.any(|key_source| index(key_source) != Some(first)) {
+        return None;
+    }
+
+    let derived = descriptor.at_derivation_index(first).ok()?;
+    Some(derived.script_pubkey())
+}
+
+impl Proposal {
+    /// Check that every non-recipient output pays back to the vault `descriptor`
+    ///
+    /// The change script is derived from the vault descriptor using the key origins of the PSBT outputs.
+    /// Proof of reserve proposals have not outputs to verify.
+    pub fn verify_outputs(
+        &self,
+        descriptor: &Descriptor<String>,
+    ) -> Result<Vec<OutputVerification>, Error> {
+        self.verify_outputs_with_change(descriptor, None)
+    }
+
+    /// Same as [`Proposal::verify_outputs`] but accept also outputs paying to the vault `change_descriptor`
+    pub fn verify_outputs_with_change(
+        &self,
+        descriptor: &Descriptor<String>,
+        change_descriptor: Option<&Descriptor<String>>,
+    ) -> Result<Vec<OutputVerification>, Error> {
+        let mut descriptors: Vec<Descriptor<DescriptorPublicKey>> = Vec::with_capacity(2);
+        for descriptor in std::iter::once(descriptor).chain(change_descriptor) {
+            descriptors.push(Descriptor::from_str(&descriptor.to_string())?);
+        }
+
+        let (recipient, amount): (Option<ScriptBuf>, Option<u64>) = match self {
+            Self::Spending { to_address, .. } => (
+                Some(to_address.clone().assume_checked().script_pubkey()),
+                None,
+            ),
+            Self::KeyAgentPayment { amount, .. } => (None, Some(*amount)),
+            Self::ProofOfReserve { .. } => return Ok(Vec::new()),
+        };
+
+        let psbt = self.psbt();
+        let mut outputs: Vec<OutputVerification> = Vec::with_capacity(psbt.outputs.len());
+        for (vout, (txout, output)) in psbt
+            .unsigned_tx
+            .output
+            .iter()
+            .zip(psbt.outputs.iter())
+            .enumerate()
+        {
+            let is_recipient: bool = match (&recipient, amount) {
+                (Some(script), _) => script == &txout.script_pubkey,
+                (None, Some(amount)) => {
+                    output.tap_key_origins.is_empty()
+                        && output.bip32_derivation.is_empty()
+                        && txout.value == amount
+                }
+                (None, None) => false,
+            };
+
+            let kind: OutputKind = if is_recipient {
+                OutputKind::Recipient
+            } else if descriptors.iter().any(|descriptor| {
+                derive_vault_script(descriptor, output).as_ref() == Some(&txout.script_pubkey)
+            }) {
+                OutputKind::Change
+            } else {
+                OutputKind::Unverified
+            };
+
+            outputs.push(OutputVerification {
+                vout,
+                script_pubkey: txout.script_pubkey.clone(),
+                value: txout.value,
+                kind,
+            });
+        }
+
+        Ok(outputs)
+    }
+}
@@ -10,6 +10,8 @@ use keechain_core::bips::bip48::ScriptType;
 use keechain_core::bitcoin::Network;
 use keechain_core::crypto::hash;
 use keechain_core::descriptors::{self, ToDescriptor};
+#[cfg(feature = "hwi")]
+use keechain_core::hwi::{types::HWIDevice, HWIClient};
 use keechain_core::miniscript::descriptor::{DescriptorKeyParseError, DescriptorType};
 use keechain_core::miniscript::{Descriptor, DescriptorPublicKey};
 use keechain_core::{ColdcardGenericJson, Purpose, Seed};
@@ -17,6 +19,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::constants::SMARTVAULTS_ACCOUNT_INDEX;
+use crate::policy::{KeyPurpose, PolicyTemplateType, ScriptType};
 use crate::SECP256K1;
 
 #[derive(Debug, Error)]
@@ -33,8 +36,34 @@ pub enum Error {
     BdkDescriptor(#[from] bdk::descriptor::DescriptorError),
     #[error(transparent)]
     Coldcard(#[from] keechain_core::export::coldcard::Error),
+    #[cfg(feature = "hwi")]
+    #[error(transparent)]
+    HWI(#[from] keechain_core::hwi::error::Error),
     #[error("must be a taproot descriptor")]
     NotTaprootDescriptor,
+    #[error("signer key derived for {purpose}: no key for {script_type} addresses")]
+    PurposeMismatch {
+        purpose: KeyPurpose,
+        script_type: ScriptType,
+    },
+}
+
+/// Key of the descriptor, if derived for the `script_type`
+fn descriptor_public_key_for(
+    descriptor: &Descriptor<DescriptorPublicKey>,
+    script_type: ScriptType,
+) -> Result<DescriptorPublicKey, Error> {
+    let key: DescriptorPublicKey = match descriptor {
+        Descriptor::Tr(tr) => tr.internal_key().clone(),
+        _ => return Err(Error::NotTaprootDescriptor),
+    };
+    match KeyPurpose::from_key(&key) {
+        Some(purpose) if !script_type.matches_purpose(&purpose) => Err(Error::PurposeMismatch {
+            purpose,
+            script_type,
+        }),
+        _ => Ok(key),
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -122,29 +151,32 @@ impl Signer {
         )
     }
 
-    // pub fn from_hwi<S>(
-    // name: S,
-    // description: Option<S>,
-    // device: HWIDevice,
-    // account: Option<u32>,
-    // network: Network,
-    // ) -> Result<Self, Error>
-    // where
-    // S: Into<String>,
-    // {
-    // let client = HWIClient::get_client(&device, false, network)?;
-    // let path = bip32::account_extended_path(86, network, account)?;
-    // let xpub = client.get_xpub(&path, false)?;
-    // let descriptor =
-    // descriptors::typed_descriptor(device.fingerprint, xpub.xpub, &path, false)?;
-    // Self::new(
-    // name,
-    // description,
-    // device.fingerprint,
-    // descriptor,
-    // SignerType::Hardware,
-    // )
-    // }
+    /// Build [`Signer`] from a hardware device connected via HWI
+    #[cfg(feature = "hwi")]
+    pub fn from_hwi<S>(
+        name: S,
+        description: Option<S>,
+        device: HWIDevice,
+        account: Option<u32>,
+        network: Network,
+    ) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let client = HWIClient::get_client(&device, false, network)?;
+        let path = bip32::account_extended_path(86, network, account)?;
+        let xpub = client.get_xpub(&path, false)?;
+        let descriptor =
+            descriptors::typed_descriptor(device.fingerprint, xpub.xpub, &path, false)?;
+        Self::new(
+            name,
+            description,
+            device.fingerprint,
+            descriptor,
+            SignerType::Hardware,
+            network,
+        )
+    }
 
     pub fn airgap<S>(
         name: S,
@@ -201,6 +233,21 @@ impl Signer {
         }
     }
 
+    /// Key to use in a policy with `script_type` addresses
+    ///
+    /// Return [`Error::PurposeMismatch`] if the key was derived for another script type (i.e. BIP48 P2WSH).
+    pub fn descriptor_public_key_for(
+        &self,
+        script_type: ScriptType,
+    ) -> Result<DescriptorPublicKey, Error> {
+        descriptor_public_key_for(&self.descriptor, script_type)
+    }
+
+    /// Purpose of the key (`None` if the key has no origin info)
+    pub fn purpose(&self) -> Option<KeyPurpose> {
+        KeyPurpose::from_key(&self.descriptor_public_key().ok()?)
+    }
+
     pub fn signer_type(&self) -> SignerType {
         self.t
     }
@@ -227,10 +274,60 @@ pub fn smartvaults_signer(seed: Seed, network: Network) -> Result<Signer, Error>
     )
 }
 
+/// Intended usage of a [`SharedSigner`]
+///
+/// Advisory only: it's not enforced by the protocol and a client is free to ignore it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharedSignerScope {
+    /// Policy templates the signer is intended for (empty means any)
+    pub templates: Vec<PolicyTemplateType>,
+    /// Unix timestamp after which the signer should no longer be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Max number of policies the signer is intended to be used in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+}
+
+impl SharedSignerScope {
+    /// Check if the scope is expired at `now` (unix timestamp)
+    pub fn is_expired(&self, now: u64) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now >= expires_at,
+            None => false,
+        }
+    }
+
+    /// Check if the signer is intended for policies matching `template`
+    ///
+    /// Policies not matching any template (`None`) are allowed only if no template is set.
+    pub fn allows_template(&self, template: Option<PolicyTemplateType>) -> bool {
+        if self.templates.is_empty() {
+            return true;
+        }
+        match template {
+            Some(template) => self.templates.contains(&template),
+            None => false,
+        }
+    }
+
+    /// Check if the signer can be used in another policy, given the current number of `uses`
+    pub fn allows_uses(&self, uses: usize) -> bool {
+        match self.max_uses {
+            Some(max_uses) => uses < max_uses as usize,
+            None => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct SharedSigner {
     fingerprint: Fingerprint,
     descriptor: Descriptor<DescriptorPublicKey>,
+    /// Not serialized if not set, so the event is still readable by old clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<SharedSignerScope>,
 }
 
 impl From<Signer> for SharedSigner {
@@ -238,6 +335,7 @@ impl From<Signer> for SharedSigner {
         Self {
             fingerprint: value.fingerprint,
             descriptor: value.descriptor,
+            scope: None,
         }
     }
 }
@@ -257,4 +355,97 @@ impl SharedSigner {
             _ => Err(Error::NotTaprootDescriptor),
         }
     }
+
+    /// See [`Signer::descriptor_public_key_for`]
+    pub fn descriptor_public_key_for(
+        &self,
+        script_type: ScriptType,
+    ) -> Result<DescriptorPublicKey, Error> {
+        descriptor_public_key_for(&self.descriptor, script_type)
+    }
+
+    /// Purpose of the key (`None` if the key has no origin info)
+    pub fn purpose(&self) -> Option<KeyPurpose> {
+        KeyPurpose::from_key(&self.descriptor_public_key().ok()?)
+    }
+
+    pub fn scope(&self) -> Option<&SharedSignerScope> {
+        self.scope.as_ref()
+    }
+
+    pub fn with_scope(mut self, scope: Option<SharedSignerScope>) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Check if the [`SharedSignerScope`] is expired at `now` (unix timestamp)
+    pub fn is_expired(&self, now: u64) -> bool {
+        match &self.scope {
+            Some(scope) => scope.is_expired(now),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARED_SIGNER: &str = r#"{"fingerprint":"9bf4354b","descriptor":"tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*)"}"#;
+
+    #[test]
+    fn test_shared_signer_scope_compat() {
+        // Shared by an old client
+        let shared_signer: SharedSigner = serde_json::from_str(SHARED_SIGNER).unwrap();
+        assert!(shared_signer.scope().is_none());
+        assert!(!shared_signer.is_expired(u64::MAX));
+        assert!(!serde_json::to_string(&shared_signer)
+            .unwrap()
+            .contains("scope"));
+
+        let scope = SharedSignerScope {
+            templates: vec![PolicyTemplateType::Multisig],
+            expires_at: Some(1_700_000_000),
+            max_uses: Some(1),
+        };
+        let shared_signer = shared_signer.with_scope(Some(scope.clone()));
+        let json: String = serde_json::to_string(&shared_signer).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SharedSigner>(&json).unwrap(),
+            shared_signer
+        );
+
+        assert!(!shared_signer.is_expired(1_699_999_999));
+        assert!(shared_signer.is_expired(1_700_000_000));
+        assert!(scope.allows_template(Some(PolicyTemplateType::Multisig)));
+        assert!(!scope.allows_template(Some(PolicyTemplateType::Hold)));
+        assert!(!scope.allows_template(None));
+        assert!(SharedSignerScope::default().allows_template(None));
+        assert!(scope.allows_uses(0));
+        assert!(!scope.allows_uses(1));
+    }
+
+    #[test]
+    fn test_descriptor_public_key_for_script_type() {
+        let shared_signer: SharedSigner = serde_json::from_str(SHARED_SIGNER).unwrap();
+        assert_eq!(shared_signer.purpose(), Some(KeyPurpose::Bip86));
+        assert_eq!(
+            shared_signer
+                .descriptor_public_key_for(ScriptType::Tr)
+                .unwrap(),
+            shared_signer.descriptor_public_key().unwrap()
+        );
+
+        // BIP48 P2WSH key
+        let shared_signer: SharedSigner = serde_json::from_str(r#"{"fingerprint":"f3ab64d8","descriptor":"tr([f3ab64d8/48'/1'/0'/2']tpubDCh4uyVDVretfgTNkazUarV9ESTh7DJy8yvMSuWn5PQFbTDEsJwHGSBvTrNF92kw3x5ZLFXw91gN5LYtuSCbr1Vo6mzQmD49sF2vGpReZp2/0/*)"}"#).unwrap();
+        assert!(matches!(
+            shared_signer.descriptor_public_key_for(ScriptType::Tr),
+            Err(Error::PurposeMismatch {
+                purpose: KeyPurpose::Bip48 {
+                    script_type: Some(2)
+                },
+                script_type: ScriptType::Tr,
+            })
+        ));
+    }
 }
@@ -20,6 +20,7 @@ pub enum Priority {
 }
 
 impl Priority {
+    /// Default target blocks (10 minutes block time)
     #[inline]
     pub fn target_blocks(&self) -> u8 {
         match self {
@@ -90,19 +91,115 @@ impl fmt::Display for FeeRate {
 pub enum Amount {
     Max,
     Custom(u64),
+    /// Send the amount to the recipient minus the fee
+    ///
+    /// Spends have a single recipient, which always pays the fee: choosing the paying output
+    /// among multiple recipients is not supported.
+    CustomSubtractFee(u64),
 }
 
+/// Max bare number (without unit) accepted as sats
+///
+/// Above this, a bare number could be a BTC amount with the decimal separator forgotten (or the
+/// other way around): an explicit unit is required.
+const MAX_BARE_AMOUNT: u64 = 21_000_000;
+/// Max supply (sat)
+const MAX_MONEY: u64 = 21_000_000 * SAT_PER_BTC;
+const SAT_PER_BTC: u64 = 100_000_000;
+const BTC_DECIMALS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseAmountError {
+    #[error("invalid amount: {0}")]
+    Invalid(String),
+    #[error("too many decimals: max 8 for BTC amounts")]
+    TooPrecise,
+    #[error("amount exceeds the max supply")]
+    Overflow,
+    #[error("ambiguous amount {0}: specify the unit (ex. `{0}sat` or `0.015btc`)")]
+    Ambiguous(String),
+}
+
+/// Parse an amount
+///
+/// Formats: `max` (or `all`), `<sat>sat`, `<btc>btc` or a bare number of sats up to
+/// 21,000,000. Underscores can be used as separators (ex. `1_500_000sat`).
 impl FromStr for Amount {
-    type Err = std::num::ParseIntError;
+    type Err = ParseAmountError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "max" => Ok(Amount::Max),
-            _ => Ok(Amount::Custom(s.parse()?)),
+        let amount: String = s.trim().to_lowercase().replace('_', "");
+        match amount.as_str() {
+            "max" | "all" => Ok(Amount::Max),
+            _ => {
+                if let Some(btc) = amount.strip_suffix("btc") {
+                    return Ok(Amount::Custom(parse_btc(btc.trim_end(), s)?));
+                }
+
+                let (sat, bare) = match amount
+                    .strip_suffix("sats")
+                    .or_else(|| amount.strip_suffix("sat"))
+                {
+                    Some(sat) => (sat.trim_end(), false),
+                    None => (amount.as_str(), true),
+                };
+
+                if bare && sat.contains('.') {
+                    return Err(ParseAmountError::Ambiguous(s.to_string()));
+                }
+
+                let sat: u64 = parse_digits(sat, s)?;
+                if sat > MAX_MONEY {
+                    return Err(ParseAmountError::Overflow);
+                }
+                if bare && sat > MAX_BARE_AMOUNT {
+                    return Err(ParseAmountError::Ambiguous(s.to_string()));
+                }
+
+                Ok(Amount::Custom(sat))
+            }
         }
     }
 }
 
+fn parse_digits(digits: &str, original: &str) -> Result<u64, ParseAmountError> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ParseAmountError::Invalid(original.to_string()));
+    }
+    digits.parse().map_err(|_| ParseAmountError::Overflow)
+}
+
+/// Parse a BTC amount without going through floats
+fn parse_btc(btc: &str, original: &str) -> Result<u64, ParseAmountError> {
+    let (int, fract) = btc.split_once('.').unwrap_or((btc, ""));
+
+    if fract.len() > BTC_DECIMALS {
+        return Err(ParseAmountError::TooPrecise);
+    }
+
+    let int: u64 = if int.is_empty() && !fract.is_empty() {
+        0
+    } else {
+        parse_digits(int, original)?
+    };
+    let fract: u64 = if fract.is_empty() {
+        0
+    } else {
+        let padded: String = format!("{fract:0<BTC_DECIMALS$}");
+        parse_digits(&padded, original)?
+    };
+
+    let sat: u64 = int
+        .checked_mul(SAT_PER_BTC)
+        .and_then(|sat| sat.checked_add(fract))
+        .ok_or(ParseAmountError::Overflow)?;
+    if sat > MAX_MONEY {
+        return Err(ParseAmountError::Overflow);
+    }
+
+    Ok(sat)
+}
+
 impl Amount {
     #[inline]
     pub fn max() -> Self {
@@ -121,6 +218,64 @@ impl Amount {
     }
 }
 
+/// Coin selection strategy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CoinSelection {
+    /// BDK default algorithm
+    #[default]
+    Default,
+    /// Minimize fees (branch-and-bound)
+    Economical,
+    /// Spend all the available UTXOs (consolidate while spending)
+    Consolidate,
+    /// Avoid linking UTXOs: spend only the largest UTXO (fail if it's not enough)
+    PrivacyFirst,
+}
+
+impl CoinSelection {
+    pub fn list() -> Vec<Self> {
+        vec![
+            Self::Default,
+            Self::Economical,
+            Self::Consolidate,
+            Self::PrivacyFirst,
+        ]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Default => "default",
+            Self::Economical => "economical",
+            Self::Consolidate => "consolidate",
+            Self::PrivacyFirst => "privacy-first",
+        }
+    }
+}
+
+impl fmt::Display for CoinSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("unknown coin selection strategy: {0}")]
+pub struct UnknownCoinSelection(String);
+
+impl FromStr for CoinSelection {
+    type Err = UnknownCoinSelection;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(Self::Default),
+            "economical" => Ok(Self::Economical),
+            "consolidate" => Ok(Self::Consolidate),
+            "privacy-first" | "privacyfirst" => Ok(Self::PrivacyFirst),
+            _ => Err(UnknownCoinSelection(s.to_string())),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -152,4 +307,125 @@ mod test {
         let amount: Amount = Amount::from_str("11535").unwrap();
         assert_eq!(Amount::Custom(11535), amount);
     }
+
+    #[test]
+    fn test_coin_selection_from_str() {
+        for cs in [
+            CoinSelection::Default,
+            CoinSelection::Economical,
+            CoinSelection::Consolidate,
+            CoinSelection::PrivacyFirst,
+        ] {
+            assert_eq!(CoinSelection::from_str(&cs.to_string()).unwrap(), cs);
+        }
+        assert!(CoinSelection::from_str("random").is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(Amount::from_str("max").unwrap(), Amount::Max);
+        assert_eq!(Amount::from_str("ALL").unwrap(), Amount::Max);
+        assert_eq!(
+            Amount::from_str("0.015btc").unwrap(),
+            Amount::Custom(1_500_000)
+        );
+        assert_eq!(
+            Amount::from_str("1 BTC").unwrap(),
+            Amount::Custom(SAT_PER_BTC)
+        );
+        assert_eq!(
+            Amount::from_str(".5btc").unwrap(),
+            Amount::Custom(50_000_000)
+        );
+        assert_eq!(
+            Amount::from_str("1_500_000sat").unwrap(),
+            Amount::Custom(1_500_000)
+        );
+        assert_eq!(Amount::from_str("1500sats").unwrap(), Amount::Custom(1500));
+        assert_eq!(
+            Amount::from_str("21000000").unwrap(),
+            Amount::Custom(21_000_000)
+        );
+
+        assert!(matches!(
+            Amount::from_str("abc"),
+            Err(ParseAmountError::Invalid(..))
+        ));
+        assert!(matches!(
+            Amount::from_str("btc"),
+            Err(ParseAmountError::Invalid(..))
+        ));
+        assert!(matches!(
+            Amount::from_str("-1sat"),
+            Err(ParseAmountError::Invalid(..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_amount_rounding() {
+        // Exact, no float rounding
+        assert_eq!(
+            Amount::from_str("0.1btc").unwrap(),
+            Amount::Custom(10_000_000)
+        );
+        assert_eq!(
+            Amount::from_str("0.29btc").unwrap(),
+            Amount::Custom(29_000_000)
+        );
+        assert_eq!(
+            Amount::from_str("0.00000001btc").unwrap(),
+            Amount::Custom(1)
+        );
+
+        // Sub-satoshi amounts are not rounded
+        assert_eq!(
+            Amount::from_str("0.000000001btc"),
+            Err(ParseAmountError::TooPrecise)
+        );
+        assert!(matches!(
+            Amount::from_str("1.5sat"),
+            Err(ParseAmountError::Invalid(..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_amount_overflow() {
+        assert_eq!(
+            Amount::from_str("21000000btc").unwrap(),
+            Amount::Custom(MAX_MONEY)
+        );
+        assert_eq!(
+            Amount::from_str("21000000.00000001btc"),
+            Err(ParseAmountError::Overflow)
+        );
+        assert_eq!(
+            Amount::from_str("184467440738btc"),
+            Err(ParseAmountError::Overflow)
+        );
+        assert_eq!(
+            Amount::from_str("99999999999999999999sat"),
+            Err(ParseAmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_parse_amount_ambiguous() {
+        assert!(matches!(
+            Amount::from_str("21000001"),
+            Err(ParseAmountError::Ambiguous(..))
+        ));
+        assert!(matches!(
+            Amount::from_str("0.015"),
+            Err(ParseAmountError::Ambiguous(..))
+        ));
+        assert_eq!(
+            Amount::from_str("21000001sat").unwrap(),
+            Amount::Custom(21_000_001)
+        );
+    }
 }
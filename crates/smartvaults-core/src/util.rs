@@ -75,6 +75,19 @@ pub fn search_network_for_descriptor(desc: &Descriptor<String>) -> Option<Networ
     None
 }
 
+/// Derive the change (internal) descriptor from the external one
+///
+/// Replace every `/0/*` derivation with `/1/*`.
+/// Return `None` if the descriptor has not `/0/*` derivations or already contains `/1/*` ones.
+pub fn derive_change_descriptor(desc: &Descriptor<String>) -> Option<Descriptor<String>> {
+    let descriptor: String = desc.to_string();
+    let descriptor: &str = descriptor.split('#').next()?;
+    if !descriptor.contains("/0/*") || descriptor.contains("/1/*") {
+        return None;
+    }
+    Descriptor::from_str(&descriptor.replace("/0/*", "/1/*")).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use core::str::FromStr;
@@ -95,6 +108,27 @@ mod tests {
         let network = search_network_for_descriptor(&desc);
         assert_eq!(network, Some(Network::Bitcoin));
     }
+
+    #[test]
+    fn test_derive_change_descriptor() {
+        let desc = Descriptor::from_str("tr([e157a520/86'/1'/784923']tpubDCCYFYCyDkxo1xAzDpoFNdtGcjD5BPLZbEJswjJmwqp67Weqd2C7fg6Jy1SBjgn3wYnKyUtoYKXG4VdQczjqb6FJnqHe3NmFdgy8vNBSty4/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#m9nhr276").unwrap();
+        let change = derive_change_descriptor(&desc).unwrap();
+        let change = change.to_string();
+        assert!(change.contains("tpubDCCYFYCyDkxo1xAzDpoFNdtGcjD5BPLZbEJswjJmwqp67Weqd2C7fg6Jy1SBjgn3wYnKyUtoYKXG4VdQczjqb6FJnqHe3NmFdgy8vNBSty4/1/*"));
+        assert!(!change.contains("/0/*"));
+        assert_eq!(search_network_for_descriptor(&desc), Some(Network::Testnet));
+
+        // Already a change descriptor
+        let desc = Descriptor::from_str(&change).unwrap();
+        assert!(derive_change_descriptor(&desc).is_none());
+
+        // Not ranged
+        let desc = Descriptor::from_str(
+            "tr(af2486c537bbb20285bb29e0dd7c05a875b684aee7d4a2501c5c1aea63eaff1c)",
+        )
+        .unwrap();
+        assert!(derive_change_descriptor(&desc).is_none());
+    }
 }
 
 #[cfg(bench)]
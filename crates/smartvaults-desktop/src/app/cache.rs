@@ -0,0 +1,399 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! App-level cache
+//!
+//! Policies, proposals, transactions and counts are shared between the screens:
+//! navigating to a screen render the cached data immediately and revalidate in background
+//! only the entries invalidated by the sync messages.
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use iced::Command;
+use smartvaults_sdk::core::policy::PolicyTemplateType;
+use smartvaults_sdk::nostr::EventId;
+use smartvaults_sdk::types::{Counts, GetPolicy, GetProposal, GetTransaction};
+use smartvaults_sdk::{Error, EventHandled, Message as SdkMessage, SmartVaults};
+
+use super::Message;
+
+#[derive(Debug, Clone)]
+pub enum CacheUpdate {
+    Policies {
+        /// If `true`, replace all the policies
+        full: bool,
+        policies: Vec<GetPolicy>,
+    },
+    Proposals(Vec<GetProposal>),
+    Transactions {
+        /// If `true`, replace the transactions of all the policies
+        full: bool,
+        txs: BTreeMap<EventId, BTreeSet<GetTransaction>>,
+    },
+    Counts(Counts),
+}
+
+/// Group of the policies screen, matched from the policy template
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PolicyGroup {
+    Singlesig,
+    Multisig,
+    Hold,
+    Recovery,
+    Decaying,
+    Custom,
+}
+
+impl fmt::Display for PolicyGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Singlesig => write!(f, "Singlesig"),
+            Self::Multisig => write!(f, "Multisig"),
+            Self::Hold => write!(f, "Hold"),
+            Self::Recovery => write!(f, "Recovery"),
+            Self::Decaying => write!(f, "Decaying"),
+            Self::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+impl From<Option<PolicyTemplateType>> for PolicyGroup {
+    fn from(template: Option<PolicyTemplateType>) -> Self {
+        match template {
+            Some(PolicyTemplateType::Singlesig) => Self::Singlesig,
+            Some(PolicyTemplateType::Multisig) => Self::Multisig,
+            Some(PolicyTemplateType::Hold) => Self::Hold,
+            Some(PolicyTemplateType::Recovery) => Self::Recovery,
+            Some(PolicyTemplateType::Decaying) => Self::Decaying,
+            None => Self::Custom,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    value: Option<T>,
+    stale: bool,
+    /// A fetch is in progress
+    revalidating: Cell<bool>,
+    /// Invalidated while a fetch was in progress: the fetched value is already stale
+    invalidated_while_revalidating: bool,
+}
+
+impl<T> Default for Entry<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            stale: true,
+            revalidating: Cell::new(false),
+            invalidated_while_revalidating: false,
+        }
+    }
+}
+
+impl<T> Entry<T> {
+    /// Check if the entry must be fetched and mark it as revalidating
+    fn start_revalidation(&self, stale: bool) -> bool {
+        if self.revalidating.get() || !(self.value.is_none() || self.stale || stale) {
+            return false;
+        }
+        self.revalidating.set(true);
+        true
+    }
+
+    fn invalidate(&mut self) {
+        self.stale = true;
+        if self.revalidating.get() {
+            self.invalidated_while_revalidating = true;
+        }
+    }
+
+    fn set(&mut self, value: T) {
+        self.value = Some(value);
+        self.stale = std::mem::take(&mut self.invalidated_while_revalidating);
+        self.revalidating.set(false);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Cache {
+    policies: Entry<BTreeMap<EventId, GetPolicy>>,
+    stale_policies: HashSet<EventId>,
+    proposals: Entry<Vec<GetProposal>>,
+    txs: Entry<BTreeMap<EventId, BTreeSet<GetTransaction>>>,
+    stale_txs: HashSet<EventId>,
+    counts: Entry<Counts>,
+    /// Template match of the policies, with the descriptor it was computed from
+    groups: HashMap<EventId, (String, PolicyGroup)>,
+}
+
+impl Cache {
+    /// Invalidate the entries affected by the sync message
+    pub fn invalidate(&mut self, msg: &SdkMessage) {
+        match msg {
+            SdkMessage::WalletSyncCompleted(policy_id) => {
+                self.stale_policies.insert(*policy_id);
+                self.stale_txs.insert(*policy_id);
+                self.counts.invalidate();
+            }
+            SdkMessage::UnexpectedOutgoingTx { policy_id, .. }
+            | SdkMessage::PolicyWalletMismatch(policy_id) => {
+                self.stale_txs.insert(*policy_id);
+            }
+            SdkMessage::ProposalReady(..) | SdkMessage::ExternalPsbtImported(..) => {
+                self.proposals.invalidate();
+                self.counts.invalidate();
+            }
+            SdkMessage::ApprovalReminder { .. } => self.counts.invalidate(),
+            SdkMessage::EventHandled(event) => match event {
+                EventHandled::SharedKey(..) | EventHandled::Policy(..) => {
+                    self.policies.invalidate();
+                    self.txs.invalidate();
+                    self.counts.invalidate();
+                }
+                EventHandled::Proposal(..) | EventHandled::Approval { .. } => {
+                    self.proposals.invalidate();
+                    self.counts.invalidate();
+                }
+                EventHandled::CompletedProposal(..) => {
+                    // The TX of the completed proposal is inserted in the wallet
+                    self.proposals.invalidate();
+                    self.txs.invalidate();
+                    self.counts.invalidate();
+                }
+                EventHandled::Signer(..)
+                | EventHandled::MySharedSigner(..)
+                | EventHandled::SharedSigner(..) => {
+                    // Signers are used to compute the proposals awaiting my approval
+                    self.counts.invalidate();
+                }
+                EventHandled::Label { policy_id } => {
+                    self.stale_txs.insert(*policy_id);
+                }
+                EventHandled::EventDeletion(..) => self.invalidate_all(),
+                EventHandled::Contacts
+                | EventHandled::Metadata(..)
+                | EventHandled::NostrConnectRequest(..)
+                | EventHandled::RelayList
+                | EventHandled::KeyAgentSignaling(..)
+                | EventHandled::KeyAgentSignerOffering(..)
+                | EventHandled::VerifiedKeyAgents
+                | EventHandled::PeerCapabilities(..) => (),
+            },
+            SdkMessage::BlockHeightUpdated
+            | SdkMessage::MempoolFeesUpdated(..)
+            | SdkMessage::PoisonEventQuarantined(..)
+            | SdkMessage::InsufficientRelayAcks { .. }
+            | SdkMessage::OutboxEventDropped { .. }
+            | SdkMessage::ProofVerified { .. }
+            | SdkMessage::ProofInvalid(..)
+            | SdkMessage::ExternalPsbtRejected { .. }
+            | SdkMessage::MemberCannotReadProposal { .. }
+            | SdkMessage::NewNostrConnectRequest { .. }
+            | SdkMessage::NostrConnectRequestAutoApproved { .. }
+            | SdkMessage::SyncProgress { .. }
+            | SdkMessage::InitialSyncComplete { .. } => (),
+        }
+    }
+
+    pub fn invalidate_all(&mut self) {
+        self.policies.invalidate();
+        self.proposals.invalidate();
+        self.txs.invalidate();
+        self.counts.invalidate();
+    }
+
+    pub fn update(&mut self, update: CacheUpdate) {
+        match update {
+            CacheUpdate::Policies { full, policies } => {
+                let mut cached = if full {
+                    self.stale_policies.clear();
+                    BTreeMap::new()
+                } else {
+                    self.policies.value.take().unwrap_or_default()
+                };
+                for policy in policies.into_iter() {
+                    self.stale_policies.remove(&policy.policy_id);
+                    self.update_group(&policy);
+                    cached.insert(policy.policy_id, policy);
+                }
+                self.groups.retain(|id, _| cached.contains_key(id));
+                self.policies.set(cached);
+            }
+            CacheUpdate::Proposals(proposals) => self.proposals.set(proposals),
+            CacheUpdate::Transactions { full, txs } => {
+                let mut cached = if full {
+                    self.stale_txs.clear();
+                    BTreeMap::new()
+                } else {
+                    self.txs.value.take().unwrap_or_default()
+                };
+                for (policy_id, list) in txs.into_iter() {
+                    self.stale_txs.remove(&policy_id);
+                    cached.insert(policy_id, list);
+                }
+                // Policies with a duplicated descriptor are never refreshed
+                self.stale_txs.retain(|id| cached.contains_key(id));
+                self.txs.set(cached);
+            }
+            CacheUpdate::Counts(counts) => self.counts.set(counts),
+        }
+    }
+
+    /// Match the template of the policy, only if the descriptor changed
+    fn update_group(&mut self, policy: &GetPolicy) {
+        let descriptor: String = policy.as_descriptor().to_string();
+        if let Some((cached, _)) = self.groups.get(&policy.policy_id) {
+            if *cached == descriptor {
+                return;
+            }
+        }
+        let group = PolicyGroup::from(policy.template_match().ok().flatten());
+        self.groups.insert(policy.policy_id, (descriptor, group));
+    }
+
+    /// Allow to retry the revalidation at next load
+    pub fn revalidation_failed(&mut self) {
+        self.policies.revalidating.set(false);
+        self.proposals.revalidating.set(false);
+        self.txs.revalidating.set(false);
+        self.counts.revalidating.set(false);
+    }
+
+    /// Cached policies (may be stale)
+    pub fn policies(&self) -> Option<Vec<GetPolicy>> {
+        let policies = self.policies.value.as_ref()?;
+        let mut policies: Vec<GetPolicy> = policies.values().cloned().collect();
+        policies.sort();
+        Some(policies)
+    }
+
+    /// Group of a cached policy
+    pub fn policy_group(&self, policy_id: &EventId) -> PolicyGroup {
+        self.groups
+            .get(policy_id)
+            .map(|(_, group)| *group)
+            .unwrap_or(PolicyGroup::Custom)
+    }
+
+    /// Cached pending proposals (may be stale)
+    pub fn proposals(&self) -> Option<Vec<GetProposal>> {
+        self.proposals.value.clone()
+    }
+
+    /// Cached transactions of all policies (may be stale)
+    pub fn transactions(&self) -> Option<BTreeSet<GetTransaction>> {
+        let txs = self.txs.value.as_ref()?;
+        Some(txs.values().flatten().cloned().collect())
+    }
+
+    /// Cached counts (may be stale)
+    pub fn counts(&self) -> Option<Counts> {
+        self.counts.value.clone()
+    }
+
+    /// Fetch the missing or invalidated policies
+    pub fn revalidate_policies(&self, client: &SmartVaults) -> Command<Message> {
+        if !self
+            .policies
+            .start_revalidation(!self.stale_policies.is_empty())
+        {
+            return Command::none();
+        }
+
+        let client = client.clone();
+        let full: bool = self.policies.value.is_none() || self.policies.stale;
+        let ids: Vec<EventId> = self.stale_policies.iter().copied().collect();
+        Command::perform(
+            async move {
+                if full {
+                    client.get_policies().await
+                } else {
+                    let mut policies: Vec<GetPolicy> = Vec::with_capacity(ids.len());
+                    for policy_id in ids.into_iter() {
+                        policies.push(client.get_policy_by_id(policy_id).await?);
+                    }
+                    Ok(policies)
+                }
+            },
+            move |res| match res {
+                Ok(policies) => Message::CacheUpdated(CacheUpdate::Policies { full, policies }),
+                Err(e) => Message::CacheError(e.to_string()),
+            },
+        )
+    }
+
+    /// Fetch the pending proposals, if missing or invalidated
+    pub fn revalidate_proposals(&self, client: &SmartVaults) -> Command<Message> {
+        if !self.proposals.start_revalidation(false) {
+            return Command::none();
+        }
+
+        let client = client.clone();
+        Command::perform(
+            async move { client.get_proposals().await },
+            |res| match res {
+                Ok(proposals) => Message::CacheUpdated(CacheUpdate::Proposals(proposals)),
+                Err(e) => Message::CacheError(e.to_string()),
+            },
+        )
+    }
+
+    /// Fetch the missing or invalidated transactions
+    pub fn revalidate_transactions(&self, client: &SmartVaults) -> Command<Message> {
+        // Skip policies with a duplicated descriptor (transactions already included in another policy)
+        let ids: Vec<EventId> = match &self.txs.value {
+            Some(cached) => self
+                .stale_txs
+                .iter()
+                .filter(|id| cached.contains_key(id))
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        if !self.txs.start_revalidation(!ids.is_empty()) {
+            return Command::none();
+        }
+
+        let client = client.clone();
+        let full: bool = self.txs.value.is_none() || self.txs.stale;
+        Command::perform(
+            async move {
+                let mut txs: BTreeMap<EventId, BTreeSet<GetTransaction>> = BTreeMap::new();
+                if full {
+                    // Policies with the same descriptor share the same transactions
+                    let mut already_seen: HashSet<String> = HashSet::new();
+                    for policy in client.get_policies().await?.into_iter() {
+                        if already_seen.insert(policy.as_descriptor().to_string()) {
+                            txs.insert(policy.policy_id, client.get_txs(policy.policy_id).await?);
+                        }
+                    }
+                } else {
+                    for policy_id in ids.into_iter() {
+                        txs.insert(policy_id, client.get_txs(policy_id).await?);
+                    }
+                }
+                Ok::<_, Error>(txs)
+            },
+            move |res| match res {
+                Ok(txs) => Message::CacheUpdated(CacheUpdate::Transactions { full, txs }),
+                Err(e) => Message::CacheError(e.to_string()),
+            },
+        )
+    }
+
+    /// Fetch the dashboard counts, if missing or invalidated
+    pub fn revalidate_counts(&self, client: &SmartVaults) -> Command<Message> {
+        if !self.counts.start_revalidation(false) {
+            return Command::none();
+        }
+
+        let client = client.clone();
+        Command::perform(async move { client.get_counts().await }, |res| match res {
+            Ok(counts) => Message::CacheUpdated(CacheUpdate::Counts(counts)),
+            Err(e) => Message::CacheError(e.to_string()),
+        })
+    }
+}
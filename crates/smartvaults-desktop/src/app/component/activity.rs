@@ -15,8 +15,8 @@ use crate::app::{Context, Message, Stage};
 use crate::component::{
     rule, Amount, AmountSign, Badge, BadgeStyle, Button, ButtonStyle, Icon, Text,
 };
-use crate::theme::color::{GREEN, YELLOW};
-use crate::theme::icon::{BROWSER, CHECK, CLIPBOARD, FULLSCREEN, HOURGLASS};
+use crate::theme::color::{GREEN, RED, YELLOW};
+use crate::theme::icon::{BROWSER, CHECK, CLIPBOARD, FULLSCREEN, HOURGLASS, PATCH_EXCLAMATION};
 
 pub struct Activity {
     proposals: Vec<GetProposal>,
@@ -119,7 +119,7 @@ impl Activity {
                             Text::new(if ctx.hide_balances {
                                 String::from("*****")
                             } else {
-                                timestamp.to_human_datetime()
+                                util::format::datetime(timestamp)
                             })
                             .width(Length::Fixed(225.0))
                             .view(),
@@ -129,7 +129,7 @@ impl Activity {
                                 .push(
                                     Badge::new(
                                         Text::new(if signed {
-                                            "To broadcast"
+                                            "Ready to finalize"
                                         } else {
                                             "To approve"
                                         })
@@ -138,7 +138,7 @@ impl Activity {
                                         .view(),
                                     )
                                     .style(if signed {
-                                        BadgeStyle::Warning
+                                        BadgeStyle::Success
                                     } else {
                                         BadgeStyle::Info
                                     })
@@ -184,7 +184,7 @@ impl Activity {
                                 .push(
                                     Badge::new(
                                         Text::new(if signed {
-                                            "To broadcast"
+                                            "Ready to finalize"
                                         } else {
                                             "To approve"
                                         })
@@ -193,7 +193,7 @@ impl Activity {
                                         .view(),
                                     )
                                     .style(if signed {
-                                        BadgeStyle::Warning
+                                        BadgeStyle::Success
                                     } else {
                                         BadgeStyle::Info
                                     })
@@ -225,9 +225,13 @@ impl Activity {
                 tx,
                 label,
                 block_explorer,
+                unexpected,
+                ..
             } in self.txs.into_iter()
             {
-                let status = if tx.confirmation_time.is_confirmed() {
+                let status = if unexpected {
+                    Icon::new(PATCH_EXCLAMATION).color(RED)
+                } else if tx.confirmation_time.is_confirmed() {
                     Icon::new(CHECK).color(GREEN)
                 } else {
                     Icon::new(HOURGLASS).color(YELLOW)
@@ -251,7 +255,7 @@ impl Activity {
                         } else {
                             match tx.confirmation_time {
                                 ConfirmationTime::Confirmed { time, .. } => {
-                                    Timestamp::from(time).to_human_datetime()
+                                    util::format::datetime(Timestamp::from(time))
                                 }
                                 ConfirmationTime::Unconfirmed { .. } => String::from("Pending"),
                             }
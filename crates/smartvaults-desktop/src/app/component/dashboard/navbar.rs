@@ -8,7 +8,7 @@ use smartvaults_sdk::util::format;
 use crate::app::component::breadcrumb::Breadcrumb;
 use crate::app::{Context, Message, Stage};
 use crate::component::{rule, Button, ButtonStyle, Icon, Text};
-use crate::theme::icon::{BOX, EYE, EYE_SLASH, FINGERPRINT, PERSON_CIRCLE};
+use crate::theme::icon::{BOX, EYE, EYE_SLASH, FINGERPRINT, PERSON_CIRCLE, RELOAD};
 
 #[derive(Clone, Default)]
 pub struct Navbar;
@@ -19,16 +19,44 @@ impl Navbar {
     }
 
     pub fn view<'a>(&self, ctx: &Context) -> Row<'a, Message> {
-        Row::new()
-            .push(
-                Row::new()
-                    .push(Breadcrumb::new(ctx.breadcrumb.clone()).view())
-                    .width(Length::Fill)
-                    .height(Length::Fill)
-                    .spacing(10)
-                    .padding(10)
-                    .align_items(Alignment::Center),
-            )
+        let mut navbar = Row::new().push(
+            Row::new()
+                .push(Breadcrumb::new(ctx.breadcrumb.clone()).view())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .spacing(10)
+                .padding(10)
+                .align_items(Alignment::Center),
+        );
+
+        if !ctx.initial_sync.completed {
+            let progress: String = if ctx.initial_sync.relays.is_empty() {
+                String::from("Syncing...")
+            } else {
+                format!(
+                    "Syncing: {} events ({}/{} relays)",
+                    format::number(ctx.initial_sync.events_received() as u64),
+                    ctx.initial_sync.synced_relays(),
+                    ctx.initial_sync.relays.len()
+                )
+            };
+            navbar = navbar
+                .push(
+                    Row::new()
+                        .push(rule::vertical())
+                        .height(Length::Fixed(40.0)),
+                )
+                .push(
+                    Row::new()
+                        .push(Icon::new(RELOAD))
+                        .push(Text::new(progress).extra_light().view())
+                        .align_items(Alignment::Center)
+                        .padding(10)
+                        .spacing(10),
+                );
+        }
+
+        navbar
             .push(
                 Row::new()
                     .push(rule::vertical())
@@ -11,11 +11,24 @@ use crate::component::{Button, ButtonStyle};
 pub struct SidebarButton<'a> {
     text: &'a str,
     icon: char,
+    badge: Option<usize>,
 }
 
 impl<'a> SidebarButton<'a> {
     pub fn new(text: &'a str, icon: char) -> Self {
-        Self { text, icon }
+        Self {
+            text,
+            icon,
+            badge: None,
+        }
+    }
+
+    /// Counter shown next to the text (hidden if zero)
+    pub fn badge(self, count: usize) -> Self {
+        Self {
+            badge: Some(count),
+            ..self
+        }
     }
 
     pub fn view(&self, ctx: &Context, msg: Message) -> Container<'a, Message> {
@@ -27,9 +40,14 @@ impl<'a> SidebarButton<'a> {
             }
         }
 
+        let text: String = match self.badge {
+            Some(count) if count > 0 => format!("{} ({count})", self.text),
+            _ => self.text.to_string(),
+        };
+
         let btn = Button::new()
             .icon(self.icon)
-            .text(self.text)
+            .text(text)
             .on_press(msg)
             .style(style)
             .width(Length::Fill)
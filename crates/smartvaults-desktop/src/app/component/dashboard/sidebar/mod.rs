@@ -8,10 +8,12 @@ mod button;
 
 use self::button::SidebarButton;
 use crate::app::context::{Mode, AVAILABLE_MODES};
-use crate::app::{Context, Message, Stage};
+use crate::app::{Context, Message, ProposalFilter, Stage};
 use crate::component::{rule, Text};
 use crate::constants::{APP_LOGO, APP_NAME};
-use crate::theme::icon::{CONTACTS, HISTORY, HOME, KEY, LINK, LIST, LOCK, PEOPLE, SETTING, VAULT};
+use crate::theme::icon::{
+    CONTACTS, HISTORY, HOME, KEY, LINK, LIST, LOCK, PEOPLE, SEND_PENDING, SETTING, VAULT,
+};
 
 const MAX_WIDTH: f32 = 240.0;
 
@@ -44,6 +46,17 @@ impl Sidebar {
             SidebarButton::new("Dashboard", HOME).view(ctx, Message::View(Stage::Dashboard));
         let vaults_button =
             SidebarButton::new("Vaults", VAULT).view(ctx, Message::View(Stage::Vaults));
+        let awaiting_my_approval: usize = ctx
+            .cache
+            .counts()
+            .map(|counts| counts.awaiting_my_approval)
+            .unwrap_or_default();
+        let proposals_button = SidebarButton::new("Proposals", SEND_PENDING)
+            .badge(awaiting_my_approval)
+            .view(
+                ctx,
+                Message::View(Stage::Activity(Some(ProposalFilter::AwaitingMyApproval))),
+            );
         let history_button =
             SidebarButton::new("History", HISTORY).view(ctx, Message::View(Stage::History));
         let addresses_button =
@@ -63,6 +76,7 @@ impl Sidebar {
             Mode::User => vec![
                 home_button,
                 vaults_button,
+                proposals_button,
                 history_button,
                 addresses_button,
                 signers_button,
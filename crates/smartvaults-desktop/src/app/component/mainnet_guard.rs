@@ -0,0 +1,120 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use iced::widget::{Column, Row};
+use iced::{Element, Length};
+use smartvaults_sdk::core::bitcoin::Network;
+
+use crate::app::Message;
+use crate::component::{Button, ButtonStyle, Card, CardStyle, Text, TextInput};
+use crate::theme::color::RED;
+
+/// Text to type to confirm a mainnet action
+pub const MAINNET_CONFIRMATION: &str = "MAINNET";
+
+/// Confirmation dialog of the value-moving and destructive actions on mainnet
+pub struct MainnetGuard {
+    action: String,
+    network: Network,
+    confirmation: String,
+    on_input: Option<Box<dyn Fn(String) -> Message>>,
+    on_confirm: Option<Message>,
+    on_cancel: Option<Message>,
+}
+
+impl MainnetGuard {
+    pub fn new<S>(action: S, network: Network, confirmation: &str) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            action: action.into(),
+            network,
+            confirmation: confirmation.to_string(),
+            on_input: None,
+            on_confirm: None,
+            on_cancel: None,
+        }
+    }
+
+    pub fn on_input(self, on_input: impl Fn(String) -> Message + 'static) -> Self {
+        Self {
+            on_input: Some(Box::new(on_input)),
+            ..self
+        }
+    }
+
+    pub fn on_confirm(self, message: Message) -> Self {
+        Self {
+            on_confirm: Some(message),
+            ..self
+        }
+    }
+
+    pub fn on_cancel(self, message: Message) -> Self {
+        Self {
+            on_cancel: Some(message),
+            ..self
+        }
+    }
+
+    pub fn view(self) -> Element<'static, Message> {
+        let mut input = TextInput::with_label(
+            format!("To confirm, type '{MAINNET_CONFIRMATION}'"),
+            self.confirmation.clone(),
+        )
+        .placeholder(MAINNET_CONFIRMATION);
+        if let Some(on_input) = self.on_input {
+            input = input.on_input(on_input);
+        }
+
+        let mut confirm = Button::new()
+            .style(ButtonStyle::BorderedDanger)
+            .text("Confirm")
+            .width(Length::Fill)
+            .loading(self.confirmation != MAINNET_CONFIRMATION);
+        if let Some(message) = self.on_confirm {
+            confirm = confirm.on_press(message);
+        }
+
+        let mut cancel = Button::new()
+            .style(ButtonStyle::Bordered)
+            .text("Cancel")
+            .width(Length::Fill);
+        if let Some(message) = self.on_cancel {
+            cancel = cancel.on_press(message);
+        }
+
+        Card::new(
+            Text::new(format!(
+                "{} action",
+                self.network.to_string().to_uppercase()
+            ))
+            .bold()
+            .color(RED)
+            .view(),
+            Text::new(format!(
+                "You are about to {} on {}: this can't be undone.",
+                self.action, self.network
+            ))
+            .view(),
+        )
+        .foot(
+            Column::new()
+                .width(Length::Fill)
+                .spacing(10)
+                .padding(5)
+                .push(input.view())
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .width(Length::Fill)
+                        .push(confirm.view())
+                        .push(cancel.view()),
+                ),
+        )
+        .style(CardStyle::Danger)
+        .max_width(400.0)
+        .view()
+    }
+}
@@ -12,6 +12,7 @@ mod balances;
 mod breadcrumb;
 mod dashboard;
 mod fee_selector;
+mod mainnet_guard;
 mod policy_tree;
 mod utxo_selector;
 
@@ -19,6 +20,7 @@ pub use self::activity::{Activity, CompletedProposalsList};
 pub use self::balances::Balances;
 pub use self::dashboard::Dashboard;
 pub use self::fee_selector::FeeSelector;
+pub use self::mainnet_guard::{MainnetGuard, MAINNET_CONFIRMATION};
 pub use self::policy_tree::PolicyTree;
 pub use self::utxo_selector::UtxoSelector;
 
@@ -6,6 +6,7 @@ use iced::Length;
 use smartvaults_sdk::core::bdk::descriptor::policy::{PkOrF, SatisfiableItem};
 use smartvaults_sdk::core::bitcoin::absolute::LockTime as AbsoluteLockTime;
 use smartvaults_sdk::nostr::Timestamp;
+use smartvaults_sdk::util::format;
 
 use crate::app::Message;
 use crate::component::Text;
@@ -76,7 +77,7 @@ fn add_node(item: &SatisfiableItem, counter: usize) -> Column<'static, Message>
             let timelock: String = match value {
                 AbsoluteLockTime::Blocks(blocks) => format!("{blocks} block height"),
                 AbsoluteLockTime::Seconds(time) => {
-                    Timestamp::from(time.to_consensus_u32() as u64).to_human_datetime()
+                    format::datetime(Timestamp::from(time.to_consensus_u32() as u64))
                 }
             };
             child = child.push(Text::new(format!("Absolute Timelock: {timelock}")).view());
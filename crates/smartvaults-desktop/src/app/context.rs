@@ -5,7 +5,7 @@ use std::collections::BTreeMap;
 use std::fmt;
 
 use smartvaults_sdk::core::bdk::FeeRate;
-use smartvaults_sdk::core::bitcoin::Txid;
+use smartvaults_sdk::core::bitcoin::{Network, Txid};
 use smartvaults_sdk::core::policy::Policy;
 use smartvaults_sdk::core::signer::Signer;
 use smartvaults_sdk::core::Priority;
@@ -14,8 +14,31 @@ use smartvaults_sdk::protocol::v1::SignerOffering;
 use smartvaults_sdk::types::{GetPolicy, GetSigner};
 use smartvaults_sdk::{util, SmartVaults};
 
+use super::cache::Cache;
+use super::component::MAINNET_CONFIRMATION;
+
 pub const AVAILABLE_MODES: [Mode; 2] = [Mode::User, Mode::KeyAgent];
 
+/// Progress of the initial relays sync
+#[derive(Debug, Clone, Default)]
+pub struct InitialSyncProgress {
+    /// Events received and EOSE of the relays that reported some progress
+    pub relays: BTreeMap<Url, (usize, bool)>,
+    pub completed: bool,
+}
+
+impl InitialSyncProgress {
+    /// Events received (duplicates included) from all the relays
+    pub fn events_received(&self) -> usize {
+        self.relays.values().map(|(events, _)| events).sum()
+    }
+
+    /// Relays that sent the EOSE
+    pub fn synced_relays(&self) -> usize {
+        self.relays.values().filter(|(_, eose)| *eose).count()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Stage {
     Dashboard,
@@ -29,7 +52,7 @@ pub enum Stage {
     Receive(Option<GetPolicy>),
     SelfTransfer,
     NewProof(Option<GetPolicy>),
-    Activity,
+    Activity(Option<ProposalFilter>),
     Proposal(EventId),
     Transaction { policy_id: EventId, txid: Txid },
     History,
@@ -59,6 +82,7 @@ pub enum Stage {
     WipeKeys,
     NostrConnect,
     AddNostrConnectSession,
+    WhatsNew,
 }
 
 impl fmt::Display for Stage {
@@ -75,7 +99,7 @@ impl fmt::Display for Stage {
             Self::Receive(_) => write!(f, "Receive"),
             Self::SelfTransfer => write!(f, "Self transfer"),
             Self::NewProof(_) => write!(f, "New Proof"),
-            Self::Activity => write!(f, "Activity"),
+            Self::Activity(..) => write!(f, "Activity"),
             Self::Proposal(id) => write!(f, "Proposal #{}", util::cut_event_id(*id)),
             Self::Transaction { txid, .. } => write!(f, "Tx #{}", util::cut_txid(*txid)),
             Self::History => write!(f, "History"),
@@ -105,6 +129,7 @@ impl fmt::Display for Stage {
             Self::WipeKeys => write!(f, "Wipe Keys"),
             Self::NostrConnect => write!(f, "Connect"),
             Self::AddNostrConnectSession => write!(f, "Add session"),
+            Self::WhatsNew => write!(f, "What's new"),
         }
     }
 }
@@ -121,7 +146,7 @@ impl Stage {
             self,
             Stage::Dashboard
                 | Stage::Vaults
-                | Stage::Activity
+                | Stage::Activity(..)
                 | Stage::History
                 | Stage::Signers
                 | Stage::KeyAgents
@@ -129,10 +154,18 @@ impl Stage {
                 | Stage::Settings
                 | Stage::Profile
                 | Stage::NostrConnect
+                | Stage::WhatsNew
         )
     }
 }
 
+/// Pending proposals filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalFilter {
+    AwaitingMyApproval,
+    ReadyToFinalize,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Mode {
     #[default]
@@ -166,10 +199,15 @@ pub struct Context {
     pub breadcrumb: Vec<Stage>,
     pub mode: Mode,
     pub current_fees: BTreeMap<Priority, FeeRate>,
+    pub cache: Cache,
+    /// Mainnet actions require a typed confirmation (see [`SmartVaults::mainnet_guard`])
+    pub mainnet_guard: bool,
+    pub initial_sync: InitialSyncProgress,
 }
 
 impl Context {
     pub fn new(stage: Stage, client: SmartVaults) -> Self {
+        let mainnet_guard: bool = client.network() == Network::Bitcoin;
         Self {
             stage: stage.clone(),
             client,
@@ -177,6 +215,9 @@ impl Context {
             breadcrumb: vec![stage],
             mode: Mode::default(),
             current_fees: BTreeMap::new(),
+            cache: Cache::default(),
+            mainnet_guard,
+            initial_sync: InitialSyncProgress::default(),
         }
     }
 
@@ -202,4 +243,9 @@ impl Context {
     pub fn reset_breadcrumb(&mut self) {
         self.breadcrumb.clear();
     }
+
+    /// Check if the mainnet action still needs the typed confirmation
+    pub fn mainnet_guard_pending(&self, confirmation: Option<&str>) -> bool {
+        self.mainnet_guard && confirmation != Some(MAINNET_CONFIRMATION)
+    }
 }
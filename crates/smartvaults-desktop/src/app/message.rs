@@ -3,6 +3,7 @@
 
 use smartvaults_sdk::Message as SdkMessage;
 
+use super::cache::CacheUpdate;
 use super::context::Mode;
 // use super::screen::AddHWSignerMessage;
 use super::screen::{
@@ -14,7 +15,7 @@ use super::screen::{
     PolicyBuilderMessage, PolicyTreeMessage, ProfileMessage, ProposalMessage, ReceiveMessage,
     RecoveryKeysMessage, RelayMessage, RelaysMessage, RestoreVaultMessage, RevokeAllSignersMessage,
     SelfTransferMessage, SettingsMessage, ShareSignerMessage, SignerMessage, SignersMessage,
-    SpendMessage, TransactionMessage, VaultMessage, WipeKeysMessage,
+    SpendMessage, TransactionMessage, VaultMessage, WhatsNewMessage, WipeKeysMessage,
 };
 use super::Stage;
 
@@ -62,12 +63,16 @@ pub enum Message {
     WipeKeys(WipeKeysMessage),
     Connect(ConnectMessage),
     AddNostrConnectSession(AddNostrConnectSessionMessage),
+    WhatsNew(WhatsNewMessage),
     Clipboard(String),
     OpenInBrowser(String),
     ChangeMode(Mode),
     ToggleHideBalances,
+    MainnetGuardChanged(bool),
     Lock,
     Sync(SdkMessage),
+    CacheUpdated(CacheUpdate),
+    CacheError(String),
     Tick,
 }
 
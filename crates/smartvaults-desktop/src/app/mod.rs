@@ -5,13 +5,14 @@ use iced::{clipboard, Command, Element, Subscription};
 use smartvaults_sdk::core::bitcoin::Network;
 use smartvaults_sdk::{Message as SdkMessage, SmartVaults};
 
+mod cache;
 mod component;
 mod context;
 mod message;
 pub mod screen;
 mod sync;
 
-pub use self::context::{Context, Stage};
+pub use self::context::{Context, ProposalFilter, Stage};
 pub use self::message::Message;
 // use self::screen::AddHWSignerState;
 use self::screen::{
@@ -22,7 +23,7 @@ use self::screen::{
     NewProofState, PoliciesState, PolicyBuilderState, PolicyTreeState, ProfileState, ProposalState,
     ReceiveState, RecoveryKeysState, RelayState, RelaysState, RestoreVaultState,
     RevokeAllSignersState, SelfTransferState, SettingsState, ShareSignerState, SignerState,
-    SignersState, SpendState, TransactionState, VaultState, WipeKeysState,
+    SignersState, SpendState, TransactionState, VaultState, WhatsNewState, WipeKeysState,
 };
 use self::sync::SmartVaultsSync;
 use crate::theme::Theme;
@@ -56,7 +57,7 @@ pub fn new_state(ctx: &Context) -> Box<dyn State> {
         Stage::Receive(policy) => ReceiveState::new(policy.clone()).into(),
         Stage::SelfTransfer => SelfTransferState::new().into(),
         Stage::NewProof(policy) => NewProofState::new(policy.clone()).into(),
-        Stage::Activity => ActivityState::new().into(),
+        Stage::Activity(filter) => ActivityState::new(*filter).into(),
         Stage::Proposal(proposal_id) => ProposalState::new(*proposal_id).into(),
         Stage::Transaction { policy_id, txid } => TransactionState::new(*policy_id, *txid).into(),
         Stage::History => HistoryState::new().into(),
@@ -88,6 +89,7 @@ pub fn new_state(ctx: &Context) -> Box<dyn State> {
         Stage::WipeKeys => WipeKeysState::new().into(),
         Stage::NostrConnect => ConnectState::new().into(),
         Stage::AddNostrConnectSession => AddNostrConnectSessionState::new().into(),
+        Stage::WhatsNew => WhatsNewState::new().into(),
     }
 }
 
@@ -106,6 +108,35 @@ impl App {
         }
     }
 
+    /// Show the "What's new" screen on the first run after an upgrade
+    pub fn init(&self) -> Command<Message> {
+        let client = self.ctx.client.clone();
+        let whats_new =
+            Command::perform(async move { client.whats_new().await }, |res| match res {
+                Ok(releases) if !releases.is_empty() => Message::View(Stage::WhatsNew),
+                Ok(_) => Message::Tick,
+                Err(e) => {
+                    tracing::error!("Impossible to get what's new: {e}");
+                    Message::Tick
+                }
+            });
+
+        let client = self.ctx.client.clone();
+        let mainnet_guard =
+            Command::perform(
+                async move { client.mainnet_guard().await },
+                |res| match res {
+                    Ok(enabled) => Message::MainnetGuardChanged(enabled),
+                    Err(e) => {
+                        tracing::error!("Impossible to get mainnet guard setting: {e}");
+                        Message::Tick
+                    }
+                },
+            );
+
+        Command::batch([whats_new, mainnet_guard])
+    }
+
     pub fn title(&self) -> String {
         match self.ctx.client.name() {
             Some(name) => format!("{} [{name}]", self.state.title()),
@@ -138,13 +169,45 @@ impl App {
                 self.state.load(&self.ctx)
             }
             Message::Tick => self.state.update(&mut self.ctx, message),
-            Message::Sync(msg) => match msg {
-                SdkMessage::MempoolFeesUpdated(fees) => {
-                    self.ctx.current_fees = fees;
-                    Command::none()
+            Message::Sync(msg) => {
+                self.ctx.cache.invalidate(&msg);
+                match msg {
+                    SdkMessage::MempoolFeesUpdated(fees) => {
+                        self.ctx.current_fees = fees;
+                        Command::none()
+                    }
+                    // The handled events already reload the state
+                    SdkMessage::SyncProgress {
+                        relay_url,
+                        events_received,
+                        eose,
+                    } => {
+                        self.ctx
+                            .initial_sync
+                            .relays
+                            .insert(relay_url, (events_received, eose));
+                        Command::none()
+                    }
+                    SdkMessage::InitialSyncComplete { .. } => {
+                        self.ctx.initial_sync.completed = true;
+                        Command::none()
+                    }
+                    // Keep the sidebar badge updated
+                    _ => Command::batch([
+                        self.state.load(&self.ctx),
+                        self.ctx.cache.revalidate_counts(&self.ctx.client),
+                    ]),
                 }
-                _ => self.state.load(&self.ctx),
-            },
+            }
+            Message::CacheUpdated(update) => {
+                self.ctx.cache.update(update);
+                self.state.load(&self.ctx)
+            }
+            Message::CacheError(e) => {
+                tracing::error!("Impossible to revalidate cache: {e}");
+                self.ctx.cache.revalidation_failed();
+                Command::none()
+            }
             Message::Clipboard(data) => clipboard::write(data),
             Message::OpenInBrowser(url) => {
                 if let Err(e) = webbrowser::open(&url) {
@@ -160,6 +223,10 @@ impl App {
                 self.ctx.toggle_hide_balances();
                 Command::none()
             }
+            Message::MainnetGuardChanged(enabled) => {
+                self.ctx.mainnet_guard = enabled;
+                Command::none()
+            }
             _ => self.state.update(&mut self.ctx, message),
         }
     }
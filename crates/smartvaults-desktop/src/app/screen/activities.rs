@@ -3,12 +3,12 @@
 
 use std::collections::BTreeSet;
 
-use iced::widget::{Column, Space};
+use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::types::{GetProposal, GetTransaction};
 
 use crate::app::component::{Activity, Dashboard};
-use crate::app::{Context, Message, State};
+use crate::app::{Context, Message, ProposalFilter, Stage, State};
 use crate::component::{Button, ButtonStyle, Text};
 use crate::theme::icon::RELOAD;
 
@@ -20,6 +20,7 @@ pub enum ActivityMessage {
 
 #[derive(Debug, Default)]
 pub struct ActivityState {
+    filter: Option<ProposalFilter>,
     loading: bool,
     loaded: bool,
     proposals: Vec<GetProposal>,
@@ -27,8 +28,11 @@ pub struct ActivityState {
 }
 
 impl ActivityState {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(filter: Option<ProposalFilter>) -> Self {
+        Self {
+            filter,
+            ..Default::default()
+        }
     }
 }
 
@@ -38,16 +42,39 @@ impl State for ActivityState {
     }
 
     fn load(&mut self, ctx: &Context) -> Command<Message> {
-        self.loading = true;
-        let client = ctx.client.clone();
-        Command::perform(
-            async move {
-                let proposals = client.get_proposals().await.unwrap();
-                let txs = client.get_all_transactions().await.unwrap();
-                (proposals, txs)
-            },
-            |(proposals, txs)| ActivityMessage::Load(proposals, txs).into(),
-        )
+        let cache = &ctx.cache;
+        match self.filter {
+            Some(ProposalFilter::AwaitingMyApproval) => {
+                // Depends on my signers: not cached
+                self.loading = true;
+                let client = ctx.client.clone();
+                Command::perform(
+                    async move { client.get_proposals_awaiting_my_approval().await.unwrap() },
+                    |proposals| ActivityMessage::Load(proposals, BTreeSet::new()).into(),
+                )
+            }
+            Some(ProposalFilter::ReadyToFinalize) => {
+                if let Some(mut proposals) = cache.proposals() {
+                    proposals.retain(|p| p.signed);
+                    self.proposals = proposals;
+                    self.loaded = true;
+                }
+                self.loading = !self.loaded;
+                cache.revalidate_proposals(&ctx.client)
+            }
+            None => {
+                if let (Some(proposals), Some(txs)) = (cache.proposals(), cache.transactions()) {
+                    self.proposals = proposals;
+                    self.txs = txs;
+                    self.loaded = true;
+                }
+                self.loading = !self.loaded;
+                Command::batch([
+                    cache.revalidate_proposals(&ctx.client),
+                    cache.revalidate_transactions(&ctx.client),
+                ])
+            }
+        }
     }
 
     fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
@@ -64,7 +91,10 @@ impl State for ActivityState {
                     self.loaded = true;
                     Command::none()
                 }
-                ActivityMessage::Reload => self.load(ctx),
+                ActivityMessage::Reload => {
+                    ctx.cache.invalidate_all();
+                    self.load(ctx)
+                }
             }
         } else {
             Command::none()
@@ -76,6 +106,40 @@ impl State for ActivityState {
         let mut center_y = true;
 
         if self.loaded {
+            if let Some(filter) = self.filter {
+                let title = match filter {
+                    ProposalFilter::AwaitingMyApproval => "Awaiting my approval",
+                    ProposalFilter::ReadyToFinalize => "Ready to finalize",
+                };
+                content = content.push(
+                    Row::new()
+                        .push(Text::new(title).bold().big().width(Length::Fill).view())
+                        .push(
+                            Button::new()
+                                .style(ButtonStyle::Bordered)
+                                .text("Show all")
+                                .on_press(Message::View(Stage::Activity(None)))
+                                .view(),
+                        )
+                        .align_items(Alignment::Center),
+                );
+            } else {
+                content = content.push(
+                    Row::new()
+                        .push(Text::new("All").bold().big().width(Length::Fill).view())
+                        .push(
+                            Button::new()
+                                .style(ButtonStyle::Bordered)
+                                .text("Awaiting my approval")
+                                .on_press(Message::View(Stage::Activity(Some(
+                                    ProposalFilter::AwaitingMyApproval,
+                                ))))
+                                .view(),
+                        )
+                        .align_items(Alignment::Center),
+                );
+            }
+
             if self.proposals.is_empty() {
                 content = content
                     .push(Text::new("No proposals").view())
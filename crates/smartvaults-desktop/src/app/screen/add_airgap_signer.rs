@@ -8,10 +8,11 @@ use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::core::bips::bip32::Fingerprint;
 use smartvaults_sdk::core::miniscript::Descriptor;
 use smartvaults_sdk::core::signer::Signer;
+use smartvaults_sdk::util::ur::CryptoAccount;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
-use crate::component::{Button, Text, TextInput};
+use crate::component::{Button, ButtonStyle, Text, TextInput};
 use crate::theme::color::DARK_RED;
 
 #[derive(Debug, Clone)]
@@ -19,6 +20,8 @@ pub enum AddAirGapSignerMessage {
     NameChanged(String),
     FingerprintChanged(String),
     DescriptorChanged(String),
+    UrChanged(String),
+    ImportUr,
     ErrorChanged(Option<String>),
     SaveSigner,
 }
@@ -28,6 +31,7 @@ pub struct AddAirGapSignerState {
     name: String,
     fingerprint: String,
     descriptor: String,
+    ur: String,
     loading: bool,
     error: Option<String>,
 }
@@ -51,6 +55,21 @@ impl State for AddAirGapSignerState {
                     self.fingerprint = fingerprint
                 }
                 AddAirGapSignerMessage::DescriptorChanged(desc) => self.descriptor = desc,
+                AddAirGapSignerMessage::UrChanged(ur) => self.ur = ur,
+                AddAirGapSignerMessage::ImportUr => {
+                    let network = ctx.client.network();
+                    match CryptoAccount::from_ur(&[&self.ur]).and_then(|account| {
+                        account.check_network(network)?;
+                        Ok((account.fingerprint, account.descriptor()?))
+                    }) {
+                        Ok((fingerprint, descriptor)) => {
+                            self.fingerprint = fingerprint.to_string();
+                            self.descriptor = descriptor.to_string();
+                            self.error = None;
+                        }
+                        Err(e) => self.error = Some(e.to_string()),
+                    }
+                }
                 AddAirGapSignerMessage::ErrorChanged(error) => {
                     self.error = error;
                     self.loading = false;
@@ -105,6 +124,18 @@ impl State for AddAirGapSignerState {
             .placeholder("Descriptor")
             .view();
 
+        let ur = TextInput::with_label("UR (optional)", &self.ur)
+            .on_input(|s| AddAirGapSignerMessage::UrChanged(s).into())
+            .placeholder("Paste the scanned ur:crypto-account QR code(s)")
+            .view();
+
+        let import_ur_btn = Button::new()
+            .text("Import from QR")
+            .style(ButtonStyle::Bordered)
+            .on_press(AddAirGapSignerMessage::ImportUr.into())
+            .width(Length::Fill)
+            .view();
+
         let error = if let Some(error) = &self.error {
             Row::new().push(Text::new(error).color(DARK_RED).view())
         } else {
@@ -124,6 +155,8 @@ impl State for AddAirGapSignerState {
                     .width(Length::Fill),
             )
             .push(name)
+            .push(ur)
+            .push(import_ur_btn)
             .push(fingerprint)
             .push(descriptor)
             .push(error)
@@ -3,7 +3,7 @@
 
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
-use smartvaults_sdk::nostr::Keys;
+use smartvaults_sdk::util;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
@@ -45,11 +45,11 @@ impl State for AddContactState {
                 }
                 AddContactMessage::SaveContact => {
                     let client = ctx.client.clone();
-                    match Keys::parse(&self.public_key) {
-                        Ok(keys) => {
+                    match util::parse_public_key(&self.public_key) {
+                        Ok(public_key) => {
                             self.loading = true;
                             return Command::perform(
-                                async move { client.add_contact(keys.public_key()).await },
+                                async move { client.add_contact(public_key).await },
                                 |res| match res {
                                     Ok(_) => Message::View(Stage::Contacts),
                                     Err(e) => {
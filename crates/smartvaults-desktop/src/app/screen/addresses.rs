@@ -18,6 +18,9 @@ use crate::app::{Context, Message, State};
 use crate::component::{rule, Button, ButtonStyle, Text};
 use crate::theme::icon::CLIPBOARD;
 
+/// Number of address balances loaded at once
+const BALANCES_PAGE_SIZE: usize = 50;
+
 #[derive(Debug, Clone, Eq)]
 pub struct PolicyPickList {
     pub policy_id: EventId,
@@ -41,7 +44,12 @@ pub enum AddressesMessage {
     LoadPolicies(Vec<PolicyPickList>),
     LoadAddresses(EventId),
     PolicySelectd(PolicyPickList),
-    AddressesChanged(Vec<GetAddress>, HashMap<ScriptBuf, u64>),
+    AddressesChanged(EventId, Vec<GetAddress>),
+    BalancesPageLoaded {
+        policy_id: EventId,
+        offset: usize,
+        balances: HashMap<ScriptBuf, u64>,
+    },
     ErrorChanged(Option<String>),
 }
 
@@ -73,6 +81,40 @@ impl AddressesState {
     }
 }
 
+impl AddressesState {
+    fn load_balances_page(
+        &self,
+        ctx: &Context,
+        policy_id: EventId,
+        offset: usize,
+    ) -> Command<Message> {
+        let page: Vec<GetAddress> = self
+            .addresses
+            .iter()
+            .skip(offset)
+            .take(BALANCES_PAGE_SIZE)
+            .cloned()
+            .collect();
+        if page.is_empty() {
+            return Command::none();
+        }
+
+        let client = ctx.client.clone();
+        Command::perform(
+            async move { client.get_addresses_balances_page(policy_id, &page).await },
+            move |res| match res {
+                Ok(balances) => AddressesMessage::BalancesPageLoaded {
+                    policy_id,
+                    offset,
+                    balances,
+                }
+                .into(),
+                Err(e) => AddressesMessage::ErrorChanged(Some(e.to_string())).into(),
+            },
+        )
+    }
+}
+
 impl State for AddressesState {
     fn title(&self) -> String {
         String::from("Addresses")
@@ -119,17 +161,10 @@ impl State for AddressesState {
                 AddressesMessage::LoadAddresses(policy_id) => {
                     let client = ctx.client.clone();
                     return Command::perform(
-                        async move {
-                            let addresses = client.get_addresses(policy_id).await?;
-                            let balances = client.get_addresses_balances(policy_id).await?;
-                            Ok::<
-                                (Vec<GetAddress>, HashMap<ScriptBuf, u64>),
-                                Box<dyn std::error::Error>,
-                            >((addresses, balances))
-                        },
-                        |res| match res {
-                            Ok((addresses, balances)) => {
-                                AddressesMessage::AddressesChanged(addresses, balances).into()
+                        async move { client.get_addresses(policy_id).await },
+                        move |res| match res {
+                            Ok(addresses) => {
+                                AddressesMessage::AddressesChanged(policy_id, addresses).into()
                             }
                             Err(e) => AddressesMessage::ErrorChanged(Some(e.to_string())).into(),
                         },
@@ -142,9 +177,25 @@ impl State for AddressesState {
                         AddressesMessage::LoadAddresses(policy_id).into()
                     });
                 }
-                AddressesMessage::AddressesChanged(addresses, balances) => {
+                AddressesMessage::AddressesChanged(policy_id, addresses) => {
+                    if self.policy.as_ref().map(|p| p.policy_id) != Some(policy_id) {
+                        return Command::none();
+                    }
                     self.addresses = addresses;
-                    self.balances = balances;
+                    self.balances.clear();
+                    return self.load_balances_page(ctx, policy_id, 0);
+                }
+                AddressesMessage::BalancesPageLoaded {
+                    policy_id,
+                    offset,
+                    balances,
+                } => {
+                    // Policy changed in the meanwhile
+                    if self.policy.as_ref().map(|p| p.policy_id) != Some(policy_id) {
+                        return Command::none();
+                    }
+                    self.balances.extend(balances);
+                    return self.load_balances_page(ctx, policy_id, offset + BALANCES_PAGE_SIZE);
                 }
                 AddressesMessage::ErrorChanged(error) => {
                     self.loading = false;
@@ -185,7 +236,7 @@ impl State for AddressesState {
                 .push(
                     Row::new()
                         .push(
-                            Text::new("#")
+                            Text::new("Index")
                                 .bold()
                                 .big()
                                 .horizontal_alignment(Horizontal::Center)
@@ -200,6 +251,14 @@ impl State for AddressesState {
                                 .width(Length::Fill)
                                 .view(),
                         )
+                        .push(
+                            Text::new("Used")
+                                .bold()
+                                .big()
+                                .horizontal_alignment(Horizontal::Center)
+                                .width(Length::Fixed(60.0))
+                                .view(),
+                        )
                         .push(
                             Text::new("Label")
                                 .bold()
@@ -223,7 +282,14 @@ impl State for AddressesState {
                 )
                 .push(rule::horizontal_bold());
 
-            for (index, GetAddress { address, label }) in self.addresses.iter().enumerate() {
+            for GetAddress {
+                address,
+                index,
+                used,
+                label,
+                ..
+            } in self.addresses.iter()
+            {
                 let address = address.clone().assume_checked();
                 let row = Row::new()
                     .push(
@@ -238,6 +304,12 @@ impl State for AddressesState {
                             .width(Length::Fill)
                             .view(),
                     )
+                    .push(
+                        Text::new(if *used { "Yes" } else { "No" })
+                            .horizontal_alignment(Horizontal::Center)
+                            .width(Length::Fixed(60.0))
+                            .view(),
+                    )
                     .push(
                         Text::new(label.clone().unwrap_or_default())
                             .horizontal_alignment(Horizontal::Center)
@@ -245,15 +317,10 @@ impl State for AddressesState {
                             .view(),
                     )
                     .push(
-                        Text::new(format!(
-                            "{} sat",
-                            util::format::number(
-                                self.balances
-                                    .get(&address.script_pubkey())
-                                    .copied()
-                                    .unwrap_or_default()
-                            )
-                        ))
+                        Text::new(match self.balances.get(&address.script_pubkey()) {
+                            Some(balance) => format!("{} sat", util::format::number(*balance)),
+                            None => String::from("..."),
+                        })
                         .horizontal_alignment(Horizontal::Center)
                         .width(Length::Fixed(125.0))
                         .view(),
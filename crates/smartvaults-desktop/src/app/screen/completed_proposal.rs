@@ -9,7 +9,7 @@ use iced::{Command, Element, Length};
 use rfd::FileDialog;
 use smartvaults_sdk::core::proposal::CompletedProposal;
 use smartvaults_sdk::nostr::EventId;
-use smartvaults_sdk::types::GetCompletedProposal;
+use smartvaults_sdk::types::{DeletionImpact, GetCompletedProposal};
 use smartvaults_sdk::util;
 
 use crate::app::component::Dashboard;
@@ -28,8 +28,11 @@ pub enum ProofStatus {
 
 #[derive(Debug, Clone)]
 pub enum CompletedProposalMessage {
-    Load(CompletedProposal, EventId),
+    Load(CompletedProposal, EventId, ProofStatus),
     Delete,
+    DeletionImpactLoaded(DeletionImpact),
+    ConfirmDelete { delete_dependents: bool },
+    CancelDelete,
     VerifyProof,
     UpdateProofStatus(ProofStatus),
     ExportProof,
@@ -45,6 +48,7 @@ pub struct CompletedProposalState {
     completed_proposal: Option<CompletedProposal>,
     policy_id: Option<EventId>,
     proof_status: ProofStatus,
+    deletion_impact: Option<DeletionImpact>,
     error: Option<String>,
 }
 
@@ -57,6 +61,7 @@ impl CompletedProposalState {
             completed_proposal: None,
             policy_id: None,
             proof_status: ProofStatus::default(),
+            deletion_impact: None,
             error: None,
         }
     }
@@ -88,11 +93,24 @@ impl State for CompletedProposalState {
                     .get_completed_proposal_by_id(completed_proposal_id)
                     .await
                     .ok()?;
-                Some((proposal, policy_id))
+                // Cached verdict of the automatic proof verification
+                let proof_status = match client
+                    .get_proof_verifications()
+                    .await
+                    .ok()
+                    .and_then(|mut v| v.remove(&completed_proposal_id))
+                {
+                    Some(verification) => match verification.spendable {
+                        Some(spendable) => ProofStatus::Valid(spendable),
+                        None => ProofStatus::Invalid,
+                    },
+                    None => ProofStatus::Unknown,
+                };
+                Some((proposal, policy_id, proof_status))
             },
             |res| match res {
-                Some((proposal, policy_id)) => {
-                    CompletedProposalMessage::Load(proposal, policy_id).into()
+                Some((proposal, policy_id, proof_status)) => {
+                    CompletedProposalMessage::Load(proposal, policy_id, proof_status).into()
                 }
                 None => Message::View(Stage::Dashboard),
             },
@@ -106,8 +124,9 @@ impl State for CompletedProposalState {
 
         if let Message::CompletedProposal(msg) = message {
             match msg {
-                CompletedProposalMessage::Load(proposal, policy_id) => {
+                CompletedProposalMessage::Load(proposal, policy_id, proof_status) => {
                     self.policy_id = Some(policy_id);
+                    self.proof_status = proof_status;
                     self.completed_proposal = Some(proposal);
                     self.loading = false;
                     self.loaded = true;
@@ -183,7 +202,49 @@ impl State for CompletedProposalState {
                     return Command::perform(
                         async move {
                             client
-                                .delete_completed_proposal_by_id(completed_proposal_id)
+                                .completed_proposal_deletion_impact(completed_proposal_id)
+                                .await
+                        },
+                        |res| match res {
+                            Ok(impact) => {
+                                CompletedProposalMessage::DeletionImpactLoaded(impact).into()
+                            }
+                            Err(e) => {
+                                CompletedProposalMessage::ErrorChanged(Some(e.to_string())).into()
+                            }
+                        },
+                    );
+                }
+                CompletedProposalMessage::DeletionImpactLoaded(impact) => {
+                    self.loading = false;
+                    if impact.has_dependents() {
+                        self.deletion_impact = Some(impact);
+                    } else {
+                        return self.update(
+                            ctx,
+                            CompletedProposalMessage::ConfirmDelete {
+                                delete_dependents: false,
+                            }
+                            .into(),
+                        );
+                    }
+                }
+                CompletedProposalMessage::CancelDelete => {
+                    self.deletion_impact = None;
+                }
+                CompletedProposalMessage::ConfirmDelete { delete_dependents } => {
+                    self.loading = true;
+                    self.deletion_impact = None;
+                    ctx.cache.invalidate_all();
+                    let client = ctx.client.clone();
+                    let completed_proposal_id = self.completed_proposal_id;
+                    return Command::perform(
+                        async move {
+                            client
+                                .delete_completed_proposal_by_id(
+                                    completed_proposal_id,
+                                    delete_dependents,
+                                )
                                 .await
                         },
                         |res| match res {
@@ -319,6 +380,78 @@ impl State for CompletedProposalState {
                         .push(Space::with_height(10.0))
                         .push(buttons.push(delete_btn))
                         .push(Space::with_height(20.0));
+
+                    if let Some(impact) = &self.deletion_impact {
+                        content = content
+                            .push(
+                                Text::new(format!(
+                                    "This proposal has {} label(s) and {} fee bump link(s) referencing its transaction",
+                                    impact.labels.len(),
+                                    impact.superseded_links
+                                ))
+                                .color(RED)
+                                .view(),
+                            );
+
+                        for label in impact.labels.values() {
+                            content = content.push(
+                                Text::new(format!("- {} label: {}", label.kind(), label.text()))
+                                    .extra_light()
+                                    .view(),
+                            );
+                        }
+
+                        if impact.audit_entries > 0 {
+                            content = content.push(
+                                Text::new(format!(
+                                    "{} signing audit entries will be retained",
+                                    impact.audit_entries
+                                ))
+                                .extra_light()
+                                .view(),
+                            );
+                        }
+
+                        content = content
+                            .push(
+                                Row::new()
+                                    .push(
+                                        Button::new()
+                                            .style(ButtonStyle::Danger)
+                                            .text("Delete all")
+                                            .on_press(
+                                                CompletedProposalMessage::ConfirmDelete {
+                                                    delete_dependents: true,
+                                                }
+                                                .into(),
+                                            )
+                                            .loading(self.loading)
+                                            .view(),
+                                    )
+                                    .push(
+                                        Button::new()
+                                            .style(ButtonStyle::Bordered)
+                                            .text("Keep labels")
+                                            .on_press(
+                                                CompletedProposalMessage::ConfirmDelete {
+                                                    delete_dependents: false,
+                                                }
+                                                .into(),
+                                            )
+                                            .loading(self.loading)
+                                            .view(),
+                                    )
+                                    .push(
+                                        Button::new()
+                                            .style(ButtonStyle::Bordered)
+                                            .text("Cancel")
+                                            .on_press(CompletedProposalMessage::CancelDelete.into())
+                                            .view(),
+                                    )
+                                    .spacing(10),
+                            )
+                            .push(Space::with_height(20.0));
+                    }
                 }
             }
         }
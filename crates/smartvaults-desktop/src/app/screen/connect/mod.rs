@@ -281,13 +281,13 @@ impl State for ConnectState {
                                 .view(),
                         )
                         .push(
-                            Text::new(timestamp.to_human_datetime())
+                            Text::new(util::format::relative_datetime(*timestamp))
                                 .width(Length::Fill)
                                 .view(),
                         )
                         .push(
                             Text::new(match self.authorizations.get(&uri.public_key) {
-                                Some(timestamp) => timestamp.to_human_datetime(),
+                                Some(timestamp) => util::format::relative_datetime(*timestamp),
                                 None => String::from("-"),
                             })
                             .width(Length::Fill)
@@ -392,7 +392,7 @@ impl State for ConnectState {
                                 )
                                 .push(Text::new(req.method()).width(Length::Fill).view())
                                 .push(
-                                    Text::new(request.timestamp.to_human_datetime())
+                                    Text::new(util::format::datetime(request.timestamp))
                                         .width(Length::Fill)
                                         .view(),
                                 )
@@ -477,7 +477,7 @@ impl State for ConnectState {
                                 )
                                 .push(Text::new(req.method()).width(Length::Fill).view())
                                 .push(
-                                    Text::new(request.timestamp.to_human_datetime())
+                                    Text::new(util::format::datetime(request.timestamp))
                                         .width(Length::Fill)
                                         .view(),
                                 )
@@ -5,7 +5,8 @@ use std::collections::BTreeSet;
 
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
-use smartvaults_sdk::nostr::{Profile, PublicKey};
+use smartvaults_sdk::nostr::PublicKey;
+use smartvaults_sdk::types::{GetContact, UserRole};
 use smartvaults_sdk::util;
 
 use crate::app::component::Dashboard;
@@ -15,7 +16,7 @@ use crate::theme::icon::{CLIPBOARD, PLUS, RELOAD, TRASH};
 
 #[derive(Debug, Clone)]
 pub enum ContactsMessage {
-    LoadContacts(BTreeSet<Profile>),
+    LoadContacts(BTreeSet<GetContact>),
     RemovePublicKey(PublicKey),
     ErrorChanged(Option<String>),
     Reload,
@@ -25,7 +26,7 @@ pub enum ContactsMessage {
 pub struct ContactsState {
     loading: bool,
     loaded: bool,
-    contacts: BTreeSet<Profile>,
+    contacts: BTreeSet<GetContact>,
     error: Option<String>,
 }
 
@@ -47,9 +48,10 @@ impl State for ContactsState {
 
         self.loading = true;
         let client = ctx.client.clone();
-        Command::perform(async move { client.get_contacts().await.unwrap() }, |p| {
-            ContactsMessage::LoadContacts(p).into()
-        })
+        Command::perform(
+            async move { client.get_contacts_with_metadata().await.unwrap() },
+            |p| ContactsMessage::LoadContacts(p).into(),
+        )
     }
 
     fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
@@ -138,6 +140,7 @@ impl State for ContactsState {
                                     .view(),
                             )
                             .push(Text::new("NIP-05").bold().big().width(Length::Fill).view())
+                            .push(Text::new("Role").bold().big().width(Length::Fill).view())
                             .push(
                                 Button::new()
                                     .style(ButtonStyle::Bordered)
@@ -161,9 +164,15 @@ impl State for ContactsState {
                     )
                     .push(rule::horizontal_bold());
 
-                for user in self.contacts.iter() {
-                    let public_key = user.public_key();
-                    let metadata = user.metadata();
+                for contact in self.contacts.iter() {
+                    let public_key = contact.profile.public_key();
+                    let metadata = contact.profile.metadata();
+                    // Roles of other users are self-declared
+                    let role = match contact.role {
+                        UserRole::KeyAgent if contact.verified => "Key agent (verified)",
+                        UserRole::KeyAgent => "Key agent (self-declared)",
+                        UserRole::User => "",
+                    };
 
                     let row = Row::new()
                         .push(
@@ -186,11 +195,12 @@ impl State for ContactsState {
                                 .width(Length::Fill)
                                 .view(),
                         )
+                        .push(Text::new(role).width(Length::Fill).view())
                         .push(
                             Button::new()
                                 .style(ButtonStyle::Bordered)
                                 .icon(CLIPBOARD)
-                                .on_press(Message::Clipboard(public_key.to_string()))
+                                .on_press(Message::Clipboard(util::display_public_key(public_key)))
                                 .width(Length::Fixed(40.0))
                                 .view(),
                         )
@@ -3,20 +3,18 @@
 
 use std::collections::BTreeSet;
 
-use iced::widget::{Column, Space};
+use iced::widget::{Column, Row, Space};
 use iced::{Command, Element, Length};
-use smartvaults_sdk::core::bdk::wallet::Balance;
-use smartvaults_sdk::types::{GetProposal, GetTransaction};
+use smartvaults_sdk::types::{Counts, GetProposal, GetTransaction};
 
 use crate::app::component::{Activity, Balances, Dashboard};
-use crate::app::{Context, Message, Stage, State};
-use crate::component::Text;
+use crate::app::{Context, Message, ProposalFilter, Stage, State};
+use crate::component::{Button, ButtonStyle, Card, Text};
 
 #[derive(Debug, Clone)]
 pub enum DashboardMessage {
     Send,
     Deposit,
-    Load(Balance, Vec<GetProposal>, BTreeSet<GetTransaction>),
     Reload,
 }
 
@@ -24,7 +22,7 @@ pub enum DashboardMessage {
 pub struct DashboardState {
     loading: bool,
     loaded: bool,
-    balance: Balance,
+    counts: Counts,
     proposals: Vec<GetProposal>,
     transactions: BTreeSet<GetTransaction>,
 }
@@ -33,6 +31,50 @@ impl DashboardState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn view_counts(&self) -> Element<'static, Message> {
+        let card = |title: &str, count: usize, stage: Stage| {
+            Card::new(
+                Text::new(title).view(),
+                Text::new(count.to_string()).bold().big().view(),
+            )
+            .foot(
+                Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("View")
+                    .on_press(Message::View(stage))
+                    .width(Length::Fill)
+                    .view(),
+            )
+            .max_width(200.0)
+            .view()
+        };
+
+        Row::new()
+            .push(card("Vaults", self.counts.policies, Stage::Vaults))
+            .push(card(
+                "Pending proposals",
+                self.counts.pending_proposals,
+                Stage::Activity(None),
+            ))
+            .push(card(
+                "Awaiting my approval",
+                self.counts.awaiting_my_approval,
+                Stage::Activity(Some(ProposalFilter::AwaitingMyApproval)),
+            ))
+            .push(card(
+                "Ready to finalize",
+                self.counts.ready_to_finalize,
+                Stage::Activity(Some(ProposalFilter::ReadyToFinalize)),
+            ))
+            .push(card(
+                "Completed this month",
+                self.counts.completed_this_month,
+                Stage::History,
+            ))
+            .spacing(10)
+            .into()
+    }
 }
 
 impl State for DashboardState {
@@ -41,18 +83,21 @@ impl State for DashboardState {
     }
 
     fn load(&mut self, ctx: &Context) -> Command<Message> {
-        let client = ctx.client.clone();
-        self.loading = true;
-        Command::perform(
-            async move {
-                let balance = client.get_total_balance().await.unwrap();
-                let txs = client.get_all_transactions().await.unwrap();
-                let proposals = client.get_proposals().await.unwrap();
-
-                (balance, proposals, txs)
-            },
-            |(balance, proposals, txs)| DashboardMessage::Load(balance, proposals, txs).into(),
-        )
+        let cache = &ctx.cache;
+        if let (Some(counts), Some(proposals), Some(txs)) =
+            (cache.counts(), cache.proposals(), cache.transactions())
+        {
+            self.counts = counts;
+            self.proposals = proposals;
+            self.transactions = txs;
+            self.loaded = true;
+        }
+        self.loading = !self.loaded;
+        Command::batch([
+            cache.revalidate_counts(&ctx.client),
+            cache.revalidate_proposals(&ctx.client),
+            cache.revalidate_transactions(&ctx.client),
+        ])
     }
 
     fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
@@ -68,14 +113,8 @@ impl State for DashboardState {
                 DashboardMessage::Deposit => {
                     return Command::perform(async {}, |_| Message::View(Stage::Receive(None)))
                 }
-                DashboardMessage::Load(balance, proposals, txs) => {
-                    self.balance = balance;
-                    self.proposals = proposals;
-                    self.transactions = txs;
-                    self.loading = false;
-                    self.loaded = true;
-                }
                 DashboardMessage::Reload => {
+                    ctx.cache.invalidate_all();
                     return self.load(ctx);
                 }
             }
@@ -95,7 +134,7 @@ impl State for DashboardState {
 
             content = content
                 .push(
-                    Balances::new(self.balance.clone())
+                    Balances::new(self.counts.balance.clone())
                         .bigger()
                         .hide(ctx.hide_balances)
                         .on_send(DashboardMessage::Send.into())
@@ -103,6 +142,8 @@ impl State for DashboardState {
                         .view(),
                 )
                 .push(Space::with_height(Length::Fixed(20.0)))
+                .push(self.view_counts())
+                .push(Space::with_height(Length::Fixed(20.0)))
                 .push(Text::new("Activity").bold().big().view())
                 .push(Space::with_height(Length::Fixed(10.0)))
                 .push(Activity::new(self.proposals.clone(), self.transactions.clone()).view(ctx));
@@ -1,10 +1,14 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use iced::widget::{Column, Row, Space};
+use std::fmt;
+
+use iced::widget::{Column, PickList, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::nostr::Metadata;
 use smartvaults_sdk::protocol::v1::KeyAgentMetadata;
+use smartvaults_sdk::types::ProfileVersion;
+use smartvaults_sdk::util::format;
 
 use crate::app::component::Dashboard;
 use crate::app::context::Mode;
@@ -12,9 +16,36 @@ use crate::app::{Context, Message, Stage, State};
 use crate::component::{Button, Text, TextInput};
 use crate::theme::color::DARK_RED;
 
+#[derive(Debug, Clone)]
+pub struct ProfileVersionPickList {
+    inner: ProfileVersion,
+}
+
+impl PartialEq for ProfileVersionPickList {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.version == other.inner.version
+    }
+}
+
+impl Eq for ProfileVersionPickList {}
+
+impl fmt::Display for ProfileVersionPickList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} - {} ({})",
+            self.inner.version,
+            self.inner.metadata.name.as_deref().unwrap_or("-"),
+            format::datetime(self.inner.timestamp)
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum EditProfileMessage {
-    LoadMetadata(Box<Metadata>),
+    LoadMetadata(Box<Metadata>, Vec<ProfileVersion>),
+    VersionSelected(ProfileVersionPickList),
+    Revert,
     NameChanged(String),
     DisplayNameChanged(String),
     NIP05Changed(String),
@@ -38,6 +69,8 @@ pub struct EditProfileState {
     twitter: String,
     facebook: String,
     linkedin: String,
+    history: Vec<ProfileVersionPickList>,
+    selected_version: Option<ProfileVersionPickList>,
     error: Option<String>,
 }
 
@@ -60,8 +93,14 @@ impl State for EditProfileState {
         self.loading = true;
         let client = ctx.client.clone();
         Command::perform(
-            async move { client.get_profile().await.unwrap().metadata() },
-            |metadata| EditProfileMessage::LoadMetadata(Box::new(metadata)).into(),
+            async move {
+                let metadata = client.get_profile().await.unwrap().metadata();
+                let history = client.get_profile_history().await.unwrap_or_default();
+                (metadata, history)
+            },
+            |(metadata, history)| {
+                EditProfileMessage::LoadMetadata(Box::new(metadata), history).into()
+            },
         )
     }
 
@@ -72,7 +111,11 @@ impl State for EditProfileState {
 
         if let Message::EditProfile(msg) = message {
             match msg {
-                EditProfileMessage::LoadMetadata(metadata) => {
+                EditProfileMessage::LoadMetadata(metadata, history) => {
+                    self.history = history
+                        .into_iter()
+                        .map(|inner| ProfileVersionPickList { inner })
+                        .collect();
                     self.current_metadata = *metadata.clone();
                     if let Some(name) = metadata.name {
                         self.name = name;
@@ -86,6 +129,25 @@ impl State for EditProfileState {
                     self.loading = false;
                     self.loaded = true;
                 }
+                EditProfileMessage::VersionSelected(version) => {
+                    self.selected_version = Some(version)
+                }
+                EditProfileMessage::Revert => {
+                    if let Some(selected) = &self.selected_version {
+                        self.loading = true;
+                        let client = ctx.client.clone();
+                        let version: u64 = selected.inner.version;
+                        return Command::perform(
+                            async move { client.revert_profile(version).await },
+                            |res| match res {
+                                Ok(_) => Message::View(Stage::Profile),
+                                Err(e) => {
+                                    EditProfileMessage::ErrorChanged(Some(e.to_string())).into()
+                                }
+                            },
+                        );
+                    }
+                }
                 EditProfileMessage::NameChanged(name) => self.name = name,
                 EditProfileMessage::DisplayNameChanged(display_name) => {
                     self.display_name = display_name
@@ -217,7 +279,46 @@ impl State for EditProfileState {
                         .loading(self.loading)
                         .width(Length::Fill)
                         .view(),
-                )
+                );
+
+            if let Some(last) = self.history.first() {
+                content = content
+                    .push(Space::with_height(Length::Fixed(15.0)))
+                    .push(
+                        Text::new(format!(
+                            "Last published at {}",
+                            format::datetime(last.inner.timestamp)
+                        ))
+                        .small()
+                        .extra_light()
+                        .view(),
+                    )
+                    .push(
+                        Row::new()
+                            .push(
+                                PickList::new(
+                                    self.history.clone(),
+                                    self.selected_version.clone(),
+                                    |version| EditProfileMessage::VersionSelected(version).into(),
+                                )
+                                .width(Length::Fill)
+                                .padding(10)
+                                .placeholder("Revert to a previous version"),
+                            )
+                            .push(
+                                Button::new()
+                                    .text("Revert")
+                                    .on_press(EditProfileMessage::Revert.into())
+                                    .loading(self.loading || self.selected_version.is_none())
+                                    .width(Length::Fixed(100.0))
+                                    .view(),
+                            )
+                            .align_items(Alignment::Center)
+                            .spacing(10),
+                    );
+            }
+
+            content = content
                 .align_items(Alignment::Center)
                 .spacing(10)
                 .padding(20)
@@ -11,7 +11,7 @@ use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::nostr::EventId;
 use smartvaults_sdk::protocol::v1::key_agent::signer::Currency;
 use smartvaults_sdk::protocol::v1::{BasisPoints, DeviceType, Price, SignerOffering, Temperature};
-use smartvaults_sdk::types::GetSigner;
+use smartvaults_sdk::types::{GetSigner, UserRole};
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
@@ -274,7 +274,13 @@ impl State for EditSignerOfferingState {
                                     network: client.network(),
                                 };
                                 return Command::perform(
-                                    async move { client.signer_offering(&signer, offering).await },
+                                    async move {
+                                        // Publishing an offering declares the key agent role
+                                        if client.my_role().await? != UserRole::KeyAgent {
+                                            client.announce_key_agent().await?;
+                                        }
+                                        client.signer_offering(&signer, offering).await
+                                    },
                                     |res| match res {
                                         Ok(_) => Message::View(Stage::Signers),
                                         Err(e) => EditSignerOfferingMessage::ErrorChanged(Some(
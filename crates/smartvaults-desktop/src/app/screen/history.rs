@@ -1,26 +1,43 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use iced::widget::{Column, Space};
+use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
-use smartvaults_sdk::types::GetCompletedProposal;
+use smartvaults_sdk::types::{GetCompletedProposal, Page, Pagination};
 
 use crate::app::component::{CompletedProposalsList, Dashboard};
 use crate::app::{Context, Message, State};
 use crate::component::{Button, ButtonStyle, Text};
 use crate::theme::icon::RELOAD;
 
+const PAGE_SIZE: usize = 25;
+
 #[derive(Debug, Clone)]
 pub enum HistoryMessage {
-    LoadCompletedProposals(Vec<GetCompletedProposal>),
+    LoadCompletedProposals(Page<GetCompletedProposal>),
+    PageChanged(usize),
     Reload,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct HistoryState {
     loading: bool,
     loaded: bool,
     proposals: Vec<GetCompletedProposal>,
+    page: usize,
+    total: usize,
+}
+
+impl Default for HistoryState {
+    fn default() -> Self {
+        Self {
+            loading: false,
+            loaded: false,
+            proposals: Vec::new(),
+            page: 1,
+            total: 0,
+        }
+    }
 }
 
 impl HistoryState {
@@ -37,8 +54,14 @@ impl State for HistoryState {
     fn load(&mut self, ctx: &Context) -> Command<Message> {
         self.loading = true;
         let client = ctx.client.clone();
+        let opts = Pagination::new().page(self.page, PAGE_SIZE);
         Command::perform(
-            async move { client.get_completed_proposals().await.unwrap() },
+            async move {
+                client
+                    .get_completed_proposals_paginated(opts)
+                    .await
+                    .unwrap()
+            },
             |p| HistoryMessage::LoadCompletedProposals(p).into(),
         )
     }
@@ -50,12 +73,17 @@ impl State for HistoryState {
 
         if let Message::History(msg) = message {
             match msg {
-                HistoryMessage::LoadCompletedProposals(proposals) => {
-                    self.proposals = proposals;
+                HistoryMessage::LoadCompletedProposals(page) => {
+                    self.proposals = page.items;
+                    self.total = page.total;
                     self.loading = false;
                     self.loaded = true;
                     Command::none()
                 }
+                HistoryMessage::PageChanged(page) => {
+                    self.page = page;
+                    self.load(ctx)
+                }
                 HistoryMessage::Reload => self.load(ctx),
             }
         } else {
@@ -84,7 +112,37 @@ impl State for HistoryState {
                     .align_items(Alignment::Center);
             } else {
                 center_y = false;
-                content = content.push(CompletedProposalsList::new(self.proposals.clone()).view());
+                let pages: usize = self.total.div_ceil(PAGE_SIZE).max(1);
+
+                let mut previous = Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("Previous")
+                    .width(Length::Fixed(120.0))
+                    .loading(self.loading);
+                if self.page > 1 {
+                    previous = previous.on_press(HistoryMessage::PageChanged(self.page - 1).into());
+                }
+
+                let mut next = Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("Next")
+                    .width(Length::Fixed(120.0))
+                    .loading(self.loading);
+                if self.page < pages {
+                    next = next.on_press(HistoryMessage::PageChanged(self.page + 1).into());
+                }
+
+                content = content
+                    .push(CompletedProposalsList::new(self.proposals.clone()).view())
+                    .push(Space::with_height(Length::Fixed(10.0)))
+                    .push(
+                        Row::new()
+                            .push(previous.view())
+                            .push(Text::new(format!("Page {} of {pages}", self.page)).view())
+                            .push(next.view())
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                    );
             }
         }
 
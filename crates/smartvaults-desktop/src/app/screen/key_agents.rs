@@ -192,7 +192,7 @@ impl State for KeyAgentsState {
                             Button::new()
                                 .style(ButtonStyle::Bordered)
                                 .icon(CLIPBOARD)
-                                .on_press(Message::Clipboard(public_key.to_string()))
+                                .on_press(Message::Clipboard(util::display_public_key(public_key)))
                                 .width(Length::Fixed(40.0))
                                 .view(),
                         )
@@ -29,6 +29,7 @@ mod signers;
 mod spend;
 mod transaction;
 mod vault;
+mod whats_new;
 
 // pub use self::add_hw_signer::{AddHWSignerMessage, AddHWSignerState};
 pub use self::activities::{ActivityMessage, ActivityState};
@@ -71,3 +72,4 @@ pub use self::vault::restore::{RestoreVaultMessage, RestoreVaultState};
 pub use self::vault::tree::{PolicyTreeMessage, PolicyTreeState};
 pub use self::vault::vaults::{PoliciesMessage, PoliciesState};
 pub use self::vault::{VaultMessage, VaultState};
+pub use self::whats_new::{WhatsNewMessage, WhatsNewState};
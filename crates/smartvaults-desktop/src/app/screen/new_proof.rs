@@ -87,6 +87,7 @@ impl State for NewProofState {
                         let message = self.message.clone();
                         if !self.message.is_empty() {
                             self.loading = true;
+                            ctx.cache.invalidate_all();
                             return Command::perform(
                                 async move { client.new_proof_proposal(policy_id, message).await },
                                 |res| match res {
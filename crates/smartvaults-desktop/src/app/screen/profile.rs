@@ -72,7 +72,7 @@ impl State for ProfileState {
                     Button::new()
                         .style(ButtonStyle::Bordered)
                         .icon(CLIPBOARD)
-                        .on_press(Message::Clipboard(public_key.to_string()))
+                        .on_press(Message::Clipboard(util::display_public_key(public_key)))
                         .width(Length::Fixed(40.0))
                         .view(),
                 )
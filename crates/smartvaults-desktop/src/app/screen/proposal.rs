@@ -7,12 +7,12 @@ use rfd::FileDialog;
 use smartvaults_sdk::core::bitcoin::psbt::PartiallySignedTransaction;
 use smartvaults_sdk::core::proposal::Proposal;
 use smartvaults_sdk::core::signer::{Signer, SignerType};
-use smartvaults_sdk::core::{CompletedProposal, PsbtUtility};
+use smartvaults_sdk::core::{CompletedProposal, FeeWarning, PsbtUtility};
 use smartvaults_sdk::nostr::{EventId, PublicKey};
-use smartvaults_sdk::types::{GetApproval, GetProposal};
+use smartvaults_sdk::types::{ApproveAndFinalize, GetApproval, GetProposal};
 use smartvaults_sdk::util;
 
-use crate::app::component::Dashboard;
+use crate::app::component::{Dashboard, MainnetGuard};
 use crate::app::{Context, Message, Stage, State};
 use crate::component::{rule, Amount, Button, ButtonStyle, Card, Modal, Text, TextInput};
 use crate::theme::color::{GREEN, RED, YELLOW};
@@ -27,15 +27,20 @@ pub enum ProposalMessage {
         Vec<GetApproval>,
         Option<Signer>,
         PublicKey,
+        Option<FeeWarning>,
+        bool,
     ),
     Approve,
     ApproveWithSeed(String),
+    ApproveAndFinalize,
+    ApproveAndFinalizeWithSeed(String),
     Finalize,
     Signed(bool),
     Reload,
     ExportPsbt,
     RevokeApproval(EventId),
     SetModal(Option<ModalType>),
+    MainnetConfirmationChanged(Option<String>),
     PasswordChanged(String),
     Delete,
     ErrorChanged(Option<String>),
@@ -44,6 +49,7 @@ pub enum ProposalMessage {
 #[derive(Debug, Clone)]
 pub enum ModalType {
     Approve,
+    ApproveAndFinalize,
     Delete,
 }
 
@@ -60,6 +66,13 @@ pub struct ProposalState {
     password: String,
     approved_proposals: Vec<GetApproval>,
     signer: Option<Signer>,
+    fee_warning: Option<FeeWarning>,
+    /// An approval with the seed would make the proposal ready to be finalized
+    approval_completes: bool,
+    /// Typed confirmation of the mainnet guard
+    mainnet_confirmation: Option<String>,
+    /// The mainnet guard is confirming an approve and finalize
+    guarding_approval: bool,
     error: Option<String>,
 }
 
@@ -77,6 +90,10 @@ impl ProposalState {
             password: String::new(),
             approved_proposals: Vec::new(),
             signer: None,
+            fee_warning: None,
+            approval_completes: false,
+            mainnet_confirmation: None,
+            guarding_approval: false,
             error: None,
         }
     }
@@ -101,6 +118,7 @@ impl State for ProposalState {
                     policy_id,
                     proposal,
                     signed,
+                    fee_warning,
                     ..
                 } = client.get_proposal_by_id(proposal_id).await.ok()?;
                 let signer = client
@@ -112,6 +130,10 @@ impl State for ProposalState {
                     .await
                     .unwrap_or_default();
                 let keys = client.keys();
+                let approval_completes: bool = client
+                    .approval_completes_proposal(proposal_id)
+                    .await
+                    .unwrap_or_default();
 
                 Some((
                     proposal,
@@ -120,15 +142,31 @@ impl State for ProposalState {
                     approvals,
                     signer,
                     keys.public_key(),
+                    fee_warning,
+                    approval_completes,
                 ))
             },
             |res| match res {
-                Some((proposal, signed, policy_id, approvals, signer, pk)) => {
-                    ProposalMessage::LoadProposal(
-                        proposal, signed, policy_id, approvals, signer, pk,
-                    )
-                    .into()
-                }
+                Some((
+                    proposal,
+                    signed,
+                    policy_id,
+                    approvals,
+                    signer,
+                    pk,
+                    fee_warning,
+                    approval_completes,
+                )) => ProposalMessage::LoadProposal(
+                    proposal,
+                    signed,
+                    policy_id,
+                    approvals,
+                    signer,
+                    pk,
+                    fee_warning,
+                    approval_completes,
+                )
+                .into(),
                 None => Message::View(Stage::Dashboard),
             },
         )
@@ -148,8 +186,12 @@ impl State for ProposalState {
                     approvals,
                     signer,
                     pk,
+                    fee_warning,
+                    approval_completes,
                 ) => {
                     self.proposal = Some(proposal);
+                    self.fee_warning = fee_warning;
+                    self.approval_completes = approval_completes;
                     self.policy_id = Some(policy_id);
                     self.my_public_key = Some(pk);
                     self.signed = signed;
@@ -174,6 +216,7 @@ impl State for ProposalState {
                             }
                             SignerType::Hardware | SignerType::AirGap => {
                                 self.loading = true;
+                                ctx.cache.invalidate_all();
                                 let client = ctx.client.clone();
                                 let proposal_id = self.proposal_id;
                                 return Command::perform(
@@ -225,18 +268,81 @@ impl State for ProposalState {
                     self.modal = None;
                     self.password.clear();
                     self.loading = true;
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     let proposal_id = self.proposal_id;
+                    // The fee warning is shown in the approval modal
+                    let acknowledge_fee: bool = self.fee_warning.is_some();
                     return Command::perform(
-                        async move { client.approve(password, proposal_id).await },
+                        async move {
+                            client
+                                .approve(password, proposal_id, acknowledge_fee, false)
+                                .await
+                        },
                         |res| match res {
                             Ok(_) => ProposalMessage::Reload.into(),
                             Err(e) => ProposalMessage::ErrorChanged(Some(e.to_string())).into(),
                         },
                     );
                 }
+                ProposalMessage::ApproveAndFinalize => {
+                    if ctx.mainnet_guard_pending(self.mainnet_confirmation.as_deref()) {
+                        self.mainnet_confirmation = Some(String::new());
+                        self.guarding_approval = true;
+                        return Command::none();
+                    }
+
+                    self.mainnet_confirmation = None;
+                    self.error = None;
+                    self.modal = Some(ModalType::ApproveAndFinalize);
+                    self.password.clear();
+                }
+                ProposalMessage::ApproveAndFinalizeWithSeed(password) => {
+                    self.modal = None;
+                    self.password.clear();
+                    self.loading = true;
+                    ctx.cache.invalidate_all();
+                    let client = ctx.client.clone();
+                    let proposal_id = self.proposal_id;
+                    // The fee warning is shown in the approval modal
+                    let acknowledge_fee: bool = self.fee_warning.is_some();
+                    if let Some(policy_id) = self.policy_id {
+                        return Command::perform(
+                            async move {
+                                client
+                                    .approve_and_finalize(
+                                        password,
+                                        proposal_id,
+                                        acknowledge_fee,
+                                        false,
+                                    )
+                                    .await
+                            },
+                            move |res| match res {
+                                Ok(ApproveAndFinalize::Finalized {
+                                    completed_proposal, ..
+                                }) => completed_proposal_stage(policy_id, completed_proposal),
+                                // Not completed (ex. approval revoked in the meantime)
+                                Ok(ApproveAndFinalize::Approved { .. }) => {
+                                    ProposalMessage::Reload.into()
+                                }
+                                Err(e) => ProposalMessage::ErrorChanged(Some(e.to_string())).into(),
+                            },
+                        );
+                    } else {
+                        self.error = Some(String::from("No policy id found"));
+                    }
+                }
                 ProposalMessage::Finalize => {
+                    if ctx.mainnet_guard_pending(self.mainnet_confirmation.as_deref()) {
+                        self.mainnet_confirmation = Some(String::new());
+                        self.guarding_approval = false;
+                        return Command::none();
+                    }
+
+                    self.mainnet_confirmation = None;
                     self.loading = true;
+                    ctx.cache.invalidate_all();
 
                     let client = ctx.client.clone();
                     let proposal_id = self.proposal_id;
@@ -245,23 +351,7 @@ impl State for ProposalState {
                         return Command::perform(
                             async move { client.finalize(proposal_id).await },
                             move |res| match res {
-                                Ok(proposal) => match proposal {
-                                    CompletedProposal::Spending { tx, .. } => {
-                                        Message::View(Stage::Transaction {
-                                            policy_id,
-                                            txid: tx.txid(),
-                                        })
-                                    }
-                                    CompletedProposal::KeyAgentPayment { tx, .. } => {
-                                        Message::View(Stage::Transaction {
-                                            policy_id,
-                                            txid: tx.txid(),
-                                        })
-                                    }
-                                    CompletedProposal::ProofOfReserve { .. } => {
-                                        Message::View(Stage::History)
-                                    }
-                                },
+                                Ok(proposal) => completed_proposal_stage(policy_id, proposal),
                                 Err(e) => ProposalMessage::ErrorChanged(Some(e.to_string())).into(),
                             },
                         );
@@ -297,6 +387,7 @@ impl State for ProposalState {
                 }
                 ProposalMessage::RevokeApproval(approval_id) => {
                     self.loading = true;
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     return Command::perform(
                         async move { client.revoke_approval(approval_id).await },
@@ -310,15 +401,19 @@ impl State for ProposalState {
                     self.modal = modal;
                     self.password.clear();
                 }
+                ProposalMessage::MainnetConfirmationChanged(confirmation) => {
+                    self.mainnet_confirmation = confirmation
+                }
                 ProposalMessage::PasswordChanged(password) => self.password = password,
                 ProposalMessage::Delete => {
                     self.loading = true;
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     let proposal_id = self.proposal_id;
                     return Command::perform(
                         async move { client.delete_proposal_by_id(proposal_id).await },
                         |res| match res {
-                            Ok(_) => Message::View(Stage::Activity),
+                            Ok(_) => Message::View(Stage::Activity(None)),
                             Err(e) => ProposalMessage::ErrorChanged(Some(e.to_string())).into(),
                         },
                     );
@@ -347,6 +442,15 @@ impl State for ProposalState {
                         )
                         .push(Space::with_height(Length::Fixed(40.0)));
 
+                    if let Some(warning) = &self.fee_warning {
+                        content = content.push(
+                            Text::new(format!("WARNING: suspicious fee - {warning}"))
+                                .color(RED)
+                                .bold()
+                                .view(),
+                        );
+                    }
+
                     let mut left_content = Column::new().spacing(10).width(Length::Fill).push(
                         Text::new(format!("Vault ID: {}", util::cut_event_id(policy_id)))
                             .on_press(Message::View(Stage::Vault(policy_id)))
@@ -458,7 +562,7 @@ impl State for ProposalState {
                     let mut status = Row::new().push(Text::new("Status: ").view());
 
                     if self.signed {
-                        status = status.push(Text::new("signed").color(GREEN).view());
+                        status = status.push(Text::new("ready to finalize").color(GREEN).view());
                     } else {
                         status = status.push(Text::new("unsigned").color(YELLOW).view());
                     }
@@ -500,8 +604,30 @@ impl State for ProposalState {
                             }
                         };
 
-                    if self.signed && !self.loading {
-                        finalize_btn = finalize_btn.on_press(ProposalMessage::Finalize.into());
+                    if self.signed {
+                        // Enough approvals: finalize is the main action
+                        finalize_btn = finalize_btn.style(ButtonStyle::Primary);
+                        if !self.loading {
+                            finalize_btn = finalize_btn.on_press(ProposalMessage::Finalize.into());
+                        }
+                    }
+
+                    let mut buttons = Row::new().push(approve_btn.view()).spacing(10);
+
+                    // Only for the seed (the other signers approve outside the app)
+                    if self.approval_completes
+                        && self
+                            .signer
+                            .as_ref()
+                            .map_or(true, |s| s.signer_type() == SignerType::Seed)
+                    {
+                        buttons = buttons.push(
+                            Button::new()
+                                .text(format!("Approve & {}", finalize_btn_text.to_lowercase()))
+                                .on_press(ProposalMessage::ApproveAndFinalize.into())
+                                .loading(self.loading)
+                                .view(),
+                        );
                     }
 
                     let export_btn = Button::new()
@@ -528,13 +654,11 @@ impl State for ProposalState {
                     left_content = left_content
                         .push(Space::with_height(10.0))
                         .push(
-                            Row::new()
-                                .push(approve_btn.view())
+                            buttons
                                 .push(finalize_btn.view())
                                 .push(export_btn)
                                 .push(copy_psbt)
-                                .push(delete_btn)
-                                .spacing(10),
+                                .push(delete_btn),
                         )
                         .push(Space::with_height(20.0));
 
@@ -573,7 +697,7 @@ impl State for ProposalState {
                         for GetApproval {
                             approval_id,
                             user,
-                            timestamp,
+                            display_timestamp,
                             ..
                         } in self.approved_proposals.iter()
                         {
@@ -584,7 +708,7 @@ impl State for ProposalState {
                                         .view(),
                                 )
                                 .push(
-                                    Text::new(timestamp.to_human_datetime())
+                                    Text::new(util::format::datetime(*display_timestamp))
                                         .width(Length::Fill)
                                         .view(),
                                 )
@@ -627,13 +751,35 @@ impl State for ProposalState {
             .scrollable(true) // TODO: change when show chat
             .view(ctx, content, false, false);
 
-        if let Some(modal) = &self.modal {
+        if let Some(confirmation) = &self.mainnet_confirmation {
+            Modal::new(
+                dashboard,
+                MainnetGuard::new("finalize this proposal", ctx.client.network(), confirmation)
+                    .on_input(|s| ProposalMessage::MainnetConfirmationChanged(Some(s)).into())
+                    .on_confirm(if self.guarding_approval {
+                        ProposalMessage::ApproveAndFinalize.into()
+                    } else {
+                        ProposalMessage::Finalize.into()
+                    })
+                    .on_cancel(ProposalMessage::MainnetConfirmationChanged(None).into())
+                    .view(),
+            )
+            .on_blur(ProposalMessage::MainnetConfirmationChanged(None).into())
+            .into()
+        } else if let Some(modal) = &self.modal {
             Modal::new(
                 dashboard,
                 match modal {
-                    ModalType::Approve => Card::new(
+                    ModalType::Approve | ModalType::ApproveAndFinalize => Card::new(
                         Text::new("Approve proposal").view(),
-                        Text::new("Do you really want approve this proposal?").view(),
+                        match &self.fee_warning {
+                            Some(warning) => Text::new(format!(
+                                "The fee of this proposal looks suspicious ({warning}). Do you really want approve this proposal?"
+                            ))
+                            .color(RED)
+                            .view(),
+                            None => Text::new("Do you really want approve this proposal?").view(),
+                        },
                     )
                     .foot(
                         Column::new()
@@ -655,12 +801,18 @@ impl State for ProposalState {
                                         Button::new()
                                             .text("Approve")
                                             .width(Length::Fill)
-                                            .on_press(
-                                                ProposalMessage::ApproveWithSeed(
+                                            .on_press(match modal {
+                                                ModalType::ApproveAndFinalize => {
+                                                    ProposalMessage::ApproveAndFinalizeWithSeed(
+                                                        self.password.clone(),
+                                                    )
+                                                    .into()
+                                                }
+                                                _ => ProposalMessage::ApproveWithSeed(
                                                     self.password.clone(),
                                                 )
                                                 .into(),
-                                            )
+                                            })
                                             .loading(self.loading)
                                             .view(),
                                     )
@@ -713,6 +865,19 @@ impl State for ProposalState {
     }
 }
 
+/// Stage to show after the proposal is finalized
+fn completed_proposal_stage(policy_id: EventId, proposal: CompletedProposal) -> Message {
+    match proposal {
+        CompletedProposal::Spending { tx, .. } | CompletedProposal::KeyAgentPayment { tx, .. } => {
+            Message::View(Stage::Transaction {
+                policy_id,
+                txid: tx.txid(),
+            })
+        }
+        CompletedProposal::ProofOfReserve { .. } => Message::View(Stage::History),
+    }
+}
+
 // impl ProposalState {
 // fn view_chat<'a>(&self) -> Container<'a, Message> {
 // let chat = Column::new().spacing(10);
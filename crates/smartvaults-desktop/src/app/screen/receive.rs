@@ -126,6 +126,7 @@ impl State for ReceiveState {
                     let client = ctx.client.clone();
                     if let Some(policy) = self.policy.as_ref() {
                         self.loading = true;
+                        ctx.cache.invalidate_all();
                         let policy_id = policy.policy_id;
                         return Command::perform(
                             async move { client.save_label(policy_id, label).await },
@@ -3,7 +3,7 @@
 
 use iced::widget::{Column, Container, PickList, Row, Space};
 use iced::{Alignment, Command, Element, Length};
-use smartvaults_sdk::core::{Amount, FeeRate};
+use smartvaults_sdk::core::{Amount, CoinSelection, FeeRate};
 use smartvaults_sdk::nostr::EventId;
 use smartvaults_sdk::types::GetProposal;
 use smartvaults_sdk::util::format;
@@ -21,6 +21,7 @@ pub enum SelfTransferMessage {
     AmountChanged(Option<u64>),
     SendAllBtnPressed,
     FeeRateChanged(FeeRate),
+    CoinSelectionChanged(CoinSelection),
     ErrorChanged(Option<String>),
     Review,
     EditProposal,
@@ -35,6 +36,8 @@ pub struct SelfTransferState {
     amount: Option<u64>,
     send_all: bool,
     fee_rate: FeeRate,
+    /// If not set, the default one of the policy is used
+    coin_selection: Option<CoinSelection>,
     reviewing: bool,
     loading: bool,
     loaded: bool,
@@ -54,9 +57,11 @@ impl SelfTransferState {
         amount: Amount,
     ) -> Command<Message> {
         self.loading = true;
+        ctx.cache.invalidate_all();
 
         let client = ctx.client.clone();
         let fee_rate = self.fee_rate;
+        let coin_selection = self.coin_selection;
 
         Command::perform(
             async move {
@@ -69,6 +74,7 @@ impl SelfTransferState {
                         None,
                         None,
                         false,
+                        coin_selection,
                     )
                     .await?;
                 Ok::<EventId, Box<dyn std::error::Error>>(proposal_id)
@@ -124,6 +130,9 @@ impl State for SelfTransferState {
                 SelfTransferMessage::AmountChanged(value) => self.amount = value,
                 SelfTransferMessage::SendAllBtnPressed => self.send_all = !self.send_all,
                 SelfTransferMessage::FeeRateChanged(fee_rate) => self.fee_rate = fee_rate,
+                SelfTransferMessage::CoinSelectionChanged(coin_selection) => {
+                    self.coin_selection = Some(coin_selection)
+                }
                 SelfTransferMessage::ErrorChanged(error) => {
                     self.loading = false;
                     self.error = error;
@@ -233,6 +242,20 @@ impl State for SelfTransferState {
                     .spacing(5)
                     .width(Length::Fill);
 
+                let coin_selection = Column::new()
+                    .push(Row::new().push(Text::new("Coin selection").bold().view()))
+                    .push(
+                        Row::new().push(
+                            Text::new(match self.coin_selection {
+                                Some(coin_selection) => coin_selection.to_string(),
+                                None => String::from("Policy default"),
+                            })
+                            .view(),
+                        ),
+                    )
+                    .spacing(5)
+                    .width(Length::Fill);
+
                 let error = if let Some(error) = &self.error {
                     Row::new().push(Text::new(error).color(DARK_RED).view())
                 } else {
@@ -244,6 +267,7 @@ impl State for SelfTransferState {
                     .push(to_policy)
                     .push(amount)
                     .push(priority)
+                    .push(coin_selection)
                     .push(error)
                     .push(Space::with_height(Length::Fixed(15.0)))
                     .push(
@@ -342,6 +366,18 @@ impl State for SelfTransferState {
                     Text::new("").view()
                 };
 
+                let coin_selection = Column::new()
+                    .push(Text::new("Coin selection").view())
+                    .push(
+                        PickList::new(CoinSelection::list(), self.coin_selection, |c| {
+                            SelfTransferMessage::CoinSelectionChanged(c).into()
+                        })
+                        .width(Length::Fill)
+                        .padding(10)
+                        .placeholder("Policy default"),
+                    )
+                    .spacing(5);
+
                 let error = if let Some(error) = &self.error {
                     Row::new().push(Text::new(error).color(DARK_RED).view())
                 } else {
@@ -359,6 +395,7 @@ impl State for SelfTransferState {
                     .push(to_policy_pick_list)
                     .push(amount)
                     .push(your_balance)
+                    .push(coin_selection)
                     .spacing(10)
                     .max_width(400);
 
@@ -387,7 +424,7 @@ impl State for SelfTransferState {
                                 .max_width(400.0),
                             )
                             .spacing(25)
-                            .height(Length::Fixed(335.0)),
+                            .height(Length::Fixed(375.0)),
                     )
                     .push(Space::with_height(Length::Fixed(5.0)))
                     .push(error)
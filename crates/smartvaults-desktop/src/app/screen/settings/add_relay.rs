@@ -5,6 +5,7 @@ use std::net::SocketAddr;
 
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
+use smartvaults_sdk::types::RelayRole;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
@@ -64,7 +65,7 @@ impl State for AddRelayState {
                                 None
                             };
 
-                            client.add_relay(url, proxy).await?;
+                            client.add_relay(url, proxy, RelayRole::default()).await?;
 
                             Ok::<(), Box<dyn std::error::Error>>(())
                         },
@@ -3,13 +3,16 @@
 
 use std::net::SocketAddr;
 
-use iced::widget::{Column, Row};
+use iced::widget::qr_code::{self, QRCode};
+use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::nostr::Url;
+use smartvaults_sdk::types::DisplayPreferences;
+use smartvaults_sdk::util;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
-use crate::component::{Button, Text, TextInput};
+use crate::component::{rule, Button, ButtonStyle, Text, TextInput};
 use crate::theme::color::DARK_RED;
 
 #[derive(Debug, Clone)]
@@ -18,12 +21,21 @@ pub enum ConfigMessage {
         electrum_endpoint: String,
         proxy: String,
         block_explorer: String,
+        drafts_expiration_days: String,
     },
     ElectrumEndpointChanged(String),
     ProxyChanged(String),
     BlockExplorerChanged(String),
+    DraftsExpirationChanged(String),
     ErrorChanged(Option<String>),
     Save,
+    ExportPasswordChanged(String),
+    Export,
+    Exported(String),
+    ImportPayloadChanged(String),
+    ImportPasswordChanged(String),
+    Import,
+    Imported(DisplayPreferences),
 }
 
 #[derive(Debug, Default)]
@@ -31,6 +43,11 @@ pub struct ConfigState {
     electrum_endpoint: String,
     proxy: String,
     block_explorer: String,
+    drafts_expiration_days: String,
+    export_password: String,
+    export_qr_code: Option<qr_code::State>,
+    import_payload: String,
+    import_password: String,
     loading: bool,
     loaded: bool,
     error: Option<String>,
@@ -56,13 +73,17 @@ impl State for ConfigState {
                     config.electrum_endpoint().await.ok(),
                     config.proxy().await.ok(),
                     config.block_explorer().await.ok(),
+                    *config.drafts.expiration_days.read().await,
                 )
             },
-            |(electrum, proxy, block_explorer)| {
+            |(electrum, proxy, block_explorer, drafts_expiration_days)| {
                 ConfigMessage::Load {
                     electrum_endpoint: electrum.map(|e| e.to_string()).unwrap_or_default(),
                     proxy: proxy.map(|p| p.to_string()).unwrap_or_default(),
                     block_explorer: block_explorer.map(|u| u.to_string()).unwrap_or_default(),
+                    drafts_expiration_days: drafts_expiration_days
+                        .map(|d| d.to_string())
+                        .unwrap_or_default(),
                 }
                 .into()
             },
@@ -76,10 +97,12 @@ impl State for ConfigState {
                     electrum_endpoint,
                     proxy,
                     block_explorer,
+                    drafts_expiration_days,
                 } => {
                     self.electrum_endpoint = electrum_endpoint;
                     self.proxy = proxy;
                     self.block_explorer = block_explorer;
+                    self.drafts_expiration_days = drafts_expiration_days;
                     self.loaded = true;
                     self.loading = false;
                 }
@@ -90,6 +113,7 @@ impl State for ConfigState {
                 ConfigMessage::BlockExplorerChanged(block_explorer) => {
                     self.block_explorer = block_explorer
                 }
+                ConfigMessage::DraftsExpirationChanged(days) => self.drafts_expiration_days = days,
                 ConfigMessage::ErrorChanged(e) => {
                     self.loading = false;
                     self.error = e;
@@ -100,6 +124,7 @@ impl State for ConfigState {
                     let endpoint = self.electrum_endpoint.clone();
                     let proxy = self.proxy.clone();
                     let block_explorer = self.block_explorer.clone();
+                    let drafts_expiration_days = self.drafts_expiration_days.clone();
 
                     return Command::perform(
                         async move {
@@ -115,9 +140,17 @@ impl State for ConfigState {
                                 Some(Url::parse(&block_explorer)?)
                             };
 
+                            let drafts_expiration_days: Option<u64> =
+                                if drafts_expiration_days.is_empty() {
+                                    None
+                                } else {
+                                    Some(drafts_expiration_days.parse::<u64>()?)
+                                };
+
                             config.set_electrum_endpoint(Some(endpoint)).await?;
                             config.set_proxy(proxy).await;
                             config.set_block_explorer(block_explorer).await;
+                            config.set_drafts_expiration(drafts_expiration_days).await;
                             config.save().await?;
 
                             Ok::<(), Box<dyn std::error::Error>>(())
@@ -128,6 +161,54 @@ impl State for ConfigState {
                         },
                     );
                 }
+                ConfigMessage::ExportPasswordChanged(password) => self.export_password = password,
+                ConfigMessage::Export => {
+                    self.loading = true;
+                    let client = ctx.client.clone();
+                    let password = self.export_password.clone();
+                    let display = DisplayPreferences {
+                        hide_balances: ctx.hide_balances,
+                        timestamp_style: util::timestamp_style(),
+                    };
+                    return Command::perform(
+                        async move { client.export_settings_qr(password, display).await },
+                        |res| match res {
+                            Ok(payload) => ConfigMessage::Exported(payload).into(),
+                            Err(e) => ConfigMessage::ErrorChanged(Some(e.to_string())).into(),
+                        },
+                    );
+                }
+                ConfigMessage::Exported(payload) => {
+                    self.loading = false;
+                    self.export_password.clear();
+                    self.export_qr_code = qr_code::State::new(payload).ok();
+                }
+                ConfigMessage::ImportPayloadChanged(payload) => self.import_payload = payload,
+                ConfigMessage::ImportPasswordChanged(password) => self.import_password = password,
+                ConfigMessage::Import => {
+                    self.loading = true;
+                    let client = ctx.client.clone();
+                    let payload = self.import_payload.clone();
+                    let password = self.import_password.clone();
+                    return Command::perform(
+                        async move { client.import_settings_qr(payload, password).await },
+                        |res| match res {
+                            Ok(display) => ConfigMessage::Imported(display).into(),
+                            Err(e) => ConfigMessage::ErrorChanged(Some(e.to_string())).into(),
+                        },
+                    );
+                }
+                ConfigMessage::Imported(display) => {
+                    if ctx.hide_balances != display.hide_balances {
+                        ctx.toggle_hide_balances();
+                    }
+                    util::set_timestamp_style(display.timestamp_style);
+                    self.import_payload.clear();
+                    self.import_password.clear();
+                    self.loading = false;
+                    self.loaded = false;
+                    return self.load(ctx);
+                }
             }
         };
 
@@ -150,12 +231,69 @@ impl State for ConfigState {
             .placeholder("Block Explorer")
             .view();
 
+        let drafts_expiration =
+            TextInput::with_label("Drafts expiration (days)", &self.drafts_expiration_days)
+                .on_input(|s| ConfigMessage::DraftsExpirationChanged(s).into())
+                .placeholder("7")
+                .view();
+
         let save_btn = Button::new()
             .text("Save")
             .on_press(ConfigMessage::Save.into())
             .loading(self.loading)
             .width(Length::Fill);
 
+        let export = match &self.export_qr_code {
+            Some(qr_code) => Column::new()
+                .push(Text::new("Scan with the other device").view())
+                .push(QRCode::new(qr_code).cell_size(3))
+                .align_items(Alignment::Center)
+                .spacing(10),
+            None => Column::new()
+                .push(
+                    TextInput::with_label("Export password", &self.export_password)
+                        .on_input(|s| ConfigMessage::ExportPasswordChanged(s).into())
+                        .placeholder("Password")
+                        .password()
+                        .view(),
+                )
+                .push(
+                    Button::new()
+                        .style(ButtonStyle::Bordered)
+                        .text("Export settings as QR")
+                        .on_press(ConfigMessage::Export.into())
+                        .loading(self.loading || self.export_password.is_empty())
+                        .width(Length::Fill)
+                        .view(),
+                )
+                .spacing(10),
+        };
+
+        let import = Column::new()
+            .push(
+                TextInput::with_label("Import settings", &self.import_payload)
+                    .on_input(|s| ConfigMessage::ImportPayloadChanged(s).into())
+                    .placeholder("Settings payload")
+                    .view(),
+            )
+            .push(
+                TextInput::new(&self.import_password)
+                    .on_input(|s| ConfigMessage::ImportPasswordChanged(s).into())
+                    .placeholder("Password")
+                    .password()
+                    .view(),
+            )
+            .push(
+                Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("Import")
+                    .on_press(ConfigMessage::Import.into())
+                    .loading(self.loading || self.import_payload.is_empty())
+                    .width(Length::Fill)
+                    .view(),
+            )
+            .spacing(10);
+
         let content = Column::new()
             .push(
                 Column::new()
@@ -167,12 +305,18 @@ impl State for ConfigState {
             .push(electrum_endpoint)
             .push(proxy)
             .push(block_explorer)
+            .push(drafts_expiration)
             .push(if let Some(error) = &self.error {
                 Row::new().push(Text::new(error).color(DARK_RED).view())
             } else {
                 Row::new()
             })
             .push(save_btn.view())
+            .push(Space::with_height(Length::Fixed(10.0)))
+            .push(rule::horizontal())
+            .push(export)
+            .push(rule::horizontal())
+            .push(import)
             .align_items(Alignment::Center)
             .spacing(10)
             .padding(20)
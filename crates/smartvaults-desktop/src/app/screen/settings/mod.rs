@@ -3,11 +3,13 @@
 
 use iced::widget::{Column, Row};
 use iced::{Command, Element, Length};
+use smartvaults_sdk::core::bitcoin::Network;
+use smartvaults_sdk::util::{self, PublicKeyStyle, TimestampStyle};
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
 use crate::component::{Button, ButtonStyle, Card, Modal, Text};
-use crate::theme::icon::{BROADCAST_PIN, KEY, NETWORK, SETTING, TRASH};
+use crate::theme::icon::{BROADCAST_PIN, CLOCK, KEY, LOCK, NETWORK, SETTING, TRASH};
 
 pub mod add_relay;
 pub mod change_password;
@@ -24,6 +26,9 @@ pub enum SettingsMessage {
     CloseModal,
     ClearCache,
     ForceFullTimechainSync,
+    TogglePublicKeyStyle,
+    ToggleTimestampStyle,
+    ToggleMainnetGuard,
 }
 
 #[derive(Debug, Default)]
@@ -61,6 +66,34 @@ impl State for SettingsState {
                         move |_| Message::View(Stage::Dashboard),
                     );
                 }
+                SettingsMessage::TogglePublicKeyStyle => {
+                    let style = match util::public_key_style() {
+                        PublicKeyStyle::Hex => PublicKeyStyle::Npub,
+                        PublicKeyStyle::Npub => PublicKeyStyle::Hex,
+                    };
+                    util::set_public_key_style(style);
+                }
+                SettingsMessage::ToggleTimestampStyle => {
+                    let style = match util::timestamp_style() {
+                        TimestampStyle::Local => TimestampStyle::Utc,
+                        TimestampStyle::Utc => TimestampStyle::Local,
+                    };
+                    util::set_timestamp_style(style);
+                }
+                SettingsMessage::ToggleMainnetGuard => {
+                    let client = ctx.client.clone();
+                    let enabled: bool = !ctx.mainnet_guard;
+                    return Command::perform(
+                        async move { client.set_mainnet_guard(enabled).await },
+                        move |res| match res {
+                            Ok(_) => Message::MainnetGuardChanged(enabled),
+                            Err(e) => {
+                                tracing::error!("Impossible to set mainnet guard: {e}");
+                                Message::Tick
+                            }
+                        },
+                    );
+                }
                 SettingsMessage::ForceFullTimechainSync => {
                     let client = ctx.client.clone();
                     return Command::perform(
@@ -75,7 +108,7 @@ impl State for SettingsState {
     }
 
     fn view(&self, ctx: &Context) -> Element<Message> {
-        let content = Column::new()
+        let mut content = Column::new()
             .push(
                 Button::new()
                     .text("Change password")
@@ -108,6 +141,38 @@ impl State for SettingsState {
                     .width(Length::Fill)
                     .view(),
             )
+            .push(
+                Button::new()
+                    .text(format!("Public keys format: {}", util::public_key_style()))
+                    .icon(KEY)
+                    .on_press(SettingsMessage::TogglePublicKeyStyle.into())
+                    .width(Length::Fill)
+                    .view(),
+            )
+            .push(
+                Button::new()
+                    .text(format!("Timestamps: {}", util::timestamp_style()))
+                    .icon(CLOCK)
+                    .on_press(SettingsMessage::ToggleTimestampStyle.into())
+                    .width(Length::Fill)
+                    .view(),
+            );
+
+        if ctx.client.network() == Network::Bitcoin {
+            content = content.push(
+                Button::new()
+                    .text(format!(
+                        "Mainnet guard: {}",
+                        if ctx.mainnet_guard { "on" } else { "off" }
+                    ))
+                    .icon(LOCK)
+                    .on_press(SettingsMessage::ToggleMainnetGuard.into())
+                    .width(Length::Fill)
+                    .view(),
+            );
+        }
+
+        let content = content
             .push(
                 Button::new()
                     .text("Rebroadcast all events")
@@ -4,14 +4,15 @@
 use std::time::Duration;
 
 use iced::widget::{Column, Row, Space};
-use iced::{time, Command, Element, Length, Subscription};
+use iced::{time, Alignment, Command, Element, Length, Subscription};
 use smartvaults_sdk::nostr::pool::RelayConnectionStats;
 use smartvaults_sdk::nostr::{RelayStatus, Url};
+use smartvaults_sdk::types::RelayHealth;
 use smartvaults_sdk::util::format;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
-use crate::component::Text;
+use crate::component::{Button, ButtonStyle, Text};
 use crate::theme::color::{GREEN, GREY, RED, YELLOW};
 
 #[derive(Debug, Clone)]
@@ -20,12 +21,15 @@ pub struct Relay {
     stats: RelayConnectionStats,
     latency: Option<Duration>,
     queue: usize,
+    health: Option<RelayHealth>,
 }
 
 #[derive(Debug, Clone)]
 pub enum RelayMessage {
     LoadRelay(Relay),
     RefreshRelays,
+    Ping,
+    PingResult(Result<Duration, String>),
     RemoveRelay(Url),
     ErrorChanged(Option<String>),
 }
@@ -36,6 +40,7 @@ pub struct RelayState {
     loaded: bool,
     url: Url,
     relay: Option<Relay>,
+    ping: Option<Duration>,
     error: Option<String>,
 }
 
@@ -46,6 +51,7 @@ impl RelayState {
             loaded: false,
             url,
             relay: None,
+            ping: None,
             error: None,
         }
     }
@@ -68,13 +74,19 @@ impl State for RelayState {
         let client = ctx.client.clone();
         Command::perform(
             async move {
-                let relay = client.relay(url).await?;
+                let relay = client.relay(url.clone()).await?;
                 let stats = relay.stats();
+                let health: Option<RelayHealth> = client
+                    .relay_status()
+                    .await
+                    .into_iter()
+                    .find(|h| h.url == url);
                 let relay = Relay {
                     status: relay.status().await,
                     latency: stats.latency().await,
                     stats,
                     queue: relay.queue(),
+                    health,
                 };
                 Ok::<Relay, Box<dyn std::error::Error>>(relay)
             },
@@ -116,6 +128,24 @@ impl State for RelayState {
                     self.loading = false;
                 }
                 RelayMessage::RefreshRelays => return self.load(ctx),
+                RelayMessage::Ping => {
+                    let url = self.url.clone();
+                    let client = ctx.client.clone();
+                    return Command::perform(
+                        async move { client.ping_relay(url).await.map_err(|e| e.to_string()) },
+                        |res| RelayMessage::PingResult(res).into(),
+                    );
+                }
+                RelayMessage::PingResult(res) => match res {
+                    Ok(rtt) => {
+                        self.ping = Some(rtt);
+                        self.error = None;
+                    }
+                    Err(e) => {
+                        self.ping = None;
+                        self.error = Some(e);
+                    }
+                },
             }
         }
 
@@ -130,6 +160,7 @@ impl State for RelayState {
             stats,
             latency,
             queue,
+            health,
         }) = &self.relay
         {
             let status = match status {
@@ -232,14 +263,96 @@ impl State for RelayState {
                             Column::new()
                                 .push(Text::new("Connected at").big().extra_light().view())
                                 .push(
-                                    Text::new(stats.connected_at().to_human_datetime())
+                                    Text::new(format::relative_datetime(stats.connected_at()))
                                         .big()
                                         .view(),
                                 )
                                 .spacing(10)
                                 .width(Length::FillPortion(2)),
                         ),
-                )
+                );
+
+            if let Some(health) = health {
+                content = content.push(
+                    Row::new()
+                        .push(
+                            Column::new()
+                                .push(Text::new("Events received").big().extra_light().view())
+                                .push(
+                                    Text::new(format::number(health.events_received))
+                                        .big()
+                                        .view(),
+                                )
+                                .spacing(10)
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            Column::new()
+                                .push(Text::new("Failed publishes").big().extra_light().view())
+                                .push(
+                                    Text::new(format::number(health.failed_publishes))
+                                        .color_maybe(if health.failed_publishes > 0 {
+                                            Some(RED)
+                                        } else {
+                                            None
+                                        })
+                                        .big()
+                                        .view(),
+                                )
+                                .spacing(10)
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            Column::new()
+                                .push(Text::new("Disconnected at").big().extra_light().view())
+                                .push(
+                                    Text::new(match health.disconnected_at {
+                                        Some(ts) => format::relative_datetime(ts),
+                                        None => String::from("-"),
+                                    })
+                                    .big()
+                                    .view(),
+                                )
+                                .spacing(10)
+                                .width(Length::Fill),
+                        )
+                        .spacing(10)
+                        .width(Length::Fill),
+                );
+            }
+
+            content = content.push(
+                Row::new()
+                    .push(
+                        Column::new()
+                            .push(Text::new("Ping").big().extra_light().view())
+                            .push(
+                                Text::new(match self.ping {
+                                    Some(rtt) => format!("{} ms", rtt.as_millis()),
+                                    None => String::from("-"),
+                                })
+                                .big()
+                                .view(),
+                            )
+                            .spacing(10)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new()
+                            .style(ButtonStyle::Bordered)
+                            .text("Ping")
+                            .on_press(RelayMessage::Ping.into())
+                            .width(Length::Fixed(100.0))
+                            .view(),
+                    )
+                    .align_items(Alignment::Center)
+                    .spacing(10)
+                    .width(Length::Fill),
+            );
+
+            if let Some(error) = &self.error {
+                content = content.push(Text::new(error).color(RED).view());
+            }
         }
 
         Dashboard::new()
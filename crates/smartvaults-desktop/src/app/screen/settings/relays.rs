@@ -7,6 +7,8 @@ use iced::alignment::Horizontal;
 use iced::widget::{Column, Row};
 use iced::{time, Alignment, Command, Element, Length, Subscription};
 use smartvaults_sdk::nostr::{RelayStatus, Url};
+use smartvaults_sdk::types::RelayHealth;
+use smartvaults_sdk::util::format;
 
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
@@ -14,16 +16,9 @@ use crate::component::{rule, Button, ButtonStyle, Circle, Text};
 use crate::theme::color::{GREEN, GREY, NEUTRAL, RED, YELLOW};
 use crate::theme::icon::{FULLSCREEN, PLUS, RELOAD, TRASH};
 
-#[derive(Debug, Clone)]
-pub struct Relay {
-    url: Url,
-    status: RelayStatus,
-    queue: usize,
-}
-
 #[derive(Debug, Clone)]
 pub enum RelaysMessage {
-    LoadRelays(Vec<Relay>),
+    LoadRelays(Vec<RelayHealth>),
     RefreshRelays,
     RemoveRelay(Url),
     ErrorChanged(Option<String>),
@@ -33,7 +28,7 @@ pub enum RelaysMessage {
 pub struct RelaysState {
     loading: bool,
     loaded: bool,
-    relays: Vec<Relay>,
+    relays: Vec<RelayHealth>,
     error: Option<String>,
 }
 
@@ -57,20 +52,9 @@ impl State for RelaysState {
     fn load(&mut self, ctx: &Context) -> Command<Message> {
         self.loading = true;
         let client = ctx.client.clone();
-        Command::perform(
-            async move {
-                let mut relays = Vec::new();
-                for (url, relay) in client.relays().await.into_iter() {
-                    relays.push(Relay {
-                        url,
-                        status: relay.status().await,
-                        queue: relay.queue(),
-                    });
-                }
-                relays
-            },
-            |r| RelaysMessage::LoadRelays(r).into(),
-        )
+        Command::perform(async move { client.relay_status().await }, |r| {
+            RelaysMessage::LoadRelays(r).into()
+        })
     }
 
     fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
@@ -123,6 +107,22 @@ impl State for RelaysState {
                                 .width(Length::Fixed(100.0))
                                 .view(),
                         )
+                        .push(
+                            Text::new("Events")
+                                .bold()
+                                .big()
+                                .horizontal_alignment(Horizontal::Center)
+                                .width(Length::Fixed(100.0))
+                                .view(),
+                        )
+                        .push(
+                            Text::new("Failed")
+                                .bold()
+                                .big()
+                                .horizontal_alignment(Horizontal::Center)
+                                .width(Length::Fixed(80.0))
+                                .view(),
+                        )
                         .push(
                             Text::new("Queue")
                                 .bold()
@@ -155,7 +155,15 @@ impl State for RelaysState {
                 )
                 .push(rule::horizontal_bold());
 
-            for Relay { url, status, queue } in self.relays.iter() {
+            for RelayHealth {
+                url,
+                status,
+                events_received,
+                failed_publishes,
+                queue,
+                ..
+            } in self.relays.iter()
+            {
                 let status = match status {
                     RelayStatus::Initialized | RelayStatus::Pending => Circle::new(7.0).color(GREY),
                     RelayStatus::Connecting => Circle::new(7.0).color(YELLOW),
@@ -173,6 +181,23 @@ impl State for RelaysState {
                             .align_items(Alignment::Center)
                             .width(Length::Fixed(100.0)),
                     )
+                    .push(
+                        Text::new(format::number(*events_received))
+                            .horizontal_alignment(Horizontal::Center)
+                            .width(Length::Fixed(100.0))
+                            .view(),
+                    )
+                    .push(
+                        Text::new(format::number(*failed_publishes))
+                            .color_maybe(if *failed_publishes > 0 {
+                                Some(RED)
+                            } else {
+                                None
+                            })
+                            .horizontal_alignment(Horizontal::Center)
+                            .width(Length::Fixed(80.0))
+                            .view(),
+                    )
                     .push(
                         Text::new(queue.to_string())
                             .horizontal_alignment(Horizontal::Center)
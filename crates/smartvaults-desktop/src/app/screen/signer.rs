@@ -7,19 +7,35 @@ use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::core::signer::Signer;
 use smartvaults_sdk::nostr::{EventId, Profile};
-use smartvaults_sdk::util;
+use smartvaults_sdk::{util, Error, SmartVaults};
 
-use crate::app::component::Dashboard;
+use crate::app::component::{Dashboard, MainnetGuard};
 use crate::app::{Context, Message, Stage, State};
-use crate::component::{rule, Button, ButtonStyle, Text};
+use crate::component::{rule, Button, ButtonStyle, Modal, Text, TextInput};
 use crate::theme::color::RED;
 use crate::theme::icon::TRASH;
 
+/// Text to type to confirm the deletion of a signer used by some vault
+const CONFIRMATION_TEXT: &str = "DELETE";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerAction {
+    Delete,
+    RevokeSharedSigner(EventId),
+}
+
 #[derive(Debug, Clone)]
 pub enum SignerMessage {
     LoadMySharedSigners(BTreeMap<EventId, Profile>),
     Delete,
     RevokeSharedSigner(EventId),
+    /// The action require a confirmation: the signer is used by these vaults
+    ConfirmationRequired(SignerAction, Vec<String>),
+    ConfirmationChanged(String),
+    Confirm,
+    CancelConfirmation,
+    MainnetConfirmationChanged(Option<String>),
+    Done(SignerAction),
     Reload,
     ErrorChanged(Option<String>),
 }
@@ -31,6 +47,11 @@ pub struct SignerState {
     signer_id: EventId,
     signer: Signer,
     my_shared_signers: BTreeMap<EventId, Profile>,
+    /// Pending action and names of the affected vaults
+    pending: Option<(SignerAction, Vec<String>)>,
+    confirmation: String,
+    /// Action waiting for the typed confirmation of the mainnet guard
+    mainnet_confirmation: Option<(SignerAction, String)>,
     error: Option<String>,
 }
 
@@ -42,9 +63,65 @@ impl SignerState {
             signer_id,
             signer,
             my_shared_signers: BTreeMap::new(),
+            pending: None,
+            confirmation: String::new(),
+            mainnet_confirmation: None,
             error: None,
         }
     }
+
+    /// Perform the action, after the confirmation of the mainnet guard
+    fn guard(&mut self, ctx: &Context, action: SignerAction) -> Command<Message> {
+        let confirmation: Option<String> = self
+            .mainnet_confirmation
+            .take()
+            .filter(|(a, ..)| *a == action)
+            .map(|(_, confirmation)| confirmation);
+        if ctx.mainnet_guard_pending(confirmation.as_deref()) {
+            self.mainnet_confirmation = Some((action, String::new()));
+            return Command::none();
+        }
+        self.perform(ctx, action, false)
+    }
+
+    fn perform(&mut self, ctx: &Context, action: SignerAction, force: bool) -> Command<Message> {
+        self.loading = true;
+        let client = ctx.client.clone();
+        let signer_id = self.signer_id;
+        Command::perform(
+            async move {
+                let res = match action {
+                    SignerAction::Delete => client.delete_signer_by_id(signer_id, force).await,
+                    SignerAction::RevokeSharedSigner(shared_signer_id) => {
+                        client.revoke_shared_signer(shared_signer_id, force).await
+                    }
+                };
+                match res {
+                    Ok(()) => Ok(None),
+                    Err(Error::SignerInUse(policies) | Error::SharedSignerInUse(policies)) => {
+                        Ok(Some(vault_names(&client, policies).await))
+                    }
+                    Err(e) => Err(e),
+                }
+            },
+            move |res| match res {
+                Ok(None) => SignerMessage::Done(action).into(),
+                Ok(Some(vaults)) => SignerMessage::ConfirmationRequired(action, vaults).into(),
+                Err(e) => SignerMessage::ErrorChanged(Some(e.to_string())).into(),
+            },
+        )
+    }
+}
+
+async fn vault_names(client: &SmartVaults, policies: Vec<EventId>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::with_capacity(policies.len());
+    for policy_id in policies.into_iter() {
+        match client.get_policy_by_id(policy_id).await {
+            Ok(policy) => names.push(policy.name()),
+            Err(_) => names.push(format!("#{}", util::cut_event_id(policy_id))),
+        }
+    }
+    names
 }
 
 impl State for SignerState {
@@ -84,27 +161,51 @@ impl State for SignerState {
                     self.error = error;
                 }
                 SignerMessage::Delete => {
-                    self.loading = true;
-                    let client = ctx.client.clone();
-                    let signer_id = self.signer_id;
-                    return Command::perform(
-                        async move { client.delete_signer_by_id(signer_id).await },
-                        |res| match res {
-                            Ok(_) => Message::View(Stage::Signers),
-                            Err(e) => SignerMessage::ErrorChanged(Some(e.to_string())).into(),
-                        },
-                    );
+                    return self.guard(ctx, SignerAction::Delete);
                 }
                 SignerMessage::RevokeSharedSigner(shared_signer_id) => {
-                    self.loading = true;
-                    let client = ctx.client.clone();
-                    return Command::perform(
-                        async move { client.revoke_shared_signer(shared_signer_id).await },
-                        |res| match res {
-                            Ok(_) => SignerMessage::Reload.into(),
-                            Err(e) => SignerMessage::ErrorChanged(Some(e.to_string())).into(),
-                        },
-                    );
+                    return self.guard(ctx, SignerAction::RevokeSharedSigner(shared_signer_id));
+                }
+                SignerMessage::ConfirmationRequired(action, vaults) => {
+                    self.loading = false;
+                    self.pending = Some((action, vaults));
+                    self.confirmation.clear();
+                }
+                SignerMessage::ConfirmationChanged(confirmation) => {
+                    self.confirmation = confirmation;
+                }
+                SignerMessage::Confirm => {
+                    if self.confirmation == CONFIRMATION_TEXT {
+                        if let Some((action, ..)) = self.pending.take() {
+                            self.confirmation.clear();
+                            return self.perform(ctx, action, true);
+                        }
+                    }
+                }
+                SignerMessage::CancelConfirmation => {
+                    self.pending = None;
+                    self.confirmation.clear();
+                }
+                SignerMessage::MainnetConfirmationChanged(confirmation) => {
+                    self.mainnet_confirmation =
+                        match (self.mainnet_confirmation.take(), confirmation) {
+                            (Some((action, ..)), Some(confirmation)) => {
+                                Some((action, confirmation))
+                            }
+                            _ => None,
+                        };
+                }
+                SignerMessage::Done(action) => {
+                    ctx.cache.invalidate_all();
+                    return match action {
+                        SignerAction::Delete => {
+                            Command::perform(async {}, |_| Message::View(Stage::Signers))
+                        }
+                        SignerAction::RevokeSharedSigner(..) => {
+                            self.loading = false;
+                            self.load(ctx)
+                        }
+                    };
                 }
                 SignerMessage::Reload => return self.load(ctx),
             }
@@ -145,6 +246,46 @@ impl State for SignerState {
                 )
                 .push(Space::with_height(20.0));
 
+            if let Some((action, vaults)) = &self.pending {
+                let label: String = format!("Type {CONFIRMATION_TEXT} to confirm");
+                let mut confirm_btn = Button::new()
+                    .style(ButtonStyle::Danger)
+                    .text("Confirm")
+                    .loading(self.loading);
+                if self.confirmation == CONFIRMATION_TEXT {
+                    confirm_btn = confirm_btn.on_press(SignerMessage::Confirm.into());
+                }
+                let what: &str = match action {
+                    SignerAction::Delete => "This signer is used by",
+                    SignerAction::RevokeSharedSigner(..) => "The contact uses this signer in",
+                };
+                content = content
+                    .push(
+                        Text::new(format!("{what} these vaults: {}", vaults.join(", ")))
+                            .color(RED)
+                            .view(),
+                    )
+                    .push(
+                        TextInput::with_label(&label, &self.confirmation)
+                            .on_input(|s| SignerMessage::ConfirmationChanged(s).into())
+                            .placeholder(CONFIRMATION_TEXT)
+                            .view(),
+                    )
+                    .push(
+                        Row::new()
+                            .push(confirm_btn.view())
+                            .push(
+                                Button::new()
+                                    .style(ButtonStyle::Bordered)
+                                    .text("Cancel")
+                                    .on_press(SignerMessage::CancelConfirmation.into())
+                                    .view(),
+                            )
+                            .spacing(10),
+                    )
+                    .push(Space::with_height(20.0));
+            }
+
             if let Some(error) = &self.error {
                 content = content.push(Text::new(error).color(RED).view());
             };
@@ -196,9 +337,31 @@ impl State for SignerState {
             }
         }
 
-        Dashboard::new()
+        let dashboard = Dashboard::new()
             .loaded(self.loaded)
-            .view(ctx, content, false, false)
+            .view(ctx, content, false, false);
+
+        if let Some((action, confirmation)) = &self.mainnet_confirmation {
+            let (text, message) = match action {
+                SignerAction::Delete => ("delete this signer", SignerMessage::Delete),
+                SignerAction::RevokeSharedSigner(shared_signer_id) => (
+                    "revoke the shared signer",
+                    SignerMessage::RevokeSharedSigner(*shared_signer_id),
+                ),
+            };
+            Modal::new(
+                dashboard,
+                MainnetGuard::new(text, ctx.client.network(), confirmation)
+                    .on_input(|s| SignerMessage::MainnetConfirmationChanged(Some(s)).into())
+                    .on_confirm(message.into())
+                    .on_cancel(SignerMessage::MainnetConfirmationChanged(None).into())
+                    .view(),
+            )
+            .on_blur(SignerMessage::MainnetConfirmationChanged(None).into())
+            .into()
+        } else {
+            dashboard
+        }
     }
 }
 
@@ -9,15 +9,19 @@ use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::core::bdk::descriptor::policy::SatisfiableItem;
 use smartvaults_sdk::core::bitcoin::address::NetworkUnchecked;
 use smartvaults_sdk::core::bitcoin::{Address, OutPoint};
-use smartvaults_sdk::core::{Amount, FeeRate, SelectableCondition};
+use smartvaults_sdk::core::{
+    Amount, CoinSelection, CompletedProposal, FeeRate, SelectableCondition,
+};
 use smartvaults_sdk::nostr::EventId;
-use smartvaults_sdk::types::{GetPolicy, GetProposal, GetUtxo};
+use smartvaults_sdk::types::{Draft, GetPolicy, GetProposal, GetUtxo, SpendDraft};
 use smartvaults_sdk::util::format;
 
-use crate::app::component::{Dashboard, FeeSelector, PolicyPickList, PolicyTree, UtxoSelector};
+use crate::app::component::{
+    Dashboard, FeeSelector, MainnetGuard, PolicyPickList, PolicyTree, UtxoSelector,
+};
 use crate::app::{Context, Message, Stage, State};
 use crate::component::{
-    rule, Amount as AmountComponent, Button, ButtonStyle, NumericInput, Text, TextInput,
+    rule, Amount as AmountComponent, Button, ButtonStyle, Modal, NumericInput, Text, TextInput,
 };
 use crate::theme::color::{DARK_RED, RED};
 
@@ -49,6 +53,7 @@ pub enum SpendMessage {
     AddressChanged(String),
     AmountChanged(Option<u64>),
     SendAllBtnPressed,
+    SetSubtractFee(bool),
     DescriptionChanged(String),
     FeeRateChanged(FeeRate),
     PolicyLoaded(
@@ -58,10 +63,18 @@ pub enum SpendMessage {
     ),
     SelectedUtxosChanged(HashSet<OutPoint>),
     SetSkipFrozenUtxos(bool),
+    CoinSelectionChanged(CoinSelection),
     EstimatedTxVSize(Option<usize>),
     ToggleCondition(String, usize),
     ErrorChanged(Option<String>),
     SetInternalStage(InternalStage),
+    SetAutoComplete(bool),
+    PasswordChanged(String),
+    DraftLoaded(Option<SpendDraft>),
+    ResumeDraft,
+    DiscardDraft,
+    DraftSaved,
+    MainnetConfirmationChanged(Option<String>),
     SendProposal,
 }
 
@@ -72,18 +85,27 @@ pub struct SpendState {
     to_address: String,
     amount: Option<u64>,
     send_all: bool,
+    subtract_fee: bool,
     description: String,
     fee_rate: FeeRate,
     utxos: Vec<GetUtxo>,
     selected_utxos: HashSet<OutPoint>,
     skip_frozen_utxos: bool,
+    /// If not set, the default one of the policy is used
+    coin_selection: Option<CoinSelection>,
     policy_path: Option<BTreeMap<String, Vec<usize>>>,
     satisfiable_item: Option<SatisfiableItem>,
     selectable_conditions: Option<Vec<SelectableCondition>>,
     estimated_tx_vsize: Option<usize>,
+    auto_complete: bool,
+    password: String,
     stage: InternalStage,
+    /// Draft waiting to be resumed or discarded
+    draft: Option<SpendDraft>,
     loading: bool,
     loaded: bool,
+    /// Typed confirmation of the mainnet guard
+    mainnet_confirmation: Option<String>,
     error: Option<String>,
 }
 
@@ -95,18 +117,24 @@ impl SpendState {
             to_address: String::new(),
             amount: None,
             send_all: false,
+            subtract_fee: false,
             description: String::new(),
             fee_rate: FeeRate::default(),
             utxos: Vec::new(),
             selected_utxos: HashSet::new(),
             skip_frozen_utxos: false,
+            coin_selection: None,
             policy_path: None,
             satisfiable_item: None,
             selectable_conditions: None,
             estimated_tx_vsize: None,
+            auto_complete: false,
+            password: String::new(),
             stage: InternalStage::default(),
+            draft: None,
             loading: false,
             loaded: false,
+            mainnet_confirmation: None,
             error: None,
         }
     }
@@ -122,6 +150,7 @@ impl SpendState {
         let selected_utxos: Vec<OutPoint> = self.selected_utxos.iter().cloned().collect();
         let policy_path = self.policy_path.clone();
         let skip_frozen_utxos: bool = self.skip_frozen_utxos;
+        let coin_selection: Option<CoinSelection> = self.coin_selection;
         Command::perform(
             async move {
                 client
@@ -136,6 +165,7 @@ impl SpendState {
                         },
                         policy_path,
                         skip_frozen_utxos,
+                        coin_selection,
                     )
                     .await
                     .ok()?
@@ -144,6 +174,51 @@ impl SpendState {
         )
     }
 
+    /// The password is never included
+    fn as_draft(&self) -> SpendDraft {
+        SpendDraft {
+            to_address: self.to_address.clone(),
+            amount: self.amount,
+            send_all: self.send_all,
+            subtract_fee: self.subtract_fee,
+            description: self.description.clone(),
+            selected_utxos: self.selected_utxos.iter().copied().collect(),
+            skip_frozen_utxos: self.skip_frozen_utxos,
+        }
+    }
+
+    /// Save the form as draft of the selected policy (skipped while a previous draft is pending)
+    fn save_draft(&self, ctx: &Context) -> Command<Message> {
+        if self.draft.is_some() {
+            return Command::none();
+        }
+
+        match &self.policy {
+            Some(policy) => {
+                let client = ctx.client.clone();
+                let policy_id = policy.policy_id;
+                let draft: SpendDraft = self.as_draft();
+                Command::perform(
+                    async move {
+                        if let Err(e) = client.save_draft(Some(policy_id), &draft).await {
+                            tracing::error!("Impossible to save spend draft: {e}");
+                        }
+                    },
+                    |_| SpendMessage::DraftSaved.into(),
+                )
+            }
+            None => Command::none(),
+        }
+    }
+
+    fn custom_amount(&self, amount: u64) -> Amount {
+        if self.subtract_fee {
+            Amount::CustomSubtractFee(amount)
+        } else {
+            Amount::Custom(amount)
+        }
+    }
+
     fn estimate_tx_vsize(&self, ctx: &mut Context) -> Command<Message> {
         match &self.policy {
             Some(pp) => match Address::from_str(&self.to_address) {
@@ -156,7 +231,7 @@ impl SpendState {
                                 ctx,
                                 pp.policy_id,
                                 address,
-                                Amount::Custom(amount),
+                                self.custom_amount(amount),
                             ),
                             None => Command::none(),
                         }
@@ -175,7 +250,9 @@ impl SpendState {
         to_address: Address<NetworkUnchecked>,
         amount: Amount,
     ) -> Command<Message> {
+        self.mainnet_confirmation = None;
         self.loading = true;
+        ctx.cache.invalidate_all();
 
         let client = ctx.client.clone();
         let description = self.description.clone();
@@ -183,6 +260,48 @@ impl SpendState {
         let selected_utxos: Vec<OutPoint> = self.selected_utxos.iter().cloned().collect();
         let policy_path = self.policy_path.clone();
         let skip_frozen_utxos: bool = self.skip_frozen_utxos;
+        let coin_selection: Option<CoinSelection> = self.coin_selection;
+
+        if self.auto_complete {
+            let password: String = self.password.clone();
+            self.password.clear();
+            return Command::perform(
+                async move {
+                    let completed_proposal = client
+                        .spend_with_auto_complete(
+                            password,
+                            policy_id,
+                            to_address,
+                            amount,
+                            description,
+                            fee_rate,
+                            if selected_utxos.is_empty() {
+                                None
+                            } else {
+                                Some(selected_utxos)
+                            },
+                            policy_path,
+                            skip_frozen_utxos,
+                            coin_selection,
+                        )
+                        .await?;
+                    if let Err(e) = client.discard_draft::<SpendDraft>(Some(policy_id)).await {
+                        tracing::error!("Impossible to discard spend draft: {e}");
+                    }
+                    Ok::<CompletedProposal, Box<dyn std::error::Error>>(completed_proposal)
+                },
+                move |res| match res {
+                    Ok(CompletedProposal::Spending { tx, .. }) => {
+                        Message::View(Stage::Transaction {
+                            policy_id,
+                            txid: tx.txid(),
+                        })
+                    }
+                    Ok(_) => Message::View(Stage::Dashboard),
+                    Err(e) => SpendMessage::ErrorChanged(Some(e.to_string())).into(),
+                },
+            );
+        }
 
         Command::perform(
             async move {
@@ -200,8 +319,12 @@ impl SpendState {
                         },
                         policy_path,
                         skip_frozen_utxos,
+                        coin_selection,
                     )
                     .await?;
+                if let Err(e) = client.discard_draft::<SpendDraft>(Some(policy_id)).await {
+                    tracing::error!("Impossible to discard spend draft: {e}");
+                }
                 Ok::<EventId, Box<dyn std::error::Error>>(proposal_id)
             },
             |res| match res {
@@ -263,7 +386,20 @@ impl State for SpendState {
                     let client = ctx.client.clone();
                     if let Some(policy) = self.policy.as_ref() {
                         let policy = policy.policy.clone();
-                        return Command::perform(
+                        let draft = {
+                            let client = client.clone();
+                            Command::perform(
+                                async move {
+                                    client
+                                        .get_draft::<SpendDraft>(Some(policy_id))
+                                        .await
+                                        .ok()
+                                        .flatten()
+                                },
+                                |draft| SpendMessage::DraftLoaded(draft).into(),
+                            )
+                        };
+                        let load = Command::perform(
                             async move {
                                 let utxos = client.get_utxos(policy_id).await?;
                                 let item = policy.satisfiable_item()?.clone();
@@ -287,6 +423,7 @@ impl State for SpendState {
                                 .into(),
                             },
                         );
+                        return Command::batch([load, draft]);
                     } else {
                         self.error = Some(String::from("Select a policy"));
                     }
@@ -299,10 +436,14 @@ impl State for SpendState {
                 }
                 SpendMessage::SelectedUtxosChanged(s) => {
                     self.selected_utxos = s;
-                    return self.estimate_tx_vsize(ctx);
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
                 }
                 SpendMessage::SetSkipFrozenUtxos(val) => {
                     self.skip_frozen_utxos = val;
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
+                }
+                SpendMessage::CoinSelectionChanged(coin_selection) => {
+                    self.coin_selection = Some(coin_selection);
                     return self.estimate_tx_vsize(ctx);
                 }
                 SpendMessage::ToggleCondition(id, index) => match self.policy_path.as_mut() {
@@ -330,19 +471,64 @@ impl State for SpendState {
                 },
                 SpendMessage::AddressChanged(value) => {
                     self.to_address = value;
-                    return self.estimate_tx_vsize(ctx);
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
                 }
                 SpendMessage::AmountChanged(value) => {
                     self.amount = value;
-                    return self.estimate_tx_vsize(ctx);
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
                 }
                 SpendMessage::SendAllBtnPressed => {
                     self.send_all = !self.send_all;
-                    return self.estimate_tx_vsize(ctx);
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
+                }
+                SpendMessage::SetSubtractFee(value) => {
+                    self.subtract_fee = value;
+                    return Command::batch([self.estimate_tx_vsize(ctx), self.save_draft(ctx)]);
                 }
-                SpendMessage::DescriptionChanged(value) => self.description = value,
+                SpendMessage::DescriptionChanged(value) => {
+                    self.description = value;
+                    return self.save_draft(ctx);
+                }
+                SpendMessage::DraftLoaded(draft) => {
+                    // Don't prompt if the form was already filled
+                    if self.as_draft().is_empty() {
+                        self.draft = draft;
+                    }
+                }
+                SpendMessage::ResumeDraft => {
+                    if let Some(draft) = self.draft.take() {
+                        self.to_address = draft.to_address;
+                        self.amount = draft.amount;
+                        self.send_all = draft.send_all;
+                        self.subtract_fee = draft.subtract_fee;
+                        self.description = draft.description;
+                        self.selected_utxos = draft.selected_utxos.into_iter().collect();
+                        self.skip_frozen_utxos = draft.skip_frozen_utxos;
+                        return self.estimate_tx_vsize(ctx);
+                    }
+                }
+                SpendMessage::DiscardDraft => {
+                    self.draft = None;
+                    if let Some(policy) = &self.policy {
+                        let client = ctx.client.clone();
+                        let policy_id = policy.policy_id;
+                        return Command::perform(
+                            async move {
+                                if let Err(e) =
+                                    client.discard_draft::<SpendDraft>(Some(policy_id)).await
+                                {
+                                    tracing::error!("Impossible to discard spend draft: {e}");
+                                }
+                            },
+                            |_| SpendMessage::DraftSaved.into(),
+                        );
+                    }
+                }
+                SpendMessage::DraftSaved => (),
                 SpendMessage::FeeRateChanged(fee_rate) => self.fee_rate = fee_rate,
                 SpendMessage::EstimatedTxVSize(vsize) => self.estimated_tx_vsize = vsize,
+                SpendMessage::SetAutoComplete(value) => self.auto_complete = value,
+                SpendMessage::PasswordChanged(password) => self.password = password,
                 SpendMessage::ErrorChanged(error) => {
                     self.loading = false;
                     self.error = error;
@@ -370,6 +556,14 @@ impl State for SpendState {
                         None => self.error = Some(String::from("You must select a policy")),
                     },
                 },
+                SpendMessage::MainnetConfirmationChanged(confirmation) => {
+                    self.mainnet_confirmation = confirmation
+                }
+                SpendMessage::SendProposal
+                    if ctx.mainnet_guard_pending(self.mainnet_confirmation.as_deref()) =>
+                {
+                    self.mainnet_confirmation = Some(String::new());
+                }
                 SpendMessage::SendProposal => match &self.policy {
                     Some(policy) => {
                         let policy_id = policy.policy_id;
@@ -380,12 +574,8 @@ impl State for SpendState {
                                 } else {
                                     match self.amount {
                                         Some(amount) => {
-                                            return self.spend(
-                                                ctx,
-                                                policy_id,
-                                                to_address,
-                                                Amount::Custom(amount),
-                                            )
+                                            let amount: Amount = self.custom_amount(amount);
+                                            return self.spend(ctx, policy_id, to_address, amount);
                                         }
                                         None => self.error = Some(String::from("Invalid amount")),
                                     };
@@ -405,7 +595,9 @@ impl State for SpendState {
     fn view(&self, ctx: &Context) -> Element<Message> {
         let mut content = Column::new();
 
-        if self.loaded {
+        if self.loaded && self.draft.is_some() {
+            content = self.view_resume_draft();
+        } else if self.loaded {
             content = match self.stage {
                 InternalStage::Build(stage) => self.view_build_tx(ctx, stage),
                 InternalStage::SelectPolicyPath => self.view_policy_tree(),
@@ -422,9 +614,24 @@ impl State for SpendState {
         .width(Length::Fill)
         .center_x();
 
-        Dashboard::new()
+        let dashboard = Dashboard::new()
             .loaded(self.loaded)
-            .view(ctx, content, true, true)
+            .view(ctx, content, true, true);
+
+        if let Some(confirmation) = &self.mainnet_confirmation {
+            Modal::new(
+                dashboard,
+                MainnetGuard::new("spend", ctx.client.network(), confirmation)
+                    .on_input(|s| SpendMessage::MainnetConfirmationChanged(Some(s)).into())
+                    .on_confirm(SpendMessage::SendProposal.into())
+                    .on_cancel(SpendMessage::MainnetConfirmationChanged(None).into())
+                    .view(),
+            )
+            .on_blur(SpendMessage::MainnetConfirmationChanged(None).into())
+            .into()
+        } else {
+            dashboard
+        }
     }
 }
 
@@ -519,6 +726,36 @@ impl SpendState {
             .max_width(850.0)
     }
 
+    fn view_resume_draft<'a>(&self) -> Column<'a, Message> {
+        Column::new()
+            .push(Text::new("Resume draft?").big().bold().view())
+            .push(
+                Text::new("A spending proposal for this policy was left unfinished")
+                    .extra_light()
+                    .view(),
+            )
+            .push(Space::with_height(Length::Fixed(15.0)))
+            .push(
+                Button::new()
+                    .text("Resume")
+                    .on_press(SpendMessage::ResumeDraft.into())
+                    .width(Length::Fill)
+                    .view(),
+            )
+            .push(
+                Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("Discard")
+                    .on_press(SpendMessage::DiscardDraft.into())
+                    .width(Length::Fill)
+                    .view(),
+            )
+            .align_items(Alignment::Center)
+            .spacing(10)
+            .padding(20)
+            .max_width(400)
+    }
+
     fn view_details<'a>(&self, ctx: &Context) -> Column<'a, Message> {
         let policy_pick_list = Column::new()
             .push(Text::new("Policy").view())
@@ -566,21 +803,28 @@ impl SpendState {
                 .button(send_all_btn)
                 .view()
         } else {
-            Column::new().push(
-                Row::new()
-                    .push(
-                        Column::new()
-                            .push(
-                                NumericInput::new("Amount (sat)", self.amount)
-                                    .on_input(|s| SpendMessage::AmountChanged(s).into())
-                                    .placeholder("Amount"),
-                            )
-                            .width(Length::Fill),
-                    )
-                    .push(send_all_btn)
-                    .align_items(Alignment::End)
-                    .spacing(5),
-            )
+            Column::new()
+                .push(
+                    Row::new()
+                        .push(
+                            Column::new()
+                                .push(
+                                    NumericInput::new("Amount (sat)", self.amount)
+                                        .on_input(|s| SpendMessage::AmountChanged(s).into())
+                                        .placeholder("Amount"),
+                                )
+                                .width(Length::Fill),
+                        )
+                        .push(send_all_btn)
+                        .align_items(Alignment::End)
+                        .spacing(5),
+                )
+                .push(Checkbox::new(
+                    "Subtract fee from amount",
+                    self.subtract_fee,
+                    |val| SpendMessage::SetSubtractFee(val).into(),
+                ))
+                .spacing(5)
         };
 
         let your_balance = if let Some(policy) = &self.policy {
@@ -637,6 +881,19 @@ impl SpendState {
                 self.skip_frozen_utxos,
                 |val| SpendMessage::SetSkipFrozenUtxos(val).into(),
             ))
+            .push(
+                Column::new()
+                    .push(Text::new("Coin selection").view())
+                    .push(
+                        PickList::new(CoinSelection::list(), self.coin_selection, |c| {
+                            SpendMessage::CoinSelectionChanged(c).into()
+                        })
+                        .width(Length::Fixed(250.0))
+                        .padding(10)
+                        .placeholder("Policy default"),
+                    )
+                    .spacing(5),
+            )
             .spacing(10)
     }
 
@@ -755,6 +1012,11 @@ impl SpendState {
                     .bold()
                     .view()
             })
+            .push(if self.subtract_fee && !self.send_all {
+                Row::new().push(Text::new("Fee subtracted from amount").small().view())
+            } else {
+                Row::new()
+            })
             .spacing(5)
             .width(Length::Fill);
 
@@ -774,6 +1036,20 @@ impl SpendState {
             .spacing(5)
             .width(Length::Fill);
 
+        let coin_selection = Column::new()
+            .push(Row::new().push(Text::new("Coin selection").bold().view()))
+            .push(
+                Row::new().push(
+                    Text::new(match self.coin_selection {
+                        Some(coin_selection) => coin_selection.to_string(),
+                        None => String::from("Policy default"),
+                    })
+                    .view(),
+                ),
+            )
+            .spacing(5)
+            .width(Length::Fill);
+
         let error = if let Some(error) = &self.error {
             Row::new().push(Text::new(error).color(DARK_RED).view())
         } else {
@@ -788,8 +1064,42 @@ impl SpendState {
             }
         };
 
+        let auto_complete = Column::new()
+            .push(Checkbox::new(
+                "Approve and finalize (transfer to another policy)",
+                self.auto_complete,
+                |val| SpendMessage::SetAutoComplete(val).into(),
+            ))
+            .push(if self.auto_complete {
+                Column::new()
+                    .push(
+                        TextInput::with_label("Password", &self.password)
+                            .password()
+                            .placeholder("Password")
+                            .on_input(|p| SpendMessage::PasswordChanged(p).into())
+                            .view(),
+                    )
+                    .push(
+                        Text::new(
+                            "Available only if your signers can satisfy the policy on their own",
+                        )
+                        .extra_light()
+                        .small()
+                        .view(),
+                    )
+                    .spacing(5)
+            } else {
+                Column::new()
+            })
+            .spacing(5)
+            .width(Length::Fill);
+
         let send_proposal_btn = Button::new()
-            .text("Send proposal")
+            .text(if self.auto_complete {
+                "Send"
+            } else {
+                "Send proposal"
+            })
             .width(Length::Fill)
             .on_press(SpendMessage::SendProposal.into())
             .loading(self.loading)
@@ -810,6 +1120,8 @@ impl SpendState {
             .push(amount)
             .push(description)
             .push(priority)
+            .push(coin_selection)
+            .push(auto_complete)
             .push(error)
             .push(Space::with_height(Length::Fixed(15.0)))
             .push(send_proposal_btn)
@@ -12,6 +12,7 @@ use smartvaults_sdk::util::{self, format};
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
 use crate::component::{rule, Amount, AmountSign, Text};
+use crate::theme::color::DARK_RED;
 
 #[derive(Debug, Clone)]
 pub enum TransactionMessage {
@@ -54,7 +55,7 @@ impl State for TransactionState {
             async move { client.get_tx(policy_id, txid).await.ok() },
             |res| match res {
                 Some(tx) => TransactionMessage::LoadTx(Box::new(tx)).into(),
-                None => Message::View(Stage::Activity),
+                None => Message::View(Stage::Activity(None)),
             },
         )
     }
@@ -83,7 +84,14 @@ impl State for TransactionState {
     fn view(&self, ctx: &Context) -> Element<Message> {
         let mut content = Column::new().spacing(20).padding(20);
 
-        if let Some(GetTransaction { tx, label, .. }) = &self.tx {
+        if let Some(GetTransaction {
+            tx,
+            label,
+            screening,
+            unexpected,
+            ..
+        }) = &self.tx
+        {
             let (total, positive): (u64, bool) = {
                 let received: i64 = tx.received as i64;
                 let sent: i64 = tx.sent as i64;
@@ -166,7 +174,7 @@ impl State for TransactionState {
                     let confirmations: u32 = ctx.client.block_height().saturating_sub(height) + 1;
                     (
                         format::number(height as u64),
-                        Timestamp::from(time).to_human_datetime(),
+                        format::datetime(Timestamp::from(time)),
                         format::number(confirmations as u64),
                     )
                 }
@@ -177,8 +185,26 @@ impl State for TransactionState {
                 ),
             };
 
+            content = content.push(Text::new(title).size(40).bold().view());
+
+            if *unexpected {
+                content = content.push(
+                    Text::new("Unexpected outgoing transaction: no matching completed proposal")
+                        .color(DARK_RED)
+                        .bold()
+                        .view(),
+                );
+            }
+
+            if screening.is_flagged() {
+                content = content.push(
+                    Text::new(format!("Screening: transaction {screening}"))
+                        .color(DARK_RED)
+                        .view(),
+                );
+            }
+
             content = content
-                .push(Text::new(title).size(40).bold().view())
                 .push(Space::with_height(Length::Fixed(10.0)))
                 .push(
                     Row::new()
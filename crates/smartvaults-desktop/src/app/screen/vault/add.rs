@@ -1,11 +1,12 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::nostr::{Profile, PublicKey};
+use smartvaults_sdk::types::{PolicyDraft, PolicyParticipant};
 use smartvaults_sdk::util;
 
 use crate::app::component::Dashboard;
@@ -19,9 +20,15 @@ pub enum AddVaultMessage {
     NameChanged(String),
     DescriptionChanged(String),
     DescriptorChanged(String),
-    Load(Box<Profile>, BTreeSet<Profile>),
+    Load(Box<Profile>, BTreeSet<Profile>, Option<PolicyDraft>),
+    ResumeDraft,
+    DiscardDraft,
+    DraftSaved,
     AddPublicKey(PublicKey),
     RemovePublicKey(PublicKey),
+    RawPublicKeyChanged(String),
+    AddRawPublicKey,
+    ParticipantResolved(Box<PolicyParticipant>),
     SelectPublicKeys(bool),
     ErrorChanged(Option<String>),
     SavePolicy,
@@ -35,9 +42,13 @@ pub struct AddVaultState {
     profile: Option<Profile>,
     contacts: BTreeSet<Profile>,
     public_keys: HashSet<PublicKey>,
+    participants: HashMap<PublicKey, PolicyParticipant>,
+    raw_public_key: String,
     loading: bool,
     loaded: bool,
     selecting: bool,
+    /// Draft waiting to be resumed or discarded
+    draft: Option<PolicyDraft>,
     error: Option<String>,
 }
 
@@ -45,6 +56,33 @@ impl AddVaultState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn as_draft(&self) -> PolicyDraft {
+        PolicyDraft {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            descriptor: self.descriptor.clone(),
+            public_keys: self.public_keys.iter().copied().collect(),
+        }
+    }
+
+    /// Save the form as draft (skipped while a previous draft is pending)
+    fn save_draft(&self, ctx: &Context) -> Command<Message> {
+        if self.draft.is_some() {
+            return Command::none();
+        }
+
+        let client = ctx.client.clone();
+        let draft: PolicyDraft = self.as_draft();
+        Command::perform(
+            async move {
+                if let Err(e) = client.save_draft(None, &draft).await {
+                    tracing::error!("Impossible to save vault draft: {e}");
+                }
+            },
+            |_| AddVaultMessage::DraftSaved.into(),
+        )
+    }
 }
 
 impl State for AddVaultState {
@@ -64,9 +102,12 @@ impl State for AddVaultState {
                 let profile = client.get_profile().await.unwrap();
                 let mut contacts = client.get_contacts().await.unwrap();
                 contacts.insert(profile.clone());
-                (profile, contacts)
+                let draft = client.get_draft::<PolicyDraft>(None).await.ok().flatten();
+                (profile, contacts, draft)
+            },
+            |(profile, contacts, draft)| {
+                AddVaultMessage::Load(Box::new(profile), contacts, draft).into()
             },
-            |(profile, contacts)| AddVaultMessage::Load(Box::new(profile), contacts).into(),
         )
     }
 
@@ -77,24 +118,88 @@ impl State for AddVaultState {
 
         if let Message::AddPolicy(msg) = message {
             match msg {
-                AddVaultMessage::NameChanged(name) => self.name = name,
-                AddVaultMessage::DescriptionChanged(desc) => self.description = desc,
-                AddVaultMessage::DescriptorChanged(desc) => self.descriptor = desc,
-                AddVaultMessage::Load(profile, contacts) => {
+                AddVaultMessage::NameChanged(name) => {
+                    self.name = name;
+                    return self.save_draft(ctx);
+                }
+                AddVaultMessage::DescriptionChanged(desc) => {
+                    self.description = desc;
+                    return self.save_draft(ctx);
+                }
+                AddVaultMessage::DescriptorChanged(desc) => {
+                    self.descriptor = desc;
+                    return self.save_draft(ctx);
+                }
+                AddVaultMessage::Load(profile, contacts, draft) => {
                     self.profile = Some(*profile);
                     self.contacts = contacts;
+                    self.draft = draft;
                     self.loading = false;
                     self.loaded = true;
                 }
+                AddVaultMessage::ResumeDraft => {
+                    if let Some(draft) = self.draft.take() {
+                        self.name = draft.name;
+                        self.description = draft.description;
+                        self.descriptor = draft.descriptor;
+                        self.public_keys = draft.public_keys.into_iter().collect();
+                        let public_keys: Vec<Command<Message>> = self
+                            .public_keys
+                            .iter()
+                            .map(|public_key| resolve_participant(ctx, *public_key))
+                            .collect();
+                        return Command::batch(public_keys);
+                    }
+                }
+                AddVaultMessage::DiscardDraft => {
+                    self.draft = None;
+                    let client = ctx.client.clone();
+                    return Command::perform(
+                        async move {
+                            if let Err(e) = client.discard_draft::<PolicyDraft>(None).await {
+                                tracing::error!("Impossible to discard vault draft: {e}");
+                            }
+                        },
+                        |_| AddVaultMessage::DraftSaved.into(),
+                    );
+                }
+                AddVaultMessage::DraftSaved => (),
                 AddVaultMessage::SelectPublicKeys(value) => self.selecting = value,
                 AddVaultMessage::AddPublicKey(public_key) => {
-                    self.public_keys.insert(public_key);
+                    if self.public_keys.insert(public_key) {
+                        return Command::batch([
+                            resolve_participant(ctx, public_key),
+                            self.save_draft(ctx),
+                        ]);
+                    }
                 }
                 AddVaultMessage::RemovePublicKey(public_key) => {
                     self.public_keys.remove(&public_key);
+                    return self.save_draft(ctx);
+                }
+                AddVaultMessage::RawPublicKeyChanged(raw) => self.raw_public_key = raw,
+                AddVaultMessage::AddRawPublicKey => {
+                    match util::parse_public_key(&self.raw_public_key) {
+                        Ok(public_key) => {
+                            self.raw_public_key.clear();
+                            self.error = None;
+                            if self.public_keys.insert(public_key) {
+                                return Command::batch([
+                                    resolve_participant(ctx, public_key),
+                                    self.save_draft(ctx),
+                                ]);
+                            }
+                        }
+                        Err(e) => self.error = Some(e.to_string()),
+                    }
+                }
+                AddVaultMessage::ParticipantResolved(participant) => {
+                    self.participants
+                        .insert(participant.public_key(), *participant);
                 }
                 AddVaultMessage::ErrorChanged(error) => self.error = error,
                 AddVaultMessage::SavePolicy => {
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     let name = self.name.clone();
                     let description = self.description.clone();
@@ -102,9 +207,15 @@ impl State for AddVaultState {
                     let public_keys: Vec<PublicKey> = self.public_keys.iter().copied().collect();
                     return Command::perform(
                         async move {
-                            client
+                            let res = client
                                 .save_policy(name, description, descriptor, public_keys)
-                                .await
+                                .await;
+                            if res.is_ok() {
+                                if let Err(e) = client.discard_draft::<PolicyDraft>(None).await {
+                                    tracing::error!("Impossible to discard vault draft: {e}");
+                                }
+                            }
+                            res
                         },
                         |res| match res {
                             Ok(_) => Message::View(Stage::Vaults),
@@ -140,38 +251,62 @@ impl State for AddVaultState {
             .push(Text::new("Public Keys (optional)").view())
             .spacing(5);
 
-        if !self.public_keys.is_empty() {
-            for public_key in self.public_keys.iter() {
-                public_keys = public_keys.push(
-                    Text::new(format!(
-                        "- {}{}",
-                        self.contacts
-                            .iter()
-                            .find(|c| c.public_key() == *public_key)
-                            .map(|u| u.name())
-                            .unwrap_or_else(|| util::cut_public_key(*public_key)),
-                        if self.profile.as_ref().map(|p| p.public_key()) == Some(*public_key) {
-                            " (me)"
-                        } else {
-                            ""
-                        }
-                    ))
-                    .small()
+        // Chips (press to remove)
+        for public_key in self.public_keys.iter().copied() {
+            let label: String = match self.participants.get(&public_key) {
+                Some(participant) => format!(
+                    "{}{} ✕",
+                    participant.name(),
+                    if participant.is_me {
+                        " (me)"
+                    } else if !participant.is_contact {
+                        " (not a contact)"
+                    } else {
+                        ""
+                    }
+                ),
+                None => format!("{} (resolving...) ✕", util::cut_public_key(public_key)),
+            };
+            public_keys = public_keys.push(
+                Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text(label)
+                    .width(Length::Fill)
+                    .on_press(AddVaultMessage::RemovePublicKey(public_key).into())
                     .view(),
-                )
-            }
+            );
         }
 
+        let raw_public_key = Row::new()
+            .push(
+                TextInput::new(&self.raw_public_key)
+                    .on_input(|s| AddVaultMessage::RawPublicKeyChanged(s).into())
+                    .on_submit(AddVaultMessage::AddRawPublicKey.into())
+                    .placeholder("npub or hex public key")
+                    .view(),
+            )
+            .push(
+                Button::new()
+                    .style(ButtonStyle::Bordered)
+                    .text("Add")
+                    .width(Length::Fixed(80.0))
+                    .on_press(AddVaultMessage::AddRawPublicKey.into())
+                    .view(),
+            )
+            .spacing(5)
+            .align_items(Alignment::Center);
+
         public_keys = public_keys
             .push(Space::with_height(Length::Fixed(5.0)))
             .push(
                 Button::new()
                     .style(ButtonStyle::Bordered)
-                    .text("Select")
+                    .text("Select from contacts")
                     .width(Length::Fill)
                     .on_press(AddVaultMessage::SelectPublicKeys(true).into())
                     .view(),
-            );
+            )
+            .push(raw_public_key);
 
         let error = if let Some(error) = &self.error {
             Row::new().push(Text::new(error).color(DARK_RED).view())
@@ -196,7 +331,9 @@ impl State for AddVaultState {
             .on_press(Message::View(Stage::VaultBuilder))
             .width(Length::Fill);
 
-        let content = if self.selecting {
+        let content = if self.draft.is_some() {
+            view_resume_draft()
+        } else if self.selecting {
             center_y = false;
             view_select_public_keys(self)
         } else {
@@ -229,6 +366,53 @@ impl State for AddVaultState {
     }
 }
 
+/// Resolve the name of the participant (metadata requested to relays if missing)
+fn resolve_participant(ctx: &Context, public_key: PublicKey) -> Command<Message> {
+    let client = ctx.client.clone();
+    Command::perform(
+        async move { client.check_policy_participants(&[public_key]).await },
+        |res| match res {
+            Ok(mut participants) => match participants.pop() {
+                Some(participant) => {
+                    AddVaultMessage::ParticipantResolved(Box::new(participant)).into()
+                }
+                None => AddVaultMessage::ErrorChanged(None).into(),
+            },
+            Err(e) => AddVaultMessage::ErrorChanged(Some(e.to_string())).into(),
+        },
+    )
+}
+
+fn view_resume_draft<'a>() -> Column<'a, Message> {
+    Column::new()
+        .push(Text::new("Resume draft?").big().bold().view())
+        .push(
+            Text::new("A vault creation was left unfinished")
+                .extra_light()
+                .view(),
+        )
+        .push(Space::with_height(Length::Fixed(15.0)))
+        .push(
+            Button::new()
+                .text("Resume")
+                .on_press(AddVaultMessage::ResumeDraft.into())
+                .width(Length::Fill)
+                .view(),
+        )
+        .push(
+            Button::new()
+                .style(ButtonStyle::Bordered)
+                .text("Discard")
+                .on_press(AddVaultMessage::DiscardDraft.into())
+                .width(Length::Fill)
+                .view(),
+        )
+        .align_items(Alignment::Center)
+        .spacing(10)
+        .padding(20)
+        .max_width(400)
+}
+
 fn view_select_public_keys<'a>(state: &AddVaultState) -> Column<'a, Message> {
     let mut content = Column::new().spacing(10).padding(20);
 
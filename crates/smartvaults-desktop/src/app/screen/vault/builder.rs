@@ -4,7 +4,7 @@
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use smartvaults_sdk::core::miniscript::DescriptorPublicKey;
-use smartvaults_sdk::core::PolicyTemplate;
+use smartvaults_sdk::core::{PolicyTemplate, ScriptType};
 use smartvaults_sdk::nostr::{Profile, PublicKey};
 use smartvaults_sdk::types::{GetAllSigners, GetSharedSigner, GetSigner};
 use smartvaults_sdk::util;
@@ -144,6 +144,7 @@ impl State for PolicyBuilderState {
                 }
                 PolicyBuilderMessage::SelectingSigner { index } => self.selecting_signer = index,
                 PolicyBuilderMessage::SavePolicy => {
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     let name = self.name.clone();
                     let description = self.description.clone();
@@ -392,7 +393,8 @@ fn view_signer_selector<'a>(state: &PolicyBuilderState, index: usize) -> Column<
 
     if let Some(user) = &state.profile {
         for GetSigner { signer_id, signer } in state.signers.my.iter() {
-            if let Ok(descriptor) = signer.descriptor_public_key() {
+            // Signers without a key for taproot addresses can't be used
+            if let Ok(descriptor) = signer.descriptor_public_key_for(ScriptType::Tr) {
                 let row = Row::new()
                     .push(
                         Text::new(util::cut_event_id(*signer_id))
@@ -479,7 +481,7 @@ fn view_signer_selector<'a>(state: &PolicyBuilderState, index: usize) -> Column<
         shared_signer,
     } in state.signers.contacts.iter()
     {
-        if let Ok(descriptor) = shared_signer.descriptor_public_key() {
+        if let Ok(descriptor) = shared_signer.descriptor_public_key_for(ScriptType::Tr) {
             let row = Row::new()
                 .push(
                     Text::new(util::cut_event_id(*shared_signer_id))
@@ -6,10 +6,13 @@ use std::collections::BTreeSet;
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use rfd::FileDialog;
+use smartvaults_sdk::core::bitcoin::Txid;
 use smartvaults_sdk::core::signer::Signer;
-use smartvaults_sdk::nostr::EventId;
-use smartvaults_sdk::types::{GetPolicy, GetProposal, GetTransaction};
-use smartvaults_sdk::util;
+use smartvaults_sdk::nostr::{EventId, Timestamp};
+use smartvaults_sdk::types::{
+    ApproverStats, FeesPaid, GetPolicy, GetProposal, GetTransaction, UnexpectedTx,
+};
+use smartvaults_sdk::util::{self, format};
 
 pub mod add;
 pub mod builder;
@@ -17,10 +20,10 @@ pub mod restore;
 pub mod tree;
 pub mod vaults;
 
-use crate::app::component::{Activity, Balances, Dashboard};
+use crate::app::component::{Activity, Balances, Dashboard, MainnetGuard};
 use crate::app::{Context, Message, Stage, State};
-use crate::component::{rule, Button, ButtonStyle, Text};
-use crate::theme::color::RED;
+use crate::component::{rule, Button, ButtonStyle, Modal, Text};
+use crate::theme::color::{DARK_RED, RED};
 use crate::theme::icon::{BINOCULARS, CLIPBOARD, GLOBE, PATCH_CHECK, SAVE, TRASH};
 
 #[derive(Debug, Clone)]
@@ -30,12 +33,17 @@ pub enum VaultMessage {
     NewProofOfReserve,
     SavePolicyBackup,
     Delete,
+    MainnetConfirmationChanged(Option<String>),
     LoadPolicy(
         GetPolicy,
         Vec<GetProposal>,
         Option<Signer>,
         BTreeSet<GetTransaction>,
+        Vec<ApproverStats>,
+        Vec<UnexpectedTx>,
+        FeesPaid,
     ),
+    AcknowledgeUnexpectedTx(Txid),
     ErrorChanged(Option<String>),
     Reload,
     RepublishSharedKeys,
@@ -50,6 +58,11 @@ pub struct VaultState {
     proposals: Vec<GetProposal>,
     signer: Option<Signer>,
     transactions: BTreeSet<GetTransaction>,
+    approver_stats: Vec<ApproverStats>,
+    unexpected_txs: Vec<UnexpectedTx>,
+    fees_paid: FeesPaid,
+    /// Typed confirmation of the mainnet guard
+    mainnet_confirmation: Option<String>,
     error: Option<String>,
 }
 
@@ -63,6 +76,10 @@ impl VaultState {
             proposals: Vec::new(),
             signer: None,
             transactions: BTreeSet::new(),
+            approver_stats: Vec::new(),
+            unexpected_txs: Vec::new(),
+            fees_paid: FeesPaid::default(),
+            mainnet_confirmation: None,
             error: None,
         }
     }
@@ -90,11 +107,26 @@ impl State for VaultState {
                     .search_signer_by_descriptor(policy.policy.descriptor())
                     .await
                     .ok();
-                Some((policy, proposals, signer, list))
+                let stats = client
+                    .get_approver_stats(policy_id)
+                    .await
+                    .unwrap_or_default();
+                let unexpected = client
+                    .get_unexpected_txs(Some(policy_id))
+                    .await
+                    .unwrap_or_default();
+                let fees = client
+                    .get_fees_paid(policy_id, None, None)
+                    .await
+                    .unwrap_or_default();
+                Some((policy, proposals, signer, list, stats, unexpected, fees))
             },
             |res| match res {
-                Some((policy, proposals, signer, list)) => {
-                    VaultMessage::LoadPolicy(policy, proposals, signer, list).into()
+                Some((policy, proposals, signer, list, stats, unexpected, fees)) => {
+                    VaultMessage::LoadPolicy(
+                        policy, proposals, signer, list, stats, unexpected, fees,
+                    )
+                    .into()
                 }
                 None => Message::View(Stage::Vaults),
             },
@@ -151,6 +183,12 @@ impl State for VaultState {
                     }
                 }
                 VaultMessage::Delete => {
+                    if ctx.mainnet_guard_pending(self.mainnet_confirmation.as_deref()) {
+                        self.mainnet_confirmation = Some(String::new());
+                        return Command::none();
+                    }
+
+                    self.mainnet_confirmation = None;
                     let client = ctx.client.clone();
                     let policy_id = self.policy_id;
 
@@ -164,6 +202,7 @@ impl State for VaultState {
 
                     if let Some(path) = path {
                         self.loading = true;
+                        ctx.cache.invalidate_all();
                         return Command::perform(
                             async move {
                                 client.save_policy_backup(policy_id, &path).await?;
@@ -177,18 +216,43 @@ impl State for VaultState {
                         );
                     }
                 }
-                VaultMessage::LoadPolicy(policy, proposals, signer, list) => {
+                VaultMessage::LoadPolicy(
+                    policy,
+                    proposals,
+                    signer,
+                    list,
+                    stats,
+                    unexpected,
+                    fees,
+                ) => {
                     self.policy = Some(policy);
                     self.proposals = proposals;
                     self.signer = signer;
                     self.transactions = list;
+                    self.approver_stats = stats;
+                    self.unexpected_txs = unexpected;
+                    self.fees_paid = fees;
                     self.loading = false;
                     self.loaded = true;
                 }
+                VaultMessage::MainnetConfirmationChanged(confirmation) => {
+                    self.mainnet_confirmation = confirmation
+                }
                 VaultMessage::ErrorChanged(e) => {
                     self.loading = false;
                     self.error = e;
                 }
+                VaultMessage::AcknowledgeUnexpectedTx(txid) => {
+                    let client = ctx.client.clone();
+                    let policy_id = self.policy_id;
+                    return Command::perform(
+                        async move { client.acknowledge_unexpected_tx(policy_id, txid).await },
+                        |res| match res {
+                            Ok(_) => VaultMessage::Reload.into(),
+                            Err(e) => VaultMessage::ErrorChanged(Some(e.to_string())).into(),
+                        },
+                    );
+                }
                 VaultMessage::Reload => {
                     return self.load(ctx);
                 }
@@ -217,6 +281,33 @@ impl State for VaultState {
 
         if is_ready {
             if let Some(policy) = &self.policy {
+                for tx in self.unexpected_txs.iter().filter(|tx| !tx.acknowledged) {
+                    content = content.push(
+                        Row::new()
+                            .push(
+                                Text::new(format!(
+                                    "ALERT: {} sat moved out of the vault by tx {} without a matching completed proposal",
+                                    format::number(tx.amount),
+                                    tx.txid
+                                ))
+                                .color(DARK_RED)
+                                .bold()
+                                .width(Length::Fill)
+                                .view(),
+                            )
+                            .push(
+                                Button::new()
+                                    .style(ButtonStyle::BorderedDanger)
+                                    .text("Acknowledge")
+                                    .on_press(VaultMessage::AcknowledgeUnexpectedTx(tx.txid).into())
+                                    .loading(self.loading)
+                                    .view(),
+                            )
+                            .spacing(10)
+                            .align_items(Alignment::Center),
+                    );
+                }
+
                 content = content
                     .push(Space::with_height(Length::Fixed(20.0)))
                     .push(
@@ -233,6 +324,17 @@ impl State for VaultState {
                                         ))
                                         .view(),
                                     )
+                                    .push(
+                                        Text::new(format!(
+                                            "Script type: {}",
+                                            policy
+                                                .policy
+                                                .script_type()
+                                                .map(|t| t.to_string())
+                                                .unwrap_or_else(|_| String::from("Unknown"))
+                                        ))
+                                        .view(),
+                                    )
                                     .push(
                                         Text::new(format!(
                                             "Signer: {}",
@@ -243,6 +345,7 @@ impl State for VaultState {
                                         ))
                                         .view(),
                                     )
+                                    .push(Text::new(self.fees_paid_line(ctx)).view())
                                     .push(
                                         Row::new()
                                             .push(
@@ -334,6 +437,14 @@ impl State for VaultState {
                         Text::new("").view()
                     });
 
+                if !self.approver_stats.is_empty() {
+                    content = content
+                        .push(Space::with_height(Length::Fixed(20.0)))
+                        .push(Text::new("Approvers").bold().big().view())
+                        .push(Space::with_height(Length::Fixed(5.0)))
+                        .push(self.view_approver_stats());
+                }
+
                 content = content
                     .push(Space::with_height(Length::Fixed(20.0)))
                     .push(Text::new("Activity").bold().big().view())
@@ -346,9 +457,108 @@ impl State for VaultState {
             }
         }
 
-        Dashboard::new()
+        let dashboard = Dashboard::new()
             .loaded(is_ready)
-            .view(ctx, content, false, false)
+            .view(ctx, content, false, false);
+
+        if let Some(confirmation) = &self.mainnet_confirmation {
+            Modal::new(
+                dashboard,
+                MainnetGuard::new("delete this vault", ctx.client.network(), confirmation)
+                    .on_input(|s| VaultMessage::MainnetConfirmationChanged(Some(s)).into())
+                    .on_confirm(VaultMessage::Delete.into())
+                    .on_cancel(VaultMessage::MainnetConfirmationChanged(None).into())
+                    .view(),
+            )
+            .on_blur(VaultMessage::MainnetConfirmationChanged(None).into())
+            .into()
+        } else {
+            dashboard
+        }
+    }
+}
+
+impl VaultState {
+    /// Fees paid this month and all time
+    fn fees_paid_line(&self, ctx: &Context) -> String {
+        if ctx.hide_balances {
+            return String::from("Fees: *****");
+        }
+
+        let start_of_month: Timestamp = util::start_of_month(Timestamp::now());
+        let this_month: u64 = self
+            .fees_paid
+            .txs
+            .iter()
+            .filter(|tx| tx.timestamp >= start_of_month)
+            .map(|tx| tx.fee)
+            .sum();
+        let prefix: &str = if self.fees_paid.estimated { "~" } else { "" };
+        format!(
+            "Fees: {prefix}{} sat this month / {prefix}{} sat all time",
+            format::number(this_month),
+            format::number(self.fees_paid.total)
+        )
+    }
+
+    fn view_approver_stats<'a>(&self) -> Column<'a, Message> {
+        let mut table = Column::new()
+            .push(
+                Row::new()
+                    .push(Text::new("Public key").bold().width(Length::Fill).view())
+                    .push(Text::new("Approved").bold().width(Length::Fill).view())
+                    .push(Text::new("Approval rate").bold().width(Length::Fill).view())
+                    .push(Text::new("Median time").bold().width(Length::Fill).view())
+                    .push(
+                        Text::new("Finalized without")
+                            .bold()
+                            .width(Length::Fill)
+                            .view(),
+                    )
+                    .spacing(10),
+            )
+            .push(rule::horizontal_bold())
+            .spacing(10);
+
+        for stats in self.approver_stats.iter() {
+            table = table.push(
+                Row::new()
+                    .push(
+                        Text::new(util::cut_public_key(stats.public_key))
+                            .width(Length::Fill)
+                            .view(),
+                    )
+                    .push(
+                        Text::new(format!("{}/{}", stats.approved, stats.proposals))
+                            .width(Length::Fill)
+                            .view(),
+                    )
+                    .push(
+                        Text::new(match stats.approval_rate() {
+                            Some(rate) => format!("{:.0}%", rate * 100.0),
+                            None => String::from("-"),
+                        })
+                        .width(Length::Fill)
+                        .view(),
+                    )
+                    .push(
+                        Text::new(match stats.median_approval_time {
+                            Some(secs) => format::duration(secs),
+                            None => String::from("-"),
+                        })
+                        .width(Length::Fill)
+                        .view(),
+                    )
+                    .push(
+                        Text::new(stats.finalized_without.to_string())
+                            .width(Length::Fill)
+                            .view(),
+                    )
+                    .spacing(10),
+            );
+        }
+
+        table
     }
 }
 
@@ -32,6 +32,7 @@ pub struct RestoreVaultState {
     name: String,
     description: String,
     descriptor: String,
+    change_descriptor: Option<String>,
     public_keys: Vec<PublicKey>,
     known_public_keys: BTreeSet<Profile>,
     loading: bool,
@@ -46,6 +47,7 @@ impl RestoreVaultState {
 
     pub fn clear(&mut self) {
         self.descriptor = String::new();
+        self.change_descriptor = None;
         self.public_keys = Vec::new();
         self.error = None;
     }
@@ -111,19 +113,29 @@ impl State for RestoreVaultState {
                         self.description = description;
                     }
                     self.descriptor = backup.descriptor().to_string();
+                    self.change_descriptor = backup.change_descriptor().map(|d| d.to_string());
                     self.public_keys = backup.public_keys();
                 }
                 RestoreVaultMessage::SavePolicy => {
                     self.loading = true;
+                    ctx.cache.invalidate_all();
                     let client = ctx.client.clone();
                     let name = self.name.clone();
                     let description = self.description.clone();
                     let descriptor = self.descriptor.clone();
+                    let change_descriptor = self.change_descriptor.clone();
                     let public_keys = self.public_keys.clone();
                     return Command::perform(
                         async move {
+                            // Keep the change keychain of the backup (none for legacy vaults)
                             client
-                                .save_policy(name, description, descriptor, public_keys)
+                                .save_policy_with_change_descriptor(
+                                    name,
+                                    description,
+                                    descriptor,
+                                    change_descriptor,
+                                    public_keys,
+                                )
                                 .await
                         },
                         |res| match res {
@@ -1,25 +1,27 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use iced::widget::Column;
-use iced::{Command, Element};
+use iced::widget::{Column, Row, Space};
+use iced::{Command, Element, Length};
 use smartvaults_sdk::core::bdk::descriptor::policy::SatisfiableItem;
 use smartvaults_sdk::nostr::EventId;
-use smartvaults_sdk::types::GetPolicy;
+use smartvaults_sdk::types::{GetPolicy, PolicyKeyReport};
 
 use crate::app::component::{Dashboard, PolicyTree};
 use crate::app::{Context, Message, Stage, State};
-use crate::component::Text;
+use crate::component::{rule, Text};
+use crate::theme::color::{DARK_RED, YELLOW};
 
 #[derive(Debug, Clone)]
 pub enum PolicyTreeMessage {
-    Load(SatisfiableItem),
+    Load(SatisfiableItem, Vec<PolicyKeyReport>),
 }
 
 #[derive(Debug)]
 pub struct PolicyTreeState {
     policy_id: EventId,
     item: Option<SatisfiableItem>,
+    keys: Vec<PolicyKeyReport>,
     loaded: bool,
     loading: bool,
 }
@@ -29,6 +31,7 @@ impl PolicyTreeState {
         Self {
             policy_id,
             item: None,
+            keys: Vec::new(),
             loaded: false,
             loading: false,
         }
@@ -52,10 +55,11 @@ impl State for PolicyTreeState {
             async move {
                 let GetPolicy { policy, .. } = client.get_policy_by_id(policy_id).await?;
                 let item = policy.satisfiable_item()?.clone();
-                Ok::<SatisfiableItem, Box<dyn std::error::Error>>(item)
+                let keys = client.get_policy_key_report(policy_id).await?;
+                Ok::<_, Box<dyn std::error::Error>>((item, keys))
             },
             |res| match res {
-                Ok(item) => PolicyTreeMessage::Load(item).into(),
+                Ok((item, keys)) => PolicyTreeMessage::Load(item, keys).into(),
                 Err(e) => {
                     tracing::error!("Impossible to load policy tree: {e}");
                     Message::View(Stage::Vaults)
@@ -71,8 +75,9 @@ impl State for PolicyTreeState {
 
         if let Message::PolicyTree(msg) = message {
             match msg {
-                PolicyTreeMessage::Load(item) => {
+                PolicyTreeMessage::Load(item, keys) => {
                     self.item = Some(item);
+                    self.keys = keys;
                     self.loading = false;
                     self.loaded = true;
                 }
@@ -89,7 +94,10 @@ impl State for PolicyTreeState {
         let content = if let Some(item) = self.item.clone() {
             center_x = false;
             center_y = false;
-            PolicyTree::new(item).view()
+            PolicyTree::new(item)
+                .view()
+                .push(Space::with_height(Length::Fixed(20.0)))
+                .push(self.view_keys())
         } else {
             Column::new().push(Text::new("Tree not loaded").view())
         };
@@ -100,6 +108,85 @@ impl State for PolicyTreeState {
     }
 }
 
+impl PolicyTreeState {
+    fn view_keys<'a>(&self) -> Column<'a, Message> {
+        let mut table = Column::new()
+            .push(Text::new("Keys").bold().bigger().view())
+            .push(
+                Row::new()
+                    .push(Text::new("Owner").bold().width(Length::Fill).view())
+                    .push(Text::new("Device").bold().width(Length::Fill).view())
+                    .push(Text::new("Fingerprint").bold().width(Length::Fill).view())
+                    .push(Text::new("Path").bold().width(Length::Fill).view())
+                    .push(Text::new("Purpose").bold().width(Length::Fill).view())
+                    .push(Text::new("Signer match").bold().width(Length::Fill).view())
+                    .spacing(10),
+            )
+            .push(rule::horizontal_bold())
+            .spacing(10);
+
+        for report in self.keys.iter() {
+            let origin = &report.origin;
+            let mut owner: String = report.owner.clone().unwrap_or_else(|| String::from("-"));
+            if report.ours {
+                owner.push_str(" (mine)");
+            }
+            if origin.internal {
+                owner.push_str(" [internal]");
+            }
+
+            let mut purpose: String = origin
+                .purpose
+                .map(|p| p.to_string())
+                .unwrap_or_else(|| String::from("-"));
+            if origin.network_mismatch {
+                purpose.push_str(" (wrong network)");
+            }
+
+            let signer_match = match (report.signer_id, report.matched_purpose) {
+                (Some(_), Some(purpose)) => Text::new(format!("{purpose} descriptor")),
+                (Some(_), None) => Text::new("fingerprint only").color(YELLOW),
+                (None, _) => Text::new("-"),
+            }
+            .width(Length::Fill);
+
+            let fingerprint = Text::new(match origin.fingerprint {
+                Some(fingerprint) => fingerprint.to_string(),
+                None => String::from("unverifiable"),
+            })
+            .width(Length::Fill);
+
+            table = table.push(
+                Row::new()
+                    .push(Text::new(owner).width(Length::Fill).view())
+                    .push(
+                        Text::new(report.device.clone().unwrap_or_else(|| String::from("-")))
+                            .width(Length::Fill)
+                            .view(),
+                    )
+                    .push(if origin.is_unverifiable() {
+                        fingerprint.color(DARK_RED).view()
+                    } else {
+                        fingerprint.view()
+                    })
+                    .push(
+                        Text::new(match &origin.derivation_path {
+                            Some(path) => path.to_string(),
+                            None => String::from("-"),
+                        })
+                        .width(Length::Fill)
+                        .view(),
+                    )
+                    .push(Text::new(purpose).width(Length::Fill).view())
+                    .push(signer_match.view())
+                    .spacing(10),
+            );
+        }
+
+        table
+    }
+}
+
 impl From<PolicyTreeState> for Box<dyn State> {
     fn from(s: PolicyTreeState) -> Box<dyn State> {
         Box::new(s)
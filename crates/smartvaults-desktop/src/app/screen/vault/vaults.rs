@@ -1,24 +1,28 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::Duration;
 
 use iced::widget::{Column, Row, Space};
 use iced::{Alignment, Command, Element, Length};
 use rfd::FileDialog;
+use smartvaults_sdk::core::bitcoin::Network;
 use smartvaults_sdk::nostr::{EventId, Timestamp};
 use smartvaults_sdk::types::GetPolicy;
 use smartvaults_sdk::util;
 
+use crate::app::cache::PolicyGroup;
 use crate::app::component::Dashboard;
 use crate::app::{Context, Message, Stage, State};
-use crate::component::{rule, Amount, Button, ButtonStyle, SpinnerLinear, Text};
-use crate::theme::icon::{FULLSCREEN, PLUS, RELOAD, SAVE};
+use crate::component::{rule, Amount, Badge, BadgeStyle, Button, ButtonStyle, SpinnerLinear, Text};
+use crate::theme::icon::{ARROW_DOWN, ARROW_UP, FULLSCREEN, PLUS, RELOAD, SAVE};
 
 #[derive(Debug, Clone)]
 pub enum PoliciesMessage {
-    LoadPolicies(Vec<GetPolicy>),
     SavePolicyBackup(EventId),
+    CollapsedGroupsLoaded(BTreeSet<String>),
+    ToggleGroup(PolicyGroup),
     Reload,
 }
 
@@ -27,6 +31,7 @@ pub struct PoliciesState {
     loading: bool,
     loaded: bool,
     policies: Vec<GetPolicy>,
+    collapsed: BTreeSet<String>,
 }
 
 impl PoliciesState {
@@ -41,11 +46,19 @@ impl State for PoliciesState {
     }
 
     fn load(&mut self, ctx: &Context) -> Command<Message> {
-        self.loading = true;
+        if let Some(policies) = ctx.cache.policies() {
+            self.policies = policies;
+            self.loaded = true;
+        }
+        self.loading = !self.loaded;
         let client = ctx.client.clone();
-        Command::perform(async move { client.get_policies().await.unwrap() }, |p| {
-            PoliciesMessage::LoadPolicies(p).into()
-        })
+        Command::batch([
+            Command::perform(
+                async move { client.config().collapsed_policy_groups().await },
+                |groups| PoliciesMessage::CollapsedGroupsLoaded(groups).into(),
+            ),
+            ctx.cache.revalidate_policies(&ctx.client),
+        ])
     }
 
     fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
@@ -55,12 +68,6 @@ impl State for PoliciesState {
 
         if let Message::Policies(msg) = message {
             match msg {
-                PoliciesMessage::LoadPolicies(policies) => {
-                    self.policies = policies;
-                    self.loading = false;
-                    self.loaded = true;
-                    Command::none()
-                }
                 PoliciesMessage::SavePolicyBackup(policy_id) => {
                     let path = FileDialog::new()
                         .set_title("Export policy backup")
@@ -79,7 +86,35 @@ impl State for PoliciesState {
                     }
                     Command::none()
                 }
-                PoliciesMessage::Reload => self.load(ctx),
+                PoliciesMessage::CollapsedGroupsLoaded(groups) => {
+                    self.collapsed = groups;
+                    Command::none()
+                }
+                PoliciesMessage::ToggleGroup(group) => {
+                    let group: String = group.to_string();
+                    let collapsed: bool = !self.collapsed.contains(&group);
+                    if collapsed {
+                        self.collapsed.insert(group.clone());
+                    } else {
+                        self.collapsed.remove(&group);
+                    }
+
+                    let config = ctx.client.config();
+                    Command::perform(
+                        async move {
+                            config.set_policy_group_collapsed(group, collapsed).await;
+                            if let Err(e) = config.save().await {
+                                tracing::error!("Impossible to save collapsed groups: {e}");
+                            }
+                            config.collapsed_policy_groups().await
+                        },
+                        |groups| PoliciesMessage::CollapsedGroupsLoaded(groups).into(),
+                    )
+                }
+                PoliciesMessage::Reload => {
+                    ctx.cache.invalidate_all();
+                    self.load(ctx)
+                }
             }
         } else {
             Command::none()
@@ -115,12 +150,24 @@ impl State for PoliciesState {
             } else {
                 center_y = false;
 
+                let mut header = Row::new()
+                    .push(Text::new("ID").bold().width(Length::Fixed(115.0)).view())
+                    .push(Text::new("Name").bold().width(Length::Fill).view())
+                    .push(Text::new("Balance").bold().width(Length::Fill).view());
+
+                // Policies on test networks are easy to confuse with the mainnet ones
+                let network = ctx.client.network();
+                if network != Network::Bitcoin {
+                    header = header.push(
+                        Badge::new(Text::new(network.to_string()).small().extra_light().view())
+                            .style(BadgeStyle::Warning)
+                            .width(Length::Fixed(80.0)),
+                    );
+                }
+
                 content = content
                     .push(
-                        Row::new()
-                            .push(Text::new("ID").bold().width(Length::Fixed(115.0)).view())
-                            .push(Text::new("Name").bold().width(Length::Fill).view())
-                            .push(Text::new("Balance").bold().width(Length::Fill).view())
+                        header
                             .push(
                                 Button::new()
                                     .style(ButtonStyle::Bordered)
@@ -144,55 +191,101 @@ impl State for PoliciesState {
                     )
                     .push(rule::horizontal_bold());
 
-                for GetPolicy {
-                    policy_id,
-                    policy,
-                    balance,
-                    last_sync,
-                } in self.policies.iter()
-                {
-                    let balance = if *last_sync != Timestamp::from(0) {
-                        Amount::new(balance.total())
-                            .bold()
-                            .view()
-                            .width(Length::Fill)
-                    } else {
-                        Row::new()
-                            .push(
-                                SpinnerLinear::new()
-                                    .width(Length::Fixed(200.0))
-                                    .cycle_duration(Duration::from_secs(2)),
-                            )
-                            .width(Length::Fill)
-                    };
+                let mut groups: BTreeMap<PolicyGroup, Vec<&GetPolicy>> = BTreeMap::new();
+                for policy in self.policies.iter() {
+                    groups
+                        .entry(ctx.cache.policy_group(&policy.policy_id))
+                        .or_default()
+                        .push(policy);
+                }
 
-                    let row = Row::new()
-                        .push(
-                            Text::new(util::cut_event_id(*policy_id))
-                                .width(Length::Fixed(115.0))
-                                .view(),
-                        )
-                        .push(Text::new(&policy.name()).width(Length::Fill).view())
-                        .push(balance)
-                        .push(
-                            Button::new()
-                                .style(ButtonStyle::Bordered)
-                                .icon(SAVE)
-                                .on_press(PoliciesMessage::SavePolicyBackup(*policy_id).into())
-                                .width(Length::Fixed(40.0))
-                                .view(),
-                        )
+                for (group, policies) in groups.into_iter() {
+                    let collapsed: bool = self.collapsed.contains(&group.to_string());
+                    let subtotal: u64 = policies.iter().map(|p| p.balance.total()).sum();
+
+                    content = content
                         .push(
-                            Button::new()
-                                .icon(FULLSCREEN)
-                                .on_press(Message::View(Stage::Vault(*policy_id)))
-                                .width(Length::Fixed(40.0))
-                                .view(),
+                            Row::new()
+                                .push(
+                                    Text::new(format!("{group} ({})", policies.len()))
+                                        .bold()
+                                        .width(Length::Fill)
+                                        .view(),
+                                )
+                                .push(
+                                    Amount::new(subtotal)
+                                        .hidden(ctx.hide_balances)
+                                        .view()
+                                        .width(Length::Fill),
+                                )
+                                .push(
+                                    Button::new()
+                                        .style(ButtonStyle::Bordered)
+                                        .icon(if collapsed { ARROW_DOWN } else { ARROW_UP })
+                                        .on_press(PoliciesMessage::ToggleGroup(group).into())
+                                        .width(Length::Fixed(40.0))
+                                        .view(),
+                                )
+                                .spacing(10)
+                                .align_items(Alignment::Center)
+                                .width(Length::Fill),
                         )
-                        .spacing(10)
-                        .align_items(Alignment::Center)
-                        .width(Length::Fill);
-                    content = content.push(row).push(rule::horizontal());
+                        .push(rule::horizontal());
+
+                    if collapsed {
+                        continue;
+                    }
+
+                    for GetPolicy {
+                        policy_id,
+                        policy,
+                        balance,
+                        last_sync,
+                    } in policies.into_iter()
+                    {
+                        let balance = if *last_sync != Timestamp::from(0) {
+                            Amount::new(balance.total())
+                                .bold()
+                                .view()
+                                .width(Length::Fill)
+                        } else {
+                            Row::new()
+                                .push(
+                                    SpinnerLinear::new()
+                                        .width(Length::Fixed(200.0))
+                                        .cycle_duration(Duration::from_secs(2)),
+                                )
+                                .width(Length::Fill)
+                        };
+
+                        let row = Row::new()
+                            .push(
+                                Text::new(util::cut_event_id(*policy_id))
+                                    .width(Length::Fixed(115.0))
+                                    .view(),
+                            )
+                            .push(Text::new(&policy.name()).width(Length::Fill).view())
+                            .push(balance)
+                            .push(
+                                Button::new()
+                                    .style(ButtonStyle::Bordered)
+                                    .icon(SAVE)
+                                    .on_press(PoliciesMessage::SavePolicyBackup(*policy_id).into())
+                                    .width(Length::Fixed(40.0))
+                                    .view(),
+                            )
+                            .push(
+                                Button::new()
+                                    .icon(FULLSCREEN)
+                                    .on_press(Message::View(Stage::Vault(*policy_id)))
+                                    .width(Length::Fixed(40.0))
+                                    .view(),
+                            )
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .width(Length::Fill);
+                        content = content.push(row).push(rule::horizontal());
+                    }
                 }
             }
         }
@@ -0,0 +1,172 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use iced::widget::{Column, Row, Space};
+use iced::{Alignment, Command, Element, Length};
+use smartvaults_sdk::changelog::{Release, Section};
+
+use crate::app::component::Dashboard;
+use crate::app::{Context, Message, Stage, State};
+use crate::component::{rule, Button, ButtonStyle, Text};
+use crate::theme::color::DARK_RED;
+use crate::theme::icon::CHECK;
+
+#[derive(Debug, Clone)]
+pub enum WhatsNewMessage {
+    Load(Vec<&'static Release>),
+    /// Mark as seen and move to the stage
+    Dismiss(Stage),
+    ErrorChanged(Option<String>),
+}
+
+#[derive(Debug, Default)]
+pub struct WhatsNewState {
+    loading: bool,
+    loaded: bool,
+    releases: Vec<&'static Release>,
+    error: Option<String>,
+}
+
+impl WhatsNewState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn section_stage(section: Section) -> Stage {
+    match section {
+        Section::Vaults => Stage::Vaults,
+        Section::Spend => Stage::Spend(None),
+        Section::Activity => Stage::Activity(None),
+        Section::Signers => Stage::Signers,
+        Section::Contacts => Stage::Contacts,
+        Section::Notifications => Stage::Dashboard,
+        Section::Settings => Stage::Settings,
+        Section::Config => Stage::Config,
+        Section::Relays => Stage::Relays,
+        Section::NostrConnect => Stage::NostrConnect,
+    }
+}
+
+impl State for WhatsNewState {
+    fn title(&self) -> String {
+        String::from("What's new")
+    }
+
+    fn load(&mut self, ctx: &Context) -> Command<Message> {
+        if self.loading {
+            return Command::none();
+        }
+
+        self.loading = true;
+        let client = ctx.client.clone();
+        Command::perform(async move { client.whats_new().await }, |res| match res {
+            Ok(releases) => WhatsNewMessage::Load(releases).into(),
+            Err(e) => WhatsNewMessage::ErrorChanged(Some(e.to_string())).into(),
+        })
+    }
+
+    fn update(&mut self, ctx: &mut Context, message: Message) -> Command<Message> {
+        if !self.loaded && !self.loading {
+            return self.load(ctx);
+        }
+
+        if let Message::WhatsNew(msg) = message {
+            match msg {
+                WhatsNewMessage::Load(releases) => {
+                    self.releases = releases;
+                    self.loading = false;
+                    self.loaded = true;
+                }
+                WhatsNewMessage::Dismiss(stage) => {
+                    let client = ctx.client.clone();
+                    return Command::perform(
+                        async move { client.mark_whats_new_seen().await },
+                        move |res| match res {
+                            Ok(_) => Message::View(stage),
+                            Err(e) => WhatsNewMessage::ErrorChanged(Some(e.to_string())).into(),
+                        },
+                    );
+                }
+                WhatsNewMessage::ErrorChanged(error) => {
+                    self.error = error;
+                    self.loading = false;
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    fn view(&self, ctx: &Context) -> Element<Message> {
+        let mut content = Column::new().spacing(10).padding(20).max_width(800);
+
+        for release in self.releases.iter() {
+            content = content
+                .push(
+                    Text::new(format!("v{}", release.version))
+                        .bold()
+                        .bigger()
+                        .view(),
+                )
+                .push(rule::horizontal_bold());
+
+            for feature in release.features.iter() {
+                let mut row = Row::new()
+                    .push(
+                        Column::new()
+                            .push(Text::new(feature.title).bold().view())
+                            .push(Text::new(feature.description).extra_light().view())
+                            .spacing(5)
+                            .width(Length::Fill),
+                    )
+                    .spacing(10)
+                    .align_items(Alignment::Center);
+
+                if let Some(section) = feature.section {
+                    row = row.push(
+                        Button::new()
+                            .style(ButtonStyle::Bordered)
+                            .text("Open")
+                            .width(Length::Fixed(120.0))
+                            .on_press(WhatsNewMessage::Dismiss(section_stage(section)).into())
+                            .view(),
+                    );
+                }
+
+                content = content.push(row).push(rule::horizontal());
+            }
+
+            content = content.push(Space::with_height(Length::Fixed(10.0)));
+        }
+
+        if let Some(error) = &self.error {
+            content = content.push(Text::new(error).color(DARK_RED).view());
+        }
+
+        content = content.push(
+            Button::new()
+                .icon(CHECK)
+                .text("Got it")
+                .width(Length::Fill)
+                .on_press(WhatsNewMessage::Dismiss(Stage::Dashboard).into())
+                .view(),
+        );
+
+        Dashboard::new()
+            .loaded(self.loaded)
+            .view(ctx, content, true, false)
+    }
+}
+
+impl From<WhatsNewState> for Box<dyn State> {
+    fn from(s: WhatsNewState) -> Box<dyn State> {
+        Box::new(s)
+    }
+}
+
+impl From<WhatsNewMessage> for Message {
+    fn from(msg: WhatsNewMessage) -> Self {
+        Self::WhatsNew(msg)
+    }
+}
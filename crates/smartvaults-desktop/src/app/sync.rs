@@ -23,10 +23,11 @@ impl Recipe for SmartVaultsSync {
     }
 
     fn stream(self: Box<Self>, _input: EventStream) -> BoxStream<Self::Output> {
-        let mut receiver = self.client.sync_notifications();
+        // Replay the notifications emitted while the app was loading
+        let mut receiver = self.client.sync_notifications_since(0);
         let stream = stream! {
-            while let Ok(msg) = receiver.recv().await {
-                yield msg;
+            while let Ok(notification) = receiver.recv().await {
+                yield notification.message;
             }
         };
         Box::pin(stream)
@@ -4,7 +4,7 @@
 use iced::widget::{container, Column, Container};
 use iced::{theme, Background, BorderRadius, Element, Length, Theme};
 
-use crate::theme::color::WHITE;
+use crate::theme::color::{RED, WHITE};
 
 pub struct Card<Message> {
     head: Element<'static, Message>,
@@ -13,6 +13,7 @@ pub struct Card<Message> {
     width: Option<Length>,
     height: Option<Length>,
     max_width: f32,
+    style: CardStyle,
 }
 
 impl<Message> Card<Message>
@@ -31,6 +32,7 @@ where
             width: None,
             height: None,
             max_width: u32::MAX as f32,
+            style: CardStyle::default(),
         }
     }
 
@@ -57,6 +59,11 @@ where
         self
     }
 
+    pub fn style(mut self, style: CardStyle) -> Self {
+        self.style = style;
+        self
+    }
+
     pub fn view(self) -> Element<'static, Message> {
         let mut card = Column::new().push(self.head).push(self.body);
 
@@ -74,10 +81,7 @@ where
 
         card = card.max_width(self.max_width);
 
-        Container::new(card)
-            .padding(10.0)
-            .style(CardStyle::Primary)
-            .into()
+        Container::new(card).padding(10.0).style(self.style).into()
     }
 }
 
@@ -85,6 +89,8 @@ where
 pub enum CardStyle {
     #[default]
     Primary,
+    /// Red border (i.e. mainnet confirmations)
+    Danger,
 }
 
 impl container::StyleSheet for CardStyle {
@@ -100,6 +106,13 @@ impl container::StyleSheet for CardStyle {
                 border_width: 1.0,
                 border_color: WHITE,
             },
+            Self::Danger => container::Appearance {
+                text_color: None,
+                background: Some(Background::Color(palette.background)),
+                border_radius: BorderRadius::from(10.0),
+                border_width: 2.0,
+                border_color: RED,
+            },
         }
     }
 }
@@ -121,8 +121,9 @@ impl Application for SmartVaultsApp {
             format!("{APP_NAME} - {title}")
         };
 
-        if network != Network::Bitcoin {
-            title.push_str(&format!(" [{network}]"));
+        match network {
+            Network::Bitcoin => title.push_str(" [MAINNET]"),
+            _ => title.push_str(&format!(" [{network}]")),
         }
 
         title
@@ -149,9 +150,12 @@ impl Application for SmartVaultsApp {
                 let (command, stage_to_move) = start.update(*msg);
                 if let Some(stage) = stage_to_move {
                     *self = stage;
-                    return Command::perform(async {}, |_| {
-                        Message::App(Box::new(app::Message::Tick))
-                    });
+                    return match &self.state {
+                        State::App(app) => app.init().map(|m| m.into()),
+                        State::Start(..) => Command::perform(async {}, |_| {
+                            Message::App(Box::new(app::Message::Tick))
+                        }),
+                    };
                 }
                 command.map(|m| m.into())
             }
@@ -1,12 +1,13 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use iced::widget::{Checkbox, Column, Row};
+use iced::widget::{Checkbox, Column, PickList, Row};
 use iced::{Command, Element, Length};
+use smartvaults_sdk::core::bips::bip39::Language;
 use smartvaults_sdk::core::types::WordCount;
 use smartvaults_sdk::SmartVaults;
 
-use super::view;
+use super::{view, LanguagePickList};
 use crate::component::{rule, Button, ButtonStyle, Text, TextInput};
 use crate::start::{Context, Message, Stage, State};
 use crate::theme::color::DARK_RED;
@@ -17,23 +18,39 @@ pub enum GenerateMessage {
     NameChanged(String),
     PasswordChanged(String),
     ConfirmPasswordChanged(String),
+    LanguageChanged(LanguagePickList),
     UsePassphrase(bool),
     PassphraseChanged(String),
     ErrorChanged(Option<String>),
     Generate,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct GenerateState {
     name: String,
     password: String,
     confirm_password: String,
+    language: Language,
     use_passphrase: bool,
     passphrase: String,
     // mnemonic: Option<Mnemonic>,
     error: Option<String>,
 }
 
+impl Default for GenerateState {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            password: String::new(),
+            confirm_password: String::new(),
+            language: Language::English,
+            use_passphrase: false,
+            passphrase: String::new(),
+            error: None,
+        }
+    }
+}
+
 impl GenerateState {
     pub fn new() -> Self {
         Self::default()
@@ -51,6 +68,11 @@ impl State for GenerateState {
                 GenerateMessage::NameChanged(name) => self.name = name,
                 GenerateMessage::PasswordChanged(passwd) => self.password = passwd,
                 GenerateMessage::ConfirmPasswordChanged(passwd) => self.confirm_password = passwd,
+                GenerateMessage::LanguageChanged(LanguagePickList(language)) => {
+                    if let Some(language) = language {
+                        self.language = language;
+                    }
+                }
                 GenerateMessage::UsePassphrase(value) => {
                     self.use_passphrase = value;
                     self.passphrase = String::new();
@@ -65,6 +87,7 @@ impl State for GenerateState {
                     let password = self.password.clone();
                     let confirm_password = self.confirm_password.clone();
                     let passphrase = self.passphrase.clone();
+                    let language = self.language;
                     return Command::perform(
                         async move {
                             SmartVaults::generate(
@@ -73,6 +96,7 @@ impl State for GenerateState {
                                 || Ok(password),
                                 || Ok(confirm_password),
                                 WordCount::W12, // TODO: let user choose the len.
+                                language,
                                 || Ok(Some(passphrase)),
                                 network,
                             )
@@ -111,6 +135,19 @@ impl State for GenerateState {
             .password()
             .view();
 
+        let language = Column::new()
+            .push(Text::new("Mnemonic language").view())
+            .push(
+                PickList::new(
+                    LanguagePickList::list(false),
+                    Some(LanguagePickList(Some(self.language))),
+                    |language| GenerateMessage::LanguageChanged(language).into(),
+                )
+                .width(Length::Fill)
+                .padding(10),
+            )
+            .spacing(5);
+
         let use_passphrase = Checkbox::new("Use a passphrase", self.use_passphrase, |value| {
             GenerateMessage::UsePassphrase(value).into()
         })
@@ -147,6 +184,7 @@ impl State for GenerateState {
             .push(name)
             .push(password)
             .push(confirm_password)
+            .push(language)
             .push(use_passphrase)
             .push(passphrase)
             .push(if let Some(error) = &self.error {
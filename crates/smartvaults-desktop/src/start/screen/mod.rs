@@ -1,8 +1,12 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::fmt;
+
 use iced::widget::{Column, Container, Row, Scrollable};
 use iced::{Alignment, Element, Length};
+use smartvaults_sdk::core::bips::bip39::Language;
+use smartvaults_sdk::core::mnemonic;
 
 mod generate;
 mod open;
@@ -17,6 +21,30 @@ use super::{Message, Stage};
 use crate::component::{Button, ButtonStyle};
 use crate::theme::icon::SETTING;
 
+/// Mnemonic language (`None` to detect it)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LanguagePickList(Option<Language>);
+
+impl LanguagePickList {
+    fn list(auto_detect: bool) -> Vec<Self> {
+        let mut list: Vec<Self> = Vec::new();
+        if auto_detect {
+            list.push(Self(None));
+        }
+        list.extend(mnemonic::languages().iter().map(|l| Self(Some(*l))));
+        list
+    }
+}
+
+impl fmt::Display for LanguagePickList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(language) => write!(f, "{}", mnemonic::language_name(language)),
+            None => write!(f, "auto-detect"),
+        }
+    }
+}
+
 fn view(column: Column<Message>) -> Element<Message> {
     let content = Container::new(
         column
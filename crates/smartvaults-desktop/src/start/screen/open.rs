@@ -5,7 +5,7 @@ use std::time::Duration;
 
 use iced::widget::{column, row, svg, Column, PickList, Rule, Space};
 use iced::{Alignment, Command, Element, Length};
-use smartvaults_sdk::SmartVaults;
+use smartvaults_sdk::{lock, Error, SmartVaults};
 
 use super::view;
 use crate::component::{Button, ButtonStyle, SpinnerCircular, Text, TextInput};
@@ -20,7 +20,9 @@ pub enum OpenMessage {
     KeychainSelect(String),
     PasswordChanged(String),
     ErrorChanged(Option<String>),
+    StaleLock(String),
     OpenButtonPressed,
+    StealLockButtonPressed,
 }
 
 #[derive(Debug, Default)]
@@ -29,6 +31,7 @@ pub struct OpenState {
     name: Option<String>,
     password: String,
     error: Option<String>,
+    stale_lock: bool,
     loading: bool,
 }
 
@@ -36,6 +39,37 @@ impl OpenState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn open(&mut self, ctx: &Context, steal_lock: bool) -> Command<Message> {
+        if let Some(name) = self.name.clone() {
+            self.loading = true;
+            let network = ctx.network;
+            let password = self.password.clone();
+            return Command::perform(
+                async move {
+                    SmartVaults::open_with_opts(
+                        BASE_PATH.as_path(),
+                        name,
+                        password,
+                        network,
+                        steal_lock,
+                    )
+                    .await
+                },
+                move |res| match res {
+                    Ok(client) => Message::OpenResult(client),
+                    Err(e @ Error::Lock(lock::Error::Stale { .. })) => {
+                        OpenMessage::StaleLock(e.to_string()).into()
+                    }
+                    Err(e) => OpenMessage::ErrorChanged(Some(e.to_string())).into(),
+                },
+            );
+        } else {
+            self.error = Some(String::from("Please, select a keychain"));
+        }
+
+        Command::none()
+    }
 }
 
 impl State for OpenState {
@@ -60,27 +94,16 @@ impl State for OpenState {
                 OpenMessage::PasswordChanged(psw) => self.password = psw,
                 OpenMessage::ErrorChanged(e) => {
                     self.error = e;
+                    self.stale_lock = false;
                     self.loading = false;
                 }
-                OpenMessage::OpenButtonPressed => {
-                    if let Some(name) = self.name.clone() {
-                        self.loading = true;
-                        let network = ctx.network;
-                        let password = self.password.clone();
-                        return Command::perform(
-                            async move {
-                                SmartVaults::open(BASE_PATH.as_path(), name, password, network)
-                                    .await
-                            },
-                            move |res| match res {
-                                Ok(client) => Message::OpenResult(client),
-                                Err(e) => OpenMessage::ErrorChanged(Some(e.to_string())).into(),
-                            },
-                        );
-                    } else {
-                        self.error = Some(String::from("Please, select a keychain"));
-                    }
+                OpenMessage::StaleLock(e) => {
+                    self.error = Some(e);
+                    self.stale_lock = true;
+                    self.loading = false;
                 }
+                OpenMessage::OpenButtonPressed => return self.open(ctx, false),
+                OpenMessage::StealLockButtonPressed => return self.open(ctx, true),
             }
         };
 
@@ -156,6 +179,16 @@ impl State for OpenState {
             } else {
                 row![]
             },
+            if self.stale_lock && !self.loading {
+                row![Button::new()
+                    .text("Steal lock and open")
+                    .style(ButtonStyle::BorderedDanger)
+                    .on_press(OpenMessage::StealLockButtonPressed.into())
+                    .width(Length::Fill)
+                    .view()]
+            } else {
+                row![]
+            },
             if self.loading {
                 row![SpinnerCircular::new()
                     .size(40.0)
@@ -1,14 +1,13 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use std::str::FromStr;
-
-use iced::widget::{Checkbox, Column, Row};
+use iced::widget::{Checkbox, Column, PickList, Row};
 use iced::{Command, Element, Length};
-use smartvaults_sdk::core::bips::bip39::Mnemonic;
+use smartvaults_sdk::core::bips::bip39::Language;
+use smartvaults_sdk::core::mnemonic;
 use smartvaults_sdk::SmartVaults;
 
-use super::view;
+use super::{view, LanguagePickList};
 use crate::component::{rule, Button, ButtonStyle, Text, TextInput};
 use crate::start::{Context, Message, Stage, State};
 use crate::theme::color::DARK_RED;
@@ -20,6 +19,7 @@ pub enum RestoreMessage {
     PasswordChanged(String),
     ConfirmPasswordChanged(String),
     MnemonicChanged(String),
+    LanguageChanged(LanguagePickList),
     UsePassphrase(bool),
     PassphraseChanged(String),
     ErrorChanged(Option<String>),
@@ -32,6 +32,8 @@ pub struct RestoreState {
     password: String,
     confirm_password: String,
     mnemonic: String,
+    /// `None` to detect it
+    language: Option<Language>,
     use_passphrase: bool,
     passphrase: String,
     error: Option<String>,
@@ -55,6 +57,9 @@ impl State for RestoreState {
                 RestoreMessage::PasswordChanged(passwd) => self.password = passwd,
                 RestoreMessage::ConfirmPasswordChanged(passwd) => self.confirm_password = passwd,
                 RestoreMessage::MnemonicChanged(mnemonic) => self.mnemonic = mnemonic,
+                RestoreMessage::LanguageChanged(LanguagePickList(language)) => {
+                    self.language = language
+                }
                 RestoreMessage::UsePassphrase(value) => {
                     self.use_passphrase = value;
                     self.passphrase = String::new();
@@ -69,6 +74,7 @@ impl State for RestoreState {
                     let password = self.password.clone();
                     let confirm_password = self.confirm_password.clone();
                     let mnemonic = self.mnemonic.clone();
+                    let language = self.language;
                     let passphrase = self.passphrase.clone();
                     return Command::perform(
                         async move {
@@ -77,7 +83,7 @@ impl State for RestoreState {
                                 name,
                                 || Ok(password),
                                 || Ok(confirm_password),
-                                || Ok(Mnemonic::from_str(&mnemonic)?),
+                                || Ok(mnemonic::parse(mnemonic, language)?),
                                 || Ok(Some(passphrase)),
                                 network,
                             )
@@ -122,6 +128,19 @@ impl State for RestoreState {
             .placeholder("Mnemonic")
             .view();
 
+        let language = Column::new()
+            .push(Text::new("Mnemonic language").view())
+            .push(
+                PickList::new(
+                    LanguagePickList::list(true),
+                    Some(LanguagePickList(self.language)),
+                    |language| RestoreMessage::LanguageChanged(language).into(),
+                )
+                .width(Length::Fill)
+                .padding(10),
+            )
+            .spacing(5);
+
         let use_passphrase = Checkbox::new("Use a passphrase", self.use_passphrase, |value| {
             RestoreMessage::UsePassphrase(value).into()
         })
@@ -162,6 +181,7 @@ impl State for RestoreState {
             .push(password)
             .push(confirm_password)
             .push(mnemonic)
+            .push(language)
             .push(use_passphrase)
             .push(passphrase)
             .push(if let Some(error) = &self.error {
@@ -27,6 +27,7 @@ pub const PLUS: char = '\u{F64D}';
 pub const HOURGLASS: char = '\u{F41F}';
 pub const NETWORK: char = '\u{F2EE}';
 pub const HISTORY: char = '\u{F292}';
+pub const CLOCK: char = '\u{F293}';
 pub const PATCH_CHECK: char = '\u{F4B6}';
 pub const PATCH_EXCLAMATION: char = '\u{F4B8}';
 pub const GLOBE: char = '\u{F3EE}';
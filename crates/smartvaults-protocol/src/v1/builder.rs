@@ -9,13 +9,17 @@ use smartvaults_core::bitcoin::Network;
 use smartvaults_core::{Policy, Proposal, Signer};
 use thiserror::Error;
 
+use super::capabilities::Capabilities;
 use super::constants::{
-    KEY_AGENT_SIGNALING, KEY_AGENT_SIGNER_OFFERING_KIND, KEY_AGENT_VERIFIED, LABELS_KIND,
-    POLICY_KIND, PROPOSAL_KIND, SHARED_KEY_KIND,
+    APPROVAL_REMINDER_KIND, CAPABILITIES_KIND, INGESTION_REPORT_KIND, KEY_AGENT_SIGNALING,
+    KEY_AGENT_SIGNER_OFFERING_KIND, KEY_AGENT_VERIFIED, LABELS_KIND, POLICY_KIND, PROPOSAL_KIND,
+    SHARED_KEY_KIND,
 };
+use super::ingestion::IngestionReport;
 use super::key_agent::signer::SignerOffering;
 use super::key_agent::verified::VerifiedKeyAgentData;
-use super::util::{Encryption, EncryptionError};
+use super::reminder::ApprovalReminder;
+use super::util::{encrypt_to, Encryption, EncryptionError};
 use super::{Label, Serde};
 
 #[derive(Debug, Error)]
@@ -39,7 +43,7 @@ pub trait SmartVaultsEventBuilder {
         receiver: &PublicKey,
         policy_id: EventId,
     ) -> Result<Event, Error> {
-        let encrypted_shared_key = nip04::encrypt(
+        let encrypted_shared_key = encrypt_to(
             keys.secret_key()?,
             receiver,
             shared_key.secret_key()?.display_secret().to_string(),
@@ -75,6 +79,22 @@ pub trait SmartVaultsEventBuilder {
         Ok(EventBuilder::new(PROPOSAL_KIND, content, tags).to_event(shared_key)?)
     }
 
+    /// Spending proposal replacing (RBF) the `original_id` one
+    fn replacement_proposal(
+        shared_key: &Keys,
+        policy_id: EventId,
+        original_id: EventId,
+        proposal: &Proposal,
+        nostr_pubkeys: &[PublicKey],
+    ) -> Result<Event, Error> {
+        let mut tags: Vec<Tag> = nostr_pubkeys.iter().copied().map(Tag::public_key).collect();
+        // The first event tag must be the policy ID
+        tags.push(Tag::event(policy_id));
+        tags.push(Tag::event(original_id));
+        let content: String = proposal.encrypt_with_keys(shared_key)?;
+        Ok(EventBuilder::new(PROPOSAL_KIND, content, tags).to_event(shared_key)?)
+    }
+
     fn label(
         shared_key: &Keys,
         policy_id: EventId,
@@ -89,6 +109,45 @@ pub trait SmartVaultsEventBuilder {
         Ok(EventBuilder::new(LABELS_KIND, content, tags).to_event(shared_key)?)
     }
 
+    /// Report a proposal that can't be read
+    ///
+    /// The report is encrypted to the proposal author (the shared key), so it can be read by the
+    /// members even if the reporter's shared key doesn't match.
+    fn ingestion_report(
+        keys: &Keys,
+        proposal_author: &PublicKey,
+        report: &IngestionReport,
+        nostr_pubkeys: &[PublicKey],
+    ) -> Result<Event, Error> {
+        let content: String =
+            nip04::encrypt(keys.secret_key()?, proposal_author, report.as_json())?;
+        let mut tags: Vec<Tag> = nostr_pubkeys
+            .iter()
+            .filter(|pk| *pk != proposal_author)
+            .copied()
+            .map(Tag::public_key)
+            .collect();
+        tags.push(Tag::public_key(*proposal_author));
+        tags.push(Tag::event(report.proposal_id));
+        Ok(EventBuilder::new(INGESTION_REPORT_KIND, content, tags).to_event(keys)?)
+    }
+
+    /// Remind the `pending` members to approve a proposal
+    ///
+    /// Encrypted with the shared key of the policy (`shared_public_key`).
+    fn approval_reminder(
+        keys: &Keys,
+        shared_public_key: &PublicKey,
+        reminder: &ApprovalReminder,
+        pending: &[PublicKey],
+    ) -> Result<Event, Error> {
+        let content: String =
+            nip04::encrypt(keys.secret_key()?, shared_public_key, reminder.as_json())?;
+        let mut tags: Vec<Tag> = pending.iter().copied().map(Tag::public_key).collect();
+        tags.push(Tag::event(reminder.proposal_id));
+        Ok(EventBuilder::new(APPROVAL_REMINDER_KIND, content, tags).to_event(keys)?)
+    }
+
     fn key_agent_signaling(keys: &Keys, network: Network) -> Result<Event, Error> {
         let identifier: String = network.magic().to_string();
         Ok(
@@ -97,6 +156,21 @@ pub trait SmartVaultsEventBuilder {
         )
     }
 
+    /// Advertise the supported protocol versions
+    fn capabilities(
+        keys: &Keys,
+        capabilities: &Capabilities,
+        network: Network,
+    ) -> Result<Event, Error> {
+        let identifier: String = network.magic().to_string();
+        Ok(EventBuilder::new(
+            CAPABILITIES_KIND,
+            capabilities.as_json(),
+            [Tag::Identifier(identifier)],
+        )
+        .to_event(keys)?)
+    }
+
     fn signer_offering(
         keys: &Keys,
         signer: &Signer,
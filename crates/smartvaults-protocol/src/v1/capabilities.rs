@@ -0,0 +1,92 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Protocol capabilities advertisement
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::Serde;
+
+/// Latest protocol version supported by this implementation
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Protocol versions supported by a client
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    pub versions: BTreeSet<u8>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            versions: (1..=PROTOCOL_VERSION).collect(),
+        }
+    }
+}
+
+impl Serde for Capabilities {}
+
+impl Capabilities {
+    pub fn new<I>(versions: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        Self {
+            versions: versions.into_iter().collect(),
+        }
+    }
+
+    /// Latest supported protocol version
+    pub fn latest(&self) -> Option<u8> {
+        self.versions.last().copied()
+    }
+
+    pub fn supports(&self, version: u8) -> bool {
+        self.versions.contains(&version)
+    }
+
+    /// Choose the latest protocol version supported by all the clients
+    ///
+    /// Return `None` if the clients don't share any version.
+    pub fn negotiate<'a, I>(capabilities: I) -> Option<u8>
+    where
+        I: IntoIterator<Item = &'a Capabilities>,
+    {
+        let mut iter = capabilities.into_iter();
+        let mut common: BTreeSet<u8> = iter.next()?.versions.clone();
+        for c in iter {
+            common = common.intersection(&c.versions).copied().collect();
+        }
+        common.last().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        let legacy = Capabilities::new([1]);
+        let latest = Capabilities::new([1, 2]);
+        assert_eq!(Capabilities::negotiate([&latest, &latest]), Some(2));
+        assert_eq!(Capabilities::negotiate([&latest, &legacy]), Some(1));
+        assert_eq!(
+            Capabilities::negotiate([&latest, &Capabilities::new([3])]),
+            None
+        );
+        assert_eq!(Capabilities::negotiate([]), None);
+    }
+
+    #[test]
+    fn test_serde() {
+        let capabilities = Capabilities::default();
+        assert_eq!(capabilities.as_json(), "{\"versions\":[1]}");
+        assert_eq!(
+            Capabilities::from_json(capabilities.as_json()).unwrap(),
+            capabilities
+        );
+    }
+}
@@ -25,10 +25,13 @@ pub const APPROVED_PROPOSAL_KIND: Kind = Kind::Custom(9291);
 pub const COMPLETED_PROPOSAL_KIND: Kind = Kind::Custom(9292);
 pub const SIGNERS_KIND: Kind = Kind::Custom(9294);
 pub const SHARED_SIGNERS_KIND: Kind = Kind::Custom(9295);
+pub const INGESTION_REPORT_KIND: Kind = Kind::Custom(9296);
+pub const APPROVAL_REMINDER_KIND: Kind = Kind::Custom(9297);
 pub const LABELS_KIND: Kind = Kind::ParameterizedReplaceable(32121);
 pub const KEY_AGENT_SIGNER_OFFERING_KIND: Kind = Kind::ParameterizedReplaceable(32122);
 pub const KEY_AGENT_VERIFIED: Kind = Kind::ParameterizedReplaceable(32123);
 pub const KEY_AGENT_SIGNALING: Kind = Kind::ParameterizedReplaceable(32124);
+pub const CAPABILITIES_KIND: Kind = Kind::ParameterizedReplaceable(32125);
 
 // Expirations
 pub const APPROVED_PROPOSAL_EXPIRATION: Duration = Duration::from_secs(60 * 60 * 24 * 7);
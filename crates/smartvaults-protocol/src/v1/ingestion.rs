@@ -0,0 +1,97 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Ingestion problem reports
+//!
+//! When a member can't read a proposal (i.e. shared key mismatch or corrupted content), its client
+//! report the problem back to the policy. The report contains only a reason code, never the content.
+
+use core::fmt;
+
+use nostr::EventId;
+use serde::{Deserialize, Serialize};
+
+use super::util::EncryptionError;
+use super::Serde;
+
+/// Why a proposal can't be read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IngestionProblem {
+    /// Content can't be decrypted (shared key mismatch or corrupted ciphertext)
+    Decryption,
+    /// Content decrypted but can't be deserialized (corrupted or unsupported format)
+    Deserialization,
+    /// Invalid shared key
+    InvalidKey,
+    /// Unknown reason (sent by newer clients)
+    #[serde(other)]
+    Other,
+}
+
+impl fmt::Display for IngestionProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_code())
+    }
+}
+
+impl From<&EncryptionError> for IngestionProblem {
+    fn from(e: &EncryptionError) -> Self {
+        match e {
+            EncryptionError::Aes(..) | EncryptionError::NIP04(..) | EncryptionError::NIP44(..) => {
+                Self::Decryption
+            }
+            EncryptionError::JSON(..) => Self::Deserialization,
+            EncryptionError::Keys(..) | EncryptionError::Secp256k1(..) => Self::InvalidKey,
+        }
+    }
+}
+
+impl IngestionProblem {
+    /// Reason code
+    pub fn as_code(&self) -> &'static str {
+        match self {
+            Self::Decryption => "decryption",
+            Self::Deserialization => "deserialization",
+            Self::InvalidKey => "invalid_key",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Report of a proposal that a member can't read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestionReport {
+    pub proposal_id: EventId,
+    pub reason: IngestionProblem,
+}
+
+impl Serde for IngestionReport {}
+
+impl IngestionReport {
+    pub fn new(proposal_id: EventId, reason: IngestionProblem) -> Self {
+        Self {
+            proposal_id,
+            reason,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_reason() {
+        let report = IngestionReport::new(EventId::all_zeros(), IngestionProblem::Decryption);
+        let json = report.as_json();
+        assert_eq!(IngestionReport::from_json(json).unwrap(), report);
+
+        let json = format!(
+            "{{\"proposal_id\":\"{}\",\"reason\":\"future_reason\"}}",
+            EventId::all_zeros()
+        );
+        let report = IngestionReport::from_json(json).unwrap();
+        assert_eq!(report.reason, IngestionProblem::Other);
+    }
+}
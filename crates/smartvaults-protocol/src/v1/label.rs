@@ -189,4 +189,16 @@ mod test {
             String::from("f225b2d56e21560d31ef180f5ff144c2")
         );
     }
+
+    #[test]
+    fn test_serde() {
+        let txid =
+            Txid::from_str("3faa6bff53689b9763ed77fc693831a14030977f0ea79411b1132d27135eb1a9")
+                .unwrap();
+        let label = Label::utxo(OutPoint::new(txid, 1), "Cold storage");
+        // v1 labels are shared with other members: must be serialized without envelope
+        let json = label.as_json();
+        assert!(!json.contains("\"v\":"));
+        assert_eq!(Label::from_json(json).unwrap(), label);
+    }
 }
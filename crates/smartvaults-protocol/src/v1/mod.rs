@@ -2,16 +2,22 @@
 // Distributed under the MIT software license
 
 pub mod builder;
+pub mod capabilities;
 pub mod constants;
+pub mod ingestion;
 pub mod key_agent;
 pub mod label;
 mod network;
+pub mod reminder;
 pub mod util;
 
 pub use self::builder::{Error as SmartVaultsEventBuilderError, SmartVaultsEventBuilder};
+pub use self::capabilities::{Capabilities, PROTOCOL_VERSION};
+pub use self::ingestion::{IngestionProblem, IngestionReport};
 pub use self::key_agent::{
     BasisPoints, DeviceType, KeyAgentMetadata, Price, SignerOffering, Temperature,
     VerifiedKeyAgentData, VerifiedKeyAgents,
 };
 pub use self::label::{Label, LabelData, LabelKind};
-pub use self::util::{Encryption, EncryptionError, Serde, SerdeSer};
+pub use self::reminder::ApprovalReminder;
+pub use self::util::{decrypt_from, encrypt_to, Encryption, EncryptionError, Serde, SerdeSer};
@@ -0,0 +1,28 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Approval reminders
+//!
+//! Sent by the client of the proposer to the members that didn't approve a proposal yet, according
+//! to the reminder settings of the policy.
+
+use nostr::EventId;
+use serde::{Deserialize, Serialize};
+
+use super::Serde;
+
+/// Reminder to approve a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalReminder {
+    pub proposal_id: EventId,
+    /// Reminder number (starts from `1`)
+    pub count: u16,
+}
+
+impl Serde for ApprovalReminder {}
+
+impl ApprovalReminder {
+    pub fn new(proposal_id: EventId, count: u16) -> Self {
+        Self { proposal_id, count }
+    }
+}
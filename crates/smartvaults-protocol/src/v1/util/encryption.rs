@@ -1,7 +1,18 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use nostr::{key, util, Keys};
+//! Encryption
+//!
+//! New policies, proposals, approvals and shared keys are encrypted with NIP-44 and wrapped in a
+//! `{"v":1,"payload":"..."}` envelope. The payloads too big for NIP-44 (i.e. big PSBTs) are split in
+//! chunks: the next ones are in `"chunks":[...]`. The other kinds keep the legacy scheme (AES with the
+//! shared key of the keys).
+//! Payloads without envelope are legacy (NIP-04 or AES) and are still decrypted.
+
+use nostr::nips::nip04;
+use nostr::nips::nip44::{self, Version};
+use nostr::{key, util, Keys, PublicKey};
+use serde::{Deserialize, Serialize};
 use smartvaults_core::bdk::wallet::ChangeSet;
 use smartvaults_core::bitcoin::secp256k1::SecretKey;
 use smartvaults_core::crypto::aes;
@@ -22,9 +33,110 @@ pub enum Error {
     Keys(#[from] key::Error),
     #[error(transparent)]
     Secp256k1(#[from] secp256k1::Error),
+    #[error(transparent)]
+    NIP04(#[from] nip04::Error),
+    #[error(transparent)]
+    NIP44(#[from] nip44::Error),
+}
+
+/// Envelope version of NIP-44 (v2) payloads
+const NIP44_ENVELOPE_VERSION: u8 = 1;
+/// Max plaintext size of NIP-44 (v2)
+const NIP44_MAX_PLAINTEXT_LEN: usize = 65535;
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    v: u8,
+    payload: String,
+    /// Next chunks of the plaintexts over [`NIP44_MAX_PLAINTEXT_LEN`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chunks: Vec<String>,
+}
+
+impl Envelope {
+    fn decrypt(self, secret_key: &SecretKey, public_key: &PublicKey) -> Result<String, Error> {
+        let mut plaintext: String = nip44::decrypt(secret_key, public_key, self.payload)?;
+        for chunk in self.chunks.into_iter() {
+            plaintext.push_str(&nip44::decrypt(secret_key, public_key, chunk)?);
+        }
+        Ok(plaintext)
+    }
+}
+
+/// Get the NIP-44 envelope, if `content` is wrapped in it
+fn nip44_envelope(content: &[u8]) -> Option<Envelope> {
+    match serde_json::from_slice::<Envelope>(content) {
+        Ok(envelope) if envelope.v == NIP44_ENVELOPE_VERSION => Some(envelope),
+        _ => None,
+    }
+}
+
+/// Split `content` in chunks of at most [`NIP44_MAX_PLAINTEXT_LEN`] bytes, at char boundaries
+fn split_chunks(content: &str) -> Vec<&str> {
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut rest: &str = content;
+    while rest.len() > NIP44_MAX_PLAINTEXT_LEN {
+        let mut mid: usize = NIP44_MAX_PLAINTEXT_LEN;
+        while !rest.is_char_boundary(mid) {
+            mid -= 1;
+        }
+        let (chunk, next) = rest.split_at(mid);
+        chunks.push(chunk);
+        rest = next;
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// Encrypt `content` for `public_key` (NIP-44)
+///
+/// Content over the NIP-44 max size is split in chunks.
+pub fn encrypt_to<T>(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    content: T,
+) -> Result<String, Error>
+where
+    T: AsRef<str>,
+{
+    let mut payloads: Vec<String> = Vec::new();
+    for chunk in split_chunks(content.as_ref()).into_iter() {
+        payloads.push(nip44::encrypt(secret_key, public_key, chunk, Version::V2)?);
+    }
+    let payload: String = payloads.remove(0);
+    Ok(serde_json::json!(Envelope {
+        v: NIP44_ENVELOPE_VERSION,
+        payload,
+        chunks: payloads,
+    })
+    .to_string())
+}
+
+/// Decrypt `content` received from `public_key`
+///
+/// Try NIP-44 first and fallback to NIP-04 (legacy events).
+pub fn decrypt_from<T>(
+    secret_key: &SecretKey,
+    public_key: &PublicKey,
+    content: T,
+) -> Result<String, Error>
+where
+    T: AsRef<[u8]>,
+{
+    match nip44_envelope(content.as_ref()) {
+        Some(envelope) => envelope.decrypt(secret_key, public_key),
+        None => Ok(nip04::decrypt(
+            secret_key,
+            public_key,
+            String::from_utf8_lossy(content.as_ref()),
+        )?),
+    }
 }
 
 pub trait Encryption: Serde {
+    /// Encrypt with NIP-44 in [`Encryption::encrypt_with_keys`]
+    const NIP44: bool = false;
+
     /// Encrypt
     fn encrypt(&self, key: [u8; 32]) -> String {
         aes::encrypt(key, self.as_json())
@@ -39,19 +151,35 @@ pub trait Encryption: Serde {
         Ok(deserialize(data)?)
     }
 
-    /// Encrypt
+    /// Encrypt with NIP-44 (see [`encrypt_to`]) if [`Encryption::NIP44`], otherwise with AES and
+    /// the shared key of `keys`
     fn encrypt_with_keys(&self, keys: &Keys) -> Result<String, Error> {
-        let key: [u8; 32] = util::generate_shared_key(keys.secret_key()?, &keys.public_key());
-        Ok(self.encrypt(key))
+        let json: String = self.as_json();
+        if Self::NIP44 {
+            encrypt_to(keys.secret_key()?, &keys.public_key(), json)
+        } else {
+            let key: [u8; 32] = util::generate_shared_key(keys.secret_key()?, &keys.public_key());
+            Ok(aes::encrypt(key, json))
+        }
     }
 
-    /// Decrypt
+    /// Decrypt both NIP-44 and legacy payloads
     fn decrypt_with_keys<T>(keys: &Keys, content: T) -> Result<Self, Error>
     where
         T: AsRef<[u8]>,
     {
-        let key: [u8; 32] = util::generate_shared_key(keys.secret_key()?, &keys.public_key());
-        Self::decrypt(key, content)
+        let content: &[u8] = content.as_ref();
+        match nip44_envelope(content) {
+            Some(envelope) => {
+                let json: String = envelope.decrypt(keys.secret_key()?, &keys.public_key())?;
+                Ok(deserialize(json.into_bytes())?)
+            }
+            None => {
+                let key: [u8; 32] =
+                    util::generate_shared_key(keys.secret_key()?, &keys.public_key());
+                Self::decrypt(key, content)
+            }
+        }
     }
 }
 
@@ -61,13 +189,19 @@ impl Encryption for SecretKey {}
 impl Serde for ChangeSet {}
 
 impl Serde for Policy {}
-impl Encryption for Policy {}
+impl Encryption for Policy {
+    const NIP44: bool = true;
+}
 
 impl Serde for Proposal {}
-impl Encryption for Proposal {}
+impl Encryption for Proposal {
+    const NIP44: bool = true;
+}
 
 impl Serde for ApprovedProposal {}
-impl Encryption for ApprovedProposal {}
+impl Encryption for ApprovedProposal {
+    const NIP44: bool = true;
+}
 
 impl Serde for CompletedProposal {}
 impl Encryption for CompletedProposal {}
@@ -77,3 +211,117 @@ impl Encryption for Signer {}
 
 impl Serde for SharedSigner {}
 impl Encryption for SharedSigner {}
+
+#[cfg(test)]
+mod tests {
+    use core::str::FromStr;
+
+    use smartvaults_core::bitcoin::absolute::LockTime;
+    use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+    use smartvaults_core::bitcoin::Transaction;
+    use smartvaults_core::miniscript::Descriptor;
+
+    use super::*;
+
+    const SECRET_KEY_A: &str = "6b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e";
+    const SECRET_KEY_B: &str = "7b911fd37cdf5c81d4c0adb1ab7fa822ed253ab0ad9aa18d77257c88b29b718e";
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))";
+
+    fn proposal(message: String) -> Proposal {
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        })
+        .unwrap();
+        Proposal::proof_of_reserve(Descriptor::from_str(DESCRIPTOR).unwrap(), message, psbt)
+    }
+
+    #[test]
+    fn test_nip44_roundtrip() {
+        let keys = Keys::new(SecretKey::from_str(SECRET_KEY_A).unwrap());
+        let proposal = proposal(String::from("test"));
+        let encrypted: String = proposal.encrypt_with_keys(&keys).unwrap();
+        assert!(nip44_envelope(encrypted.as_bytes()).is_some());
+        assert_eq!(
+            Proposal::decrypt_with_keys(&keys, encrypted).unwrap(),
+            proposal
+        );
+
+        // Kinds not moved to NIP-44
+        let secret_key = SecretKey::from_str(SECRET_KEY_B).unwrap();
+        let encrypted: String = secret_key.encrypt_with_keys(&keys).unwrap();
+        assert!(nip44_envelope(encrypted.as_bytes()).is_none());
+        assert_eq!(
+            SecretKey::decrypt_with_keys(&keys, encrypted).unwrap(),
+            secret_key
+        );
+
+        let a = Keys::new(SecretKey::from_str(SECRET_KEY_A).unwrap());
+        let b = Keys::new(SecretKey::from_str(SECRET_KEY_B).unwrap());
+        let encrypted: String =
+            encrypt_to(a.secret_key().unwrap(), &b.public_key(), "shared key").unwrap();
+        assert_eq!(
+            decrypt_from(b.secret_key().unwrap(), &a.public_key(), encrypted).unwrap(),
+            "shared key"
+        );
+    }
+
+    #[test]
+    fn test_over_nip44_max_size_roundtrip() {
+        let keys = Keys::new(SecretKey::from_str(SECRET_KEY_A).unwrap());
+        let multibyte = proposal("€".repeat(50_000));
+        let proposal = proposal("a".repeat(70_000));
+        assert!(proposal.as_json().len() > NIP44_MAX_PLAINTEXT_LEN);
+
+        // Still NIP-44, in chunks
+        let encrypted: String = proposal.encrypt_with_keys(&keys).unwrap();
+        let envelope = nip44_envelope(encrypted.as_bytes()).unwrap();
+        assert_eq!(envelope.chunks.len(), 1);
+        assert_eq!(
+            Proposal::decrypt_with_keys(&keys, encrypted).unwrap(),
+            proposal
+        );
+
+        // Multi-byte chars across the chunk boundaries
+        let encrypted: String = multibyte.encrypt_with_keys(&keys).unwrap();
+        let envelope = nip44_envelope(encrypted.as_bytes()).unwrap();
+        assert_eq!(envelope.chunks.len(), 2);
+        assert_eq!(
+            Proposal::decrypt_with_keys(&keys, encrypted).unwrap(),
+            multibyte
+        );
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        assert_eq!(split_chunks("abc"), vec!["abc"]);
+
+        let content: String = format!("a{}", "€".repeat(30_000));
+        let chunks: Vec<&str> = split_chunks(&content);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks.iter().all(|c| c.len() <= NIP44_MAX_PLAINTEXT_LEN));
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn test_legacy_fixtures() {
+        let a = Keys::new(SecretKey::from_str(SECRET_KEY_A).unwrap());
+        let b = Keys::new(SecretKey::from_str(SECRET_KEY_B).unwrap());
+
+        // Encrypted with the shared key of `b` (AES)
+        let legacy = "K9CxwEjbw84rZy/7M0CSrNMpTgVAK4c3u1ODoLIv0bQGkcLOOuKUORkhgkp+1EYVIlDazRkBlV+EMTXwVA7H+y/hNXs/nDVxnAeJelDGRi4=?iv=AAECAwQFBgcICQoLDA0ODw==";
+        assert_eq!(
+            SecretKey::decrypt_with_keys(&b, legacy).unwrap(),
+            SecretKey::from_str(SECRET_KEY_A).unwrap()
+        );
+
+        // Shared key sent from `a` to `b` (NIP-04)
+        let legacy = "JVnyxBnLD+sYo9EciEmqh0r8b2NwpU4DrUkZzQOB9x0bMnin6BVkj43QSjwHKHqjg3bqjVqNBaPOHE8YVzlKh9EhIxmIV8k6aWtSB9mDUkc=?iv=EBESExQVFhcYGRobHB0eHw==";
+        assert_eq!(
+            decrypt_from(b.secret_key().unwrap(), &a.public_key(), legacy).unwrap(),
+            SECRET_KEY_B
+        );
+    }
+}
@@ -1,12 +1,38 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use serde::de::DeserializeOwned;
-use serde::Serialize;
+//! Serialization
+//!
+//! Payloads newer than `v1` are wrapped in a `{"v": <version>, "data": {...}}` envelope.
+//! Unversioned payloads are `v1`, so types must keep deserializing them (i.e. new fields with a
+//! default value).
+
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
 pub use serde_json::Error;
 use smartvaults_core::bdk::descriptor::policy::{Policy as SpendingPolicy, SatisfiableItem};
 pub use smartvaults_core::util::serde::*;
 
+#[derive(Serialize)]
+struct EnvelopeRef<'a, T> {
+    v: u8,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Envelope<T> {
+    #[allow(dead_code)]
+    v: u8,
+    data: T,
+}
+
+/// Detect the envelope without deserializing the data
+#[derive(Deserialize)]
+struct EnvelopeProbe {
+    v: Option<u8>,
+    data: Option<IgnoredAny>,
+}
+
 pub trait SerdeSer: Sized + Serialize {
     /// Serialize to `JSON` string
     fn as_json(&self) -> String {
@@ -15,19 +41,123 @@ pub trait SerdeSer: Sized + Serialize {
 }
 
 pub trait Serde: Sized + Serialize + DeserializeOwned {
+    /// Payload version (`v1` payloads are serialized without envelope)
+    const VERSION: u8 = 1;
+
     /// Deserialize from `JSON` string
+    ///
+    /// Unversioned payloads are deserialized as `v1`. Payloads of newer versions are
+    /// deserialized as the current one (unknown fields are ignored).
     fn from_json<S>(json: S) -> Result<Self, Error>
     where
         S: Into<String>,
     {
-        serde_json::from_str(&json.into())
+        let json: String = json.into();
+        match serde_json::from_str::<EnvelopeProbe>(&json) {
+            Ok(EnvelopeProbe {
+                v: Some(..),
+                data: Some(..),
+            }) => Ok(serde_json::from_str::<Envelope<Self>>(&json)?.data),
+            _ => serde_json::from_str(&json),
+        }
     }
 
     /// Serialize to `JSON` string
     fn as_json(&self) -> String {
-        serde_json::json!(self).to_string()
+        if Self::VERSION <= 1 {
+            serde_json::json!(self).to_string()
+        } else {
+            serde_json::json!(EnvelopeRef {
+                v: Self::VERSION,
+                data: self,
+            })
+            .to_string()
+        }
     }
 }
 
 impl SerdeSer for SpendingPolicy {}
 impl SerdeSer for SatisfiableItem {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct V1 {
+        name: String,
+    }
+
+    impl Serde for V1 {}
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct V2 {
+        name: String,
+        #[serde(default)]
+        description: Option<String>,
+        /// Unknown fields (preserved)
+        #[serde(flatten)]
+        extra: BTreeMap<String, Value>,
+    }
+
+    impl Serde for V2 {
+        const VERSION: u8 = 2;
+    }
+
+    #[test]
+    fn test_versioned_roundtrip() {
+        let v1 = V1 {
+            name: String::from("test"),
+        };
+        let json = v1.as_json();
+        assert_eq!(json, r#"{"name":"test"}"#);
+        assert_eq!(V1::from_json(json).unwrap(), v1);
+
+        let v2 = V2 {
+            name: String::from("test"),
+            description: Some(String::from("desc")),
+            extra: BTreeMap::new(),
+        };
+        let json = v2.as_json();
+        assert_eq!(
+            serde_json::from_str::<Value>(&json).unwrap()["v"],
+            Value::from(2)
+        );
+        assert_eq!(V2::from_json(json).unwrap(), v2);
+    }
+
+    #[test]
+    fn test_backward_compatibility() {
+        // Unversioned payload read as v1
+        let v2 = V2::from_json(
+            V1 {
+                name: String::from("test"),
+            }
+            .as_json(),
+        )
+        .unwrap();
+        assert_eq!(v2.name, "test");
+        assert_eq!(v2.description, None);
+    }
+
+    #[test]
+    fn test_forward_compatibility() {
+        // Newer payload read by an older client
+        let json = r#"{"v":3,"data":{"name":"test","description":null,"tags":["a"]}}"#;
+        assert_eq!(
+            V1::from_json(json).unwrap(),
+            V1 {
+                name: String::from("test")
+            }
+        );
+
+        // Unknown fields preserved
+        let v2 = V2::from_json(json).unwrap();
+        assert_eq!(v2.extra.get("tags"), Some(&serde_json::json!(["a"])));
+        assert!(v2.as_json().contains(r#""tags":["a"]"#));
+    }
+}
@@ -0,0 +1,16 @@
+PRAGMA user_version = 4; -- Schema version
+
+-- Events that are currently being handled
+-- A row left here at startup means that the app crashed while handling the event: only the ID is
+-- stored, the event is read from the nostr database
+CREATE TABLE IF NOT EXISTS processing_events (
+    event_id BLOB PRIMARY KEY NOT NULL
+);
+
+-- Quarantined (poison) events
+CREATE TABLE IF NOT EXISTS dead_letter_events (
+    event_id BLOB PRIMARY KEY NOT NULL,
+    event BLOB,
+    reason TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
@@ -0,0 +1,7 @@
+PRAGMA user_version = 5; -- Schema version
+
+-- Default coin selection strategy per policy
+CREATE TABLE IF NOT EXISTS coin_selection (
+    policy_id BLOB PRIMARY KEY NOT NULL,
+    strategy TEXT NOT NULL
+);
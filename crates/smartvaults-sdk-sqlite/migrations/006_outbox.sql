@@ -0,0 +1,13 @@
+PRAGMA user_version = 6; -- Schema version
+
+-- Events not published to a relay, retried by the background drainer
+CREATE TABLE IF NOT EXISTS outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id BLOB NOT NULL,
+    relay_url TEXT NOT NULL,
+    event BLOB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    timestamp BIGINT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS outbox_index ON outbox(event_id,relay_url);
@@ -0,0 +1,34 @@
+PRAGMA user_version = 7; -- Schema version
+
+-- Append-only log of the signing operations
+-- Every entry commits to the previous one (`hash = hmac(key, prev_hash | entry)`, with a key
+-- derived from the account key)
+CREATE TABLE IF NOT EXISTS signing_audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp BIGINT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    object_id TEXT NOT NULL,
+    digest TEXT NOT NULL,
+    prev_hash TEXT NOT NULL,
+    hash TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS signing_audit_fingerprint_index ON signing_audit(fingerprint,timestamp);
+
+-- Head of the hash chain (used to detect truncation)
+CREATE TABLE IF NOT EXISTS signing_audit_head (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    entries BIGINT NOT NULL,
+    hash TEXT NOT NULL
+);
+
+CREATE TRIGGER IF NOT EXISTS signing_audit_no_update BEFORE UPDATE ON signing_audit
+BEGIN
+    SELECT RAISE(ABORT, 'signing audit log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS signing_audit_no_delete BEFORE DELETE ON signing_audit
+BEGIN
+    SELECT RAISE(ABORT, 'signing audit log is append-only');
+END;
@@ -0,0 +1,8 @@
+PRAGMA user_version = 8; -- Schema version
+
+-- Proposals (or completed proposals) replaced by a confirmed RBF fee bump
+CREATE TABLE IF NOT EXISTS superseded (
+    proposal_id BLOB PRIMARY KEY NOT NULL,
+    replaced_by BLOB NOT NULL,
+    timestamp BIGINT NOT NULL
+);
@@ -0,0 +1,12 @@
+PRAGMA user_version = 9; -- Schema version
+
+-- Outgoing transactions classified by matching completed proposal
+CREATE TABLE IF NOT EXISTS outgoing_txs (
+    policy_id BLOB NOT NULL,
+    txid BLOB NOT NULL,
+    amount BIGINT NOT NULL,
+    expected BOOLEAN NOT NULL,
+    acknowledged BOOLEAN NOT NULL DEFAULT FALSE,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY (policy_id, txid)
+);
@@ -0,0 +1,8 @@
+PRAGMA user_version = 10; -- Schema version
+
+-- Protocol versions advertised by the peers
+CREATE TABLE IF NOT EXISTS peer_capabilities (
+    public_key BLOB PRIMARY KEY NOT NULL,
+    capabilities TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
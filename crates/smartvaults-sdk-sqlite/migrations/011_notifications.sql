@@ -0,0 +1,12 @@
+PRAGMA user_version = 11; -- Schema version
+
+-- Notifications
+CREATE TABLE IF NOT EXISTS notifications (
+    kind TEXT NOT NULL,
+    event_id BLOB NOT NULL,
+    timestamp BIGINT NOT NULL,
+    seen BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (kind, event_id)
+);
+
+CREATE INDEX IF NOT EXISTS notifications_seen_index ON notifications(seen);
@@ -0,0 +1,12 @@
+PRAGMA user_version = 12; -- Schema version
+
+-- Cached verdicts of the Proof of Reserve completed proposals
+CREATE TABLE IF NOT EXISTS proof_verifications (
+    completed_proposal_id BLOB PRIMARY KEY NOT NULL,
+    policy_id BLOB NOT NULL,
+    spendable BIGINT,
+    error TEXT,
+    height INTEGER NOT NULL,
+    utxos_digest TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
@@ -0,0 +1,11 @@
+PRAGMA user_version = 13; -- Schema version
+
+-- Wallet policies registered on hardware devices (ex. Ledger)
+CREATE TABLE IF NOT EXISTS hw_registrations (
+    policy_id BLOB NOT NULL,
+    fingerprint TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    hmac BLOB NOT NULL,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY(policy_id, fingerprint, device_id)
+);
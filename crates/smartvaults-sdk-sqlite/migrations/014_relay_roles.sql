@@ -0,0 +1,5 @@
+PRAGMA user_version = 14; -- Schema version
+
+-- Relay read/write policy
+ALTER TABLE relays ADD COLUMN read BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE relays ADD COLUMN write BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,9 @@
+PRAGMA user_version = 15; -- Schema version
+
+-- Published profile metadata versions
+CREATE TABLE IF NOT EXISTS profile_history (
+    version INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id BLOB NOT NULL,
+    metadata TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
@@ -0,0 +1,10 @@
+PRAGMA user_version = 16; -- Schema version
+
+-- Sent reports of proposals that can't be read (rate limit)
+CREATE TABLE IF NOT EXISTS ingestion_reports (
+    proposal_id BLOB PRIMARY KEY NOT NULL,
+    reason TEXT NOT NULL,
+    timestamp BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS ingestion_reports_timestamp_index ON ingestion_reports(timestamp);
@@ -0,0 +1,10 @@
+PRAGMA user_version = 17; -- Schema version
+
+-- Unsubmitted forms (JSON), by screen and policy (empty if none)
+CREATE TABLE IF NOT EXISTS drafts (
+    screen TEXT NOT NULL,
+    policy_id TEXT NOT NULL DEFAULT '',
+    data TEXT NOT NULL,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY (screen, policy_id)
+);
@@ -0,0 +1,8 @@
+PRAGMA user_version = 18; -- Schema version
+
+-- Policy birthdays: creation timestamp and user-supplied height (imported descriptors, corrections)
+CREATE TABLE IF NOT EXISTS birthdays (
+    policy_id BLOB PRIMARY KEY NOT NULL,
+    timestamp BIGINT,
+    height INTEGER
+);
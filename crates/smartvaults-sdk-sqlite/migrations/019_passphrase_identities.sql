@@ -0,0 +1,9 @@
+PRAGMA user_version = 19; -- Schema version
+
+-- Identities derived from the seed with the BIP39 passphrases used (the passphrase is never stored)
+CREATE TABLE IF NOT EXISTS passphrase_identities (
+    fingerprint TEXT PRIMARY KEY NOT NULL,
+    public_key BLOB NOT NULL,
+    passphrase_hash TEXT,
+    last_used BIGINT NOT NULL
+);
@@ -0,0 +1,16 @@
+PRAGMA user_version = 20; -- Schema version
+
+-- Descriptor and network the wallet state of each policy was built for
+CREATE TABLE IF NOT EXISTS wallet_integrity (
+    policy_id BLOB PRIMARY KEY NOT NULL,
+    descriptor_hash TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    network TEXT NOT NULL
+);
+
+-- Wallet states set aside after an integrity mismatch (the wallet is rescanned from scratch)
+CREATE TABLE IF NOT EXISTS quarantined_timechain (
+    descriptor_hash TEXT NOT NULL,
+    data BLOB NOT NULL,
+    timestamp BIGINT NOT NULL
+);
@@ -0,0 +1,7 @@
+PRAGMA user_version = 21; -- Schema version
+
+-- Application state (ex. last version the changelog was seen for)
+CREATE TABLE IF NOT EXISTS app_state (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL
+);
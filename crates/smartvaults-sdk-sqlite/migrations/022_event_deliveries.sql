@@ -0,0 +1,12 @@
+PRAGMA user_version = 22; -- Schema version
+
+-- Relays each recipient-addressed event (ex. shared signer) was sent to
+CREATE TABLE IF NOT EXISTS event_deliveries (
+    event_id BLOB NOT NULL,
+    recipient BLOB NOT NULL,
+    relay_url TEXT NOT NULL,
+    recipient_relay BOOLEAN NOT NULL,
+    error TEXT,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY(event_id, recipient, relay_url)
+);
@@ -0,0 +1,13 @@
+PRAGMA user_version = 23; -- Schema version
+
+-- Fees paid by the outgoing transactions of the policies
+CREATE TABLE IF NOT EXISTS tx_fees (
+    policy_id BLOB NOT NULL,
+    txid BLOB NOT NULL,
+    fee BIGINT NOT NULL,
+    estimated BOOLEAN NOT NULL,
+    timestamp BIGINT NOT NULL,
+    PRIMARY KEY (policy_id, txid)
+);
+
+CREATE INDEX IF NOT EXISTS tx_fees_policy_timestamp_index ON tx_fees(policy_id, timestamp);
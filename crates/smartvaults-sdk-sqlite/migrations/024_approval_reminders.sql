@@ -0,0 +1,22 @@
+PRAGMA user_version = 24; -- Schema version
+
+-- Approval reminders settings of the policies
+CREATE TABLE IF NOT EXISTS reminder_settings (
+    policy_id BLOB PRIMARY KEY NOT NULL,
+    interval BIGINT NOT NULL,
+    max_reminders INTEGER NOT NULL
+);
+
+-- Proposals created by me, with the reminders sent to the pending approvers
+CREATE TABLE IF NOT EXISTS approval_reminders (
+    proposal_id BLOB PRIMARY KEY NOT NULL,
+    policy_id BLOB NOT NULL,
+    created_at BIGINT NOT NULL,
+    sent INTEGER NOT NULL DEFAULT 0,
+    last_sent_at BIGINT
+);
+
+-- Proposals with muted reminders
+CREATE TABLE IF NOT EXISTS muted_reminders (
+    proposal_id BLOB PRIMARY KEY NOT NULL
+);
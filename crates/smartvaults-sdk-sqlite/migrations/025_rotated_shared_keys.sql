@@ -0,0 +1,9 @@
+PRAGMA user_version = 25; -- Schema version
+
+-- Shared keys replaced by a rotation, to keep reading the old events of the policy
+CREATE TABLE IF NOT EXISTS rotated_shared_keys (
+    policy_id BLOB PRIMARY KEY NOT NULL,
+    shared_key BLOB NOT NULL,
+    new_policy_id BLOB NOT NULL,
+    rotated_at BIGINT NOT NULL
+);
@@ -0,0 +1,11 @@
+PRAGMA user_version = 26; -- Schema version
+
+-- Retry metadata of the pending events (i.e. received before their shared key)
+-- A `failed` event is not retried anymore, unless forced
+CREATE TABLE IF NOT EXISTS pending_event_retries (
+    event_id BLOB PRIMARY KEY NOT NULL,
+    event BLOB NOT NULL,
+    retries INTEGER NOT NULL,
+    next_retry_at BIGINT NOT NULL,
+    failed BOOLEAN NOT NULL DEFAULT FALSE
+);
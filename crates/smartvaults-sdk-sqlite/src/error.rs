@@ -61,9 +61,24 @@ pub enum Error {
     /// Policy error
     #[error(transparent)]
     Policy(#[from] policy::Error),
+    /// Coin selection error
+    #[error(transparent)]
+    CoinSelection(#[from] smartvaults_core::types::UnknownCoinSelection),
     /// Label error
     #[error(transparent)]
     Label(#[from] smartvaults_protocol::v1::label::Error),
+    /// BIP32 error
+    #[error(transparent)]
+    BIP32(#[from] bitcoin::bip32::Error),
+    /// Unknown signing kind
+    #[error("unknown signing kind: {0}")]
+    UnknownSigningKind(String),
+    /// Unknown notification kind
+    #[error("unknown notification kind: {0}")]
+    UnknownNotificationKind(String),
+    /// Unknown network
+    #[error("unknown network: {0}")]
+    UnknownNetwork(String),
     /// Not found
     #[error("sqlite: {0} not found")]
     NotFound(String),
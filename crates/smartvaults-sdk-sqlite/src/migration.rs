@@ -9,7 +9,7 @@ use rusqlite::Connection;
 use super::Error;
 
 /// Latest database version
-pub const DB_VERSION: usize = 3;
+pub const DB_VERSION: usize = 26;
 
 /// Startup DB Pragmas
 pub const STARTUP_SQL: &str = r##"
@@ -61,20 +61,100 @@ pub(crate) async fn run(conn: &Object) -> Result<(), Error> {
                     curr_version = mig_2_to_3(conn)?;
                 }
 
-                // if curr_version == 3 {
-                // curr_version = mig_3_to_4(conn)?;
-                // }
-                //
-                // if curr_version == 4 {
-                // curr_version = mig_4_to_5(conn)?;
-                // }
-                //
-                // if curr_version == 5 {
-                // curr_version = mig_5_to_6(conn)?;
-                // }
-                //
-                // if curr_version == 6 {
-                // curr_version = mig_6_to_7(conn)?;
+                if curr_version == 3 {
+                    curr_version = mig_3_to_4(conn)?;
+                }
+
+                if curr_version == 4 {
+                    curr_version = mig_4_to_5(conn)?;
+                }
+
+                if curr_version == 5 {
+                    curr_version = mig_5_to_6(conn)?;
+                }
+
+                if curr_version == 6 {
+                    curr_version = mig_6_to_7(conn)?;
+                }
+
+                if curr_version == 7 {
+                    curr_version = mig_7_to_8(conn)?;
+                }
+
+                if curr_version == 8 {
+                    curr_version = mig_8_to_9(conn)?;
+                }
+
+                if curr_version == 9 {
+                    curr_version = mig_9_to_10(conn)?;
+                }
+
+                if curr_version == 10 {
+                    curr_version = mig_10_to_11(conn)?;
+                }
+
+                if curr_version == 11 {
+                    curr_version = mig_11_to_12(conn)?;
+                }
+
+                if curr_version == 12 {
+                    curr_version = mig_12_to_13(conn)?;
+                }
+
+                if curr_version == 13 {
+                    curr_version = mig_13_to_14(conn)?;
+                }
+
+                if curr_version == 14 {
+                    curr_version = mig_14_to_15(conn)?;
+                }
+
+                if curr_version == 15 {
+                    curr_version = mig_15_to_16(conn)?;
+                }
+
+                if curr_version == 16 {
+                    curr_version = mig_16_to_17(conn)?;
+                }
+
+                if curr_version == 17 {
+                    curr_version = mig_17_to_18(conn)?;
+                }
+
+                if curr_version == 18 {
+                    curr_version = mig_18_to_19(conn)?;
+                }
+
+                if curr_version == 19 {
+                    curr_version = mig_19_to_20(conn)?;
+                }
+
+                if curr_version == 20 {
+                    curr_version = mig_20_to_21(conn)?;
+                }
+
+                if curr_version == 21 {
+                    curr_version = mig_21_to_22(conn)?;
+                }
+
+                if curr_version == 22 {
+                    curr_version = mig_22_to_23(conn)?;
+                }
+
+                if curr_version == 23 {
+                    curr_version = mig_23_to_24(conn)?;
+                }
+
+                if curr_version == 24 {
+                    curr_version = mig_24_to_25(conn)?;
+                }
+
+                if curr_version == 25 {
+                    curr_version = mig_25_to_26(conn)?;
+                }
+
+                // if curr_version == 26 {
+                // curr_version = mig_26_to_27(conn)?;
                 // }
 
                 if curr_version == DB_VERSION {
@@ -116,5 +196,143 @@ fn mig_1_to_2(conn: &mut Connection) -> Result<usize, Error> {
 fn mig_2_to_3(conn: &mut Connection) -> Result<usize, Error> {
     conn.execute_batch(include_str!("../migrations/003_drop_again.sql"))?;
     tracing::info!("database schema upgraded v2 -> v3");
-    Ok(2)
+    Ok(3)
+}
+
+fn mig_3_to_4(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/004_dead_letter.sql"))?;
+    tracing::info!("database schema upgraded v3 -> v4");
+    Ok(4)
+}
+
+fn mig_4_to_5(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/005_coin_selection.sql"))?;
+    tracing::info!("database schema upgraded v4 -> v5");
+    Ok(5)
+}
+
+fn mig_5_to_6(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/006_outbox.sql"))?;
+    tracing::info!("database schema upgraded v5 -> v6");
+    Ok(6)
+}
+
+fn mig_6_to_7(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/007_signing_audit.sql"))?;
+    tracing::info!("database schema upgraded v6 -> v7");
+    Ok(7)
+}
+
+fn mig_7_to_8(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/008_superseded.sql"))?;
+    tracing::info!("database schema upgraded v7 -> v8");
+    Ok(8)
+}
+
+fn mig_8_to_9(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/009_outgoing_txs.sql"))?;
+    tracing::info!("database schema upgraded v8 -> v9");
+    Ok(9)
+}
+
+fn mig_9_to_10(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/010_peer_capabilities.sql"))?;
+    tracing::info!("database schema upgraded v9 -> v10");
+    Ok(10)
+}
+
+fn mig_10_to_11(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/011_notifications.sql"))?;
+    tracing::info!("database schema upgraded v10 -> v11");
+    Ok(11)
+}
+
+fn mig_11_to_12(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/012_proof_verifications.sql"))?;
+    tracing::info!("database schema upgraded v11 -> v12");
+    Ok(12)
+}
+
+fn mig_12_to_13(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/013_hw_registrations.sql"))?;
+    tracing::info!("database schema upgraded v12 -> v13");
+    Ok(13)
+}
+
+fn mig_13_to_14(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/014_relay_roles.sql"))?;
+    tracing::info!("database schema upgraded v13 -> v14");
+    Ok(14)
+}
+
+fn mig_14_to_15(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/015_profile_history.sql"))?;
+    tracing::info!("database schema upgraded v14 -> v15");
+    Ok(15)
+}
+
+fn mig_15_to_16(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/016_ingestion_reports.sql"))?;
+    tracing::info!("database schema upgraded v15 -> v16");
+    Ok(16)
+}
+
+fn mig_16_to_17(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/017_drafts.sql"))?;
+    tracing::info!("database schema upgraded v16 -> v17");
+    Ok(17)
+}
+
+fn mig_17_to_18(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/018_birthdays.sql"))?;
+    tracing::info!("database schema upgraded v17 -> v18");
+    Ok(18)
+}
+
+fn mig_18_to_19(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/019_passphrase_identities.sql"))?;
+    tracing::info!("database schema upgraded v18 -> v19");
+    Ok(19)
+}
+
+fn mig_19_to_20(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/020_wallet_integrity.sql"))?;
+    tracing::info!("database schema upgraded v19 -> v20");
+    Ok(20)
+}
+
+fn mig_20_to_21(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/021_app_state.sql"))?;
+    tracing::info!("database schema upgraded v20 -> v21");
+    Ok(21)
+}
+
+fn mig_21_to_22(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/022_event_deliveries.sql"))?;
+    tracing::info!("database schema upgraded v21 -> v22");
+    Ok(22)
+}
+
+fn mig_22_to_23(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/023_tx_fees.sql"))?;
+    tracing::info!("database schema upgraded v22 -> v23");
+    Ok(23)
+}
+
+fn mig_23_to_24(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/024_approval_reminders.sql"))?;
+    tracing::info!("database schema upgraded v23 -> v24");
+    Ok(24)
+}
+
+fn mig_24_to_25(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/025_rotated_shared_keys.sql"))?;
+    tracing::info!("database schema upgraded v24 -> v25");
+    Ok(25)
+}
+
+fn mig_25_to_26(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/026_pending_event_retries.sql"))?;
+    tracing::info!("database schema upgraded v25 -> v26");
+    Ok(26)
 }
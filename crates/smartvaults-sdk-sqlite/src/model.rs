@@ -1,8 +1,16 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use core::fmt;
+use core::str::FromStr;
+use std::time::Duration;
+
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::{Network, Txid};
 use smartvaults_protocol::nostr::nips::nip46::Message;
-use smartvaults_protocol::nostr::{EventId, PublicKey, Timestamp};
+use smartvaults_protocol::nostr::{Event, EventId, Keys, Metadata, PublicKey, Timestamp, Url};
+use smartvaults_protocol::v1::Capabilities;
 
 #[derive(Debug, Clone)]
 pub struct NostrConnectRequest {
@@ -12,3 +20,405 @@ pub struct NostrConnectRequest {
     pub timestamp: Timestamp,
     pub approved: bool,
 }
+
+#[derive(Debug, Clone)]
+pub struct QuarantinedEvent {
+    pub event_id: EventId,
+    /// `None` if the event was not found in the nostr database when quarantined
+    pub event: Option<Event>,
+    pub reason: String,
+    pub timestamp: Timestamp,
+}
+
+/// Retry metadata of a pending event
+#[derive(Debug, Clone)]
+pub struct PendingEventRetry {
+    pub event: Event,
+    /// Number of failed attempts
+    pub retries: u32,
+    pub next_retry_at: Timestamp,
+    /// Dead-letter: not retried anymore
+    pub failed: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutboxEvent {
+    pub relay_url: Url,
+    pub event: Event,
+    pub attempts: u32,
+    pub timestamp: Timestamp,
+}
+
+/// Delivery of a recipient-addressed event to a relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDelivery {
+    pub recipient: PublicKey,
+    pub relay_url: Url,
+    /// Read relay of the recipient (NIP-65), not one of ours
+    pub recipient_relay: bool,
+    /// `None` if the relay acknowledged the event
+    pub error: Option<String>,
+    pub timestamp: Timestamp,
+}
+
+/// Outgoing transaction without a matching completed proposal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnexpectedTx {
+    pub policy_id: EventId,
+    pub txid: Txid,
+    /// Net amount (sat) moved out of the policy
+    pub amount: u64,
+    pub acknowledged: bool,
+    /// Detection timestamp
+    pub timestamp: Timestamp,
+}
+
+/// Fee paid by an outgoing transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxFee {
+    pub txid: Txid,
+    /// Fee (sat)
+    pub fee: u64,
+    /// Not all the input values are known: the fee is a lower bound
+    pub estimated: bool,
+    /// Confirmation time (or last seen, if unconfirmed)
+    pub timestamp: Timestamp,
+}
+
+/// Approval reminders settings of a policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReminderSettings {
+    /// Remind the pending approvers every `interval`
+    pub interval: Duration,
+    /// Max number of reminders for each proposal
+    pub max_reminders: u16,
+}
+
+/// Shared key replaced by a rotation (see `SmartVaults::rotate_shared_key`)
+#[derive(Debug, Clone)]
+pub struct RotatedSharedKey {
+    pub policy_id: EventId,
+    pub shared_key: Keys,
+    /// Policy published with the new shared key
+    pub new_policy_id: EventId,
+    pub rotated_at: Timestamp,
+}
+
+/// Proposal created by me, tracked to remind the pending approvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackedReminder {
+    pub proposal_id: EventId,
+    pub policy_id: EventId,
+    pub created_at: Timestamp,
+    /// Number of reminders sent
+    pub sent: u16,
+    pub last_sent_at: Option<Timestamp>,
+}
+
+impl TrackedReminder {
+    /// When the next reminder is due (`None` if all the reminders were sent)
+    pub fn next_due(&self, settings: &ReminderSettings) -> Option<Timestamp> {
+        if self.sent >= settings.max_reminders {
+            return None;
+        }
+        Some(self.last_sent_at.unwrap_or(self.created_at) + settings.interval)
+    }
+}
+
+/// Published version of the profile metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileVersion {
+    pub version: u64,
+    /// Metadata event ID
+    pub event_id: EventId,
+    pub metadata: Metadata,
+    /// Publication timestamp
+    pub timestamp: Timestamp,
+}
+
+/// Relay read/write policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RelayRole {
+    /// Subscribe to the relay
+    pub read: bool,
+    /// Publish to the relay
+    pub write: bool,
+}
+
+impl Default for RelayRole {
+    fn default() -> Self {
+        Self {
+            read: true,
+            write: true,
+        }
+    }
+}
+
+impl fmt::Display for RelayRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.read, self.write) {
+            (true, true) => write!(f, "read/write"),
+            (true, false) => write!(f, "read"),
+            (false, true) => write!(f, "write"),
+            (false, false) => write!(f, "none"),
+        }
+    }
+}
+
+impl RelayRole {
+    pub fn read_only() -> Self {
+        Self {
+            read: true,
+            write: false,
+        }
+    }
+
+    pub fn write_only() -> Self {
+        Self {
+            read: false,
+            write: true,
+        }
+    }
+}
+
+/// Protocol capabilities advertised by a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCapabilities {
+    pub capabilities: Capabilities,
+    /// Advertisement timestamp
+    pub timestamp: Timestamp,
+}
+
+/// Kind of signing operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SigningKind {
+    /// Proposal approval
+    Approval,
+    /// Nostr Connect signature
+    NostrConnect,
+    /// Proof of Reserve
+    ProofOfReserve,
+}
+
+impl fmt::Display for SigningKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Approval => write!(f, "approval"),
+            Self::NostrConnect => write!(f, "nostr-connect"),
+            Self::ProofOfReserve => write!(f, "proof-of-reserve"),
+        }
+    }
+}
+
+impl FromStr for SigningKind {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "approval" => Ok(Self::Approval),
+            "nostr-connect" => Ok(Self::NostrConnect),
+            "proof-of-reserve" => Ok(Self::ProofOfReserve),
+            _ => Err(crate::Error::UnknownSigningKind(s.to_string())),
+        }
+    }
+}
+
+/// Policy birthday
+///
+/// The effective birthday is the earliest of the two.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Birthday {
+    /// Policy event creation
+    pub timestamp: Option<Timestamp>,
+    /// User-supplied block height
+    pub height: Option<u32>,
+}
+
+/// Screen of a form draft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DraftScreen {
+    Spend,
+    AddPolicy,
+}
+
+impl fmt::Display for DraftScreen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Spend => write!(f, "spend"),
+            Self::AddPolicy => write!(f, "add-policy"),
+        }
+    }
+}
+
+/// Kind of notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotificationKind {
+    NewPolicy,
+    NewProposal,
+    /// The collected approvals are enough to finalize the proposal
+    ProposalReadyToFinalize,
+    CompletedProposal,
+    /// Proof of Reserve automatically verified
+    ProofVerified,
+    /// Proof of Reserve automatic verification failed
+    ProofInvalid,
+    /// PSBT signed by an external signer imported as approval
+    ExternalPsbtImported,
+    /// PSBT signed by an external signer rejected
+    ExternalPsbtRejected,
+    /// A member reported that can't read a proposal
+    MemberCannotReadProposal,
+    /// The wallet state didn't match the policy: quarantined and rescanned
+    PolicyWalletMismatch,
+    /// Nostr Connect request waiting for approval
+    NostrConnectRequest,
+    /// Nostr Connect request approved automatically (informational)
+    NostrConnectRequestAutoApproved,
+    /// Reminder to approve a proposal
+    ApprovalReminder,
+}
+
+impl NotificationKind {
+    /// Informational notifications don't require any action
+    pub fn is_informational(&self) -> bool {
+        matches!(self, Self::NostrConnectRequestAutoApproved)
+    }
+}
+
+impl fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NewPolicy => write!(f, "new-policy"),
+            Self::NewProposal => write!(f, "new-proposal"),
+            Self::ProposalReadyToFinalize => write!(f, "proposal-ready-to-finalize"),
+            Self::CompletedProposal => write!(f, "completed-proposal"),
+            Self::ProofVerified => write!(f, "proof-verified"),
+            Self::ProofInvalid => write!(f, "proof-invalid"),
+            Self::ExternalPsbtImported => write!(f, "external-psbt-imported"),
+            Self::ExternalPsbtRejected => write!(f, "external-psbt-rejected"),
+            Self::MemberCannotReadProposal => write!(f, "member-cannot-read-proposal"),
+            Self::PolicyWalletMismatch => write!(f, "policy-wallet-mismatch"),
+            Self::NostrConnectRequest => write!(f, "nostr-connect-request"),
+            Self::NostrConnectRequestAutoApproved => {
+                write!(f, "nostr-connect-request-auto-approved")
+            }
+            Self::ApprovalReminder => write!(f, "approval-reminder"),
+        }
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new-policy" => Ok(Self::NewPolicy),
+            "new-proposal" => Ok(Self::NewProposal),
+            "proposal-ready-to-finalize" => Ok(Self::ProposalReadyToFinalize),
+            "completed-proposal" => Ok(Self::CompletedProposal),
+            "proof-verified" => Ok(Self::ProofVerified),
+            "proof-invalid" => Ok(Self::ProofInvalid),
+            "external-psbt-imported" => Ok(Self::ExternalPsbtImported),
+            "external-psbt-rejected" => Ok(Self::ExternalPsbtRejected),
+            "member-cannot-read-proposal" => Ok(Self::MemberCannotReadProposal),
+            "policy-wallet-mismatch" => Ok(Self::PolicyWalletMismatch),
+            "nostr-connect-request" => Ok(Self::NostrConnectRequest),
+            "nostr-connect-request-auto-approved" => Ok(Self::NostrConnectRequestAutoApproved),
+            "approval-reminder" => Ok(Self::ApprovalReminder),
+            _ => Err(crate::Error::UnknownNotificationKind(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetNotificationsResult {
+    pub kind: NotificationKind,
+    /// ID of the policy, proposal, ... the notification refers to
+    pub event_id: EventId,
+    pub timestamp: Timestamp,
+    pub seen: bool,
+}
+
+/// Cached verdict of a Proof of Reserve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofVerification {
+    pub completed_proposal_id: EventId,
+    pub policy_id: EventId,
+    /// Spendable amount (sat), `None` if the proof is invalid
+    pub spendable: Option<u64>,
+    /// Verification error
+    pub error: Option<String>,
+    /// Block height at verification
+    pub height: u32,
+    /// Digest of the policy UTXO set at verification
+    pub utxos_digest: Sha256Hash,
+    pub timestamp: Timestamp,
+}
+
+impl ProofVerification {
+    pub fn is_valid(&self) -> bool {
+        self.spendable.is_some()
+    }
+}
+
+/// Wallet policy registered on a hardware device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HwRegistration {
+    pub policy_id: EventId,
+    pub fingerprint: Fingerprint,
+    /// Device identifier (ex. serial number): the same seed may be loaded on many devices
+    pub device_id: String,
+    /// Proof of registration returned by the device
+    pub hmac: Vec<u8>,
+    pub timestamp: Timestamp,
+}
+
+/// Identity derived from the seed with a BIP39 passphrase
+///
+/// The passphrase is never stored, only a hash salted with the fingerprint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassphraseIdentity {
+    pub fingerprint: Fingerprint,
+    pub public_key: PublicKey,
+    /// `None` if the seed is used without passphrase
+    pub passphrase_hash: Option<Sha256Hash>,
+    pub last_used: Timestamp,
+}
+
+/// Descriptor and network the wallet state of a policy was built for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletIntegrity {
+    /// Key of the wallet state in the `timechain` table
+    pub descriptor_hash: Sha256Hash,
+    /// Descriptor checksum (`<descriptor>+<change descriptor>` for policies with change descriptor)
+    pub checksum: String,
+    pub network: Network,
+}
+
+/// Signing audit log entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningAuditEntry {
+    pub id: u64,
+    pub timestamp: Timestamp,
+    pub fingerprint: Fingerprint,
+    pub kind: SigningKind,
+    /// ID of the signed object (proposal, nostr connect request, ...)
+    pub object_id: String,
+    /// Digest of the signed content
+    pub digest: Sha256Hash,
+    pub prev_hash: Sha256Hash,
+    pub hash: Sha256Hash,
+}
+
+/// Signing audit log integrity
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditLogIntegrity {
+    Valid { entries: u64 },
+    Tampered { reason: String },
+}
+
+impl AuditLogIntegrity {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid { .. })
+    }
+}
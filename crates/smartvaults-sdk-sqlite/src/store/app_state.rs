@@ -0,0 +1,128 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use rusqlite::OptionalExtension;
+use smartvaults_protocol::nostr::Timestamp;
+
+use super::Store;
+use crate::Error;
+
+const LAST_SEEN_VERSION: &str = "last_seen_version";
+const MAINNET_GUARD: &str = "mainnet_guard";
+const LAST_REBROADCAST: &str = "last_rebroadcast";
+
+impl Store {
+    /// Get the last version the changelog was seen for
+    pub async fn get_last_seen_version(&self) -> Result<Option<String>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT value FROM app_state WHERE key = ?;")?;
+            Ok(stmt
+                .query_row([LAST_SEEN_VERSION], |row| row.get(0))
+                .optional()?)
+        })
+        .await?
+    }
+
+    pub async fn set_last_seen_version<S>(&self, version: S) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let version: String = version.into();
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO app_state (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value;",
+                (LAST_SEEN_VERSION, version),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the mainnet guard setting (`None` if never set)
+    pub async fn get_mainnet_guard(&self) -> Result<Option<bool>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT value FROM app_state WHERE key = ?;")?;
+            let value: Option<String> = stmt
+                .query_row([MAINNET_GUARD], |row| row.get(0))
+                .optional()?;
+            Ok(value.map(|v| v == "true"))
+        })
+        .await?
+    }
+
+    pub async fn set_mainnet_guard(&self, enabled: bool) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO app_state (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value;",
+                (MAINNET_GUARD, enabled.to_string()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the timestamp of the last rebroadcast of all the events
+    pub async fn get_last_rebroadcast(&self) -> Result<Option<Timestamp>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT value FROM app_state WHERE key = ?;")?;
+            let value: Option<String> = stmt
+                .query_row([LAST_REBROADCAST], |row| row.get(0))
+                .optional()?;
+            Ok(value
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Timestamp::from))
+        })
+        .await?
+    }
+
+    pub async fn set_last_rebroadcast(&self, timestamp: Timestamp) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO app_state (key, value) VALUES (?, ?) ON CONFLICT(key) DO UPDATE SET value = excluded.value;",
+                (LAST_REBROADCAST, timestamp.as_u64().to_string()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_last_seen_version() {
+        let store = TempStore::new().await;
+
+        assert_eq!(store.get_last_seen_version().await.unwrap(), None);
+        store.set_last_seen_version("0.3.0").await.unwrap();
+        store.set_last_seen_version("0.4.0").await.unwrap();
+        assert_eq!(
+            store.get_last_seen_version().await.unwrap(),
+            Some(String::from("0.4.0"))
+        );
+
+        assert_eq!(store.get_mainnet_guard().await.unwrap(), None);
+        store.set_mainnet_guard(false).await.unwrap();
+        assert_eq!(store.get_mainnet_guard().await.unwrap(), Some(false));
+
+        assert_eq!(store.get_last_rebroadcast().await.unwrap(), None);
+        store
+            .set_last_rebroadcast(Timestamp::from(1_700_000_000))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_last_rebroadcast().await.unwrap(),
+            Some(Timestamp::from(1_700_000_000))
+        );
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use rusqlite::OptionalExtension;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::Birthday;
+use crate::Error;
+
+impl Store {
+    /// Save the policy creation timestamp (the earliest is kept)
+    pub async fn save_birthday_timestamp(
+        &self,
+        policy_id: EventId,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO birthdays (policy_id, timestamp) VALUES (?, ?) ON CONFLICT(policy_id) DO UPDATE SET timestamp = MIN(COALESCE(timestamp, excluded.timestamp), excluded.timestamp);",
+                (policy_id.to_hex(), timestamp.as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Set (or remove, if `None`) the user-supplied birthday height
+    pub async fn set_birthday_height(
+        &self,
+        policy_id: EventId,
+        height: Option<u32>,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO birthdays (policy_id, height) VALUES (?, ?) ON CONFLICT(policy_id) DO UPDATE SET height = excluded.height;",
+                (policy_id.to_hex(), height),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_birthday(&self, policy_id: EventId) -> Result<Birthday, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn
+                .prepare_cached("SELECT timestamp, height FROM birthdays WHERE policy_id = ?;")?;
+            let birthday = stmt
+                .query_row([policy_id.to_hex()], |row| {
+                    let timestamp: Option<u64> = row.get(0)?;
+                    let height: Option<u32> = row.get(1)?;
+                    Ok(Birthday {
+                        timestamp: timestamp.map(Timestamp::from),
+                        height,
+                    })
+                })
+                .optional()?;
+            Ok(birthday.unwrap_or_default())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_birthdays() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        assert_eq!(
+            store.get_birthday(policy_id).await.unwrap(),
+            Birthday::default()
+        );
+
+        store
+            .set_birthday_height(policy_id, Some(100))
+            .await
+            .unwrap();
+        store
+            .save_birthday_timestamp(policy_id, Timestamp::from(20))
+            .await
+            .unwrap();
+        store
+            .save_birthday_timestamp(policy_id, Timestamp::from(10))
+            .await
+            .unwrap();
+        store
+            .save_birthday_timestamp(policy_id, Timestamp::from(30))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_birthday(policy_id).await.unwrap(),
+            Birthday {
+                timestamp: Some(Timestamp::from(10)),
+                height: Some(100),
+            }
+        );
+
+        store.set_birthday_height(policy_id, None).await.unwrap();
+        assert_eq!(store.get_birthday(policy_id).await.unwrap().height, None);
+    }
+}
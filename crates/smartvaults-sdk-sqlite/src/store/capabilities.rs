@@ -0,0 +1,103 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+
+use smartvaults_protocol::nostr::{PublicKey, Timestamp};
+use smartvaults_protocol::v1::{Capabilities, Serde};
+
+use super::Store;
+use crate::model::PeerCapabilities;
+use crate::Error;
+
+impl Store {
+    /// Save the capabilities advertised by a peer
+    ///
+    /// Ignored if older than the already saved ones.
+    pub async fn save_peer_capabilities(
+        &self,
+        public_key: PublicKey,
+        capabilities: Capabilities,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO peer_capabilities (public_key, capabilities, timestamp) VALUES (?, ?, ?) ON CONFLICT(public_key) DO UPDATE SET capabilities = excluded.capabilities, timestamp = excluded.timestamp WHERE excluded.timestamp > peer_capabilities.timestamp;",
+                (public_key.to_string(), capabilities.as_json(), timestamp.as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the capabilities advertised by the peers
+    ///
+    /// The peers that never advertised their capabilities are not included.
+    pub async fn get_peers_capabilities<I>(
+        &self,
+        public_keys: I,
+    ) -> Result<HashMap<PublicKey, PeerCapabilities>, Error>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let public_keys: Vec<PublicKey> = public_keys.into_iter().collect();
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT capabilities, timestamp FROM peer_capabilities WHERE public_key = ?;",
+            )?;
+            let mut peers = HashMap::with_capacity(public_keys.len());
+            for public_key in public_keys.into_iter() {
+                let mut rows = stmt.query([public_key.to_string()])?;
+                if let Ok(Some(row)) = rows.next() {
+                    let json: String = row.get(0)?;
+                    let timestamp: u64 = row.get(1)?;
+                    peers.insert(
+                        public_key,
+                        PeerCapabilities {
+                            capabilities: Capabilities::from_json(json)?,
+                            timestamp: Timestamp::from(timestamp),
+                        },
+                    );
+                }
+            }
+            Ok(peers)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_protocol::nostr::Keys;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_peer_capabilities() {
+        let store = TempStore::new().await;
+
+        let peer = Keys::generate().public_key();
+        let unknown = Keys::generate().public_key();
+
+        store
+            .save_peer_capabilities(peer, Capabilities::new([1, 2]), Timestamp::from(20))
+            .await
+            .unwrap();
+
+        // Older advertisement
+        store
+            .save_peer_capabilities(peer, Capabilities::new([1]), Timestamp::from(10))
+            .await
+            .unwrap();
+
+        let peers = store.get_peers_capabilities([peer, unknown]).await.unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(
+            peers.get(&peer).unwrap().capabilities,
+            Capabilities::new([1, 2])
+        );
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use smartvaults_core::CoinSelection;
+use smartvaults_protocol::nostr::EventId;
+
+use super::Store;
+use crate::Error;
+
+impl Store {
+    pub async fn set_coin_selection(
+        &self,
+        policy_id: EventId,
+        coin_selection: CoinSelection,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO coin_selection (policy_id, strategy) VALUES (?, ?) ON CONFLICT(policy_id) DO UPDATE SET strategy = ?;",
+                (policy_id.to_hex(), coin_selection.as_str(), coin_selection.as_str()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_coin_selection(&self, policy_id: EventId) -> Result<CoinSelection, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT strategy FROM coin_selection WHERE policy_id = ?;")?;
+            let mut rows = stmt.query([policy_id.to_hex()])?;
+            let row = rows
+                .next()?
+                .ok_or(Error::NotFound("coin selection".into()))?;
+            let strategy: String = row.get(0)?;
+            Ok(CoinSelection::from_str(&strategy)?)
+        })
+        .await?
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::{HashMap, HashSet};
+
+use smartvaults_protocol::nostr::{Event, EventId, JsonUtil, Timestamp};
+
+use super::Store;
+use crate::model::{PendingEventRetry, QuarantinedEvent};
+use crate::Error;
+
+const CRASHED_WHILE_PROCESSING: &str = "crashed while processing";
+
+impl Store {
+    /// Mark event as `processing`
+    ///
+    /// Only the ID is stored: if the app crash, the event is read from the nostr database at
+    /// next startup (see [`Store::quarantine_processing_events`]).
+    ///
+    /// Must be cleared with [`Store::clear_processing_event`] after the event is handled.
+    pub async fn set_processing_event(&self, event_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO processing_events (event_id) VALUES (?);",
+                [event_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn clear_processing_event(&self, event_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM processing_events WHERE event_id = ?;",
+                [event_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the IDs of the events left in `processing` state (app crashed while handling them)
+    pub async fn get_processing_event_ids(&self) -> Result<Vec<EventId>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT event_id FROM processing_events;")?;
+            let mut rows = stmt.query([])?;
+            let mut ids = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let event_id: String = row.get(0)?;
+                ids.push(EventId::from_hex(event_id)?);
+            }
+            Ok(ids)
+        })
+        .await?
+    }
+
+    /// Move the `events` to the dead-letter table and clear all the `processing` markers
+    ///
+    /// The `events` are the ones of [`Store::get_processing_event_ids`] found in the nostr database:
+    /// the IDs of the missing ones are quarantined without the event.
+    pub async fn quarantine_processing_events(&self, events: Vec<Event>) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let tx = conn.transaction()?;
+            let now: u64 = Timestamp::now().as_u64();
+            for event in events.into_iter() {
+                tx.execute(
+                    "INSERT OR REPLACE INTO dead_letter_events (event_id, event, reason, timestamp) VALUES (?, ?, ?, ?);",
+                    (event.id.to_hex(), event.as_json(), CRASHED_WHILE_PROCESSING, now),
+                )?;
+            }
+            tx.execute(
+                "INSERT OR IGNORE INTO dead_letter_events (event_id, event, reason, timestamp) SELECT event_id, NULL, ?, ? FROM processing_events;",
+                (CRASHED_WHILE_PROCESSING, now),
+            )?;
+            tx.execute("DELETE FROM processing_events;", [])?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_quarantined_event_ids(&self) -> Result<HashSet<EventId>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT event_id FROM dead_letter_events;")?;
+            let mut rows = stmt.query([])?;
+            let mut ids = HashSet::new();
+            while let Ok(Some(row)) = rows.next() {
+                let event_id: String = row.get(0)?;
+                ids.insert(EventId::from_hex(event_id)?);
+            }
+            Ok(ids)
+        })
+        .await?
+    }
+
+    pub async fn get_quarantined_events(&self) -> Result<Vec<QuarantinedEvent>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT event_id, event, reason, timestamp FROM dead_letter_events ORDER BY timestamp ASC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut events = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let event_id: String = row.get(0)?;
+                let event: Option<String> = row.get(1)?;
+                let reason: String = row.get(2)?;
+                let timestamp: u64 = row.get(3)?;
+                events.push(QuarantinedEvent {
+                    event_id: EventId::from_hex(event_id)?,
+                    event: match event {
+                        Some(event) => Some(Event::from_json(event)?),
+                        None => None,
+                    },
+                    reason,
+                    timestamp: Timestamp::from(timestamp),
+                });
+            }
+            Ok(events)
+        })
+        .await?
+    }
+
+    pub async fn delete_quarantined_event(&self, event_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM dead_letter_events WHERE event_id = ?;",
+                [event_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn save_pending_event_retry(&self, retry: PendingEventRetry) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO pending_event_retries (event_id, event, retries, next_retry_at, failed) VALUES (?, ?, ?, ?, ?);",
+                (
+                    retry.event.id.to_hex(),
+                    retry.event.as_json(),
+                    retry.retries,
+                    retry.next_retry_at.as_u64(),
+                    retry.failed,
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_pending_event_retries(
+        &self,
+    ) -> Result<HashMap<EventId, PendingEventRetry>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT event, retries, next_retry_at, failed FROM pending_event_retries;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut retries = HashMap::new();
+            while let Ok(Some(row)) = rows.next() {
+                let event: String = row.get(0)?;
+                let event: Event = Event::from_json(event)?;
+                let next_retry_at: u64 = row.get(2)?;
+                retries.insert(
+                    event.id,
+                    PendingEventRetry {
+                        event,
+                        retries: row.get(1)?,
+                        next_retry_at: Timestamp::from(next_retry_at),
+                        failed: row.get(3)?,
+                    },
+                );
+            }
+            Ok(retries)
+        })
+        .await?
+    }
+
+    /// Get the pending events moved to the dead-letter state (not retried anymore)
+    pub async fn get_failed_events(&self) -> Result<Vec<PendingEventRetry>, Error> {
+        let mut events: Vec<PendingEventRetry> = self
+            .get_pending_event_retries()
+            .await?
+            .into_values()
+            .filter(|r| r.failed)
+            .collect();
+        events.sort_by_key(|r| r.event.created_at);
+        Ok(events)
+    }
+
+    pub async fn delete_pending_event_retry(&self, event_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM pending_event_retries WHERE event_id = ?;",
+                [event_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Clear the retry metadata of all the pending events (failed ones included)
+    pub async fn reset_pending_event_retries(&self) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute("DELETE FROM pending_event_retries;", [])?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_protocol::nostr::EventBuilder;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_quarantine_processing_events() {
+        let store = TempStore::new().await;
+
+        let event = EventBuilder::text_note("ok", [])
+            .to_event(&store.keys)
+            .unwrap();
+        let poison = EventBuilder::text_note("poison", [])
+            .to_event(&store.keys)
+            .unwrap();
+        let missing = EventBuilder::text_note("missing", [])
+            .to_event(&store.keys)
+            .unwrap();
+
+        store.set_processing_event(event.id).await.unwrap();
+        store.clear_processing_event(event.id).await.unwrap();
+        store.set_processing_event(poison.id).await.unwrap();
+        store.set_processing_event(missing.id).await.unwrap();
+
+        let mut ids = store.get_processing_event_ids().await.unwrap();
+        ids.sort();
+        let mut expected = vec![poison.id, missing.id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // The `missing` event is not in the nostr database
+        store
+            .quarantine_processing_events(vec![poison.clone()])
+            .await
+            .unwrap();
+        assert!(store.get_processing_event_ids().await.unwrap().is_empty());
+
+        // The ID of the `missing` event is quarantined too
+        let ids = store.get_quarantined_event_ids().await.unwrap();
+        assert_eq!(ids, HashSet::from([poison.id, missing.id]));
+
+        let events = store.get_quarantined_events().await.unwrap();
+        assert_eq!(events.len(), 2);
+        let quarantined = events.iter().find(|q| q.event_id == poison.id).unwrap();
+        assert_eq!(quarantined.event, Some(poison.clone()));
+        let quarantined = events.iter().find(|q| q.event_id == missing.id).unwrap();
+        assert_eq!(quarantined.event, None);
+
+        store.delete_quarantined_event(poison.id).await.unwrap();
+        store.delete_quarantined_event(missing.id).await.unwrap();
+        assert!(store.get_quarantined_event_ids().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_event_retries() {
+        let store = TempStore::new().await;
+
+        let event = EventBuilder::text_note("pending", [])
+            .to_event(&store.keys)
+            .unwrap();
+        let failed = EventBuilder::text_note("failed", [])
+            .to_event(&store.keys)
+            .unwrap();
+
+        store
+            .save_pending_event_retry(PendingEventRetry {
+                event: event.clone(),
+                retries: 1,
+                next_retry_at: Timestamp::from(30),
+                failed: false,
+            })
+            .await
+            .unwrap();
+        store
+            .save_pending_event_retry(PendingEventRetry {
+                event: failed.clone(),
+                retries: 10,
+                next_retry_at: Timestamp::from(60),
+                failed: true,
+            })
+            .await
+            .unwrap();
+
+        let retries = store.get_pending_event_retries().await.unwrap();
+        assert_eq!(retries.len(), 2);
+        assert_eq!(retries.get(&event.id).unwrap().retries, 1);
+
+        let events = store.get_failed_events().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, failed);
+
+        // Success
+        store.delete_pending_event_retry(event.id).await.unwrap();
+        assert!(!store
+            .get_pending_event_retries()
+            .await
+            .unwrap()
+            .contains_key(&event.id));
+
+        store.reset_pending_event_retries().await.unwrap();
+        assert!(store.get_failed_events().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use rusqlite::OptionalExtension;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::DraftScreen;
+use crate::Error;
+
+fn policy_key(policy_id: Option<EventId>) -> String {
+    policy_id.map(|id| id.to_hex()).unwrap_or_default()
+}
+
+impl Store {
+    /// Save (or replace) the draft of a screen
+    pub async fn save_draft<S>(
+        &self,
+        screen: DraftScreen,
+        policy_id: Option<EventId>,
+        data: S,
+        timestamp: Timestamp,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let data: String = data.into();
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO drafts (screen, policy_id, data, timestamp) VALUES (?, ?, ?, ?);",
+                (screen.to_string(), policy_key(policy_id), data, timestamp.as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the draft of a screen and when it was saved
+    pub async fn get_draft(
+        &self,
+        screen: DraftScreen,
+        policy_id: Option<EventId>,
+    ) -> Result<Option<(String, Timestamp)>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT data, timestamp FROM drafts WHERE screen = ? AND policy_id = ?;",
+            )?;
+            let draft = stmt
+                .query_row((screen.to_string(), policy_key(policy_id)), |row| {
+                    let data: String = row.get(0)?;
+                    let timestamp: u64 = row.get(1)?;
+                    Ok((data, Timestamp::from(timestamp)))
+                })
+                .optional()?;
+            Ok(draft)
+        })
+        .await?
+    }
+
+    /// Delete the draft of a screen
+    ///
+    /// Return `false` if there wasn't a draft.
+    pub async fn delete_draft(
+        &self,
+        screen: DraftScreen,
+        policy_id: Option<EventId>,
+    ) -> Result<bool, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "DELETE FROM drafts WHERE screen = ? AND policy_id = ?;",
+                (screen.to_string(), policy_key(policy_id)),
+            )?;
+            Ok(n > 0)
+        })
+        .await?
+    }
+
+    /// Delete the drafts saved before `timestamp`
+    ///
+    /// Return the number of deleted drafts.
+    pub async fn delete_drafts_older_than(&self, timestamp: Timestamp) -> Result<usize, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "DELETE FROM drafts WHERE timestamp < ?;",
+                [timestamp.as_u64()],
+            )?;
+            Ok(n)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_drafts() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+
+        store
+            .save_draft(DraftScreen::AddPolicy, None, "{}", Timestamp::from(10))
+            .await
+            .unwrap();
+        store
+            .save_draft(
+                DraftScreen::Spend,
+                Some(policy_id),
+                "{\"a\":1}",
+                Timestamp::from(10),
+            )
+            .await
+            .unwrap();
+        store
+            .save_draft(
+                DraftScreen::Spend,
+                Some(policy_id),
+                "{\"a\":2}",
+                Timestamp::from(20),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_draft(DraftScreen::Spend, Some(policy_id))
+                .await
+                .unwrap(),
+            Some((String::from("{\"a\":2}"), Timestamp::from(20)))
+        );
+        assert!(store
+            .get_draft(DraftScreen::Spend, None)
+            .await
+            .unwrap()
+            .is_none());
+
+        // Expire the oldest
+        assert_eq!(
+            store
+                .delete_drafts_older_than(Timestamp::from(15))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(store
+            .get_draft(DraftScreen::AddPolicy, None)
+            .await
+            .unwrap()
+            .is_none());
+
+        assert!(store
+            .delete_draft(DraftScreen::Spend, Some(policy_id))
+            .await
+            .unwrap());
+        assert!(!store
+            .delete_draft(DraftScreen::Spend, Some(policy_id))
+            .await
+            .unwrap());
+    }
+}
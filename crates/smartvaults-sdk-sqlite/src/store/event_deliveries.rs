@@ -0,0 +1,108 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use smartvaults_protocol::nostr::{EventId, PublicKey, Timestamp, Url};
+
+use super::Store;
+use crate::model::EventDelivery;
+use crate::Error;
+
+impl Store {
+    /// Save the result of sending a recipient-addressed event to a relay
+    pub async fn save_event_delivery(
+        &self,
+        event_id: EventId,
+        delivery: EventDelivery,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO event_deliveries (event_id, recipient, relay_url, recipient_relay, error, timestamp) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT(event_id, recipient, relay_url) DO UPDATE SET error = excluded.error, timestamp = excluded.timestamp;",
+                (
+                    event_id.to_hex(),
+                    delivery.recipient.to_string(),
+                    delivery.relay_url.to_string(),
+                    delivery.recipient_relay,
+                    delivery.error,
+                    delivery.timestamp.as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the relays a recipient-addressed event was sent to
+    pub async fn get_event_deliveries(
+        &self,
+        event_id: EventId,
+    ) -> Result<Vec<EventDelivery>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT recipient, relay_url, recipient_relay, error, timestamp FROM event_deliveries WHERE event_id = ? ORDER BY recipient_relay ASC, relay_url ASC;",
+            )?;
+            let mut rows = stmt.query([event_id.to_hex()])?;
+            let mut deliveries = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let recipient: String = row.get(0)?;
+                let relay_url: String = row.get(1)?;
+                let timestamp: u64 = row.get(4)?;
+                deliveries.push(EventDelivery {
+                    recipient: PublicKey::from_str(&recipient)?,
+                    relay_url: Url::parse(&relay_url)?,
+                    recipient_relay: row.get(2)?,
+                    error: row.get(3)?,
+                    timestamp: Timestamp::from(timestamp),
+                });
+            }
+            Ok(deliveries)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_protocol::nostr::Keys;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_event_deliveries() {
+        let store = TempStore::new().await;
+
+        let event_id = EventId::all_zeros();
+        let recipient = Keys::generate().public_key();
+        let relay_url = Url::parse("wss://relay.example.com").unwrap();
+        let delivery = EventDelivery {
+            recipient,
+            relay_url: relay_url.clone(),
+            recipient_relay: true,
+            error: Some(String::from("timeout")),
+            timestamp: Timestamp::from(10),
+        };
+        store
+            .save_event_delivery(event_id, delivery.clone())
+            .await
+            .unwrap();
+
+        // Retry succeeded
+        let delivery = EventDelivery {
+            error: None,
+            timestamp: Timestamp::from(20),
+            ..delivery
+        };
+        store
+            .save_event_delivery(event_id, delivery.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_event_deliveries(event_id).await.unwrap(),
+            vec![delivery]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use rusqlite::{OptionalExtension, Row};
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::HwRegistration;
+use crate::Error;
+
+fn hw_registration_from_row(row: &Row) -> Result<HwRegistration, Error> {
+    let policy_id: String = row.get(0)?;
+    let fingerprint: String = row.get(1)?;
+    let timestamp: u64 = row.get(4)?;
+    Ok(HwRegistration {
+        policy_id: EventId::from_hex(policy_id)?,
+        fingerprint: Fingerprint::from_str(&fingerprint)?,
+        device_id: row.get(2)?,
+        hmac: row.get(3)?,
+        timestamp: Timestamp::from(timestamp),
+    })
+}
+
+impl Store {
+    /// Save (or replace) the registration of a policy on a hardware device
+    pub async fn save_hw_registration(&self, registration: HwRegistration) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO hw_registrations (policy_id, fingerprint, device_id, hmac, timestamp) VALUES (?, ?, ?, ?, ?);",
+                (
+                    registration.policy_id.to_hex(),
+                    registration.fingerprint.to_string(),
+                    registration.device_id,
+                    registration.hmac,
+                    registration.timestamp.as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_hw_registration<S>(
+        &self,
+        policy_id: EventId,
+        fingerprint: Fingerprint,
+        device_id: S,
+    ) -> Result<Option<HwRegistration>, Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let device_id: String = device_id.into();
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT policy_id, fingerprint, device_id, hmac, timestamp FROM hw_registrations WHERE policy_id = ? AND fingerprint = ? AND device_id = ?;",
+            )?;
+            let row = stmt
+                .query_row(
+                    (policy_id.to_hex(), fingerprint.to_string(), device_id),
+                    |row| Ok(hw_registration_from_row(row)),
+                )
+                .optional()?;
+            row.transpose()
+        })
+        .await?
+    }
+
+    pub async fn get_hw_registrations(&self) -> Result<Vec<HwRegistration>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT policy_id, fingerprint, device_id, hmac, timestamp FROM hw_registrations ORDER BY timestamp DESC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut registrations = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                registrations.push(hw_registration_from_row(row)?);
+            }
+            Ok(registrations)
+        })
+        .await?
+    }
+
+    /// Delete a registration
+    ///
+    /// Return `false` if not found.
+    pub async fn delete_hw_registration<S>(
+        &self,
+        policy_id: EventId,
+        fingerprint: Fingerprint,
+        device_id: S,
+    ) -> Result<bool, Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let device_id: String = device_id.into();
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "DELETE FROM hw_registrations WHERE policy_id = ? AND fingerprint = ? AND device_id = ?;",
+                (policy_id.to_hex(), fingerprint.to_string(), device_id),
+            )?;
+            Ok(n > 0)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_hw_registrations() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let fingerprint = Fingerprint::from_str("f57a6b99").unwrap();
+
+        let registration = HwRegistration {
+            policy_id,
+            fingerprint,
+            device_id: String::from("ledger-1"),
+            hmac: vec![1; 32],
+            timestamp: Timestamp::from(10),
+        };
+        store
+            .save_hw_registration(registration.clone())
+            .await
+            .unwrap();
+
+        // Re-registration replaces the HMAC
+        let registration = HwRegistration {
+            hmac: vec![2; 32],
+            timestamp: Timestamp::from(20),
+            ..registration
+        };
+        store
+            .save_hw_registration(registration.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store
+                .get_hw_registration(policy_id, fingerprint, "ledger-1")
+                .await
+                .unwrap(),
+            Some(registration)
+        );
+        assert!(store
+            .get_hw_registration(policy_id, fingerprint, "ledger-2")
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(store.get_hw_registrations().await.unwrap().len(), 1);
+
+        assert!(store
+            .delete_hw_registration(policy_id, fingerprint, "ledger-1")
+            .await
+            .unwrap());
+        assert!(!store
+            .delete_hw_registration(policy_id, fingerprint, "ledger-1")
+            .await
+            .unwrap());
+    }
+}
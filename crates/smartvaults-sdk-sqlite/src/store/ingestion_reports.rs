@@ -0,0 +1,60 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::Error;
+
+impl Store {
+    /// Save a sent report of a proposal that can't be read
+    ///
+    /// Return `false` if the proposal was already reported.
+    pub async fn save_ingestion_report<S>(
+        &self,
+        proposal_id: EventId,
+        reason: S,
+        timestamp: Timestamp,
+    ) -> Result<bool, Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let reason: String = reason.into();
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "INSERT OR IGNORE INTO ingestion_reports (proposal_id, reason, timestamp) VALUES (?, ?, ?);",
+                (proposal_id.to_hex(), reason, timestamp.as_u64()),
+            )?;
+            Ok(n > 0)
+        })
+        .await?
+    }
+
+    pub async fn ingestion_report_exists(&self, proposal_id: EventId) -> Result<bool, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT EXISTS(SELECT 1 FROM ingestion_reports WHERE proposal_id = ? LIMIT 1);",
+            )?;
+            let exists: bool = stmt.query_row([proposal_id.to_hex()], |row| row.get(0))?;
+            Ok(exists)
+        })
+        .await?
+    }
+
+    /// Count the reports sent since `timestamp` (inclusive)
+    pub async fn count_ingestion_reports_since(
+        &self,
+        timestamp: Timestamp,
+    ) -> Result<usize, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn
+                .prepare_cached("SELECT COUNT(*) FROM ingestion_reports WHERE timestamp >= ?;")?;
+            let count: usize = stmt.query_row([timestamp.as_u64()], |row| row.get(0))?;
+            Ok(count)
+        })
+        .await?
+    }
+}
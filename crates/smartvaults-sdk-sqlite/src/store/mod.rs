@@ -8,6 +8,8 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use chacha20poly1305::aead::KeyInit;
@@ -17,9 +19,30 @@ use rusqlite::config::DbConfig;
 use smartvaults_protocol::nostr::{Keys, PublicKey, Timestamp};
 use tokio::sync::RwLock;
 
+mod app_state;
+mod birthdays;
+mod capabilities;
+mod coin_selection;
 mod connect;
+mod dead_letter;
+mod drafts;
+mod event_deliveries;
+mod hw_registrations;
+mod ingestion_reports;
+mod notifications;
+mod outbox;
+mod outgoing_txs;
+mod passphrase_identities;
+mod profile_history;
+mod proof_verifications;
 mod relays;
+mod reminders;
+mod shared_keys;
+mod signing_audit;
+mod superseded;
 mod timechain;
+mod tx_fees;
+mod wallet_integrity;
 
 use super::encryption::StoreEncryption;
 use super::migration::{self, STARTUP_SQL};
@@ -30,6 +53,8 @@ use super::Error;
 pub struct Store {
     pool: Pool,
     cipher: XChaCha20Poly1305,
+    /// Key of the signing audit log hash chain
+    audit_key: [u8; 32],
     nostr_connect_auto_approve: Arc<RwLock<HashMap<PublicKey, Timestamp>>>,
 }
 
@@ -57,6 +82,7 @@ impl Store {
         Ok(Self {
             pool,
             cipher: XChaCha20Poly1305::new(&key.into()),
+            audit_key: signing_audit::audit_key(&key),
             nostr_connect_auto_approve: Arc::new(RwLock::new(HashMap::new())),
         })
     }
@@ -91,3 +117,37 @@ impl Store {
         Ok(())
     }
 }
+
+/// [`Store`] opened on a temporary file, removed on drop
+#[cfg(test)]
+pub(crate) struct TempStore {
+    pub keys: Keys,
+    pub path: PathBuf,
+    store: Store,
+}
+
+#[cfg(test)]
+impl TempStore {
+    pub async fn new() -> Self {
+        let keys = Keys::generate();
+        let path = std::env::temp_dir().join(format!("smartvaults-{}.db", keys.public_key()));
+        let store = Store::open(&path, &keys).await.unwrap();
+        Self { keys, path, store }
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempStore {
+    type Target = Store;
+
+    fn deref(&self) -> &Self::Target {
+        &self.store
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempStore {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
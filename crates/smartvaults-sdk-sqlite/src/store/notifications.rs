@@ -0,0 +1,185 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::{GetNotificationsResult, NotificationKind};
+use crate::Error;
+
+impl Store {
+    /// Save notification
+    ///
+    /// Return `false` if the notification already exists.
+    pub async fn save_notification(
+        &self,
+        kind: NotificationKind,
+        event_id: EventId,
+        timestamp: Timestamp,
+    ) -> Result<bool, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let rows = conn.execute(
+                "INSERT OR IGNORE INTO notifications (kind, event_id, timestamp) VALUES (?, ?, ?);",
+                (kind.to_string(), event_id.to_hex(), timestamp.as_u64()),
+            )?;
+            Ok(rows > 0)
+        })
+        .await?
+    }
+
+    /// Save notification or, if already exists, bring it back as new unseen notification
+    pub async fn resurface_notification(
+        &self,
+        kind: NotificationKind,
+        event_id: EventId,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO notifications (kind, event_id, timestamp) VALUES (?, ?, ?) ON CONFLICT(kind, event_id) DO UPDATE SET timestamp = excluded.timestamp, seen = FALSE;",
+                (kind.to_string(), event_id.to_hex(), timestamp.as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get notifications (newest first)
+    pub async fn get_notifications(
+        &self,
+        unseen_only: bool,
+    ) -> Result<Vec<GetNotificationsResult>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let sql = if unseen_only {
+                "SELECT kind, event_id, timestamp, seen FROM notifications WHERE seen = FALSE ORDER BY timestamp DESC;"
+            } else {
+                "SELECT kind, event_id, timestamp, seen FROM notifications ORDER BY timestamp DESC;"
+            };
+            let mut stmt = conn.prepare_cached(sql)?;
+            let mut rows = stmt.query([])?;
+            let mut notifications = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let kind: String = row.get(0)?;
+                let event_id: String = row.get(1)?;
+                let timestamp: u64 = row.get(2)?;
+                let seen: bool = row.get(3)?;
+                notifications.push(GetNotificationsResult {
+                    kind: NotificationKind::from_str(&kind)?,
+                    event_id: EventId::from_hex(event_id)?,
+                    timestamp: Timestamp::from(timestamp),
+                    seen,
+                });
+            }
+            Ok(notifications)
+        })
+        .await?
+    }
+
+    pub async fn count_unseen_notifications(&self) -> Result<usize, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT COUNT(*) FROM notifications WHERE seen = FALSE;")?;
+            let count: usize = stmt.query_row([], |row| row.get(0))?;
+            Ok(count)
+        })
+        .await?
+    }
+
+    /// Mark as seen the notifications of the event
+    pub async fn mark_notification_as_seen(&self, event_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE notifications SET seen = TRUE WHERE event_id = ?;",
+                [event_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn mark_all_notifications_as_seen(&self) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute("UPDATE notifications SET seen = TRUE;", [])?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn delete_all_notifications(&self) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute("DELETE FROM notifications;", [])?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_notifications() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let proposal_id =
+            EventId::from_hex("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+
+        assert!(store
+            .save_notification(NotificationKind::NewPolicy, policy_id, Timestamp::from(10))
+            .await
+            .unwrap());
+        assert!(store
+            .save_notification(
+                NotificationKind::NewProposal,
+                proposal_id,
+                Timestamp::from(20)
+            )
+            .await
+            .unwrap());
+        // Already saved
+        assert!(!store
+            .save_notification(NotificationKind::NewPolicy, policy_id, Timestamp::from(30))
+            .await
+            .unwrap());
+        assert_eq!(store.count_unseen_notifications().await.unwrap(), 2);
+
+        store.mark_notification_as_seen(policy_id).await.unwrap();
+        let unseen = store.get_notifications(true).await.unwrap();
+        assert_eq!(unseen.len(), 1);
+        assert_eq!(unseen[0].event_id, proposal_id);
+        assert_eq!(store.get_notifications(false).await.unwrap().len(), 2);
+
+        store.mark_all_notifications_as_seen().await.unwrap();
+        assert_eq!(store.count_unseen_notifications().await.unwrap(), 0);
+
+        // Resurfaced as unseen
+        store
+            .resurface_notification(
+                NotificationKind::NewProposal,
+                proposal_id,
+                Timestamp::from(40),
+            )
+            .await
+            .unwrap();
+        let unseen = store.get_notifications(true).await.unwrap();
+        assert_eq!(unseen.len(), 1);
+        assert_eq!(unseen[0].timestamp, Timestamp::from(40));
+
+        store.delete_all_notifications().await.unwrap();
+        assert!(store.get_notifications(false).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use smartvaults_protocol::nostr::{Event, EventId, JsonUtil, Timestamp, Url};
+
+use super::Store;
+use crate::model::OutboxEvent;
+use crate::Error;
+
+impl Store {
+    /// Save event that failed to be published to `relay_url`
+    pub async fn save_outbox_event(&self, relay_url: Url, event: &Event) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        let event_id: String = event.id.to_hex();
+        let event: String = event.as_json();
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO outbox (event_id, relay_url, event, timestamp) VALUES (?, ?, ?, ?);",
+                (event_id, relay_url.to_string(), event, Timestamp::now().as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_outbox_events(&self) -> Result<Vec<OutboxEvent>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT relay_url, event, attempts, timestamp FROM outbox ORDER BY timestamp ASC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut events = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let relay_url: String = row.get(0)?;
+                let event: String = row.get(1)?;
+                let attempts: u32 = row.get(2)?;
+                let timestamp: u64 = row.get(3)?;
+                events.push(OutboxEvent {
+                    relay_url: Url::parse(&relay_url)?,
+                    event: Event::from_json(event)?,
+                    attempts,
+                    timestamp: Timestamp::from(timestamp),
+                });
+            }
+            Ok(events)
+        })
+        .await?
+    }
+
+    pub async fn increment_outbox_event_attempts(
+        &self,
+        relay_url: Url,
+        event_id: EventId,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE outbox SET attempts = attempts + 1 WHERE event_id = ? AND relay_url = ?;",
+                (event_id.to_hex(), relay_url.to_string()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn delete_outbox_event(
+        &self,
+        relay_url: Url,
+        event_id: EventId,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM outbox WHERE event_id = ? AND relay_url = ?;",
+                (event_id.to_hex(), relay_url.to_string()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+}
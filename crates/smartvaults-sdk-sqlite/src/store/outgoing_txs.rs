@@ -0,0 +1,156 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use smartvaults_core::bitcoin::Txid;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::UnexpectedTx;
+use crate::Error;
+
+impl Store {
+    /// Get the already classified outgoing transactions of a policy (`txid` -> `expected`)
+    pub async fn get_outgoing_txs(&self, policy_id: EventId) -> Result<HashMap<Txid, bool>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn
+                .prepare_cached("SELECT txid, expected FROM outgoing_txs WHERE policy_id = ?;")?;
+            let mut rows = stmt.query([policy_id.to_hex()])?;
+            let mut txs = HashMap::new();
+            while let Ok(Some(row)) = rows.next() {
+                let txid: String = row.get(0)?;
+                let expected: bool = row.get(1)?;
+                txs.insert(Txid::from_str(&txid)?, expected);
+            }
+            Ok(txs)
+        })
+        .await?
+    }
+
+    /// Save the classification of an outgoing transaction
+    ///
+    /// An already classified transaction is never reclassified as unexpected,
+    /// but an unexpected one is marked as expected if a matching completed proposal shows up later.
+    pub async fn save_outgoing_tx(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+        amount: u64,
+        expected: bool,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            if expected {
+                conn.execute(
+                    "INSERT INTO outgoing_txs (policy_id, txid, amount, expected, timestamp) VALUES (?, ?, ?, TRUE, ?) ON CONFLICT(policy_id, txid) DO UPDATE SET expected = TRUE;",
+                    (policy_id.to_hex(), txid.to_string(), amount, Timestamp::now().as_u64()),
+                )?;
+            } else {
+                conn.execute(
+                    "INSERT OR IGNORE INTO outgoing_txs (policy_id, txid, amount, expected, timestamp) VALUES (?, ?, ?, FALSE, ?);",
+                    (policy_id.to_hex(), txid.to_string(), amount, Timestamp::now().as_u64()),
+                )?;
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get unexpected outgoing transactions
+    pub async fn get_unexpected_txs(
+        &self,
+        policy_id: Option<EventId>,
+    ) -> Result<Vec<UnexpectedTx>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT policy_id, txid, amount, acknowledged, timestamp FROM outgoing_txs WHERE expected = FALSE ORDER BY timestamp DESC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut txs = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let id: String = row.get(0)?;
+                let id = EventId::from_hex(id)?;
+                if let Some(policy_id) = policy_id {
+                    if id != policy_id {
+                        continue;
+                    }
+                }
+                let txid: String = row.get(1)?;
+                let amount: u64 = row.get(2)?;
+                let acknowledged: bool = row.get(3)?;
+                let timestamp: u64 = row.get(4)?;
+                txs.push(UnexpectedTx {
+                    policy_id: id,
+                    txid: Txid::from_str(&txid)?,
+                    amount,
+                    acknowledged,
+                    timestamp: Timestamp::from(timestamp),
+                });
+            }
+            Ok(txs)
+        })
+        .await?
+    }
+
+    /// Acknowledge an unexpected outgoing transaction
+    pub async fn acknowledge_unexpected_tx(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE outgoing_txs SET acknowledged = TRUE WHERE policy_id = ? AND txid = ?;",
+                (policy_id.to_hex(), txid.to_string()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_outgoing_txs_classification() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let txid =
+            Txid::from_str("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+
+        store
+            .save_outgoing_tx(policy_id, txid, 10_000, false)
+            .await
+            .unwrap();
+        let txs = store.get_unexpected_txs(Some(policy_id)).await.unwrap();
+        assert_eq!(txs.len(), 1);
+        assert!(!txs[0].acknowledged);
+
+        store
+            .acknowledge_unexpected_tx(policy_id, txid)
+            .await
+            .unwrap();
+        let txs = store.get_unexpected_txs(None).await.unwrap();
+        assert!(txs[0].acknowledged);
+
+        // Matching completed proposal received later
+        store
+            .save_outgoing_tx(policy_id, txid, 10_000, true)
+            .await
+            .unwrap();
+        assert!(store.get_unexpected_txs(None).await.unwrap().is_empty());
+        let outgoing = store.get_outgoing_txs(policy_id).await.unwrap();
+        assert_eq!(outgoing.get(&txid), Some(&true));
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_protocol::nostr::{PublicKey, Timestamp};
+
+use super::Store;
+use crate::model::PassphraseIdentity;
+use crate::Error;
+
+impl Store {
+    /// Save a passphrase identity (the most recent `last_used` is kept)
+    pub async fn save_passphrase_identity(
+        &self,
+        identity: PassphraseIdentity,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO passphrase_identities (fingerprint, public_key, passphrase_hash, last_used) VALUES (?, ?, ?, ?) ON CONFLICT(fingerprint) DO UPDATE SET last_used = MAX(last_used, excluded.last_used);",
+                (
+                    identity.fingerprint.to_string(),
+                    identity.public_key.to_string(),
+                    identity.passphrase_hash.map(|h| h.to_string()),
+                    identity.last_used.as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the passphrase identities, most recently used first
+    pub async fn get_passphrase_identities(&self) -> Result<Vec<PassphraseIdentity>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT fingerprint, public_key, passphrase_hash, last_used FROM passphrase_identities ORDER BY last_used DESC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut identities = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let fingerprint: String = row.get(0)?;
+                let public_key: String = row.get(1)?;
+                let passphrase_hash: Option<String> = row.get(2)?;
+                let last_used: u64 = row.get(3)?;
+                identities.push(PassphraseIdentity {
+                    fingerprint: Fingerprint::from_str(&fingerprint)?,
+                    public_key: PublicKey::from_str(&public_key)?,
+                    passphrase_hash: passphrase_hash
+                        .map(|h| Sha256Hash::from_str(&h))
+                        .transpose()?,
+                    last_used: Timestamp::from(last_used),
+                });
+            }
+            Ok(identities)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_core::bitcoin::hashes::Hash;
+    use smartvaults_protocol::nostr::Keys;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_passphrase_identities() {
+        let store = TempStore::new().await;
+
+        let without = PassphraseIdentity {
+            fingerprint: Fingerprint::from_str("9bf4354b").unwrap(),
+            public_key: store.keys.public_key(),
+            passphrase_hash: None,
+            last_used: Timestamp::from(10),
+        };
+        let with = PassphraseIdentity {
+            fingerprint: Fingerprint::from_str("7356e457").unwrap(),
+            public_key: Keys::generate().public_key(),
+            passphrase_hash: Some(Sha256Hash::hash(b"passphrase")),
+            last_used: Timestamp::from(20),
+        };
+        store
+            .save_passphrase_identity(without.clone())
+            .await
+            .unwrap();
+        store.save_passphrase_identity(with.clone()).await.unwrap();
+        assert_eq!(
+            store.get_passphrase_identities().await.unwrap(),
+            vec![with.clone(), without.clone()]
+        );
+
+        // An older record doesn't move the identity back
+        store
+            .save_passphrase_identity(PassphraseIdentity {
+                last_used: Timestamp::from(5),
+                ..without.clone()
+            })
+            .await
+            .unwrap();
+        store
+            .save_passphrase_identity(PassphraseIdentity {
+                last_used: Timestamp::from(30),
+                ..without.clone()
+            })
+            .await
+            .unwrap();
+        let identities = store.get_passphrase_identities().await.unwrap();
+        assert_eq!(identities[0].fingerprint, without.fingerprint);
+        assert_eq!(identities[0].last_used, Timestamp::from(30));
+    }
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use rusqlite::{OptionalExtension, Row};
+use smartvaults_protocol::nostr::{EventId, JsonUtil, Metadata, Timestamp};
+
+use super::Store;
+use crate::model::ProfileVersion;
+use crate::Error;
+
+fn profile_version_from_row(row: &Row) -> Result<ProfileVersion, Error> {
+    let event_id: String = row.get(1)?;
+    let metadata: String = row.get(2)?;
+    let timestamp: u64 = row.get(3)?;
+    Ok(ProfileVersion {
+        version: row.get(0)?,
+        event_id: EventId::from_hex(event_id)?,
+        metadata: Metadata::from_json(metadata)?,
+        timestamp: Timestamp::from(timestamp),
+    })
+}
+
+impl Store {
+    /// Save a published version of the profile metadata and return the version number
+    pub async fn save_profile_version(
+        &self,
+        event_id: EventId,
+        metadata: Metadata,
+        timestamp: Timestamp,
+    ) -> Result<u64, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO profile_history (event_id, metadata, timestamp) VALUES (?, ?, ?);",
+                (event_id.to_hex(), metadata.as_json(), timestamp.as_u64()),
+            )?;
+            Ok(conn.last_insert_rowid() as u64)
+        })
+        .await?
+    }
+
+    /// Get the published versions of the profile metadata (newest first)
+    pub async fn get_profile_history(&self) -> Result<Vec<ProfileVersion>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT version, event_id, metadata, timestamp FROM profile_history ORDER BY version DESC;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut history = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                history.push(profile_version_from_row(row)?);
+            }
+            Ok(history)
+        })
+        .await?
+    }
+
+    pub async fn get_profile_version(&self, version: u64) -> Result<ProfileVersion, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT version, event_id, metadata, timestamp FROM profile_history WHERE version = ?;",
+            )?;
+            stmt.query_row([version], |row| Ok(profile_version_from_row(row)))
+                .optional()?
+                .ok_or(Error::NotFound("profile version".into()))?
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_profile_history() {
+        let store = TempStore::new().await;
+
+        let first = Metadata::new().name("alice");
+        let second = Metadata::new().name("alice").display_name("Alice");
+
+        let v1 = store
+            .save_profile_version(EventId::all_zeros(), first.clone(), Timestamp::from(10))
+            .await
+            .unwrap();
+        let v2 = store
+            .save_profile_version(EventId::all_zeros(), second.clone(), Timestamp::from(20))
+            .await
+            .unwrap();
+        assert!(v2 > v1);
+
+        let history = store.get_profile_history().await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].metadata, second);
+        assert_eq!(store.get_profile_version(v1).await.unwrap().metadata, first);
+        assert!(store.get_profile_version(v2 + 1).await.is_err());
+    }
+}
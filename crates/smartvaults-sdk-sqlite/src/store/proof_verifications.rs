@@ -0,0 +1,73 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::ProofVerification;
+use crate::Error;
+
+impl Store {
+    /// Save (or replace) the verdict of a Proof of Reserve
+    pub async fn save_proof_verification(
+        &self,
+        verification: ProofVerification,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO proof_verifications (completed_proposal_id, policy_id, spendable, error, height, utxos_digest, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?);",
+                (
+                    verification.completed_proposal_id.to_hex(),
+                    verification.policy_id.to_hex(),
+                    verification.spendable,
+                    verification.error,
+                    verification.height,
+                    verification.utxos_digest.to_string(),
+                    verification.timestamp.as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the Proof of Reserve verdicts (`completed proposal ID` -> `verdict`)
+    pub async fn get_proof_verifications(
+        &self,
+    ) -> Result<HashMap<EventId, ProofVerification>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT completed_proposal_id, policy_id, spendable, error, height, utxos_digest, timestamp FROM proof_verifications;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut verifications = HashMap::new();
+            while let Ok(Some(row)) = rows.next() {
+                let completed_proposal_id: String = row.get(0)?;
+                let completed_proposal_id = EventId::from_hex(completed_proposal_id)?;
+                let policy_id: String = row.get(1)?;
+                let utxos_digest: String = row.get(5)?;
+                let timestamp: u64 = row.get(6)?;
+                verifications.insert(
+                    completed_proposal_id,
+                    ProofVerification {
+                        completed_proposal_id,
+                        policy_id: EventId::from_hex(policy_id)?,
+                        spendable: row.get(2)?,
+                        error: row.get(3)?,
+                        height: row.get(4)?,
+                        utxos_digest: Sha256Hash::from_str(&utxos_digest)?,
+                        timestamp: Timestamp::from(timestamp),
+                    },
+                );
+            }
+            Ok(verifications)
+        })
+        .await?
+    }
+}
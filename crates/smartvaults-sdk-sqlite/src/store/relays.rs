@@ -1,10 +1,12 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 
 use smartvaults_protocol::nostr::{Timestamp, Url};
 
+use crate::model::RelayRole;
 use crate::{Error, Store};
 
 impl Store {
@@ -35,26 +37,43 @@ impl Store {
         .await?
     }
 
-    pub async fn insert_relay(&self, url: Url, proxy: Option<SocketAddr>) -> Result<(), Error> {
+    /// Insert relay
+    ///
+    /// If the relay already exists, the proxy is updated only if `Some`
+    /// (see [`Store::set_relay_proxy`] to remove it).
+    pub async fn insert_relay(
+        &self,
+        url: Url,
+        proxy: Option<SocketAddr>,
+        role: RelayRole,
+    ) -> Result<(), Error> {
         let conn = self.acquire().await?;
         conn.interact(move |conn| {
             conn.execute(
-                "INSERT OR IGNORE INTO relays (url, proxy) VALUES (?, ?);",
-                (url.as_str(), proxy.map(|a| a.to_string())),
+                "INSERT INTO relays (url, proxy, read, write) VALUES (?, ?, ?, ?) ON CONFLICT(url) DO UPDATE SET proxy = COALESCE(excluded.proxy, proxy);",
+                (
+                    url.as_str(),
+                    proxy.map(|a| a.to_string()),
+                    role.read,
+                    role.write,
+                ),
             )?;
             Ok(())
         })
         .await?
     }
 
-    pub async fn get_relays(&self, enabled: bool) -> Result<Vec<(Url, Option<SocketAddr>)>, Error> {
+    pub async fn get_relays(
+        &self,
+        enabled: bool,
+    ) -> Result<Vec<(Url, Option<SocketAddr>, RelayRole)>, Error> {
         let conn = self.acquire().await?;
         conn.interact(move |conn| {
-            let mut stmt =
-                conn.prepare_cached("SELECT url, proxy FROM relays WHERE enabled = ?")?;
+            let mut stmt = conn
+                .prepare_cached("SELECT url, proxy, read, write FROM relays WHERE enabled = ?")?;
             let mut rows = stmt.query([enabled])?;
 
-            let mut relays: Vec<(Url, Option<SocketAddr>)> = Vec::new();
+            let mut relays: Vec<(Url, Option<SocketAddr>, RelayRole)> = Vec::new();
             while let Ok(Some(row)) = rows.next() {
                 let url: String = row.get(0)?;
                 let proxy: Option<String> = row.get(1)?;
@@ -64,6 +83,10 @@ impl Store {
                         .map(|p| p.parse())
                         .filter(|r| r.is_ok())
                         .map(|r| r.unwrap()),
+                    RelayRole {
+                        read: row.get(2)?,
+                        write: row.get(3)?,
+                    },
                 ));
             }
             Ok(relays)
@@ -71,6 +94,60 @@ impl Store {
         .await?
     }
 
+    /// Get the relays roles (`url` -> `role`)
+    ///
+    /// The relays not included are both read and write.
+    pub async fn get_relays_roles(&self) -> Result<HashMap<Url, RelayRole>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT url, read, write FROM relays")?;
+            let mut rows = stmt.query([])?;
+            let mut roles = HashMap::new();
+            while let Ok(Some(row)) = rows.next() {
+                let url: String = row.get(0)?;
+                roles.insert(
+                    Url::parse(&url)?,
+                    RelayRole {
+                        read: row.get(1)?,
+                        write: row.get(2)?,
+                    },
+                );
+            }
+            Ok(roles)
+        })
+        .await?
+    }
+
+    pub async fn set_relay_role(&self, url: Url, role: RelayRole) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "UPDATE relays SET read = ?, write = ? WHERE url = ?;",
+                (role.read, role.write, url.as_str()),
+            )?;
+            if n == 0 {
+                return Err(Error::NotFound("relay".into()));
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn set_relay_proxy(&self, url: Url, proxy: Option<SocketAddr>) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let n: usize = conn.execute(
+                "UPDATE relays SET proxy = ? WHERE url = ?;",
+                (proxy.map(|a| a.to_string()), url.as_str()),
+            )?;
+            if n == 0 {
+                return Err(Error::NotFound("relay".into()));
+            }
+            Ok(())
+        })
+        .await?
+    }
+
     pub async fn delete_relay(&self, url: Url) -> Result<(), Error> {
         let conn = self.acquire().await?;
         conn.interact(move |conn| {
@@ -104,3 +181,48 @@ impl Store {
         .await?
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_relay_proxy() {
+        let store = TempStore::new().await;
+
+        let url = Url::parse("ws://example.onion").unwrap();
+        let proxy: SocketAddr = "127.0.0.1:9050".parse().unwrap();
+
+        // Row created by the sync, before the relay is added
+        store
+            .save_last_relay_sync(url.clone(), Timestamp::from(10))
+            .await
+            .unwrap();
+        store
+            .insert_relay(url.clone(), Some(proxy), RelayRole::default())
+            .await
+            .unwrap();
+        store.enable_relay(url.clone()).await.unwrap();
+        assert_eq!(
+            store.get_relays(true).await.unwrap(),
+            vec![(url.clone(), Some(proxy), RelayRole::default())]
+        );
+
+        // Re-adding without proxy keeps the stored one
+        store
+            .insert_relay(url.clone(), None, RelayRole::default())
+            .await
+            .unwrap();
+        assert_eq!(store.get_relays(true).await.unwrap()[0].1, Some(proxy));
+
+        store.set_relay_proxy(url.clone(), None).await.unwrap();
+        assert_eq!(store.get_relays(true).await.unwrap()[0].1, None);
+
+        assert!(store
+            .set_relay_proxy(Url::parse("wss://unknown.com").unwrap(), Some(proxy))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::time::Duration;
+
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::{ReminderSettings, TrackedReminder};
+use crate::Error;
+
+impl Store {
+    /// Set the approval reminders settings of a policy (`None` to disable the reminders)
+    pub async fn set_reminder_settings(
+        &self,
+        policy_id: EventId,
+        settings: Option<ReminderSettings>,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            match settings {
+                Some(settings) => {
+                    conn.execute(
+                        "INSERT OR REPLACE INTO reminder_settings (policy_id, interval, max_reminders) VALUES (?, ?, ?);",
+                        (
+                            policy_id.to_hex(),
+                            settings.interval.as_secs(),
+                            settings.max_reminders,
+                        ),
+                    )?;
+                }
+                None => {
+                    conn.execute(
+                        "DELETE FROM reminder_settings WHERE policy_id = ?;",
+                        [policy_id.to_hex()],
+                    )?;
+                }
+            }
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_reminder_settings(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Option<ReminderSettings>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT interval, max_reminders FROM reminder_settings WHERE policy_id = ?;",
+            )?;
+            let mut rows = stmt.query([policy_id.to_hex()])?;
+            match rows.next()? {
+                Some(row) => {
+                    let interval: u64 = row.get(0)?;
+                    let max_reminders: u16 = row.get(1)?;
+                    Ok(Some(ReminderSettings {
+                        interval: Duration::from_secs(interval),
+                        max_reminders,
+                    }))
+                }
+                None => Ok(None),
+            }
+        })
+        .await?
+    }
+
+    /// Track a proposal created by me, to remind the pending approvers
+    pub async fn track_reminders(
+        &self,
+        proposal_id: EventId,
+        policy_id: EventId,
+        created_at: Timestamp,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO approval_reminders (proposal_id, policy_id, created_at) VALUES (?, ?, ?);",
+                (proposal_id.to_hex(), policy_id.to_hex(), created_at.as_u64()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_tracked_reminders(&self) -> Result<Vec<TrackedReminder>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT proposal_id, policy_id, created_at, sent, last_sent_at FROM approval_reminders;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut reminders = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let proposal_id: String = row.get(0)?;
+                let policy_id: String = row.get(1)?;
+                let created_at: u64 = row.get(2)?;
+                let sent: u16 = row.get(3)?;
+                let last_sent_at: Option<u64> = row.get(4)?;
+                reminders.push(TrackedReminder {
+                    proposal_id: EventId::from_hex(proposal_id)?,
+                    policy_id: EventId::from_hex(policy_id)?,
+                    created_at: Timestamp::from(created_at),
+                    sent,
+                    last_sent_at: last_sent_at.map(Timestamp::from),
+                });
+            }
+            Ok(reminders)
+        })
+        .await?
+    }
+
+    /// Increment the number of reminders sent for the proposal
+    pub async fn reminder_sent(
+        &self,
+        proposal_id: EventId,
+        timestamp: Timestamp,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "UPDATE approval_reminders SET sent = sent + 1, last_sent_at = ? WHERE proposal_id = ?;",
+                (timestamp.as_u64(), proposal_id.to_hex()),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Stop tracking the proposal (finalized, deleted or all reminders sent)
+    pub async fn untrack_reminders(&self, proposal_id: EventId) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "DELETE FROM approval_reminders WHERE proposal_id = ?;",
+                [proposal_id.to_hex()],
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Mute or unmute the reminders received for a proposal
+    pub async fn set_reminders_muted(
+        &self,
+        proposal_id: EventId,
+        muted: bool,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let sql = if muted {
+                "INSERT OR IGNORE INTO muted_reminders (proposal_id) VALUES (?);"
+            } else {
+                "DELETE FROM muted_reminders WHERE proposal_id = ?;"
+            };
+            conn.execute(sql, [proposal_id.to_hex()])?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn are_reminders_muted(&self, proposal_id: EventId) -> Result<bool, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT EXISTS(SELECT 1 FROM muted_reminders WHERE proposal_id = ? LIMIT 1);",
+            )?;
+            let exists: u8 = stmt.query_row([proposal_id.to_hex()], |row| row.get(0))?;
+            Ok(exists == 1)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_approval_reminders() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let proposal_id =
+            EventId::from_hex("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+
+        let settings = ReminderSettings {
+            interval: Duration::from_secs(3600),
+            max_reminders: 2,
+        };
+        store
+            .set_reminder_settings(policy_id, Some(settings))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_reminder_settings(policy_id).await.unwrap(),
+            Some(settings)
+        );
+
+        store
+            .track_reminders(proposal_id, policy_id, Timestamp::from(1000))
+            .await
+            .unwrap();
+        let reminder = store.get_tracked_reminders().await.unwrap()[0];
+        assert_eq!(reminder.next_due(&settings), Some(Timestamp::from(4600)));
+
+        store
+            .reminder_sent(proposal_id, Timestamp::from(5000))
+            .await
+            .unwrap();
+        store
+            .reminder_sent(proposal_id, Timestamp::from(9000))
+            .await
+            .unwrap();
+        let reminder = store.get_tracked_reminders().await.unwrap()[0];
+        assert_eq!(reminder.sent, 2);
+        assert_eq!(reminder.last_sent_at, Some(Timestamp::from(9000)));
+        assert_eq!(reminder.next_due(&settings), None);
+
+        store.untrack_reminders(proposal_id).await.unwrap();
+        assert!(store.get_tracked_reminders().await.unwrap().is_empty());
+
+        assert!(!store.are_reminders_muted(proposal_id).await.unwrap());
+        store.set_reminders_muted(proposal_id, true).await.unwrap();
+        assert!(store.are_reminders_muted(proposal_id).await.unwrap());
+        store.set_reminders_muted(proposal_id, false).await.unwrap();
+        assert!(!store.are_reminders_muted(proposal_id).await.unwrap());
+
+        store.set_reminder_settings(policy_id, None).await.unwrap();
+        assert_eq!(store.get_reminder_settings(policy_id).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use smartvaults_core::secp256k1::SecretKey;
+use smartvaults_protocol::nostr::{EventId, Keys, Timestamp};
+
+use super::{Store, StoreEncryption};
+use crate::model::RotatedSharedKey;
+use crate::Error;
+
+impl Store {
+    /// Keep the shared key replaced by a rotation
+    pub async fn save_rotated_shared_key(
+        &self,
+        policy_id: EventId,
+        shared_key: &Keys,
+        new_policy_id: EventId,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        let cipher = self.cipher.clone();
+        let secret_key: SecretKey = *shared_key.secret_key()?;
+        conn.interact(move |conn| {
+            let data: Vec<u8> = secret_key.encrypt(&cipher)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO rotated_shared_keys (policy_id, shared_key, new_policy_id, rotated_at) VALUES (?, ?, ?, ?);",
+                (
+                    policy_id.to_hex(),
+                    data,
+                    new_policy_id.to_hex(),
+                    Timestamp::now().as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    pub async fn get_rotated_shared_keys(&self) -> Result<Vec<RotatedSharedKey>, Error> {
+        let conn = self.acquire().await?;
+        let cipher = self.cipher.clone();
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT policy_id, shared_key, new_policy_id, rotated_at FROM rotated_shared_keys;",
+            )?;
+            let mut rows = stmt.query([])?;
+            let mut keys = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let policy_id: String = row.get(0)?;
+                let data: Vec<u8> = row.get(1)?;
+                let new_policy_id: String = row.get(2)?;
+                let rotated_at: u64 = row.get(3)?;
+                keys.push(RotatedSharedKey {
+                    policy_id: EventId::from_hex(policy_id)?,
+                    shared_key: Keys::new(SecretKey::decrypt(&cipher, data)?),
+                    new_policy_id: EventId::from_hex(new_policy_id)?,
+                    rotated_at: Timestamp::from(rotated_at),
+                });
+            }
+            Ok(keys)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_rotated_shared_keys() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let new_policy_id =
+            EventId::from_hex("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+        let shared_key = Keys::generate();
+        store
+            .save_rotated_shared_key(policy_id, &shared_key, new_policy_id)
+            .await
+            .unwrap();
+
+        let rotated = store.get_rotated_shared_keys().await.unwrap();
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(rotated[0].policy_id, policy_id);
+        assert_eq!(rotated[0].new_policy_id, new_policy_id);
+        assert_eq!(rotated[0].shared_key.public_key(), shared_key.public_key());
+    }
+}
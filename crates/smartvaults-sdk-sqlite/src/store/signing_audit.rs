@@ -0,0 +1,429 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use rusqlite::{Connection, OptionalExtension, Transaction};
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_core::bitcoin::hashes::hmac::{Hmac, HmacEngine};
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::hashes::{Hash, HashEngine};
+use smartvaults_protocol::nostr::Timestamp;
+
+use super::Store;
+use crate::model::{AuditLogIntegrity, SigningAuditEntry, SigningKind};
+use crate::Error;
+
+/// Derive the key of the hash chain from the account secret key
+pub(super) fn audit_key(secret_key: &[u8; 32]) -> [u8; 32] {
+    let mut engine: HmacEngine<Sha256Hash> = HmacEngine::new(secret_key);
+    engine.input(b"smartvaults-signing-audit");
+    Hmac::<Sha256Hash>::from_engine(engine).to_byte_array()
+}
+
+fn entry_data(
+    prev_hash: &Sha256Hash,
+    timestamp: Timestamp,
+    fingerprint: &Fingerprint,
+    kind: SigningKind,
+    object_id: &str,
+    digest: &Sha256Hash,
+) -> String {
+    format!("{prev_hash}|{timestamp}|{fingerprint}|{kind}|{object_id}|{digest}")
+}
+
+/// `hmac(key, prev_hash | entry)`: can't be recomputed without the account key
+fn entry_hash(
+    key: &[u8; 32],
+    prev_hash: &Sha256Hash,
+    timestamp: Timestamp,
+    fingerprint: &Fingerprint,
+    kind: SigningKind,
+    object_id: &str,
+    digest: &Sha256Hash,
+) -> Sha256Hash {
+    let data: String = entry_data(prev_hash, timestamp, fingerprint, kind, object_id, digest);
+    let mut engine: HmacEngine<Sha256Hash> = HmacEngine::new(key);
+    engine.input(data.as_bytes());
+    let hmac: Hmac<Sha256Hash> = Hmac::from_engine(engine);
+    Sha256Hash::from_byte_array(hmac.to_byte_array())
+}
+
+fn get_head(conn: &Connection) -> Result<Option<(u64, Sha256Hash)>, Error> {
+    let head: Option<(u64, String)> = conn
+        .query_row(
+            "SELECT entries, hash FROM signing_audit_head WHERE id = 0;",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    match head {
+        Some((entries, hash)) => Ok(Some((entries, Sha256Hash::from_str(&hash)?))),
+        None => Ok(None),
+    }
+}
+
+fn append(
+    tx: &Transaction,
+    key: &[u8; 32],
+    timestamp: Timestamp,
+    fingerprint: Fingerprint,
+    kind: SigningKind,
+    object_id: String,
+    digest: Sha256Hash,
+) -> Result<(), Error> {
+    let (entries, prev_hash) = get_head(tx)?.unwrap_or((0, Sha256Hash::all_zeros()));
+    let hash = entry_hash(
+        key,
+        &prev_hash,
+        timestamp,
+        &fingerprint,
+        kind,
+        &object_id,
+        &digest,
+    );
+    tx.execute(
+        "INSERT INTO signing_audit (timestamp, fingerprint, kind, object_id, digest, prev_hash, hash) VALUES (?, ?, ?, ?, ?, ?, ?);",
+        (
+            timestamp.as_u64(),
+            fingerprint.to_string(),
+            kind.to_string(),
+            object_id,
+            digest.to_string(),
+            prev_hash.to_string(),
+            hash.to_string(),
+        ),
+    )?;
+    tx.execute(
+        "INSERT OR REPLACE INTO signing_audit_head (id, entries, hash) VALUES (0, ?, ?);",
+        (entries + 1, hash.to_string()),
+    )?;
+    Ok(())
+}
+
+fn query_entries(
+    conn: &Connection,
+    fingerprint: Option<Fingerprint>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> Result<Vec<SigningAuditEntry>, Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, timestamp, fingerprint, kind, object_id, digest, prev_hash, hash FROM signing_audit WHERE (?1 IS NULL OR fingerprint = ?1) AND timestamp >= ?2 AND timestamp <= ?3 ORDER BY id ASC;",
+    )?;
+    let mut rows = stmt.query((
+        fingerprint.map(|f| f.to_string()),
+        from.map(|t| t.as_u64()).unwrap_or(0),
+        to.map(|t| t.as_u64()).unwrap_or(i64::MAX as u64),
+    ))?;
+    let mut entries = Vec::new();
+    while let Ok(Some(row)) = rows.next() {
+        let timestamp: u64 = row.get(1)?;
+        let fingerprint: String = row.get(2)?;
+        let kind: String = row.get(3)?;
+        let digest: String = row.get(5)?;
+        let prev_hash: String = row.get(6)?;
+        let hash: String = row.get(7)?;
+        entries.push(SigningAuditEntry {
+            id: row.get(0)?,
+            timestamp: Timestamp::from(timestamp),
+            fingerprint: Fingerprint::from_str(&fingerprint)?,
+            kind: SigningKind::from_str(&kind)?,
+            object_id: row.get(4)?,
+            digest: Sha256Hash::from_str(&digest)?,
+            prev_hash: Sha256Hash::from_str(&prev_hash)?,
+            hash: Sha256Hash::from_str(&hash)?,
+        });
+    }
+    Ok(entries)
+}
+
+/// Verify the hash chain of `entries` (all of them, ordered by ID) against the `head`
+///
+/// Detect edited, removed or reordered entries and the truncation of the log.
+fn verify_chain<F>(
+    entries: &[SigningAuditEntry],
+    head: Option<(u64, Sha256Hash)>,
+    hash_entry: F,
+) -> AuditLogIntegrity
+where
+    F: Fn(&SigningAuditEntry) -> Sha256Hash,
+{
+    let mut prev_hash: Sha256Hash = Sha256Hash::all_zeros();
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.id != index as u64 + 1 {
+            return AuditLogIntegrity::Tampered {
+                reason: format!("missing entry before #{}", entry.id),
+            };
+        }
+
+        if entry.prev_hash != prev_hash {
+            return AuditLogIntegrity::Tampered {
+                reason: format!("broken chain at entry #{}", entry.id),
+            };
+        }
+
+        let hash: Sha256Hash = hash_entry(entry);
+        if entry.hash != hash {
+            return AuditLogIntegrity::Tampered {
+                reason: format!("entry #{} was edited", entry.id),
+            };
+        }
+
+        prev_hash = hash;
+    }
+
+    let len: u64 = entries.len() as u64;
+    match head {
+        Some((count, hash)) if count == len && hash == prev_hash => {
+            AuditLogIntegrity::Valid { entries: len }
+        }
+        None if len == 0 => AuditLogIntegrity::Valid { entries: 0 },
+        Some((count, ..)) if count > len => AuditLogIntegrity::Tampered {
+            reason: format!("log truncated: {len}/{count} entries"),
+        },
+        _ => AuditLogIntegrity::Tampered {
+            reason: String::from("head of the chain not match"),
+        },
+    }
+}
+
+impl Store {
+    /// Append a signing operation to the audit log
+    pub async fn append_signing_audit<S>(
+        &self,
+        fingerprint: Fingerprint,
+        kind: SigningKind,
+        object_id: S,
+        digest: Sha256Hash,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let key: [u8; 32] = self.audit_key;
+        let object_id: String = object_id.into();
+        conn.interact(move |conn| {
+            let tx = conn.transaction()?;
+            append(
+                &tx,
+                &key,
+                Timestamp::now(),
+                fingerprint,
+                kind,
+                object_id,
+                digest,
+            )?;
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get signing audit log entries, optionally filtered by [`Fingerprint`] and time range (inclusive)
+    pub async fn get_signing_audit(
+        &self,
+        fingerprint: Option<Fingerprint>,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+    ) -> Result<Vec<SigningAuditEntry>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| query_entries(conn, fingerprint, from, to))
+            .await?
+    }
+
+    /// Count the signing audit log entries of `object_id`
+    pub async fn count_signing_audit_by_object<S>(&self, object_id: S) -> Result<usize, Error>
+    where
+        S: Into<String>,
+    {
+        let conn = self.acquire().await?;
+        let object_id: String = object_id.into();
+        conn.interact(move |conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT COUNT(*) FROM signing_audit WHERE object_id = ?;")?;
+            let count: usize = stmt.query_row([object_id], |row| row.get(0))?;
+            Ok(count)
+        })
+        .await?
+    }
+
+    /// Verify the keyed hash chain of the signing audit log
+    ///
+    /// Detect edited, removed or reordered entries and the truncation of the log.
+    pub async fn verify_signing_audit(&self) -> Result<AuditLogIntegrity, Error> {
+        let conn = self.acquire().await?;
+        let key: [u8; 32] = self.audit_key;
+        conn.interact(move |conn| {
+            // Same snapshot for the entries and the head
+            let tx = conn.transaction()?;
+            let entries: Vec<SigningAuditEntry> = query_entries(&tx, None, None, None)?;
+            let head: Option<(u64, Sha256Hash)> = get_head(&tx)?;
+            tx.commit()?;
+            Ok(verify_chain(&entries, head, |entry| {
+                entry_hash(
+                    &key,
+                    &entry.prev_hash,
+                    entry.timestamp,
+                    &entry.fingerprint,
+                    entry.kind,
+                    &entry.object_id,
+                    &entry.digest,
+                )
+            }))
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_protocol::nostr::Keys;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_signing_audit_chain() {
+        let store = TempStore::new().await;
+
+        let fingerprint = Fingerprint::from_str("7356e457").unwrap();
+        for i in 0..3 {
+            store
+                .append_signing_audit(
+                    fingerprint,
+                    SigningKind::Approval,
+                    format!("proposal-{i}"),
+                    Sha256Hash::hash(&[i]),
+                )
+                .await
+                .unwrap();
+        }
+
+        let other = Fingerprint::from_str("9bf4354b").unwrap();
+        store
+            .append_signing_audit(
+                other,
+                SigningKind::ProofOfReserve,
+                "proposal-3",
+                Sha256Hash::hash(&[3]),
+            )
+            .await
+            .unwrap();
+
+        let entries = store
+            .get_signing_audit(Some(fingerprint), None, None)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[1].prev_hash, entries[0].hash);
+        let entries = store.get_signing_audit(None, None, None).await.unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].fingerprint, other);
+        assert_eq!(
+            store.verify_signing_audit().await.unwrap(),
+            AuditLogIntegrity::Valid { entries: 4 }
+        );
+
+        // Entries can't be edited or deleted
+        let conn = store.acquire().await.unwrap();
+        let res = conn
+            .interact(|conn| conn.execute("DELETE FROM signing_audit WHERE id = 4;", []))
+            .await
+            .unwrap();
+        assert!(res.is_err());
+
+        // Truncation (bypassing the triggers)
+        conn.interact(|conn| {
+            conn.execute_batch(
+                "DROP TRIGGER signing_audit_no_delete; DELETE FROM signing_audit WHERE id = 4;",
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!store.verify_signing_audit().await.unwrap().is_valid());
+    }
+
+    /// Plain SHA256 of the entry: what an attacker without the account key could compute
+    fn sha256_entry_hash(entry: &SigningAuditEntry) -> Sha256Hash {
+        let data: String = entry_data(
+            &entry.prev_hash,
+            entry.timestamp,
+            &entry.fingerprint,
+            entry.kind,
+            &entry.object_id,
+            &entry.digest,
+        );
+        Sha256Hash::hash(data.as_bytes())
+    }
+
+    /// Rewrite the log consistently, without the account key (hash chain and head)
+    fn rewrite_chain(conn: &mut Connection) {
+        let tx = conn.transaction().unwrap();
+        let entries = query_entries(&tx, None, None, None).unwrap();
+        tx.execute_batch(
+            "DROP TRIGGER IF EXISTS signing_audit_no_update; DROP TRIGGER IF EXISTS signing_audit_no_delete; DELETE FROM signing_audit; DELETE FROM signing_audit_head;",
+        )
+        .unwrap();
+        let mut prev_hash = Sha256Hash::all_zeros();
+        for mut entry in entries.into_iter() {
+            entry.object_id = format!("{}-edited", entry.object_id);
+            entry.prev_hash = prev_hash;
+            entry.hash = sha256_entry_hash(&entry);
+            tx.execute(
+                "INSERT INTO signing_audit (id, timestamp, fingerprint, kind, object_id, digest, prev_hash, hash) VALUES (?, ?, ?, ?, ?, ?, ?, ?);",
+                (
+                    entry.id,
+                    entry.timestamp.as_u64(),
+                    entry.fingerprint.to_string(),
+                    entry.kind.to_string(),
+                    entry.object_id,
+                    entry.digest.to_string(),
+                    entry.prev_hash.to_string(),
+                    entry.hash.to_string(),
+                ),
+            )
+            .unwrap();
+            prev_hash = entry.hash;
+            tx.execute(
+                "INSERT OR REPLACE INTO signing_audit_head (id, entries, hash) VALUES (0, ?, ?);",
+                (entry.id, prev_hash.to_string()),
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_signing_audit_keyed_chain() {
+        let store = TempStore::new().await;
+
+        let fingerprint = Fingerprint::from_str("7356e457").unwrap();
+        for i in 0..2 {
+            store
+                .append_signing_audit(
+                    fingerprint,
+                    SigningKind::Approval,
+                    format!("proposal-{i}"),
+                    Sha256Hash::hash(&[i]),
+                )
+                .await
+                .unwrap();
+        }
+        assert!(store.verify_signing_audit().await.unwrap().is_valid());
+
+        // Not valid with the key of another account
+        let other = Store::open(&store.path, &Keys::generate()).await.unwrap();
+        assert!(!other.verify_signing_audit().await.unwrap().is_valid());
+
+        // Consistent rewrite with plain SHA256
+        let conn = store.acquire().await.unwrap();
+        conn.interact(rewrite_chain).await.unwrap();
+        assert_eq!(
+            store.verify_signing_audit().await.unwrap(),
+            AuditLogIntegrity::Tampered {
+                reason: String::from("entry #1 was edited")
+            }
+        );
+    }
+}
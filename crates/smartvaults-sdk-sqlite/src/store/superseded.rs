@@ -0,0 +1,118 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::{HashMap, HashSet};
+
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::Error;
+
+impl Store {
+    /// Mark proposal (or completed proposal) as superseded by a confirmed replacement
+    pub async fn set_superseded(
+        &self,
+        proposal_id: EventId,
+        replaced_by: EventId,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO superseded (proposal_id, replaced_by, timestamp) VALUES (?, ?, ?);",
+                (
+                    proposal_id.to_hex(),
+                    replaced_by.to_hex(),
+                    Timestamp::now().as_u64(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get superseded proposals (`proposal_id` -> `replaced_by`)
+    pub async fn get_superseded(&self) -> Result<HashMap<EventId, EventId>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt =
+                conn.prepare_cached("SELECT proposal_id, replaced_by FROM superseded;")?;
+            let mut rows = stmt.query([])?;
+            let mut superseded = HashMap::new();
+            while let Ok(Some(row)) = rows.next() {
+                let proposal_id: String = row.get(0)?;
+                let replaced_by: String = row.get(1)?;
+                superseded.insert(
+                    EventId::from_hex(proposal_id)?,
+                    EventId::from_hex(replaced_by)?,
+                );
+            }
+            Ok(superseded)
+        })
+        .await?
+    }
+
+    /// Count the superseded links referencing `event_id` (as superseded or as replacement)
+    pub async fn count_superseded_links(&self, event_id: EventId) -> Result<usize, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT COUNT(*) FROM superseded WHERE proposal_id = ? OR replaced_by = ?;",
+            )?;
+            let count: usize =
+                stmt.query_row([event_id.to_hex(), event_id.to_hex()], |row| row.get(0))?;
+            Ok(count)
+        })
+        .await?
+    }
+
+    /// Delete the superseded links referencing any of `event_ids`
+    ///
+    /// Return the number of deleted rows.
+    pub async fn delete_superseded_links(
+        &self,
+        event_ids: HashSet<EventId>,
+    ) -> Result<usize, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let tx = conn.transaction()?;
+            let mut deleted: usize = 0;
+            for event_id in event_ids.into_iter() {
+                deleted += tx.execute(
+                    "DELETE FROM superseded WHERE proposal_id = ? OR replaced_by = ?;",
+                    [event_id.to_hex(), event_id.to_hex()],
+                )?;
+            }
+            tx.commit()?;
+            Ok(deleted)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_superseded_links() {
+        let store = TempStore::new().await;
+
+        let original = EventId::all_zeros();
+        let replacement =
+            EventId::from_hex("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+        store.set_superseded(original, replacement).await.unwrap();
+
+        assert_eq!(store.count_superseded_links(original).await.unwrap(), 1);
+        assert_eq!(store.count_superseded_links(replacement).await.unwrap(), 1);
+
+        let deleted = store
+            .delete_superseded_links(HashSet::from([replacement]))
+            .await
+            .unwrap();
+        assert_eq!(deleted, 1);
+        assert!(store.get_superseded().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use smartvaults_core::bitcoin::Txid;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::TxFee;
+use crate::Error;
+
+impl Store {
+    /// Replace the fees of the outgoing transactions of a policy
+    ///
+    /// The fees of the transactions not in `fees` (ex. replaced by RBF) are removed.
+    pub async fn save_tx_fees(&self, policy_id: EventId, fees: Vec<TxFee>) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM tx_fees WHERE policy_id = ?;",
+                [policy_id.to_hex()],
+            )?;
+            {
+                let mut stmt = tx.prepare_cached(
+                    "INSERT INTO tx_fees (policy_id, txid, fee, estimated, timestamp) VALUES (?, ?, ?, ?, ?);",
+                )?;
+                for fee in fees.into_iter() {
+                    stmt.execute((
+                        policy_id.to_hex(),
+                        fee.txid.to_string(),
+                        fee.fee,
+                        fee.estimated,
+                        fee.timestamp.as_u64(),
+                    ))?;
+                }
+            }
+            tx.commit()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the fees paid by a policy between `from` and `to` (inclusive), newest first
+    pub async fn get_tx_fees(
+        &self,
+        policy_id: EventId,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+    ) -> Result<Vec<TxFee>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT txid, fee, estimated, timestamp FROM tx_fees WHERE policy_id = ? AND timestamp >= ? AND timestamp <= ? ORDER BY timestamp DESC;",
+            )?;
+            let mut rows = stmt.query((
+                policy_id.to_hex(),
+                from.map(|t| t.as_u64()).unwrap_or(0),
+                to.map(|t| t.as_u64()).unwrap_or(i64::MAX as u64),
+            ))?;
+            let mut fees = Vec::new();
+            while let Ok(Some(row)) = rows.next() {
+                let txid: String = row.get(0)?;
+                let fee: u64 = row.get(1)?;
+                let estimated: bool = row.get(2)?;
+                let timestamp: u64 = row.get(3)?;
+                fees.push(TxFee {
+                    txid: Txid::from_str(&txid)?,
+                    fee,
+                    estimated,
+                    timestamp: Timestamp::from(timestamp),
+                });
+            }
+            Ok(fees)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_tx_fees() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let txid1 =
+            Txid::from_str("b3e392b11f5d4f28321cedd09303a748acfd0487aea5a7450b3481c60b6e4f87")
+                .unwrap();
+        let txid2 =
+            Txid::from_str("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b")
+                .unwrap();
+
+        let fee1 = TxFee {
+            txid: txid1,
+            fee: 1_500,
+            estimated: false,
+            timestamp: Timestamp::from(1_700_000_000),
+        };
+        let fee2 = TxFee {
+            txid: txid2,
+            fee: 800,
+            estimated: true,
+            timestamp: Timestamp::from(1_710_000_000),
+        };
+        store
+            .save_tx_fees(policy_id, vec![fee1, fee2])
+            .await
+            .unwrap();
+
+        let fees = store.get_tx_fees(policy_id, None, None).await.unwrap();
+        assert_eq!(fees, vec![fee2, fee1]);
+
+        let fees = store
+            .get_tx_fees(policy_id, Some(Timestamp::from(1_705_000_000)), None)
+            .await
+            .unwrap();
+        assert_eq!(fees, vec![fee2]);
+
+        // Replaced
+        store.save_tx_fees(policy_id, vec![fee1]).await.unwrap();
+        let fees = store.get_tx_fees(policy_id, None, None).await.unwrap();
+        assert_eq!(fees, vec![fee1]);
+    }
+}
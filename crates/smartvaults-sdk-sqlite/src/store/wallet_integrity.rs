@@ -0,0 +1,150 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::str::FromStr;
+
+use rusqlite::OptionalExtension;
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::Network;
+use smartvaults_protocol::nostr::{EventId, Timestamp};
+
+use super::Store;
+use crate::model::WalletIntegrity;
+use crate::Error;
+
+impl Store {
+    /// Save the descriptor and network the wallet state of the policy is built for
+    pub async fn save_wallet_integrity(
+        &self,
+        policy_id: EventId,
+        integrity: WalletIntegrity,
+    ) -> Result<(), Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            conn.execute(
+                "INSERT INTO wallet_integrity (policy_id, descriptor_hash, checksum, network) VALUES (?, ?, ?, ?) ON CONFLICT(policy_id) DO UPDATE SET descriptor_hash = excluded.descriptor_hash, checksum = excluded.checksum, network = excluded.network;",
+                (
+                    policy_id.to_hex(),
+                    integrity.descriptor_hash.to_string(),
+                    integrity.checksum,
+                    integrity.network.to_string(),
+                ),
+            )?;
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Get the descriptor and network the wallet state of the policy was built for
+    ///
+    /// `None` if the policy was never loaded.
+    pub async fn get_wallet_integrity(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Option<WalletIntegrity>, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached(
+                "SELECT descriptor_hash, checksum, network FROM wallet_integrity WHERE policy_id = ?;",
+            )?;
+            let row: Option<(String, String, String)> = stmt
+                .query_row([policy_id.to_hex()], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .optional()?;
+            match row {
+                Some((descriptor_hash, checksum, network)) => Ok(Some(WalletIntegrity {
+                    descriptor_hash: Sha256Hash::from_str(&descriptor_hash)?,
+                    checksum,
+                    network: Network::from_str(&network)
+                        .map_err(|_| Error::UnknownNetwork(network))?,
+                })),
+                None => Ok(None),
+            }
+        })
+        .await?
+    }
+
+    /// Move the wallet state out of the `timechain` table, so the wallet is rescanned from scratch
+    ///
+    /// Return `false` if there was no wallet state to quarantine.
+    pub async fn quarantine_changeset(&self, descriptor_hash: Sha256Hash) -> Result<bool, Error> {
+        let conn = self.acquire().await?;
+        conn.interact(move |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO quarantined_timechain (descriptor_hash, data, timestamp) SELECT descriptor_hash, data, ? FROM timechain WHERE descriptor_hash = ?;",
+                (Timestamp::now().as_u64(), descriptor_hash.to_string()),
+            )?;
+            let rows = tx.execute(
+                "DELETE FROM timechain WHERE descriptor_hash = ?;",
+                [descriptor_hash.to_string()],
+            )?;
+            tx.commit()?;
+            Ok(rows > 0)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_core::bdk::wallet::ChangeSet;
+    use smartvaults_core::bitcoin::hashes::Hash;
+
+    use super::*;
+    use crate::store::TempStore;
+
+    #[tokio::test]
+    async fn test_wallet_integrity() {
+        let store = TempStore::new().await;
+
+        let policy_id = EventId::all_zeros();
+        let descriptor_hash = Sha256Hash::hash(b"descriptor");
+        assert_eq!(store.get_wallet_integrity(policy_id).await.unwrap(), None);
+
+        let integrity = WalletIntegrity {
+            descriptor_hash,
+            checksum: String::from("rs0udsfg"),
+            network: Network::Testnet,
+        };
+        store
+            .save_wallet_integrity(policy_id, integrity.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_wallet_integrity(policy_id).await.unwrap(),
+            Some(integrity.clone())
+        );
+
+        let integrity = WalletIntegrity {
+            network: Network::Bitcoin,
+            ..integrity
+        };
+        store
+            .save_wallet_integrity(policy_id, integrity.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_wallet_integrity(policy_id).await.unwrap(),
+            Some(integrity)
+        );
+
+        // Quarantine
+        assert!(!store.quarantine_changeset(descriptor_hash).await.unwrap());
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        store
+            .save_changeset(descriptor_hash, changeset)
+            .await
+            .unwrap();
+        assert!(store.get_changeset(descriptor_hash).await.is_ok());
+        assert!(store.quarantine_changeset(descriptor_hash).await.unwrap());
+        assert!(matches!(
+            store.get_changeset(descriptor_hash).await,
+            Err(Error::NotFound(..))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use smartvaults_sdk::core::bips::bip39::Language;
 use smartvaults_sdk::prelude::*;
 
 const NETWORK: Network = Network::Testnet;
@@ -14,6 +15,7 @@ async fn main() {
         || Ok(String::from("password")),
         || Ok(String::from("confirm-password")),
         WordCount::W24,
+        Language::English,
         || Ok(None),
         NETWORK,
     )
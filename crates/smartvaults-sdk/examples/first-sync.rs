@@ -0,0 +1,63 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! First bitcoind sync of a vault, before and after starting from its birthday
+//!
+//! Before: the blocks are emitted from the genesis.
+//! After: the blocks are emitted from the last one mined (by median time past) before the birthday,
+//! minus the [`BIRTHDAY_SAFETY_MARGIN`].
+//!
+//! Usage: `cargo run --release --example first-sync -- <rpc-url> <rpc-user> <rpc-password> <descriptor> <birthday-timestamp>`
+//! against a Bitcoin Core node of the descriptor network (i.e. a vault created months ago on testnet).
+//!
+//! Results: **not collected yet**.
+
+use std::time::{Duration, Instant};
+
+use bdk_bitcoind_rpc::bitcoincore_rpc::{Auth, Client};
+use bdk_bitcoind_rpc::Emitter;
+use smartvaults_sdk::constants::BIRTHDAY_SAFETY_MARGIN;
+use smartvaults_sdk::core::bdk::Wallet;
+use smartvaults_sdk::manager::wallet::height_before;
+use smartvaults_sdk::prelude::*;
+
+const NETWORK: Network = Network::Testnet;
+
+/// Sync a new in-memory wallet from `start_height`: return the emitted blocks, the balance and the elapsed time
+fn first_sync(client: &Client, descriptor: &str, start_height: u32) -> (u32, u64, Duration) {
+    let mut wallet = Wallet::new_no_persist(descriptor, None, NETWORK).unwrap();
+    let now = Instant::now();
+    let mut emitter = Emitter::new(client, wallet.latest_checkpoint(), start_height);
+    let mut blocks: u32 = 0;
+    while let Some(event) = emitter.next_block().unwrap() {
+        wallet
+            .apply_block_connected_to(&event.block, event.block_height(), event.connected_to())
+            .unwrap();
+        blocks += 1;
+    }
+    (blocks, wallet.get_balance().total(), now.elapsed())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 6 {
+        eprintln!("Usage: first-sync <rpc-url> <rpc-user> <rpc-password> <descriptor> <birthday-timestamp>");
+        std::process::exit(1);
+    }
+
+    let client = Client::new(&args[1], Auth::UserPass(args[2].clone(), args[3].clone())).unwrap();
+    let descriptor: &str = &args[4];
+    let birthday = Timestamp::from(args[5].parse::<u64>().unwrap());
+
+    let start_height: u32 = height_before(&client, birthday - BIRTHDAY_SAFETY_MARGIN).unwrap();
+    println!("Birthday height: {start_height}\n");
+
+    let (blocks, before_balance, elapsed) = first_sync(&client, descriptor, 0);
+    println!("Before (from genesis)   {blocks:>8} blocks  {elapsed:>10.3?}");
+
+    let (blocks, after_balance, elapsed) = first_sync(&client, descriptor, start_height);
+    println!("After (from birthday)   {blocks:>8} blocks  {elapsed:>10.3?}");
+
+    // Starting from the birthday must not miss any tx
+    assert_eq!(before_balance, after_balance);
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Navigation latency of the desktop screens, before and after the app-level cache
+//!
+//! Before: every navigation issued the queries of the screen `load()`.
+//! After: the navigation renders the cached values (clone) and revalidates in background.
+//!
+//! Usage: `cargo run --release --example navigation-latency -- <base-path> <account-name> <password> [iterations]`
+//! against a seeded account (i.e. a restored account with many vaults, proposals and transactions).
+//!
+//! Results: **not collected yet**. The before/after numbers requested for the GUI cache must be
+//! recorded here (dataset size, avg/p50/p95 per screen) before the cache work is considered done.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use smartvaults_sdk::prelude::*;
+
+const NETWORK: Network = Network::Testnet;
+
+struct Stats {
+    samples: Vec<Duration>,
+}
+
+impl Stats {
+    fn percentile(&self, p: usize) -> Duration {
+        let index = (self.samples.len() * p / 100).min(self.samples.len() - 1);
+        self.samples[index]
+    }
+
+    fn print(&self, name: &str) {
+        let total: Duration = self.samples.iter().sum();
+        println!(
+            "{name:<24} avg {:>10.3?}  p50 {:>10.3?}  p95 {:>10.3?}",
+            total / self.samples.len() as u32,
+            self.percentile(50),
+            self.percentile(95),
+        );
+    }
+}
+
+async fn measure<F, Fut>(iterations: usize, mut f: F) -> Stats
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut samples: Vec<Duration> = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let now = Instant::now();
+        f().await;
+        samples.push(now.elapsed());
+    }
+    samples.sort();
+    Stats { samples }
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 4 {
+        eprintln!("Usage: navigation-latency <base-path> <account-name> <password> [iterations]");
+        std::process::exit(1);
+    }
+    let iterations: usize = args.get(4).and_then(|i| i.parse().ok()).unwrap_or(100);
+
+    let client = SmartVaults::open(&args[1], &args[2], &args[3], NETWORK)
+        .await
+        .unwrap();
+
+    let policies = client.get_policies().await.unwrap();
+    let proposals = client.get_proposals().await.unwrap();
+    let txs = client.get_all_transactions().await.unwrap();
+    let counts = client.get_counts().await.unwrap();
+    println!(
+        "Dataset: {} vaults, {} proposals, {} transactions ({iterations} iterations)\n",
+        policies.len(),
+        proposals.len(),
+        txs.len()
+    );
+
+    let client = &client;
+    let (policies, proposals, txs, counts) = (&policies, &proposals, &txs, &counts);
+
+    println!("Before (load on navigation)");
+    measure(iterations, || async move {
+        client.get_counts().await.unwrap();
+        client.get_all_transactions().await.unwrap();
+        client.get_proposals().await.unwrap();
+    })
+    .await
+    .print("Dashboard");
+    measure(iterations, || async move {
+        client.get_policies().await.unwrap();
+    })
+    .await
+    .print("Vaults");
+    measure(iterations, || async move {
+        client.get_proposals().await.unwrap();
+        client.get_all_transactions().await.unwrap();
+    })
+    .await
+    .print("Activity");
+
+    println!("\nAfter (cached)");
+    measure(iterations, || async move {
+        let _ = (counts.clone(), proposals.clone(), txs.clone());
+    })
+    .await
+    .print("Dashboard");
+    measure(iterations, || async move {
+        let _ = policies.clone();
+    })
+    .await
+    .print("Vaults");
+    measure(iterations, || async move {
+        let _ = (proposals.clone(), txs.clone());
+    })
+    .await
+    .print("Activity");
+
+    client.clone().shutdown().await.unwrap();
+}
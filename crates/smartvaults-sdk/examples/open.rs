@@ -16,7 +16,10 @@ async fn main() {
         .unwrap();
 
     // Edit relays
-    client.add_relay("wss://you.relay.com", None).await.unwrap();
+    client
+        .add_relay("wss://you.relay.com", None, RelayRole::default())
+        .await
+        .unwrap();
 
     // Edit configs
     let config = client.config();
@@ -46,6 +49,7 @@ async fn main() {
             None,                                // Specify the UTXOs to use (optional)
             None, // Specify the policy path to use (needed only if exists a timelock in the policy descriptor)
             false, // Allow usage of UTXOs frozen by others proposals
+            None, // Coin selection strategy (optional, default to the one of the policy)
         )
         .await
         .unwrap();
@@ -59,7 +63,7 @@ async fn main() {
 
     // Approve a proposal
     client
-        .approve("password", proposal.proposal_id)
+        .approve("password", proposal.proposal_id, false, false)
         .await
         .unwrap();
     // other approvals ...
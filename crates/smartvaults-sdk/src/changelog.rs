@@ -0,0 +1,267 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Changelog
+//!
+//! Compiled in and shown once after an upgrade ("What's new"). Newest release first: every
+//! release note in `doc/release-notes` must have an entry here.
+
+/// Section of the app a feature can be found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Vaults,
+    Spend,
+    Activity,
+    Signers,
+    Contacts,
+    Notifications,
+    Settings,
+    Config,
+    Relays,
+    NostrConnect,
+}
+
+/// New feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Feature {
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Where to find it in the GUI
+    pub section: Option<Section>,
+    /// CLI command to try it
+    pub command: Option<&'static str>,
+}
+
+impl Feature {
+    const fn new(title: &'static str, description: &'static str) -> Self {
+        Self {
+            title,
+            description,
+            section: None,
+            command: None,
+        }
+    }
+
+    const fn section(self, section: Section) -> Self {
+        Self {
+            section: Some(section),
+            ..self
+        }
+    }
+
+    const fn command(self, command: &'static str) -> Self {
+        Self {
+            command: Some(command),
+            ..self
+        }
+    }
+}
+
+/// Release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Release {
+    pub version: &'static str,
+    pub features: &'static [Feature],
+}
+
+impl Release {
+    fn version(&self) -> Option<Version> {
+        Version::parse(self.version)
+    }
+}
+
+/// Released versions, newest first
+pub const CHANGELOG: &[Release] = &[
+    Release {
+        version: "0.4.0",
+        features: &[
+            Feature::new(
+                "Fiat balances",
+                "Show the vault balances in USD or EUR (prices from a configurable endpoint).",
+            )
+            .section(Section::Config)
+            .command("config set --fiat-currency usd"),
+            Feature::new(
+                "Fee presets",
+                "Spend with a high, medium or low priority: the fee rate is estimated by the backend.",
+            )
+            .section(Section::Spend)
+            .command("get fees"),
+            Feature::new(
+                "Policy templates",
+                "Create multisig, recovery and decaying policies from templates.",
+            )
+            .section(Section::Vaults)
+            .command("add policy-template"),
+            Feature::new(
+                "Descriptor analysis",
+                "Inspect the keys, spending paths and timelocks of a descriptor before saving it.",
+            )
+            .section(Section::Vaults)
+            .command("inspect-descriptor"),
+            Feature::new(
+                "Notifications",
+                "Persistent notifications, with Nostr Connect requests to approve or reject.",
+            )
+            .section(Section::Notifications)
+            .command("get notifications"),
+            Feature::new(
+                "Relay health",
+                "Connection status, failed publications and latency of each relay.",
+            )
+            .section(Section::Relays)
+            .command("get relays --verbose"),
+            Feature::new(
+                "Bitcoin Core backend",
+                "Sync the vaults with your own node via RPC, as alternative to electrum.",
+            )
+            .section(Section::Config)
+            .command("config set --bitcoind-url <url>"),
+        ],
+    },
+    Release {
+        version: "0.3.0",
+        features: &[
+            Feature::new(
+                "Nostr Connect",
+                "Sign events for other apps with your keys (NIP-46).",
+            )
+            .section(Section::NostrConnect)
+            .command("connect"),
+            Feature::new(
+                "Editable config",
+                "Electrum endpoint, proxy and block explorer can be changed from the settings.",
+            )
+            .section(Section::Config)
+            .command("config view"),
+            Feature::new("Relays", "Add and remove relays.")
+                .section(Section::Relays)
+                .command("add relay"),
+        ],
+    },
+    Release {
+        version: "0.2.0",
+        features: &[
+            Feature::new("Signers", "Add signers and share them with your contacts.")
+                .section(Section::Signers)
+                .command("get signers"),
+            Feature::new(
+                "Notifications",
+                "Get notified about new vaults, proposals and approvals.",
+            ),
+            Feature::new(
+                "Rebroadcast",
+                "Publish again your events to the relays.",
+            )
+            .section(Section::Relays)
+            .command("rebroadcast"),
+        ],
+    },
+    Release {
+        version: "0.1.0",
+        features: &[Feature::new("First release", "First alpha release.")],
+    },
+];
+
+/// Releases after `last_seen` up to `current` (newest first)
+///
+/// If `last_seen` is not set (fresh install or first run with the changelog), only the `current`
+/// release is returned.
+pub fn releases_since(last_seen: Option<&str>, current: &str) -> Vec<&'static Release> {
+    let current: Option<Version> = Version::parse(current);
+    match last_seen.and_then(Version::parse) {
+        Some(last_seen) => CHANGELOG
+            .iter()
+            .filter(|r| match r.version() {
+                Some(version) => version > last_seen && current.map_or(true, |c| version <= c),
+                None => false,
+            })
+            .collect(),
+        None => CHANGELOG
+            .iter()
+            .filter(|r| r.version().is_some() && r.version() == current)
+            .collect(),
+    }
+}
+
+/// `<major>.<minor>.<patch>` (pre-release suffix ignored)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version(u64, u64, u64);
+
+impl Version {
+    fn parse(version: &str) -> Option<Self> {
+        let version: &str = version.split('-').next()?;
+        let mut parts = version.split('.').map(|p| p.parse::<u64>());
+        let version = Self(
+            parts.next()?.ok()?,
+            parts.next()?.ok()?,
+            parts.next()?.ok()?,
+        );
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_changelog_entries() {
+        // No duplicates, newest first
+        let mut versions = HashSet::new();
+        for release in CHANGELOG.iter() {
+            assert!(
+                versions.insert(release.version),
+                "{} duplicated",
+                release.version
+            );
+            assert!(
+                release.version().is_some(),
+                "invalid version {}",
+                release.version
+            );
+            assert!(!release.features.is_empty());
+        }
+        for releases in CHANGELOG.windows(2) {
+            assert!(releases[0].version() > releases[1].version());
+        }
+
+        // Current version
+        assert!(versions.contains(env!("CARGO_PKG_VERSION")));
+
+        // Every released version
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../doc/release-notes");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let name = entry.unwrap().file_name().into_string().unwrap();
+            let version = name
+                .trim_start_matches("release-notes-")
+                .trim_end_matches(".md");
+            assert!(versions.contains(version), "{version} missing");
+        }
+    }
+
+    #[test]
+    fn test_releases_since() {
+        let versions = |releases: Vec<&Release>| -> Vec<&str> {
+            releases.into_iter().map(|r| r.version).collect()
+        };
+        assert_eq!(
+            versions(releases_since(Some("0.2.0"), "0.4.0")),
+            vec!["0.4.0", "0.3.0"]
+        );
+        assert_eq!(
+            versions(releases_since(Some("0.4.0"), "0.4.0")),
+            Vec::<&str>::new()
+        );
+        assert_eq!(versions(releases_since(None, "0.4.0")), vec!["0.4.0"]);
+        // Downgrade
+        assert!(releases_since(Some("0.4.0"), "0.3.0").is_empty());
+        assert_eq!(Version::parse("0.4.0-rc.1"), Some(Version(0, 4, 0)));
+        assert_eq!(Version::parse("0.4"), None);
+    }
+}
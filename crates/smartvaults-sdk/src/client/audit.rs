@@ -0,0 +1,52 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use nostr_sdk::{EventId, Timestamp};
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::hashes::Hash;
+use smartvaults_core::Signer;
+use smartvaults_sdk_sqlite::model::{AuditLogIntegrity, SigningAuditEntry, SigningKind};
+
+use super::{Error, SmartVaults};
+
+impl SmartVaults {
+    /// Record a signing operation of the local keychain in the audit log
+    pub(crate) async fn audit_signing<S>(
+        &self,
+        kind: SigningKind,
+        object_id: S,
+        content: &[u8],
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        Ok(self
+            .db
+            .append_signing_audit(
+                self.fingerprint(),
+                kind,
+                object_id,
+                Sha256Hash::hash(content),
+            )
+            .await?)
+    }
+
+    /// Get the signing operations made by a [`Signer`] (time range is inclusive)
+    pub async fn get_signing_audit(
+        &self,
+        signer_id: EventId,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+    ) -> Result<Vec<SigningAuditEntry>, Error> {
+        let signer: Signer = self.storage.signer(&signer_id).await?;
+        Ok(self
+            .db
+            .get_signing_audit(Some(signer.fingerprint()), from, to)
+            .await?)
+    }
+
+    /// Verify the integrity of the signing audit log
+    pub async fn verify_signing_audit(&self) -> Result<AuditLogIntegrity, Error> {
+        Ok(self.db.verify_signing_audit().await?)
+    }
+}
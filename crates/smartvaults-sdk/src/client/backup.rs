@@ -0,0 +1,316 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use nostr_sdk::database::{NostrDatabase, Order};
+use nostr_sdk::nips::nip06::FromMnemonic;
+use nostr_sdk::{Event, Filter, Keys, SQLiteDatabase};
+use smartvaults_core::bips::bip39::Mnemonic;
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::types::{KeeChain, Keychain};
+use smartvaults_core::{mnemonic, SECP256K1};
+
+use super::{Error, SmartVaults};
+use crate::lock::LockFile;
+use crate::types::KeychainBackup;
+use crate::util;
+
+impl SmartVaults {
+    /// Export an encrypted backup of the keychain (seed and passphrases) and of the nostr events
+    /// (signers, policies, proposals, labels, contacts, ...)
+    ///
+    /// The backup is encrypted with the keychain `password`.
+    pub async fn export_backup<P, T>(&self, path: P, password: T) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        T: AsRef<[u8]>,
+    {
+        let password: &[u8] = password.as_ref();
+        if !self.check_password(password) {
+            return Err(Error::PasswordNotMatch);
+        }
+
+        let keychain: Keychain = self.keychain(password)?;
+        let passphrases: Vec<String> = (0..).map_while(|i| keychain.get_passphrase(i)).collect();
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![Filter::new()], Order::Asc)
+            .await?;
+
+        let backup = KeychainBackup {
+            name: self.name().unwrap_or_default(),
+            network: self.network,
+            mnemonic: keychain.seed().mnemonic().to_string(),
+            passphrases,
+            events,
+        };
+        fs::write(path, backup.encrypt(password)?)?;
+
+        tracing::info!("Keychain backup exported");
+
+        Ok(())
+    }
+
+    /// Recreate the keychain and repopulate the db from a backup (see [`SmartVaults::export_backup`])
+    ///
+    /// The keychain keeps the name and the password of the backup. Importing over an existing
+    /// keychain with the same name fails with [`Error::KeychainAlreadyExists`], unless `force` is
+    /// `true`.
+    ///
+    /// The keychain is restored to a temporary file and replaces the existing one only after the
+    /// db is repopulated, so a failed import never loses the existing keychain. Fails if the
+    /// account is opened by another process (see [`LockFile`]).
+    pub async fn import_backup<P, B, S>(
+        base_path: P,
+        path: B,
+        password: S,
+        network: Network,
+        force: bool,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        B: AsRef<Path>,
+        S: Into<String>,
+    {
+        let base_path = base_path.as_ref();
+        let password: String = password.into();
+
+        let payload: Vec<u8> = fs::read(path)?;
+        let KeychainBackup {
+            name,
+            mnemonic,
+            passphrases,
+            events,
+            ..
+        } = KeychainBackup::decrypt(payload, &password, network)?;
+
+        if !util::dir::is_valid_keychain_name(&name) {
+            return Err(Error::InvalidKeychainName(name));
+        }
+
+        let exists: bool = util::dir::get_keychains_list(base_path, network)?.contains(&name);
+        if exists && !force {
+            return Err(Error::KeychainAlreadyExists(name));
+        }
+
+        // Lock the account of the backup: refuse if opened by another process
+        let mnemonic: Mnemonic = mnemonic::parse(mnemonic, None)?;
+        let passphrase: Option<String> = passphrases.first().cloned();
+        let keys = Keys::from_mnemonic(mnemonic.to_string(), passphrase.clone())?;
+        let lock = LockFile::acquire(
+            util::dir::lock_file(base_path, network, keys.public_key())?,
+            false,
+        )?;
+
+        // Restore to a temporary keychain (not a valid name, so it can't collide)
+        let tmp_name: String = format!(".{name}.restore");
+        util::dir::remove_keychain(base_path, network, &tmp_name)?;
+        if let Err(e) = Self::restore_backup(
+            base_path,
+            &tmp_name,
+            &password,
+            mnemonic,
+            &passphrases,
+            &keys,
+            &events,
+            network,
+        )
+        .await
+        {
+            if let Err(e) = util::dir::remove_keychain(base_path, network, &tmp_name) {
+                tracing::error!("Impossible to remove the temporary keychain {tmp_name}: {e}");
+            }
+            return Err(e);
+        }
+
+        // Replace the existing keychain only now
+        util::dir::replace_keychain(base_path, network, &tmp_name, &name)?;
+        if exists {
+            tracing::warn!("Keychain {name} replaced by the backup");
+        }
+
+        tracing::info!("Keychain backup imported ({} events)", events.len());
+
+        let keychains_path: PathBuf = util::dir::keychains_path(base_path, network)?;
+        let mut keechain: KeeChain = KeeChain::open(
+            keychains_path,
+            name,
+            || Ok(password.clone()),
+            network,
+            &SECP256K1,
+        )?;
+        if passphrase.is_some() {
+            keechain.apply_passphrase(&password, passphrase, &SECP256K1)?;
+        }
+
+        // Taken again by the client
+        drop(lock);
+
+        Self::new(base_path, password, keechain, network, false).await
+    }
+
+    /// Recreate the keychain `name` and repopulate the nostr db of `keys`
+    #[allow(clippy::too_many_arguments)]
+    async fn restore_backup(
+        base_path: &Path,
+        name: &str,
+        password: &str,
+        mnemonic: Mnemonic,
+        passphrases: &[String],
+        keys: &Keys,
+        events: &[Event],
+        network: Network,
+    ) -> Result<(), Error> {
+        let keychains_path: PathBuf = util::dir::keychains_path(base_path, network)?;
+        let mut keechain: KeeChain = KeeChain::restore(
+            keychains_path,
+            name,
+            || Ok(password.to_string()),
+            || Ok(password.to_string()),
+            || Ok(mnemonic),
+            network,
+            &SECP256K1,
+        )?;
+        if !passphrases.is_empty() {
+            for passphrase in passphrases.iter() {
+                keechain.add_passphrase(password, passphrase)?;
+            }
+            keechain.save()?;
+        }
+
+        let nostr_db =
+            SQLiteDatabase::open(util::dir::nostr_db(base_path, keys.public_key(), network)?)
+                .await?;
+        for event in events.iter() {
+            nostr_db.save_event(event).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::EventBuilder;
+    use smartvaults_core::bips::bip39::Language;
+    use smartvaults_core::types::WordCount;
+
+    use super::*;
+    use crate::client::TempBasePath;
+
+    async fn generate(base_path: &Path, name: &str, network: Network) -> SmartVaults {
+        SmartVaults::generate(
+            base_path,
+            name,
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(Some(String::from("passphrase"))),
+            network,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_import_backup_over_existing_keychain() {
+        let base_path = TempBasePath::new("backup");
+        let local = base_path.join("local");
+        let backup_path = base_path.join("main.backup");
+        let network = Network::Testnet;
+
+        let client = generate(&local, "main", network).await;
+        let public_key = client.keys().public_key();
+        let event = EventBuilder::text_note("backed up", [])
+            .to_event(client.keys())
+            .unwrap();
+        client.client.database().save_event(&event).await.unwrap();
+        assert!(matches!(
+            client.export_backup(&backup_path, "wrong").await,
+            Err(Error::PasswordNotMatch)
+        ));
+        client.export_backup(&backup_path, "test").await.unwrap();
+
+        // Account opened by the client
+        assert!(matches!(
+            SmartVaults::import_backup(&local, &backup_path, "test", network, true).await,
+            Err(Error::Lock(..))
+        ));
+        client.shutdown().await.unwrap();
+
+        assert!(matches!(
+            SmartVaults::import_backup(&local, &backup_path, "test", network, false).await,
+            Err(Error::KeychainAlreadyExists(..))
+        ));
+
+        // Corrupted backup
+        let corrupted = base_path.join("corrupted.backup");
+        let mut payload: Vec<u8> = fs::read(&backup_path).unwrap();
+        payload.truncate(payload.len() / 2);
+        fs::write(&corrupted, payload).unwrap();
+        assert!(
+            SmartVaults::import_backup(&local, &corrupted, "test", network, true)
+                .await
+                .is_err()
+        );
+
+        // Backup of another account, failing while repopulating the db
+        let other = base_path.join("other");
+        let other_backup_path = base_path.join("other.backup");
+        let client = generate(&other, "main", network).await;
+        let other_public_key = client.keys().public_key();
+        client
+            .export_backup(&other_backup_path, "test")
+            .await
+            .unwrap();
+        client.shutdown().await.unwrap();
+        fs::create_dir_all(util::dir::nostr_db(&local, other_public_key, network).unwrap())
+            .unwrap();
+        assert!(
+            SmartVaults::import_backup(&local, &other_backup_path, "test", network, true)
+                .await
+                .is_err()
+        );
+
+        // Existing keychain left intact
+        assert_eq!(
+            SmartVaults::list_keychains(&local, network).unwrap(),
+            vec![String::from("main")]
+        );
+        let client = SmartVaults::open(&local, "main", "test", network)
+            .await
+            .unwrap();
+        assert_eq!(client.keys().public_key(), public_key);
+        client.shutdown().await.unwrap();
+
+        let client = SmartVaults::import_backup(&local, &backup_path, "test", network, true)
+            .await
+            .unwrap();
+        assert_eq!(client.name().as_deref(), Some("main"));
+        assert_eq!(client.keys().public_key(), public_key);
+        assert_eq!(
+            SmartVaults::list_keychains(&local, network).unwrap(),
+            vec![String::from("main")]
+        );
+        client.shutdown().await.unwrap();
+
+        // New machine: the events come back
+        let new = base_path.join("new");
+        let client = SmartVaults::import_backup(&new, &backup_path, "test", network, false)
+            .await
+            .unwrap();
+        assert_eq!(client.keys().public_key(), public_key);
+        let events: Vec<Event> = client
+            .client
+            .database()
+            .query(vec![Filter::new().id(event.id)], Order::Asc)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![event]);
+        client.shutdown().await.unwrap();
+    }
+}
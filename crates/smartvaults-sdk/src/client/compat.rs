@@ -0,0 +1,119 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+
+use nostr_sdk::{Event, EventBuilder, EventId, Filter, Keys, PublicKey, Timestamp};
+use smartvaults_protocol::v1::constants::CAPABILITIES_KIND;
+use smartvaults_protocol::v1::{Capabilities, Serde, SmartVaultsEventBuilder};
+use smartvaults_sdk_sqlite::model::PeerCapabilities;
+
+use super::{Error, SmartVaults};
+use crate::constants::BACKFILL_TIMEOUT;
+use crate::storage::InternalPolicy;
+use crate::types::{MemberCompatibility, PolicyCompatibility};
+
+impl SmartVaults {
+    /// Advertise the protocol versions supported by this client
+    pub async fn publish_capabilities(&self) -> Result<EventId, Error> {
+        let keys: &Keys = self.keys();
+        let event: Event =
+            EventBuilder::capabilities(keys, &Capabilities::default(), self.network)?;
+        Ok(self.client.send_event(event).await?)
+    }
+
+    /// Get the capabilities of the members of a policy
+    ///
+    /// The members that never advertised their capabilities are legacy (v1 only) clients.
+    async fn members_capabilities(
+        &self,
+        public_keys: Vec<PublicKey>,
+    ) -> Result<HashMap<PublicKey, PeerCapabilities>, Error> {
+        let mut peers = self.db.get_peers_capabilities(public_keys).await?;
+        peers.insert(
+            self.keys().public_key(),
+            PeerCapabilities {
+                capabilities: Capabilities::default(),
+                timestamp: Timestamp::now(),
+            },
+        );
+        Ok(peers)
+    }
+
+    /// Choose the latest protocol version supported by all the members
+    ///
+    /// Fallback to v1 (supported by every client) if the members don't share a newer version.
+    pub(crate) async fn negotiate_protocol_version(
+        &self,
+        public_keys: Vec<PublicKey>,
+    ) -> Result<u8, Error> {
+        let peers = self.members_capabilities(public_keys.clone()).await?;
+        let legacy = Capabilities::new([1]);
+        let capabilities = public_keys
+            .iter()
+            .map(|public_key| peers.get(public_key).map_or(&legacy, |p| &p.capabilities));
+        Ok(Capabilities::negotiate(capabilities).unwrap_or(1))
+    }
+
+    /// Get the protocol compatibility report of a policy
+    ///
+    /// The capabilities of the members are refreshed from relays before building the report.
+    pub async fn policy_compatibility(
+        &self,
+        policy_id: EventId,
+    ) -> Result<PolicyCompatibility, Error> {
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+
+        // Refresh capabilities
+        let filter: Filter = Filter::new()
+            .kind(CAPABILITIES_KIND)
+            .identifier(self.network.magic().to_string())
+            .authors(public_keys.clone());
+        match self
+            .client
+            .get_events_of(vec![filter], Some(BACKFILL_TIMEOUT))
+            .await
+        {
+            Ok(events) => {
+                for event in events.into_iter() {
+                    match Capabilities::from_json(event.content()) {
+                        Ok(capabilities) => {
+                            self.db
+                                .save_peer_capabilities(
+                                    event.author(),
+                                    capabilities,
+                                    event.created_at,
+                                )
+                                .await?
+                        }
+                        Err(e) => tracing::warn!(
+                            "Invalid capabilities advertised by {}: {e}",
+                            event.author()
+                        ),
+                    }
+                }
+            }
+            Err(e) => tracing::error!("Impossible to refresh members capabilities: {e}"),
+        }
+
+        let protocol_version: u8 = self.negotiate_protocol_version(public_keys.clone()).await?;
+        let mut peers = self.members_capabilities(public_keys.clone()).await?;
+        let members = public_keys
+            .into_iter()
+            .map(|public_key| {
+                let peer = peers.remove(&public_key);
+                MemberCompatibility {
+                    public_key,
+                    last_seen: peer.as_ref().map(|p| p.timestamp),
+                    capabilities: peer.map(|p| p.capabilities),
+                }
+            })
+            .collect();
+
+        Ok(PolicyCompatibility {
+            policy_id,
+            protocol_version,
+            members,
+        })
+    }
+}
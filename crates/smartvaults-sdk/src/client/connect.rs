@@ -6,10 +6,10 @@ use std::time::Duration;
 
 use nostr_sdk::nips::nip46::{Message as NIP46Message, NostrConnectURI, Request as NIP46Request};
 use nostr_sdk::{
-    ClientMessage, EventBuilder, EventId, Keys, PublicKey, RelaySendOptions, SubscribeOptions,
-    SubscriptionId, Timestamp, Url,
+    ClientMessage, EventBuilder, EventId, JsonUtil, Keys, PublicKey, RelaySendOptions,
+    SubscribeOptions, SubscriptionId, Timestamp, Url,
 };
-use smartvaults_sdk_sqlite::model::NostrConnectRequest;
+use smartvaults_sdk_sqlite::model::{NostrConnectRequest, SigningKind};
 
 use super::{Error, SmartVaults};
 use crate::constants::NOSTR_CONNECT_SUBSCRIPTION_ID;
@@ -114,6 +114,12 @@ impl SmartVaults {
             let msg = message
                 .generate_response(keys)?
                 .ok_or(Error::CantGenerateNostrConnectResponse)?;
+            self.audit_signing(
+                SigningKind::NostrConnect,
+                event_id.to_hex(),
+                msg.as_json().as_bytes(),
+            )
+            .await?;
             let nip46_event =
                 EventBuilder::nostr_connect(keys, uri.public_key, msg)?.to_event(keys)?;
             self.client
@@ -122,6 +128,7 @@ impl SmartVaults {
             self.db
                 .set_nostr_connect_request_as_approved(event_id)
                 .await?;
+            self.db.mark_notification_as_seen(event_id).await?;
             Ok(())
         } else {
             Err(Error::NostrConnectRequestAlreadyApproved)
@@ -145,6 +152,7 @@ impl SmartVaults {
                 .send_event_to([uri.relay_url], nip46_event)
                 .await?;
             self.db.delete_nostr_connect_request(event_id).await?;
+            self.db.mark_notification_as_seen(event_id).await?;
             Ok(())
         } else {
             Err(Error::NostrConnectRequestAlreadyApproved)
@@ -0,0 +1,66 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use nostr_sdk::{EventId, Timestamp};
+use smartvaults_protocol::v1::Serde;
+
+use super::{Error, SmartVaults};
+use crate::types::Draft;
+
+impl SmartVaults {
+    /// Save the draft of a form (see [`Draft`])
+    ///
+    /// Empty drafts are discarded.
+    pub async fn save_draft<D>(&self, policy_id: Option<EventId>, draft: &D) -> Result<(), Error>
+    where
+        D: Draft,
+    {
+        if draft.is_empty() {
+            return self.discard_draft::<D>(policy_id).await;
+        }
+
+        self.db
+            .save_draft(D::SCREEN, policy_id, draft.as_json(), Timestamp::now())
+            .await?;
+        Ok(())
+    }
+
+    /// Get the draft of a form
+    ///
+    /// Drafts older than [`Config::drafts_expiration`](crate::config::Config::drafts_expiration)
+    /// are deleted.
+    pub async fn get_draft<D>(&self, policy_id: Option<EventId>) -> Result<Option<D>, Error>
+    where
+        D: Draft,
+    {
+        let expiration = self.config.drafts_expiration().await;
+        let expired: usize = self
+            .db
+            .delete_drafts_older_than(Timestamp::now() - expiration)
+            .await?;
+        if expired > 0 {
+            tracing::debug!("Deleted {expired} expired drafts");
+        }
+
+        match self.db.get_draft(D::SCREEN, policy_id).await? {
+            Some((data, ..)) => match D::from_json(data) {
+                Ok(draft) => Ok(Some(draft)),
+                Err(e) => {
+                    tracing::warn!("Discarding invalid {} draft: {e}", D::SCREEN);
+                    self.discard_draft::<D>(policy_id).await?;
+                    Ok(None)
+                }
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Discard the draft of a form
+    pub async fn discard_draft<D>(&self, policy_id: Option<EventId>) -> Result<(), Error>
+    where
+        D: Draft,
+    {
+        self.db.delete_draft(D::SCREEN, policy_id).await?;
+        Ok(())
+    }
+}
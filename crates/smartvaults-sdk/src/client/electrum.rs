@@ -0,0 +1,92 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::net::SocketAddr;
+
+use async_utility::thread;
+use bdk_electrum::electrum_client::{
+    self, Client as ElectrumClient, Config as ElectrumConfig, ElectrumApi, Socks5Config,
+};
+
+use super::{Error, SmartVaults};
+use crate::config::{self, ElectrumEndpoint};
+use crate::constants::{
+    ELECTRUM_FAILOVER_BACKOFF, ELECTRUM_FAILOVER_MAX_BACKOFF, ELECTRUM_TIMEOUT,
+};
+
+/// Check if the error is caused by an unreachable or unresponsive server
+fn is_connection_error(e: &electrum_client::Error) -> bool {
+    matches!(
+        e,
+        electrum_client::Error::IOError(..)
+            | electrum_client::Error::SharedIOError(..)
+            | electrum_client::Error::AllAttemptsErrored(..)
+            | electrum_client::Error::CouldntLockReader
+            | electrum_client::Error::Mpsc
+    )
+}
+
+fn ping(
+    endpoint: &ElectrumEndpoint,
+    proxy: Option<SocketAddr>,
+) -> Result<(), electrum_client::Error> {
+    let config = ElectrumConfig::builder()
+        .validate_domain(endpoint.validate_tls())
+        .socks5(proxy.map(Socks5Config::new))
+        .timeout(Some(ELECTRUM_TIMEOUT))
+        .build();
+    let client = ElectrumClient::from_config(&endpoint.as_non_standard_format(), config)?;
+    client.ping()
+}
+
+impl SmartVaults {
+    /// Get a working electrum endpoint
+    ///
+    /// Try the configured endpoints in order, starting from the active one, and rotate on
+    /// connection errors (with exponential backoff). The working endpoint is remembered as
+    /// the active one (see [`Config::active_electrum_endpoint`](crate::config::Config::active_electrum_endpoint)).
+    pub(crate) async fn electrum_endpoint_with_failover(&self) -> Result<ElectrumEndpoint, Error> {
+        let endpoints: Vec<ElectrumEndpoint> = self.config.electrum_endpoints().await;
+        let active: ElectrumEndpoint = self.config.active_electrum_endpoint().await?;
+        let proxy: Option<SocketAddr> = self.config.proxy().await.ok();
+
+        let start: usize = endpoints.iter().position(|e| e == &active).unwrap_or(0);
+        let mut backoff = ELECTRUM_FAILOVER_BACKOFF;
+        let mut last_error: Option<electrum_client::Error> = None;
+
+        for (attempt, endpoint) in endpoints
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(endpoints.len())
+            .enumerate()
+        {
+            if attempt > 0 {
+                thread::sleep(backoff).await;
+                backoff = (backoff * 2).min(ELECTRUM_FAILOVER_MAX_BACKOFF);
+            }
+
+            match ping(endpoint, proxy) {
+                Ok(()) => {
+                    if endpoint != &active {
+                        tracing::warn!("Electrum failover: {active} -> {endpoint}");
+                        self.config
+                            .set_active_electrum_endpoint(endpoint.clone())
+                            .await;
+                    }
+                    return Ok(endpoint.clone());
+                }
+                Err(e) if is_connection_error(&e) => {
+                    tracing::warn!("Electrum endpoint {endpoint} unreachable: {e}");
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e.into()),
+            None => Err(config::Error::ElectrumEndpointNotSet.into()),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! External signers (i.e. HSM) watching a directory
+//!
+//! For every pending proposal are exported `<proposal_id>.psbt` (binary) and `<proposal_id>.json`
+//! (policy ID, description and recipients). The signer drops `<proposal_id>.signed.psbt`
+//! files, that are imported as approvals and moved to the `archive` subdirectory.
+//! The rejected ones are moved to the `rejected` subdirectory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use async_utility::thread;
+use futures_util::stream::AbortHandle;
+use nostr_sdk::{EventId, Timestamp};
+use serde::Serialize;
+use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+use smartvaults_core::bitcoin::Address;
+use smartvaults_core::{OutputKind, OutputVerification};
+use smartvaults_sdk_sqlite::model::NotificationKind;
+
+use super::{Error, Message, SmartVaults};
+use crate::constants::PSBT_DIR_WATCH_INTERVAL;
+use crate::types::GetProposal;
+
+const PSBT_EXTENSION: &str = "psbt";
+const SIGNED_PSBT_SUFFIX: &str = ".signed.psbt";
+const ARCHIVE_DIR: &str = "archive";
+const REJECTED_DIR: &str = "rejected";
+
+#[derive(Serialize)]
+struct PsbtRecipient {
+    /// Address or, if not representable, the script pubkey (hex)
+    address: String,
+    amount: u64,
+    verified: bool,
+}
+
+/// JSON sidecar of an exported PSBT
+#[derive(Serialize)]
+struct PsbtSidecar {
+    proposal_id: EventId,
+    policy_id: EventId,
+    description: String,
+    recipients: Vec<PsbtRecipient>,
+}
+
+/// Move a file in a subdirectory, creating it if not exists
+fn move_to<P>(path: P, subdir: &str) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let path: &Path = path.as_ref();
+    if let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) {
+        let dir: PathBuf = parent.join(subdir);
+        fs::create_dir_all(&dir)?;
+        fs::rename(path, dir.join(file_name))?;
+    }
+    Ok(())
+}
+
+/// Parse a PSBT file (binary or base64)
+pub(super) fn read_psbt<P>(path: P) -> Result<PartiallySignedTransaction, Error>
+where
+    P: AsRef<Path>,
+{
+    let bytes: Vec<u8> = fs::read(path)?;
+    match PartiallySignedTransaction::deserialize(&bytes) {
+        Ok(psbt) => Ok(psbt),
+        Err(e) => match std::str::from_utf8(&bytes) {
+            Ok(base64) => PartiallySignedTransaction::from_str(base64.trim())
+                .map_err(|e| Error::Generic(format!("invalid PSBT: {e}"))),
+            Err(_) => Err(Error::Generic(format!("invalid PSBT: {e}"))),
+        },
+    }
+}
+
+impl SmartVaults {
+    /// Export the PSBT of every pending proposal in a directory
+    ///
+    /// Write `<proposal_id>.psbt` (binary) and a `<proposal_id>.json` sidecar with policy ID,
+    /// description and recipients. Already exported proposals are skipped.
+    ///
+    /// Return the IDs of the exported proposals.
+    pub async fn export_pending_proposals<P>(&self, dir: P) -> Result<Vec<EventId>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let dir: &Path = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let mut exported: Vec<EventId> = Vec::new();
+        for GetProposal {
+            proposal_id,
+            policy_id,
+            proposal,
+            ..
+        } in self.get_proposals().await?.into_iter()
+        {
+            // Skip the proposals already exported or signed (but still pending)
+            let file_name: String = format!("{proposal_id}.{PSBT_EXTENSION}");
+            let psbt_path: PathBuf = dir.join(&file_name);
+            if psbt_path.exists() || dir.join(ARCHIVE_DIR).join(&file_name).exists() {
+                continue;
+            }
+
+            let recipients: Vec<PsbtRecipient> = self
+                .verify_proposal_outputs(proposal_id)
+                .await?
+                .into_iter()
+                .filter(|o| o.kind != OutputKind::Change)
+                .map(
+                    |OutputVerification {
+                         script_pubkey,
+                         value,
+                         kind,
+                         ..
+                     }| PsbtRecipient {
+                        address: Address::from_script(&script_pubkey, self.network())
+                            .map(|a| a.to_string())
+                            .unwrap_or_else(|_| script_pubkey.to_hex_string()),
+                        amount: value,
+                        verified: kind == OutputKind::Recipient,
+                    },
+                )
+                .collect();
+            let sidecar = PsbtSidecar {
+                proposal_id,
+                policy_id,
+                description: proposal.description(),
+                recipients,
+            };
+
+            fs::write(
+                dir.join(format!("{proposal_id}.json")),
+                serde_json::to_vec_pretty(&sidecar)?,
+            )?;
+            fs::write(&psbt_path, proposal.psbt().serialize())?;
+
+            tracing::info!("Proposal {proposal_id} exported to {}", psbt_path.display());
+            exported.push(proposal_id);
+        }
+
+        Ok(exported)
+    }
+
+    /// Import the `<proposal_id>.signed.psbt` files of a directory as approvals
+    ///
+    /// The PSBTs are validated (see [`SmartVaults::approve_with_signed_psbt`]): the imported
+    /// ones are moved, with the exported PSBT and the sidecar, to the `archive` subdirectory,
+    /// the rejected ones to the `rejected` subdirectory.
+    ///
+    /// Return the IDs of the approved proposals.
+    pub async fn import_signed_psbt_dir<P>(&self, dir: P) -> Result<Vec<EventId>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let dir: &Path = dir.as_ref();
+        let mut imported: Vec<EventId> = Vec::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path: PathBuf = entry?.path();
+            let proposal_id: EventId = match path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(SIGNED_PSBT_SUFFIX))
+                .map(EventId::from_hex)
+            {
+                Some(Ok(proposal_id)) => proposal_id,
+                _ => continue,
+            };
+
+            let res = match read_psbt(&path) {
+                Ok(psbt) => self.approve_with_signed_psbt(proposal_id, psbt).await,
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Ok((approval_id, ..)) => {
+                    tracing::info!(
+                        "Signed PSBT of proposal {proposal_id} imported as approval {approval_id}"
+                    );
+                    move_to(&path, ARCHIVE_DIR)?;
+                    for ext in [PSBT_EXTENSION, "json"] {
+                        let exported: PathBuf = dir.join(format!("{proposal_id}.{ext}"));
+                        if exported.exists() {
+                            move_to(exported, ARCHIVE_DIR)?;
+                        }
+                    }
+                    self.db
+                        .save_notification(
+                            NotificationKind::ExternalPsbtImported,
+                            proposal_id,
+                            Timestamp::now(),
+                        )
+                        .await?;
+                    self.sync_channel
+                        .send(Message::ExternalPsbtImported(proposal_id));
+                    imported.push(proposal_id);
+                }
+                Err(e) => {
+                    tracing::error!("Signed PSBT of proposal {proposal_id} rejected: {e}");
+                    move_to(&path, REJECTED_DIR)?;
+                    self.db
+                        .save_notification(
+                            NotificationKind::ExternalPsbtRejected,
+                            proposal_id,
+                            Timestamp::now(),
+                        )
+                        .await?;
+                    self.sync_channel.send(Message::ExternalPsbtRejected {
+                        proposal_id,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Watch a directory for PSBTs signed by external signers
+    ///
+    /// Every [`PSBT_DIR_WATCH_INTERVAL`] export the pending proposals
+    /// (see [`SmartVaults::export_pending_proposals`]) and import the signed PSBTs
+    /// (see [`SmartVaults::import_signed_psbt_dir`]).
+    ///
+    /// The directory configured with [`Config::set_psbt_dir`](crate::config::Config::set_psbt_dir)
+    /// is already watched by the sync.
+    pub fn watch_signed_psbt_dir<P>(&self, dir: P) -> Result<AbortHandle, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let this = self.clone();
+        let dir: PathBuf = dir.as_ref().to_path_buf();
+        Ok(thread::abortable(async move {
+            loop {
+                this.process_psbt_dir(&dir).await;
+                thread::sleep(PSBT_DIR_WATCH_INTERVAL).await;
+            }
+        })?)
+    }
+
+    pub(crate) fn psbt_dir_watcher(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                if let Some(dir) = this.config.psbt_dir().await {
+                    this.process_psbt_dir(&dir).await;
+                }
+                thread::sleep(PSBT_DIR_WATCH_INTERVAL).await;
+            }
+        })?)
+    }
+
+    async fn process_psbt_dir(&self, dir: &Path) {
+        if let Err(e) = self.export_pending_proposals(dir).await {
+            tracing::error!("Impossible to export pending proposals: {e}");
+        }
+        if let Err(e) = self.import_signed_psbt_dir(dir).await {
+            tracing::error!("Impossible to import signed PSBTs: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_core::bitcoin::absolute::LockTime;
+    use smartvaults_core::bitcoin::Transaction;
+
+    use super::*;
+    use crate::client::TempBasePath;
+
+    #[test]
+    fn test_read_and_archive_psbt() {
+        let dir = TempBasePath::new("psbt");
+        fs::create_dir_all(&dir).unwrap();
+
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        })
+        .unwrap();
+
+        // Binary
+        let binary = dir.join("binary.signed.psbt");
+        fs::write(&binary, psbt.serialize()).unwrap();
+        assert_eq!(read_psbt(&binary).unwrap(), psbt);
+
+        // Base64
+        let base64 = dir.join("base64.signed.psbt");
+        fs::write(&base64, format!("{psbt}\n")).unwrap();
+        assert_eq!(read_psbt(&base64).unwrap(), psbt);
+
+        let invalid = dir.join("invalid.signed.psbt");
+        fs::write(&invalid, "invalid").unwrap();
+        assert!(read_psbt(&invalid).is_err());
+
+        move_to(&binary, ARCHIVE_DIR).unwrap();
+        assert!(!binary.exists());
+        assert!(dir.join(ARCHIVE_DIR).join("binary.signed.psbt").exists());
+    }
+}
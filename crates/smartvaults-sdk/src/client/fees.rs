@@ -0,0 +1,75 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::time::Instant;
+
+use bdk_bitcoind_rpc::bitcoincore_rpc::RpcApi;
+use bdk_electrum::electrum_client::ElectrumApi;
+use smartvaults_core::bdk::FeeRate as BdkFeeRate;
+
+use super::{Error, SmartVaults};
+use crate::config::Backend;
+use crate::constants::{FALLBACK_FEE_RATES, FEE_ESTIMATES_CACHE_TTL};
+use crate::manager;
+use crate::types::FeeEstimates;
+
+impl SmartVaults {
+    /// Estimate the fee rates with the configured backend
+    ///
+    /// The estimates are cached for a minute. If the backend can't estimate (ex. fresh regtest),
+    /// fall back to conservative defaults (see [`FeeEstimates::fallback`]).
+    pub async fn estimate_fee_rates(&self) -> Result<FeeEstimates, Error> {
+        let cached: Option<(FeeEstimates, Instant)> = *self.fee_estimates.read();
+        if let Some((estimates, updated_at)) = cached {
+            if updated_at.elapsed() < FEE_ESTIMATES_CACHE_TTL {
+                return Ok(estimates);
+            }
+        }
+
+        let fees: Vec<Option<f64>> = match self.config.backend().await {
+            Backend::Electrum => {
+                let blockchain = self.blockchain().await?;
+                blockchain
+                    .batch_estimate_fee(FeeEstimates::TARGET_BLOCKS.iter().map(|t| *t as usize))?
+                    .into_iter()
+                    // -1 if not enough data
+                    .map(|btc_per_kvb| Some(btc_per_kvb).filter(|f| *f > 0.0))
+                    .collect()
+            }
+            Backend::BitcoinCore { url, auth } => {
+                let client = manager::bitcoind_client(&url, auth)?;
+                let mut fees = Vec::with_capacity(FeeEstimates::TARGET_BLOCKS.len());
+                for target_blocks in FeeEstimates::TARGET_BLOCKS.into_iter() {
+                    let res = client.estimate_smart_fee(target_blocks as u16, None)?;
+                    fees.push(res.fee_rate.map(|rate| rate.to_btc()));
+                }
+                fees
+            }
+        };
+
+        let estimates: FeeEstimates = match to_sat_per_vb(fees) {
+            Some(rates) => FeeEstimates::from_rates(rates, false),
+            None => {
+                tracing::warn!("Fee estimation unavailable: using the fallback fee rates");
+                FeeEstimates::from_rates(FALLBACK_FEE_RATES, true)
+            }
+        };
+
+        let mut fee_estimates = self.fee_estimates.write();
+        *fee_estimates = Some((estimates, Instant::now()));
+
+        Ok(estimates)
+    }
+}
+
+/// Convert the BTC/kvB estimates to sat/vB (`None` if any is missing)
+fn to_sat_per_vb(fees: Vec<Option<f64>>) -> Option<[f32; 4]> {
+    let mut rates = [0.0; 4];
+    if fees.len() != rates.len() {
+        return None;
+    }
+    for (rate, btc_per_kvb) in rates.iter_mut().zip(fees.into_iter()) {
+        *rate = BdkFeeRate::from_btc_per_kvb(btc_per_kvb? as f32).as_sat_per_vb();
+    }
+    Some(rates)
+}
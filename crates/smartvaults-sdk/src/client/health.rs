@@ -0,0 +1,110 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nostr_sdk::pool::pool;
+use nostr_sdk::{Filter, FilterOptions, RelayStatus, Timestamp, TryIntoUrl, Url};
+use parking_lot::RwLock as ParkingLotRwLock;
+
+use super::{Error, SmartVaults};
+use crate::constants::PING_TIMEOUT;
+use crate::types::RelayHealth;
+
+#[derive(Debug, Clone, Default)]
+struct RelayCounters {
+    disconnected_at: Option<Timestamp>,
+    events_received: u64,
+    failed_publishes: u64,
+    send_latency_total: Duration,
+    send_latency_samples: u32,
+}
+
+/// Per-relay counters collected since startup
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RelayMonitor {
+    counters: Arc<ParkingLotRwLock<HashMap<Url, RelayCounters>>>,
+}
+
+impl RelayMonitor {
+    pub fn event_received(&self, url: &Url) {
+        let mut counters = self.counters.write();
+        counters.entry(url.clone()).or_default().events_received += 1;
+    }
+
+    pub fn publish_failed(&self, url: &Url) {
+        let mut counters = self.counters.write();
+        counters.entry(url.clone()).or_default().failed_publishes += 1;
+    }
+
+    pub fn send_latency(&self, url: &Url, latency: Duration) {
+        let mut counters = self.counters.write();
+        let c = counters.entry(url.clone()).or_default();
+        c.send_latency_total += latency;
+        c.send_latency_samples += 1;
+    }
+
+    pub fn status_changed(&self, url: &Url, status: RelayStatus) {
+        if let RelayStatus::Disconnected = status {
+            let mut counters = self.counters.write();
+            counters.entry(url.clone()).or_default().disconnected_at = Some(Timestamp::now());
+        }
+    }
+
+    fn get(&self, url: &Url) -> RelayCounters {
+        self.counters.read().get(url).cloned().unwrap_or_default()
+    }
+}
+
+impl SmartVaults {
+    /// Get connection state and health counters of every relay
+    ///
+    /// Counters are collected since startup.
+    pub async fn relay_status(&self) -> Vec<RelayHealth> {
+        let mut list: Vec<RelayHealth> = Vec::new();
+        for (url, relay) in self.client.relays().await.into_iter() {
+            let stats = relay.stats();
+            let counters: RelayCounters = self.relay_monitor.get(&url);
+            let connected_at: Timestamp = stats.connected_at();
+            list.push(RelayHealth {
+                status: relay.status().await,
+                connected_at: if connected_at == Timestamp::from(0) {
+                    None
+                } else {
+                    Some(connected_at)
+                },
+                disconnected_at: counters.disconnected_at,
+                attempts: stats.attempts(),
+                success: stats.success(),
+                events_received: counters.events_received,
+                failed_publishes: counters.failed_publishes,
+                avg_send_latency: counters
+                    .send_latency_total
+                    .checked_div(counters.send_latency_samples),
+                latency: stats.latency().await,
+                queue: relay.queue(),
+                url,
+            });
+        }
+        list
+    }
+
+    /// Measure the round-trip time to a relay
+    ///
+    /// Send an empty `REQ` and wait for the `EOSE`.
+    pub async fn ping_relay<U>(&self, url: U) -> Result<Duration, Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let relay = self.client.relay(url).await?;
+        let filter = Filter::new().limit(0);
+        let now = Instant::now();
+        relay
+            .get_events_of(vec![filter], PING_TIMEOUT, FilterOptions::ExitOnEOSE)
+            .await?;
+        Ok(now.elapsed())
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use nostr_sdk::{EventId, Timestamp};
+#[cfg(feature = "hwi")]
+use smartvaults_core::bdk::descriptor::DescriptorError;
+#[cfg(feature = "hwi")]
+use smartvaults_core::bdk::keys::KeyError;
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+#[cfg(feature = "hwi")]
+use smartvaults_core::bitcoin::Network;
+#[cfg(feature = "hwi")]
+use smartvaults_core::constants::SMARTVAULTS_ACCOUNT_INDEX;
+#[cfg(feature = "hwi")]
+use smartvaults_core::hwi::{types::HWIDevice, HWIClient};
+#[cfg(feature = "hwi")]
+use smartvaults_core::signer::{self, Signer};
+use smartvaults_core::ApprovedProposal;
+use smartvaults_sdk_sqlite::model::HwRegistration;
+
+use super::{Error, SmartVaults};
+#[cfg(feature = "hwi")]
+use crate::hw::HwiDevice;
+use crate::hw::{self, HardwareDevice, HwSignature};
+use crate::storage::InternalPolicy;
+use crate::types::GetProposal;
+
+impl SmartVaults {
+    /// Approve proposal with a hardware device
+    ///
+    /// The wallet policy is registered on the device at first use and the HMAC stored.
+    /// If the device no longer recognizes the stored HMAC (i.e. wiped or re-flashed),
+    /// the policy is registered again only if `confirm_reregistration` returns `true`.
+    pub async fn approve_with_hw<D, F>(
+        &self,
+        proposal_id: EventId,
+        device: &D,
+        confirm_reregistration: F,
+    ) -> Result<(EventId, ApprovedProposal), Error>
+    where
+        D: HardwareDevice + ?Sized,
+        F: FnOnce() -> bool,
+    {
+        let GetProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.get_proposal_by_id(proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+
+        // Check the device before asking the user to confirm anything on it
+        let fingerprint: Fingerprint = device.fingerprint();
+        if !policy.is_fingerprint_involved(&fingerprint)? {
+            return Err(hw::Error::NotInvolved(fingerprint).into());
+        }
+
+        let device_id: String = device.device_id();
+        let hmac: Option<Vec<u8>> = self
+            .db
+            .get_hw_registration(policy_id, fingerprint, device_id.clone())
+            .await?
+            .map(|r| r.hmac);
+
+        let HwSignature { psbt, registration } = hw::sign_with_registration(
+            device,
+            &policy.name(),
+            &policy.as_descriptor().to_string(),
+            &proposal.psbt(),
+            hmac,
+            confirm_reregistration,
+        )?;
+
+        if let Some(hmac) = registration {
+            tracing::info!("Policy {policy_id} registered on device {fingerprint} ({device_id})");
+            self.db
+                .save_hw_registration(HwRegistration {
+                    policy_id,
+                    fingerprint,
+                    device_id,
+                    hmac,
+                    timestamp: Timestamp::now(),
+                })
+                .await?;
+        }
+
+        self.approve_with_signed_psbt(proposal_id, psbt).await
+    }
+
+    /// Get the wallet policies registered on hardware devices
+    pub async fn get_hw_registrations(&self) -> Result<Vec<HwRegistration>, Error> {
+        Ok(self.db.get_hw_registrations().await?)
+    }
+
+    /// Delete a hardware device registration
+    ///
+    /// The policy will be registered again at the next approval with the device.
+    pub async fn delete_hw_registration<S>(
+        &self,
+        policy_id: EventId,
+        fingerprint: Fingerprint,
+        device_id: S,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        if self
+            .db
+            .delete_hw_registration(policy_id, fingerprint, device_id)
+            .await?
+        {
+            Ok(())
+        } else {
+            Err(Error::NotFound)
+        }
+    }
+}
+
+#[cfg(feature = "hwi")]
+impl SmartVaults {
+    /// Enumerate the hardware devices connected via HWI (Ledger, Trezor, Jade, Coldcard, ...)
+    ///
+    /// The devices that can't be queried are returned as errors.
+    pub fn list_hw_devices() -> Result<Vec<Result<HWIDevice, hw::Error>>, Error> {
+        let devices = HWIClient::enumerate().map_err(|e| hw::Error::Device(e.to_string()))?;
+        Ok(devices
+            .into_iter()
+            .map(|device| device.map_err(|e| hw::Error::Device(e.to_string())))
+            .collect())
+    }
+
+    /// Save (and publish) the signer of a hardware device connected via HWI
+    ///
+    /// Return [`hw::Error::Locked`] if the device is waiting for the PIN or the passphrase and
+    /// [`Error::NetworkMismatch`] if the device exports keys for another network.
+    pub async fn save_hw_signer<S>(&self, name: S, device: HWIDevice) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        if device.needs_pin_sent || device.needs_passphrase_sent {
+            return Err(hw::Error::Locked(device.model).into());
+        }
+
+        let signer: Signer = match Signer::from_hwi(
+            name.into(),
+            None,
+            device,
+            Some(SMARTVAULTS_ACCOUNT_INDEX),
+            self.network,
+        ) {
+            Ok(signer) => signer,
+            Err(signer::Error::BdkDescriptor(DescriptorError::Key(KeyError::InvalidNetwork))) => {
+                // Testnet, signet and regtest share the same keys: only mainnet vs test networks
+                return Err(Error::NetworkMismatch {
+                    expected: self.network,
+                    found: if self.network == Network::Bitcoin {
+                        Network::Testnet
+                    } else {
+                        Network::Bitcoin
+                    },
+                });
+            }
+            Err(signer::Error::HWI(e)) => return Err(hw::Error::Device(e.to_string()).into()),
+            Err(e) => return Err(e.into()),
+        };
+
+        self.save_signer(signer).await
+    }
+
+    /// Approve proposal signing with a hardware device connected via HWI
+    ///
+    /// Return [`hw::Error::NotInvolved`] if the device is not a signer of the policy and
+    /// [`hw::Error::Rejected`] if the user cancels the signing on the device.
+    pub async fn approve_with_hwi(
+        &self,
+        proposal_id: EventId,
+        device: HWIDevice,
+    ) -> Result<(EventId, ApprovedProposal), Error> {
+        let device = HwiDevice::new(device, self.network)?;
+        self.approve_with_hw(proposal_id, &device, || false).await
+    }
+}
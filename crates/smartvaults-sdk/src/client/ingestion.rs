@@ -0,0 +1,105 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use nostr_sdk::nips::nip04;
+use nostr_sdk::{Event, EventBuilder, EventId, Keys, PublicKey, Timestamp};
+use smartvaults_protocol::v1::{IngestionProblem, IngestionReport, Serde, SmartVaultsEventBuilder};
+use smartvaults_sdk_sqlite::model::NotificationKind;
+
+use super::{Error, Message, SmartVaults};
+use crate::constants::{INGESTION_REPORT_MAX_PER_WINDOW, INGESTION_REPORT_WINDOW};
+use crate::storage::{InternalPolicy, InternalProposal};
+
+impl SmartVaults {
+    /// Report back to the policy members a proposal that can't be read
+    ///
+    /// Every proposal is reported at most once and no more than
+    /// [`INGESTION_REPORT_MAX_PER_WINDOW`] reports are sent every [`INGESTION_REPORT_WINDOW`].
+    pub(crate) async fn report_unreadable_proposal(
+        &self,
+        proposal: &Event,
+        reason: IngestionProblem,
+    ) -> Result<(), Error> {
+        if self.db.ingestion_report_exists(proposal.id).await? {
+            return Ok(());
+        }
+
+        let since: Timestamp = Timestamp::now() - INGESTION_REPORT_WINDOW;
+        if self.db.count_ingestion_reports_since(since).await? >= INGESTION_REPORT_MAX_PER_WINDOW {
+            tracing::warn!(
+                "Report of unreadable proposal {} skipped: rate limit reached",
+                proposal.id
+            );
+            return Ok(());
+        }
+
+        let keys: &Keys = self.keys();
+        let nostr_pubkeys: Vec<PublicKey> = proposal.public_keys().copied().collect();
+        let report = IngestionReport::new(proposal.id, reason);
+        let event: Event =
+            EventBuilder::ingestion_report(keys, proposal.author_ref(), &report, &nostr_pubkeys)?;
+        self.client.send_event(event).await?;
+        self.db
+            .save_ingestion_report(proposal.id, reason.as_code(), Timestamp::now())
+            .await?;
+
+        tracing::warn!("Reported unreadable proposal {} ({reason})", proposal.id);
+
+        Ok(())
+    }
+
+    /// Handle the report of a member that can't read a proposal
+    ///
+    /// Emit [`Message::MemberCannotReadProposal`] if the proposal is known and the reporter is a
+    /// policy member.
+    pub(crate) async fn handle_ingestion_report(&self, event: &Event) -> Result<(), Error> {
+        let member: PublicKey = event.author();
+        if member == self.keys().public_key() {
+            return Ok(());
+        }
+
+        let proposal_id: EventId = match event.event_ids().next() {
+            Some(id) => *id,
+            None => return Ok(()),
+        };
+        let InternalProposal { policy_id, .. } = match self.storage.proposal(&proposal_id).await {
+            Ok(proposal) => proposal,
+            // Unknown proposal: nothing to do
+            Err(..) => return Ok(()),
+        };
+
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+        if !public_keys.contains(&member) {
+            tracing::warn!("Ingestion report from {member}, not a member of policy {policy_id}");
+            return Ok(());
+        }
+
+        let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+        let content: String = nip04::decrypt(shared_key.secret_key()?, &member, &event.content)?;
+        let report = IngestionReport::from_json(content)?;
+        if report.proposal_id != proposal_id {
+            tracing::warn!("Ingestion report {} refers to another proposal", event.id);
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Member {member} can't read proposal {proposal_id}: {}",
+            report.reason
+        );
+
+        self.db
+            .save_notification(
+                NotificationKind::MemberCannotReadProposal,
+                proposal_id,
+                event.created_at,
+            )
+            .await?;
+        self.sync_channel.send(Message::MemberCannotReadProposal {
+            member,
+            proposal_id,
+            reason_code: report.reason,
+        });
+
+        Ok(())
+    }
+}
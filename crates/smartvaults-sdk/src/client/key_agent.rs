@@ -15,7 +15,7 @@ use smartvaults_protocol::v1::constants::{KEY_AGENT_SIGNALING, KEY_AGENT_SIGNER_
 use smartvaults_protocol::v1::{Serde, SignerOffering, SmartVaultsEventBuilder, VerifiedKeyAgents};
 
 use super::{Error, SmartVaults};
-use crate::types::{GetProposal, GetSigner, GetSignerOffering, KeyAgent};
+use crate::types::{GetProposal, GetSigner, GetSignerOffering, KeyAgent, UserRole};
 
 impl SmartVaults {
     /// Announce as Key Agent
@@ -41,7 +41,41 @@ impl SmartVaults {
         Ok(())
     }
 
+    /// Get the public keys, between `public_keys`, that announced as Key Agent
+    pub(crate) async fn declared_key_agents<I>(
+        &self,
+        public_keys: I,
+    ) -> Result<HashSet<PublicKey>, Error>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let filter = Filter::new()
+            .identifier(self.network.magic().to_string())
+            .kind(KEY_AGENT_SIGNALING)
+            .authors(public_keys);
+        Ok(self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?
+            .into_iter()
+            .map(|event| event.author())
+            .collect())
+    }
+
+    /// Get my role
+    pub async fn my_role(&self) -> Result<UserRole, Error> {
+        let public_key: PublicKey = self.keys().public_key();
+        if self.declared_key_agents([public_key]).await?.is_empty() {
+            Ok(UserRole::User)
+        } else {
+            Ok(UserRole::KeyAgent)
+        }
+    }
+
     /// Create/Edit signer offering
+    ///
+    /// Require the [`UserRole::KeyAgent`] role (see [`SmartVaults::announce_key_agent`]).
     pub async fn signer_offering(
         &self,
         signer: &Signer,
@@ -50,22 +84,8 @@ impl SmartVaults {
         // Get keys
         let keys: &Keys = self.keys();
 
-        // Check if exists key agent signaling event
-        let filter = Filter::new()
-            .identifier(self.network.magic().to_string())
-            .kind(KEY_AGENT_SIGNALING)
-            .author(keys.public_key())
-            .limit(1);
-        let res = self
-            .client
-            .database()
-            .event_ids_by_filters(vec![filter], Order::Desc)
-            .await?;
-
-        if res.is_empty() {
-            self.announce_key_agent().await?;
-        } else {
-            tracing::debug!("Key agent already announced");
+        if self.my_role().await? != UserRole::KeyAgent {
+            return Err(Error::KeyAgentRoleRequired);
         }
 
         // Compose event
@@ -244,6 +264,7 @@ impl SmartVaults {
                 utxos,
                 policy_path.clone(),
                 skip_frozen_utxos,
+                None,
             )
             .await?;
         if let Proposal::Spending {
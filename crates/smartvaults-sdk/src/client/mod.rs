@@ -1,74 +1,122 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::collections::hash_map::Entry as HashMapEntry;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::ops::Add;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_utility::thread;
+use bdk_bitcoind_rpc::bitcoincore_rpc::RpcApi;
 use bdk_electrum::electrum_client::{
     Client as ElectrumClient, Config as ElectrumConfig, ElectrumApi, Socks5Config,
 };
 use nostr_sdk::database::{NostrDatabaseExt, Order};
+use nostr_sdk::nips::nip01::Coordinate;
 use nostr_sdk::nips::nip06::FromMnemonic;
+use nostr_sdk::nips::nip65::RelayMetadata;
 use nostr_sdk::pool::pool;
 use nostr_sdk::{
-    nips, Client, ClientBuilder, ClientMessage, Contact, Event, EventBuilder, EventId, Filter,
-    JsonUtil, Keys, Kind, Metadata, Options, Profile, PublicKey, Relay, RelayOptions,
-    RelayPoolNotification, RelaySendOptions, Result, SQLiteDatabase, SubscribeAutoCloseOptions,
-    SubscribeOptions, Tag, Timestamp, TryIntoUrl, UncheckedUrl, Url,
+    Client, ClientBuilder, ClientMessage, Contact, Event, EventBuilder, EventId, Filter, JsonUtil,
+    Keys, Kind, Metadata, Options, Profile, PublicKey, Relay, RelayOptions, RelayPoolNotification,
+    RelaySendOptions, Result, SQLiteDatabase, SubscribeAutoCloseOptions, SubscribeOptions, Tag,
+    Timestamp, TryIntoUrl, UncheckedUrl, Url,
 };
 use parking_lot::RwLock as ParkingLotRwLock;
 use smartvaults_core::bdk::chain::ConfirmationTime;
 use smartvaults_core::bdk::wallet::{AddressIndex, Balance};
-use smartvaults_core::bdk::FeeRate as BdkFeeRate;
-use smartvaults_core::bips::bip39::Mnemonic;
+use smartvaults_core::bdk::{FeeRate as BdkFeeRate, KeychainKind};
+use smartvaults_core::bips::bip39::{Language, Mnemonic};
 use smartvaults_core::bitcoin::address::NetworkUnchecked;
 use smartvaults_core::bitcoin::bip32::Fingerprint;
 use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
-use smartvaults_core::bitcoin::{Address, Network, OutPoint, ScriptBuf, Txid};
-use smartvaults_core::miniscript::Descriptor;
+use smartvaults_core::bitcoin::{
+    Address, Network, OutPoint, Script, ScriptBuf, Transaction, TxOut, Txid,
+};
+use smartvaults_core::miniscript::{Descriptor, DescriptorPublicKey};
+use smartvaults_core::policy::{
+    KeyPurpose, LimitExceeded, PolicyPath, PolicyPathSelector, ScriptType,
+};
+use smartvaults_core::secp256k1::rand;
 use smartvaults_core::signer::smartvaults_signer;
 use smartvaults_core::types::{KeeChain, Keychain, Seed, WordCount};
 use smartvaults_core::{
-    Amount, ApprovedProposal, CompletedProposal, FeeRate, Policy, PolicyTemplate, Proposal, Signer,
+    mnemonic, Amount, ApprovedProposal, CoinSelection, CompletedProposal, FeeRate, FeeWarning,
+    OutputKind, OutputVerification, Policy, PolicyTemplate, Priority, Proposal, Signer, SignerType,
     SECP256K1,
 };
 use smartvaults_protocol::v1::constants::{
-    APPROVED_PROPOSAL_EXPIRATION, APPROVED_PROPOSAL_KIND, COMPLETED_PROPOSAL_KIND, PROPOSAL_KIND,
-    SHARED_KEY_KIND,
+    APPROVED_PROPOSAL_EXPIRATION, APPROVED_PROPOSAL_KIND, COMPLETED_PROPOSAL_KIND, LABELS_KIND,
+    PROPOSAL_KIND, SHARED_KEY_KIND,
 };
 use smartvaults_protocol::v1::{Encryption, Label, LabelData, SmartVaultsEventBuilder};
+use smartvaults_sdk_sqlite::model::{
+    Birthday, NotificationKind, ProfileVersion, RelayRole, SigningKind, TxFee,
+};
 use smartvaults_sdk_sqlite::Store;
-use tokio::sync::broadcast::{self, Sender};
+use tokio::sync::Notify;
 
+mod audit;
+mod backup;
+mod compat;
 mod connect;
+mod drafts;
+mod electrum;
+mod external_signer;
+mod fees;
+mod health;
+mod hw;
+mod ingestion;
 mod key_agent;
 mod label;
+mod notifications;
+mod offline;
+mod passphrase;
+mod price;
+mod proof;
+mod publish;
+mod reminders;
+mod settings;
 mod signers;
+mod stats;
 mod sync;
 
+pub(crate) use self::health::RelayMonitor;
+pub(crate) use self::notifications::SyncChannel;
+pub use self::notifications::{Notification, SyncSubscription};
 pub use self::sync::{EventHandled, Message};
-use crate::config::{Config, ElectrumEndpoint};
-use crate::constants::{MAINNET_RELAYS, SEND_TIMEOUT, TESTNET_RELAYS};
-use crate::manager::{Manager, SmartVaultsWallet, TransactionDetails};
+use crate::changelog::{self, Release};
+use crate::config::{Backend, Config, ElectrumEndpoint, SyncProfile};
+use crate::constants::{MAX_FEE_RATE_MULTIPLIER, PRICE_CACHE_TTL, SEND_TIMEOUT};
+use crate::lock::LockFile;
+use crate::manager::{self, IndexedAddress, Manager, SmartVaultsWallet, TransactionDetails};
+use crate::price::{HttpPriceProvider, PriceCache, PriceProvider};
+use crate::screening::{
+    FileScreeningProvider, NoScreening, ScreeningAction, ScreeningProvider, ScreeningResult,
+};
 use crate::storage::{
     InternalApproval, InternalCompletedProposal, InternalPolicy, InternalProposal,
     SmartVaultsStorage,
 };
 use crate::types::{
-    GetAddress, GetApproval, GetApprovedProposals, GetCompletedProposal, GetPolicy, GetProposal,
-    GetTransaction, GetUtxo, PolicyBackup,
+    ApproveAndFinalize, BatchSendReport, DeletionImpact, FeeEstimates, FeesPaid, GetAddress,
+    GetApproval, GetApprovedProposals, GetCompletedProposal, GetContact, GetNotificationsResult,
+    GetPolicy, GetProposal, GetSharedSigner, GetSigner, GetTransaction, GetUtxo, HwRegistration,
+    MetadataChange, Page, Pagination, PolicyBackup, PolicyKeyReport, PolicyParticipant,
+    SpendPreview, TxDetails, TxInputDetails, TxOutputDetails, UnexpectedTx, UserRole,
 };
+use crate::util::progress::{CancellationToken, Progress, ProgressCallback, Task};
 use crate::{util, Error};
 
 /// Smart Vaults Client
 #[derive(Debug, Clone)]
 pub struct SmartVaults {
+    base_path: PathBuf,
     network: Network,
     keechain: Arc<ParkingLotRwLock<KeeChain>>,
     keys: Keys,
@@ -78,8 +126,18 @@ pub struct SmartVaults {
     storage: SmartVaultsStorage,
     db: Store,
     syncing: Arc<AtomicBool>,
-    sync_channel: Sender<Message>,
+    sync_channel: SyncChannel,
+    /// Notified when the sync filters change (see [`SmartVaults::refresh_subscriptions`])
+    subscriptions_changed: Arc<Notify>,
+    relay_monitor: RelayMonitor,
+    screening: Arc<ParkingLotRwLock<Arc<dyn ScreeningProvider>>>,
+    price_provider: Arc<ParkingLotRwLock<Arc<dyn PriceProvider>>>,
+    prices: PriceCache,
+    fee_estimates: Arc<ParkingLotRwLock<Option<(FeeEstimates, Instant)>>>,
     default_signer: Signer,
+    lock: Arc<LockFile>,
+    /// Quarantined (poison) events, skipped by the sync
+    quarantined: Arc<ParkingLotRwLock<HashSet<EventId>>>,
 }
 
 impl SmartVaults {
@@ -88,6 +146,23 @@ impl SmartVaults {
         password: String,
         keechain: KeeChain,
         network: Network,
+        steal_lock: bool,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let this = Self::build(base_path, password, keechain, network, steal_lock).await?;
+        this.init().await?;
+        Ok(this)
+    }
+
+    /// Open the dbs and build the storage, without connecting to relays and starting the sync
+    async fn build<P>(
+        base_path: P,
+        password: String,
+        keechain: KeeChain,
+        network: Network,
+        steal_lock: bool,
     ) -> Result<Self, Error>
     where
         P: AsRef<Path>,
@@ -98,6 +173,12 @@ impl SmartVaults {
         let seed = keechain.seed(password)?;
         let keys = Keys::from_mnemonic(seed.mnemonic().to_string(), seed.passphrase())?;
 
+        // Lock the account before touching the dbs
+        let lock = LockFile::acquire(
+            util::dir::lock_file(base_path, network, keys.public_key())?,
+            steal_lock,
+        )?;
+
         // Open db
         let db = Store::open(
             util::dir::user_db(base_path, network, keys.public_key())?,
@@ -105,6 +186,15 @@ impl SmartVaults {
         )
         .await?;
 
+        // Record the identity in use (see `SmartVaults::list_passphrase_identities`)
+        let passphrase: Option<String> = seed.passphrase().map(|p| p.to_string());
+        db.save_passphrase_identity(passphrase::passphrase_identity(
+            keechain.fingerprint(),
+            keys.public_key(),
+            passphrase.as_deref(),
+        ))
+        .await?;
+
         // Nostr client
         let nostr_db_path = util::dir::nostr_db(base_path, keys.public_key(), network)?;
         let nostr_db = SQLiteDatabase::open(nostr_db_path).await?;
@@ -119,30 +209,127 @@ impl SmartVaults {
             .opts(opts)
             .build();
 
+        // Quarantine the events that were being handled when the app crashed
+        let database = client.database();
+        let quarantined: Vec<EventId> = db.get_processing_event_ids().await?;
+        let mut crashed: Vec<Event> = Vec::new();
+        for event_id in quarantined.iter().copied() {
+            tracing::warn!("Event {event_id} quarantined: crashed while handling it");
+            match database.event_by_id(event_id).await {
+                Ok(event) => crashed.push(event),
+                // Only the ID is quarantined
+                Err(e) => tracing::error!("Event {event_id} not found in nostr database: {e}"),
+            }
+        }
+        db.quarantine_processing_events(crashed).await?;
+        let quarantined_ids: HashSet<EventId> = db.get_quarantined_event_ids().await?;
+
         // Storage
-        let storage = SmartVaultsStorage::build(keys.clone(), client.database(), network).await?;
+        let storage = SmartVaultsStorage::build(
+            keys.clone(),
+            client.database(),
+            network,
+            &quarantined_ids,
+            db.get_rotated_shared_keys()
+                .await?
+                .into_iter()
+                .map(|r| (r.policy_id, r.shared_key))
+                .collect(),
+        )
+        .await?;
 
-        let (sender, _) = broadcast::channel::<Message>(4096);
+        let config = Config::try_from_file(base_path, network)?;
+        let screening: Arc<dyn ScreeningProvider> = match config.screening_list_path().await {
+            Some(path) => Arc::new(FileScreeningProvider::new(path, network)),
+            None => Arc::new(NoScreening),
+        };
+        let price_provider: Arc<dyn PriceProvider> = Arc::new(HttpPriceProvider::new(
+            config.price_endpoint().await?,
+            config.proxy().await.ok(),
+        ));
 
         let this = Self {
+            base_path: base_path.to_path_buf(),
             network,
             keechain: Arc::new(ParkingLotRwLock::new(keechain)),
             keys,
             client,
             manager: Manager::new(db.clone(), network),
-            config: Config::try_from_file(base_path, network)?,
+            config,
             storage,
             db,
             syncing: Arc::new(AtomicBool::new(false)),
-            sync_channel: sender,
+            sync_channel: SyncChannel::new(4096),
+            subscriptions_changed: Arc::new(Notify::new()),
+            relay_monitor: RelayMonitor::default(),
+            screening: Arc::new(ParkingLotRwLock::new(screening)),
+            price_provider: Arc::new(ParkingLotRwLock::new(price_provider)),
+            prices: PriceCache::new(PRICE_CACHE_TTL),
+            fee_estimates: Arc::new(ParkingLotRwLock::new(None)),
             default_signer: smartvaults_signer(seed, network)?,
+            lock: Arc::new(lock),
+            quarantined: Arc::new(ParkingLotRwLock::new(quarantined_ids)),
         };
 
-        this.init().await?;
+        for event_id in quarantined.into_iter() {
+            this.sync_channel
+                .send(Message::PoisonEventQuarantined(event_id));
+        }
 
         Ok(this)
     }
 
+    /// Generate keychain (password `test`) and build the client without connecting to relays
+    /// and starting the sync
+    #[cfg(test)]
+    pub(crate) async fn generate_offline<P>(
+        base_path: P,
+        name: &str,
+        network: Network,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let base_path = base_path.as_ref();
+        let password = String::from("test");
+        let mnemonic: Mnemonic = mnemonic::generate(WordCount::W12, Language::English)?;
+        let keychains_path: PathBuf = util::dir::keychains_path(base_path, network)?;
+        let keechain: KeeChain = KeeChain::restore(
+            keychains_path,
+            name,
+            || Ok(password.clone()),
+            || Ok(password.clone()),
+            || Ok(mnemonic),
+            network,
+            &SECP256K1,
+        )?;
+        Self::build(base_path, password, keechain, network, false).await
+    }
+
+    /// Open keychain (password `test`) and build the client without connecting to relays and
+    /// starting the sync
+    #[cfg(test)]
+    pub(crate) async fn open_offline<P>(
+        base_path: P,
+        name: &str,
+        network: Network,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let base_path = base_path.as_ref();
+        let password = String::from("test");
+        let keychains_path: PathBuf = util::dir::keychains_path(base_path, network)?;
+        let keechain: KeeChain = KeeChain::open(
+            keychains_path,
+            name,
+            || Ok(password.clone()),
+            network,
+            &SECP256K1,
+        )?;
+        Self::build(base_path, password, keechain, network, false).await
+    }
+
     /// Open keychain
     pub async fn open<P, S>(
         base_path: P,
@@ -150,6 +337,23 @@ impl SmartVaults {
         password: S,
         network: Network,
     ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+        S: Into<String>,
+    {
+        Self::open_with_opts(base_path, name, password, network, false).await
+    }
+
+    /// Open keychain
+    ///
+    /// If `steal_lock` is `true`, the lock left by a crashed process is removed.
+    pub async fn open_with_opts<P, S>(
+        base_path: P,
+        name: S,
+        password: S,
+        network: Network,
+        steal_lock: bool,
+    ) -> Result<Self, Error>
     where
         P: AsRef<Path>,
         S: Into<String>,
@@ -169,16 +373,20 @@ impl SmartVaults {
         let passphrase: Option<String> = keechain.keychain(&password)?.get_passphrase(0);
         keechain.apply_passphrase(&password, passphrase, &SECP256K1)?;
 
-        Self::new(base_path, password, keechain, network).await
+        Self::new(base_path, password, keechain, network, steal_lock).await
     }
 
     /// Generate keychain
+    ///
+    /// The mnemonic is generated using the wordlist of `language`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn generate<P, S, PSW, CPSW, PASSP>(
         base_path: P,
         name: S,
         get_password: PSW,
         get_confirm_password: CPSW,
         word_count: WordCount,
+        language: Language,
         get_passphrase: PASSP,
         network: Network,
     ) -> Result<Self, Error>
@@ -194,14 +402,14 @@ impl SmartVaults {
         let password: String = get_password().map_err(|e| Error::Generic(e.to_string()))?;
 
         // Generate keychain
+        let mnemonic: Mnemonic = mnemonic::generate(word_count, language)?;
         let keychains_path: PathBuf = util::dir::keychains_path(base_path, network)?;
-        let mut keechain: KeeChain = KeeChain::generate(
+        let mut keechain: KeeChain = KeeChain::restore(
             keychains_path,
             name,
             || Ok(password.clone()),
             get_confirm_password,
-            word_count,
-            || Ok(None),
+            || Ok(mnemonic),
             network,
             &SECP256K1,
         )?;
@@ -213,10 +421,12 @@ impl SmartVaults {
             keechain.apply_passphrase(&password, Some(passphrase), &SECP256K1)?;
         }
 
-        Self::new(base_path, password, keechain, network).await
+        Self::new(base_path, password, keechain, network, false).await
     }
 
     /// Restore keychain
+    ///
+    /// The mnemonic keeps its wordlist (see [`mnemonic::parse`] to detect it).
     pub async fn restore<P, S, PSW, CPSW, M, PASSP>(
         base_path: P,
         name: S,
@@ -257,7 +467,7 @@ impl SmartVaults {
             keechain.apply_passphrase(&password, Some(passphrase), &SECP256K1)?;
         }
 
-        Self::new(base_path, password, keechain, network).await
+        Self::new(base_path, password, keechain, network, false).await
     }
 
     #[tracing::instrument(skip_all, level = "trace")]
@@ -272,12 +482,31 @@ impl SmartVaults {
     async fn init(&self) -> Result<(), Error> {
         for (policy_id, InternalPolicy { policy, .. }) in self.storage.vaults().await.into_iter() {
             let manager = self.manager.clone();
+            let sync_channel = self.sync_channel.clone();
+            let created_at: Option<Timestamp> = self
+                .client
+                .database()
+                .event_by_id(policy_id)
+                .await
+                .ok()
+                .map(|e| e.created_at);
             thread::spawn(async move {
-                if let Err(e) = manager.load_policy(policy_id, policy).await {
+                if let Err(e) = manager
+                    .load_policy(policy_id, policy, created_at, Some(sync_channel))
+                    .await
+                {
                     tracing::error!("Impossible to load policy {policy_id}: {e}");
                 }
             })?;
         }
+        for proposal_id in self.storage.proposals().await.into_keys() {
+            if let Err(e) = self.check_proposal_fee(proposal_id).await {
+                tracing::error!("Impossible to check fee of proposal {proposal_id}: {e}");
+            }
+            if let Err(e) = self.check_proposal_ready(proposal_id).await {
+                tracing::error!("Impossible to check if proposal {proposal_id} is ready: {e}");
+            }
+        }
         self.restore_relays().await?;
         self.client.connect().await;
         self.sync()?;
@@ -285,7 +514,7 @@ impl SmartVaults {
     }
 
     async fn blockchain(&self) -> Result<ElectrumClient, Error> {
-        let endpoint = self.config.electrum_endpoint().await?;
+        let endpoint = self.config.active_electrum_endpoint().await?;
         let proxy: Option<SocketAddr> = self.config.proxy().await.ok();
         let config = ElectrumConfig::builder()
             .validate_domain(endpoint.validate_tls())
@@ -311,11 +540,30 @@ impl SmartVaults {
     }
 
     /// Rename keychain file
+    ///
+    /// The databases and the logs are bound to the nostr public key and not to the keychain name,
+    /// so the open handles are not affected by the rename.
     pub fn rename<S>(&self, new_name: S) -> Result<(), Error>
     where
         S: Into<String>,
     {
+        let new_name: String = new_name.into();
+
+        if !util::dir::is_valid_keychain_name(&new_name) {
+            return Err(Error::InvalidKeychainName(new_name));
+        }
+
+        // Hold the lock until the file is renamed, to avoid races with another rename
         let mut keechain = self.keechain.write();
+
+        if keechain.name().as_deref() == Some(new_name.as_str()) {
+            return Ok(());
+        }
+
+        if util::dir::get_keychains_list(&self.base_path, self.network)?.contains(&new_name) {
+            return Err(Error::KeychainAlreadyExists(new_name));
+        }
+
         Ok(keechain.rename(new_name)?)
     }
 
@@ -365,11 +613,116 @@ impl SmartVaults {
 
     /// Force a full timechain sync
     pub async fn force_full_timechain_sync(&self) -> Result<(), Error> {
-        let endpoint = self.config.electrum_endpoint().await?;
-        let proxy = self.config.proxy().await.ok();
-        self.manager
-            .full_sync_all(endpoint, proxy, true, None)
-            .await?;
+        match self.config.backend().await {
+            Backend::Electrum => {
+                let endpoint = self.electrum_endpoint_with_failover().await?;
+                let proxy = self.config.proxy().await.ok();
+                let gap_limit: usize = self.config.gap_limit().await;
+                self.manager
+                    .full_sync_all(endpoint, proxy, gap_limit, true, None)
+                    .await?;
+            }
+            Backend::BitcoinCore { url, auth } => {
+                self.manager
+                    .bitcoind_sync_all(&url, auth, true, None)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rescan the timechain for every policy, one at a time
+    ///
+    /// Unlike [`SmartVaults::force_full_timechain_sync`], wait for the completion.
+    /// Progress is reported as synced policies and the cancellation is checked between policies.
+    pub async fn rescan_with_progress(
+        &self,
+        progress: Option<ProgressCallback>,
+        token: CancellationToken,
+    ) -> Result<(), Error> {
+        let task = Task::new(progress, token);
+        let policy_ids: Vec<EventId> = self.manager.policy_ids().await;
+        let total: usize = policy_ids.len();
+        task.report(Progress::new(0, total));
+
+        let backend: Backend = self.config.backend().await;
+        for (index, policy_id) in policy_ids.into_iter().enumerate() {
+            task.checkpoint()?;
+
+            let res = match &backend {
+                Backend::Electrum => {
+                    let endpoint = self.electrum_endpoint_with_failover().await?;
+                    let proxy = self.config.proxy().await.ok();
+                    let gap_limit: usize = self.config.gap_limit().await;
+                    self.manager
+                        .full_sync(policy_id, endpoint, proxy, gap_limit, true)
+                        .await
+                }
+                Backend::BitcoinCore { url, auth } => {
+                    self.manager
+                        .bitcoind_sync(policy_id, url, auth.clone(), true)
+                        .await
+                }
+            };
+
+            match res {
+                Ok(()) => self
+                    .sync_channel
+                    .send(Message::WalletSyncCompleted(policy_id)),
+                Err(manager::Error::Wallet(manager::WalletError::AlreadySyncing)) => {
+                    tracing::warn!("Policy {policy_id} is already syncing");
+                }
+                Err(e) => return Err(e.into()),
+            }
+
+            task.report(Progress::new(index + 1, total));
+        }
+
+        Ok(())
+    }
+
+    /// Set the birthday height of a policy (i.e. for imported descriptors) and re-sync it in
+    /// background
+    ///
+    /// The birthday is the earliest of `height` and of the policy creation. Use `None` to remove
+    /// the height.
+    pub async fn set_policy_birthday(
+        &self,
+        policy_id: EventId,
+        height: Option<u32>,
+    ) -> Result<(), Error> {
+        self.db.set_birthday_height(policy_id, height).await?;
+        let birthday: Birthday = self.db.get_birthday(policy_id).await?;
+        self.manager.set_birthday(policy_id, birthday).await?;
+
+        let this = self.clone();
+        thread::spawn(async move {
+            let res = match this.config.backend().await {
+                Backend::Electrum => match this.electrum_endpoint_with_failover().await {
+                    Ok(endpoint) => {
+                        let proxy = this.config.proxy().await.ok();
+                        let gap_limit: usize = this.config.gap_limit().await;
+                        this.manager
+                            .full_sync(policy_id, endpoint, proxy, gap_limit, true)
+                            .await
+                            .map_err(Error::from)
+                    }
+                    Err(e) => Err(e),
+                },
+                Backend::BitcoinCore { url, auth } => this
+                    .manager
+                    .bitcoind_sync(policy_id, &url, auth, true)
+                    .await
+                    .map_err(Error::from),
+            };
+            match res {
+                Ok(()) => this
+                    .sync_channel
+                    .send(Message::WalletSyncCompleted(policy_id)),
+                Err(e) => tracing::error!("Impossible to re-sync policy {policy_id}: {e}"),
+            }
+        })?;
+
         Ok(())
     }
 
@@ -402,6 +755,72 @@ impl SmartVaults {
         Ok(self.keechain.read().keychain(password)?)
     }
 
+    /// Verify the mnemonic (and the passphrase) written down by the user, without exporting
+    /// anything
+    ///
+    /// Return [`Error::MnemonicNotMatch`] if the words are wrong and [`Error::PassphraseNotMatch`]
+    /// if only the passphrase is wrong.
+    pub fn verify_mnemonic<T>(
+        &self,
+        password: T,
+        mnemonic: &Mnemonic,
+        passphrase: Option<&str>,
+    ) -> Result<(), Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let seed: Seed = self.keechain.read().seed(password)?;
+        if seed.mnemonic().to_entropy() != mnemonic.to_entropy() {
+            return Err(Error::MnemonicNotMatch);
+        }
+        check_passphrase(&seed, passphrase)
+    }
+
+    /// Random 1-based positions of the mnemonic words to ask the user (see
+    /// [`SmartVaults::verify_mnemonic_words`])
+    pub fn mnemonic_challenge<T>(&self, password: T, count: usize) -> Result<Vec<usize>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let seed: Seed = self.keechain.read().seed(password)?;
+        let word_count: usize = seed.mnemonic().word_count();
+        let mut positions: Vec<usize> =
+            rand::seq::index::sample(&mut rand::thread_rng(), word_count, count.min(word_count))
+                .into_iter()
+                .map(|i| i + 1)
+                .collect();
+        positions.sort();
+        Ok(positions)
+    }
+
+    /// Verify some words of the mnemonic (1-based position and word) and the passphrase
+    ///
+    /// Return [`Error::MnemonicNotMatch`] if a word is wrong and [`Error::PassphraseNotMatch`] if
+    /// only the passphrase is wrong.
+    pub fn verify_mnemonic_words<T>(
+        &self,
+        password: T,
+        words: &[(usize, String)],
+        passphrase: Option<&str>,
+    ) -> Result<(), Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let seed: Seed = self.keechain.read().seed(password)?;
+        let mnemonic: Mnemonic = seed.mnemonic();
+        let stored: Vec<&str> = mnemonic.word_iter().collect();
+        for (position, word) in words.iter() {
+            let matches: bool = match position.checked_sub(1).and_then(|i| stored.get(i)) {
+                Some(stored) => stored.eq_ignore_ascii_case(word.trim()),
+                None => false,
+            };
+            if !matches {
+                return Err(Error::MnemonicNotMatch);
+            }
+        }
+        check_passphrase(&seed, passphrase)
+    }
+
     pub fn keys(&self) -> &Keys {
         &self.keys
     }
@@ -414,42 +833,59 @@ impl SmartVaults {
         self.network
     }
 
-    pub async fn add_relay<S>(&self, url: S, proxy: Option<SocketAddr>) -> Result<(), Error>
+    /// Add relay
+    ///
+    /// The `proxy` is used only for this relay. For `.onion` relays without a proxy, the
+    /// global one is used (see [`Config::proxy`](crate::config::Config::proxy)).
+    ///
+    /// Write-only relays are never subscribed and read-only relays are skipped when publishing.
+    pub async fn add_relay<S>(
+        &self,
+        url: S,
+        proxy: Option<SocketAddr>,
+        role: RelayRole,
+    ) -> Result<(), Error>
     where
         S: Into<String>,
     {
-        self.add_relay_with_opts(url, proxy, true).await
+        self.add_relay_with_opts(url, proxy, role, true).await
     }
 
     pub async fn add_relay_with_opts<S>(
         &self,
         url: S,
         proxy: Option<SocketAddr>,
+        role: RelayRole,
         save_to_relay_list: bool,
     ) -> Result<(), Error>
     where
         S: Into<String>,
     {
         let url = Url::parse(&url.into())?;
-        self.db.insert_relay(url.clone(), proxy).await?;
+        self.db.insert_relay(url.clone(), proxy, role).await?;
         self.db.enable_relay(url.clone()).await?;
 
-        let opts = RelayOptions::new().proxy(proxy);
+        let opts = RelayOptions::new()
+            .proxy(self.relay_proxy(&url, proxy).await)
+            .read(role.read)
+            .write(role.write);
 
         if self.client.add_relay_with_opts(url.as_str(), opts).await? {
             let relay = self.client.relay(&url).await?;
-            let last_sync: Timestamp = match self.db.get_last_relay_sync(url.clone()).await {
-                Ok(ts) => ts,
-                Err(_) => Timestamp::from(0),
-            };
-            let filters: Vec<Filter> = self.sync_filters(last_sync).await;
-            relay
-                .subscribe(
-                    filters,
-                    SubscribeOptions::default()
-                        .send_opts(RelaySendOptions::new().skip_send_confirmation(true)),
-                )
-                .await?;
+            if role.read {
+                let last_sync: Timestamp = match self.db.get_last_relay_sync(url.clone()).await {
+                    Ok(ts) => ts,
+                    Err(_) => Timestamp::from(0),
+                };
+                let filters: Vec<Filter> = self.sync_filters(last_sync).await;
+                relay
+                    .subscribe(
+                        filters,
+                        SubscribeOptions::default()
+                            .send_opts(RelaySendOptions::new().skip_send_confirmation(true)),
+                    )
+                    .await?;
+            }
             relay.connect(None).await;
 
             if save_to_relay_list {
@@ -461,30 +897,124 @@ impl SmartVaults {
                 })?;
             }
 
-            if let Err(e) = self.rebroadcast_to(url.clone()).await {
-                tracing::error!("Impossible to rebroadcast events to {url}: {e}");
+            if role.write {
+                if let Err(e) = self.rebroadcast_to(url.clone()).await {
+                    tracing::error!("Impossible to rebroadcast events to {url}: {e}");
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Change the read/write policy of a relay
+    ///
+    /// The relay is reconnected with the new role: subscribed again only if readable.
+    pub async fn set_relay_role<S>(&self, url: S, role: RelayRole) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let url = Url::parse(&url.into())?;
+        let proxy: Option<SocketAddr> = self
+            .db
+            .get_relays(true)
+            .await?
+            .into_iter()
+            .find(|(u, ..)| u == &url)
+            .and_then(|(_, proxy, _)| proxy);
+        self.db.set_relay_role(url.clone(), role).await?;
+        self.client.remove_relay(url.as_str()).await?;
+        self.add_relay_with_opts(url, proxy, role, true).await
+    }
+
+    /// Set (or remove, if `None`) the proxy of a relay
+    ///
+    /// The relay is reconnected through the new proxy.
+    pub async fn set_relay_proxy<S>(&self, url: S, proxy: Option<SocketAddr>) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let url = Url::parse(&url.into())?;
+        let role: RelayRole = self
+            .db
+            .get_relays_roles()
+            .await?
+            .get(&url)
+            .copied()
+            .unwrap_or_default();
+        self.db.set_relay_proxy(url.clone(), proxy).await?;
+        self.client.remove_relay(url.as_str()).await?;
+        self.add_relay_with_opts(url, proxy, role, false).await
+    }
+
+    /// Proxy to use for a relay: `.onion` relays without a proxy fall back to the global one
+    async fn relay_proxy(&self, url: &Url, proxy: Option<SocketAddr>) -> Option<SocketAddr> {
+        match proxy {
+            Some(proxy) => Some(proxy),
+            None => match url.host_str() {
+                Some(host) if host.ends_with(".onion") => self.config.proxy().await.ok(),
+                _ => None,
+            },
+        }
+    }
+
+    /// Get the relays roles
+    ///
+    /// The relays not included (i.e. nostr connect relays) are both read and write.
+    pub async fn relays_roles(&self) -> Result<HashMap<Url, RelayRole>, Error> {
+        Ok(self.db.get_relays_roles().await?)
+    }
+
+    /// Get the connected relays with the requested capability
+    pub(crate) async fn relays_with_role(&self, read: bool, write: bool) -> BTreeMap<Url, Relay> {
+        let roles: HashMap<Url, RelayRole> = match self.db.get_relays_roles().await {
+            Ok(roles) => roles,
+            Err(e) => {
+                tracing::error!("Impossible to get relays roles: {e}");
+                HashMap::new()
+            }
+        };
+        self.client
+            .relays()
+            .await
+            .into_iter()
+            .filter(|(url, ..)| {
+                let role: RelayRole = roles.get(url).copied().unwrap_or_default();
+                (!read || role.read) && (!write || role.write)
+            })
+            .collect()
+    }
+
     /// Save relay list (NIP65)
     pub async fn save_relay_list(&self) -> Result<EventId, Error> {
+        let roles: HashMap<Url, RelayRole> = self.db.get_relays_roles().await?;
         let relays = self.client.relays().await;
-        let list = relays
-            .into_keys()
-            .map(|url| (UncheckedUrl::from(url), None));
+        let list = relays.into_keys().map(|url| {
+            let metadata: Option<RelayMetadata> = match roles.get(&url) {
+                Some(RelayRole {
+                    read: true,
+                    write: false,
+                }) => Some(RelayMetadata::Read),
+                Some(RelayRole {
+                    read: false,
+                    write: true,
+                }) => Some(RelayMetadata::Write),
+                _ => None,
+            };
+            (UncheckedUrl::from(url), metadata)
+        });
         let event = EventBuilder::relay_list(list);
         Ok(self.client.send_event_builder(event).await?)
     }
 
     /// Get default relays for current [`Network`]
     pub fn default_relays(&self) -> Vec<String> {
-        match self.network {
-            Network::Bitcoin => MAINNET_RELAYS.into_iter().map(|r| r.to_string()).collect(),
-            _ => TESTNET_RELAYS.into_iter().map(|r| r.to_string()).collect(),
-        }
+        self.config
+            .profile
+            .relays
+            .iter()
+            .map(|r| r.to_string())
+            .collect()
     }
 
     async fn load_nostr_connect_relays(&self) -> Result<(), Error> {
@@ -497,8 +1027,11 @@ impl SmartVaults {
     #[tracing::instrument(skip_all, level = "trace")]
     async fn restore_relays(&self) -> Result<(), Error> {
         let relays = self.db.get_relays(true).await?;
-        for (url, proxy) in relays.into_iter() {
-            let opts = RelayOptions::new().proxy(proxy);
+        for (url, proxy, role) in relays.into_iter() {
+            let opts = RelayOptions::new()
+                .proxy(self.relay_proxy(&url, proxy).await)
+                .read(role.read)
+                .write(role.write);
             self.client.add_relay_with_opts(url, opts).await?;
         }
 
@@ -506,7 +1039,9 @@ impl SmartVaults {
             for url in self.default_relays().into_iter() {
                 let url = Url::parse(&url)?;
                 self.client.add_relay(&url).await?;
-                self.db.insert_relay(url.clone(), None).await?;
+                self.db
+                    .insert_relay(url.clone(), None, RelayRole::default())
+                    .await?;
                 self.db.enable_relay(url).await?;
             }
         }
@@ -576,7 +1111,10 @@ impl SmartVaults {
 
     pub async fn shutdown(self) -> Result<(), Error> {
         self.manager.unload_policies().await;
-        Ok(self.client.shutdown().await?)
+        self.client.shutdown().await?;
+        // The syncing threads may still hold a clone of the client
+        self.lock.release();
+        Ok(())
     }
 
     /// Get config
@@ -599,16 +1137,90 @@ impl SmartVaults {
         Ok(self.config.electrum_endpoint().await?)
     }
 
+    /// Set the electrum endpoints, in failover order
+    pub async fn set_electrum_endpoints<I, S>(&self, endpoints: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.config.set_electrum_endpoints(endpoints).await?;
+        self.config.save().await?;
+        Ok(())
+    }
+
+    /// Get the electrum endpoint currently in use
+    pub async fn active_electrum_endpoint(&self) -> Result<ElectrumEndpoint, Error> {
+        Ok(self.config.active_electrum_endpoint().await?)
+    }
+
     pub fn block_height(&self) -> u32 {
         self.manager.block_height()
     }
 
+    /// Publish the profile metadata
+    ///
+    /// The published version is saved in the profile history (see [`SmartVaults::revert_profile`]).
     pub async fn set_metadata(&self, metadata: &Metadata) -> Result<(), Error> {
-        let builder = EventBuilder::metadata(metadata);
-        self.client.send_event_builder(builder).await?;
+        let event: Event = EventBuilder::metadata(metadata).to_event(self.keys())?;
+        let timestamp: Timestamp = event.created_at;
+        let event_id: EventId = self.client.send_event(event).await?;
+        self.db
+            .save_profile_version(event_id, metadata.clone(), timestamp)
+            .await?;
         Ok(())
     }
 
+    /// Get the profile metadata versions published from this device (newest first)
+    pub async fn get_profile_history(&self) -> Result<Vec<ProfileVersion>, Error> {
+        Ok(self.db.get_profile_history().await?)
+    }
+
+    /// Publish again an older version of the profile metadata
+    ///
+    /// The republished metadata is saved as a new version.
+    pub async fn revert_profile(&self, version: u64) -> Result<(), Error> {
+        let ProfileVersion { metadata, .. } = self.db.get_profile_version(version).await?;
+        self.set_metadata(&metadata).await
+    }
+
+    /// Get the releases not seen yet (see [`changelog`])
+    pub async fn whats_new(&self) -> Result<Vec<&'static Release>, Error> {
+        let last_seen: Option<String> = self.db.get_last_seen_version().await?;
+        Ok(changelog::releases_since(
+            last_seen.as_deref(),
+            env!("CARGO_PKG_VERSION"),
+        ))
+    }
+
+    /// Mark the changelog of the current version as seen
+    pub async fn mark_whats_new_seen(&self) -> Result<(), Error> {
+        Ok(self
+            .db
+            .set_last_seen_version(env!("CARGO_PKG_VERSION"))
+            .await?)
+    }
+
+    /// Check if the value-moving and destructive actions require an extra confirmation
+    ///
+    /// Enabled by default on mainnet, always disabled on the other networks.
+    pub async fn mainnet_guard(&self) -> Result<bool, Error> {
+        if self.network != Network::Bitcoin {
+            return Ok(false);
+        }
+        Ok(self.db.get_mainnet_guard().await?.unwrap_or(true))
+    }
+
+    /// Enable or disable the mainnet guard for this keychain
+    pub async fn set_mainnet_guard(&self, enabled: bool) -> Result<(), Error> {
+        Ok(self.db.set_mainnet_guard(enabled).await?)
+    }
+
+    /// Compare `metadata` with the currently published profile
+    pub async fn profile_diff(&self, metadata: &Metadata) -> Result<Vec<MetadataChange>, Error> {
+        let current: Metadata = self.get_profile().await?.metadata();
+        Ok(MetadataChange::diff(&current, metadata))
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_profile(&self) -> Result<Profile, Error> {
         let public_key: PublicKey = self.keys().public_key();
@@ -644,18 +1256,100 @@ impl SmartVaults {
         Ok(self.client.database().contacts(keys.public_key()).await?)
     }
 
-    pub async fn add_contact(&self, public_key: PublicKey) -> Result<(), Error> {
-        let keys: &Keys = self.keys();
-        if public_key != keys.public_key() {
-            // Add contact
-            let mut contacts: Vec<Contact> = self
-                .client
-                .database()
-                .contacts_public_keys(keys.public_key())
-                .await?
-                .into_iter()
-                .map(|p| Contact::new::<String>(p, None, None))
-                .collect();
+    /// Get contacts with the metadata cached in the local database
+    ///
+    /// Never wait for relays: missing or outdated metadata (see [`GetContact::is_stale`]) is
+    /// requested in background and notified with [`EventHandled::Metadata`],
+    /// unless the light sync profile is active.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_contacts_with_metadata(&self) -> Result<BTreeSet<GetContact>, Error> {
+        let database = self.client.database();
+        let public_keys: Vec<PublicKey> = database
+            .contacts_public_keys(self.keys().public_key())
+            .await?
+            .into_iter()
+            .collect();
+
+        if public_keys.is_empty() {
+            return Ok(BTreeSet::new());
+        }
+
+        // Latest metadata event of each contact
+        let filter: Filter = Filter::new()
+            .authors(public_keys.iter().copied())
+            .kind(Kind::Metadata);
+        let mut cached: HashMap<PublicKey, (Metadata, Timestamp)> = HashMap::new();
+        for event in database.query(vec![filter], Order::Desc).await?.into_iter() {
+            if let HashMapEntry::Vacant(e) = cached.entry(event.author()) {
+                let metadata: Metadata = Metadata::from_json(event.content()).unwrap_or_default();
+                e.insert((metadata, event.created_at));
+            }
+        }
+
+        // Roles
+        let key_agents: HashSet<PublicKey> = self
+            .declared_key_agents(public_keys.iter().copied())
+            .await?;
+        let verified_key_agents = self.storage.verified_key_agents().await;
+
+        let contacts: BTreeSet<GetContact> = public_keys
+            .into_iter()
+            .map(|public_key| {
+                let (metadata, updated_at) = match cached.remove(&public_key) {
+                    Some((metadata, updated_at)) => (metadata, Some(updated_at)),
+                    None => (Metadata::default(), None),
+                };
+                let role: UserRole = if key_agents.contains(&public_key) {
+                    UserRole::KeyAgent
+                } else {
+                    UserRole::User
+                };
+                GetContact {
+                    profile: Profile::new(public_key, metadata),
+                    updated_at,
+                    role,
+                    verified: verified_key_agents.is_verified(&public_key),
+                }
+            })
+            .collect();
+
+        // Refresh stale metadata in background (skipped by the light sync profile)
+        let stale: Vec<PublicKey> = contacts
+            .iter()
+            .filter(|c| c.is_stale())
+            .map(|c| c.profile.public_key())
+            .collect();
+        if !stale.is_empty() && self.config.sync_profile().await == SyncProfile::Full {
+            let client = self.client.clone();
+            thread::spawn(async move {
+                tracing::debug!("Refreshing metadata of {} contacts", stale.len());
+                client
+                    .subscribe(
+                        vec![Filter::new().authors(stale).kind(Kind::Metadata)],
+                        Some(
+                            SubscribeAutoCloseOptions::default()
+                                .timeout(Some(Duration::from_secs(10))),
+                        ),
+                    )
+                    .await;
+            })?;
+        }
+
+        Ok(contacts)
+    }
+
+    pub async fn add_contact(&self, public_key: PublicKey) -> Result<(), Error> {
+        let keys: &Keys = self.keys();
+        if public_key != keys.public_key() {
+            // Add contact
+            let mut contacts: Vec<Contact> = self
+                .client
+                .database()
+                .contacts_public_keys(keys.public_key())
+                .await?
+                .into_iter()
+                .map(|p| Contact::new::<String>(p, None, None))
+                .collect();
             contacts.push(Contact::new::<String>(public_key, None, None));
             let event = EventBuilder::contact_list(contacts);
             self.client.send_event_builder(event).await?;
@@ -703,6 +1397,110 @@ impl SmartVaults {
         })
     }
 
+    /// Get the origin of every key of a policy, with the signer (mine or shared with me) behind it
+    ///
+    /// Keys without origin info can't be matched to any signer: see [`KeyOriginInfo::is_unverifiable`](smartvaults_core::policy::KeyOriginInfo::is_unverifiable).
+    pub async fn get_policy_key_report(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Vec<PolicyKeyReport>, Error> {
+        let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+        let signers = self.storage.signers().await;
+        let shared_signers = self.storage.shared_signers().await;
+        let registrations: Vec<HwRegistration> = self
+            .db
+            .get_hw_registrations()
+            .await?
+            .into_iter()
+            .filter(|r| r.policy_id == policy_id)
+            .collect();
+
+        let mut report: Vec<PolicyKeyReport> = Vec::new();
+        for origin in policy.key_origins(self.network)?.into_iter() {
+            let fingerprint: Option<Fingerprint> = origin.fingerprint;
+            let key = origin.key.clone();
+            // Purpose of the signer key, if it's exactly the key of the policy
+            let matched_purpose = |signer_key: Option<DescriptorPublicKey>| match signer_key {
+                Some(signer_key) if signer_key == key => KeyPurpose::from_key(&signer_key),
+                _ => None,
+            };
+            // Prefer the signer with the same key (the same device can have more accounts)
+            let my_signer = signers
+                .iter()
+                .find(|(_, signer)| matched_purpose(signer.descriptor_public_key().ok()).is_some())
+                .or_else(|| {
+                    signers
+                        .iter()
+                        .find(|(_, signer)| Some(signer.fingerprint()) == fingerprint)
+                });
+            let entry: PolicyKeyReport = match my_signer {
+                Some((signer_id, signer)) => {
+                    let mut device: String = signer.signer_type().to_string();
+                    let devices: Vec<&str> = registrations
+                        .iter()
+                        .filter(|r| Some(r.fingerprint) == fingerprint)
+                        .map(|r| r.device_id.as_str())
+                        .collect();
+                    if !devices.is_empty() {
+                        device.push_str(&format!(" ({})", devices.join(", ")));
+                    }
+                    PolicyKeyReport {
+                        matched_purpose: matched_purpose(signer.descriptor_public_key().ok()),
+                        origin,
+                        signer_id: Some(*signer_id),
+                        ours: true,
+                        owner: Some(signer.name()),
+                        device: Some(device),
+                    }
+                }
+                None => {
+                    let shared_signer = shared_signers
+                        .iter()
+                        .find(|(_, internal)| {
+                            matched_purpose(internal.shared_signer.descriptor_public_key().ok())
+                                .is_some()
+                        })
+                        .or_else(|| {
+                            shared_signers.iter().find(|(_, internal)| {
+                                Some(internal.shared_signer.fingerprint()) == fingerprint
+                            })
+                        });
+                    match shared_signer {
+                        Some((shared_signer_id, internal)) => {
+                            let owner: String = self
+                                .client
+                                .database()
+                                .profile(internal.owner_public_key)
+                                .await?
+                                .name();
+                            PolicyKeyReport {
+                                matched_purpose: matched_purpose(
+                                    internal.shared_signer.descriptor_public_key().ok(),
+                                ),
+                                origin,
+                                signer_id: Some(*shared_signer_id),
+                                ours: false,
+                                owner: Some(owner),
+                                device: None,
+                            }
+                        }
+                        None => PolicyKeyReport {
+                            origin,
+                            signer_id: None,
+                            ours: false,
+                            owner: None,
+                            device: None,
+                            matched_purpose: None,
+                        },
+                    }
+                }
+            };
+            report.push(entry);
+        }
+
+        Ok(report)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_proposal_by_id(&self, proposal_id: EventId) -> Result<GetProposal, Error> {
         let InternalProposal {
@@ -723,9 +1521,89 @@ impl SmartVaults {
             signed: proposal.finalize(approvals, self.network).is_ok(),
             proposal,
             timestamp,
+            fee_warning: self.storage.fee_warning(&proposal_id).await,
         })
     }
 
+    /// Export the unsigned PSBT of a pending proposal (i.e. for airgapped signing)
+    ///
+    /// Work for both spending and proof of reserve proposals.
+    pub async fn export_proposal_psbt(
+        &self,
+        proposal_id: EventId,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        let InternalProposal { proposal, .. } = self.storage.proposal(&proposal_id).await?;
+        Ok(proposal.psbt())
+    }
+
+    /// Save the unsigned PSBT of a pending proposal to file, as base64 or, if `binary` is `true`, as raw bytes
+    ///
+    /// The policy descriptors are saved alongside (same path with `descriptor` extension),
+    /// so the signing device can register the policy. No nostr key or shared key is exported.
+    ///
+    /// Return the path of the descriptor file.
+    pub async fn save_proposal_psbt<P>(
+        &self,
+        proposal_id: EventId,
+        path: P,
+        binary: bool,
+    ) -> Result<PathBuf, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path: &Path = path.as_ref();
+        let InternalProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.storage.proposal(&proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+
+        let psbt: PartiallySignedTransaction = proposal.psbt();
+        if binary {
+            std::fs::write(path, psbt.serialize())?;
+        } else {
+            std::fs::write(path, psbt.to_string())?;
+        }
+
+        let mut descriptors: String = format!("{}\n", policy.descriptor());
+        if let Some(change_descriptor) = policy.change_descriptor() {
+            descriptors.push_str(&format!("{change_descriptor}\n"));
+        }
+        let descriptor_path: PathBuf = path.with_extension("descriptor");
+        std::fs::write(&descriptor_path, descriptors)?;
+
+        Ok(descriptor_path)
+    }
+
+    /// Export the unsigned PSBT of a pending proposal as `crypto-psbt` UR parts (animated QR code)
+    ///
+    /// See [`util::ur::DEFAULT_MAX_FRAGMENT_LEN`].
+    pub async fn export_proposal_ur(
+        &self,
+        proposal_id: EventId,
+        max_fragment_len: usize,
+    ) -> Result<Vec<String>, Error> {
+        let psbt: PartiallySignedTransaction = self.export_proposal_psbt(proposal_id).await?;
+        Ok(util::ur::encode_psbt(&psbt, max_fragment_len)?)
+    }
+
+    /// Approve proposal with the signed PSBT scanned from an animated QR code (`crypto-psbt` UR parts)
+    ///
+    /// See [`SmartVaults::approve_with_signed_psbt`].
+    pub async fn approve_with_ur<S>(
+        &self,
+        proposal_id: EventId,
+        parts: Vec<S>,
+    ) -> Result<(EventId, ApprovedProposal), Error>
+    where
+        S: AsRef<str>,
+    {
+        let signed_psbt: PartiallySignedTransaction = util::ur::decode_psbt(&parts)?;
+        self.approve_with_signed_psbt(proposal_id, signed_psbt)
+            .await
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_completed_proposal_by_id(
         &self,
@@ -775,6 +1653,8 @@ impl SmartVaults {
             // Unload policy
             self.manager.unload_policy(policy_id).await?;
 
+            self.request_subscriptions_refresh();
+
             Ok(())
         } else {
             Err(Error::TryingToDeleteNotOwnedEvent)
@@ -828,11 +1708,11 @@ impl SmartVaults {
         }
     }
 
-    pub async fn delete_completed_proposal_by_id(
+    /// Get the local data depending on a completed proposal (see [`DeletionImpact`])
+    pub async fn completed_proposal_deletion_impact(
         &self,
         completed_proposal_id: EventId,
-    ) -> Result<(), Error> {
-        // Get the completed proposal
+    ) -> Result<DeletionImpact, Error> {
         let proposal_event = self
             .client
             .database()
@@ -842,13 +1722,65 @@ impl SmartVaults {
             return Err(Error::ProposalNotFound);
         }
 
-        let policy_id: &EventId = proposal_event
-            .event_ids()
-            .nth(1)
-            .ok_or(Error::PolicyNotFound)?;
+        let mut event_ids = proposal_event.event_ids();
+        let proposal_id: Option<EventId> = event_ids.next().copied();
+        let policy_id: EventId = event_ids.next().copied().ok_or(Error::PolicyNotFound)?;
+
+        let InternalCompletedProposal { proposal, .. } = self
+            .storage
+            .completed_proposal(&completed_proposal_id)
+            .await?;
+        let txid: Option<Txid> = proposal.tx().map(|tx| tx.txid());
+
+        let labels: BTreeMap<String, Label> = match txid {
+            Some(txid) => self.storage.get_labels_by_txid(policy_id, txid).await,
+            None => BTreeMap::new(),
+        };
+
+        let audit_entries: usize = match proposal_id {
+            Some(proposal_id) => {
+                self.db
+                    .count_signing_audit_by_object(proposal_id.to_hex())
+                    .await?
+            }
+            None => 0,
+        };
+
+        Ok(DeletionImpact {
+            completed_proposal_id,
+            policy_id,
+            txid,
+            labels,
+            superseded_links: self
+                .db
+                .count_superseded_links(completed_proposal_id)
+                .await?,
+            audit_entries,
+        })
+    }
+
+    /// Delete completed proposal
+    ///
+    /// If `delete_dependents` is `true`, the labels targeting its txid are deleted in the same
+    /// deletion event and the local fee bump links are removed.
+    /// Return the [`DeletionImpact`] computed before the deletion.
+    pub async fn delete_completed_proposal_by_id(
+        &self,
+        completed_proposal_id: EventId,
+        delete_dependents: bool,
+    ) -> Result<DeletionImpact, Error> {
+        let impact: DeletionImpact = self
+            .completed_proposal_deletion_impact(completed_proposal_id)
+            .await?;
 
         // Get shared key
-        let shared_key: Keys = self.storage.shared_key(policy_id).await?;
+        let shared_key: Keys = self.storage.shared_key(&impact.policy_id).await?;
+
+        let proposal_event = self
+            .client
+            .database()
+            .event_by_id(completed_proposal_id)
+            .await?;
 
         if proposal_event.author() == shared_key.public_key() {
             // Extract `p` tags from proposal event to notify users about proposal deletion
@@ -860,6 +1792,15 @@ impl SmartVaults {
 
             tags.push(Tag::event(completed_proposal_id));
 
+            if delete_dependents {
+                for identifier in impact.labels.keys() {
+                    let coordinate: Coordinate =
+                        Coordinate::new(LABELS_KIND, shared_key.public_key())
+                            .identifier(identifier);
+                    tags.push(Tag::from(coordinate));
+                }
+            }
+
             let event = EventBuilder::new(Kind::EventDeletion, "", tags).to_event(&shared_key)?;
             self.client.send_event(event).await?;
 
@@ -867,12 +1808,42 @@ impl SmartVaults {
                 .delete_completed_proposal(&completed_proposal_id)
                 .await;
 
-            Ok(())
+            if delete_dependents {
+                for identifier in impact.labels.keys() {
+                    self.storage.delete_label(identifier).await;
+                }
+
+                self.db
+                    .delete_superseded_links(HashSet::from([completed_proposal_id]))
+                    .await?;
+            }
+
+            Ok(impact)
         } else {
             Err(Error::TryingToDeleteNotOwnedEvent)
         }
     }
 
+    /// Remove the local rows referencing deleted proposals or completed proposals
+    pub(crate) async fn prune_orphans(&self) -> Result<(), Error> {
+        let database = self.client.database();
+        let mut orphans: HashSet<EventId> = HashSet::new();
+        for (proposal_id, replaced_by) in self.db.get_superseded().await?.into_iter() {
+            for event_id in [proposal_id, replaced_by] {
+                if database.has_event_id_been_deleted(&event_id).await? {
+                    orphans.insert(event_id);
+                }
+            }
+        }
+
+        if !orphans.is_empty() {
+            let deleted: usize = self.db.delete_superseded_links(orphans).await?;
+            tracing::info!("Pruned {deleted} orphaned superseded links");
+        }
+
+        Ok(())
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_policies(&self) -> Result<Vec<GetPolicy>, Error> {
         let items = self.storage.vaults().await;
@@ -910,12 +1881,55 @@ impl SmartVaults {
                 signed: p.proposal.finalize(approvals, self.network).is_ok(),
                 proposal: p.proposal,
                 timestamp: p.timestamp,
+                fee_warning: self.storage.fee_warning(&proposal_id).await,
             });
         }
         list.sort();
         Ok(list)
     }
 
+    /// Get a page of proposals
+    ///
+    /// The proposals are sorted and sliced before computing the signing status,
+    /// so only the requested page is processed.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_proposals_paginated(
+        &self,
+        opts: Pagination,
+    ) -> Result<Page<GetProposal>, Error> {
+        let proposals: Vec<(InternalProposal, EventId)> = self
+            .storage
+            .proposals()
+            .await
+            .into_iter()
+            .filter(|(_, p)| opts.match_policy(&p.policy_id))
+            .map(|(id, p)| (p, id))
+            .collect();
+        let page: Page<(InternalProposal, EventId)> = opts.apply(proposals);
+
+        let approvals = self.storage.approvals().await;
+        let mut items: Vec<GetProposal> = Vec::with_capacity(page.items.len());
+        for (p, proposal_id) in page.items.into_iter() {
+            let approvals = approvals
+                .values()
+                .filter(|a| a.proposal_id == proposal_id)
+                .map(|a| a.approval.clone());
+            items.push(GetProposal {
+                proposal_id,
+                policy_id: p.policy_id,
+                signed: p.proposal.finalize(approvals, self.network).is_ok(),
+                proposal: p.proposal,
+                timestamp: p.timestamp,
+                fee_warning: self.storage.fee_warning(&proposal_id).await,
+            });
+        }
+
+        Ok(Page {
+            items,
+            total: page.total,
+        })
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_proposals_by_policy_id(
         &self,
@@ -940,6 +1954,7 @@ impl SmartVaults {
                 signed: p.proposal.finalize(approvals, self.network).is_ok(),
                 proposal: p.proposal,
                 timestamp: p.timestamp,
+                fee_warning: self.storage.fee_warning(&proposal_id).await,
             });
         }
         list.sort();
@@ -965,12 +1980,12 @@ impl SmartVaults {
             .into_iter()
             .filter(|(_, a)| a.proposal_id == proposal_id)
         {
-            list.push(GetApproval {
+            list.push(GetApproval::new(
                 approval_id,
-                user: self.client.database().profile(public_key).await?,
-                approved_proposal: approval,
+                self.client.database().profile(public_key).await?,
+                approval,
                 timestamp,
-            });
+            ));
         }
         list.sort();
         Ok(list)
@@ -994,6 +2009,29 @@ impl SmartVaults {
         Ok(list)
     }
 
+    /// Get a page of completed proposals
+    pub async fn get_completed_proposals_paginated(
+        &self,
+        opts: Pagination,
+    ) -> Result<Page<GetCompletedProposal>, Error> {
+        let completed_proposals: Vec<(InternalCompletedProposal, EventId)> = self
+            .storage
+            .completed_proposals()
+            .await
+            .into_iter()
+            .filter(|(_, p)| opts.match_policy(&p.policy_id))
+            .map(|(id, p)| (p, id))
+            .collect();
+        Ok(opts
+            .apply(completed_proposals)
+            .map(|(p, id)| GetCompletedProposal {
+                policy_id: p.policy_id,
+                completed_proposal_id: id,
+                proposal: p.proposal,
+                timestamp: p.timestamp,
+            }))
+    }
+
     pub async fn get_members_of_policy(&self, policy_id: EventId) -> Result<Vec<Profile>, Error> {
         let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
         let mut users = Vec::with_capacity(public_keys.len());
@@ -1005,6 +2043,9 @@ impl SmartVaults {
         Ok(users)
     }
 
+    /// Save a new policy
+    ///
+    /// The change descriptor is derived from the external one (`/0/*` -> `/1/*`), when possible.
     pub async fn save_policy<S>(
         &self,
         name: S,
@@ -1015,32 +2056,148 @@ impl SmartVaults {
     where
         S: AsRef<str>,
     {
+        self.save_policy_with_limits(name, description, descriptor, nostr_pubkeys, true)
+            .await
+    }
+
+    /// Save a new policy, optionally without enforcing the complexity limits
+    ///
+    /// The exceeded limits are logged as warnings (see [`Policy::complexity`]).
+    pub async fn save_policy_with_limits<S>(
+        &self,
+        name: S,
+        description: S,
+        descriptor: S,
+        nostr_pubkeys: Vec<PublicKey>,
+        enforce_limits: bool,
+    ) -> Result<EventId, Error>
+    where
+        S: AsRef<str>,
+    {
+        util::network::check_descriptor_str_network(descriptor.as_ref(), self.network)?;
+        let policy = Policy::from_desc_or_policy_with_limits(
+            name,
+            description,
+            descriptor,
+            self.network,
+            enforce_limits,
+        )?
+        .with_derived_change_descriptor()?;
+        for LimitExceeded { metric, value, max } in policy.complexity().exceeded().into_iter() {
+            tracing::warn!("Policy complexity limit overridden: {value} {metric} (max {max})");
+        }
+        self.publish_policy(policy, nostr_pubkeys).await
+    }
+
+    /// Save a policy with an explicit change descriptor
+    ///
+    /// If `change_descriptor` is `None`, change outputs are derived from the external descriptor
+    /// (i.e. to restore policies created before the separate change descriptor support).
+    pub async fn save_policy_with_change_descriptor<S>(
+        &self,
+        name: S,
+        description: S,
+        descriptor: S,
+        change_descriptor: Option<S>,
+        nostr_pubkeys: Vec<PublicKey>,
+    ) -> Result<EventId, Error>
+    where
+        S: AsRef<str>,
+    {
+        util::network::check_descriptor_str_network(descriptor.as_ref(), self.network)?;
+        let mut policy = Policy::from_desc_or_policy(name, description, descriptor, self.network)?;
+        if let Some(change_descriptor) = change_descriptor {
+            util::network::check_descriptor_str_network(change_descriptor.as_ref(), self.network)?;
+            let change_descriptor = Descriptor::from_str(change_descriptor.as_ref())
+                .map_err(smartvaults_core::policy::Error::from)?;
+            policy = policy.with_change_descriptor(change_descriptor)?;
+        }
+        self.publish_policy(policy, nostr_pubkeys).await
+    }
+
+    /// Check the public keys of a new policy before saving it
+    ///
+    /// Error if a public key (i.e. my own) is included more than once.
+    /// The metadata not available in the local database is requested to relays (see [`SmartVaults::get_public_key_metadata`]),
+    /// so the names can be displayed for confirmation.
+    pub async fn check_policy_participants(
+        &self,
+        nostr_pubkeys: &[PublicKey],
+    ) -> Result<Vec<PolicyParticipant>, Error> {
+        if let Some(public_key) = util::find_duplicated_public_key(nostr_pubkeys) {
+            return Err(Error::DuplicatedPublicKey(public_key));
+        }
+
+        let my_public_key: PublicKey = self.keys().public_key();
+        let contacts: HashSet<PublicKey> = self
+            .client
+            .database()
+            .contacts_public_keys(my_public_key)
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut participants: Vec<PolicyParticipant> = Vec::with_capacity(nostr_pubkeys.len());
+        for public_key in nostr_pubkeys.iter().copied() {
+            let metadata: Metadata = self.get_public_key_metadata(public_key).await?;
+            participants.push(PolicyParticipant {
+                user: Profile::new(public_key, metadata),
+                is_me: public_key == my_public_key,
+                is_contact: contacts.contains(&public_key),
+            });
+        }
+        Ok(participants)
+    }
+
+    async fn publish_policy(
+        &self,
+        policy: Policy,
+        nostr_pubkeys: Vec<PublicKey>,
+    ) -> Result<EventId, Error> {
         if nostr_pubkeys.is_empty() {
             return Err(Error::NotEnoughPublicKeys);
         }
 
+        util::network::check_descriptor_network(policy.as_descriptor(), self.network)?;
+        if let Some(change_descriptor) = policy.as_change_descriptor() {
+            util::network::check_descriptor_network(change_descriptor, self.network)?;
+        }
+
+        // A duplicated public key would receive the shared key twice
+        if let Some(public_key) = util::find_duplicated_public_key(&nostr_pubkeys) {
+            return Err(Error::DuplicatedPublicKey(public_key));
+        }
+
+        // The scope of shared signers is advisory: only warn
+        for GetSharedSigner {
+            shared_signer_id, ..
+        } in self.shared_signers_out_of_scope(&policy).await?.into_iter()
+        {
+            tracing::warn!("Shared signer {shared_signer_id} used outside of its scope");
+        }
+
         // Generate a shared key
         let shared_key = Keys::generate();
-        let policy = Policy::from_desc_or_policy(name, description, descriptor, self.network)?;
 
         // Compose the event
         // Publish it with `shared_key` so every owner can delete it
         let policy_event: Event = EventBuilder::policy(&shared_key, &policy, &nostr_pubkeys)?;
         let policy_id = policy_event.id;
+        let created_at: Timestamp = policy_event.created_at;
 
-        // Publish the shared key
+        // Publish the shared keys and the policy
+        let mut shared_keys: Vec<(Event, PublicKey)> = Vec::with_capacity(nostr_pubkeys.len());
         for pubkey in nostr_pubkeys.iter() {
             let event: Event =
                 EventBuilder::shared_key(self.keys(), &shared_key, pubkey, policy_id)?;
-            let event_id: EventId = event.id;
-
-            // TODO: use send_batch_event method from nostr-sdk
-            self.client.send_event(event).await?;
-            tracing::info!("Published shared key for {pubkey} at event {event_id}");
+            shared_keys.push((event, *pubkey));
         }
-
-        // Publish the event
-        self.client.send_event(policy_event).await?;
+        let mut events: Vec<Event> = shared_keys.iter().map(|(e, _)| e.clone()).collect();
+        events.push(policy_event);
+        let report: BatchSendReport = self.publish_events(events).await?;
+        tracing::info!("Policy {policy_id} published: {report}");
+        self.ensure_relay_acks(&report, policy_id).await?;
+        self.deliver_to_recipients(&report, shared_keys).await?;
 
         // Index event
         self.storage.save_shared_key(policy_id, shared_key).await;
@@ -1055,30 +2212,66 @@ impl SmartVaults {
             .await;
 
         // Load policy
-        self.manager.load_policy(policy_id, policy).await?;
+        self.manager
+            .load_policy(
+                policy_id,
+                policy,
+                Some(created_at),
+                Some(self.sync_channel.clone()),
+            )
+            .await?;
+
+        // Subscribe to the events of the new shared key
+        self.request_subscriptions_refresh();
 
         Ok(policy_id)
     }
 
-    pub async fn save_policy_from_template<S>(
+    /// Compile a [`PolicyTemplate`], without publishing it
+    ///
+    /// Useful to preview the descriptor before [`SmartVaults::save_policy_from_template`].
+    /// Return [`Error::KeyPurposeMismatch`] if a key was derived for another script type (see
+    /// [`Signer::descriptor_public_key_for`] to pick the right key of a signer).
+    pub fn policy_from_template<S>(
         &self,
         name: S,
         description: S,
         template: PolicyTemplate,
-        nostr_pubkeys: Vec<PublicKey>,
-    ) -> Result<EventId, Error>
+    ) -> Result<Policy, Error>
     where
         S: Into<String>,
     {
-        let policy: Policy = Policy::from_template(name, description, template, self.network)?;
-        self.save_policy(
-            policy.name(),
-            policy.description(),
-            policy.as_descriptor().to_string(),
-            nostr_pubkeys,
-        )
-        .await
-    }
+        for key in template.keys().into_iter() {
+            util::network::check_key_network(key, self.network)?;
+            if let Some(purpose) = KeyPurpose::from_key(key) {
+                if !ScriptType::Tr.matches_purpose(&purpose) {
+                    return Err(Error::KeyPurposeMismatch {
+                        key: key.to_string(),
+                        purpose,
+                        script_type: ScriptType::Tr,
+                    });
+                }
+            }
+        }
+        Ok(
+            Policy::from_template(name, description, template, self.network)?
+                .with_derived_change_descriptor()?,
+        )
+    }
+
+    pub async fn save_policy_from_template<S>(
+        &self,
+        name: S,
+        description: S,
+        template: PolicyTemplate,
+        nostr_pubkeys: Vec<PublicKey>,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let policy: Policy = self.policy_from_template(name, description, template)?;
+        self.publish_policy(policy, nostr_pubkeys).await
+    }
 
     pub async fn estimate_tx_vsize(
         &self,
@@ -1088,6 +2281,7 @@ impl SmartVaults {
         utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
         skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
     ) -> Result<Option<usize>, Error> {
         let mut frozen_utxos: Option<Vec<OutPoint>> = None;
         if !skip_frozen_utxos {
@@ -1103,60 +2297,160 @@ impl SmartVaults {
             );
         }
 
+        let coin_selection: CoinSelection = self
+            .resolve_coin_selection(policy_id, coin_selection)
+            .await?;
+
         Ok(self
             .manager
-            .estimate_tx_vsize(policy_id, address, amount, utxos, frozen_utxos, policy_path)
+            .estimate_tx_vsize(
+                policy_id,
+                address,
+                amount,
+                utxos,
+                frozen_utxos,
+                policy_path,
+                coin_selection,
+            )
             .await?)
     }
 
-    /// Make a spending proposal
-    pub async fn spend<S>(
+    /// Replace the [`ScreeningProvider`]
+    pub fn set_screening_provider<T>(&self, provider: T)
+    where
+        T: ScreeningProvider + 'static,
+    {
+        let mut screening = self.screening.write();
+        *screening = Arc::new(provider);
+    }
+
+    /// Screen an address against the configured [`ScreeningProvider`]
+    pub fn screen_address(&self, address: &Address) -> ScreeningResult {
+        self.screen_script(&address.script_pubkey())
+    }
+
+    fn screen_script(&self, script: &Script) -> ScreeningResult {
+        let provider = self.screening.read().clone();
+        provider.screen(script)
+    }
+
+    /// Screen the proposal outputs that not pay back to the vault
+    pub async fn screen_proposal(
+        &self,
+        proposal_id: EventId,
+    ) -> Result<Vec<(ScriptBuf, ScreeningResult)>, Error> {
+        Ok(self
+            .verify_proposal_outputs(proposal_id)
+            .await?
+            .into_iter()
+            .filter(|o| o.kind != OutputKind::Change)
+            .map(|o| {
+                let result = self.screen_script(&o.script_pubkey);
+                (o.script_pubkey, result)
+            })
+            .collect())
+    }
+
+    /// Check and convert [`FeeRate`] to BDK fee rate
+    async fn calculate_fee_rate(&self, fee_rate: FeeRate) -> Result<BdkFeeRate, Error> {
+        if !fee_rate.is_valid() {
+            return Err(Error::InvalidFeeRate);
+        }
+
+        Ok(match fee_rate {
+            FeeRate::Priority(Priority::Custom(target_blocks)) => {
+                let btc_per_kvb: f32 = match self.config.backend().await {
+                    Backend::Electrum => {
+                        let blockchain = self.blockchain().await?;
+                        blockchain.estimate_fee(target_blocks as usize)? as f32
+                    }
+                    Backend::BitcoinCore { url, auth } => {
+                        let client = manager::bitcoind_client(&url, auth)?;
+                        let res = client.estimate_smart_fee(target_blocks as u16, None)?;
+                        let rate = res.fee_rate.ok_or(Error::FeeEstimationUnavailable)?;
+                        rate.to_btc() as f32
+                    }
+                };
+                BdkFeeRate::from_btc_per_kvb(btc_per_kvb)
+            }
+            FeeRate::Priority(priority) => {
+                let target_blocks: u8 = self.config.target_blocks(priority).await;
+                let estimates: FeeEstimates = self.estimate_fee_rates().await?;
+                BdkFeeRate::from_sat_per_vb(estimates.fee_rate(target_blocks))
+            }
+            FeeRate::Rate(rate) => BdkFeeRate::from_sat_per_vb(rate),
+        })
+    }
+
+    /// Build spending proposal (without publishing it)
+    async fn build_spending_proposal(
         &self,
         policy_id: EventId,
         address: Address<NetworkUnchecked>,
         amount: Amount,
-        description: S,
+        description: &str,
         fee_rate: FeeRate,
         utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
         skip_frozen_utxos: bool,
-    ) -> Result<GetProposal, Error>
-    where
-        S: Into<String>,
-    {
-        let description: &str = &description.into();
+        coin_selection: Option<CoinSelection>,
+    ) -> Result<Proposal, Error> {
+        // Screen recipient
+        let recipient: Address = util::network::check_address_network(&address, self.network)?;
+        let result: ScreeningResult = self.screen_address(&recipient);
+        if result.is_flagged() {
+            return Err(Error::ScreeningFlagged {
+                address: recipient.to_string(),
+                result,
+            });
+        }
 
         // Check and calculate fee rate
-        if !fee_rate.is_valid() {
-            return Err(Error::InvalidFeeRate);
-        }
+        let fee_rate: BdkFeeRate = self.calculate_fee_rate(fee_rate).await?;
 
-        let fee_rate: BdkFeeRate = match fee_rate {
-            FeeRate::Priority(priority) => {
-                let blockchain = self.blockchain().await?;
-                let btc_per_kvb: f32 =
-                    blockchain.estimate_fee(priority.target_blocks() as usize)? as f32;
-                BdkFeeRate::from_btc_per_kvb(btc_per_kvb)
-            }
-            FeeRate::Rate(rate) => BdkFeeRate::from_sat_per_vb(rate),
-        };
+        let policy_utxos: HashSet<OutPoint> = self
+            .manager
+            .get_utxos(policy_id)
+            .await?
+            .into_iter()
+            .map(|utxo| utxo.outpoint)
+            .collect();
 
         let mut frozen_utxos: Option<Vec<OutPoint>> = None;
         if !skip_frozen_utxos {
             let set: HashSet<OutPoint> = self.storage.get_frozen_utxos(&policy_id).await;
-            frozen_utxos = Some(
-                self.manager
-                    .get_utxos(policy_id)
-                    .await?
-                    .into_iter()
-                    .filter(|utxo| set.contains(&utxo.outpoint))
-                    .map(|utxo| utxo.outpoint)
-                    .collect(),
-            );
+            frozen_utxos = Some(policy_utxos.intersection(&set).copied().collect());
+        }
+
+        // Check manually selected UTXOs before building the proposal
+        if let Some(utxos) = &utxos {
+            for outpoint in utxos.iter() {
+                if !policy_utxos.contains(outpoint) {
+                    return Err(Error::UtxoNotInPolicy(*outpoint));
+                }
+                if let Some(frozen_utxos) = &frozen_utxos {
+                    if frozen_utxos.contains(outpoint) {
+                        return Err(Error::UtxoFrozen(*outpoint));
+                    }
+                }
+            }
         }
 
+        let coin_selection: CoinSelection = self
+            .resolve_coin_selection(policy_id, coin_selection)
+            .await?;
+
+        let policy_path: Option<BTreeMap<String, Vec<usize>>> = match policy_path {
+            Some(policy_path) => {
+                let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+                policy.validate_policy_path(&policy_path)?;
+                Some(policy_path)
+            }
+            None => self.select_policy_path(policy_id).await?,
+        };
+
         // Build spending proposal
-        let proposal: Proposal = self
+        Ok(self
             .manager
             .spend(
                 policy_id,
@@ -1167,34 +2461,61 @@ impl SmartVaults {
                 utxos,
                 frozen_utxos,
                 policy_path,
+                coin_selection,
             )
-            .await?;
+            .await?)
+    }
+
+    /// Select the policy path from the local signers
+    ///
+    /// If the path can't be selected automatically, return [`Error::PolicyPathRequired`] with the
+    /// conditions to select (see [`SmartVaults::spend_with_path`]).
+    async fn select_policy_path(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Option<BTreeMap<String, Vec<usize>>>, Error> {
+        let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+        let conditions = match policy.selectable_conditions()? {
+            Some(conditions) if !conditions.is_empty() => conditions,
+            _ => return Ok(None),
+        };
+
+        let signers = self.storage.signers().await.into_values();
+        match policy.get_policy_paths_from_signers(signers)? {
+            PolicyPath::Single(PolicyPathSelector::Complete { path }) => Ok(Some(path)),
+            PolicyPath::Single(PolicyPathSelector::Partial { .. })
+            | PolicyPath::Multiple(..)
+            | PolicyPath::None => Err(Error::PolicyPathRequired(conditions)),
+        }
+    }
 
+    /// Publish spending proposal
+    ///
+    /// If `replaces` is set, the proposal is published as fee bump replacement of that proposal.
+    async fn publish_spending_proposal(
+        &self,
+        policy_id: EventId,
+        proposal: Proposal,
+        replaces: Option<EventId>,
+    ) -> Result<GetProposal, Error> {
         if let Proposal::Spending { psbt, .. } = &proposal {
             // Get shared keys
             let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
 
             // Compose the event
             let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
-            let event: Event =
-                EventBuilder::proposal(&shared_key, policy_id, &proposal, &public_keys)?;
+            let event: Event = match replaces {
+                Some(original_id) => EventBuilder::replacement_proposal(
+                    &shared_key,
+                    policy_id,
+                    original_id,
+                    &proposal,
+                    &public_keys,
+                )?,
+                None => EventBuilder::proposal(&shared_key, policy_id, &proposal, &public_keys)?,
+            };
             let timestamp = event.created_at;
-            let proposal_id = self.client.send_event(event).await?;
-
-            // Send DM msg
-            // TODO: send withoud wait for OK
-            /* let sender = self.client.keys().public_key();
-            let mut msg = String::from("New spending proposal:\n");
-            msg.push_str(&format!(
-                "- Amount: {} sat\n",
-                util::format::big_number(*amount)
-            ));
-            msg.push_str(&format!("- Description: {description}"));
-            for pubkey in nostr_pubkeys.into_iter() {
-                if sender != pubkey {
-                    self.client.send_direct_msg(pubkey, &msg, None).await?;
-                }
-            } */
+            let proposal_id = self.send_critical_event(event).await?;
 
             // Index proposal
             self.storage
@@ -1219,6 +2540,29 @@ impl SmartVaults {
                 )
                 .await;
 
+            // Link the replacement to the original proposal
+            if let Some(original_id) = replaces {
+                self.storage
+                    .save_replacement(original_id, policy_id, proposal_id, psbt.unsigned_tx.txid())
+                    .await;
+            }
+
+            // Notify co-signers
+            if self.config.notify_cosigners().await {
+                if let Err(e) = self.notify_cosigners(proposal_id).await {
+                    tracing::warn!("Impossible to notify co-signers of {proposal_id}: {e}");
+                }
+            }
+
+            // Remind the pending approvers, if enabled for the policy
+            if let Err(e) = self
+                .db
+                .track_reminders(proposal_id, policy_id, timestamp)
+                .await
+            {
+                tracing::warn!("Impossible to track reminders of {proposal_id}: {e}");
+            }
+
             // Compose output
             Ok(GetProposal {
                 proposal_id,
@@ -1226,12 +2570,726 @@ impl SmartVaults {
                 proposal,
                 signed: false,
                 timestamp,
+                fee_warning: None,
             })
         } else {
             Err(Error::UnexpectedProposal)
         }
     }
 
+    /// Make a spending proposal
+    pub async fn spend<S>(
+        &self,
+        policy_id: EventId,
+        address: Address<NetworkUnchecked>,
+        amount: Amount,
+        description: S,
+        fee_rate: FeeRate,
+        utxos: Option<Vec<OutPoint>>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
+    ) -> Result<GetProposal, Error>
+    where
+        S: Into<String>,
+    {
+        let description: String = description.into();
+        let proposal: Proposal = self
+            .build_spending_proposal(
+                policy_id,
+                address,
+                amount,
+                &description,
+                fee_rate,
+                utxos,
+                policy_path,
+                skip_frozen_utxos,
+                coin_selection,
+            )
+            .await?;
+        self.publish_spending_proposal(policy_id, proposal, None)
+            .await
+    }
+
+    /// Preview a spending proposal without publishing it
+    ///
+    /// Take the same arguments of [`SmartVaults::spend`] and build the PSBT against the wallet,
+    /// but no proposal event is published and the selected UTXOs are **not** frozen: safe to call
+    /// on every change of a spend form.
+    pub async fn build_spend_preview<S>(
+        &self,
+        policy_id: EventId,
+        address: Address<NetworkUnchecked>,
+        amount: Amount,
+        description: S,
+        fee_rate: FeeRate,
+        utxos: Option<Vec<OutPoint>>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
+    ) -> Result<SpendPreview, Error>
+    where
+        S: Into<String>,
+    {
+        let description: String = description.into();
+        let proposal: Proposal = self
+            .build_spending_proposal(
+                policy_id,
+                address,
+                amount,
+                &description,
+                fee_rate,
+                utxos,
+                policy_path,
+                skip_frozen_utxos,
+                coin_selection,
+            )
+            .await?;
+
+        let wallet: SmartVaultsWallet = self.manager.wallet(policy_id).await?;
+        let psbt: PartiallySignedTransaction = proposal.psbt();
+
+        let mut selected_utxos: Vec<TxInputDetails> =
+            Vec::with_capacity(psbt.unsigned_tx.input.len());
+        for txin in psbt.unsigned_tx.input.iter() {
+            let prevout: Option<TxOut> = wallet.get_txout(txin.previous_output).await;
+            selected_utxos.push(TxInputDetails {
+                previous_output: txin.previous_output,
+                value: prevout.map(|txout| txout.value),
+                is_mine: true,
+            });
+        }
+
+        let mut change: Option<TxOutputDetails> = None;
+        for txout in psbt.unsigned_tx.output.iter() {
+            if wallet.keychain_of(&txout.script_pubkey).await == Some(KeychainKind::Internal) {
+                change = Some(TxOutputDetails {
+                    address: Address::from_script(&txout.script_pubkey, self.network)
+                        .ok()
+                        .map(|a| Address::new(self.network, a.payload)),
+                    value: txout.value,
+                    is_mine: true,
+                    is_change: true,
+                });
+                break;
+            }
+        }
+
+        Ok(SpendPreview {
+            policy_id,
+            fee: proposal.fee()?,
+            fee_rate: proposal.fee_rate()?,
+            proposal,
+            psbt,
+            selected_utxos,
+            change,
+        })
+    }
+
+    /// Check that a [`SpendPreview`] can still be published
+    async fn check_spend_preview(&self, preview: &SpendPreview) -> Result<(), Error> {
+        if preview.proposal.psbt() != preview.psbt {
+            return Err(Error::UnexpectedProposal);
+        }
+
+        let policy_utxos: HashSet<OutPoint> = self
+            .manager
+            .get_utxos(preview.policy_id)
+            .await?
+            .into_iter()
+            .map(|utxo| utxo.outpoint)
+            .collect();
+        for txin in preview.psbt.unsigned_tx.input.iter() {
+            if !policy_utxos.contains(&txin.previous_output) {
+                return Err(Error::UtxoNotInPolicy(txin.previous_output));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish the proposal of a [`SpendPreview`]
+    ///
+    /// The PSBT is **not** rebuilt: the published proposal has exactly the inputs, outputs and fee
+    /// of the preview. Return [`Error::UtxoNotInPolicy`] if a selected UTXO is no longer spendable.
+    pub async fn publish_spend_preview(&self, preview: SpendPreview) -> Result<GetProposal, Error> {
+        self.check_spend_preview(&preview).await?;
+        self.publish_spending_proposal(preview.policy_id, preview.proposal, None)
+            .await
+    }
+
+    /// Create a spending proposal using the selected policy path
+    ///
+    /// Use it when [`SmartVaults::spend`] return [`Error::PolicyPathRequired`]: `policy_path` map
+    /// the path of each [`SelectableCondition`](smartvaults_core::policy::SelectableCondition) to
+    /// the indexes of the selected sub-paths.
+    pub async fn spend_with_path<S>(
+        &self,
+        policy_id: EventId,
+        address: Address<NetworkUnchecked>,
+        amount: Amount,
+        description: S,
+        fee_rate: FeeRate,
+        policy_path: BTreeMap<String, Vec<usize>>,
+    ) -> Result<GetProposal, Error>
+    where
+        S: Into<String>,
+    {
+        self.spend(
+            policy_id,
+            address,
+            amount,
+            description,
+            fee_rate,
+            None,
+            Some(policy_path),
+            false,
+            None,
+        )
+        .await
+    }
+
+    /// Send a NIP-04 DM, with a summary of a pending spending proposal, to the other participants
+    /// of the policy
+    ///
+    /// Called automatically on proposal creation if [`Config::notify_cosigners`](crate::config::Config::notify_cosigners)
+    /// is enabled: use it to nudge the co-signers again. Return the notified public keys.
+    pub async fn notify_cosigners(&self, proposal_id: EventId) -> Result<Vec<PublicKey>, Error> {
+        let InternalProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.storage.proposal(&proposal_id).await?;
+        let (to_address, amount, description) = match proposal {
+            Proposal::Spending {
+                to_address,
+                amount,
+                description,
+                ..
+            } => (to_address, amount, description),
+            _ => return Err(Error::UnexpectedProposal),
+        };
+
+        let mut msg = String::from("New spending proposal to sign:\n");
+        msg.push_str(&format!("- Amount: {} sat\n", util::format::number(amount)));
+        msg.push_str(&format!("- Address: {}\n", to_address.assume_checked()));
+        msg.push_str(&format!("- Description: {description}\n"));
+        msg.push_str(&format!("- Proposal ID: {proposal_id}"));
+
+        // The same public key may be in the policy more than once
+        let my_public_key: PublicKey = self.keys().public_key();
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+        let public_keys: BTreeSet<PublicKey> = public_keys
+            .into_iter()
+            .filter(|pk| *pk != my_public_key)
+            .collect();
+
+        let mut notified: Vec<PublicKey> = Vec::with_capacity(public_keys.len());
+        for public_key in public_keys.into_iter() {
+            match self.client.send_direct_msg(public_key, &msg, None).await {
+                Ok(_) => notified.push(public_key),
+                Err(e) => tracing::warn!("Impossible to notify {public_key}: {e}"),
+            }
+        }
+
+        Ok(notified)
+    }
+
+    /// Bump the fee (RBF) of a pending spending proposal or of an unconfirmed transaction of the policy
+    ///
+    /// The replacement is published as a new spending proposal that references the original one:
+    /// the original is marked as superseded once the replacement confirms.
+    /// Return [`Error::FeeBumpTooLow`] if the replacement doesn't pay more than the original.
+    ///
+    /// `policy_path` is the one used by the original transaction: if not set, the one of the
+    /// pending proposal is used or, for broadcasted transactions, it's selected from the local
+    /// signers (see [`SmartVaults::spend`]).
+    pub async fn bump_fee(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+        fee_rate: FeeRate,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+    ) -> Result<GetProposal, Error> {
+        // Estimate once: the rate is checked against the original one
+        let fee_rate: BdkFeeRate = self.calculate_fee_rate(fee_rate).await?;
+
+        // Pending proposal: rebuild it spending the same UTXOs
+        let pending = self
+            .storage
+            .proposals()
+            .await
+            .into_iter()
+            .find(|(_, internal)| {
+                internal.policy_id == policy_id
+                    && internal.proposal.psbt().unsigned_tx.txid() == txid
+            });
+        if let Some((original_id, InternalProposal { proposal, .. })) = pending {
+            let original_fee: u64 = proposal.fee()?;
+            let original_rate: f32 = proposal.fee_rate()?;
+            if let Proposal::Spending {
+                to_address,
+                amount,
+                description,
+                psbt,
+                policy_path: original_policy_path,
+                ..
+            } = proposal
+            {
+                let utxos: Vec<OutPoint> = psbt
+                    .unsigned_tx
+                    .input
+                    .iter()
+                    .map(|txin| txin.previous_output)
+                    .collect();
+                // Without change, the fee is paid by the recipient
+                let amount: Amount = if psbt.unsigned_tx.output.len() == 1 {
+                    Amount::Max
+                } else {
+                    Amount::Custom(amount)
+                };
+                let replacement: Proposal = self
+                    .build_spending_proposal(
+                        policy_id,
+                        to_address,
+                        amount,
+                        &description,
+                        FeeRate::Rate(fee_rate.as_sat_per_vb()),
+                        Some(utxos),
+                        policy_path.or(original_policy_path),
+                        true,
+                        None,
+                    )
+                    .await?;
+
+                // Same inputs: both the fee and the (approximated) rate must increase
+                let new: u64 = replacement.fee()?;
+                let new_rate: f32 = replacement.fee_rate()?;
+                if new <= original_fee || new_rate <= original_rate {
+                    return Err(Error::FeeBumpTooLow {
+                        original: original_fee,
+                        original_rate,
+                        new,
+                        new_rate,
+                    });
+                }
+
+                return self
+                    .publish_spending_proposal(policy_id, replacement, Some(original_id))
+                    .await;
+            }
+            return Err(Error::UnexpectedProposal);
+        }
+
+        // Broadcasted transaction: replace it with BDK
+        let completed = self
+            .storage
+            .completed_proposals()
+            .await
+            .into_iter()
+            .find_map(|(id, internal)| match internal.proposal {
+                CompletedProposal::Spending { tx, description }
+                    if internal.policy_id == policy_id && tx.txid() == txid =>
+                {
+                    Some((id, description))
+                }
+                _ => None,
+            });
+        let (original_id, description) = match completed {
+            Some((id, description)) => (Some(id), description),
+            None => (None, format!("Fee bump of {txid}")),
+        };
+        let policy_path: Option<BTreeMap<String, Vec<usize>>> = match policy_path {
+            Some(policy_path) => {
+                let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+                policy.validate_policy_path(&policy_path)?;
+                Some(policy_path)
+            }
+            None => self.select_policy_path(policy_id).await?,
+        };
+        let proposal: Proposal = self
+            .manager
+            .bump_fee(policy_id, txid, description, fee_rate, policy_path)
+            .await?;
+        self.publish_spending_proposal(policy_id, proposal, original_id)
+            .await
+    }
+
+    /// Mark the proposals replaced by a confirmed fee bump as superseded
+    pub(crate) async fn mark_superseded_proposals(&self) -> Result<(), Error> {
+        let superseded: HashMap<EventId, EventId> = self.db.get_superseded().await?;
+        for (original_id, (policy_id, replacement_id, txid)) in
+            self.storage.replacements().await.into_iter()
+        {
+            if superseded.contains_key(&original_id) {
+                continue;
+            }
+
+            if let Ok(TransactionDetails {
+                confirmation_time: ConfirmationTime::Confirmed { .. },
+                ..
+            }) = self.manager.get_tx(policy_id, txid).await
+            {
+                tracing::info!("Proposal {original_id} superseded by {replacement_id}");
+                self.db.set_superseded(original_id, replacement_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Classify the outgoing transactions of every vault by matching completed proposal
+    ///
+    /// The ones without a match are flagged as unexpected and notified with
+    /// [`Message::UnexpectedOutgoingTx`]. A transaction is classified only once, so deleting a
+    /// completed proposal later doesn't raise an alarm; a flagged transaction is cleared if
+    /// the matching completed proposal is received later.
+    pub(crate) async fn classify_outgoing_txs(&self) -> Result<(), Error> {
+        let mut known: HashSet<Txid> = HashSet::new();
+        for InternalCompletedProposal { proposal, .. } in
+            self.storage.completed_proposals().await.into_values()
+        {
+            if let Some(tx) = proposal.tx() {
+                known.insert(tx.txid());
+            }
+        }
+
+        // Proposal finalized by a co-signer: the completed proposal may not be received yet
+        for InternalProposal { proposal, .. } in self.storage.proposals().await.into_values() {
+            known.insert(proposal.psbt().unsigned_tx.txid());
+        }
+
+        for policy_id in self.storage.vaults().await.into_keys() {
+            let txs: BTreeSet<TransactionDetails> = match self.manager.get_txs(policy_id).await {
+                Ok(txs) => txs,
+                Err(e) => {
+                    tracing::warn!("Impossible to get txs of policy {policy_id}: {e}");
+                    continue;
+                }
+            };
+            let classified: HashMap<Txid, bool> = self.db.get_outgoing_txs(policy_id).await?;
+
+            for tx in txs.into_iter().filter(|tx| tx.sent > 0) {
+                let txid: Txid = tx.txid();
+                let amount: u64 = tx.sent.saturating_sub(tx.received);
+                let expected: bool = known.contains(&txid);
+                match (classified.get(&txid), expected) {
+                    (Some(true), _) | (Some(false), false) => (),
+                    (None, false) => {
+                        tracing::warn!(
+                            "Unexpected outgoing tx {txid} for policy {policy_id}: no matching completed proposal"
+                        );
+                        self.db
+                            .save_outgoing_tx(policy_id, txid, amount, false)
+                            .await?;
+                        self.sync_channel.send(Message::UnexpectedOutgoingTx {
+                            policy_id,
+                            txid,
+                            amount,
+                        });
+                    }
+                    (_, true) => {
+                        self.db
+                            .save_outgoing_tx(policy_id, txid, amount, true)
+                            .await?
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Store the fees paid by the outgoing transactions of every vault
+    ///
+    /// The fee is `inputs - outputs`: if some input value is unknown (ex. inputs of other
+    /// wallets), only the known ones are counted and the fee is marked as estimated.
+    pub(crate) async fn record_tx_fees(&self) -> Result<(), Error> {
+        for policy_id in self.storage.vaults().await.into_keys() {
+            let txs: BTreeSet<TransactionDetails> = match self.manager.get_txs(policy_id).await {
+                Ok(txs) => txs,
+                Err(e) => {
+                    tracing::warn!("Impossible to get txs of policy {policy_id}: {e}");
+                    continue;
+                }
+            };
+
+            let fees: Vec<TxFee> = txs
+                .into_iter()
+                .filter(|tx| tx.sent > 0)
+                .map(|tx| {
+                    let timestamp: u64 = match tx.confirmation_time {
+                        ConfirmationTime::Confirmed { time, .. } => time,
+                        ConfirmationTime::Unconfirmed { last_seen } => last_seen,
+                    };
+                    let (fee, estimated): (u64, bool) = match tx.fee.amount {
+                        Some(fee) => (fee, false),
+                        None => {
+                            let outputs: u64 = tx.output.iter().map(|txout| txout.value).sum();
+                            (tx.sent.saturating_sub(outputs), true)
+                        }
+                    };
+                    TxFee {
+                        txid: tx.txid(),
+                        fee,
+                        estimated,
+                        timestamp: Timestamp::from(timestamp),
+                    }
+                })
+                .collect();
+
+            self.db.save_tx_fees(policy_id, fees).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the miner fees paid by a policy between `from` and `to` (inclusive)
+    ///
+    /// The fees are updated at every wallet sync.
+    pub async fn get_fees_paid(
+        &self,
+        policy_id: EventId,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+    ) -> Result<FeesPaid, Error> {
+        let fees: Vec<TxFee> = self.db.get_tx_fees(policy_id, from, to).await?;
+        Ok(FeesPaid::from(fees))
+    }
+
+    /// Get outgoing transactions without a matching completed proposal
+    ///
+    /// If `policy_id` is `None`, return the ones of all the vaults.
+    pub async fn get_unexpected_txs(
+        &self,
+        policy_id: Option<EventId>,
+    ) -> Result<Vec<UnexpectedTx>, Error> {
+        Ok(self.db.get_unexpected_txs(policy_id).await?)
+    }
+
+    /// Acknowledge an unexpected outgoing transaction (it remains flagged in the tx list)
+    pub async fn acknowledge_unexpected_tx(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+    ) -> Result<(), Error> {
+        Ok(self.db.acknowledge_unexpected_tx(policy_id, txid).await?)
+    }
+
+    /// Get notifications (newest first)
+    pub async fn get_notifications(
+        &self,
+        unseen_only: bool,
+    ) -> Result<Vec<GetNotificationsResult>, Error> {
+        Ok(self.db.get_notifications(unseen_only).await?)
+    }
+
+    pub async fn unseen_notifications_count(&self) -> Result<usize, Error> {
+        Ok(self.db.count_unseen_notifications().await?)
+    }
+
+    /// Mark as seen the notifications of the event (policy ID, proposal ID, ...)
+    pub async fn mark_notification_seen(&self, event_id: EventId) -> Result<(), Error> {
+        Ok(self.db.mark_notification_as_seen(event_id).await?)
+    }
+
+    pub async fn mark_all_notifications_seen(&self) -> Result<(), Error> {
+        Ok(self.db.mark_all_notifications_as_seen().await?)
+    }
+
+    pub async fn delete_all_notifications(&self) -> Result<(), Error> {
+        Ok(self.db.delete_all_notifications().await?)
+    }
+
+    /// Get proposals (or completed proposals) superseded by a confirmed fee bump
+    ///
+    /// Return `superseded ID` -> `replacement proposal ID`
+    pub async fn get_superseded_proposals(&self) -> Result<HashMap<EventId, EventId>, Error> {
+        Ok(self.db.get_superseded().await?)
+    }
+
+    /// Get the local vault (other than `policy_id`) that owns `script`
+    async fn internal_destination(
+        &self,
+        policy_id: EventId,
+        script: &Script,
+    ) -> Result<Option<EventId>, Error> {
+        for (id, ..) in self.storage.vaults().await.into_iter() {
+            if id != policy_id && self.manager.wallet(id).await?.is_mine(script) {
+                return Ok(Some(id));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Make a spending proposal to another local vault and, if the local signers can satisfy
+    /// the policy on their own, approve and finalize it in one go.
+    ///
+    /// The proposal, approval and completed proposal events are published as usual,
+    /// so the co-signers still see the history.
+    /// Never auto-complete when the proposal pays external recipients.
+    pub async fn spend_with_auto_complete<T, S>(
+        &self,
+        password: T,
+        policy_id: EventId,
+        address: Address<NetworkUnchecked>,
+        amount: Amount,
+        description: S,
+        fee_rate: FeeRate,
+        utxos: Option<Vec<OutPoint>>,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
+    ) -> Result<CompletedProposal, Error>
+    where
+        T: AsRef<[u8]>,
+        S: Into<String>,
+    {
+        let recipient: Address = util::network::check_address_network(&address, self.network)?;
+        self.check_auto_complete_destination(policy_id, &recipient)
+            .await?;
+
+        let description: String = description.into();
+        let proposal: Proposal = self
+            .build_spending_proposal(
+                policy_id,
+                address,
+                amount,
+                &description,
+                fee_rate,
+                utxos,
+                policy_path,
+                skip_frozen_utxos,
+                coin_selection,
+            )
+            .await?;
+        self.auto_complete(password, policy_id, proposal).await
+    }
+
+    /// Publish the proposal of a [`SpendPreview`] and auto-complete it
+    ///
+    /// Like [`SmartVaults::spend_with_auto_complete`] but the PSBT is **not** rebuilt (see
+    /// [`SmartVaults::publish_spend_preview`]).
+    pub async fn publish_spend_preview_with_auto_complete<T>(
+        &self,
+        password: T,
+        preview: SpendPreview,
+    ) -> Result<CompletedProposal, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.check_spend_preview(&preview).await?;
+        if let Proposal::Spending { to_address, .. } = &preview.proposal {
+            let recipient: Address =
+                util::network::check_address_network(to_address, self.network)?;
+            self.check_auto_complete_destination(preview.policy_id, &recipient)
+                .await?;
+        }
+        self.auto_complete(password, preview.policy_id, preview.proposal)
+            .await
+    }
+
+    async fn check_auto_complete_destination(
+        &self,
+        policy_id: EventId,
+        recipient: &Address,
+    ) -> Result<(), Error> {
+        if self
+            .internal_destination(policy_id, &recipient.script_pubkey())
+            .await?
+            .is_none()
+        {
+            return Err(Error::AutoCompleteNotPossible(String::from(
+                "destination is not a local vault",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check, publish, approve and finalize a spending proposal to another local vault
+    async fn auto_complete<T>(
+        &self,
+        password: T,
+        policy_id: EventId,
+        proposal: Proposal,
+    ) -> Result<CompletedProposal, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        // Check that every output pays back to the vault or to another local vault
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+        for output in proposal
+            .verify_outputs_with_change(policy.as_descriptor(), policy.as_change_descriptor())?
+            .into_iter()
+        {
+            if output.kind != OutputKind::Change
+                && self
+                    .internal_destination(policy_id, &output.script_pubkey)
+                    .await?
+                    .is_none()
+            {
+                return Err(Error::AutoCompleteNotPossible(String::from(
+                    "proposal has external recipients",
+                )));
+            }
+        }
+
+        // Check fee
+        let fee_cap = self.config.fee_cap().await;
+        if let Some(warning) = proposal.check_fee(fee_cap, None)? {
+            return Err(Error::FeeWarning(warning));
+        }
+
+        // Check if the eligible local signers can satisfy the policy together
+        let seed: Seed = self.keechain.read().seed(password)?;
+        let mut approvals: Vec<ApprovedProposal> = Vec::new();
+        for signer in self.local_signers_in_policy(&policy).await.iter() {
+            match proposal.approve_with_signer(&seed, signer, self.network) {
+                Ok(approved_proposal) => approvals.push(approved_proposal),
+                Err(smartvaults_core::proposal::Error::PsbtNotSigned) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if approvals.is_empty() || proposal.finalize(approvals.clone(), self.network).is_err() {
+            return Err(Error::AutoCompleteNotPossible(String::from(
+                "local signers can't satisfy the policy",
+            )));
+        }
+
+        let GetProposal { proposal_id, .. } = self
+            .publish_spending_proposal(policy_id, proposal, None)
+            .await?;
+        self.check_before_approval(proposal_id, true, false).await?;
+        for approved_proposal in approvals.into_iter() {
+            self.audit_signing(
+                SigningKind::Approval,
+                proposal_id.to_hex(),
+                &approved_proposal.psbt().serialize(),
+            )
+            .await?;
+            self.publish_approval(proposal_id, policy_id, approved_proposal)
+                .await?;
+        }
+        self.finalize(proposal_id).await
+    }
+
+    /// Get the signers derived from the seed (the default one and the other local accounts)
+    /// used in `policy`
+    async fn local_signers_in_policy(&self, policy: &Policy) -> Vec<Signer> {
+        let fingerprint: Fingerprint = self.fingerprint();
+        let mut signers: Vec<Signer> = vec![self.default_signer.clone()];
+        for signer in self.storage.signers().await.into_values() {
+            if signer.signer_type() == SignerType::Seed
+                && signer.fingerprint() == fingerprint
+                && !signers
+                    .iter()
+                    .any(|s| s.descriptor() == signer.descriptor())
+            {
+                signers.push(signer);
+            }
+        }
+        policy.search_used_signers(signers.into_iter()).collect()
+    }
+
     /// Spend to another [`Policy`]
     pub async fn self_transfer(
         &self,
@@ -1242,6 +3300,7 @@ impl SmartVaults {
         utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
         skip_frozen_utxos: bool,
+        coin_selection: Option<CoinSelection>,
     ) -> Result<GetProposal, Error> {
         let address = self
             .get_address(to_policy_id, AddressIndex::New)
@@ -1261,10 +3320,46 @@ impl SmartVaults {
             utxos,
             policy_path,
             skip_frozen_utxos,
+            coin_selection,
         )
         .await
     }
 
+    /// Get the default [`CoinSelection`] strategy of a policy
+    pub async fn default_coin_selection(&self, policy_id: EventId) -> Result<CoinSelection, Error> {
+        match self.db.get_coin_selection(policy_id).await {
+            Ok(coin_selection) => Ok(coin_selection),
+            Err(smartvaults_sdk_sqlite::Error::NotFound(..)) => Ok(CoinSelection::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Get the [`CoinSelection`] strategy used for a spend of a policy
+    ///
+    /// Returns `coin_selection` if set, otherwise the default one of the policy.
+    pub async fn resolve_coin_selection(
+        &self,
+        policy_id: EventId,
+        coin_selection: Option<CoinSelection>,
+    ) -> Result<CoinSelection, Error> {
+        match coin_selection {
+            Some(coin_selection) => Ok(coin_selection),
+            None => self.default_coin_selection(policy_id).await,
+        }
+    }
+
+    /// Set the default [`CoinSelection`] strategy of a policy
+    pub async fn set_default_coin_selection(
+        &self,
+        policy_id: EventId,
+        coin_selection: CoinSelection,
+    ) -> Result<(), Error> {
+        Ok(self
+            .db
+            .set_coin_selection(policy_id, coin_selection)
+            .await?)
+    }
+
     /* async fn is_internal_key<S>(&self, descriptor: S) -> Result<bool, Error>
     where
         S: Into<String>,
@@ -1277,14 +3372,171 @@ impl SmartVaults {
         )
     } */
 
+    /// Check the proposal fee against the fee cap and the current mempool estimates
+    ///
+    /// The result is stored and exposed in [`GetProposal::fee_warning`].
+    pub async fn check_proposal_fee(
+        &self,
+        proposal_id: EventId,
+    ) -> Result<Option<FeeWarning>, Error> {
+        let InternalProposal { proposal, .. } = self.storage.proposal(&proposal_id).await?;
+        let max_fee_rate: Option<f32> = self
+            .manager
+            .mempool_fees()
+            .await
+            .get(&Priority::High)
+            .map(|rate| rate.as_sat_per_vb() * MAX_FEE_RATE_MULTIPLIER);
+        let fee_cap = self.config.fee_cap().await;
+        let warning: Option<FeeWarning> = proposal.check_fee(fee_cap, max_fee_rate)?;
+        if let Some(warning) = &warning {
+            tracing::warn!("Suspicious fee in proposal {proposal_id}: {warning}");
+        }
+        self.storage.set_fee_warning(proposal_id, warning).await;
+        Ok(warning)
+    }
+
+    /// Check if the collected approvals are enough to finalize the proposal
+    ///
+    /// Dry-run of the finalization (PSBTs combination and satisfaction of the descriptor): nothing is broadcasted.
+    /// The timelocks are checked against the transaction, so only the policy path selected at proposal creation is satisfiable.
+    ///
+    /// The result is stored and a [`Message::ProposalReady`] is sent when the proposal become ready.
+    pub async fn check_proposal_ready(&self, proposal_id: EventId) -> Result<bool, Error> {
+        let GetApprovedProposals {
+            proposal,
+            approved_proposals,
+            ..
+        } = self.storage.approvals_by_proposal_id(&proposal_id).await?;
+        let ready: bool = proposal.finalize(approved_proposals, self.network).is_ok();
+        if self.storage.set_ready_to_finalize(proposal_id, ready).await {
+            tracing::info!("Proposal {proposal_id} is ready to be finalized");
+
+            // The notification is saved only once, so extra approvals (or a restart) don't notify again
+            if self
+                .db
+                .save_notification(
+                    NotificationKind::ProposalReadyToFinalize,
+                    proposal_id,
+                    Timestamp::now(),
+                )
+                .await?
+            {
+                self.sync_channel.send(Message::ProposalReady(proposal_id));
+            }
+        }
+        Ok(ready)
+    }
+
+    /// Minimum number of signatures still missing to finalize the proposal
+    ///
+    /// The collected approvals are combined and the keys with fingerprint in `assume_signed` are
+    /// considered as signed (see [`Policy::missing_signatures`]).
+    pub async fn missing_signatures(
+        &self,
+        proposal_id: EventId,
+        assume_signed: &[Fingerprint],
+    ) -> Result<Option<usize>, Error> {
+        let GetApprovedProposals {
+            policy_id,
+            proposal,
+            approved_proposals,
+        } = self.storage.approvals_by_proposal_id(&proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+        let psbt: PartiallySignedTransaction = proposal.combine_approvals(approved_proposals)?;
+        Ok(policy.missing_signatures(&psbt, assume_signed)?)
+    }
+
+    /// Check if an approval with the local seed would make the proposal ready to be finalized
+    ///
+    /// Return `false` if the proposal is already ready (nothing to approve).
+    pub async fn approval_completes_proposal(&self, proposal_id: EventId) -> Result<bool, Error> {
+        if self.storage.is_ready_to_finalize(&proposal_id).await {
+            return Ok(false);
+        }
+        let missing: Option<usize> = self
+            .missing_signatures(proposal_id, &[self.fingerprint()])
+            .await?;
+        Ok(missing == Some(0))
+    }
+
+    /// Verify that the proposal outputs are the recipient or pay back to the vault
+    pub async fn verify_proposal_outputs(
+        &self,
+        proposal_id: EventId,
+    ) -> Result<Vec<OutputVerification>, Error> {
+        let InternalProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.storage.proposal(&proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+        Ok(proposal
+            .verify_outputs_with_change(policy.as_descriptor(), policy.as_change_descriptor())?)
+    }
+
+    /// Checks before approving a proposal (see [`SmartVaults::approve`])
+    async fn check_before_approval(
+        &self,
+        proposal_id: EventId,
+        acknowledge_fee: bool,
+        force: bool,
+    ) -> Result<(), Error> {
+        // Check fee
+        if let Some(warning) = self.check_proposal_fee(proposal_id).await? {
+            if !acknowledge_fee {
+                return Err(Error::FeeWarning(warning));
+            }
+        }
+
+        // Check outputs
+        let unverified: usize = self
+            .verify_proposal_outputs(proposal_id)
+            .await?
+            .into_iter()
+            .filter(|o| o.kind == OutputKind::Unverified)
+            .count();
+        if unverified > 0 && !force {
+            return Err(Error::UnverifiedOutputs(unverified));
+        }
+
+        // Screen recipients
+        for (script, result) in self.screen_proposal(proposal_id).await? {
+            if result.is_flagged() {
+                let address: String = Address::from_script(&script, self.network)
+                    .map(|a| a.to_string())
+                    .unwrap_or_else(|_| script.to_string());
+                match self.config.screening_on_approve().await {
+                    ScreeningAction::Warn => {
+                        tracing::warn!("Proposal {proposal_id} pays {address}: {result}")
+                    }
+                    ScreeningAction::Block => {
+                        return Err(Error::ScreeningFlagged { address, result })
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Approve proposal
+    ///
+    /// If the proposal fee is flagged as suspicious (see [`SmartVaults::check_proposal_fee`]),
+    /// `acknowledge_fee` must be `true`.
+    /// If some output can't be verified (see [`SmartVaults::verify_proposal_outputs`]), `force` must be `true`.
     pub async fn approve<T>(
         &self,
         password: T,
         proposal_id: EventId,
+        acknowledge_fee: bool,
+        force: bool,
     ) -> Result<(EventId, ApprovedProposal), Error>
     where
         T: AsRef<[u8]>,
     {
+        self.check_before_approval(proposal_id, acknowledge_fee, force)
+            .await?;
+
         // Get proposal and policy
         let GetProposal {
             policy_id,
@@ -1292,8 +3544,6 @@ impl SmartVaults {
             ..
         } = self.get_proposal_by_id(proposal_id).await?;
 
-        let keys: &Keys = self.keys();
-
         /* // Sign PSBT
         // Custom signer
         let signer = SignerWrapper::new(
@@ -1306,50 +3556,119 @@ impl SmartVaults {
         ); */
         let seed: Seed = self.keechain.read().seed(password)?;
         let approved_proposal = proposal.approve(&seed, Vec::new(), self.network)?;
+        self.audit_signing(
+            match &proposal {
+                Proposal::ProofOfReserve { .. } => SigningKind::ProofOfReserve,
+                _ => SigningKind::Approval,
+            },
+            proposal_id.to_hex(),
+            &approved_proposal.psbt().serialize(),
+        )
+        .await?;
 
-        // Get shared keys
-        let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+        self.publish_approval(proposal_id, policy_id, approved_proposal)
+            .await
+    }
 
-        // Compose the event
-        let content = approved_proposal.encrypt_with_keys(&shared_key)?;
-        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
-        let mut tags: Vec<Tag> = public_keys.into_iter().map(Tag::public_key).collect();
-        tags.push(Tag::event(proposal_id));
-        tags.push(Tag::event(policy_id));
-        tags.push(Tag::Expiration(
-            Timestamp::now().add(APPROVED_PROPOSAL_EXPIRATION),
-        ));
+    /// Approve proposal and, if the collected approvals are enough, finalize and broadcast it
+    ///
+    /// See [`SmartVaults::approve`] for `acknowledge_fee` and `force`.
+    pub async fn approve_and_finalize<T>(
+        &self,
+        password: T,
+        proposal_id: EventId,
+        acknowledge_fee: bool,
+        force: bool,
+    ) -> Result<ApproveAndFinalize, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let (approval_id, approved_proposal) = self
+            .approve(password, proposal_id, acknowledge_fee, force)
+            .await?;
 
-        let event = EventBuilder::new(APPROVED_PROPOSAL_KIND, content, tags).to_event(keys)?;
-        let timestamp = event.created_at;
+        if self.check_proposal_ready(proposal_id).await? {
+            let completed_proposal: CompletedProposal = self.finalize(proposal_id).await?;
+            Ok(ApproveAndFinalize::Finalized {
+                approval_id,
+                completed_proposal,
+            })
+        } else {
+            let missing_signatures: Option<usize> =
+                self.missing_signatures(proposal_id, &[]).await?;
+            Ok(ApproveAndFinalize::Approved {
+                approval_id,
+                approved_proposal,
+                missing_signatures,
+            })
+        }
+    }
 
-        // Publish the event
-        let event_id = self.client.send_event(event).await?;
+    /// Approve proposal signing only with the keys of a local signer
+    ///
+    /// Useful when the seed holds more keys of the policy (i.e. decaying policies, where
+    /// different keys unlock different paths). Return [`Error::SignerNotInPolicy`] if the signer
+    /// doesn't participate in the policy and [`Error::SignerNotLocal`] if its keys are not
+    /// derived from the seed (i.e. hardware or airgap signers).
+    /// See [`SmartVaults::approve`] for `acknowledge_fee` and `force`.
+    pub async fn approve_with_signer<T>(
+        &self,
+        password: T,
+        proposal_id: EventId,
+        signer_id: EventId,
+        acknowledge_fee: bool,
+        force: bool,
+    ) -> Result<(EventId, ApprovedProposal), Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let GetProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.get_proposal_by_id(proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
 
-        // Index approved proposal
-        self.storage
-            .save_approval(
-                event_id,
-                InternalApproval {
-                    proposal_id,
-                    policy_id,
-                    public_key: keys.public_key(),
-                    approval: approved_proposal.clone(),
-                    timestamp,
-                },
-            )
-            .await;
+        let signer: Signer = self.get_signer_by_id(signer_id).await?;
+        if policy
+            .search_used_signers(std::iter::once(signer.clone()))
+            .next()
+            .is_none()
+        {
+            return Err(Error::SignerNotInPolicy);
+        }
+        if signer.signer_type() != SignerType::Seed || signer.fingerprint() != self.fingerprint() {
+            return Err(Error::SignerNotLocal(signer.signer_type()));
+        }
 
-        Ok((event_id, approved_proposal))
+        self.check_before_approval(proposal_id, acknowledge_fee, force)
+            .await?;
+
+        let seed: Seed = self.keechain.read().seed(password)?;
+        let approved_proposal = proposal.approve_with_signer(&seed, &signer, self.network)?;
+        self.audit_signing(
+            match &proposal {
+                Proposal::ProofOfReserve { .. } => SigningKind::ProofOfReserve,
+                _ => SigningKind::Approval,
+            },
+            proposal_id.to_hex(),
+            &approved_proposal.psbt().serialize(),
+        )
+        .await?;
+
+        self.publish_approval(proposal_id, policy_id, approved_proposal)
+            .await
     }
 
+    /// Approve proposal with a PSBT signed externally (ex. airgapped signer)
+    ///
+    /// The PSBT must spend the same unsigned transaction of the proposal and contain new valid
+    /// signatures of the vault keys: only these signatures are published in the approval.
     pub async fn approve_with_signed_psbt(
         &self,
         proposal_id: EventId,
         signed_psbt: PartiallySignedTransaction,
     ) -> Result<(EventId, ApprovedProposal), Error> {
-        let keys: &Keys = self.keys();
-
         // Get proposal and policy
         let GetProposal {
             policy_id,
@@ -1359,6 +3678,19 @@ impl SmartVaults {
 
         let approved_proposal = proposal.approve_with_signed_psbt(signed_psbt)?;
 
+        self.publish_approval(proposal_id, policy_id, approved_proposal)
+            .await
+    }
+
+    /// Publish and index an approval
+    async fn publish_approval(
+        &self,
+        proposal_id: EventId,
+        policy_id: EventId,
+        approved_proposal: ApprovedProposal,
+    ) -> Result<(EventId, ApprovedProposal), Error> {
+        let keys: &Keys = self.keys();
+
         // Get shared keys
         let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
 
@@ -1376,7 +3708,7 @@ impl SmartVaults {
         let timestamp = event.created_at;
 
         // Publish the event
-        let event_id = self.client.send_event(event).await?;
+        let event_id = self.send_critical_event(event).await?;
 
         // Index approved proposal
         self.storage
@@ -1391,10 +3723,46 @@ impl SmartVaults {
                 },
             )
             .await;
+        self.check_proposal_ready(proposal_id).await?;
 
         Ok((event_id, approved_proposal))
     }
 
+    /// Known signers (mine) participating in the policy of the proposal, with a flag telling
+    /// whether they have already approved it
+    pub async fn get_proposal_signers_status(
+        &self,
+        proposal_id: EventId,
+    ) -> Result<Vec<(GetSigner, bool)>, Error> {
+        let GetProposal { policy_id, .. } = self.get_proposal_by_id(proposal_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
+        let approvals: Vec<ApprovedProposal> = self
+            .storage
+            .approvals_by_proposal_id(&proposal_id)
+            .await?
+            .approved_proposals;
+
+        let mut list: Vec<(GetSigner, bool)> = Vec::new();
+        for GetSigner { signer_id, signer } in self.get_signers().await.into_iter() {
+            if policy
+                .search_used_signers(std::iter::once(signer.clone()))
+                .next()
+                .is_none()
+            {
+                continue;
+            }
+            let mut approved: bool = false;
+            for approval in approvals.iter() {
+                if approval.is_signed_by(&signer)? {
+                    approved = true;
+                    break;
+                }
+            }
+            list.push((GetSigner { signer_id, signer }, approved));
+        }
+        Ok(list)
+    }
+
     // pub async fn approve_with_hwi_signer(
     // &self,
     // proposal_id: EventId,
@@ -1469,6 +3837,21 @@ impl SmartVaults {
         }
     }
 
+    /// Broadcast a transaction with the configured backend
+    async fn broadcast_tx(&self, tx: &Transaction) -> Result<(), Error> {
+        match self.config.backend().await {
+            Backend::Electrum => {
+                let blockchain = self.blockchain().await?;
+                blockchain.transaction_broadcast(tx)?;
+            }
+            Backend::BitcoinCore { url, auth } => {
+                let client = manager::bitcoind_client(&url, auth)?;
+                client.send_raw_transaction(tx)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Finalize [`Proposal`]
     pub async fn finalize(&self, proposal_id: EventId) -> Result<CompletedProposal, Error> {
         // Get PSBTs
@@ -1487,8 +3870,7 @@ impl SmartVaults {
 
         // Broadcast
         if let CompletedProposal::Spending { tx, .. } = &completed_proposal {
-            let blockchain = self.blockchain().await?;
-            blockchain.transaction_broadcast(tx)?;
+            self.broadcast_tx(tx).await?;
 
             // Try insert transactions into wallet (without wait for the next sync)
             let txid: Txid = tx.txid();
@@ -1524,7 +3906,7 @@ impl SmartVaults {
         let timestamp = event.created_at;
 
         // Publish the event
-        let event_id = self.client.send_event(event).await?;
+        let event_id = self.send_critical_event(event).await?;
 
         // Delete the proposal
         if let Err(e) = self.delete_proposal_by_id(proposal_id).await {
@@ -1570,7 +3952,7 @@ impl SmartVaults {
         // Publish proposal with `shared_key` so every owner can delete it
         let event = EventBuilder::new(PROPOSAL_KIND, content, tags).to_event(&shared_key)?;
         let timestamp = event.created_at;
-        let proposal_id = self.client.send_event(event).await?;
+        let proposal_id = self.send_critical_event(event).await?;
 
         // Send DM msg
         // TODO: send withoud wait for OK
@@ -1619,6 +4001,19 @@ impl SmartVaults {
         self.manager.get_balance(policy_id).await.ok()
     }
 
+    /// Flag incoming transaction if any output is in the screening list
+    fn screen_incoming_tx(&self, tx: &TransactionDetails) -> ScreeningResult {
+        if tx.received > tx.sent {
+            for txout in tx.output.iter() {
+                let result = self.screen_script(&txout.script_pubkey);
+                if result.is_flagged() {
+                    return result;
+                }
+            }
+        }
+        ScreeningResult::Clear
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_txs(&self, policy_id: EventId) -> Result<BTreeSet<GetTransaction>, Error> {
         let wallet: SmartVaultsWallet = self.manager.wallet(policy_id).await?;
@@ -1629,6 +4024,7 @@ impl SmartVaults {
             self.storage.get_addresses_labels(policy_id).await;
 
         let block_explorer = self.config.block_explorer().await.ok();
+        let outgoing: HashMap<Txid, bool> = self.db.get_outgoing_txs(policy_id).await?;
 
         let mut list: BTreeSet<GetTransaction> = BTreeSet::new();
 
@@ -1649,9 +4045,13 @@ impl SmartVaults {
                 descriptions.get(&txid).cloned()
             };
 
+            let screening: ScreeningResult = self.screen_incoming_tx(&tx);
+
             list.insert(GetTransaction {
                 policy_id,
                 label,
+                screening,
+                unexpected: outgoing.get(&txid) == Some(&false),
                 tx,
                 block_explorer: block_explorer
                     .as_ref()
@@ -1693,26 +4093,96 @@ impl SmartVaults {
 
         let block_explorer = self.config.block_explorer().await.ok();
 
+        let screening: ScreeningResult = self.screen_incoming_tx(&tx);
+        let unexpected: bool =
+            self.db.get_outgoing_txs(policy_id).await?.get(&txid) == Some(&false);
+
         Ok(GetTransaction {
             policy_id,
             tx,
             label,
+            screening,
+            unexpected,
             block_explorer: block_explorer
                 .as_ref()
                 .map(|url| format!("{url}/tx/{txid}")),
         })
     }
 
+    /// Get detailed view of a transaction: inputs, outputs, fee and attached labels
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_tx_details(&self, policy_id: EventId, txid: Txid) -> Result<TxDetails, Error> {
+        let wallet: SmartVaultsWallet = self.manager.wallet(policy_id).await?;
+        let tx: TransactionDetails = wallet.get_tx(txid).await?;
+
+        let mut inputs: Vec<TxInputDetails> = Vec::with_capacity(tx.input.len());
+        for txin in tx.input.iter() {
+            let prevout: Option<TxOut> = wallet.get_txout(txin.previous_output).await;
+            let is_mine: bool = match &prevout {
+                Some(txout) => wallet.is_mine(&txout.script_pubkey).await,
+                None => false,
+            };
+            inputs.push(TxInputDetails {
+                previous_output: txin.previous_output,
+                value: prevout.map(|txout| txout.value),
+                is_mine,
+            });
+        }
+
+        let mut labels: BTreeMap<String, Label> =
+            self.storage.get_labels_by_txid(policy_id, txid).await;
+        let script_labels: HashMap<ScriptBuf, Label> =
+            self.storage.get_addresses_labels(policy_id).await;
+        let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+
+        let mut outputs: Vec<TxOutputDetails> = Vec::with_capacity(tx.output.len());
+        for txout in tx.output.iter() {
+            let keychain: Option<KeychainKind> = wallet.keychain_of(&txout.script_pubkey).await;
+            let address: Option<Address> =
+                Address::from_script(&txout.script_pubkey, self.network).ok();
+
+            if let (Some(address), Some(label)) =
+                (address.as_ref(), script_labels.get(&txout.script_pubkey))
+            {
+                let identifier: String =
+                    LabelData::Address(Address::new(self.network, address.payload.clone()))
+                        .generate_identifier(&shared_key)?;
+                labels.insert(identifier, label.clone());
+            }
+
+            outputs.push(TxOutputDetails {
+                address: address.map(|a| Address::new(self.network, a.payload)),
+                value: txout.value,
+                is_mine: keychain.is_some(),
+                is_change: keychain == Some(KeychainKind::Internal),
+            });
+        }
+
+        let block_explorer = self.config.block_explorer().await.ok();
+
+        Ok(TxDetails {
+            policy_id,
+            txid,
+            confirmation_time: tx.confirmation_time,
+            fee: tx.fee.amount,
+            fee_rate: tx.fee.rate,
+            inputs,
+            outputs,
+            labels,
+            block_explorer: block_explorer.map(|url| format!("{url}/tx/{txid}")),
+        })
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_address(
         &self,
         policy_id: EventId,
         index: AddressIndex,
     ) -> Result<GetAddress, Error> {
-        let address = self.manager.get_address(policy_id, index).await?.address;
+        let info = self.manager.get_address(policy_id, index).await?;
 
         let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
-        let address = Address::new(self.network, address.payload);
+        let address = Address::new(self.network, info.address.payload);
         let identifier: String =
             LabelData::Address(address.clone()).generate_identifier(&shared_key)?;
         let label = self
@@ -1721,7 +4191,21 @@ impl SmartVaults {
             .await
             .ok()
             .map(|l| l.text());
-        Ok(GetAddress { address, label })
+        let script: ScriptBuf = address.payload.script_pubkey();
+        let balance: u64 = self
+            .manager
+            .get_scripts_balances(policy_id, &[script.clone()])
+            .await?
+            .get(&script)
+            .copied()
+            .unwrap_or_default();
+        Ok(GetAddress {
+            address,
+            index: info.index,
+            used: self.manager.is_address_used(policy_id, info.index).await?,
+            balance,
+            label,
+        })
     }
 
     #[tracing::instrument(skip_all, level = "trace")]
@@ -1729,21 +4213,68 @@ impl SmartVaults {
         self.get_address(policy_id, AddressIndex::LastUnused).await
     }
 
+    /// Get the external addresses, up to 20 past the last unused one
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_addresses(&self, policy_id: EventId) -> Result<Vec<GetAddress>, Error> {
+        let addresses: Vec<IndexedAddress> = self.manager.get_addresses(policy_id).await?;
+        self.compose_addresses(policy_id, addresses).await
+    }
+
+    /// Derive `count` external addresses starting at `from_index`, also beyond the gap limit
+    ///
+    /// Funds received by the addresses at or after [`SmartVaults::gap_limit_boundary`] are not
+    /// detected until the gap limit is raised (see [`Config::set_gap_limit`]).
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_addresses_range(
+        &self,
+        policy_id: EventId,
+        from_index: u32,
+        count: usize,
+    ) -> Result<Vec<GetAddress>, Error> {
+        let addresses: Vec<IndexedAddress> = self
+            .manager
+            .get_addresses_range(policy_id, from_index, count)
+            .await?;
+        self.compose_addresses(policy_id, addresses).await
+    }
+
+    /// Index of the first external address past the gap limit (after the last used one)
+    pub async fn gap_limit_boundary(&self, policy_id: EventId) -> Result<u32, Error> {
+        let first_unused: u32 = match self.manager.last_used_address_index(policy_id).await? {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        let gap_limit: u32 = self.config.gap_limit().await.try_into().unwrap_or(u32::MAX);
+        Ok(first_unused.saturating_add(gap_limit))
+    }
+
+    async fn compose_addresses(
+        &self,
+        policy_id: EventId,
+        addresses: Vec<IndexedAddress>,
+    ) -> Result<Vec<GetAddress>, Error> {
         let script_labels: HashMap<ScriptBuf, Label> =
             self.storage.get_addresses_labels(policy_id).await;
-        Ok(self
-            .manager
-            .get_addresses(policy_id)
-            .await?
+        let balances: HashMap<ScriptBuf, u64> =
+            self.manager.get_addresses_balances(policy_id).await?;
+        Ok(addresses
             .into_iter()
-            .map(|address| GetAddress {
-                label: script_labels
-                    .get(&address.payload.script_pubkey())
-                    .map(|l| l.text()),
-                address,
-            })
+            .map(
+                |IndexedAddress {
+                     index,
+                     address,
+                     used,
+                 }| {
+                    let script: ScriptBuf = address.payload.script_pubkey();
+                    GetAddress {
+                        index,
+                        used,
+                        balance: balances.get(&script).copied().unwrap_or_default(),
+                        label: script_labels.get(&script).map(|l| l.text()),
+                        address,
+                    }
+                },
+            )
             .collect())
     }
 
@@ -1755,6 +4286,26 @@ impl SmartVaults {
         Ok(self.manager.get_addresses_balances(policy_id).await?)
     }
 
+    /// Get balances of a page of addresses
+    ///
+    /// Allow to show the address list (see [`SmartVaults::get_addresses`]) immediately
+    /// and load the balances incrementally.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_addresses_balances_page(
+        &self,
+        policy_id: EventId,
+        addresses: &[GetAddress],
+    ) -> Result<HashMap<ScriptBuf, u64>, Error> {
+        let scripts: Vec<ScriptBuf> = addresses
+            .iter()
+            .map(|a| a.address.payload.script_pubkey())
+            .collect();
+        Ok(self
+            .manager
+            .get_scripts_balances(policy_id, &scripts)
+            .await?)
+    }
+
     /// Get wallet UTXOs
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_utxos(&self, policy_id: EventId) -> Result<Vec<GetUtxo>, Error> {
@@ -1812,23 +4363,86 @@ impl SmartVaults {
     }
 
     pub async fn rebroadcast_all_events(&self) -> Result<(), Error> {
+        self.rebroadcast_all_events_with_progress(None, CancellationToken::default())
+            .await
+    }
+
+    /// Rebroadcast all events, reporting the sent events
+    pub async fn rebroadcast_all_events_with_progress(
+        &self,
+        progress: Option<ProgressCallback>,
+        token: CancellationToken,
+    ) -> Result<(), Error> {
+        let task = Task::new(progress, token);
         let pool = self.client.pool();
         let events: Vec<Event> = self
             .client
             .database()
             .query(vec![Filter::new()], Order::Asc)
             .await?;
-        for event in events.into_iter() {
+        let total: usize = events.len();
+        task.report(Progress::new(0, total));
+        for (index, event) in events.into_iter().enumerate() {
+            task.checkpoint()?;
             pool.send_msg(
                 ClientMessage::event(event),
                 RelaySendOptions::new().skip_send_confirmation(true),
             )
             .await?;
+            task.report(Progress::new(index + 1, total));
         }
-        // TODO: save last rebroadcast timestamp
+        self.db.set_last_rebroadcast(Timestamp::now()).await?;
         Ok(())
     }
 
+    /// Rebroadcast a single event to the write relays
+    pub async fn rebroadcast_event(&self, event_id: EventId) -> Result<BatchSendReport, Error> {
+        let event: Event = self.client.database().event_by_id(event_id).await?;
+        self.publish_events(vec![event]).await
+    }
+
+    /// Re-submit to the backend the transactions of the completed spending proposals not
+    /// confirmed yet (ex. evicted from the mempool)
+    ///
+    /// Return the txids of the re-submitted transactions.
+    pub async fn rebroadcast_unconfirmed_txs(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Vec<Txid>, Error> {
+        let wallet = self.manager.wallet(policy_id).await?;
+        let mut txids: Vec<Txid> = Vec::new();
+        for InternalCompletedProposal {
+            policy_id: id,
+            proposal,
+            ..
+        } in self.storage.completed_proposals().await.into_values()
+        {
+            if id != policy_id {
+                continue;
+            }
+
+            if let CompletedProposal::Spending { tx, .. } = proposal {
+                let txid: Txid = tx.txid();
+                let confirmed: bool = match wallet.get_tx(txid).await {
+                    Ok(details) => details.confirmation_time.is_confirmed(),
+                    Err(_) => false,
+                };
+                if confirmed {
+                    continue;
+                }
+
+                match self.broadcast_tx(&tx).await {
+                    Ok(..) => {
+                        tracing::info!("Tx {txid} rebroadcasted");
+                        txids.push(txid);
+                    }
+                    Err(e) => tracing::warn!("Impossible to rebroadcast tx {txid}: {e}"),
+                }
+            }
+        }
+        Ok(txids)
+    }
+
     pub async fn rebroadcast_to<S>(&self, url: S) -> Result<(), Error>
     where
         S: Into<String>,
@@ -1852,30 +4466,66 @@ impl SmartVaults {
         Ok(())
     }
 
-    pub async fn republish_shared_key_for_policy(&self, policy_id: EventId) -> Result<(), Error> {
+    pub async fn republish_shared_key_for_policy(
+        &self,
+        policy_id: EventId,
+    ) -> Result<BatchSendReport, Error> {
         let keys: &Keys = self.keys();
         let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
         let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
         // Publish the shared key
+        let mut events: Vec<Event> = Vec::with_capacity(public_keys.len());
         for public_key in public_keys.into_iter() {
-            let encrypted_shared_key = nips::nip04::encrypt(
-                keys.secret_key()?,
-                &public_key,
-                shared_key.secret_key()?.display_secret().to_string(),
-            )?;
-            let event: Event = EventBuilder::new(
-                SHARED_KEY_KIND,
-                encrypted_shared_key,
-                [Tag::event(policy_id), Tag::public_key(public_key)],
-            )
-            .to_event(keys)?;
-            let event_id: EventId = event.id;
+            let event: Event = EventBuilder::shared_key(keys, &shared_key, &public_key, policy_id)?;
+            events.push(event);
+        }
+        self.publish_events(events).await
+    }
+
+    /// Rotate the shared key of a policy (i.e. the nostr key of a member was compromised)
+    ///
+    /// The policy is republished with a new shared key, sent to each of `nostr_pubkeys`, and the
+    /// shared key events of the old policy published by me are deleted. The old shared key is kept
+    /// in the database, so the old proposals remain readable.
+    ///
+    /// Return the ID of the new policy.
+    pub async fn rotate_shared_key(
+        &self,
+        policy_id: EventId,
+        nostr_pubkeys: Vec<PublicKey>,
+    ) -> Result<EventId, Error> {
+        let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+        let InternalPolicy { policy, .. } = self.storage.vault(&policy_id).await?;
 
-            // TODO: use send_batch_event method from nostr-sdk
+        // Republish the policy with a new shared key
+        let new_policy_id: EventId = self.publish_policy(policy, nostr_pubkeys).await?;
+        self.db
+            .save_rotated_shared_key(policy_id, &shared_key, new_policy_id)
+            .await?;
+        tracing::info!("Shared key of policy {policy_id} rotated: new policy {new_policy_id}");
+
+        // Delete the old shared key events published by me
+        let filter: Filter = Filter::new()
+            .kind(SHARED_KEY_KIND)
+            .author(self.keys.public_key())
+            .event(policy_id);
+        let tags: Vec<Tag> = self
+            .client
+            .database()
+            .event_ids_by_filters(vec![filter], Order::Desc)
+            .await?
+            .into_iter()
+            .map(Tag::event)
+            .collect();
+        if !tags.is_empty() {
+            let event = EventBuilder::new(Kind::EventDeletion, "", tags).to_event(&self.keys)?;
             self.client.send_event(event).await?;
-            tracing::info!("Published shared key for {public_key} at event {event_id}");
         }
-        Ok(())
+
+        self.storage.mark_rotated(policy_id).await;
+        self.manager.unload_policy(policy_id).await?;
+
+        Ok(new_policy_id)
     }
 
     #[tracing::instrument(skip_all, level = "trace")]
@@ -1889,6 +4539,7 @@ impl SmartVaults {
             policy.name(),
             policy.description(),
             policy.descriptor(),
+            policy.change_descriptor(),
             public_keys,
         ))
     }
@@ -1903,6 +4554,31 @@ impl SmartVaults {
         Ok(())
     }
 
+    /// Restore a policy from a [`PolicyBackup`]
+    ///
+    /// A new shared key is generated and distributed to the public keys of the backup.
+    /// Error if the descriptor is for another network or if the policy already exists.
+    pub async fn restore_policy_backup(&self, backup: PolicyBackup) -> Result<EventId, Error> {
+        let descriptor: Descriptor<String> = backup.descriptor();
+
+        util::network::check_descriptor_network(&descriptor, self.network)?;
+
+        for (policy_id, InternalPolicy { policy, .. }) in self.storage.vaults().await.into_iter() {
+            if policy.descriptor() == descriptor {
+                return Err(Error::PolicyAlreadyExists(policy_id));
+            }
+        }
+
+        self.save_policy_with_change_descriptor(
+            backup.name().unwrap_or_default(),
+            backup.description().unwrap_or_default(),
+            descriptor.to_string(),
+            backup.change_descriptor().map(|d| d.to_string()),
+            backup.public_keys(),
+        )
+        .await
+    }
+
     pub async fn get_known_profiles(&self) -> Result<BTreeSet<Profile>, Error> {
         let filter = Filter::new().kind(Kind::Metadata);
         Ok(self
@@ -1918,3 +4594,682 @@ impl SmartVaults {
             .collect())
     }
 }
+
+fn check_passphrase(seed: &Seed, passphrase: Option<&str>) -> Result<(), Error> {
+    let stored: String = seed.passphrase().map(|p| p.to_string()).unwrap_or_default();
+    if stored == passphrase.unwrap_or_default() {
+        Ok(())
+    } else {
+        Err(Error::PassphraseNotMatch)
+    }
+}
+
+/// Temporary base path, removed on drop
+#[cfg(test)]
+pub(crate) struct TempBasePath(PathBuf);
+
+#[cfg(test)]
+impl TempBasePath {
+    pub fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!(
+            "smartvaults-{name}-{}",
+            Keys::generate().public_key()
+        )))
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempBasePath {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TempBasePath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempBasePath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use smartvaults_core::bdk::chain::BlockId;
+    use smartvaults_core::bitcoin::absolute::LockTime;
+    use smartvaults_core::bitcoin::hashes::Hash;
+    use smartvaults_core::bitcoin::BlockHash;
+
+    use super::*;
+
+    /// Save and load a vault of the default signer of `client`, funded with an unconfirmed UTXO
+    async fn funded_vault(client: &SmartVaults, policy_id: EventId, value: u64) -> Policy {
+        let descriptor = format!(
+            "tr({})",
+            client.default_signer.descriptor_public_key().unwrap()
+        );
+        funded_vault_with_descriptor(client, policy_id, descriptor, value).await
+    }
+
+    /// Save and load a vault of `descriptor`, funded with an unconfirmed UTXO
+    async fn funded_vault_with_descriptor(
+        client: &SmartVaults,
+        policy_id: EventId,
+        descriptor: String,
+        value: u64,
+    ) -> Policy {
+        let policy = Policy::from_descriptor("Vault", "", descriptor, client.network)
+            .unwrap()
+            .with_derived_change_descriptor()
+            .unwrap();
+        client
+            .storage
+            .save_shared_key(policy_id, Keys::generate())
+            .await;
+        client
+            .storage
+            .save_vault(
+                policy_id,
+                InternalPolicy {
+                    policy: policy.clone(),
+                    public_keys: vec![client.keys.public_key()],
+                },
+            )
+            .await;
+        client
+            .manager
+            .load_policy(policy_id, policy.clone(), None, None)
+            .await
+            .unwrap();
+
+        let address = client
+            .manager
+            .get_address(policy_id, AddressIndex::New)
+            .await
+            .unwrap();
+        client
+            .manager
+            .insert_tx(
+                policy_id,
+                Transaction {
+                    version: 1,
+                    lock_time: LockTime::ZERO,
+                    input: Vec::new(),
+                    output: vec![TxOut {
+                        value,
+                        script_pubkey: address.script_pubkey(),
+                    }],
+                },
+                ConfirmationTime::Unconfirmed { last_seen: 0 },
+            )
+            .await
+            .unwrap();
+
+        policy
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_rename_while_syncing() {
+        let base_path = TempBasePath::new("rename");
+        let network = Network::Testnet;
+
+        let client = SmartVaults::generate_offline(&base_path, "first", network)
+            .await
+            .unwrap();
+
+        // Another keychain, to test the collision
+        let other = SmartVaults::generate_offline(&base_path, "other", network)
+            .await
+            .unwrap();
+        drop(other);
+
+        // Drive the sync event handler while renaming
+        let stop = Arc::new(AtomicBool::new(false));
+        let c = client.clone();
+        let s = stop.clone();
+        let handler = tokio::spawn(async move {
+            let database = c.client.database();
+            let mut handled: usize = 0;
+            while !s.load(Ordering::SeqCst) || handled == 0 {
+                let event = EventBuilder::text_note(format!("event {handled}"), [])
+                    .to_event(&c.keys)
+                    .unwrap();
+                // The relay pool saves the events before notifying them
+                database.save_event(&event).await.unwrap();
+                c.handle_event(event).await.unwrap();
+                handled += 1;
+            }
+            handled
+        });
+
+        assert!(matches!(
+            client.rename("other"),
+            Err(Error::KeychainAlreadyExists(..))
+        ));
+        assert!(matches!(
+            client.rename("../escape"),
+            Err(Error::InvalidKeychainName(..))
+        ));
+
+        client.rename("second").unwrap();
+        assert_eq!(client.name().as_deref(), Some("second"));
+
+        stop.store(true, Ordering::SeqCst);
+        assert!(handler.await.unwrap() > 0);
+
+        let mut keychains = SmartVaults::list_keychains(&base_path, network).unwrap();
+        keychains.sort();
+        assert_eq!(
+            keychains,
+            vec![String::from("other"), String::from("second")]
+        );
+
+        // Db still usable after the rename
+        assert!(client.get_policies().await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_verify_mnemonic() {
+        let base_path = TempBasePath::new("verify");
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "verify",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(Some(String::from("passphrase"))),
+            Network::Testnet,
+        )
+        .await
+        .unwrap();
+
+        let mnemonic: Mnemonic = client.keychain("test").unwrap().seed().mnemonic();
+        client
+            .verify_mnemonic("test", &mnemonic, Some("passphrase"))
+            .unwrap();
+        assert!(matches!(
+            client.verify_mnemonic("test", &mnemonic, None),
+            Err(Error::PassphraseNotMatch)
+        ));
+        let other: Mnemonic = mnemonic::generate(WordCount::W12, Language::English).unwrap();
+        assert!(matches!(
+            client.verify_mnemonic("test", &other, Some("passphrase")),
+            Err(Error::MnemonicNotMatch)
+        ));
+
+        let positions: Vec<usize> = client.mnemonic_challenge("test", 3).unwrap();
+        assert_eq!(positions.len(), 3);
+        let words: Vec<&str> = mnemonic.word_iter().collect();
+        let mut answers: Vec<(usize, String)> = positions
+            .iter()
+            .map(|p| (*p, words[p - 1].to_uppercase()))
+            .collect();
+        client
+            .verify_mnemonic_words("test", &answers, Some("passphrase"))
+            .unwrap();
+        answers[0].1 = String::from("wrong");
+        assert!(matches!(
+            client.verify_mnemonic_words("test", &answers, Some("passphrase")),
+            Err(Error::MnemonicNotMatch)
+        ));
+        assert!(matches!(
+            client.verify_mnemonic_words("test", &[(13, String::from("abandon"))], None),
+            Err(Error::MnemonicNotMatch)
+        ));
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_contacts_metadata_cache() {
+        let base_path = TempBasePath::new("contacts");
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "contacts",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(None),
+            Network::Testnet,
+        )
+        .await
+        .unwrap();
+        assert!(client
+            .get_contacts_with_metadata()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let known = Keys::generate();
+        let unknown = Keys::generate();
+        let database = client.client.database();
+        let contact_list = EventBuilder::contact_list([
+            Contact::new::<String>(known.public_key(), None, None),
+            Contact::new::<String>(unknown.public_key(), None, None),
+        ])
+        .to_event(client.keys())
+        .unwrap();
+        database.save_event(&contact_list).await.unwrap();
+        let metadata = Metadata::new().name("known");
+        let metadata_event = EventBuilder::metadata(&metadata).to_event(&known).unwrap();
+        database.save_event(&metadata_event).await.unwrap();
+        let signaling = EventBuilder::key_agent_signaling(&unknown, Network::Testnet).unwrap();
+        database.save_event(&signaling).await.unwrap();
+
+        let contacts: HashMap<PublicKey, GetContact> = client
+            .get_contacts_with_metadata()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.profile.public_key(), c))
+            .collect();
+        assert_eq!(contacts.len(), 2);
+
+        let contact = contacts.get(&known.public_key()).unwrap();
+        assert_eq!(contact.profile.metadata(), metadata);
+        assert_eq!(contact.updated_at, Some(metadata_event.created_at));
+        assert!(!contact.is_stale());
+        assert_eq!(contact.role, UserRole::User);
+
+        let contact = contacts.get(&unknown.public_key()).unwrap();
+        assert_eq!(contact.profile.metadata(), Metadata::default());
+        assert_eq!(contact.updated_at, None);
+        assert!(contact.is_stale());
+        assert_eq!(contact.role, UserRole::KeyAgent);
+        assert!(!contact.verified);
+
+        // Never announced as key agent
+        assert_eq!(client.my_role().await.unwrap(), UserRole::User);
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_network_mismatch_guards() {
+        let base_path = TempBasePath::new("network");
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "network",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(None),
+            Network::Testnet,
+        )
+        .await
+        .unwrap();
+
+        // Mainnet signer
+        let mnemonic = Mnemonic::from_str(
+            "possible suffer flavor boring essay zoo collect stairs day cabbage wasp tackle",
+        )
+        .unwrap();
+        let signer = Signer::from_seed(
+            "Signer",
+            None,
+            Seed::from_mnemonic(mnemonic),
+            None,
+            Network::Bitcoin,
+        )
+        .unwrap();
+        assert!(matches!(
+            client.save_signer(signer).await,
+            Err(Error::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Bitcoin
+            })
+        ));
+
+        // Mainnet policy
+        let descriptor = "tr([7c997e72/86'/0'/784923']xpub6DGQCZUmD4kdGDj8ttgba5Jc6pUSkFWaMwB1jedmzer1BtKDdef18k3cWwC9k7HfJGci7Q9S5KTRD9bBn4JZm3xPcDvidkSXvZ6pg4now57/0/*)";
+        assert!(matches!(
+            client
+                .save_policy(
+                    "Name",
+                    "Description",
+                    descriptor,
+                    vec![client.keys().public_key()]
+                )
+                .await,
+            Err(Error::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Bitcoin
+            })
+        ));
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_publish_spend_preview() {
+        let base_path = TempBasePath::new("preview");
+
+        let client = SmartVaults::generate_offline(&base_path, "preview", Network::Testnet)
+            .await
+            .unwrap();
+        let policy_id = EventId::from_slice(&[1; 32]).unwrap();
+        let policy = funded_vault(&client, policy_id, 10_000).await;
+
+        let preview: SpendPreview = client
+            .build_spend_preview(
+                policy_id,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap(),
+                Amount::Custom(1_000),
+                "Preview",
+                FeeRate::Rate(1.0),
+                None,
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(preview.policy_id, policy_id);
+        assert_eq!(preview.proposal.psbt(), preview.psbt);
+
+        // Nothing frozen by the preview
+        assert!(client.storage.get_frozen_utxos(&policy_id).await.is_empty());
+
+        // The previewed proposal is checked and published as is: only the publishing fails,
+        // without relays
+        assert!(matches!(
+            client.publish_spend_preview(preview.clone()).await,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+
+        // External recipient: never auto-completed
+        assert!(matches!(
+            client
+                .publish_spend_preview_with_auto_complete("test", preview.clone())
+                .await,
+            Err(Error::AutoCompleteNotPossible(..))
+        ));
+
+        // PSBT not matching the proposal
+        let mut tampered = preview.clone();
+        tampered.psbt.unsigned_tx.output.pop();
+        assert!(matches!(
+            client.publish_spend_preview(tampered).await,
+            Err(Error::UnexpectedProposal)
+        ));
+
+        // Selected UTXO no longer spendable
+        let mut psbt = preview.psbt.clone();
+        psbt.unsigned_tx.input[0].previous_output.vout = 99;
+        let outpoint: OutPoint = psbt.unsigned_tx.input[0].previous_output;
+        let mut spent = preview;
+        spent.proposal = Proposal::spending(
+            policy.descriptor(),
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap(),
+            1_000,
+            "Preview",
+            psbt.clone(),
+            None,
+        );
+        spent.psbt = psbt;
+        assert!(matches!(
+            client.publish_spend_preview(spent).await,
+            Err(Error::UtxoNotInPolicy(o)) if o == outpoint
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_spend_with_auto_complete() {
+        let base_path = TempBasePath::new("auto-complete");
+
+        let client = SmartVaults::generate_offline(&base_path, "auto", Network::Testnet)
+            .await
+            .unwrap();
+        let policy_id = EventId::from_slice(&[1; 32]).unwrap();
+        funded_vault(&client, policy_id, 10_000).await;
+
+        // Another local vault, without keys of the seed
+        let other_id = EventId::from_slice(&[2; 32]).unwrap();
+        let descriptor = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+        funded_vault_with_descriptor(&client, other_id, descriptor.to_string(), 10_000).await;
+        let to_other = client
+            .get_address(other_id, AddressIndex::New)
+            .await
+            .unwrap()
+            .address;
+        let to_own = client
+            .get_address(policy_id, AddressIndex::New)
+            .await
+            .unwrap()
+            .address;
+
+        let client_ref = &client;
+        let spend = move |policy_id: EventId, address: Address<NetworkUnchecked>| {
+            client_ref.spend_with_auto_complete(
+                "test",
+                policy_id,
+                address,
+                Amount::Custom(1_000),
+                "Auto",
+                FeeRate::Rate(1.0),
+                None,
+                None,
+                false,
+                None,
+            )
+        };
+
+        // Destination not a local vault
+        assert!(matches!(
+            spend(
+                policy_id,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap()
+            )
+            .await,
+            Err(Error::AutoCompleteNotPossible(reason)) if reason == "destination is not a local vault"
+        ));
+
+        // No local signer in the policy
+        assert!(matches!(
+            spend(other_id, to_own).await,
+            Err(Error::AutoCompleteNotPossible(reason)) if reason == "local signers can't satisfy the policy"
+        ));
+
+        // The local signers are enough: only the publishing fails, without relays
+        assert!(matches!(
+            spend(policy_id, to_other.clone()).await,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+
+        // External recipient added to a proposal paying a local vault
+        let mut preview: SpendPreview = client
+            .build_spend_preview(
+                policy_id,
+                to_other.clone(),
+                Amount::Custom(1_000),
+                "Auto",
+                FeeRate::Rate(1.0),
+                None,
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        let external = Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")
+            .unwrap()
+            .assume_checked();
+        preview.psbt.unsigned_tx.output.push(TxOut {
+            value: 1_000,
+            script_pubkey: external.script_pubkey(),
+        });
+        preview.psbt.outputs.push(Default::default());
+        preview.proposal = Proposal::spending(
+            preview.proposal.descriptor(),
+            to_other,
+            1_000,
+            "Auto",
+            preview.psbt.clone(),
+            None,
+        );
+        assert!(matches!(
+            client
+                .publish_spend_preview_with_auto_complete("test", preview)
+                .await,
+            Err(Error::AutoCompleteNotPossible(reason)) if reason == "proposal has external recipients"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bump_fee() {
+        let base_path = TempBasePath::new("bump-fee");
+
+        let client = SmartVaults::generate_offline(&base_path, "bump", Network::Testnet)
+            .await
+            .unwrap();
+        let policy_id = EventId::from_slice(&[1; 32]).unwrap();
+        funded_vault(&client, policy_id, 10_000).await;
+
+        // Pending proposal
+        let proposal: Proposal = client
+            .build_spending_proposal(
+                policy_id,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap(),
+                Amount::Custom(1_000),
+                "Bump",
+                FeeRate::Rate(2.0),
+                None,
+                None,
+                false,
+                None,
+            )
+            .await
+            .unwrap();
+        let original_id = EventId::from_slice(&[2; 32]).unwrap();
+        let txid: Txid = proposal.psbt().unsigned_tx.txid();
+        client
+            .storage
+            .save_proposal(
+                original_id,
+                InternalProposal {
+                    policy_id,
+                    proposal: proposal.clone(),
+                    timestamp: Timestamp::now(),
+                },
+            )
+            .await;
+
+        // Same or lower rate
+        for rate in [1.0, 2.0] {
+            assert!(matches!(
+                client
+                    .bump_fee(policy_id, txid, FeeRate::Rate(rate), None)
+                    .await,
+                Err(Error::FeeBumpTooLow { .. })
+            ));
+        }
+
+        // Higher rate: only the publishing fails, without relays
+        assert!(matches!(
+            client
+                .bump_fee(policy_id, txid, FeeRate::Rate(5.0), None)
+                .await,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+
+        // The original is superseded only once the replacement confirms
+        let replacement_id = EventId::from_slice(&[3; 32]).unwrap();
+        let tx: Transaction = proposal.psbt().unsigned_tx;
+        client
+            .storage
+            .save_replacement(original_id, policy_id, replacement_id, tx.txid())
+            .await;
+        client
+            .manager
+            .insert_tx(
+                policy_id,
+                tx.clone(),
+                ConfirmationTime::Unconfirmed { last_seen: 0 },
+            )
+            .await
+            .unwrap();
+        client.mark_superseded_proposals().await.unwrap();
+        assert!(client.get_superseded_proposals().await.unwrap().is_empty());
+
+        let wallet = client.manager.wallet(policy_id).await.unwrap();
+        wallet
+            .insert_checkpoint(BlockId {
+                height: 100,
+                hash: BlockHash::all_zeros(),
+            })
+            .await
+            .unwrap();
+        client
+            .manager
+            .insert_tx(
+                policy_id,
+                tx,
+                ConfirmationTime::Confirmed {
+                    height: 100,
+                    time: 1_700_000_000,
+                },
+            )
+            .await
+            .unwrap();
+        client.mark_superseded_proposals().await.unwrap();
+        assert_eq!(
+            client.get_superseded_proposals().await.unwrap(),
+            HashMap::from([(original_id, replacement_id)])
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sync_filters_follow_shared_keys() {
+        use nostr_sdk::JsonUtil;
+
+        let base_path = TempBasePath::new("filters");
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "filters",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(None),
+            Network::Testnet,
+        )
+        .await
+        .unwrap();
+
+        let before: Vec<Filter> = client.sync_filters(Timestamp::now()).await;
+
+        // New policy shared key: its events are included without reconnecting
+        let shared_key = Keys::generate();
+        client
+            .storage
+            .save_shared_key(EventId::all_zeros(), shared_key.clone())
+            .await;
+        let after: Vec<Filter> = client.sync_filters(Timestamp::now()).await;
+        assert_eq!(after.len(), before.len() + 1);
+        assert!(after
+            .iter()
+            .any(|f| f.as_json().contains(&shared_key.public_key().to_string())));
+
+        // Refresh without relays: nothing to do, must not fail
+        client.refresh_subscriptions().await;
+
+        client.shutdown().await.unwrap();
+    }
+}
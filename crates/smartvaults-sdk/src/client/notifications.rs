@@ -0,0 +1,149 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Sync notifications with replay
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+use super::Message;
+use crate::constants::SYNC_NOTIFICATIONS_BUFFER_SIZE;
+
+/// Sync [`Message`] with its sequence number
+#[derive(Debug, Clone)]
+pub struct Notification {
+    /// Sequence number (starts from `1`)
+    pub seq: u64,
+    pub message: Message,
+}
+
+#[derive(Debug)]
+struct Buffer {
+    last_seq: u64,
+    notifications: VecDeque<Notification>,
+}
+
+/// Broadcast channel that keep the last [`SYNC_NOTIFICATIONS_BUFFER_SIZE`] messages,
+/// so subscribers can catch-up with the messages emitted before they subscribed.
+#[derive(Debug, Clone)]
+pub(crate) struct SyncChannel {
+    sender: Sender<Notification>,
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl SyncChannel {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel::<Notification>(capacity);
+        Self {
+            sender,
+            buffer: Arc::new(Mutex::new(Buffer {
+                last_seq: 0,
+                notifications: VecDeque::with_capacity(SYNC_NOTIFICATIONS_BUFFER_SIZE),
+            })),
+        }
+    }
+
+    /// Send message to subscribers and save it in the replay buffer
+    ///
+    /// Return the sequence number of the message.
+    pub fn send(&self, message: Message) -> u64 {
+        let mut buffer = self.buffer.lock();
+        buffer.last_seq += 1;
+        let notification = Notification {
+            seq: buffer.last_seq,
+            message,
+        };
+        if buffer.notifications.len() >= SYNC_NOTIFICATIONS_BUFFER_SIZE {
+            buffer.notifications.pop_front();
+        }
+        buffer.notifications.push_back(notification.clone());
+        // Sent while holding the lock: a new subscriber can't miss or receive twice a message
+        let _ = self.sender.send(notification);
+        buffer.last_seq
+    }
+
+    /// Sequence number of the last sent message (`0` if nothing was sent)
+    pub fn last_seq(&self) -> u64 {
+        self.buffer.lock().last_seq
+    }
+
+    /// Subscribe to new messages
+    ///
+    /// If `since` is set, the buffered messages with sequence number `>= since` are replayed first.
+    pub fn subscribe(&self, since: Option<u64>) -> SyncSubscription {
+        let buffer = self.buffer.lock();
+        let replay: VecDeque<Notification> = match since {
+            Some(since) => buffer
+                .notifications
+                .iter()
+                .filter(|n| n.seq >= since)
+                .cloned()
+                .collect(),
+            None => VecDeque::new(),
+        };
+        SyncSubscription {
+            replay,
+            receiver: self.sender.subscribe(),
+        }
+    }
+}
+
+/// Sync notifications subscription
+#[derive(Debug)]
+pub struct SyncSubscription {
+    replay: VecDeque<Notification>,
+    receiver: Receiver<Notification>,
+}
+
+impl SyncSubscription {
+    /// Receive the next notification (replayed ones first)
+    pub async fn recv(&mut self) -> Result<Notification, RecvError> {
+        match self.replay.pop_front() {
+            Some(notification) => Ok(notification),
+            None => self.receiver.recv().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+
+    #[test]
+    fn test_replay_from_seq() {
+        let channel = SyncChannel::new(16);
+
+        // Nobody is listening
+        assert_eq!(channel.send(Message::BlockHeightUpdated), 1);
+        assert_eq!(channel.send(Message::BlockHeightUpdated), 2);
+        assert_eq!(channel.send(Message::BlockHeightUpdated), 3);
+
+        let mut live = channel.subscribe(None);
+        let mut sub = channel.subscribe(Some(2));
+
+        assert_eq!(channel.send(Message::BlockHeightUpdated), 4);
+
+        assert_eq!(block_on(live.recv()).unwrap().seq, 4);
+        for seq in 2..=4 {
+            assert_eq!(block_on(sub.recv()).unwrap().seq, seq);
+        }
+        assert_eq!(channel.last_seq(), 4);
+    }
+
+    #[test]
+    fn test_buffer_size() {
+        let channel = SyncChannel::new(16);
+        for _ in 0..SYNC_NOTIFICATIONS_BUFFER_SIZE + 10 {
+            channel.send(Message::BlockHeightUpdated);
+        }
+        let sub = channel.subscribe(Some(0));
+        assert_eq!(sub.replay.len(), SYNC_NOTIFICATIONS_BUFFER_SIZE);
+        assert_eq!(sub.replay.front().unwrap().seq, 11);
+    }
+}
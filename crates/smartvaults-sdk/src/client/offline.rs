@@ -0,0 +1,438 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Offline co-signers
+//!
+//! The approval request is exported to an encrypted file (see [`ApprovalRequest`]), moved to the
+//! offline instance (ex. via USB) and signed with [`SmartVaults::sign_approval_request_offline`],
+//! if it matches a vault known by the offline instance.
+//! The response (or a bare signed PSBT, from any other wallet) is then imported and published as
+//! an approval.
+
+use std::fs;
+use std::path::Path;
+
+use nostr_sdk::EventId;
+use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+use smartvaults_core::proposal::ProposalType;
+use smartvaults_core::types::Seed;
+use smartvaults_core::{ApprovedProposal, Policy};
+use smartvaults_sdk_sqlite::model::SigningKind;
+
+use super::external_signer::read_psbt;
+use super::{Error, SmartVaults};
+use crate::types::{ApprovalRequest, ApprovalResponse, GetProposal};
+
+impl SmartVaults {
+    /// Export a self-contained approval request (proposal PSBT, policy descriptor and metadata)
+    /// for an offline co-signer
+    ///
+    /// The file is encrypted with `password`, that must be shared with the co-signer out of band.
+    pub async fn export_approval_request<P, T>(
+        &self,
+        proposal_id: EventId,
+        path: P,
+        password: T,
+    ) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+        T: AsRef<[u8]>,
+    {
+        let GetProposal {
+            policy_id,
+            proposal,
+            ..
+        } = self.get_proposal_by_id(proposal_id).await?;
+        let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+
+        let request = ApprovalRequest {
+            proposal_id,
+            policy_id,
+            network: self.network,
+            policy_name: policy.name(),
+            descriptor: policy.descriptor().to_string(),
+            proposal,
+        };
+        fs::write(path, request.encrypt(password)?)?;
+
+        tracing::info!("Approval request for proposal {proposal_id} exported");
+
+        Ok(())
+    }
+
+    /// Sign an approval request (see [`SmartVaults::export_approval_request`]) and write the
+    /// response to `out`, encrypted with the same `request_password`
+    ///
+    /// The request must match a vault known by this instance (same descriptor) and the PSBT inputs
+    /// must belong to it, otherwise nothing is signed.
+    /// Nothing is published: the instance can be offline.
+    pub async fn sign_approval_request_offline<T, P, O, R>(
+        &self,
+        password: T,
+        path: P,
+        out: O,
+        request_password: R,
+    ) -> Result<ApprovedProposal, Error>
+    where
+        T: AsRef<[u8]>,
+        P: AsRef<Path>,
+        O: AsRef<Path>,
+        R: AsRef<[u8]>,
+    {
+        let request_password: &[u8] = request_password.as_ref();
+        let ApprovalRequest {
+            proposal_id,
+            policy_id,
+            descriptor,
+            proposal,
+            ..
+        } = ApprovalRequest::decrypt(fs::read(path)?, request_password, self.network)?;
+
+        // Never sign for a descriptor chosen by the requester
+        let policy: Policy = self.storage.vault(&policy_id).await?.policy;
+        if descriptor != policy.descriptor().to_string()
+            || proposal.descriptor() != policy.descriptor()
+        {
+            return Err(Error::ApprovalRequestPolicyMismatch);
+        }
+
+        // The first input of a proof of reserve is the challenge (not spendable)
+        let psbt: PartiallySignedTransaction = proposal.psbt();
+        let skip: usize = match proposal.get_type() {
+            ProposalType::ProofOfReserve => 1,
+            _ => 0,
+        };
+        for (index, txin) in psbt.unsigned_tx.input.iter().enumerate().skip(skip) {
+            if !policy.is_psbt_input_mine(&psbt, index)? {
+                return Err(Error::UtxoNotInPolicy(txin.previous_output));
+            }
+        }
+
+        let seed: Seed = self.keechain.read().seed(password)?;
+        let approval: ApprovedProposal = proposal.approve(&seed, Vec::new(), self.network)?;
+        self.audit_signing(
+            SigningKind::Approval,
+            proposal_id.to_hex(),
+            &approval.psbt().serialize(),
+        )
+        .await?;
+
+        let response = ApprovalResponse {
+            proposal_id,
+            network: self.network,
+            approval: approval.clone(),
+        };
+        fs::write(out, response.encrypt(request_password)?)?;
+
+        tracing::info!("Approval request for proposal {proposal_id} signed offline");
+
+        Ok(approval)
+    }
+
+    /// Import an approval from an offline co-signer and publish it
+    ///
+    /// Accepts both the response of [`SmartVaults::sign_approval_request_offline`] (`password` is
+    /// required) and a bare signed PSBT (binary or base64), matched to the pending proposal
+    /// spending the same transaction. The signatures are validated as in
+    /// [`SmartVaults::approve_with_signed_psbt`].
+    pub async fn import_approval<P, T>(
+        &self,
+        path: P,
+        password: Option<T>,
+    ) -> Result<(EventId, ApprovedProposal), Error>
+    where
+        P: AsRef<Path>,
+        T: AsRef<[u8]>,
+    {
+        let path: &Path = path.as_ref();
+        let payload: Vec<u8> = fs::read(path)?;
+
+        let (proposal_id, psbt): (EventId, PartiallySignedTransaction) =
+            if ApprovalResponse::is_response(&payload) {
+                let password = password.ok_or_else(|| {
+                    Error::Generic(String::from("password required to decrypt approval"))
+                })?;
+                let ApprovalResponse {
+                    proposal_id,
+                    approval,
+                    ..
+                } = ApprovalResponse::decrypt(payload, password, self.network)?;
+                (proposal_id, approval.psbt())
+            } else {
+                let psbt: PartiallySignedTransaction = read_psbt(path)?;
+                let proposal_id: EventId = self
+                    .get_proposals()
+                    .await?
+                    .into_iter()
+                    .find(|p| p.proposal.psbt().unsigned_tx == psbt.unsigned_tx)
+                    .map(|p| p.proposal_id)
+                    .ok_or(Error::ProposalNotFound)?;
+                (proposal_id, psbt)
+            };
+
+        self.approve_with_signed_psbt(proposal_id, psbt).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use nostr_sdk::{Keys, Timestamp};
+    use smartvaults_core::bdk::chain::{BlockId, ConfirmationTime};
+    use smartvaults_core::bdk::wallet::AddressIndex;
+    use smartvaults_core::bdk::{FeeRate, Wallet};
+    use smartvaults_core::bips::bip39::Language;
+    use smartvaults_core::bitcoin::absolute::LockTime;
+    use smartvaults_core::bitcoin::hashes::Hash;
+    use smartvaults_core::bitcoin::{Address, BlockHash, Network, Transaction, TxOut};
+    use smartvaults_core::types::WordCount;
+    use smartvaults_core::{Amount, CoinSelection, Proposal};
+
+    use super::*;
+    use crate::client::TempBasePath;
+    use crate::storage::{InternalPolicy, InternalProposal};
+
+    /// Spending proposal of a funded (in memory) wallet of `policy`
+    fn spending_proposal(policy: &Policy) -> Proposal {
+        let descriptor: String = policy.as_descriptor().to_string();
+        let change_descriptor: Option<String> =
+            policy.as_change_descriptor().map(|d| d.to_string());
+        let mut wallet = Wallet::new_no_persist(
+            descriptor.as_str(),
+            change_descriptor.as_deref(),
+            Network::Testnet,
+        )
+        .unwrap();
+        wallet
+            .insert_checkpoint(BlockId {
+                height: 2_520_450,
+                hash: BlockHash::all_zeros(),
+            })
+            .unwrap();
+        let address = wallet.get_address(AddressIndex::New).address;
+        wallet
+            .insert_tx(
+                Transaction {
+                    version: 1,
+                    lock_time: LockTime::ZERO,
+                    input: Vec::new(),
+                    output: vec![TxOut {
+                        value: 10_000,
+                        script_pubkey: address.script_pubkey(),
+                    }],
+                },
+                ConfirmationTime::Confirmed {
+                    height: 2_520_000,
+                    time: 1_700_000_000,
+                },
+            )
+            .unwrap();
+        policy
+            .spend(
+                &mut wallet,
+                Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap(),
+                Amount::Custom(1_000),
+                "Offline",
+                FeeRate::from_sat_per_vb(1.0),
+                None,
+                None,
+                None,
+                CoinSelection::default(),
+            )
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_offline_approval_roundtrip() {
+        let base_path = TempBasePath::new("offline-roundtrip");
+        let network = Network::Testnet;
+
+        // Two instances, without relays
+        let online = SmartVaults::generate_offline(base_path.join("online"), "online", network)
+            .await
+            .unwrap();
+        let offline = SmartVaults::generate_offline(base_path.join("offline"), "offline", network)
+            .await
+            .unwrap();
+
+        // Vault of the offline co-signer, known by both
+        let descriptor = format!(
+            "tr({})",
+            offline.default_signer.descriptor_public_key().unwrap()
+        );
+        let policy = Policy::from_descriptor("Offline", "", descriptor, network)
+            .unwrap()
+            .with_derived_change_descriptor()
+            .unwrap();
+        let policy_id = EventId::from_slice(&[1; 32]).unwrap();
+        let shared_key = Keys::generate();
+        let public_keys = vec![online.keys.public_key(), offline.keys.public_key()];
+        for client in [&online, &offline] {
+            client
+                .storage
+                .save_shared_key(policy_id, shared_key.clone())
+                .await;
+            client
+                .storage
+                .save_vault(
+                    policy_id,
+                    InternalPolicy {
+                        policy: policy.clone(),
+                        public_keys: public_keys.clone(),
+                    },
+                )
+                .await;
+        }
+
+        // Proposal known only by the online instance
+        let proposal = spending_proposal(&policy);
+        let proposal_id = EventId::from_slice(&[2; 32]).unwrap();
+        online
+            .storage
+            .save_proposal(
+                proposal_id,
+                InternalProposal {
+                    policy_id,
+                    proposal: proposal.clone(),
+                    timestamp: Timestamp::now(),
+                },
+            )
+            .await;
+
+        // Export -> sign offline -> import
+        let request = base_path.join("request");
+        let response = base_path.join("response");
+        online
+            .export_approval_request(proposal_id, &request, "password")
+            .await
+            .unwrap();
+        let approval = offline
+            .sign_approval_request_offline("test", &request, &response, "password")
+            .await
+            .unwrap();
+        assert!(approval.psbt().inputs[0].tap_key_sig.is_some());
+
+        // Signatures validated: only the publishing fails, without relays
+        assert!(matches!(
+            online.import_approval(&response, Some("password")).await,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+
+        // Bare signed PSBT
+        let bare = base_path.join("signed.psbt");
+        fs::write(&bare, approval.psbt().serialize()).unwrap();
+        assert!(matches!(
+            online.import_approval(&bare, None::<&str>).await,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+
+        // Descriptor not matching the local vault
+        let mut tampered =
+            ApprovalRequest::decrypt(fs::read(&request).unwrap(), "password", network).unwrap();
+        tampered.descriptor = String::from("tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg");
+        fs::write(&request, tampered.encrypt("password").unwrap()).unwrap();
+        assert!(matches!(
+            offline
+                .sign_approval_request_offline("test", &request, &response, "password")
+                .await,
+            Err(Error::ApprovalRequestPolicyMismatch)
+        ));
+
+        // Input not belonging to the vault
+        let mut psbt = proposal.psbt();
+        psbt.inputs[0].witness_utxo = Some(TxOut {
+            value: 10_000,
+            script_pubkey: Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78")
+                .unwrap()
+                .assume_checked()
+                .script_pubkey(),
+        });
+        let mut tampered =
+            ApprovalRequest::decrypt(fs::read(&request).unwrap(), "password", network).unwrap();
+        tampered.descriptor = policy.descriptor().to_string();
+        tampered.proposal = Proposal::spending(
+            policy.descriptor(),
+            Address::from_str("mohjSavDdQYHRYXcS3uS6ttaHP8amyvX78").unwrap(),
+            1_000,
+            "Offline",
+            psbt.clone(),
+            None,
+        );
+        fs::write(&request, tampered.encrypt("password").unwrap()).unwrap();
+        assert!(matches!(
+            offline
+                .sign_approval_request_offline("test", &request, &response, "password")
+                .await,
+            Err(Error::UtxoNotInPolicy(outpoint)) if outpoint == psbt.unsigned_tx.input[0].previous_output
+        ));
+
+        // Vault unknown to the offline instance
+        offline.storage.delete_vault(&policy_id).await;
+        online
+            .export_approval_request(proposal_id, &request, "password")
+            .await
+            .unwrap();
+        assert!(offline
+            .sign_approval_request_offline("test", &request, &response, "password")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_import_approval_unknown_proposal() {
+        let base_path = TempBasePath::new("offline");
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "offline",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(None),
+            Network::Testnet,
+        )
+        .await
+        .unwrap();
+
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        })
+        .unwrap();
+
+        // Bare PSBT not matching any proposal
+        let bare = base_path.join("bare.psbt");
+        fs::write(&bare, psbt.serialize()).unwrap();
+        assert!(matches!(
+            client.import_approval(&bare, None::<&str>).await,
+            Err(Error::ProposalNotFound)
+        ));
+
+        // Response without password
+        let response = ApprovalResponse {
+            proposal_id: EventId::all_zeros(),
+            network: Network::Testnet,
+            approval: ApprovedProposal::spending(psbt),
+        };
+        let path = base_path.join("response");
+        fs::write(&path, response.encrypt("password").unwrap()).unwrap();
+        assert!(matches!(
+            client.import_approval(&path, None::<&str>).await,
+            Err(Error::Generic(..))
+        ));
+        assert!(matches!(
+            client.import_approval(&path, Some("wrong")).await,
+            Err(Error::OfflineApproval(..))
+        ));
+        assert!(matches!(
+            client.import_approval(&path, Some("password")).await,
+            Err(Error::NotFound)
+        ));
+
+        client.shutdown().await.unwrap();
+    }
+}
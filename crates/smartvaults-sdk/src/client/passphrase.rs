@@ -0,0 +1,145 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::path::PathBuf;
+
+use nostr_sdk::{PublicKey, Timestamp};
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::hashes::Hash;
+use smartvaults_core::types::KeeChain;
+use smartvaults_core::SECP256K1;
+
+use super::{Error, SmartVaults};
+use crate::types::PassphraseIdentity;
+use crate::util;
+
+/// Identity in use, with the passphrase hashed (salted with the fingerprint)
+pub(super) fn passphrase_identity(
+    fingerprint: Fingerprint,
+    public_key: PublicKey,
+    passphrase: Option<&str>,
+) -> PassphraseIdentity {
+    PassphraseIdentity {
+        fingerprint,
+        public_key,
+        passphrase_hash: passphrase
+            .map(|passphrase| Sha256Hash::hash(format!("{fingerprint}:{passphrase}").as_bytes())),
+        last_used: Timestamp::now(),
+    }
+}
+
+impl SmartVaults {
+    /// Switch to the identity derived from the seed with `passphrase` (`None` to use the seed
+    /// without passphrase)
+    ///
+    /// Signer, descriptors and nostr keys change with the passphrase, so the wallets of the
+    /// current identity are unloaded and the client is reopened (and resubscribed) for the new
+    /// one. The passphrase is not saved in the keychain.
+    pub async fn apply_passphrase<T>(
+        self,
+        password: T,
+        passphrase: Option<String>,
+    ) -> Result<Self, Error>
+    where
+        T: Into<String>,
+    {
+        let password: String = password.into();
+        if !self.check_password(&password) {
+            return Err(Error::PasswordNotMatch);
+        }
+
+        let keychains_path: PathBuf = util::dir::keychains_path(&self.base_path, self.network)?;
+        let mut keechain: KeeChain = KeeChain::open(
+            keychains_path,
+            self.name().unwrap_or_default(),
+            || Ok(password.clone()),
+            self.network,
+            &SECP256K1,
+        )?;
+        keechain.apply_passphrase(&password, passphrase, &SECP256K1)?;
+
+        if keechain.fingerprint() == self.fingerprint() {
+            return Ok(self);
+        }
+
+        // Known identities are recorded in the db of each identity
+        let identities: Vec<PassphraseIdentity> = self.db.get_passphrase_identities().await?;
+
+        let base_path: PathBuf = self.base_path.clone();
+        let network = self.network;
+        self.shutdown().await?;
+
+        let this = Self::new(base_path, password, keechain, network, false).await?;
+        for identity in identities.into_iter() {
+            this.db.save_passphrase_identity(identity).await?;
+        }
+
+        tracing::info!("Switched to identity {}", this.fingerprint());
+
+        Ok(this)
+    }
+
+    /// Fingerprints of the identities used with this seed, most recently used first
+    pub async fn list_passphrase_identities(&self) -> Result<Vec<PassphraseIdentity>, Error> {
+        Ok(self.db.get_passphrase_identities().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_core::bips::bip39::Language;
+    use smartvaults_core::bitcoin::Network;
+    use smartvaults_core::types::WordCount;
+
+    use super::*;
+    use crate::client::TempBasePath;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_apply_passphrase() {
+        let base_path = TempBasePath::new("passphrase");
+        let network = Network::Testnet;
+
+        let client = SmartVaults::generate(
+            &base_path,
+            "main",
+            || Ok(String::from("test")),
+            || Ok(String::from("test")),
+            WordCount::W12,
+            Language::English,
+            || Ok(None),
+            network,
+        )
+        .await
+        .unwrap();
+        let fingerprint: Fingerprint = client.fingerprint();
+        let public_key: PublicKey = client.keys().public_key();
+
+        let client = client
+            .apply_passphrase("test", Some(String::from("passphrase")))
+            .await
+            .unwrap();
+        assert_ne!(client.fingerprint(), fingerprint);
+        assert_ne!(client.keys().public_key(), public_key);
+
+        let identities = client.list_passphrase_identities().await.unwrap();
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].fingerprint, client.fingerprint());
+        assert!(identities[0].passphrase_hash.is_some());
+        assert_eq!(identities[1].fingerprint, fingerprint);
+        assert_eq!(identities[1].passphrase_hash, None);
+
+        // Back to the seed without passphrase
+        let client = client.apply_passphrase("test", None).await.unwrap();
+        assert_eq!(client.fingerprint(), fingerprint);
+        assert_eq!(client.keys().public_key(), public_key);
+        assert_eq!(client.list_passphrase_identities().await.unwrap().len(), 2);
+
+        assert!(matches!(
+            client.clone().apply_passphrase("wrong", None).await,
+            Err(Error::PasswordNotMatch)
+        ));
+
+        client.shutdown().await.unwrap();
+    }
+}
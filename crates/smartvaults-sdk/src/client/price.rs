@@ -0,0 +1,81 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::sync::Arc;
+
+use async_utility::thread;
+use futures_util::stream::AbortHandle;
+use nostr_sdk::EventId;
+use smartvaults_core::bdk::wallet::Balance;
+
+use super::{Error, SmartVaults};
+use crate::constants::PRICE_REFRESH_INTERVAL;
+use crate::price::{FiatAmount, FiatCurrency, PriceProvider, Prices};
+use crate::types::BalanceWithFiat;
+
+impl SmartVaults {
+    /// Replace the price provider (default: HTTP endpoint of the config)
+    pub fn set_price_provider<T>(&self, provider: T)
+    where
+        T: PriceProvider + 'static,
+    {
+        let mut price_provider = self.price_provider.write();
+        *price_provider = Arc::new(provider);
+    }
+
+    /// Fetch the prices and update the cache
+    pub async fn refresh_prices(&self) -> Result<(), Error> {
+        let provider = self.price_provider.read().clone();
+        let prices: Prices = provider.fetch().await?;
+        self.prices.update(prices);
+        Ok(())
+    }
+
+    /// Get the BTC price in `currency`
+    ///
+    /// The cached price is used if not expired, otherwise is fetched. Return `None` if the price
+    /// is not available (ex. network failure).
+    pub async fn get_price(&self, currency: FiatCurrency) -> Option<f64> {
+        if let Some(price) = self.prices.get(currency) {
+            return Some(price);
+        }
+
+        if let Err(e) = self.refresh_prices().await {
+            tracing::warn!("Impossible to refresh prices: {e}");
+            return None;
+        }
+
+        self.prices.get(currency)
+    }
+
+    /// Get balance with the total converted to `currency`
+    ///
+    /// The fiat amount is `None` if the price is not available.
+    pub async fn get_balance_with_fiat(
+        &self,
+        policy_id: EventId,
+        currency: FiatCurrency,
+    ) -> Option<BalanceWithFiat> {
+        let balance: Balance = self.get_balance(policy_id).await?;
+        let fiat: Option<FiatAmount> = self
+            .get_price(currency)
+            .await
+            .map(|price| FiatAmount::from_sats(balance.total(), currency, price));
+        Some(BalanceWithFiat { balance, fiat })
+    }
+
+    /// Keep the prices cache warm while a fiat currency is configured
+    pub(crate) fn prices_syncer(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                if this.config.fiat_currency().await.is_some() {
+                    if let Err(e) = this.refresh_prices().await {
+                        tracing::warn!("Impossible to refresh prices: {e}");
+                    }
+                }
+                thread::sleep(PRICE_REFRESH_INTERVAL).await;
+            }
+        })?)
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::{BTreeSet, HashMap};
+
+use nostr_sdk::{EventId, Timestamp};
+use smartvaults_core::bitcoin::hashes::sha256::Hash as Sha256Hash;
+use smartvaults_core::bitcoin::hashes::{Hash, HashEngine};
+use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+use smartvaults_core::bitcoin::OutPoint;
+use smartvaults_core::CompletedProposal;
+use smartvaults_sdk_sqlite::model::{NotificationKind, ProofVerification};
+
+use super::{Error, Message, SmartVaults};
+use crate::storage::InternalCompletedProposal;
+use crate::util::progress::{CancellationToken, Progress, ProgressCallback, Task};
+
+impl SmartVaults {
+    /// Digest of the policy UTXO set, used to detect when a proof must be verified again
+    async fn utxos_digest(&self, policy_id: EventId) -> Result<Sha256Hash, Error> {
+        let outpoints: BTreeSet<OutPoint> = self
+            .manager
+            .get_utxos(policy_id)
+            .await?
+            .into_iter()
+            .map(|utxo| utxo.outpoint)
+            .collect();
+        let mut engine = Sha256Hash::engine();
+        for outpoint in outpoints.into_iter() {
+            engine.input(outpoint.to_string().as_bytes());
+        }
+        Ok(Sha256Hash::from_engine(engine))
+    }
+
+    /// Verify the Proof of Reserve completed proposals against the synced wallets
+    ///
+    /// A proof is verified when received (after the first sync of the policy wallet)
+    /// and again every time the policy UTXO set changes.
+    /// A [`Message::ProofVerified`] or [`Message::ProofInvalid`] is emitted when the verdict changes.
+    pub(crate) async fn verify_proofs(&self) -> Result<(), Error> {
+        self.internal_verify_proofs(&Task::default()).await
+    }
+
+    async fn internal_verify_proofs(&self, task: &Task) -> Result<(), Error> {
+        let mut verifications = self.db.get_proof_verifications().await?;
+        let proofs: Vec<(EventId, EventId, String, PartiallySignedTransaction)> = self
+            .storage
+            .completed_proposals()
+            .await
+            .into_iter()
+            .filter_map(
+                |(
+                    completed_proposal_id,
+                    InternalCompletedProposal {
+                        policy_id,
+                        proposal,
+                        ..
+                    },
+                )| match proposal {
+                    CompletedProposal::ProofOfReserve { message, psbt, .. } => {
+                        Some((completed_proposal_id, policy_id, message, psbt))
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+
+        let total: usize = proofs.len();
+        for (index, (completed_proposal_id, policy_id, message, psbt)) in
+            proofs.into_iter().enumerate()
+        {
+            task.checkpoint()?;
+            task.report(Progress::new(index, total));
+
+            // Wait for fresh timechain data
+            if self.manager.last_sync(policy_id).await? == Timestamp::from(0) {
+                continue;
+            }
+
+            let utxos_digest: Sha256Hash = self.utxos_digest(policy_id).await?;
+            let previous: Option<ProofVerification> = verifications.remove(&completed_proposal_id);
+            if let Some(previous) = &previous {
+                if previous.utxos_digest == utxos_digest {
+                    continue;
+                }
+            }
+
+            let (spendable, error) =
+                match self.manager.verify_proof(policy_id, &psbt, message).await {
+                    Ok(spendable) => (Some(spendable), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+            let verification = ProofVerification {
+                completed_proposal_id,
+                policy_id,
+                spendable,
+                error,
+                height: self.manager.block_height(),
+                utxos_digest,
+                timestamp: Timestamp::now(),
+            };
+
+            let changed: bool = previous.map_or(true, |p| p.spendable != spendable);
+            self.db.save_proof_verification(verification).await?;
+
+            if changed {
+                match spendable {
+                    Some(spendable) => {
+                        tracing::info!("Proof {completed_proposal_id} verified: {spendable} sat");
+                        self.db
+                            .save_notification(
+                                NotificationKind::ProofVerified,
+                                completed_proposal_id,
+                                Timestamp::now(),
+                            )
+                            .await?;
+                        self.sync_channel.send(Message::ProofVerified {
+                            completed_proposal_id,
+                            spendable,
+                        });
+                    }
+                    None => {
+                        tracing::warn!("Proof {completed_proposal_id} is invalid");
+                        self.db
+                            .save_notification(
+                                NotificationKind::ProofInvalid,
+                                completed_proposal_id,
+                                Timestamp::now(),
+                            )
+                            .await?;
+                        self.sync_channel
+                            .send(Message::ProofInvalid(completed_proposal_id));
+                    }
+                }
+            }
+        }
+
+        task.report(Progress::new(total, total));
+
+        Ok(())
+    }
+
+    /// Verify the Proof of Reserve completed proposals and get the verdicts report
+    /// (`completed proposal ID` -> `verdict`)
+    ///
+    /// Progress is reported as processed proofs.
+    pub async fn proof_report_with_progress(
+        &self,
+        progress: Option<ProgressCallback>,
+        token: CancellationToken,
+    ) -> Result<HashMap<EventId, ProofVerification>, Error> {
+        let task = Task::new(progress, token);
+        self.internal_verify_proofs(&task).await?;
+        self.get_proof_verifications().await
+    }
+
+    /// Get the cached Proof of Reserve verdicts (`completed proposal ID` -> `verdict`)
+    ///
+    /// The proofs not verified yet are not included.
+    pub async fn get_proof_verifications(
+        &self,
+    ) -> Result<HashMap<EventId, ProofVerification>, Error> {
+        let completed_proposals = self.storage.completed_proposals().await;
+        Ok(self
+            .db
+            .get_proof_verifications()
+            .await?
+            .into_iter()
+            .filter(|(id, ..)| completed_proposals.contains_key(id))
+            .collect())
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
+
+use futures_util::future;
+use nostr_sdk::database::Order;
+use nostr_sdk::nips::nip65::{self, RelayMetadata};
+use nostr_sdk::{Event, EventId, Filter, Kind, PublicKey, Relay, RelaySendOptions, Timestamp, Url};
+use smartvaults_sdk_sqlite::model::{EventDelivery, OutboxEvent};
+
+use super::{Error, Message, SmartVaults};
+use crate::constants::RECIPIENT_RELAY_TIMEOUT;
+use crate::types::BatchSendReport;
+
+impl SmartVaults {
+    /// Publish many events with a single batched call per relay
+    ///
+    /// The events rejected by a relay are retried once, one by one.
+    /// If still failing, they are saved in the outbox and retried in background
+    /// (up to [`OUTBOX_MAX_ATTEMPTS`](crate::constants::OUTBOX_MAX_ATTEMPTS) times).
+    pub async fn publish_events(&self, events: Vec<Event>) -> Result<BatchSendReport, Error> {
+        let mut report = BatchSendReport::default();
+
+        if events.is_empty() {
+            return Ok(report);
+        }
+
+        // Save events in the local database
+        let database = self.client.database();
+        for event in events.iter() {
+            database.save_event(event).await?;
+        }
+
+        let relays: BTreeMap<Url, Relay> = self.relays_with_role(false, true).await;
+        let results = future::join_all(relays.iter().map(|(url, relay)| {
+            let events = events.clone();
+            async move {
+                let now = Instant::now();
+                let results = batch_send_to_relay(relay, events).await;
+                (url, now.elapsed(), results)
+            }
+        }))
+        .await;
+
+        for (url, latency, results) in results.into_iter() {
+            self.relay_monitor.send_latency(url, latency);
+            for (event_id, res) in results.into_iter() {
+                report
+                    .results
+                    .entry(event_id)
+                    .or_default()
+                    .insert(url.clone(), res);
+            }
+        }
+
+        // Retry the rejected ones, one by one
+        let failures: Vec<(EventId, Url)> = report
+            .failures()
+            .map(|(event_id, url, _)| (*event_id, url.clone()))
+            .collect();
+        for (event_id, url) in failures.into_iter() {
+            let relay: Option<&Relay> = relays.get(&url);
+            let event: Option<&Event> = events.iter().find(|e| e.id == event_id);
+            if let (Some(relay), Some(event)) = (relay, event) {
+                let res = relay
+                    .send_event(event.clone(), RelaySendOptions::new())
+                    .await;
+                if let Some(relays) = report.results.get_mut(&event_id) {
+                    relays.insert(url, res.err().map(|e| e.to_string()));
+                }
+            }
+        }
+
+        // Save the still failing ones in the outbox
+        for event in events.iter() {
+            if let Some(relays) = report.results.get(&event.id) {
+                for (url, res) in relays.iter() {
+                    if let Some(e) = res {
+                        tracing::warn!("Event {} not published to {url}: {e}", event.id);
+                        self.relay_monitor.publish_failed(url);
+                        self.db.save_outbox_event(url.clone(), event).await?;
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Batch publication: {report}");
+
+        Ok(report)
+    }
+
+    /// Publish a critical event (policy, proposal, approval or completed proposal)
+    ///
+    /// Succeed only if at least [`Config::min_relay_acks`](crate::config::Config::min_relay_acks)
+    /// relays (or all, if less are configured) acknowledged the event.
+    /// The relays that didn't acknowledge it are retried through the outbox.
+    pub(crate) async fn send_critical_event(&self, event: Event) -> Result<EventId, Error> {
+        let event_id: EventId = event.id;
+        let report: BatchSendReport = self.publish_events(vec![event]).await?;
+        self.ensure_relay_acks(&report, event_id).await?;
+        Ok(event_id)
+    }
+
+    /// Check that enough relays acknowledged `event_id` (see [`SmartVaults::send_critical_event`])
+    pub(crate) async fn ensure_relay_acks(
+        &self,
+        report: &BatchSendReport,
+        event_id: EventId,
+    ) -> Result<(), Error> {
+        let (relays, got): (usize, usize) = match report.results.get(&event_id) {
+            Some(relays) => (
+                relays.len(),
+                relays.values().filter(|res| res.is_none()).count(),
+            ),
+            None => (0, 0),
+        };
+        let want: usize = self.config.min_relay_acks().await.min(relays).max(1);
+
+        if got < want {
+            tracing::warn!("Event {event_id} acknowledged by {got}/{want} relays");
+            self.sync_channel.send(Message::InsufficientRelayAcks {
+                event_id,
+                got,
+                want,
+            });
+            return Err(Error::InsufficientRelayAcks { got, want });
+        }
+
+        Ok(())
+    }
+
+    /// Send recipient-addressed events also to the read relays of their recipient (NIP-65)
+    ///
+    /// Must be called after [`SmartVaults::publish_events`], with its report: the results of our
+    /// relays are recorded too. At most [`Config::max_recipient_relays`](crate::config::Config::max_recipient_relays)
+    /// extra relays are used for each event. Failures are only recorded, never returned.
+    pub(crate) async fn deliver_to_recipients(
+        &self,
+        report: &BatchSendReport,
+        events: Vec<(Event, PublicKey)>,
+    ) -> Result<(), Error> {
+        let max_relays: usize = self.config.max_recipient_relays().await;
+
+        for (event, recipient) in events.into_iter() {
+            let event_id: EventId = event.id;
+            let timestamp = Timestamp::now();
+
+            // Our relays
+            let mut used: HashSet<Url> = HashSet::new();
+            if let Some(relays) = report.results.get(&event_id) {
+                for (url, res) in relays.iter() {
+                    used.insert(url.clone());
+                    let delivery = EventDelivery {
+                        recipient,
+                        relay_url: url.clone(),
+                        recipient_relay: false,
+                        error: res.clone(),
+                        timestamp,
+                    };
+                    self.db.save_event_delivery(event_id, delivery).await?;
+                }
+            }
+
+            // Recipient relays
+            let relays: Vec<Url> = self
+                .recipient_read_relays(recipient)
+                .await?
+                .into_iter()
+                .filter(|url| !used.contains(url))
+                .take(max_relays)
+                .collect();
+            for url in relays.into_iter() {
+                let error: Option<String> = self
+                    .send_to_recipient_relay(&url, event.clone())
+                    .await
+                    .err()
+                    .map(|e| e.to_string());
+                if let Some(e) = &error {
+                    tracing::warn!("Event {event_id} not sent to {recipient} relay {url}: {e}");
+                }
+                let delivery = EventDelivery {
+                    recipient,
+                    relay_url: url,
+                    recipient_relay: true,
+                    error,
+                    timestamp,
+                };
+                self.db.save_event_delivery(event_id, delivery).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the read relays of `public_key` from its last known relay list (NIP-65)
+    async fn recipient_read_relays(&self, public_key: PublicKey) -> Result<Vec<Url>, Error> {
+        let filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::RelayList)
+            .limit(1);
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?;
+        Ok(match events.first() {
+            Some(event) => nip65::extract_relay_list(event)
+                .into_iter()
+                .filter(|(_, metadata)| *metadata != Some(RelayMetadata::Write))
+                .filter_map(|(url, _)| Url::try_from(url).ok())
+                .collect(),
+            None => Vec::new(),
+        })
+    }
+
+    /// Send an event to a relay, adding it to the pool only for the time needed
+    async fn send_to_recipient_relay(&self, url: &Url, event: Event) -> Result<EventId, Error> {
+        let added: bool = self.client.add_relay(url).await?;
+        if added {
+            let relay: Relay = self.client.relay(url).await?;
+            relay.connect(Some(RECIPIENT_RELAY_TIMEOUT)).await;
+        }
+        let res = self.client.send_event_to([url.clone()], event).await;
+        if added {
+            self.client.remove_relay(url).await?;
+        }
+        Ok(res?)
+    }
+
+    /// Get the relays a recipient-addressed event was sent to
+    pub async fn get_event_delivery(&self, event_id: EventId) -> Result<Vec<EventDelivery>, Error> {
+        Ok(self.db.get_event_deliveries(event_id).await?)
+    }
+
+    /// Get the events waiting in the outbox, with the relays that still owe an acknowledgment
+    pub async fn get_outbox(&self) -> Result<BTreeMap<EventId, Vec<OutboxEvent>>, Error> {
+        let mut outbox: BTreeMap<EventId, Vec<OutboxEvent>> = BTreeMap::new();
+        for event in self.db.get_outbox_events().await?.into_iter() {
+            outbox.entry(event.event.id).or_default().push(event);
+        }
+        Ok(outbox)
+    }
+}
+
+async fn batch_send_to_relay(
+    relay: &Relay,
+    events: Vec<Event>,
+) -> BTreeMap<EventId, Option<String>> {
+    let res: Option<String> = match relay
+        .batch_event(events.clone(), RelaySendOptions::new())
+        .await
+    {
+        Ok(()) => None,
+        Err(e) => {
+            tracing::warn!("Batch publication to {} failed: {e}", relay.url());
+            Some(e.to_string())
+        }
+    };
+    events
+        .into_iter()
+        .map(|event| (event.id, res.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use smartvaults_core::bitcoin::Network;
+
+    use super::*;
+    use crate::client::TempBasePath;
+
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_save_policy_not_accepted() {
+        let base_path = TempBasePath::new("acks");
+
+        // No relays
+        let client = SmartVaults::generate_offline(&base_path, "acks", Network::Testnet)
+            .await
+            .unwrap();
+        let public_key: PublicKey = client.keys().public_key();
+        let res = client
+            .save_policy("Vault", "", DESCRIPTOR, vec![public_key])
+            .await;
+        assert!(matches!(
+            res,
+            Err(Error::InsufficientRelayAcks { got: 0, want: 1 })
+        ));
+        assert!(client.get_policies().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::BTreeSet;
+
+use nostr_sdk::nips::nip04;
+use nostr_sdk::{Event, EventBuilder, EventId, Keys, PublicKey, Timestamp};
+use smartvaults_protocol::v1::{ApprovalReminder, Serde, SmartVaultsEventBuilder};
+use smartvaults_sdk_sqlite::model::{NotificationKind, ReminderSettings, TrackedReminder};
+
+use super::{Error, Message, SmartVaults};
+use crate::constants::APPROVAL_REMINDERS_MAX_AGE;
+use crate::storage::{InternalPolicy, InternalProposal};
+
+impl SmartVaults {
+    /// Set the approval reminders settings of a policy (`None` to disable them)
+    ///
+    /// The reminders are sent by the client that created the proposal.
+    pub async fn set_reminder_settings(
+        &self,
+        policy_id: EventId,
+        settings: Option<ReminderSettings>,
+    ) -> Result<(), Error> {
+        self.storage.vault(&policy_id).await?;
+        Ok(self.db.set_reminder_settings(policy_id, settings).await?)
+    }
+
+    pub async fn get_reminder_settings(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Option<ReminderSettings>, Error> {
+        Ok(self.db.get_reminder_settings(policy_id).await?)
+    }
+
+    /// Mute (or unmute) the approval reminders received for a proposal
+    pub async fn mute_reminders(&self, proposal_id: EventId, muted: bool) -> Result<(), Error> {
+        Ok(self.db.set_reminders_muted(proposal_id, muted).await?)
+    }
+
+    /// Members of the policy that didn't approve the proposal yet (me excluded)
+    async fn pending_approvers(
+        &self,
+        policy_id: EventId,
+        proposal_id: EventId,
+    ) -> Result<BTreeSet<PublicKey>, Error> {
+        let my_public_key: PublicKey = self.keys().public_key();
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+        let approvers: BTreeSet<PublicKey> = self
+            .storage
+            .approvals()
+            .await
+            .into_values()
+            .filter(|internal| internal.proposal_id == proposal_id)
+            .map(|internal| internal.public_key)
+            .collect();
+        Ok(public_keys
+            .into_iter()
+            .filter(|pk| *pk != my_public_key && !approvers.contains(pk))
+            .collect())
+    }
+
+    /// Remind the pending approvers of my proposals, according to the reminder settings of the
+    /// policies
+    ///
+    /// A proposal stops being tracked when finalized or deleted, when all the reminders were sent
+    /// or when older than [`APPROVAL_REMINDERS_MAX_AGE`].
+    pub(crate) async fn send_approval_reminders(&self) -> Result<(), Error> {
+        let now = Timestamp::now();
+        for tracked in self.db.get_tracked_reminders().await?.into_iter() {
+            let TrackedReminder {
+                proposal_id,
+                policy_id,
+                created_at,
+                sent,
+                ..
+            } = tracked;
+
+            // Finalized or deleted
+            if self.storage.proposal(&proposal_id).await.is_err()
+                || created_at + APPROVAL_REMINDERS_MAX_AGE < now
+            {
+                self.db.untrack_reminders(proposal_id).await?;
+                continue;
+            }
+
+            // Nothing to approve
+            if self.storage.is_ready_to_finalize(&proposal_id).await {
+                continue;
+            }
+
+            let settings: ReminderSettings = match self.db.get_reminder_settings(policy_id).await? {
+                Some(settings) => settings,
+                None => continue,
+            };
+            match tracked.next_due(&settings) {
+                Some(due) if due <= now => (),
+                Some(..) => continue,
+                None => {
+                    self.db.untrack_reminders(proposal_id).await?;
+                    continue;
+                }
+            }
+
+            let pending: Vec<PublicKey> = self
+                .pending_approvers(policy_id, proposal_id)
+                .await?
+                .into_iter()
+                .collect();
+            if pending.is_empty() {
+                continue;
+            }
+
+            let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+            let reminder = ApprovalReminder::new(proposal_id, sent + 1);
+            let event: Event = EventBuilder::approval_reminder(
+                self.keys(),
+                &shared_key.public_key(),
+                &reminder,
+                &pending,
+            )?;
+            self.client.send_event(event).await?;
+            self.db.reminder_sent(proposal_id, now).await?;
+
+            tracing::info!(
+                "Sent reminder #{} of proposal {proposal_id} to {} members",
+                reminder.count,
+                pending.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Handle an approval reminder
+    ///
+    /// The [`NotificationKind::ApprovalReminder`] notification is brought back as unseen and a
+    /// [`Message::ApprovalReminder`] is emitted, unless the proposal is already approved by me or
+    /// its reminders are muted.
+    pub(crate) async fn handle_approval_reminder(&self, event: &Event) -> Result<(), Error> {
+        let member: PublicKey = event.author();
+        let my_public_key: PublicKey = self.keys().public_key();
+        if member == my_public_key {
+            return Ok(());
+        }
+
+        let proposal_id: EventId = match event.event_ids().next() {
+            Some(id) => *id,
+            None => return Ok(()),
+        };
+        let InternalProposal { policy_id, .. } = match self.storage.proposal(&proposal_id).await {
+            Ok(proposal) => proposal,
+            // Unknown, finalized or deleted proposal
+            Err(..) => return Ok(()),
+        };
+
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+        if !public_keys.contains(&member) {
+            tracing::warn!("Approval reminder from {member}, not a member of policy {policy_id}");
+            return Ok(());
+        }
+
+        let shared_key: Keys = self.storage.shared_key(&policy_id).await?;
+        let content: String = nip04::decrypt(shared_key.secret_key()?, &member, &event.content)?;
+        let reminder = ApprovalReminder::from_json(content)?;
+        if reminder.proposal_id != proposal_id {
+            tracing::warn!("Approval reminder {} refers to another proposal", event.id);
+            return Ok(());
+        }
+
+        let approved: bool = self
+            .storage
+            .approvals()
+            .await
+            .into_values()
+            .any(|internal| {
+                internal.proposal_id == proposal_id && internal.public_key == my_public_key
+            });
+        if approved {
+            return Ok(());
+        }
+
+        if self.db.are_reminders_muted(proposal_id).await? {
+            tracing::debug!("Reminders of proposal {proposal_id} muted");
+            return Ok(());
+        }
+
+        self.db
+            .resurface_notification(
+                NotificationKind::ApprovalReminder,
+                proposal_id,
+                event.created_at,
+            )
+            .await?;
+        self.sync_channel.send(Message::ApprovalReminder {
+            proposal_id,
+            count: reminder.count,
+        });
+
+        Ok(())
+    }
+}
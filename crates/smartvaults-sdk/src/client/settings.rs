@@ -0,0 +1,98 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use smartvaults_sdk_sqlite::model::RelayRole;
+
+use super::{Error, SmartVaults};
+use crate::types::settings::{DisplayPreferences, RelaySettings, SettingsExport};
+
+impl SmartVaults {
+    /// Export relays and settings as an encrypted payload, to render as QR code
+    ///
+    /// Keys are never exported.
+    pub async fn export_settings_qr<T>(
+        &self,
+        password: T,
+        display: DisplayPreferences,
+    ) -> Result<String, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let config = self.config();
+        let settings = SettingsExport {
+            network: self.network,
+            relays: self
+                .db
+                .get_relays(true)
+                .await?
+                .into_iter()
+                .map(|(url, proxy, role)| RelaySettings {
+                    url,
+                    proxy,
+                    role: if role == RelayRole::default() {
+                        None
+                    } else {
+                        Some(role)
+                    },
+                })
+                .collect(),
+            electrum_server: config
+                .electrum_endpoint()
+                .await
+                .ok()
+                .map(|e| e.as_standard_format()),
+            proxy: config.proxy().await.ok(),
+            block_explorer: config.block_explorer().await.ok(),
+            fee_targets: *config.bitcoin.fee_targets.read().await,
+            fee_cap: *config.bitcoin.fee_cap.read().await,
+            display,
+        };
+        Ok(settings.encrypt(password)?)
+    }
+
+    /// Import relays and settings exported with [`SmartVaults::export_settings_qr`]
+    ///
+    /// Return the display preferences, that must be applied by the frontend.
+    pub async fn import_settings_qr<S, T>(
+        &self,
+        payload: S,
+        password: T,
+    ) -> Result<DisplayPreferences, Error>
+    where
+        S: AsRef<str>,
+        T: AsRef<[u8]>,
+    {
+        let settings = SettingsExport::decrypt(payload, password, self.network)?;
+
+        for relay in settings.relays.into_iter() {
+            self.add_relay_with_opts(
+                relay.url.to_string(),
+                relay.proxy,
+                relay.role.unwrap_or_default(),
+                false,
+            )
+            .await?;
+        }
+        self.save_relay_list().await?;
+
+        let config = self.config();
+        if let Some(endpoint) = settings.electrum_server {
+            config.set_electrum_endpoint(Some(endpoint)).await?;
+        }
+        if let Some(proxy) = settings.proxy {
+            config.set_proxy(Some(proxy)).await;
+        }
+        if let Some(block_explorer) = settings.block_explorer {
+            config.set_block_explorer(Some(block_explorer)).await;
+        }
+        if let Some(fee_targets) = settings.fee_targets {
+            config.set_fee_targets(Some(fee_targets)).await;
+        }
+        if let Some(fee_cap) = settings.fee_cap {
+            config.set_fee_cap(Some(fee_cap)).await;
+        }
+        config.save().await?;
+
+        Ok(settings.display)
+    }
+}
@@ -2,21 +2,22 @@
 // Distributed under the MIT software license
 
 use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
 
 use nostr_sdk::database::NostrDatabaseExt;
 use nostr_sdk::nips::nip04;
-use nostr_sdk::{
-    ClientMessage, Event, EventBuilder, EventId, Keys, Kind, Profile, PublicKey, RelaySendOptions,
-    Tag,
-};
+use nostr_sdk::{Event, EventBuilder, EventId, Keys, Kind, Profile, PublicKey, Tag, Timestamp};
+use smartvaults_core::bitcoin::Network;
 use smartvaults_core::miniscript::Descriptor;
-use smartvaults_core::signer::{SharedSigner, Signer};
+use smartvaults_core::signer::{self, SharedSigner, SharedSignerScope, Signer};
+use smartvaults_core::{ColdcardGenericJson, Policy, PolicyTemplateType};
 use smartvaults_protocol::v1::constants::{SHARED_SIGNERS_KIND, SIGNERS_KIND};
 use smartvaults_protocol::v1::util::{Encryption, Serde};
 
 use super::{Error, SmartVaults};
-use crate::storage::InternalSharedSigner;
-use crate::types::{GetAllSigners, GetSharedSigner, GetSigner};
+use crate::storage::{InternalPolicy, InternalSharedSigner};
+use crate::types::{BatchSendReport, GetAllSigners, GetSharedSigner, GetSigner};
+use crate::util;
 
 impl SmartVaults {
     #[tracing::instrument(skip_all, level = "trace")]
@@ -24,7 +25,40 @@ impl SmartVaults {
         self.storage.signer(&signer_id).await
     }
 
-    pub async fn delete_signer_by_id(&self, signer_id: EventId) -> Result<(), Error> {
+    /// Get the IDs of the policies that use the signer
+    pub async fn get_policies_using_signer(
+        &self,
+        signer_id: EventId,
+    ) -> Result<Vec<EventId>, Error> {
+        let signer: Signer = self.storage.signer(&signer_id).await?;
+        let mut policies: Vec<EventId> = self
+            .storage
+            .vaults()
+            .await
+            .into_iter()
+            .filter(|(_, InternalPolicy { policy, .. })| {
+                policy
+                    .search_used_signers(std::iter::once(signer.clone()))
+                    .next()
+                    .is_some()
+            })
+            .map(|(policy_id, ..)| policy_id)
+            .collect();
+        policies.sort();
+        Ok(policies)
+    }
+
+    /// Delete signer
+    ///
+    /// If the signer is used by some policy, return [`Error::SignerInUse`] unless `force` is `true`.
+    pub async fn delete_signer_by_id(&self, signer_id: EventId, force: bool) -> Result<(), Error> {
+        if !force {
+            let policies: Vec<EventId> = self.get_policies_using_signer(signer_id).await?;
+            if !policies.is_empty() {
+                return Err(Error::SignerInUse(policies));
+            }
+        }
+
         let my_shared_signers = self
             .storage
             .get_my_shared_signers_by_signer_id(&signer_id)
@@ -46,9 +80,17 @@ impl SmartVaults {
         Ok(())
     }
 
+    /// Save (and publish) a signer
+    ///
+    /// Return [`Error::NetworkMismatch`] if the signer keys are for another network.
     pub async fn save_signer(&self, signer: Signer) -> Result<EventId, Error> {
         let keys: &Keys = self.keys();
 
+        util::network::check_descriptor_str_network(
+            &signer.descriptor().to_string(),
+            self.network,
+        )?;
+
         if self
             .storage
             .signer_descriptor_exists(signer.descriptor())
@@ -72,6 +114,39 @@ impl SmartVaults {
         Ok(signer_id)
     }
 
+    /// Import an airgap signer from a Coldcard generic JSON export (`coldcard-export.json`)
+    ///
+    /// Return [`Error::NetworkMismatch`] if the export is for another network.
+    pub async fn save_coldcard_signer<S, P>(&self, name: S, path: P) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+    {
+        let name: String = name.into();
+        let coldcard: ColdcardGenericJson =
+            ColdcardGenericJson::from_file(path).map_err(signer::Error::from)?;
+        let signer: Signer =
+            match Signer::from_coldcard(name.clone(), coldcard.clone(), self.network) {
+                Ok(signer) => signer,
+                Err(e) => {
+                    // Testnet, signet and regtest share the same keys: check only mainnet vs test networks
+                    let other: Network = if self.network == Network::Bitcoin {
+                        Network::Testnet
+                    } else {
+                        Network::Bitcoin
+                    };
+                    if Signer::from_coldcard(name, coldcard, other).is_ok() {
+                        return Err(Error::NetworkMismatch {
+                            expected: self.network,
+                            found: other,
+                        });
+                    }
+                    return Err(e.into());
+                }
+            };
+        self.save_signer(signer).await
+    }
+
     pub async fn smartvaults_signer_exists(&self) -> bool {
         self.storage
             .signer_descriptor_exists(self.default_signer.descriptor())
@@ -122,21 +197,28 @@ impl SmartVaults {
         &self,
         signer_id: EventId,
         public_key: PublicKey,
+    ) -> Result<EventId, Error> {
+        self.share_signer_with_scope(signer_id, public_key, None)
+            .await
+    }
+
+    /// Share signer with an optional [`SharedSignerScope`]
+    ///
+    /// The scope is advisory: the receiver is warned when using the signer outside of it.
+    pub async fn share_signer_with_scope(
+        &self,
+        signer_id: EventId,
+        public_key: PublicKey,
+        scope: Option<SharedSignerScope>,
     ) -> Result<EventId, Error> {
         if !self
             .storage
             .my_shared_signer_already_shared(signer_id, public_key)
             .await
         {
-            let keys: &Keys = self.keys();
-            let signer: Signer = self.get_signer_by_id(signer_id).await?;
-            let shared_signer: SharedSigner = signer.to_shared_signer();
-            let content: String =
-                nip04::encrypt(keys.secret_key()?, &public_key, shared_signer.as_json())?;
-            let tags = [Tag::event(signer_id), Tag::public_key(public_key)];
-            let event: Event =
-                EventBuilder::new(SHARED_SIGNERS_KIND, content, tags).to_event(keys)?;
-            let event_id = self.client.send_event(event).await?;
+            let event_id = self
+                .send_shared_signer(signer_id, public_key, scope)
+                .await?;
             self.storage
                 .save_my_shared_signer(signer_id, event_id, public_key)
                 .await;
@@ -146,11 +228,58 @@ impl SmartVaults {
         }
     }
 
+    async fn send_shared_signer(
+        &self,
+        signer_id: EventId,
+        public_key: PublicKey,
+        scope: Option<SharedSignerScope>,
+    ) -> Result<EventId, Error> {
+        let keys: &Keys = self.keys();
+        let signer: Signer = self.get_signer_by_id(signer_id).await?;
+        let shared_signer: SharedSigner = signer.to_shared_signer().with_scope(scope);
+        let content: String =
+            nip04::encrypt(keys.secret_key()?, &public_key, shared_signer.as_json())?;
+        let tags = [Tag::event(signer_id), Tag::public_key(public_key)];
+        let event: Event = EventBuilder::new(SHARED_SIGNERS_KIND, content, tags).to_event(keys)?;
+        let event_id: EventId = event.id;
+        let report: BatchSendReport = self.publish_events(vec![event.clone()]).await?;
+        self.deliver_to_recipients(&report, vec![(event, public_key)])
+            .await?;
+        Ok(event_id)
+    }
+
+    /// Update (or remove, if `None`) the [`SharedSignerScope`] of a shared signer
+    ///
+    /// A new shared signer event is published, superseding the previous one (that is deleted).
+    /// Return the new shared signer ID.
+    pub async fn update_shared_signer_scope(
+        &self,
+        shared_signer_id: EventId,
+        scope: Option<SharedSignerScope>,
+    ) -> Result<EventId, Error> {
+        let (signer_id, public_key) = self.storage.get_my_shared_signer(shared_signer_id).await?;
+
+        let event_id = self
+            .send_shared_signer(signer_id, public_key, scope)
+            .await?;
+        self.storage
+            .save_my_shared_signer(signer_id, event_id, public_key)
+            .await;
+
+        let tags = [Tag::public_key(public_key), Tag::event(shared_signer_id)];
+        let event = EventBuilder::new(Kind::EventDeletion, "", tags);
+        self.client.send_event_builder(event).await?;
+
+        tracing::info!("Shared signer {shared_signer_id} superseded by {event_id}");
+
+        Ok(event_id)
+    }
+
     pub async fn share_signer_to_multiple_public_keys(
         &self,
         signer_id: EventId,
         public_keys: Vec<PublicKey>,
-    ) -> Result<(), Error> {
+    ) -> Result<BatchSendReport, Error> {
         if public_keys.is_empty() {
             return Err(Error::NotEnoughPublicKeys);
         }
@@ -159,6 +288,7 @@ impl SmartVaults {
         let signer: Signer = self.get_signer_by_id(signer_id).await?;
         let shared_signer: SharedSigner = signer.to_shared_signer();
 
+        let mut events: Vec<(Event, PublicKey)> = Vec::with_capacity(public_keys.len());
         for public_key in public_keys.into_iter() {
             if self
                 .storage
@@ -172,41 +302,71 @@ impl SmartVaults {
                 let tags = [Tag::event(signer_id), Tag::public_key(public_key)];
                 let event: Event =
                     EventBuilder::new(SHARED_SIGNERS_KIND, content, tags).to_event(keys)?;
-                let event_id: EventId = event.id;
-
-                // TODO: use send_batch_event method from nostr-sdk
-                self.client
-                    .pool()
-                    .send_msg(
-                        ClientMessage::event(event),
-                        RelaySendOptions::new().skip_send_confirmation(true),
-                    )
-                    .await?;
-
                 self.storage
-                    .save_my_shared_signer(signer_id, event_id, public_key)
+                    .save_my_shared_signer(signer_id, event.id, public_key)
                     .await;
+                events.push((event, public_key));
             }
         }
 
-        Ok(())
+        let report: BatchSendReport = self
+            .publish_events(events.iter().map(|(e, _)| e.clone()).collect())
+            .await?;
+        self.deliver_to_recipients(&report, events).await?;
+        Ok(report)
     }
 
-    pub async fn revoke_all_shared_signers(&self) -> Result<(), Error> {
+    pub async fn revoke_all_shared_signers(&self) -> Result<BatchSendReport, Error> {
+        let keys: &Keys = self.keys();
+        let mut events: Vec<Event> = Vec::new();
         for (shared_signer_id, public_key) in self.storage.my_shared_signers().await.into_iter() {
             let tags = [Tag::public_key(public_key), Tag::event(shared_signer_id)];
-            let event = EventBuilder::new(Kind::EventDeletion, "", tags);
-            self.client.send_event_builder(event).await?;
+            let event: Event = EventBuilder::new(Kind::EventDeletion, "", tags).to_event(keys)?;
+            events.push(event);
             self.storage.delete_shared_signer(&shared_signer_id).await;
         }
-        Ok(())
+        self.publish_events(events).await
+    }
+
+    /// Get the IDs of the policies, shared with the receiver, that use my shared signer
+    pub async fn get_policies_using_shared_signer(
+        &self,
+        shared_signer_id: EventId,
+    ) -> Result<Vec<EventId>, Error> {
+        let (signer_id, public_key) = self.storage.get_my_shared_signer(shared_signer_id).await?;
+        let mut policies: Vec<EventId> = Vec::new();
+        for policy_id in self.get_policies_using_signer(signer_id).await?.into_iter() {
+            let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+            if public_keys.contains(&public_key) {
+                policies.push(policy_id);
+            }
+        }
+        Ok(policies)
     }
 
-    pub async fn revoke_shared_signer(&self, shared_signer_id: EventId) -> Result<(), Error> {
+    /// Revoke shared signer
+    ///
+    /// If the signer is used by some policy shared with the receiver, return [`Error::SharedSignerInUse`]
+    /// unless `force` is `true`.
+    pub async fn revoke_shared_signer(
+        &self,
+        shared_signer_id: EventId,
+        force: bool,
+    ) -> Result<(), Error> {
         let public_key: PublicKey = self
             .storage
             .get_public_key_for_my_shared_signer(shared_signer_id)
             .await?;
+
+        if !force {
+            let policies: Vec<EventId> = self
+                .get_policies_using_shared_signer(shared_signer_id)
+                .await?;
+            if !policies.is_empty() {
+                return Err(Error::SharedSignerInUse(policies));
+            }
+        }
+
         let tags = [Tag::public_key(public_key), Tag::event(shared_signer_id)];
         let event = EventBuilder::new(Kind::EventDeletion, "", tags);
         self.client.send_event_builder(event).await?;
@@ -243,17 +403,35 @@ impl SmartVaults {
         Ok(map)
     }
 
+    /// Get contacts shared signers
+    ///
+    /// Shared signers with an expired [`SharedSignerScope`] are hidden (see [`SmartVaults::get_expired_shared_signers`]).
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_shared_signers(&self) -> Result<Vec<GetSharedSigner>, Error> {
+        self._get_shared_signers(false).await
+    }
+
+    /// Get contacts shared signers with an expired [`SharedSignerScope`]
+    pub async fn get_expired_shared_signers(&self) -> Result<Vec<GetSharedSigner>, Error> {
+        self._get_shared_signers(true).await
+    }
+
+    async fn _get_shared_signers(&self, expired: bool) -> Result<Vec<GetSharedSigner>, Error> {
+        let now: u64 = Timestamp::now().as_u64();
         let mut list = Vec::new();
         for (
             shared_signer_id,
             InternalSharedSigner {
                 owner_public_key,
                 shared_signer,
+                ..
             },
         ) in self.storage.shared_signers().await.into_iter()
         {
+            if shared_signer.is_expired(now) != expired {
+                continue;
+            }
+
             let profile: Profile = self.client.database().profile(owner_public_key).await?;
             list.push(GetSharedSigner {
                 shared_signer_id,
@@ -289,12 +467,14 @@ impl SmartVaults {
         &self,
         public_key: PublicKey,
     ) -> Result<Vec<GetSharedSigner>, Error> {
+        let now: u64 = Timestamp::now().as_u64();
         let profile: Profile = self.client.database().profile(public_key).await?;
         Ok(self
             .storage
             .get_shared_signers_by_public_key(public_key)
             .await
             .into_iter()
+            .filter(|(_, shared_signer)| !shared_signer.is_expired(now))
             .map(|(shared_signer_id, shared_signer)| GetSharedSigner {
                 shared_signer_id,
                 owner: profile.clone(),
@@ -302,4 +482,59 @@ impl SmartVaults {
             })
             .collect())
     }
+
+    /// Get the contacts shared signers used by `policy` outside of their [`SharedSignerScope`]
+    ///
+    /// The scope is advisory: use it to warn the user before publishing the policy.
+    pub async fn shared_signers_out_of_scope(
+        &self,
+        policy: &Policy,
+    ) -> Result<Vec<GetSharedSigner>, Error> {
+        let now: u64 = Timestamp::now().as_u64();
+        let template: Option<PolicyTemplateType> = policy.template_match().ok().flatten();
+        let descriptor: String = policy.as_descriptor().to_string();
+        let vaults: Vec<String> = self
+            .storage
+            .vaults()
+            .await
+            .into_values()
+            .map(|InternalPolicy { policy, .. }| policy.as_descriptor().to_string())
+            .collect();
+
+        let mut list = Vec::new();
+        for (
+            shared_signer_id,
+            InternalSharedSigner {
+                owner_public_key,
+                shared_signer,
+                ..
+            },
+        ) in self.storage.shared_signers().await.into_iter()
+        {
+            let scope: &SharedSignerScope = match shared_signer.scope() {
+                Some(scope) => scope,
+                None => continue,
+            };
+            let key: String = match shared_signer.descriptor_public_key() {
+                Ok(key) => key.to_string(),
+                Err(_) => continue,
+            };
+            if !descriptor.contains(&key) {
+                continue;
+            }
+
+            let uses: usize = vaults.iter().filter(|d| d.contains(&key)).count();
+            if scope.is_expired(now) || !scope.allows_template(template) || !scope.allows_uses(uses)
+            {
+                let profile: Profile = self.client.database().profile(owner_public_key).await?;
+                list.push(GetSharedSigner {
+                    shared_signer_id,
+                    owner: profile,
+                    shared_signer,
+                });
+            }
+        }
+        list.sort();
+        Ok(list)
+    }
 }
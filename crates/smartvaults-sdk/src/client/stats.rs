@@ -0,0 +1,296 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+use std::collections::{HashMap, HashSet};
+
+use nostr_sdk::database::Order;
+use nostr_sdk::{Event, EventId, Filter, PublicKey, Timestamp};
+use smartvaults_core::Signer;
+use smartvaults_protocol::v1::constants::{COMPLETED_PROPOSAL_KIND, PROPOSAL_KIND};
+
+use super::{Error, SmartVaults};
+use crate::storage::{InternalApproval, InternalPolicy};
+use crate::types::{ApproverStats, Counts, GetProposal};
+use crate::util;
+
+/// Proposal data needed to compute the approver stats
+struct ProposalRecord {
+    created_at: Timestamp,
+    /// Participants at proposal creation
+    participants: HashSet<PublicKey>,
+    completed: bool,
+}
+
+impl SmartVaults {
+    /// Get per-participant approval statistics of a policy
+    ///
+    /// The participants added after a proposal was created are not counted for that proposal.
+    pub async fn get_approver_stats(
+        &self,
+        policy_id: EventId,
+    ) -> Result<Vec<ApproverStats>, Error> {
+        let InternalPolicy { public_keys, .. } = self.storage.vault(&policy_id).await?;
+
+        // Completed proposals
+        let filter = Filter::new().kind(COMPLETED_PROPOSAL_KIND).event(policy_id);
+        let completed: HashSet<EventId> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?
+            .into_iter()
+            .filter_map(|event| event.event_ids().next().copied())
+            .collect();
+
+        // Proposals (the completed ones are deleted from storage, so use the events)
+        let filter = Filter::new().kind(PROPOSAL_KIND).event(policy_id);
+        let proposals: HashMap<EventId, ProposalRecord> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?
+            .into_iter()
+            .map(|event: Event| {
+                (
+                    event.id,
+                    ProposalRecord {
+                        created_at: event.created_at,
+                        participants: event.public_keys().copied().collect(),
+                        completed: completed.contains(&event.id),
+                    },
+                )
+            })
+            .collect();
+
+        // First approval of each participant per proposal
+        let mut approvals: HashMap<(EventId, PublicKey), Timestamp> = HashMap::new();
+        for approval in self.storage.approvals().await.into_values() {
+            if approval.policy_id == policy_id {
+                approvals
+                    .entry((approval.proposal_id, approval.public_key))
+                    .and_modify(|t| *t = (*t).min(approval.timestamp))
+                    .or_insert(approval.timestamp);
+            }
+        }
+
+        Ok(approver_stats(public_keys, &proposals, &approvals))
+    }
+
+    /// Get pending proposals, with a flag that indicate if they are awaiting the user's approval
+    async fn pending_proposals_with_state(&self) -> Vec<(GetProposal, bool)> {
+        let vaults: HashMap<EventId, InternalPolicy> = self.storage.vaults().await;
+        let signers: Vec<Signer> = self.storage.signers().await.into_values().collect();
+        let my_public_key: PublicKey = self.keys.public_key();
+
+        let mut approvals: HashMap<EventId, Vec<InternalApproval>> = HashMap::new();
+        for approval in self.storage.approvals().await.into_values() {
+            approvals
+                .entry(approval.proposal_id)
+                .or_default()
+                .push(approval);
+        }
+
+        let mut list = Vec::new();
+        for (proposal_id, p) in self.storage.proposals().await.into_iter() {
+            let proposal_approvals: Vec<InternalApproval> =
+                approvals.remove(&proposal_id).unwrap_or_default();
+            let signed: bool = p
+                .proposal
+                .finalize(
+                    proposal_approvals.iter().map(|a| a.approval.clone()),
+                    self.network,
+                )
+                .is_ok();
+            let awaiting_my_approval: bool = !signed
+                && !proposal_approvals
+                    .iter()
+                    .any(|a| a.public_key == my_public_key)
+                && vaults
+                    .get(&p.policy_id)
+                    .map(|v| {
+                        v.policy
+                            .search_used_signers(signers.iter().cloned())
+                            .next()
+                            .is_some()
+                    })
+                    .unwrap_or_default();
+            list.push((
+                GetProposal {
+                    proposal_id,
+                    policy_id: p.policy_id,
+                    signed,
+                    proposal: p.proposal,
+                    timestamp: p.timestamp,
+                    fee_warning: self.storage.fee_warning(&proposal_id).await,
+                },
+                awaiting_my_approval,
+            ));
+        }
+        list
+    }
+
+    /// Get pending proposals, of all the policies, that need an approval from one of the user's
+    /// signers and that the user has not approved yet
+    ///
+    /// Sorted oldest first (see [`GetProposal::age`]).
+    pub async fn get_proposals_awaiting_my_approval(&self) -> Result<Vec<GetProposal>, Error> {
+        let mut list: Vec<GetProposal> = self
+            .pending_proposals_with_state()
+            .await
+            .into_iter()
+            .filter_map(|(proposal, awaiting)| if awaiting { Some(proposal) } else { None })
+            .collect();
+        list.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.proposal_id.cmp(&b.proposal_id))
+        });
+        Ok(list)
+    }
+
+    /// Get dashboard counters
+    pub async fn get_counts(&self) -> Result<Counts, Error> {
+        let pending = self.pending_proposals_with_state().await;
+        let start_of_month: Timestamp = util::start_of_month(Timestamp::now());
+        Ok(Counts {
+            policies: self.storage.vaults().await.len(),
+            pending_proposals: pending.len(),
+            awaiting_my_approval: pending.iter().filter(|(_, awaiting)| *awaiting).count(),
+            ready_to_finalize: pending.iter().filter(|(p, ..)| p.signed).count(),
+            completed_this_month: self
+                .storage
+                .completed_proposals()
+                .await
+                .into_values()
+                .filter(|p| p.timestamp >= start_of_month)
+                .count(),
+            balance: self.get_total_balance().await?,
+        })
+    }
+}
+
+fn approver_stats<I>(
+    participants: I,
+    proposals: &HashMap<EventId, ProposalRecord>,
+    approvals: &HashMap<(EventId, PublicKey), Timestamp>,
+) -> Vec<ApproverStats>
+where
+    I: IntoIterator<Item = PublicKey>,
+{
+    participants
+        .into_iter()
+        .map(|public_key| {
+            let mut stats = ApproverStats {
+                public_key,
+                proposals: 0,
+                approved: 0,
+                median_approval_time: None,
+                finalized_without: 0,
+            };
+            let mut times: Vec<u64> = Vec::new();
+
+            for (proposal_id, proposal) in proposals.iter() {
+                if !proposal.participants.contains(&public_key) {
+                    continue;
+                }
+
+                stats.proposals += 1;
+                match approvals.get(&(*proposal_id, public_key)) {
+                    Some(timestamp) => {
+                        stats.approved += 1;
+                        times.push(
+                            timestamp
+                                .as_u64()
+                                .saturating_sub(proposal.created_at.as_u64()),
+                        );
+                    }
+                    None => {
+                        if proposal.completed {
+                            stats.finalized_without += 1;
+                        }
+                    }
+                }
+            }
+
+            stats.median_approval_time = median(times);
+            stats
+        })
+        .collect()
+}
+
+fn median(mut values: Vec<u64>) -> Option<u64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid: usize = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2)
+    } else {
+        Some(values[mid])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::Keys;
+
+    use super::*;
+
+    #[test]
+    fn test_approver_stats() {
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+
+        let p1 = EventId::all_zeros();
+        let p2 = EventId::from_slice(&[1; 32]).unwrap();
+        let p3 = EventId::from_slice(&[2; 32]).unwrap();
+
+        let mut proposals = HashMap::new();
+        proposals.insert(
+            p1,
+            ProposalRecord {
+                created_at: Timestamp::from(1_000),
+                participants: [alice].into(),
+                completed: true,
+            },
+        );
+        proposals.insert(
+            p2,
+            ProposalRecord {
+                created_at: Timestamp::from(2_000),
+                participants: [alice, bob].into(),
+                completed: true,
+            },
+        );
+        proposals.insert(
+            p3,
+            ProposalRecord {
+                created_at: Timestamp::from(3_000),
+                participants: [alice, bob].into(),
+                completed: false,
+            },
+        );
+
+        let mut approvals = HashMap::new();
+        approvals.insert((p1, alice), Timestamp::from(1_100));
+        approvals.insert((p2, alice), Timestamp::from(2_300));
+        approvals.insert((p3, bob), Timestamp::from(3_600));
+
+        let stats = approver_stats([alice, bob], &proposals, &approvals);
+
+        let alice_stats = &stats[0];
+        assert_eq!(alice_stats.proposals, 3);
+        assert_eq!(alice_stats.approved, 2);
+        assert_eq!(alice_stats.median_approval_time, Some(200));
+        assert_eq!(alice_stats.finalized_without, 0);
+
+        // Bob was added after p1
+        let bob_stats = &stats[1];
+        assert_eq!(bob_stats.proposals, 2);
+        assert_eq!(bob_stats.approved, 1);
+        assert_eq!(bob_stats.approval_rate(), Some(0.5));
+        assert_eq!(bob_stats.median_approval_time, Some(600));
+        assert_eq!(bob_stats.finalized_without, 1);
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::ops::Add;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -10,46 +10,82 @@ use async_utility::thread;
 use futures_util::stream::AbortHandle;
 use nostr_sdk::database::NostrDatabaseExt;
 use nostr_sdk::nips::nip46::{Message as NIP46Message, Request as NIP46Request};
+use nostr_sdk::nips::nip65::RelayMetadata;
 use nostr_sdk::nips::{nip04, nip65};
 use nostr_sdk::{
     ClientMessage, Event, EventBuilder, EventId, Filter, JsonUtil, Keys, Kind, NegentropyDirection,
     NegentropyOptions, PublicKey, RelayMessage, RelayPoolNotification, RelaySendOptions, Result,
     SubscribeAutoCloseOptions, SubscribeOptions, SubscriptionId, Timestamp, Url,
 };
+use parking_lot::Mutex;
 use smartvaults_core::bdk::chain::ConfirmationTime;
 use smartvaults_core::bdk::FeeRate;
-use smartvaults_core::bitcoin::Network;
+use smartvaults_core::bitcoin::{Network, Txid};
 use smartvaults_core::{CompletedProposal, Priority};
 use smartvaults_protocol::v1::constants::{
-    APPROVED_PROPOSAL_KIND, COMPLETED_PROPOSAL_KIND, KEY_AGENT_SIGNALING,
-    KEY_AGENT_SIGNER_OFFERING_KIND, KEY_AGENT_VERIFIED, LABELS_KIND, POLICY_KIND, PROPOSAL_KIND,
-    SHARED_KEY_KIND, SHARED_SIGNERS_KIND, SIGNERS_KIND, SMARTVAULTS_MAINNET_PUBLIC_KEY,
-    SMARTVAULTS_TESTNET_PUBLIC_KEY,
+    APPROVAL_REMINDER_KIND, APPROVED_PROPOSAL_KIND, CAPABILITIES_KIND, COMPLETED_PROPOSAL_KIND,
+    INGESTION_REPORT_KIND, KEY_AGENT_SIGNALING, KEY_AGENT_SIGNER_OFFERING_KIND, KEY_AGENT_VERIFIED,
+    LABELS_KIND, POLICY_KIND, PROPOSAL_KIND, SHARED_KEY_KIND, SHARED_SIGNERS_KIND, SIGNERS_KIND,
+    SMARTVAULTS_MAINNET_PUBLIC_KEY, SMARTVAULTS_TESTNET_PUBLIC_KEY,
+};
+use smartvaults_protocol::v1::{Capabilities, IngestionProblem, Serde};
+use smartvaults_sdk_sqlite::model::{
+    NotificationKind, OutboxEvent, PendingEventRetry, QuarantinedEvent, RelayRole, SigningKind,
 };
-use tokio::sync::broadcast::Receiver;
 
-use super::{Error, SmartVaults};
-use crate::constants::DEFAULT_SUBSCRIPTION_ID;
+use super::{Error, SmartVaults, SyncSubscription};
+use crate::config::{Backend, SyncProfile};
+use crate::constants::{
+    BACKFILL_TIMEOUT, CAPABILITIES_REFRESH_INTERVAL, DEFAULT_SUBSCRIPTION_ID,
+    LIGHT_TIMECHAIN_SYNC_INTERVAL, OUTBOX_MAX_ATTEMPTS, PENDING_EVENTS_CHECK_INTERVAL,
+    PENDING_EVENT_BACKOFF, PENDING_EVENT_MAX_RETRIES, REBROADCAST_CHECK_INTERVAL,
+    REBROADCAST_INTERVAL, SYNC_PROGRESS_STEP,
+};
 use crate::storage::{InternalCompletedProposal, InternalPolicy};
+use crate::util::progress::{CancellationToken, Progress, ProgressCallback, Task};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+/// What changed after handling an event
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum EventHandled {
+    /// Shared key of a policy received (event ID)
     SharedKey(EventId),
+    /// New policy (policy ID)
     Policy(EventId),
+    /// New proposal (proposal ID)
     Proposal(EventId),
-    Approval { proposal_id: EventId },
+    /// New approval of a proposal
+    Approval {
+        proposal_id: EventId,
+        approval_id: EventId,
+    },
+    /// Proposal completed (completed proposal ID)
     CompletedProposal(EventId),
+    /// New signer (signer ID)
     Signer(EventId),
+    /// One of my signers was shared (shared signer ID)
     MySharedSigner(EventId),
+    /// A signer was shared with me (shared signer ID)
     SharedSigner(EventId),
+    /// Contact list updated
     Contacts,
+    /// Metadata of the public key updated
     Metadata(PublicKey),
+    /// New Nostr Connect request (request ID)
     NostrConnectRequest(EventId),
-    Label,
-    EventDeletion,
+    /// New or updated label of the policy
+    Label { policy_id: EventId },
+    /// Events deleted (IDs of the deleted events)
+    EventDeletion(Vec<EventId>),
+    /// My relay list updated
     RelayList,
-    KeyAgentSignerOffering,
+    /// Key agent signaling (key agent public key)
+    KeyAgentSignaling(PublicKey),
+    /// New or updated signer offering (event ID)
+    KeyAgentSignerOffering(EventId),
+    /// Verified key agents list updated
     VerifiedKeyAgents,
+    /// Protocol capabilities advertised (peer public key)
+    PeerCapabilities(PublicKey),
 }
 
 #[derive(Debug, Clone)]
@@ -58,27 +94,188 @@ pub enum Message {
     WalletSyncCompleted(EventId),
     BlockHeightUpdated,
     MempoolFeesUpdated(BTreeMap<Priority, FeeRate>),
+    /// The app crashed while handling this event: moved to the dead-letter table
+    PoisonEventQuarantined(EventId),
+    /// The collected approvals are enough to finalize the proposal
+    ProposalReady(EventId),
+    /// A critical event was acknowledged by less relays than required: it's waiting in the outbox
+    InsufficientRelayAcks {
+        event_id: EventId,
+        got: usize,
+        want: usize,
+    },
+    /// An event was rejected by a relay [`OUTBOX_MAX_ATTEMPTS`] times: removed from the outbox
+    OutboxEventDropped {
+        event_id: EventId,
+        relay_url: Url,
+    },
+    /// **High severity**: funds moved out of the vault by a transaction without a matching
+    /// completed proposal (i.e. co-signers colluding outside the app or a timelock path exercised)
+    UnexpectedOutgoingTx {
+        policy_id: EventId,
+        txid: Txid,
+        /// Net amount (sat) moved out of the vault
+        amount: u64,
+    },
+    /// Proof of Reserve automatically verified
+    ProofVerified {
+        completed_proposal_id: EventId,
+        /// Spendable amount (sat)
+        spendable: u64,
+    },
+    /// Proof of Reserve automatic verification failed (completed proposal ID)
+    ProofInvalid(EventId),
+    /// PSBT signed by an external signer imported as approval (proposal ID)
+    ExternalPsbtImported(EventId),
+    /// PSBT signed by an external signer rejected
+    ExternalPsbtRejected {
+        proposal_id: EventId,
+        reason: String,
+    },
+    /// A policy member can't read a proposal (i.e. shared key mismatch)
+    MemberCannotReadProposal {
+        member: PublicKey,
+        proposal_id: EventId,
+        reason_code: IngestionProblem,
+    },
+    /// Reminder to approve a proposal (not sent if muted)
+    ApprovalReminder {
+        proposal_id: EventId,
+        /// Reminder number (starts from `1`)
+        count: u16,
+    },
+    /// The wallet state of the policy didn't match its descriptor or the network: quarantined and
+    /// rescanned from scratch
+    PolicyWalletMismatch(EventId),
+    /// Nostr Connect request waiting for approval
+    NewNostrConnectRequest {
+        app_public_key: PublicKey,
+        request_id: EventId,
+        method: String,
+    },
+    /// Nostr Connect request approved automatically (pre-authorized app)
+    NostrConnectRequestAutoApproved {
+        app_public_key: PublicKey,
+        request_id: EventId,
+        method: String,
+    },
+    /// Initial sync progress of a relay (sent every [`SYNC_PROGRESS_STEP`] events and at EOSE)
+    SyncProgress {
+        relay_url: Url,
+        events_received: usize,
+        eose: bool,
+    },
+    /// Every subscribed relay sent the EOSE of the initial subscription
+    InitialSyncComplete {
+        /// Events received (without duplicates)
+        events: usize,
+        relays: usize,
+    },
+}
+
+/// Per-relay state of the initial subscription, until every relay sent the EOSE
+#[derive(Debug, Default)]
+struct InitialSync {
+    /// Events received and EOSE
+    relays: HashMap<Url, (usize, bool)>,
+    events: HashSet<EventId>,
+    completed: bool,
+}
+
+impl InitialSync {
+    fn new<I>(relays: I) -> Self
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        let relays: HashMap<Url, (usize, bool)> =
+            relays.into_iter().map(|url| (url, (0, false))).collect();
+        Self {
+            completed: relays.is_empty(),
+            relays,
+            events: HashSet::new(),
+        }
+    }
+
+    /// Return the events received from the relay, if a progress message must be sent
+    fn event_received(&mut self, relay_url: &Url, event_id: EventId) -> Option<usize> {
+        if self.completed {
+            return None;
+        }
+        match self.relays.get_mut(relay_url) {
+            Some((events_received, false)) => {
+                self.events.insert(event_id);
+                *events_received += 1;
+                if *events_received == 1 || *events_received % SYNC_PROGRESS_STEP == 0 {
+                    Some(*events_received)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the events received from the relay, if it's the first EOSE
+    fn eose_received(&mut self, relay_url: &Url) -> Option<usize> {
+        if self.completed {
+            return None;
+        }
+        match self.relays.get_mut(relay_url) {
+            Some((events_received, eose @ false)) => {
+                *eose = true;
+                Some(*events_received)
+            }
+            _ => None,
+        }
+    }
+
+    /// Mark as completed if every relay sent the EOSE
+    ///
+    /// Return `true` only the first time.
+    fn check_completed(&mut self) -> bool {
+        if !self.completed && self.relays.values().all(|(_, eose)| *eose) {
+            self.completed = true;
+            return true;
+        }
+        false
+    }
 }
 
 impl SmartVaults {
+    async fn timechain_sync_interval(&self) -> Duration {
+        match self.config.sync_profile().await {
+            SyncProfile::Full => Duration::from_secs(10),
+            SyncProfile::Light => LIGHT_TIMECHAIN_SYNC_INTERVAL,
+        }
+    }
+
     fn block_height_syncer(&self) -> Result<AbortHandle, Error> {
         let this = self.clone();
         Ok(thread::abortable(async move {
             loop {
-                match this.config.electrum_endpoint().await {
-                    Ok(endpoint) => {
-                        let proxy = this.config.proxy().await.ok();
-                        match this.manager.sync_block_height(endpoint, proxy).await {
-                            Ok(_) => {
-                                let _ = this.sync_channel.send(Message::BlockHeightUpdated);
+                if let Backend::BitcoinCore { url, auth } = this.config.backend().await {
+                    match this.manager.sync_block_height_bitcoind(&url, auth).await {
+                        Ok(_) => {
+                            this.sync_channel.send(Message::BlockHeightUpdated);
+                        }
+                        Err(e) => tracing::error!("Impossible to sync block height: {e}"),
+                    }
+                } else {
+                    match this.config.active_electrum_endpoint().await {
+                        Ok(endpoint) => {
+                            let proxy = this.config.proxy().await.ok();
+                            match this.manager.sync_block_height(endpoint, proxy).await {
+                                Ok(_) => {
+                                    this.sync_channel.send(Message::BlockHeightUpdated);
+                                }
+                                Err(e) => tracing::error!("Impossible to sync block height: {e}"),
                             }
-                            Err(e) => tracing::error!("Impossible to sync block height: {e}"),
                         }
+                        Err(e) => tracing::error!("Impossible to sync wallets: {e}"),
                     }
-                    Err(e) => tracing::error!("Impossible to sync wallets: {e}"),
                 }
 
-                thread::sleep(Duration::from_secs(10)).await;
+                thread::sleep(this.timechain_sync_interval().await).await;
             }
         })?)
     }
@@ -87,21 +284,34 @@ impl SmartVaults {
         let this = self.clone();
         Ok(thread::abortable(async move {
             loop {
-                match this.config.electrum_endpoint().await {
-                    Ok(endpoint) => {
-                        let proxy = this.config.proxy().await.ok();
-                        match this.manager.sync_mempool_fees(endpoint, proxy).await {
-                            Ok(Some(fees)) => {
-                                let _ = this.sync_channel.send(Message::MempoolFeesUpdated(fees));
-                            }
-                            Ok(None) => (),
-                            Err(e) => tracing::error!("Impossible to get mempool fees: {e}"),
+                let fee_targets = this.config.fee_targets().await;
+                let res = match this.config.backend().await {
+                    Backend::Electrum => match this.config.active_electrum_endpoint().await {
+                        Ok(endpoint) => {
+                            let proxy = this.config.proxy().await.ok();
+                            this.manager
+                                .sync_mempool_fees(endpoint, proxy, fee_targets)
+                                .await
+                                .map_err(Error::from)
                         }
+                        Err(e) => Err(e.into()),
+                    },
+                    Backend::BitcoinCore { url, auth } => this
+                        .manager
+                        .sync_mempool_fees_bitcoind(&url, auth, fee_targets)
+                        .await
+                        .map_err(Error::from),
+                };
+
+                match res {
+                    Ok(Some(fees)) => {
+                        this.sync_channel.send(Message::MempoolFeesUpdated(fees));
                     }
+                    Ok(None) => (),
                     Err(e) => tracing::error!("Impossible to get mempool fees: {e}"),
                 }
 
-                thread::sleep(Duration::from_secs(10)).await;
+                thread::sleep(this.timechain_sync_interval().await).await;
             }
         })?)
     }
@@ -110,21 +320,89 @@ impl SmartVaults {
         let this = self.clone();
         Ok(thread::abortable(async move {
             loop {
-                match this.config.electrum_endpoint().await {
-                    Ok(endpoint) => {
-                        let proxy = this.config.proxy().await.ok();
+                let backend: Backend = this.config.backend().await;
+                let res = match &backend {
+                    Backend::Electrum => match this.electrum_endpoint_with_failover().await {
+                        Ok(endpoint) => {
+                            let proxy = this.config.proxy().await.ok();
+                            let gap_limit: usize = this.config.gap_limit().await;
+                            if let Err(e) = this
+                                .manager
+                                .sync_all(
+                                    endpoint,
+                                    proxy,
+                                    gap_limit,
+                                    Some(this.sync_channel.clone()),
+                                )
+                                .await
+                            {
+                                tracing::error!("Impossible to sync all wallets: {e}");
+                            }
+                            Ok(())
+                        }
+                        Err(e) => Err(e),
+                    },
+                    Backend::BitcoinCore { url, auth } => {
                         if let Err(e) = this
                             .manager
-                            .sync_all(endpoint, proxy, Some(this.sync_channel.clone()))
+                            .bitcoind_sync_all(
+                                url,
+                                auth.clone(),
+                                false,
+                                Some(this.sync_channel.clone()),
+                            )
                             .await
                         {
                             tracing::error!("Impossible to sync all wallets: {e}");
                         }
+                        Ok(())
+                    }
+                };
+
+                match res {
+                    Ok(()) => {
+                        if let Err(e) = this.mark_superseded_proposals().await {
+                            tracing::error!("Impossible to mark superseded proposals: {e}");
+                        }
+
+                        if let Err(e) = this.classify_outgoing_txs().await {
+                            tracing::error!("Impossible to classify outgoing txs: {e}");
+                        }
+
+                        if let Err(e) = this.record_tx_fees().await {
+                            tracing::error!("Impossible to record tx fees: {e}");
+                        }
+
+                        if let Err(e) = this.send_approval_reminders().await {
+                            tracing::error!("Impossible to send approval reminders: {e}");
+                        }
+
+                        if let Err(e) = this.verify_proofs().await {
+                            tracing::error!("Impossible to verify proofs: {e}");
+                        }
+
+                        if let Err(e) = this.prune_orphans().await {
+                            tracing::error!("Impossible to prune orphaned rows: {e}");
+                        }
                     }
                     Err(e) => tracing::error!("Impossible to sync wallets: {e}"),
                 }
 
-                thread::sleep(Duration::from_secs(10)).await;
+                let interval: Duration = this.timechain_sync_interval().await;
+                match backend {
+                    // Wake up as soon as a new block is found
+                    Backend::BitcoinCore { url, auth } => {
+                        match this.manager.wait_for_new_block(&url, auth, interval).await {
+                            Ok(Some(_)) => this.sync_channel.send(Message::BlockHeightUpdated),
+                            Ok(None) => (),
+                            Err(e) => {
+                                tracing::error!("Impossible to wait for a new block: {e}");
+                                thread::sleep(interval).await;
+                            }
+                        }
+                    }
+                    Backend::Electrum => thread::sleep(interval).await,
+                }
             }
         })?)
     }
@@ -133,45 +411,247 @@ impl SmartVaults {
         let this = self.clone();
         Ok(thread::abortable(async move {
             loop {
-                for event in this.storage.pending_events().await.into_iter() {
-                    let event_id = event.id;
-                    if let Err(e) = this.handle_event(event).await {
-                        tracing::error!("Impossible to handle pending event {event_id}: {e}");
+                if let Err(e) = this.retry_pending_events().await {
+                    tracing::error!("Impossible to retry pending events: {e}");
+                }
+                thread::sleep(PENDING_EVENTS_CHECK_INTERVAL).await;
+            }
+        })?)
+    }
+
+    /// Retry the pending events not backing off nor failed
+    async fn retry_pending_events(&self) -> Result<(), Error> {
+        let pending: BTreeSet<Event> = self.storage.pending_events().await;
+        let mut retries: HashMap<EventId, PendingEventRetry> =
+            self.db.get_pending_event_retries().await?;
+
+        // Handled in the meantime
+        for event_id in retries.keys() {
+            if !pending.iter().any(|e| &e.id == event_id) {
+                self.db.delete_pending_event_retry(*event_id).await?;
+            }
+        }
+
+        let now = Timestamp::now();
+        for event in pending.into_iter() {
+            let retries: u32 = match retries.remove(&event.id) {
+                Some(retry) if retry.failed || retry.next_retry_at > now => continue,
+                Some(retry) => retry.retries,
+                None => 0,
+            };
+            self.retry_pending_event(event, retries).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Handle again a pending event: if still pending, the next retry is delayed by
+    /// [`PENDING_EVENT_BACKOFF`] and, after [`PENDING_EVENT_MAX_RETRIES`], the event is moved to
+    /// the dead-letter state.
+    ///
+    /// Return `true` if the event was handled.
+    async fn retry_pending_event(&self, event: Event, retries: u32) -> Result<bool, Error> {
+        let event_id: EventId = event.id;
+        if let Err(e) = self.handle_event(event.clone()).await {
+            tracing::error!("Impossible to handle pending event {event_id}: {e}");
+        }
+
+        if !self.storage.is_pending(&event_id).await {
+            self.db.delete_pending_event_retry(event_id).await?;
+            return Ok(true);
+        }
+
+        let retries: u32 = retries.saturating_add(1);
+        let failed: bool = retries >= PENDING_EVENT_MAX_RETRIES;
+        let index: usize = (retries as usize - 1).min(PENDING_EVENT_BACKOFF.len() - 1);
+        if failed {
+            tracing::warn!("Pending event {event_id} still pending after {retries} retries: moved to dead-letter");
+        } else {
+            tracing::debug!("Pending event {event_id} still pending (retry {retries})");
+        }
+        self.db
+            .save_pending_event_retry(PendingEventRetry {
+                event,
+                retries,
+                next_retry_at: Timestamp::now().add(PENDING_EVENT_BACKOFF[index]),
+                failed,
+            })
+            .await?;
+        Ok(false)
+    }
+
+    fn outbox_drainer(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                if let Err(e) = this.drain_outbox().await {
+                    tracing::error!("Impossible to drain outbox: {e}");
+                }
+                thread::sleep(Duration::from_secs(60)).await;
+            }
+        })?)
+    }
+
+    /// Refresh the subscriptions every time the sync filters change
+    fn subscriptions_refresher(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                // Requests received while refreshing are coalesced in a single refresh
+                this.subscriptions_changed.notified().await;
+                this.refresh_subscriptions().await;
+            }
+        })?)
+    }
+
+    fn capabilities_publisher(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                if let Err(e) = this.publish_capabilities().await {
+                    tracing::error!("Impossible to publish capabilities: {e}");
+                }
+                thread::sleep(CAPABILITIES_REFRESH_INTERVAL).await;
+            }
+        })?)
+    }
+
+    /// Rebroadcast all the events every [`REBROADCAST_INTERVAL`]
+    fn rebroadcaster(&self) -> Result<AbortHandle, Error> {
+        let this = self.clone();
+        Ok(thread::abortable(async move {
+            loop {
+                let due: bool = match this.db.get_last_rebroadcast().await {
+                    Ok(Some(last)) => last.add(REBROADCAST_INTERVAL) <= Timestamp::now(),
+                    Ok(None) => true,
+                    Err(e) => {
+                        tracing::error!("Impossible to get last rebroadcast timestamp: {e}");
+                        false
+                    }
+                };
+
+                if due {
+                    match this.rebroadcast_all_events().await {
+                        Ok(..) => tracing::info!("All events rebroadcasted"),
+                        Err(e) => tracing::error!("Impossible to rebroadcast events: {e}"),
                     }
                 }
-                thread::sleep(Duration::from_secs(30)).await;
+
+                thread::sleep(REBROADCAST_CHECK_INTERVAL).await;
             }
         })?)
     }
 
-    pub fn sync_notifications(&self) -> Receiver<Message> {
-        self.sync_channel.subscribe()
+    async fn drain_outbox(&self) -> Result<(), Error> {
+        let relays = self.relays_with_role(false, true).await;
+        for OutboxEvent {
+            relay_url,
+            event,
+            attempts,
+            ..
+        } in self.db.get_outbox_events().await?.into_iter()
+        {
+            let event_id: EventId = event.id;
+            match relays.get(&relay_url) {
+                Some(relay) => match relay.send_event(event, RelaySendOptions::new()).await {
+                    Ok(..) => {
+                        tracing::info!("Outbox event {event_id} published to {relay_url}");
+                        self.db.delete_outbox_event(relay_url, event_id).await?;
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Impossible to publish event {event_id} to {relay_url}: {e}"
+                        );
+                        self.relay_monitor.publish_failed(&relay_url);
+                        if attempts + 1 >= OUTBOX_MAX_ATTEMPTS {
+                            tracing::error!(
+                                "Outbox event {event_id} dropped after {OUTBOX_MAX_ATTEMPTS} attempts to publish to {relay_url}"
+                            );
+                            self.db
+                                .delete_outbox_event(relay_url.clone(), event_id)
+                                .await?;
+                            self.sync_channel.send(Message::OutboxEventDropped {
+                                event_id,
+                                relay_url,
+                            });
+                        } else {
+                            self.db
+                                .increment_outbox_event_attempts(relay_url, event_id)
+                                .await?;
+                        }
+                    }
+                },
+                // Relay removed or no longer writable
+                None => self.db.delete_outbox_event(relay_url, event_id).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Subscribe to sync notifications
+    pub fn sync_notifications(&self) -> SyncSubscription {
+        self.sync_channel.subscribe(None)
+    }
+
+    /// Subscribe to sync notifications, replaying the buffered ones with sequence number `>= since`
+    ///
+    /// Use `0` to replay all the buffered notifications.
+    pub fn sync_notifications_since(&self, since: u64) -> SyncSubscription {
+        self.sync_channel.subscribe(Some(since))
+    }
+
+    /// Sequence number of the last sync notification
+    pub fn last_sync_notification_seq(&self) -> u64 {
+        self.sync_channel.last_seq()
     }
 
     pub(crate) async fn sync_filters(&self, since: Timestamp) -> Vec<Filter> {
+        let profile: SyncProfile = self.config.sync_profile().await;
+
         let base_filter = Filter::new().kinds([
             POLICY_KIND,
-            PROPOSAL_KIND,
-            APPROVED_PROPOSAL_KIND,
-            COMPLETED_PROPOSAL_KIND,
             SHARED_KEY_KIND,
             SIGNERS_KIND,
             SHARED_SIGNERS_KIND,
             LABELS_KIND,
+            INGESTION_REPORT_KIND,
+            APPROVAL_REMINDER_KIND,
             Kind::EventDeletion,
         ]);
+        let history_filter = Filter::new().kinds([
+            PROPOSAL_KIND,
+            APPROVED_PROPOSAL_KIND,
+            COMPLETED_PROPOSAL_KIND,
+        ]);
+
+        // The light profile syncs the history only within the horizon (see `backfill_policy`)
+        let history_since: Timestamp = match profile {
+            SyncProfile::Full => since,
+            SyncProfile::Light => {
+                let horizon = self.config.light_sync_horizon().await;
+                since.max(Timestamp::now() - horizon)
+            }
+        };
 
         let keys: &Keys = self.keys();
         let public_key: PublicKey = keys.public_key();
-        let contacts: Vec<PublicKey> = self
-            .client
-            .database()
-            .contacts_public_keys(public_key)
-            .await
-            .unwrap_or_default();
+        let contacts: Vec<PublicKey> = match profile {
+            SyncProfile::Full => self
+                .client
+                .database()
+                .contacts_public_keys(public_key)
+                .await
+                .unwrap_or_default(),
+            SyncProfile::Light => Vec::new(),
+        };
 
         let author_filter: Filter = base_filter.clone().author(public_key).since(since);
         let pubkey_filter: Filter = base_filter.pubkey(public_key).since(since);
+        let history_author_filter: Filter = history_filter
+            .clone()
+            .author(public_key)
+            .since(history_since);
+        let history_pubkey_filter: Filter = history_filter.pubkey(public_key).since(history_since);
         let nostr_connect_filter = Filter::new()
             .pubkey(public_key)
             .kind(Kind::NostrConnect)
@@ -183,6 +663,33 @@ impl SmartVaults {
         let key_agents: Filter = Filter::new()
             .kinds([KEY_AGENT_SIGNALING, KEY_AGENT_SIGNER_OFFERING_KIND])
             .since(since);
+        // Capabilities of the co-signers
+        let members: HashSet<PublicKey> = self
+            .storage
+            .vaults()
+            .await
+            .into_values()
+            .flat_map(|internal| internal.public_keys)
+            .collect();
+        let capabilities: Filter = Filter::new()
+            .kind(CAPABILITIES_KIND)
+            .identifier(self.network.magic().to_string())
+            .authors(members)
+            .author(public_key)
+            .since(since);
+        // Events authored by the shared keys of the policies
+        let shared_public_keys: Vec<PublicKey> = self.storage.shared_public_keys().await;
+        let shared_keys: Filter = Filter::new()
+            .authors(shared_public_keys.clone())
+            .kinds([
+                POLICY_KIND,
+                PROPOSAL_KIND,
+                APPROVED_PROPOSAL_KIND,
+                COMPLETED_PROPOSAL_KIND,
+                LABELS_KIND,
+                Kind::EventDeletion,
+            ])
+            .since(history_since);
         let smartvaults: Filter = Filter::new()
             .author(match self.network {
                 Network::Bitcoin => *SMARTVAULTS_MAINNET_PUBLIC_KEY,
@@ -193,12 +700,19 @@ impl SmartVaults {
         let mut filters = vec![
             author_filter,
             pubkey_filter,
+            history_author_filter,
+            history_pubkey_filter,
             nostr_connect_filter,
             other_filters,
             key_agents,
+            capabilities,
             smartvaults,
         ];
 
+        if !shared_public_keys.is_empty() {
+            filters.push(shared_keys);
+        }
+
         if !contacts.is_empty() {
             filters.push(Filter::new().authors(contacts).since(since));
         }
@@ -206,6 +720,132 @@ impl SmartVaults {
         filters
     }
 
+    /// Replace, in place, the subscription of every read relay with the current sync filters
+    ///
+    /// The filters change when a policy is saved or deleted, or when a shared key is received:
+    /// the refresh is done automatically in these cases. The relays are not reconnected.
+    pub async fn refresh_subscriptions(&self) {
+        for (relay_url, relay) in self.relays_with_role(true, false).await {
+            let last_sync: Timestamp = match self.db.get_last_relay_sync(relay_url.clone()).await {
+                Ok(ts) => ts,
+                Err(e) => {
+                    tracing::error!("Impossible to get last relay sync: {e}");
+                    Timestamp::from(0)
+                }
+            };
+            let filters: Vec<Filter> = self.sync_filters(last_sync).await;
+            // Same subscription ID: the relay replaces the previous subscription
+            if let Err(e) = relay
+                .subscribe_with_id(
+                    SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID),
+                    filters,
+                    SubscribeOptions::default(),
+                )
+                .await
+            {
+                tracing::error!("Impossible to subscribe to {relay_url}: {e}");
+            }
+        }
+    }
+
+    /// Request a [`SmartVaults::refresh_subscriptions`] to the syncing thread
+    pub(crate) fn request_subscriptions_refresh(&self) {
+        self.subscriptions_changed.notify_one();
+    }
+
+    /// Fetch and handle all the history events (proposals, approvals and completed proposals)
+    /// of a policy, included the ones older than the light profile horizon
+    ///
+    /// Return the number of handled events.
+    pub async fn backfill_policy(&self, policy_id: EventId) -> Result<usize, Error> {
+        self.backfill_policy_with_progress(policy_id, None, CancellationToken::default())
+            .await
+    }
+
+    /// Same as [`SmartVaults::backfill_policy`], reporting the handled events
+    pub async fn backfill_policy_with_progress(
+        &self,
+        policy_id: EventId,
+        progress: Option<ProgressCallback>,
+        token: CancellationToken,
+    ) -> Result<usize, Error> {
+        let task = Task::new(progress, token);
+        task.report(Progress::default());
+
+        let filter: Filter = Filter::new()
+            .kinds([
+                PROPOSAL_KIND,
+                APPROVED_PROPOSAL_KIND,
+                COMPLETED_PROPOSAL_KIND,
+                LABELS_KIND,
+            ])
+            .event(policy_id);
+        let events: Vec<Event> = self
+            .client
+            .get_events_of(vec![filter], Some(BACKFILL_TIMEOUT))
+            .await?;
+
+        let total: usize = events.len();
+        task.report(Progress::new(0, total));
+
+        let mut handled: usize = 0;
+        for (index, event) in events.into_iter().enumerate() {
+            task.checkpoint()?;
+            let event_id: EventId = event.id;
+            match self.handle_event(event).await {
+                Ok(()) => handled += 1,
+                Err(e) => tracing::error!("Impossible to handle backfilled event {event_id}: {e}"),
+            }
+            task.report(Progress::new(index + 1, total));
+        }
+
+        tracing::info!("Backfilled {handled} events of policy {policy_id}");
+
+        Ok(handled)
+    }
+
+    /// Set the sync profile
+    ///
+    /// Switching from [`SyncProfile::Light`] to [`SyncProfile::Full`] resubscribes with the full
+    /// filters and backfills, in background, the history of every policy and the contacts metadata.
+    pub async fn set_sync_profile(&self, profile: SyncProfile) -> Result<(), Error> {
+        let previous: SyncProfile = self.config.sync_profile().await;
+        self.config.set_sync_profile(profile).await;
+        self.config.save().await?;
+
+        if previous == SyncProfile::Light && profile == SyncProfile::Full {
+            let filters: Vec<Filter> = self.sync_filters(Timestamp::now()).await;
+            for (relay_url, relay) in self.relays_with_role(true, false).await {
+                if let Err(e) = relay
+                    .subscribe_with_id(
+                        SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID),
+                        filters.clone(),
+                        SubscribeOptions::default(),
+                    )
+                    .await
+                {
+                    tracing::error!("Impossible to subscribe to {relay_url}: {e}");
+                }
+            }
+
+            let this = self.clone();
+            thread::spawn(async move {
+                for policy_id in this.storage.vaults().await.into_keys() {
+                    if let Err(e) = this.backfill_policy(policy_id).await {
+                        tracing::error!("Impossible to backfill policy {policy_id}: {e}");
+                    }
+                }
+
+                // Contacts metadata
+                if let Err(e) = this.get_contacts_with_metadata().await {
+                    tracing::error!("Impossible to refresh contacts metadata: {e}");
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn sync(&self) -> Result<(), Error> {
         if self.syncing.load(Ordering::SeqCst) {
             tracing::warn!("Syncing threads are already running");
@@ -220,37 +860,51 @@ impl SmartVaults {
                 let mempool_fees_syncer: AbortHandle = this.mempool_fees_syncer()?;
                 let policies_syncer: AbortHandle = this.policies_syncer()?;
 
+                // Fiat prices
+                let prices_syncer: AbortHandle = this.prices_syncer()?;
+
                 // Pending events handler
                 let pending_event_handler = this.handle_pending_events()?;
 
-                for (relay_url, relay) in this.client.relays().await {
-                    let last_sync: Timestamp =
-                        match this.db.get_last_relay_sync(relay_url.clone()).await {
-                            Ok(ts) => ts,
-                            Err(e) => {
-                                tracing::error!("Impossible to get last relay sync: {e}");
-                                Timestamp::from(0)
-                            }
-                        };
-                    let filters: Vec<Filter> = this.sync_filters(last_sync).await;
-                    if let Err(e) = relay
-                        .subscribe_with_id(
-                            SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID),
-                            filters,
-                            SubscribeOptions::default(),
-                        )
-                        .await
-                    {
-                        tracing::error!("Impossible to subscribe to {relay_url}: {e}");
-                    }
+                // Outbox drainer
+                let outbox_drainer = this.outbox_drainer()?;
+
+                // Protocol capabilities advertisement
+                let capabilities_publisher = this.capabilities_publisher()?;
+
+                // External signers PSBT directory
+                let psbt_dir_watcher = this.psbt_dir_watcher()?;
+
+                // Periodic rebroadcast
+                let rebroadcaster = this.rebroadcaster()?;
+
+                // Same relays subscribed by the refresh
+                let initial_sync = Mutex::new(InitialSync::new(
+                    this.relays_with_role(true, false).await.into_keys(),
+                ));
+                if initial_sync.lock().completed {
+                    // No relay to sync with
+                    this.sync_channel.send(Message::InitialSyncComplete {
+                        events: 0,
+                        relays: 0,
+                    });
                 }
+                this.refresh_subscriptions().await;
+                let subscriptions_refresher = this.subscriptions_refresher()?;
 
                 let _ = this
                     .client
                     .handle_notifications(|notification| async {
                         match notification {
-                            RelayPoolNotification::Event { event, ..} => {
+                            RelayPoolNotification::Event { relay_url, subscription_id, event } => {
+                                this.relay_monitor.event_received(&relay_url);
                                 let event_id = event.id;
+                                if subscription_id == SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID) {
+                                    let progress = initial_sync.lock().event_received(&relay_url, event_id);
+                                    if let Some(events_received) = progress {
+                                        this.sync_channel.send(Message::SyncProgress { relay_url, events_received, eose: false });
+                                    }
+                                }
                                 if event.is_expired() {
                                     tracing::warn!("Event {event_id} expired");
                                 } else if let Err(e) = this.handle_event(*event).await {
@@ -263,20 +917,42 @@ impl SmartVaults {
                                         if subscription_id == SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID) {
                                             if let Err(e) = this
                                                 .db
-                                                .save_last_relay_sync(relay_url, Timestamp::now()).await
+                                                .save_last_relay_sync(relay_url.clone(), Timestamp::now()).await
                                             {
                                                 tracing::error!("Impossible to save last relay sync: {e}");
                                             }
+
+                                            let (progress, completed) = {
+                                                let mut initial_sync = initial_sync.lock();
+                                                let progress = initial_sync.eose_received(&relay_url);
+                                                let completed = initial_sync.check_completed().then(|| (initial_sync.events.len(), initial_sync.relays.len()));
+                                                (progress, completed)
+                                            };
+                                            if let Some(events_received) = progress {
+                                                this.sync_channel.send(Message::SyncProgress { relay_url, events_received, eose: true });
+                                            }
+                                            if let Some((events, relays)) = completed {
+                                                tracing::info!("Initial sync completed: {events} events from {relays} relays");
+                                                this.sync_channel.send(Message::InitialSyncComplete { events, relays });
+                                            }
                                         }
                                 }
                             }
-                            RelayPoolNotification::RelayStatus { .. } => (),
+                            RelayPoolNotification::RelayStatus { relay_url, status } => {
+                                this.relay_monitor.status_changed(&relay_url, status);
+                            }
                             RelayPoolNotification::Stop | RelayPoolNotification::Shutdown => {
                                 tracing::debug!("Received stop/shutdown msg");
                                 block_height_syncer.abort();
                                 mempool_fees_syncer.abort();
                                 policies_syncer.abort();
+                                prices_syncer.abort();
                                 pending_event_handler.abort();
+                                outbox_drainer.abort();
+                                capabilities_publisher.abort();
+                                psbt_dir_watcher.abort();
+                                rebroadcaster.abort();
+                                subscriptions_refresher.abort();
                                 let _ = this.syncing.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| Some(false));
                             }
                         }
@@ -302,25 +978,181 @@ impl SmartVaults {
         Ok(())
     }
 
-    async fn handle_event(&self, event: Event) -> Result<()> {
+    pub(super) async fn handle_event(&self, event: Event) -> Result<()> {
+        if self.quarantined.read().contains(&event.id) {
+            tracing::warn!("Skipping quarantined event {}", event.id);
+            return Ok(());
+        }
+
+        self.guarded_handle_event(event).await
+    }
+
+    /// Mark the event as `processing` before handling it and clear the marker after.
+    ///
+    /// If the app crash while handling the event, the marker is found at next startup and the event quarantined.
+    async fn guarded_handle_event(&self, event: Event) -> Result<()> {
+        let event_id: EventId = event.id;
+        let created_at: Timestamp = event.created_at;
+        self.db.set_processing_event(event_id).await?;
+        let res = self.internal_handle_event(event).await;
+        self.db.clear_processing_event(event_id).await?;
+        if let Some(handled) = res? {
+            // New policy or shared key: the sync filters changed
+            if matches!(
+                handled,
+                EventHandled::Policy(..) | EventHandled::SharedKey(..)
+            ) {
+                self.request_subscriptions_refresh();
+            }
+            // New shared key: the pending events may be handled now
+            if let EventHandled::SharedKey(..) = handled {
+                self.db.reset_pending_event_retries().await?;
+            }
+            self.save_notification(&handled, created_at).await?;
+            self.sync_channel.send(Message::EventHandled(handled));
+        }
+        Ok(())
+    }
+
+    async fn save_notification(&self, handled: &EventHandled, timestamp: Timestamp) -> Result<()> {
+        let (kind, event_id) = match handled {
+            EventHandled::Policy(policy_id) => (NotificationKind::NewPolicy, *policy_id),
+            EventHandled::Proposal(proposal_id) => (NotificationKind::NewProposal, *proposal_id),
+            EventHandled::CompletedProposal(completed_proposal_id) => {
+                (NotificationKind::CompletedProposal, *completed_proposal_id)
+            }
+            _ => return Ok(()),
+        };
+        self.db.save_notification(kind, event_id, timestamp).await?;
+        Ok(())
+    }
+
+    /// Get quarantined (poison) events
+    pub async fn quarantined_events(&self) -> Result<Vec<QuarantinedEvent>, Error> {
+        Ok(self.db.get_quarantined_events().await?)
+    }
+
+    /// Get the pending events moved to the dead-letter state (still pending after
+    /// [`PENDING_EVENT_MAX_RETRIES`] retries)
+    pub async fn failed_events(&self) -> Result<Vec<PendingEventRetry>, Error> {
+        Ok(self.db.get_failed_events().await?)
+    }
+
+    /// Retry now a pending event, clearing its backoff (failed events included)
+    ///
+    /// Return `true` if the event was handled.
+    pub async fn retry_event(&self, event_id: EventId) -> Result<bool, Error> {
+        let event: Event = self
+            .storage
+            .pending_events()
+            .await
+            .into_iter()
+            .find(|e| e.id == event_id)
+            .ok_or(Error::EventNotPending(event_id))?;
+        self.db.delete_pending_event_retry(event_id).await?;
+        self.retry_pending_event(event, 0).await
+    }
+
+    /// Try to handle again the quarantined events
+    ///
+    /// Return the IDs of the events successfully handled (removed from quarantine).
+    pub async fn replay_quarantined_events(&self) -> Result<Vec<EventId>, Error> {
+        let mut replayed: Vec<EventId> = Vec::new();
+        let database = self.client.database();
+        for QuarantinedEvent {
+            event_id, event, ..
+        } in self.db.get_quarantined_events().await?.into_iter()
+        {
+            // Only the ID was quarantined: the event may have been received since
+            let event: Event = match event {
+                Some(event) => event,
+                None => match database.event_by_id(event_id).await {
+                    Ok(event) => event,
+                    Err(e) => {
+                        tracing::warn!("Quarantined event {event_id} not found: {e}");
+                        continue;
+                    }
+                },
+            };
+            match self.guarded_handle_event(event).await {
+                Ok(()) => {
+                    self.db.delete_quarantined_event(event_id).await?;
+                    self.quarantined.write().remove(&event_id);
+                    replayed.push(event_id);
+                }
+                Err(e) => tracing::error!("Impossible to replay quarantined event {event_id}: {e}"),
+            }
+        }
+        Ok(replayed)
+    }
+
+    /// Check the wallet state of every policy against the policy descriptor and the network
+    ///
+    /// Return the IDs of the mismatching policies. With `repair`, their wallet state is
+    /// quarantined and the wallet reloaded (rescanned from scratch).
+    pub async fn check_wallets_integrity(&self, repair: bool) -> Result<Vec<EventId>, Error> {
+        let mut mismatches: Vec<EventId> = Vec::new();
+        for (policy_id, InternalPolicy { policy, .. }) in self.storage.vaults().await.into_iter() {
+            if self
+                .manager
+                .wallet_integrity_mismatch(policy_id, &policy)
+                .await?
+            {
+                if repair {
+                    // Not loaded if the wallet state was unreadable
+                    let _ = self.manager.unload_policy(policy_id).await;
+                    self.manager
+                        .load_policy(policy_id, policy, None, Some(self.sync_channel.clone()))
+                        .await?;
+                }
+                mismatches.push(policy_id);
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Handle the event with the storage, reporting the proposals that can't be read
+    async fn storage_handle_event(&self, event: &Event) -> Result<Option<EventHandled>, Error> {
+        match self.storage.handle_event(event).await {
+            Err(Error::Encryption(e)) if event.kind == PROPOSAL_KIND => {
+                let reason = IngestionProblem::from(&e);
+                if let Err(e) = self.report_unreadable_proposal(event, reason).await {
+                    tracing::error!("Impossible to report unreadable proposal {}: {e}", event.id);
+                }
+                Err(Error::Encryption(e))
+            }
+            res => res,
+        }
+    }
+
+    /// Handle the event and return what changed (`None` if nothing changed)
+    async fn internal_handle_event(&self, event: Event) -> Result<Option<EventHandled>> {
+        // Synthetic poison event (see `test_quarantine_poison_event`)
+        #[cfg(test)]
+        if event.content() == tests::POISON_EVENT_CONTENT {
+            panic!("poison event {}", event.id);
+        }
+
         if event.kind == Kind::ContactList {
-            let pubkeys = event.public_keys().copied();
-            let filter: Filter = Filter::new().authors(pubkeys).kind(Kind::Metadata);
-            self.client
-                .subscribe(
-                    vec![filter],
-                    Some(
-                        SubscribeAutoCloseOptions::default().timeout(Some(Duration::from_secs(10))),
-                    ),
-                )
-                .await;
-            self.sync_channel
-                .send(Message::EventHandled(EventHandled::Contacts))?;
+            // The light profile skips the contacts metadata sync
+            if self.config.sync_profile().await == SyncProfile::Full {
+                let pubkeys = event.public_keys().copied();
+                let filter: Filter = Filter::new()
+                    .authors(pubkeys)
+                    .kinds([Kind::Metadata, Kind::RelayList]);
+                self.client
+                    .subscribe(
+                        vec![filter],
+                        Some(
+                            SubscribeAutoCloseOptions::default()
+                                .timeout(Some(Duration::from_secs(10))),
+                        ),
+                    )
+                    .await;
+            }
+            Ok(Some(EventHandled::Contacts))
         } else if event.kind == Kind::Metadata {
-            self.sync_channel
-                .send(Message::EventHandled(EventHandled::Metadata(
-                    event.author(),
-                )))?;
+            Ok(Some(EventHandled::Metadata(event.author())))
         } else if event.kind == Kind::RelayList {
             if event.author() == self.keys().public_key() {
                 tracing::debug!("Received relay list: {:?}", event.tags);
@@ -331,27 +1163,37 @@ impl SmartVaults {
                     .into_iter()
                     .map(|(url, ..)| url)
                     .collect();
-                let list: HashSet<Url> = nip65::extract_relay_list(&event)
+                let list: HashMap<Url, RelayRole> = nip65::extract_relay_list(&event)
                     .into_iter()
-                    .filter_map(|(url, ..)| Url::try_from(url).ok())
+                    .filter_map(|(url, metadata)| {
+                        let role: RelayRole = match metadata {
+                            Some(RelayMetadata::Read) => RelayRole::read_only(),
+                            Some(RelayMetadata::Write) => RelayRole::write_only(),
+                            None => RelayRole::default(),
+                        };
+                        Some((Url::try_from(url).ok()?, role))
+                    })
                     .collect();
 
                 // Add relays
-                for relay_url in list.difference(&current_relays) {
-                    tracing::debug!("[relay list] Added {relay_url}");
-                    self.add_relay_with_opts(relay_url.to_string(), None, false)
-                        .await?;
+                for (relay_url, role) in list.iter() {
+                    if !current_relays.contains(relay_url) {
+                        tracing::debug!("[relay list] Added {relay_url} ({role})");
+                        self.add_relay_with_opts(relay_url.to_string(), None, *role, false)
+                            .await?;
+                    }
                 }
 
                 // Remove relays
-                for relay_url in current_relays.difference(&list) {
+                for relay_url in current_relays.iter().filter(|u| !list.contains_key(u)) {
                     tracing::debug!("[relay list] Removed {relay_url}");
                     self.remove_relay_with_opts(relay_url.to_string(), false)
                         .await?;
                 }
 
-                self.sync_channel
-                    .send(Message::EventHandled(EventHandled::RelayList))?;
+                Ok(Some(EventHandled::RelayList))
+            } else {
+                Ok(None)
             }
         } else if event.kind == Kind::NostrConnect
             && self.db.nostr_connect_session_exists(event.author()).await?
@@ -382,7 +1224,8 @@ impl SmartVaults {
                             )
                             .await?;
                     }
-                    _ => {
+                    request => {
+                        let method: String = request.method().to_string();
                         if self
                             .db
                             .is_nostr_connect_session_pre_authorized(event.author())
@@ -394,6 +1237,12 @@ impl SmartVaults {
                             let msg = msg
                                 .generate_response(keys)?
                                 .ok_or(Error::CantGenerateNostrConnectResponse)?;
+                            self.audit_signing(
+                                SigningKind::NostrConnect,
+                                event.id.to_hex(),
+                                msg.as_json().as_bytes(),
+                            )
+                            .await?;
                             let nip46_event =
                                 EventBuilder::nostr_connect(keys, uri.public_key, msg)?
                                     .to_event(keys)?;
@@ -414,6 +1263,19 @@ impl SmartVaults {
                                     true,
                                 )
                                 .await?;
+                            self.db
+                                .save_notification(
+                                    NotificationKind::NostrConnectRequestAutoApproved,
+                                    event.id,
+                                    event.created_at,
+                                )
+                                .await?;
+                            self.sync_channel
+                                .send(Message::NostrConnectRequestAutoApproved {
+                                    app_public_key: event.author(),
+                                    request_id: event.id,
+                                    method,
+                                });
                             tracing::info!(
                                 "Auto approved nostr connect request {} for app {}",
                                 event.id,
@@ -429,18 +1291,66 @@ impl SmartVaults {
                                     false,
                                 )
                                 .await?;
+                            self.db
+                                .save_notification(
+                                    NotificationKind::NostrConnectRequest,
+                                    event.id,
+                                    event.created_at,
+                                )
+                                .await?;
+                            self.sync_channel.send(Message::NewNostrConnectRequest {
+                                app_public_key: event.author(),
+                                request_id: event.id,
+                                method,
+                            });
                         }
                     }
                 };
-                self.sync_channel.send(Message::EventHandled(
-                    EventHandled::NostrConnectRequest(event.id),
-                ))?;
+                Ok(Some(EventHandled::NostrConnectRequest(event.id)))
+            } else {
+                Ok(None)
             }
-        } else if let Some(h) = self.storage.handle_event(&event).await? {
+        } else if event.kind == KEY_AGENT_SIGNALING {
+            // Key agents are queried from the database when needed
+            Ok(Some(EventHandled::KeyAgentSignaling(event.author())))
+        } else if event.kind == KEY_AGENT_SIGNER_OFFERING_KIND {
+            Ok(Some(EventHandled::KeyAgentSignerOffering(event.id)))
+        } else if event.kind == CAPABILITIES_KIND {
+            let capabilities = Capabilities::from_json(event.content())?;
+            self.db
+                .save_peer_capabilities(event.author(), capabilities, event.created_at)
+                .await?;
+            Ok(Some(EventHandled::PeerCapabilities(event.author())))
+        } else if event.kind == INGESTION_REPORT_KIND {
+            self.handle_ingestion_report(&event).await?;
+            Ok(None)
+        } else if event.kind == APPROVAL_REMINDER_KIND {
+            self.handle_approval_reminder(&event).await?;
+            Ok(None)
+        } else if let Some(h) = self.storage_handle_event(&event).await? {
             match h {
                 EventHandled::Policy(vault_id) => {
                     let InternalPolicy { policy, .. } = self.storage.vault(&vault_id).await?;
-                    self.manager.load_policy(event.id, policy).await?;
+                    self.manager
+                        .load_policy(
+                            event.id,
+                            policy,
+                            Some(event.created_at),
+                            Some(self.sync_channel.clone()),
+                        )
+                        .await?;
+                }
+                EventHandled::Proposal(proposal_id) => {
+                    if let Err(e) = self.check_proposal_fee(proposal_id).await {
+                        tracing::error!("Impossible to check fee of proposal {proposal_id}: {e}");
+                    }
+                }
+                EventHandled::Approval { proposal_id, .. } => {
+                    if let Err(e) = self.check_proposal_ready(proposal_id).await {
+                        tracing::error!(
+                            "Impossible to check if proposal {proposal_id} is ready: {e}"
+                        );
+                    }
                 }
                 EventHandled::CompletedProposal(completed_proposal_id) => {
                     let InternalCompletedProposal {
@@ -488,9 +1398,265 @@ impl SmartVaults {
                 }
                 _ => (),
             };
-            self.sync_channel.send(Message::EventHandled(h))?;
+            Ok(Some(h))
+        } else {
+            Ok(None)
         }
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    use futures_util::{SinkExt, StreamExt};
+    use nostr_sdk::RelayStatus;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc::{self, UnboundedSender};
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+    use super::*;
+    use crate::client::TempBasePath;
+
+    /// Content of the synthetic event that makes [`SmartVaults::internal_handle_event`] panic
+    pub(super) const POISON_EVENT_CONTENT: &str = "smartvaults-test-poison";
+
+    /// Local relay that keeps the filters of each subscription (a `REQ` with the same ID replaces
+    /// them) and sends only the matching events
+    #[derive(Clone, Default)]
+    struct MockRelay {
+        connections: Arc<AtomicUsize>,
+        reqs: Arc<AtomicUsize>,
+        subscriptions: Arc<Mutex<HashMap<SubscriptionId, Vec<Filter>>>>,
+        sender: Arc<Mutex<Option<UnboundedSender<String>>>>,
+    }
+
+    impl MockRelay {
+        async fn run() -> (Self, Url) {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = Url::parse(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+            let relay = Self::default();
+            let this = relay.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    this.connections.fetch_add(1, Ordering::SeqCst);
+                    let ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let (mut ws_tx, mut ws_rx) = ws.split();
+                    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+                    *this.sender.lock() = Some(tx.clone());
+                    tokio::spawn(async move {
+                        while let Some(msg) = rx.recv().await {
+                            if ws_tx.send(WsMessage::Text(msg)).await.is_err() {
+                                break;
+                            }
+                        }
+                    });
+                    let this = this.clone();
+                    tokio::spawn(async move {
+                        while let Some(Ok(msg)) = ws_rx.next().await {
+                            if let WsMessage::Text(text) = msg {
+                                if let Ok(ClientMessage::Req {
+                                    subscription_id,
+                                    filters,
+                                }) = ClientMessage::from_json(text)
+                                {
+                                    this.subscriptions
+                                        .lock()
+                                        .insert(subscription_id.clone(), filters);
+                                    this.reqs.fetch_add(1, Ordering::SeqCst);
+                                    let _ = tx.send(RelayMessage::eose(subscription_id).as_json());
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+            (relay, url)
+        }
+
+        /// Send `event` to the matching subscriptions. Return `true` if sent.
+        fn publish(&self, event: &Event) -> bool {
+            let sender = self.sender.lock().clone().unwrap();
+            let mut sent: bool = false;
+            for (subscription_id, filters) in self.subscriptions.lock().iter() {
+                if filters.iter().any(|f| f.match_event(event)) {
+                    let msg = RelayMessage::event(subscription_id.clone(), event.clone());
+                    sender.send(msg.as_json()).unwrap();
+                    sent = true;
+                }
+            }
+            sent
+        }
+
+        async fn wait_for_reqs(&self, reqs: usize) {
+            tokio::time::timeout(Duration::from_secs(10), async {
+                while self.reqs.load(Ordering::SeqCst) < reqs {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_subscriptions_without_reconnect() {
+        let base_path = TempBasePath::new("refresh");
+        let client = SmartVaults::generate_offline(&base_path, "refresh", Network::Testnet)
+            .await
+            .unwrap();
+
+        let (mock, url) = MockRelay::run().await;
+        client.client.add_relay(url.as_str()).await.unwrap();
+        client.client.connect().await;
+        let relay = client.client.relay(&url).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !matches!(relay.status().await, RelayStatus::Connected) {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        client.refresh_subscriptions().await;
+        mock.wait_for_reqs(1).await;
+
+        // Policy event authored by a shared key not known yet
+        let policy_id = EventId::all_zeros();
+        let shared_key = Keys::generate();
+        let event = EventBuilder::new(POLICY_KIND, "policy", [])
+            .to_event(&shared_key)
+            .unwrap();
+        assert!(!mock.publish(&event));
+
+        // Shared key received: the subscription is replaced and the event sent
+        client.storage.save_shared_key(policy_id, shared_key).await;
+        client.refresh_subscriptions().await;
+        mock.wait_for_reqs(2).await;
+
+        let mut notifications = client.client.notifications();
+        assert!(mock.publish(&event));
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let RelayPoolNotification::Event {
+                    subscription_id,
+                    event: received,
+                    ..
+                } = notifications.recv().await.unwrap()
+                {
+                    if received.id == event.id {
+                        assert_eq!(
+                            subscription_id,
+                            SubscriptionId::new(DEFAULT_SUBSCRIPTION_ID)
+                        );
+                        break;
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        // Same connection and a single subscription
+        assert_eq!(mock.connections.load(Ordering::SeqCst), 1);
+        assert_eq!(mock.subscriptions.lock().len(), 1);
+
+        client.shutdown().await.unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_quarantine_poison_event() {
+        let base_path = TempBasePath::new("poison");
+        let network = Network::Testnet;
+
+        let client = SmartVaults::generate_offline(&base_path, "poison", network)
+            .await
+            .unwrap();
+        let event = EventBuilder::text_note("ok", [])
+            .to_event(&client.keys)
+            .unwrap();
+        let poison = EventBuilder::text_note(POISON_EVENT_CONTENT, [])
+            .to_event(&client.keys)
+            .unwrap();
+
+        // The relay pool saves the events before notifying them
+        let database = client.client.database();
+        database.save_event(&event).await.unwrap();
+        database.save_event(&poison).await.unwrap();
+        drop(database);
+
+        client.handle_event(event.clone()).await.unwrap();
+
+        // Crash inside the handler
+        let c = client.clone();
+        let p = poison.clone();
+        let res = tokio::spawn(async move { c.handle_event(p).await }).await;
+        assert!(res.unwrap_err().is_panic());
+
+        // Restart
+        drop(client);
+        let client = SmartVaults::open_offline(&base_path, "poison", network)
+            .await
+            .unwrap();
+
+        let events = client.quarantined_events().await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, Some(poison.clone()));
+
+        let mut sub = client.sync_notifications_since(0);
+        assert!(matches!(
+            sub.recv().await.unwrap().message,
+            Message::PoisonEventQuarantined(event_id) if event_id == poison.id
+        ));
+
+        // Skipped by the sync
+        client.handle_event(poison.clone()).await.unwrap();
+        client.handle_event(event).await.unwrap();
+
+        // Crash while handling an event not saved in the nostr database
+        let missing = EventBuilder::text_note("missing", [])
+            .to_event(&client.keys)
+            .unwrap();
+        client.db.set_processing_event(missing.id).await.unwrap();
+
+        // Restart: the ID stays quarantined
+        drop(client);
+        let client = SmartVaults::open_offline(&base_path, "poison", network)
+            .await
+            .unwrap();
+        let events = client.quarantined_events().await.unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .any(|q| q.event_id == missing.id && q.event.is_none()));
+        assert!(client.quarantined.read().contains(&missing.id));
+    }
+
+    #[test]
+    fn test_initial_sync_progress() {
+        let relay1 = Url::parse("wss://relay1.example.com").unwrap();
+        let relay2 = Url::parse("wss://relay2.example.com").unwrap();
+        let unknown = Url::parse("wss://unknown.example.com").unwrap();
+        let event_id = |n: u8| EventId::from_slice(&[n; 32]).unwrap();
+
+        let mut initial_sync = InitialSync::new([relay1.clone(), relay2.clone()]);
+        assert_eq!(initial_sync.event_received(&relay1, event_id(1)), Some(1));
+        assert_eq!(initial_sync.event_received(&relay1, event_id(2)), None);
+        assert_eq!(initial_sync.event_received(&relay2, event_id(1)), Some(1));
+        assert_eq!(initial_sync.event_received(&unknown, event_id(3)), None);
+
+        assert_eq!(initial_sync.eose_received(&relay1), Some(2));
+        assert_eq!(initial_sync.eose_received(&relay1), None);
+        assert!(!initial_sync.check_completed());
+        // Events after the EOSE are not part of the initial sync
+        assert_eq!(initial_sync.event_received(&relay1, event_id(4)), None);
+
+        assert_eq!(initial_sync.eose_received(&relay2), Some(1));
+        assert!(initial_sync.check_completed());
+        assert!(!initial_sync.check_completed());
+        assert_eq!(initial_sync.events.len(), 2);
+
+        assert!(InitialSync::new(Vec::<Url>::new()).completed);
     }
 }
@@ -2,22 +2,86 @@
 // Distributed under the MIT software license
 
 use core::fmt;
+use std::collections::BTreeSet;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use bdk_bitcoind_rpc::bitcoincore_rpc::Auth;
 use nostr_sdk::Url;
 use serde::{Deserialize, Serialize};
 use smartvaults_core::bitcoin::Network;
-use smartvaults_core::util;
+use smartvaults_core::{util, FeeCap, Priority};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
+pub mod profile;
+
+pub use self::profile::{FeeTargets, Profile};
+use crate::constants::{
+    DEFAULT_DRAFTS_EXPIRATION, DEFAULT_GAP_LIMIT, DEFAULT_LIGHT_SYNC_HORIZON,
+    DEFAULT_MAX_RECIPIENT_RELAYS, DEFAULT_MIN_RELAY_ACKS, DEFAULT_PRICE_ENDPOINT,
+};
+use crate::price::FiatCurrency;
+use crate::screening::ScreeningAction;
 use crate::util::dir;
 
+/// Sync profile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncProfile {
+    /// Sync all the history, contacts metadata included
+    #[default]
+    Full,
+    /// For low-bandwidth devices: skip contacts metadata, sync the history within an horizon
+    /// (older events are fetched on-demand per policy) and sync the timechain less frequently
+    Light,
+}
+
+impl fmt::Display for SyncProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Light => write!(f, "light"),
+        }
+    }
+}
+
+/// Bitcoin Core RPC authentication
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BitcoindAuth {
+    /// Cookie file
+    Cookie(PathBuf),
+    UserPass {
+        user: String,
+        pass: String,
+    },
+}
+
+impl From<BitcoindAuth> for Auth {
+    fn from(auth: BitcoindAuth) -> Self {
+        match auth {
+            BitcoindAuth::Cookie(path) => Self::CookieFile(path),
+            BitcoindAuth::UserPass { user, pass } => Self::UserPass(user, pass),
+        }
+    }
+}
+
+/// Timechain backend
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Backend {
+    /// Electrum servers (see [`Config::electrum_endpoints`])
+    #[default]
+    Electrum,
+    /// Bitcoin Core RPC
+    BitcoinCore { url: Url, auth: BitcoindAuth },
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
@@ -36,6 +100,8 @@ pub enum Error {
     ProxyNotSet,
     #[error("block explorer not set")]
     BlockExplorerNotSet,
+    #[error("Invalid fee targets: {0} (expected <high>,<medium>,<low>)")]
+    InvalidFeeTargets(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -183,26 +249,180 @@ impl<'de> Deserialize<'de> for ElectrumEndpoint {
 #[derive(Serialize, Deserialize)]
 struct BitcoinFile {
     electrum_server: Option<ElectrumEndpoint>,
+    /// Endpoints used if the `electrum_server` is unreachable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    electrum_fallback_servers: Vec<ElectrumEndpoint>,
     proxy: Option<SocketAddr>,
     block_explorer: Option<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_targets: Option<FeeTargets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fee_cap: Option<FeeCap>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gap_limit: Option<usize>,
+    /// Bitcoin Core RPC (used instead of electrum, if set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bitcoind: Option<BitcoindFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BitcoindFile {
+    url: Url,
+    auth: BitcoindAuth,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ScreeningFile {
+    list_path: Option<PathBuf>,
+    #[serde(default)]
+    on_approve: ScreeningAction,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct NostrFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_relay_acks: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_recipient_relays: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notify_cosigners: Option<bool>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ExternalSignerFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    psbt_dir: Option<PathBuf>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct SyncFile {
+    #[serde(default)]
+    profile: SyncProfile,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    light_horizon_days: Option<u64>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct DraftsFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expiration_days: Option<u64>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct FiatFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    currency: Option<FiatCurrency>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    price_endpoint: Option<Url>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct GuiFile {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    collapsed_policy_groups: BTreeSet<String>,
 }
 
 #[derive(Serialize, Deserialize)]
 struct ConfigFile {
     bitcoin: BitcoinFile,
+    #[serde(default)]
+    screening: ScreeningFile,
+    #[serde(default)]
+    nostr: NostrFile,
+    #[serde(default)]
+    sync: SyncFile,
+    #[serde(default)]
+    external_signer: ExternalSignerFile,
+    #[serde(default)]
+    drafts: DraftsFile,
+    #[serde(default)]
+    fiat: FiatFile,
+    #[serde(default)]
+    gui: GuiFile,
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Bitcoin {
-    pub electrum_server: Arc<RwLock<Option<ElectrumEndpoint>>>,
+    /// Electrum endpoints, in failover order
+    pub electrum_servers: Arc<RwLock<Vec<ElectrumEndpoint>>>,
+    /// Last working electrum endpoint
+    pub active_electrum_server: Arc<RwLock<Option<ElectrumEndpoint>>>,
     pub proxy: Arc<RwLock<Option<SocketAddr>>>,
     pub block_explorer: Arc<RwLock<Option<Url>>>,
+    /// Override of the profile fee targets
+    pub fee_targets: Arc<RwLock<Option<FeeTargets>>>,
+    /// Override of the default proposal fee cap
+    pub fee_cap: Arc<RwLock<Option<FeeCap>>>,
+    /// Override of the default gap limit
+    pub gap_limit: Arc<RwLock<Option<usize>>>,
+    pub backend: Arc<RwLock<Backend>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Screening {
+    /// Path of the screening list (see [`FileScreeningProvider`](crate::screening::FileScreeningProvider))
+    pub list_path: Arc<RwLock<Option<PathBuf>>>,
+    /// Action to take when a proposal to approve pays a flagged address
+    pub on_approve: Arc<RwLock<ScreeningAction>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Nostr {
+    /// Override of the min number of relays that must acknowledge a critical event
+    pub min_relay_acks: Arc<RwLock<Option<usize>>>,
+    /// Override of the max number of recipient read relays an addressed event is also sent to
+    pub max_recipient_relays: Arc<RwLock<Option<usize>>>,
+    /// Send a DM to the co-signers when a spending proposal is created
+    pub notify_cosigners: Arc<RwLock<Option<bool>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    pub profile: Arc<RwLock<SyncProfile>>,
+    /// Override of the light profile history horizon (days)
+    pub light_horizon_days: Arc<RwLock<Option<u64>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExternalSigner {
+    /// Directory watched by external signers (i.e. HSM): the pending proposals PSBTs are
+    /// exported there and the signed ones imported as approvals. Disabled if `None`.
+    pub psbt_dir: Arc<RwLock<Option<PathBuf>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Drafts {
+    /// Override of the days after which the unsubmitted forms drafts are deleted
+    pub expiration_days: Arc<RwLock<Option<u64>>>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Fiat {
+    /// Currency of the fiat balances. Disabled if `None`.
+    pub currency: Arc<RwLock<Option<FiatCurrency>>>,
+    /// Override of the price endpoint (default: [`DEFAULT_PRICE_ENDPOINT`])
+    pub price_endpoint: Arc<RwLock<Option<Url>>>,
+}
+
+/// Local state of the frontends
+#[derive(Debug, Clone, Default)]
+pub struct Gui {
+    /// Collapsed groups of the policies list
+    pub collapsed_policy_groups: Arc<RwLock<BTreeSet<String>>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub config_file_path: PathBuf,
+    pub profile: Profile,
     pub bitcoin: Bitcoin,
+    pub screening: Screening,
+    pub nostr: Nostr,
+    pub sync: SyncOptions,
+    pub external_signer: ExternalSigner,
+    pub drafts: Drafts,
+    pub fiat: Fiat,
+    pub gui: Gui,
 }
 
 impl Config {
@@ -213,6 +433,7 @@ impl Config {
     {
         let base_path: PathBuf = base_path.as_ref().to_path_buf();
         let config_file_path: PathBuf = dir::config_file_path(base_path, network)?;
+        let profile: Profile = Profile::from_network(network);
 
         if config_file_path.exists() {
             let mut file: File = File::open(config_file_path.as_path())?;
@@ -223,14 +444,66 @@ impl Config {
                 Ok(config_file) => {
                     return Ok(Self {
                         config_file_path,
+                        profile,
                         bitcoin: Bitcoin {
-                            electrum_server: Arc::new(RwLock::new(
-                                config_file.bitcoin.electrum_server,
+                            electrum_servers: Arc::new(RwLock::new(
+                                config_file
+                                    .bitcoin
+                                    .electrum_server
+                                    .into_iter()
+                                    .chain(config_file.bitcoin.electrum_fallback_servers)
+                                    .collect(),
                             )),
+                            active_electrum_server: Arc::new(RwLock::new(None)),
                             proxy: Arc::new(RwLock::new(config_file.bitcoin.proxy)),
                             block_explorer: Arc::new(RwLock::new(
                                 config_file.bitcoin.block_explorer,
                             )),
+                            fee_targets: Arc::new(RwLock::new(config_file.bitcoin.fee_targets)),
+                            fee_cap: Arc::new(RwLock::new(config_file.bitcoin.fee_cap)),
+                            gap_limit: Arc::new(RwLock::new(config_file.bitcoin.gap_limit)),
+                            backend: Arc::new(RwLock::new(match config_file.bitcoin.bitcoind {
+                                Some(BitcoindFile { url, auth }) => {
+                                    Backend::BitcoinCore { url, auth }
+                                }
+                                None => Backend::Electrum,
+                            })),
+                        },
+                        screening: Screening {
+                            list_path: Arc::new(RwLock::new(config_file.screening.list_path)),
+                            on_approve: Arc::new(RwLock::new(config_file.screening.on_approve)),
+                        },
+                        nostr: Nostr {
+                            min_relay_acks: Arc::new(RwLock::new(config_file.nostr.min_relay_acks)),
+                            max_recipient_relays: Arc::new(RwLock::new(
+                                config_file.nostr.max_recipient_relays,
+                            )),
+                            notify_cosigners: Arc::new(RwLock::new(
+                                config_file.nostr.notify_cosigners,
+                            )),
+                        },
+                        sync: SyncOptions {
+                            profile: Arc::new(RwLock::new(config_file.sync.profile)),
+                            light_horizon_days: Arc::new(RwLock::new(
+                                config_file.sync.light_horizon_days,
+                            )),
+                        },
+                        external_signer: ExternalSigner {
+                            psbt_dir: Arc::new(RwLock::new(config_file.external_signer.psbt_dir)),
+                        },
+                        drafts: Drafts {
+                            expiration_days: Arc::new(RwLock::new(
+                                config_file.drafts.expiration_days,
+                            )),
+                        },
+                        fiat: Fiat {
+                            currency: Arc::new(RwLock::new(config_file.fiat.currency)),
+                            price_endpoint: Arc::new(RwLock::new(config_file.fiat.price_endpoint)),
+                        },
+                        gui: Gui {
+                            collapsed_policy_groups: Arc::new(RwLock::new(
+                                config_file.gui.collapsed_policy_groups,
+                            )),
                         },
                     })
                 }
@@ -238,57 +511,73 @@ impl Config {
             };
         }
 
-        tracing::warn!("Using default config");
+        tracing::warn!("Using default config ({} profile)", profile.name);
 
-        let (endpoint, block_explorer) = match network {
-            Network::Bitcoin => (
-                ElectrumEndpoint::Tls {
-                    host: String::from("blockstream.info"),
-                    port: 700,
-                    validate_tls: true,
-                },
-                Some(Url::parse("https://mempool.space")?),
-            ),
-            Network::Testnet => (
-                ElectrumEndpoint::Tls {
-                    host: String::from("blockstream.info"),
-                    port: 993,
-                    validate_tls: true,
-                },
-                Some(Url::parse("https://mempool.space/testnet")?),
-            ),
-            Network::Signet => (
-                ElectrumEndpoint::Plaintext {
-                    host: String::from("signet-electrumx.wakiyamap.dev"),
-                    port: 50001,
-                },
-                Some(Url::parse("https://mempool.space/signet")?),
-            ),
-            _ => (
-                ElectrumEndpoint::Plaintext {
-                    host: String::from("localhost"),
-                    port: 60401,
-                },
-                None,
-            ),
+        let endpoint = ElectrumEndpoint::from_str(profile.electrum_server)?;
+        let block_explorer: Option<Url> = match profile.block_explorer {
+            Some(url) => Some(Url::parse(url)?),
+            None => None,
         };
 
         Ok(Self {
             config_file_path,
+            profile,
             bitcoin: Bitcoin {
-                electrum_server: Arc::new(RwLock::new(Some(endpoint))),
+                electrum_servers: Arc::new(RwLock::new(vec![endpoint])),
                 block_explorer: Arc::new(RwLock::new(block_explorer)),
                 ..Default::default()
             },
+            screening: Screening::default(),
+            nostr: Nostr::default(),
+            sync: SyncOptions::default(),
+            external_signer: ExternalSigner::default(),
+            drafts: Drafts::default(),
+            fiat: Fiat::default(),
+            gui: Gui::default(),
         })
     }
 
     async fn to_config_file(&self) -> ConfigFile {
+        let mut electrum_servers = self.electrum_endpoints().await.into_iter();
         ConfigFile {
             bitcoin: BitcoinFile {
-                electrum_server: (*self.bitcoin.electrum_server.read().await).clone(),
+                electrum_server: electrum_servers.next(),
+                electrum_fallback_servers: electrum_servers.collect(),
                 proxy: *self.bitcoin.proxy.read().await,
                 block_explorer: (*self.bitcoin.block_explorer.read().await).clone(),
+                fee_targets: *self.bitcoin.fee_targets.read().await,
+                fee_cap: *self.bitcoin.fee_cap.read().await,
+                gap_limit: *self.bitcoin.gap_limit.read().await,
+                bitcoind: match self.backend().await {
+                    Backend::Electrum => None,
+                    Backend::BitcoinCore { url, auth } => Some(BitcoindFile { url, auth }),
+                },
+            },
+            screening: ScreeningFile {
+                list_path: self.screening.list_path.read().await.clone(),
+                on_approve: *self.screening.on_approve.read().await,
+            },
+            nostr: NostrFile {
+                min_relay_acks: *self.nostr.min_relay_acks.read().await,
+                max_recipient_relays: *self.nostr.max_recipient_relays.read().await,
+                notify_cosigners: *self.nostr.notify_cosigners.read().await,
+            },
+            sync: SyncFile {
+                profile: *self.sync.profile.read().await,
+                light_horizon_days: *self.sync.light_horizon_days.read().await,
+            },
+            external_signer: ExternalSignerFile {
+                psbt_dir: self.external_signer.psbt_dir.read().await.clone(),
+            },
+            drafts: DraftsFile {
+                expiration_days: *self.drafts.expiration_days.read().await,
+            },
+            fiat: FiatFile {
+                currency: *self.fiat.currency.read().await,
+                price_endpoint: self.fiat.price_endpoint.read().await.clone(),
+            },
+            gui: GuiFile {
+                collapsed_policy_groups: self.gui.collapsed_policy_groups.read().await.clone(),
             },
         }
     }
@@ -306,24 +595,77 @@ impl Config {
         Ok(())
     }
 
-    pub async fn set_electrum_endpoint<S>(&self, endpoint: Option<S>) -> Result<(), Error>
+    /// Set the electrum endpoints, in failover order
+    ///
+    /// An empty list unset the endpoints.
+    pub async fn set_electrum_endpoints<I, S>(&self, endpoints: I) -> Result<(), Error>
     where
+        I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        let mut e = self.bitcoin.electrum_server.write().await;
-        match endpoint {
-            Some(endpoint) => {
-                let endpoint = ElectrumEndpoint::from_str(endpoint.as_ref())?;
-                *e = Some(endpoint);
+        let mut list: Vec<ElectrumEndpoint> = Vec::new();
+        for endpoint in endpoints.into_iter() {
+            let endpoint = ElectrumEndpoint::from_str(endpoint.as_ref())?;
+            if !list.contains(&endpoint) {
+                list.push(endpoint);
             }
-            None => *e = None,
         }
+
+        let mut e = self.bitcoin.electrum_servers.write().await;
+        *e = list;
+
+        let mut active = self.bitcoin.active_electrum_server.write().await;
+        *active = None;
+
         Ok(())
     }
 
+    /// Set a single electrum endpoint (see [`Config::set_electrum_endpoints`])
+    pub async fn set_electrum_endpoint<S>(&self, endpoint: Option<S>) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.set_electrum_endpoints(endpoint).await
+    }
+
+    /// Get the electrum endpoints, in failover order
+    pub async fn electrum_endpoints(&self) -> Vec<ElectrumEndpoint> {
+        self.bitcoin.electrum_servers.read().await.clone()
+    }
+
+    /// Get the primary electrum endpoint
     pub async fn electrum_endpoint(&self) -> Result<ElectrumEndpoint, Error> {
-        let endpoint = self.bitcoin.electrum_server.read().await;
-        endpoint.clone().ok_or(Error::ElectrumEndpointNotSet)
+        let endpoints = self.bitcoin.electrum_servers.read().await;
+        endpoints
+            .first()
+            .cloned()
+            .ok_or(Error::ElectrumEndpointNotSet)
+    }
+
+    /// Get the electrum endpoint currently in use
+    ///
+    /// It's the last working one or, if none worked yet, the primary one.
+    pub async fn active_electrum_endpoint(&self) -> Result<ElectrumEndpoint, Error> {
+        let active = self.bitcoin.active_electrum_server.read().await;
+        match active.as_ref() {
+            Some(endpoint) => Ok(endpoint.clone()),
+            None => self.electrum_endpoint().await,
+        }
+    }
+
+    pub(crate) async fn set_active_electrum_endpoint(&self, endpoint: ElectrumEndpoint) {
+        let mut active = self.bitcoin.active_electrum_server.write().await;
+        *active = Some(endpoint);
+    }
+
+    pub async fn set_backend(&self, backend: Backend) {
+        let mut e = self.bitcoin.backend.write().await;
+        *e = backend;
+    }
+
+    /// Get the timechain backend
+    pub async fn backend(&self) -> Backend {
+        self.bitcoin.backend.read().await.clone()
     }
 
     pub async fn set_proxy(&self, proxy: Option<SocketAddr>) {
@@ -346,6 +688,285 @@ impl Config {
         block_explorer.clone().ok_or(Error::BlockExplorerNotSet)
     }
 
+    pub async fn set_fee_targets(&self, fee_targets: Option<FeeTargets>) {
+        let mut e = self.bitcoin.fee_targets.write().await;
+        *e = fee_targets;
+    }
+
+    /// Get fee targets (the profile ones if not overridden)
+    pub async fn fee_targets(&self) -> FeeTargets {
+        let fee_targets = self.bitcoin.fee_targets.read().await;
+        fee_targets.unwrap_or(self.profile.fee_targets)
+    }
+
+    /// Get target blocks for [`Priority`] from the active profile
+    pub async fn target_blocks(&self, priority: Priority) -> u8 {
+        self.fee_targets().await.target_blocks(priority)
+    }
+
+    pub async fn set_fee_cap(&self, fee_cap: Option<FeeCap>) {
+        let mut e = self.bitcoin.fee_cap.write().await;
+        *e = fee_cap;
+    }
+
+    /// Get proposal fee cap (the default one if not overridden)
+    pub async fn fee_cap(&self) -> FeeCap {
+        let fee_cap = self.bitcoin.fee_cap.read().await;
+        fee_cap.unwrap_or_default()
+    }
+
+    pub async fn set_gap_limit(&self, gap_limit: Option<usize>) {
+        let mut e = self.bitcoin.gap_limit.write().await;
+        *e = gap_limit;
+    }
+
+    /// Consecutive unused addresses after which the timechain full scan stops (default:
+    /// [`DEFAULT_GAP_LIMIT`])
+    ///
+    /// Funds received by addresses beyond the gap are not detected. Used only by the electrum
+    /// backend.
+    pub async fn gap_limit(&self) -> usize {
+        let gap_limit = self.bitcoin.gap_limit.read().await;
+        gap_limit.unwrap_or(DEFAULT_GAP_LIMIT)
+    }
+
+    pub async fn set_screening_list_path(&self, path: Option<PathBuf>) {
+        let mut e = self.screening.list_path.write().await;
+        *e = path;
+    }
+
+    pub async fn screening_list_path(&self) -> Option<PathBuf> {
+        self.screening.list_path.read().await.clone()
+    }
+
+    pub async fn set_screening_on_approve(&self, action: ScreeningAction) {
+        let mut e = self.screening.on_approve.write().await;
+        *e = action;
+    }
+
+    pub async fn screening_on_approve(&self) -> ScreeningAction {
+        *self.screening.on_approve.read().await
+    }
+
+    pub async fn set_min_relay_acks(&self, min_relay_acks: Option<usize>) {
+        let mut e = self.nostr.min_relay_acks.write().await;
+        *e = min_relay_acks;
+    }
+
+    /// Min number of relays that must acknowledge a critical event (default: [`DEFAULT_MIN_RELAY_ACKS`])
+    pub async fn min_relay_acks(&self) -> usize {
+        let min_relay_acks = self.nostr.min_relay_acks.read().await;
+        min_relay_acks.unwrap_or(DEFAULT_MIN_RELAY_ACKS)
+    }
+
+    pub async fn set_max_recipient_relays(&self, max_recipient_relays: Option<usize>) {
+        let mut e = self.nostr.max_recipient_relays.write().await;
+        *e = max_recipient_relays;
+    }
+
+    /// Max number of recipient read relays (NIP-65) an addressed event is also sent to
+    /// (default: [`DEFAULT_MAX_RECIPIENT_RELAYS`])
+    pub async fn max_recipient_relays(&self) -> usize {
+        let max_recipient_relays = self.nostr.max_recipient_relays.read().await;
+        max_recipient_relays.unwrap_or(DEFAULT_MAX_RECIPIENT_RELAYS)
+    }
+
+    pub async fn set_notify_cosigners(&self, notify_cosigners: Option<bool>) {
+        let mut e = self.nostr.notify_cosigners.write().await;
+        *e = notify_cosigners;
+    }
+
+    /// Send a DM to the co-signers when a spending proposal is created (default: `false`)
+    pub async fn notify_cosigners(&self) -> bool {
+        let notify_cosigners = self.nostr.notify_cosigners.read().await;
+        notify_cosigners.unwrap_or_default()
+    }
+
+    pub async fn set_sync_profile(&self, profile: SyncProfile) {
+        let mut e = self.sync.profile.write().await;
+        *e = profile;
+    }
+
+    pub async fn sync_profile(&self) -> SyncProfile {
+        *self.sync.profile.read().await
+    }
+
+    pub async fn set_light_sync_horizon(&self, days: Option<u64>) {
+        let mut e = self.sync.light_horizon_days.write().await;
+        *e = days;
+    }
+
+    /// History window synced by the light profile (default: [`DEFAULT_LIGHT_SYNC_HORIZON`])
+    pub async fn light_sync_horizon(&self) -> Duration {
+        let days = self.sync.light_horizon_days.read().await;
+        match *days {
+            Some(days) => Duration::from_secs(days * 60 * 60 * 24),
+            None => DEFAULT_LIGHT_SYNC_HORIZON,
+        }
+    }
+
+    /// Set the external signer PSBT directory (`None` to disable the watcher)
+    pub async fn set_psbt_dir(&self, path: Option<PathBuf>) {
+        let mut e = self.external_signer.psbt_dir.write().await;
+        *e = path;
+    }
+
+    pub async fn psbt_dir(&self) -> Option<PathBuf> {
+        self.external_signer.psbt_dir.read().await.clone()
+    }
+
+    pub async fn set_drafts_expiration(&self, days: Option<u64>) {
+        let mut e = self.drafts.expiration_days.write().await;
+        *e = days;
+    }
+
+    /// Age after which the forms drafts are deleted (default: [`DEFAULT_DRAFTS_EXPIRATION`])
+    pub async fn drafts_expiration(&self) -> Duration {
+        let days = self.drafts.expiration_days.read().await;
+        match *days {
+            Some(days) => Duration::from_secs(days * 60 * 60 * 24),
+            None => DEFAULT_DRAFTS_EXPIRATION,
+        }
+    }
+
+    /// Set the currency of the fiat balances (`None` to show only sats)
+    pub async fn set_fiat_currency(&self, currency: Option<FiatCurrency>) {
+        let mut e = self.fiat.currency.write().await;
+        *e = currency;
+    }
+
+    pub async fn fiat_currency(&self) -> Option<FiatCurrency> {
+        *self.fiat.currency.read().await
+    }
+
+    pub async fn set_price_endpoint(&self, endpoint: Option<Url>) {
+        let mut e = self.fiat.price_endpoint.write().await;
+        *e = endpoint;
+    }
+
+    /// Price endpoint (default: [`DEFAULT_PRICE_ENDPOINT`])
+    pub async fn price_endpoint(&self) -> Result<Url, Error> {
+        let endpoint = self.fiat.price_endpoint.read().await;
+        match endpoint.as_ref() {
+            Some(endpoint) => Ok(endpoint.clone()),
+            None => Ok(Url::parse(DEFAULT_PRICE_ENDPOINT)?),
+        }
+    }
+
+    /// Collapse or expand a group of the policies list
+    pub async fn set_policy_group_collapsed<S>(&self, group: S, collapsed: bool)
+    where
+        S: Into<String>,
+    {
+        let mut groups = self.gui.collapsed_policy_groups.write().await;
+        if collapsed {
+            groups.insert(group.into());
+        } else {
+            groups.remove(&group.into());
+        }
+    }
+
+    /// Collapsed groups of the policies list
+    pub async fn collapsed_policy_groups(&self) -> BTreeSet<String> {
+        self.gui.collapsed_policy_groups.read().await.clone()
+    }
+
+    /// Get the names of the fields that differ from the active [`Profile`]
+    pub async fn overridden_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+
+        let electrum_servers = self.bitcoin.electrum_servers.read().await;
+        if electrum_servers.first().map(|e| e.as_standard_format())
+            != Some(self.profile.electrum_server.to_string())
+        {
+            fields.push("electrum_server");
+        }
+
+        if electrum_servers.len() > 1 {
+            fields.push("electrum_fallback_servers");
+        }
+
+        if *self.bitcoin.backend.read().await != Backend::default() {
+            fields.push("bitcoind");
+        }
+
+        if self.bitcoin.proxy.read().await.is_some() {
+            fields.push("proxy");
+        }
+
+        let block_explorer = self.bitcoin.block_explorer.read().await;
+        let default_block_explorer: Option<Url> = self
+            .profile
+            .block_explorer
+            .and_then(|url| Url::parse(url).ok());
+        if *block_explorer != default_block_explorer {
+            fields.push("block_explorer");
+        }
+
+        let fee_targets = self.bitcoin.fee_targets.read().await;
+        if matches!(*fee_targets, Some(targets) if targets != self.profile.fee_targets) {
+            fields.push("fee_targets");
+        }
+
+        let fee_cap = self.bitcoin.fee_cap.read().await;
+        if matches!(*fee_cap, Some(cap) if cap != FeeCap::default()) {
+            fields.push("fee_cap");
+        }
+
+        let gap_limit = self.bitcoin.gap_limit.read().await;
+        if matches!(*gap_limit, Some(gap) if gap != DEFAULT_GAP_LIMIT) {
+            fields.push("gap_limit");
+        }
+
+        if self.screening.list_path.read().await.is_some() {
+            fields.push("list_path");
+        }
+
+        if *self.screening.on_approve.read().await != ScreeningAction::default() {
+            fields.push("on_approve");
+        }
+
+        let min_relay_acks = self.nostr.min_relay_acks.read().await;
+        if matches!(*min_relay_acks, Some(acks) if acks != DEFAULT_MIN_RELAY_ACKS) {
+            fields.push("min_relay_acks");
+        }
+
+        let max_recipient_relays = self.nostr.max_recipient_relays.read().await;
+        if matches!(*max_recipient_relays, Some(max) if max != DEFAULT_MAX_RECIPIENT_RELAYS) {
+            fields.push("max_recipient_relays");
+        }
+
+        if *self.nostr.notify_cosigners.read().await == Some(true) {
+            fields.push("notify_cosigners");
+        }
+
+        if *self.sync.profile.read().await != SyncProfile::default() {
+            fields.push("profile");
+        }
+
+        if self.sync.light_horizon_days.read().await.is_some() {
+            fields.push("light_horizon_days");
+        }
+
+        if self.external_signer.psbt_dir.read().await.is_some() {
+            fields.push("psbt_dir");
+        }
+
+        if self.drafts.expiration_days.read().await.is_some() {
+            fields.push("drafts_expiration_days");
+        }
+
+        if self.fiat.currency.read().await.is_some() {
+            fields.push("currency");
+        }
+
+        if self.fiat.price_endpoint.read().await.is_some() {
+            fields.push("price_endpoint");
+        }
+
+        fields
+    }
+
     pub async fn as_pretty_json(&self) -> Result<String, Error> {
         let config_file: ConfigFile = self.to_config_file().await;
         Ok(nostr_sdk::serde_json::to_string_pretty(&config_file)?)
@@ -425,4 +1046,104 @@ mod tests {
             }
         );
     }
+
+    #[tokio::test]
+    async fn test_electrum_endpoints() {
+        let config = Config {
+            config_file_path: PathBuf::new(),
+            profile: Profile::from_network(Network::Testnet),
+            bitcoin: Bitcoin::default(),
+            screening: Screening::default(),
+            nostr: Nostr::default(),
+            sync: SyncOptions::default(),
+            external_signer: ExternalSigner::default(),
+            drafts: Drafts::default(),
+            fiat: Fiat::default(),
+            gui: Gui::default(),
+        };
+        assert!(config.active_electrum_endpoint().await.is_err());
+
+        config
+            .set_electrum_endpoints([
+                "ssl://blockstream.info:993",
+                "127.0.0.1:60001:t",
+                "blockstream.info:993:s",
+            ])
+            .await
+            .unwrap();
+        let endpoints = config.electrum_endpoints().await;
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(
+            config.active_electrum_endpoint().await.unwrap(),
+            endpoints[0]
+        );
+
+        config
+            .set_active_electrum_endpoint(endpoints[1].clone())
+            .await;
+        assert_eq!(config.electrum_endpoint().await.unwrap(), endpoints[0]);
+        assert_eq!(
+            config.active_electrum_endpoint().await.unwrap(),
+            endpoints[1]
+        );
+
+        let file = config.to_config_file().await;
+        assert_eq!(file.bitcoin.electrum_server, Some(endpoints[0].clone()));
+        assert_eq!(
+            file.bitcoin.electrum_fallback_servers,
+            vec![endpoints[1].clone()]
+        );
+
+        // Single-value API
+        config
+            .set_electrum_endpoint(Some("127.0.0.1:50001"))
+            .await
+            .unwrap();
+        assert_eq!(config.electrum_endpoints().await.len(), 1);
+        assert_eq!(
+            config.active_electrum_endpoint().await.unwrap(),
+            ElectrumEndpoint::Plaintext {
+                host: String::from("127.0.0.1"),
+                port: 50001
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bitcoind_backend() {
+        let config = Config {
+            config_file_path: PathBuf::new(),
+            profile: Profile::from_network(Network::Regtest),
+            bitcoin: Bitcoin::default(),
+            screening: Screening::default(),
+            nostr: Nostr::default(),
+            sync: SyncOptions::default(),
+            external_signer: ExternalSigner::default(),
+            drafts: Drafts::default(),
+            fiat: Fiat::default(),
+            gui: Gui::default(),
+        };
+        assert_eq!(config.backend().await, Backend::Electrum);
+
+        let backend = Backend::BitcoinCore {
+            url: Url::parse("http://127.0.0.1:18443").unwrap(),
+            auth: BitcoindAuth::UserPass {
+                user: String::from("user"),
+                pass: String::from("pass"),
+            },
+        };
+        config.set_backend(backend.clone()).await;
+        assert!(config.overridden_fields().await.contains(&"bitcoind"));
+
+        let json: String = config.as_pretty_json().await.unwrap();
+        let file: ConfigFile = nostr_sdk::serde_json::from_str(&json).unwrap();
+        let bitcoind = file.bitcoin.bitcoind.unwrap();
+        assert_eq!(
+            Backend::BitcoinCore {
+                url: bitcoind.url,
+                auth: bitcoind.auth
+            },
+            backend
+        );
+    }
 }
@@ -0,0 +1,105 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Per-network configuration profiles
+
+use core::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::Priority;
+
+use super::Error;
+use crate::constants::{MAINNET_PROFILE, REGTEST_PROFILE, SIGNET_PROFILE, TESTNET_PROFILE};
+
+/// Priority to target blocks mapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTargets {
+    pub high: u8,
+    pub medium: u8,
+    pub low: u8,
+}
+
+impl FeeTargets {
+    pub fn target_blocks(&self, priority: Priority) -> u8 {
+        match priority {
+            Priority::High => self.high,
+            Priority::Medium => self.medium,
+            Priority::Low => self.low,
+            Priority::Custom(target) => target,
+        }
+    }
+}
+
+impl fmt::Display for FeeTargets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.high, self.medium, self.low)
+    }
+}
+
+/// Parse `<high>,<medium>,<low>` format
+impl FromStr for FeeTargets {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let targets: Vec<u8> = s
+            .split(',')
+            .map(|t| t.trim().parse::<u8>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| Error::InvalidFeeTargets(s.to_string()))?;
+        match targets.as_slice() {
+            [high, medium, low] if *high > 0 && high <= medium && medium <= low => Ok(Self {
+                high: *high,
+                medium: *medium,
+                low: *low,
+            }),
+            _ => Err(Error::InvalidFeeTargets(s.to_string())),
+        }
+    }
+}
+
+/// Network defaults (electrum server, block explorer, fee targets and relays)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile {
+    pub name: &'static str,
+    /// Electrum endpoint in standard format (see [`ElectrumEndpoint`](super::ElectrumEndpoint))
+    pub electrum_server: &'static str,
+    pub block_explorer: Option<&'static str>,
+    pub fee_targets: FeeTargets,
+    pub relays: &'static [&'static str],
+}
+
+impl Profile {
+    pub fn from_network(network: Network) -> Self {
+        match network {
+            Network::Bitcoin => MAINNET_PROFILE,
+            Network::Testnet => TESTNET_PROFILE,
+            Network::Signet => SIGNET_PROFILE,
+            _ => REGTEST_PROFILE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_targets() {
+        let targets = FeeTargets::from_str("1,6,12").unwrap();
+        assert_eq!(targets, MAINNET_PROFILE.fee_targets);
+        assert_eq!(targets.target_blocks(Priority::Medium), 6);
+        assert_eq!(targets.target_blocks(Priority::Custom(3)), 3);
+        assert_eq!(targets.to_string(), "1,6,12");
+
+        assert!(FeeTargets::from_str("1,6").is_err());
+        assert!(FeeTargets::from_str("0,6,12").is_err());
+        assert!(FeeTargets::from_str("12,6,1").is_err());
+
+        assert_ne!(
+            Profile::from_network(Network::Signet).fee_targets,
+            Profile::from_network(Network::Testnet).fee_targets
+        );
+    }
+}
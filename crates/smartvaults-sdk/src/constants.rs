@@ -3,6 +3,8 @@
 
 use std::time::Duration;
 
+use crate::config::{FeeTargets, Profile};
+
 // Default relays
 pub const MAINNET_RELAYS: [&str; 2] = [
     "wss://relay-prod1.hashed.systems",
@@ -13,14 +15,164 @@ pub const TESTNET_RELAYS: [&str; 2] = [
     "wss://relay-test2.hashed.systems",
 ];
 
+// Network profiles
+pub const MAINNET_PROFILE: Profile = Profile {
+    name: "mainnet",
+    electrum_server: "blockstream.info:700:s",
+    block_explorer: Some("https://mempool.space"),
+    fee_targets: FeeTargets {
+        high: 1,
+        medium: 6,
+        low: 12,
+    },
+    relays: &MAINNET_RELAYS,
+};
+pub const TESTNET_PROFILE: Profile = Profile {
+    name: "testnet",
+    electrum_server: "blockstream.info:993:s",
+    block_explorer: Some("https://mempool.space/testnet"),
+    fee_targets: FeeTargets {
+        high: 1,
+        medium: 6,
+        low: 12,
+    },
+    relays: &TESTNET_RELAYS,
+};
+/// Signet profile (Mutinynet friendly: 30 seconds block time)
+pub const SIGNET_PROFILE: Profile = Profile {
+    name: "signet",
+    electrum_server: "signet-electrumx.wakiyamap.dev:50001:t",
+    block_explorer: Some("https://mempool.space/signet"),
+    fee_targets: FeeTargets {
+        high: 20,
+        medium: 60,
+        low: 180,
+    },
+    relays: &TESTNET_RELAYS,
+};
+pub const REGTEST_PROFILE: Profile = Profile {
+    name: "regtest",
+    electrum_server: "localhost:60401:t",
+    block_explorer: None,
+    fee_targets: FeeTargets {
+        high: 1,
+        medium: 6,
+        low: 12,
+    },
+    relays: &TESTNET_RELAYS,
+};
+
+// Proposal fee sanity checks
+/// Max fee rate allowed in a proposal, as multiple of the current high priority estimate
+pub const MAX_FEE_RATE_MULTIPLIER: f32 = 5.0;
+
+// Fee estimates
+pub const FEE_ESTIMATES_CACHE_TTL: Duration = Duration::from_secs(60);
+/// Fee rates (sat/vByte) used when the backend can't estimate: fastest, half hour, hour and economy
+///
+/// On the high side: better overpay than a transaction stuck for days.
+pub const FALLBACK_FEE_RATES: [f32; 4] = [25.0, 20.0, 15.0, 10.0];
+
 // Sync intervals
 pub const BLOCK_HEIGHT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
 pub const MEMPOOL_TX_FEES_SYNC_INTERVAL: Duration = Duration::from_secs(60);
 pub const WALLET_SYNC_INTERVAL: Duration = Duration::from_secs(60);
 pub const METADATA_SYNC_INTERVAL: Duration = Duration::from_secs(3600);
+/// Cached contact metadata older than this is refreshed in background
+pub const METADATA_STALE_AFTER: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+// Light sync profile
+/// Default history window (proposals, approvals and completed proposals) of the light profile
+pub const DEFAULT_LIGHT_SYNC_HORIZON: Duration = Duration::from_secs(60 * 60 * 24 * 90);
+/// Timechain sync interval of the light profile
+pub const LIGHT_TIMECHAIN_SYNC_INTERVAL: Duration = Duration::from_secs(300);
+pub(crate) const BACKFILL_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Protocol capabilities
+/// Interval between capabilities advertisements (see `Capabilities`)
+pub const CAPABILITIES_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24);
+
+// External signers
+/// Interval between the exports/imports of the external signer PSBT directory
+pub const PSBT_DIR_WATCH_INTERVAL: Duration = Duration::from_secs(10);
+
+// Pending events
+/// Interval between the checks of the pending events (i.e. received before their shared key)
+pub(crate) const PENDING_EVENTS_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// Delay before retrying a pending event, by number of failed retries (the last one is repeated)
+pub const PENDING_EVENT_BACKOFF: [Duration; 5] = [
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(60 * 5),
+    Duration::from_secs(60 * 30),
+    Duration::from_secs(60 * 60 * 6),
+];
+/// Failed retries after which a pending event is moved to the dead-letter state
+pub const PENDING_EVENT_MAX_RETRIES: u32 = 10;
+
+// Outbox
+/// Failed publications after which an outbox event is dropped (drained every minute)
+pub const OUTBOX_MAX_ATTEMPTS: u32 = 60;
+
+// Rebroadcast
+/// Interval between the rebroadcasts of all the events
+pub const REBROADCAST_INTERVAL: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+/// Interval between the checks of the last rebroadcast timestamp
+pub(crate) const REBROADCAST_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// Ingestion reports
+/// Max number of unreadable proposals reported every [`INGESTION_REPORT_WINDOW`]
+pub const INGESTION_REPORT_MAX_PER_WINDOW: usize = 10;
+/// Rate limit window of the unreadable proposals reports
+pub const INGESTION_REPORT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+// Approval reminders
+/// Proposals older than this are not reminded anymore
+pub const APPROVAL_REMINDERS_MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
+
+// Forms drafts
+/// Default age after which the forms drafts are deleted
+pub const DEFAULT_DRAFTS_EXPIRATION: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+
+// Addresses
+/// Default stop gap of the timechain full scan (consecutive unused addresses)
+pub const DEFAULT_GAP_LIMIT: usize = 50;
+
+// Wallet birthday
+/// Blocks mined (by median time past) less than this before the policy creation are scanned too
+pub const BIRTHDAY_SAFETY_MARGIN: Duration = Duration::from_secs(60 * 60 * 2);
+
+// Sync notifications
+/// Number of messages kept for subscribers replay
+pub const SYNC_NOTIFICATIONS_BUFFER_SIZE: usize = 256;
+/// Initial sync events received from a relay between two progress messages
+pub(crate) const SYNC_PROGRESS_STEP: usize = 50;
+
+// Relay redundancy
+/// Relays that must acknowledge a critical event (policy, proposal, approval, completed proposal)
+pub const DEFAULT_MIN_RELAY_ACKS: usize = 2;
+/// Recipient read relays (NIP-65) an addressed event (ex. shared signer) is also sent to
+pub const DEFAULT_MAX_RECIPIENT_RELAYS: usize = 3;
+/// Timeout for the connection to a recipient relay not in the pool
+pub(crate) const RECIPIENT_RELAY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Fiat prices
+/// Default price endpoint (mempool.space)
+pub const DEFAULT_PRICE_ENDPOINT: &str = "https://mempool.space/api/v1/prices";
+/// Age after which a cached price is not shown anymore
+pub const PRICE_CACHE_TTL: Duration = Duration::from_secs(60 * 15);
+pub(crate) const PRICE_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 5);
 
 // Timeout
 pub(crate) const SEND_TIMEOUT: Duration = Duration::from_secs(20);
+pub(crate) const PING_TIMEOUT: Duration = Duration::from_secs(10);
+pub(crate) const PRICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Electrum connection timeout (secs)
+pub(crate) const ELECTRUM_TIMEOUT: u8 = 15;
+
+// Electrum failover
+pub(crate) const ELECTRUM_FAILOVER_BACKOFF: Duration = Duration::from_secs(1);
+pub(crate) const ELECTRUM_FAILOVER_MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 pub(crate) const DEFAULT_SUBSCRIPTION_ID: &str = "smartvaults";
 pub(crate) const NOSTR_CONNECT_SUBSCRIPTION_ID: &str = "ncs";
@@ -21,12 +21,18 @@ pub enum Error {
     #[error(transparent)]
     Keychain(#[from] smartvaults_core::types::keychain::Error),
     #[error(transparent)]
+    Mnemonic(#[from] smartvaults_core::mnemonic::Error),
+    #[error(transparent)]
     Dir(#[from] util::dir::Error),
     #[error(transparent)]
+    Lock(#[from] crate::lock::Error),
+    #[error(transparent)]
     JSON(#[from] serde_json::Error),
     #[error(transparent)]
     Electrum(#[from] bdk_electrum::electrum_client::Error),
     #[error(transparent)]
+    Bitcoind(#[from] bdk_bitcoind_rpc::bitcoincore_rpc::Error),
+    #[error(transparent)]
     Url(#[from] nostr_sdk::types::url::ParseError),
     #[error(transparent)]
     Client(#[from] nostr_sdk::client::Error),
@@ -73,6 +79,18 @@ pub enum Error {
     #[error(transparent)]
     Config(#[from] crate::config::Error),
     #[error(transparent)]
+    Price(#[from] crate::price::Error),
+    #[error(transparent)]
+    HW(#[from] crate::hw::Error),
+    #[error(transparent)]
+    Ur(#[from] crate::util::ur::Error),
+    #[error(transparent)]
+    Settings(#[from] crate::types::settings::Error),
+    #[error(transparent)]
+    KeychainBackup(#[from] crate::types::keychain_backup::Error),
+    #[error(transparent)]
+    OfflineApproval(#[from] crate::types::offline_approval::Error),
+    #[error(transparent)]
     Store(#[from] smartvaults_sdk_sqlite::Error),
     #[error(transparent)]
     Label(#[from] smartvaults_protocol::v1::label::Error),
@@ -80,22 +98,59 @@ pub enum Error {
     KeyAgentVerified(#[from] smartvaults_protocol::v1::key_agent::verified::Error),
     #[error("password not match")]
     PasswordNotMatch,
+    #[error("mnemonic not match")]
+    MnemonicNotMatch,
+    #[error("passphrase not match")]
+    PassphraseNotMatch,
+    #[error("invalid keychain name: {0}")]
+    InvalidKeychainName(String),
+    #[error("keychain {0} already exists")]
+    KeychainAlreadyExists(String),
     #[error("not enough public keys")]
     NotEnoughPublicKeys,
+    #[error("duplicated public key: {0}")]
+    DuplicatedPublicKey(nostr_sdk::PublicKey),
     #[error("shared keys not found")]
     SharedKeysNotFound,
+    #[error("fee estimation not available")]
+    FeeEstimationUnavailable,
     #[error("policy not found")]
     PolicyNotFound,
+    #[error("policy already exists: {0}")]
+    PolicyAlreadyExists(nostr_sdk::EventId),
+    #[error("network mismatch: found {found} keys, but the current network is {expected}")]
+    NetworkMismatch {
+        expected: smartvaults_core::bitcoin::Network,
+        found: smartvaults_core::bitcoin::Network,
+    },
+    #[error("key {key} derived for {purpose}, but the policy has {script_type} addresses")]
+    KeyPurposeMismatch {
+        key: String,
+        purpose: smartvaults_core::policy::KeyPurpose,
+        script_type: smartvaults_core::policy::ScriptType,
+    },
+    #[error("address {address} is for {found}, but the current network is {expected}")]
+    AddressNetworkMismatch {
+        address: String,
+        expected: smartvaults_core::bitcoin::Network,
+        found: smartvaults_core::bitcoin::Network,
+    },
     #[error("proposal not found")]
     ProposalNotFound,
     #[error("unexpected proposal")]
     UnexpectedProposal,
+    #[error("approval request doesn't match the local vault")]
+    ApprovalRequestPolicyMismatch,
     #[error("approved proposal/s not found")]
     ApprovedProposalNotFound,
     #[error("signer not found")]
     SignerNotFound,
     #[error("signer ID not found")]
     SignerIdNotFound,
+    #[error("signer not in policy")]
+    SignerNotInPolicy,
+    #[error("{0} signer can't sign locally: approve with a signed PSBT or with the device")]
+    SignerNotLocal(smartvaults_core::signer::SignerType),
     #[error("public key not found")]
     PublicKeyNotFound,
     #[error("signer already shared")]
@@ -108,10 +163,46 @@ pub enum Error {
     CantGenerateNostrConnectResponse,
     #[error("invalid fee rate")]
     InvalidFeeRate,
+    #[error("fee bump not higher than the original fee: {new} sat ({new_rate:.2} sat/vB) <= {original} sat ({original_rate:.2} sat/vB)")]
+    FeeBumpTooLow {
+        original: u64,
+        original_rate: f32,
+        new: u64,
+        new_rate: f32,
+    },
+    #[error("suspicious proposal fee ({0}): acknowledge the fee to approve")]
+    FeeWarning(smartvaults_core::FeeWarning),
+    #[error("{0} proposal output/s not match the vault descriptor: force to approve")]
+    UnverifiedOutputs(usize),
+    #[error("address {address} is {result}")]
+    ScreeningFlagged {
+        address: String,
+        result: crate::screening::ScreeningResult,
+    },
+    #[error("auto-complete not possible: {0}")]
+    AutoCompleteNotPossible(String),
+    #[error("policy path required: select the sub-paths to use for {} conditions", .0.len())]
+    PolicyPathRequired(Vec<smartvaults_core::policy::SelectableCondition>),
+    #[error("UTXO {0} not belongs to the policy")]
+    UtxoNotInPolicy(smartvaults_core::bitcoin::OutPoint),
+    #[error("UTXO {0} is frozen by another proposal")]
+    UtxoFrozen(smartvaults_core::bitcoin::OutPoint),
+    #[error("signer used by {} policies: force to delete", .0.len())]
+    SignerInUse(Vec<nostr_sdk::EventId>),
+    #[error("shared signer used by {} policies shared with the contact: force to revoke", .0.len())]
+    SharedSignerInUse(Vec<nostr_sdk::EventId>),
+    #[error("key agent role not declared: announce as key agent first")]
+    KeyAgentRoleRequired,
+    #[error("event acknowledged by {got} relays of the {want} required: it will be retried in background")]
+    InsufficientRelayAcks { got: usize, want: usize },
+    #[error("event {0} is not pending")]
+    EventNotPending(nostr_sdk::EventId),
     #[error("impossible to delete a not owned event")]
     TryingToDeleteNotOwnedEvent,
     #[error("not found")]
     NotFound,
+    #[error("operation cancelled")]
+    Cancelled,
     #[error("{0}")]
     Generic(String),
 }
@@ -0,0 +1,314 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Hardware wallets
+//!
+//! Some devices (i.e. Ledger) sign only for wallet policies registered in advance: the
+//! registration returns an HMAC that must be provided at every signing session.
+//! The HMAC becomes invalid if the device is wiped or re-flashed.
+
+use core::fmt;
+
+use smartvaults_core::bitcoin::bip32::Fingerprint;
+use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+#[cfg(feature = "hwi")]
+use smartvaults_core::bitcoin::Network;
+#[cfg(feature = "hwi")]
+use smartvaults_core::hwi::error::{Error as HWIError, ErrorCode as HWIErrorCode};
+#[cfg(feature = "hwi")]
+use smartvaults_core::hwi::{types::HWIDevice, HWIClient};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The device doesn't recognize the HMAC (device wiped or re-flashed)
+    #[error("wallet policy registration invalid")]
+    RegistrationInvalid,
+    #[error("wallet policy re-registration rejected by the user")]
+    ReRegistrationRejected,
+    /// Cancelled by the user on the device
+    #[error("rejected on device")]
+    Rejected,
+    /// The device master fingerprint is not one of the policy keys
+    #[error("device {0} is not a signer of the policy")]
+    NotInvolved(Fingerprint),
+    /// PIN or passphrase not entered yet
+    #[error("device {0} locked: unlock it (PIN or passphrase) and retry")]
+    Locked(String),
+    #[error("device: {0}")]
+    Device(String),
+}
+
+/// Hardware device
+pub trait HardwareDevice: fmt::Debug + Send + Sync {
+    /// Master fingerprint
+    fn fingerprint(&self) -> Fingerprint;
+
+    /// Device identifier (ex. serial number)
+    fn device_id(&self) -> String;
+
+    /// Check if the device requires the wallet policy registration before signing
+    fn requires_registration(&self) -> bool {
+        true
+    }
+
+    /// Register a wallet policy and return the HMAC
+    fn register_policy(&self, name: &str, descriptor: &str) -> Result<Vec<u8>, Error>;
+
+    /// Sign a PSBT
+    ///
+    /// `hmac` is `None` if the device doesn't require the registration.
+    fn sign_psbt(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        name: &str,
+        descriptor: &str,
+        hmac: Option<&[u8]>,
+    ) -> Result<PartiallySignedTransaction, Error>;
+}
+
+/// Hardware device connected via HWI
+///
+/// HWI registers the wallet policy on Ledger devices at every signing session (the user confirms
+/// it on the device), so no HMAC is stored.
+#[cfg(feature = "hwi")]
+#[derive(Debug, Clone)]
+pub struct HwiDevice {
+    device: HWIDevice,
+    network: Network,
+}
+
+#[cfg(feature = "hwi")]
+impl HwiDevice {
+    pub fn new(device: HWIDevice, network: Network) -> Result<Self, Error> {
+        if device.needs_pin_sent || device.needs_passphrase_sent {
+            return Err(Error::Locked(device.model));
+        }
+        Ok(Self { device, network })
+    }
+
+    fn client(&self) -> Result<HWIClient, Error> {
+        HWIClient::get_client(&self.device, false, self.network).map_err(Error::from)
+    }
+}
+
+#[cfg(feature = "hwi")]
+impl From<HWIError> for Error {
+    fn from(e: HWIError) -> Self {
+        match e {
+            HWIError::Hwi(_, Some(HWIErrorCode::ActionCanceled)) => Self::Rejected,
+            e => Self::Device(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "hwi")]
+impl HardwareDevice for HwiDevice {
+    fn fingerprint(&self) -> Fingerprint {
+        self.device.fingerprint
+    }
+
+    fn device_id(&self) -> String {
+        self.device.model.clone()
+    }
+
+    fn requires_registration(&self) -> bool {
+        false
+    }
+
+    fn register_policy(&self, _name: &str, _descriptor: &str) -> Result<Vec<u8>, Error> {
+        Err(Error::Device(String::from(
+            "wallet policy registration is handled by HWI while signing",
+        )))
+    }
+
+    fn sign_psbt(
+        &self,
+        psbt: &PartiallySignedTransaction,
+        _name: &str,
+        _descriptor: &str,
+        _hmac: Option<&[u8]>,
+    ) -> Result<PartiallySignedTransaction, Error> {
+        Ok(self.client()?.sign_tx(psbt)?.psbt)
+    }
+}
+
+/// PSBT signed by a hardware device
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HwSignature {
+    pub psbt: PartiallySignedTransaction,
+    /// New HMAC to store, if the wallet policy was (re-)registered
+    pub registration: Option<Vec<u8>>,
+}
+
+/// Sign a PSBT, registering the wallet policy if needed
+///
+/// If the device reports the stored `hmac` as invalid, the registration is done again only
+/// if `confirm_reregistration` returns `true`.
+pub fn sign_with_registration<D, F>(
+    device: &D,
+    name: &str,
+    descriptor: &str,
+    psbt: &PartiallySignedTransaction,
+    hmac: Option<Vec<u8>>,
+    confirm_reregistration: F,
+) -> Result<HwSignature, Error>
+where
+    D: HardwareDevice + ?Sized,
+    F: FnOnce() -> bool,
+{
+    if !device.requires_registration() {
+        return Ok(HwSignature {
+            psbt: device.sign_psbt(psbt, name, descriptor, None)?,
+            registration: None,
+        });
+    }
+
+    let (hmac, mut registration) = match hmac {
+        Some(hmac) => (hmac, None),
+        None => {
+            let hmac: Vec<u8> = device.register_policy(name, descriptor)?;
+            (hmac.clone(), Some(hmac))
+        }
+    };
+
+    match device.sign_psbt(psbt, name, descriptor, Some(&hmac)) {
+        Ok(psbt) => Ok(HwSignature { psbt, registration }),
+        // Never re-register a policy just registered
+        Err(Error::RegistrationInvalid) if registration.is_none() => {
+            tracing::warn!(
+                "Wallet policy registration invalid on device {}",
+                device.fingerprint()
+            );
+            if !confirm_reregistration() {
+                return Err(Error::ReRegistrationRejected);
+            }
+            let hmac: Vec<u8> = device.register_policy(name, descriptor)?;
+            let psbt = device.sign_psbt(psbt, name, descriptor, Some(&hmac))?;
+            registration = Some(hmac);
+            Ok(HwSignature { psbt, registration })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use parking_lot::Mutex;
+    use smartvaults_core::bitcoin::absolute::LockTime;
+    use smartvaults_core::bitcoin::Transaction;
+
+    use super::*;
+
+    const NAME: &str = "Test";
+    const DESCRIPTOR: &str = "tr(xpub/0/*)";
+
+    /// Mocked device: the HMAC is derived from a device secret that changes on re-flash
+    #[derive(Debug, Default)]
+    struct MockDevice {
+        secret: Mutex<u8>,
+        registrations: Mutex<usize>,
+    }
+
+    impl MockDevice {
+        fn reflash(&self) {
+            *self.secret.lock() += 1;
+        }
+
+        fn hmac(&self) -> Vec<u8> {
+            vec![*self.secret.lock(); 32]
+        }
+    }
+
+    impl HardwareDevice for MockDevice {
+        fn fingerprint(&self) -> Fingerprint {
+            Fingerprint::from_str("f57a6b99").unwrap()
+        }
+
+        fn device_id(&self) -> String {
+            String::from("mock")
+        }
+
+        fn register_policy(&self, _name: &str, _descriptor: &str) -> Result<Vec<u8>, Error> {
+            *self.registrations.lock() += 1;
+            Ok(self.hmac())
+        }
+
+        fn sign_psbt(
+            &self,
+            psbt: &PartiallySignedTransaction,
+            _name: &str,
+            _descriptor: &str,
+            hmac: Option<&[u8]>,
+        ) -> Result<PartiallySignedTransaction, Error> {
+            if hmac != Some(self.hmac().as_slice()) {
+                return Err(Error::RegistrationInvalid);
+            }
+            Ok(psbt.clone())
+        }
+    }
+
+    fn psbt() -> PartiallySignedTransaction {
+        PartiallySignedTransaction::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_first_registration() {
+        let device = MockDevice::default();
+        let res = sign_with_registration(&device, NAME, DESCRIPTOR, &psbt(), None, || {
+            panic!("unexpected confirmation")
+        })
+        .unwrap();
+        assert_eq!(res.registration, Some(device.hmac()));
+        assert_eq!(*device.registrations.lock(), 1);
+
+        // Registration reused
+        let res =
+            sign_with_registration(&device, NAME, DESCRIPTOR, &psbt(), res.registration, || {
+                panic!("unexpected confirmation")
+            })
+            .unwrap();
+        assert!(res.registration.is_none());
+        assert_eq!(*device.registrations.lock(), 1);
+    }
+
+    #[test]
+    fn test_reregistration_after_reflash() {
+        let device = MockDevice::default();
+        let old_hmac: Vec<u8> = device.register_policy(NAME, DESCRIPTOR).unwrap();
+        device.reflash();
+
+        // Rejected by the user
+        let res = sign_with_registration(
+            &device,
+            NAME,
+            DESCRIPTOR,
+            &psbt(),
+            Some(old_hmac.clone()),
+            || false,
+        );
+        assert!(matches!(res, Err(Error::ReRegistrationRejected)));
+        assert_eq!(*device.registrations.lock(), 1);
+
+        // Confirmed
+        let res = sign_with_registration(
+            &device,
+            NAME,
+            DESCRIPTOR,
+            &psbt(),
+            Some(old_hmac.clone()),
+            || true,
+        )
+        .unwrap();
+        assert_eq!(res.registration, Some(device.hmac()));
+        assert_ne!(res.registration, Some(old_hmac));
+        assert_eq!(*device.registrations.lock(), 2);
+    }
+}
@@ -6,20 +6,27 @@
 #![warn(rustdoc::bare_urls)]
 #![doc = include_str!("../README.md")]
 
-pub use {nostr_sdk as nostr, smartvaults_core as core, smartvaults_protocol as protocol};
+pub use nostr_sdk as nostr;
+pub use smartvaults_core as core;
+pub use smartvaults_protocol as protocol;
 
+pub mod changelog;
 pub mod client;
 pub mod config;
 pub mod constants;
 mod error;
+pub mod hw;
+pub mod lock;
 pub mod logger;
 pub mod manager;
 pub mod prelude;
+pub mod price;
+pub mod screening;
 mod storage;
 pub mod types;
 pub mod util;
 
-pub use self::client::{EventHandled, Message, SmartVaults};
+pub use self::client::{EventHandled, Message, Notification, SmartVaults, SyncSubscription};
 pub use self::error::Error;
 pub use self::types::PolicyBackup;
 
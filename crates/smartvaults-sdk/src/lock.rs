@@ -0,0 +1,376 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Advisory lock file
+//!
+//! Prevent opening the same account from more processes at the same time (i.e. GUI and CLI).
+//!
+//! The lock is an OS advisory lock (`flock` / `LockFileEx`) held on the open file, so it's released
+//! by the kernel when the owner exits, even if it crashes. The file content (pid and start time of the
+//! owner) is informational only and is cleared on release: a non-empty file whose lock is free was left
+//! by a crashed process.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use nostr_sdk::Timestamp;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    IO(#[from] io::Error),
+    #[error("account already opened by process {pid} (since {})", .started_at.to_human_datetime())]
+    Locked { pid: u32, started_at: Timestamp },
+    #[error("stale lock of process {pid} (since {}): steal the lock to recover", .started_at.to_human_datetime())]
+    Stale { pid: u32, started_at: Timestamp },
+    #[error("invalid lock file")]
+    InvalidLockFile,
+}
+
+/// Lock owner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub started_at: Timestamp,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: Timestamp::now(),
+        }
+    }
+
+    /// Read owner from lock file content (`None` if the file is empty)
+    fn read(file: &mut File) -> Result<Option<Self>, Error> {
+        let mut content = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut content)?;
+
+        if content.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let mut lines = content.lines();
+        let pid: u32 = lines
+            .next()
+            .and_then(|l| l.trim().parse().ok())
+            .ok_or(Error::InvalidLockFile)?;
+        let started_at: u64 = lines
+            .next()
+            .and_then(|l| l.trim().parse().ok())
+            .ok_or(Error::InvalidLockFile)?;
+        Ok(Some(Self {
+            pid,
+            started_at: Timestamp::from(started_at),
+        }))
+    }
+
+    fn write(&self, file: &mut File) -> Result<(), Error> {
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        writeln!(file, "{}", self.pid)?;
+        writeln!(file, "{}", self.started_at)?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Lock file, released on drop
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    file: File,
+    released: AtomicBool,
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl LockFile {
+    /// Acquire lock
+    ///
+    /// If `steal` is `true`, a lock left by a crashed process is taken over.
+    /// A lock held by a running process is never stolen.
+    pub fn acquire<P>(path: P, steal: bool) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path: &Path = path.as_ref();
+        let mut file: File = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if !try_lock_exclusive(&file)? {
+            return match LockOwner::read(&mut file) {
+                Ok(Some(other)) => Err(Error::Locked {
+                    pid: other.pid,
+                    started_at: other.started_at,
+                }),
+                // Owner info not written yet
+                Ok(None) | Err(Error::InvalidLockFile) => Err(Error::Locked {
+                    pid: 0,
+                    started_at: Timestamp::from(0),
+                }),
+                Err(e) => Err(e),
+            };
+        }
+
+        // Dropping `file` releases the OS lock
+        match LockOwner::read(&mut file) {
+            Ok(None) => (),
+            Ok(Some(other)) if steal => {
+                tracing::warn!("Stealing stale lock of process {}", other.pid);
+            }
+            Ok(Some(other)) => {
+                return Err(Error::Stale {
+                    pid: other.pid,
+                    started_at: other.started_at,
+                })
+            }
+            // Lock file partially written by a crashed process
+            Err(Error::InvalidLockFile) if steal => (),
+            Err(e) => return Err(e),
+        }
+
+        LockOwner::current().write(&mut file)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            released: AtomicBool::new(false),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Release lock (only once)
+    ///
+    /// The file is emptied, not removed, to not race with processes that already opened it.
+    pub fn release(&self) {
+        if self.released.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        if let Err(e) = self.file.set_len(0) {
+            tracing::error!("Impossible to clear lock {}: {e}", self.path.display());
+        }
+
+        if let Err(e) = unlock(&self.file) {
+            tracing::error!("Impossible to release lock {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Try to take the exclusive advisory lock (`false` if held by another file handle)
+#[cfg(unix)]
+fn try_lock_exclusive(file: &File) -> io::Result<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the file descriptor is valid for the lifetime of `file`
+    let res = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+    if res == 0 {
+        return Ok(true);
+    }
+
+    let e = io::Error::last_os_error();
+    if e.kind() == io::ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(e)
+    }
+}
+
+/// Try to take the exclusive advisory lock (`false` if held by another file handle)
+///
+/// Windows locks are mandatory: lock a byte range past the content, so the owner info can still be read.
+#[cfg(windows)]
+fn try_lock_exclusive(file: &File) -> io::Result<bool> {
+    use std::os::windows::io::AsRawHandle;
+
+    use windows_sys::Win32::Foundation::{ERROR_LOCK_VIOLATION, HANDLE};
+    use windows_sys::Win32::Storage::FileSystem::{
+        LockFileEx, LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY,
+    };
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+
+    // SAFETY: all-zero is a valid `OVERLAPPED`
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    overlapped.Anonymous.Anonymous.OffsetHigh = u32::MAX;
+
+    // SAFETY: the handle is valid for the lifetime of `file`
+    let res = unsafe {
+        LockFileEx(
+            file.as_raw_handle() as HANDLE,
+            LOCKFILE_EXCLUSIVE_LOCK | LOCKFILE_FAIL_IMMEDIATELY,
+            0,
+            1,
+            0,
+            &mut overlapped,
+        )
+    };
+    if res != 0 {
+        return Ok(true);
+    }
+
+    let e = io::Error::last_os_error();
+    if e.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+        Ok(false)
+    } else {
+        Err(e)
+    }
+}
+
+#[cfg(unix)]
+fn unlock(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: the file descriptor is valid for the lifetime of `file`
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(windows)]
+fn unlock(file: &File) -> io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::Storage::FileSystem::UnlockFileEx;
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+
+    // SAFETY: all-zero is a valid `OVERLAPPED`
+    let mut overlapped: OVERLAPPED = unsafe { std::mem::zeroed() };
+    overlapped.Anonymous.Anonymous.OffsetHigh = u32::MAX;
+
+    // SAFETY: the handle is valid for the lifetime of `file`
+    let res = unsafe { UnlockFileEx(file.as_raw_handle() as HANDLE, 0, 1, 0, &mut overlapped) };
+    if res != 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn try_lock_exclusive(_file: &File) -> io::Result<bool> {
+    // No advisory locks available: can't detect a running owner
+    Ok(true)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn unlock(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn lock_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("smartvaults-{name}-{}.lock", std::process::id()))
+    }
+
+    #[test]
+    fn test_simultaneous_acquire() {
+        let path = lock_path("simultaneous");
+
+        let lock = LockFile::acquire(&path, false).unwrap();
+        assert!(matches!(
+            LockFile::acquire(&path, false),
+            Err(Error::Locked { .. })
+        ));
+        // A running owner is never stolen
+        assert!(matches!(
+            LockFile::acquire(&path, true),
+            Err(Error::Locked { .. })
+        ));
+
+        drop(lock);
+        assert!(fs::read_to_string(&path).unwrap().is_empty());
+
+        let lock = LockFile::acquire(&path, false).unwrap();
+
+        // Explicit release, even if the handle is still alive (i.e. clones held by the syncing threads)
+        lock.release();
+        let other = LockFile::acquire(&path, false).unwrap();
+        drop(lock);
+        assert!(matches!(
+            LockFile::acquire(&path, false),
+            Err(Error::Locked { .. })
+        ));
+
+        drop(other);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_recovery() {
+        let path = lock_path("stale");
+
+        // Pid of an exited process
+        let mut child = std::process::Command::new(if cfg!(windows) { "cmd" } else { "true" })
+            .args(if cfg!(windows) {
+                vec!["/C", "exit"]
+            } else {
+                vec![]
+            })
+            .spawn()
+            .unwrap();
+        child.wait().unwrap();
+        fs::write(&path, format!("{}\n{}\n", child.id(), Timestamp::now())).unwrap();
+
+        assert!(matches!(
+            LockFile::acquire(&path, false),
+            Err(Error::Stale { .. })
+        ));
+
+        let lock = LockFile::acquire(&path, true).unwrap();
+        assert_eq!(
+            LockOwner::read(&mut File::open(lock.path()).unwrap())
+                .unwrap()
+                .unwrap()
+                .pid,
+            std::process::id()
+        );
+        drop(lock);
+        assert!(fs::read_to_string(&path).unwrap().is_empty());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_reused_pid() {
+        let path = lock_path("reused-pid");
+
+        // Left by a crash before a reboot: the pid is now used by a running process (this one)
+        fs::write(
+            &path,
+            format!("{}\n{}\n", std::process::id(), Timestamp::from(0)),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            LockFile::acquire(&path, false),
+            Err(Error::Stale { .. })
+        ));
+
+        let lock = LockFile::acquire(&path, true).unwrap();
+        drop(lock);
+        fs::remove_file(path).unwrap();
+    }
+}
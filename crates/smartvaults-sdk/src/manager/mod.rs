@@ -7,39 +7,50 @@ use std::net::SocketAddr;
 use std::ops::Add;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_utility::thread;
+use bdk_bitcoind_rpc::bitcoincore_rpc::{self, Client as BitcoindClient, RpcApi};
 use bdk_electrum::electrum_client::{
     self, Client as ElectrumClient, Config as ElectrumConfig, ElectrumApi, HeaderNotification,
     Socks5Config,
 };
 use nostr_sdk::hashes::sha256::Hash as Sha256Hash;
 use nostr_sdk::hashes::Hash;
-use nostr_sdk::{EventId, Timestamp};
+use nostr_sdk::{EventId, Timestamp, Url};
 use smartvaults_core::bdk::chain::ConfirmationTime;
 use smartvaults_core::bdk::wallet::{AddressIndex, AddressInfo, Balance, NewOrLoadError};
 use smartvaults_core::bdk::{FeeRate, LocalOutput, Wallet};
 use smartvaults_core::bitcoin::address::NetworkUnchecked;
 use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
 use smartvaults_core::bitcoin::{Address, Network, OutPoint, ScriptBuf, Transaction, Txid};
-use smartvaults_core::{Amount, Policy, Priority, Proposal};
+use smartvaults_core::{Amount, CoinSelection, Policy, Priority, Proposal};
+use smartvaults_sdk_sqlite::model::{Birthday, NotificationKind, WalletIntegrity};
 use smartvaults_sdk_sqlite::Store;
 use thiserror::Error;
-use tokio::sync::broadcast::Sender;
 use tokio::sync::RwLock;
 
 pub mod wallet;
 
 pub use self::wallet::{
-    Error as WalletError, SmartVaultsWallet, SmartVaultsWalletStorage, StorageError,
-    TransactionDetails,
+    Error as WalletError, IndexedAddress, SmartVaultsWallet, SmartVaultsWalletStorage,
+    StorageError, TransactionDetails,
 };
-use crate::config::ElectrumEndpoint;
+use crate::client::SyncChannel;
+use crate::config::{BitcoindAuth, ElectrumEndpoint, FeeTargets};
 use crate::constants::{BLOCK_HEIGHT_SYNC_INTERVAL, MEMPOOL_TX_FEES_SYNC_INTERVAL};
 use crate::Message;
 
 const TARGET_BLOCKS: [Priority; 3] = [Priority::High, Priority::Medium, Priority::Low];
 
+/// Build a Bitcoin Core RPC client
+pub(crate) fn bitcoind_client(
+    url: &Url,
+    auth: BitcoindAuth,
+) -> Result<BitcoindClient, bitcoincore_rpc::Error> {
+    BitcoindClient::new(url.as_str(), auth.into())
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error(transparent)]
@@ -49,9 +60,13 @@ pub enum Error {
     #[error(transparent)]
     Electrum(#[from] electrum_client::Error),
     #[error(transparent)]
+    Bitcoind(#[from] bitcoincore_rpc::Error),
+    #[error(transparent)]
     Wallet(#[from] WalletError),
     #[error(transparent)]
     Join(#[from] tokio::task::JoinError),
+    #[error(transparent)]
+    Store(#[from] smartvaults_sdk_sqlite::Error),
     #[error("policy {0} already loaded")]
     AlreadyLoaded(EventId),
     #[error("policy {0} not loaded")]
@@ -115,6 +130,14 @@ impl EstimatedMempoolFees {
     }
 }
 
+/// Checksum of a descriptor string (empty if missing)
+fn checksum(descriptor: &str) -> &str {
+    descriptor
+        .rsplit_once('#')
+        .map(|(_, checksum)| checksum)
+        .unwrap_or_default()
+}
+
 #[derive(Debug, Clone)]
 pub struct Manager {
     db: Store,
@@ -135,23 +158,135 @@ impl Manager {
         }
     }
 
+    /// Descriptor and network the wallet state of the policy must be built for
+    pub fn expected_wallet_integrity(&self, policy: &Policy) -> WalletIntegrity {
+        let descriptor: String = policy.as_descriptor().to_string();
+        let change_descriptor: Option<String> =
+            policy.as_change_descriptor().map(|d| d.to_string());
+
+        // Single descriptor policies keep the previous storage key
+        let (descriptor_hash, checksum) = match &change_descriptor {
+            Some(change_descriptor) => (
+                Sha256Hash::hash(format!("{descriptor}{change_descriptor}").as_bytes()),
+                format!("{}+{}", checksum(&descriptor), checksum(change_descriptor)),
+            ),
+            None => (
+                Sha256Hash::hash(descriptor.as_bytes()),
+                checksum(&descriptor).to_string(),
+            ),
+        };
+
+        WalletIntegrity {
+            descriptor_hash,
+            checksum,
+            network: self.network,
+        }
+    }
+
+    /// Check if the stored wallet state of the policy was built for another descriptor or network
+    pub async fn wallet_integrity_mismatch(
+        &self,
+        policy_id: EventId,
+        policy: &Policy,
+    ) -> Result<bool, Error> {
+        let expected: WalletIntegrity = self.expected_wallet_integrity(policy);
+        if let Some(stored) = self.db.get_wallet_integrity(policy_id).await? {
+            if stored != expected {
+                return Ok(true);
+            }
+        }
+        match self.db.get_changeset(expected.descriptor_hash).await {
+            Ok(changeset) => Ok(changeset
+                .network
+                .map(|network| network != self.network)
+                .unwrap_or(false)),
+            Err(smartvaults_sdk_sqlite::Error::NotFound(..)) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Quarantine the wallet state of the policy (must not be loaded), so it's rescanned from
+    /// scratch at the next load
+    pub async fn repair_wallet_integrity(
+        &self,
+        policy_id: EventId,
+        policy: &Policy,
+    ) -> Result<(), Error> {
+        let expected: WalletIntegrity = self.expected_wallet_integrity(policy);
+        if let Some(stored) = self.db.get_wallet_integrity(policy_id).await? {
+            if stored.descriptor_hash != expected.descriptor_hash {
+                self.db.quarantine_changeset(stored.descriptor_hash).await?;
+            }
+        }
+        self.db
+            .quarantine_changeset(expected.descriptor_hash)
+            .await?;
+        self.db.save_wallet_integrity(policy_id, expected).await?;
+        tracing::warn!("Wallet state of policy {policy_id} quarantined");
+        Ok(())
+    }
+
+    /// Load policy
+    ///
+    /// `created_at` is the policy event creation, recorded as birthday.
+    ///
+    /// The wallet state is verified against the policy descriptor and the network: on mismatch,
+    /// it's quarantined (the wallet is rescanned from scratch) and
+    /// [`Message::PolicyWalletMismatch`] is emitted.
     #[tracing::instrument(skip_all, level = "trace")]
-    pub async fn load_policy(&self, policy_id: EventId, policy: Policy) -> Result<(), Error> {
+    pub async fn load_policy(
+        &self,
+        policy_id: EventId,
+        policy: Policy,
+        created_at: Option<Timestamp>,
+        sync_channel: Option<SyncChannel>,
+    ) -> Result<(), Error> {
+        if let Some(created_at) = created_at {
+            self.db
+                .save_birthday_timestamp(policy_id, created_at)
+                .await?;
+        }
+        let birthday: Birthday = self.db.get_birthday(policy_id).await?;
+
         let this = self.clone();
         let mut wallets = self.wallets.write().await;
         if let Entry::Vacant(e) = wallets.entry(policy_id) {
+            let integrity: WalletIntegrity = self.expected_wallet_integrity(&policy);
+            if self.wallet_integrity_mismatch(policy_id, &policy).await? {
+                tracing::warn!("Wallet state of policy {policy_id} doesn't match the policy");
+                self.repair_wallet_integrity(policy_id, &policy).await?;
+                self.db
+                    .save_notification(
+                        NotificationKind::PolicyWalletMismatch,
+                        policy_id,
+                        Timestamp::now(),
+                    )
+                    .await?;
+                if let Some(sync_channel) = &sync_channel {
+                    sync_channel.send(Message::PolicyWalletMismatch(policy_id));
+                }
+            } else {
+                self.db
+                    .save_wallet_integrity(policy_id, integrity.clone())
+                    .await?;
+            }
+
             let wallet: SmartVaultsWallet = tokio::task::spawn_blocking(move || {
-                let descriptor_hash =
-                    Sha256Hash::hash(policy.as_descriptor().to_string().as_bytes());
+                let descriptor: String = policy.as_descriptor().to_string();
+                let change_descriptor: Option<String> =
+                    policy.as_change_descriptor().map(|d| d.to_string());
+                let descriptor_hash: Sha256Hash = integrity.descriptor_hash;
                 let db: SmartVaultsWalletStorage =
                     SmartVaultsWalletStorage::new(descriptor_hash, this.db.clone());
                 let wallet: Wallet<SmartVaultsWalletStorage> = Wallet::new_or_load(
-                    &policy.as_descriptor().to_string(),
-                    None,
+                    &descriptor,
+                    change_descriptor.as_deref(),
                     db,
                     this.network,
                 )?;
-                Ok::<SmartVaultsWallet, Error>(SmartVaultsWallet::new(policy_id, policy, wallet))
+                Ok::<SmartVaultsWallet, Error>(SmartVaultsWallet::new(
+                    policy_id, policy, wallet, birthday,
+                ))
             })
             .await??;
             e.insert(wallet);
@@ -178,6 +313,12 @@ impl Manager {
         }
     }
 
+    /// Update the birthday of a loaded policy
+    pub async fn set_birthday(&self, policy_id: EventId, birthday: Birthday) -> Result<(), Error> {
+        self.wallet(policy_id).await?.set_birthday(birthday).await;
+        Ok(())
+    }
+
     pub fn block_height(&self) -> u32 {
         self.block_height.block_height()
     }
@@ -214,6 +355,7 @@ impl Manager {
         &self,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
+        fee_targets: FeeTargets,
     ) -> Result<Option<BTreeMap<Priority, FeeRate>>, Error> {
         if !self.mempool_fees.is_synced().await {
             tracing::info!("Initializing electrum client: endpoint={endpoint}, proxy={proxy:?}");
@@ -224,8 +366,11 @@ impl Manager {
                 .build();
             let client = ElectrumClient::from_config(&endpoint.as_non_standard_format(), config)?;
 
-            let fees: Vec<f64> = client
-                .batch_estimate_fee(TARGET_BLOCKS.iter().map(|p| p.target_blocks() as usize))?;
+            let fees: Vec<f64> = client.batch_estimate_fee(
+                TARGET_BLOCKS
+                    .iter()
+                    .map(|p| fee_targets.target_blocks(*p) as usize),
+            )?;
             if TARGET_BLOCKS.len() == fees.len() {
                 let mut estimated_fees = BTreeMap::new();
                 for (priority, btc_per_kvb) in TARGET_BLOCKS.into_iter().zip(fees) {
@@ -245,6 +390,95 @@ impl Manager {
         Ok(None)
     }
 
+    pub async fn sync_block_height_bitcoind(
+        &self,
+        url: &Url,
+        auth: BitcoindAuth,
+    ) -> Result<(), Error> {
+        if !self.block_height.is_synced().await {
+            let client: BitcoindClient = bitcoind_client(url, auth)?;
+            let height: u32 = client.get_block_count()? as u32;
+
+            if self.block_height() != height {
+                self.block_height.set_block_height(height);
+                self.block_height.just_synced().await;
+
+                tracing::info!("Block height synced [bitcoind]")
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn sync_mempool_fees_bitcoind(
+        &self,
+        url: &Url,
+        auth: BitcoindAuth,
+        fee_targets: FeeTargets,
+    ) -> Result<Option<BTreeMap<Priority, FeeRate>>, Error> {
+        if !self.mempool_fees.is_synced().await {
+            let client: BitcoindClient = bitcoind_client(url, auth)?;
+
+            let mut estimated_fees = BTreeMap::new();
+            for priority in TARGET_BLOCKS.into_iter() {
+                let target_blocks: u16 = fee_targets.target_blocks(priority) as u16;
+                let res = client.estimate_smart_fee(target_blocks, None)?;
+                // Not enough data (i.e. regtest)
+                if let Some(btc_per_kvb) = res.fee_rate {
+                    let rate = FeeRate::from_btc_per_kvb(btc_per_kvb.to_btc() as f32);
+                    estimated_fees.insert(priority, rate);
+                }
+            }
+
+            if estimated_fees.len() == TARGET_BLOCKS.len() {
+                // Save
+                self.mempool_fees.set_fees(estimated_fees.clone()).await;
+                self.mempool_fees.just_synced().await;
+                tracing::info!("Mempool fees synced [bitcoind]");
+
+                return Ok(Some(estimated_fees));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Wait for a new block, up to `timeout`
+    ///
+    /// Return the new height, if any.
+    pub async fn wait_for_new_block(
+        &self,
+        url: &Url,
+        auth: BitcoindAuth,
+        timeout: Duration,
+    ) -> Result<Option<u32>, Error> {
+        let client: BitcoindClient = bitcoind_client(url, auth)?;
+        let current: u32 = self.block_height();
+        let block = tokio::task::spawn_blocking(move || {
+            client.wait_for_new_block(timeout.as_millis() as u64)
+        })
+        .await??;
+        let height: u32 = block.height as u32;
+        if height != current {
+            self.block_height.set_block_height(height);
+            self.block_height.just_synced().await;
+            Ok(Some(height))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the last estimated mempool fees
+    pub async fn mempool_fees(&self) -> BTreeMap<Priority, FeeRate> {
+        self.mempool_fees.get().await
+    }
+
+    /// Get the IDs of the loaded policies
+    pub async fn policy_ids(&self) -> Vec<EventId> {
+        let wallets = self.wallets.read().await;
+        wallets.keys().copied().collect()
+    }
+
     pub async fn wallet(&self, policy_id: EventId) -> Result<SmartVaultsWallet, Error> {
         let wallets = self.wallets.read().await;
         Ok(wallets
@@ -282,11 +516,33 @@ impl Manager {
         Ok(self.wallet(policy_id).await?.get_address(index).await?)
     }
 
-    pub async fn get_addresses(
+    pub async fn get_addresses(&self, policy_id: EventId) -> Result<Vec<IndexedAddress>, Error> {
+        Ok(self.wallet(policy_id).await?.get_addresses().await?)
+    }
+
+    pub async fn get_addresses_range(
         &self,
         policy_id: EventId,
-    ) -> Result<Vec<Address<NetworkUnchecked>>, Error> {
-        Ok(self.wallet(policy_id).await?.get_addresses().await?)
+        from_index: u32,
+        count: usize,
+    ) -> Result<Vec<IndexedAddress>, Error> {
+        Ok(self
+            .wallet(policy_id)
+            .await?
+            .get_addresses_range(from_index, count)
+            .await?)
+    }
+
+    pub async fn is_address_used(&self, policy_id: EventId, index: u32) -> Result<bool, Error> {
+        Ok(self.wallet(policy_id).await?.is_address_used(index).await)
+    }
+
+    pub async fn last_used_address_index(&self, policy_id: EventId) -> Result<Option<u32>, Error> {
+        Ok(self
+            .wallet(policy_id)
+            .await?
+            .last_used_address_index()
+            .await)
     }
 
     pub async fn get_addresses_balances(
@@ -296,6 +552,18 @@ impl Manager {
         Ok(self.wallet(policy_id).await?.get_addresses_balances().await)
     }
 
+    pub async fn get_scripts_balances(
+        &self,
+        policy_id: EventId,
+        scripts: &[ScriptBuf],
+    ) -> Result<HashMap<ScriptBuf, u64>, Error> {
+        Ok(self
+            .wallet(policy_id)
+            .await?
+            .get_scripts_balances(scripts)
+            .await)
+    }
+
     pub async fn get_txs(&self, policy_id: EventId) -> Result<BTreeSet<TransactionDetails>, Error> {
         Ok(self.wallet(policy_id).await?.txs().await)
     }
@@ -313,21 +581,53 @@ impl Manager {
     }
 
     /// Sync all policies with the timechain
-    pub async fn sync_all(
+    pub(crate) async fn sync_all(
         &self,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
-        sync_channel: Option<Sender<Message>>,
+        stop_gap: usize,
+        sync_channel: Option<SyncChannel>,
     ) -> Result<(), Error> {
         let wallets = self.wallets.read().await;
         for (id, wallet) in wallets.clone().into_iter() {
             let endpoint = endpoint.clone();
             let sync_channel = sync_channel.clone();
             thread::spawn(async move {
-                match wallet.full_sync(endpoint, proxy, false).await {
+                match wallet.full_sync(endpoint, proxy, stop_gap, false).await {
+                    Ok(_) => {
+                        if let Some(sync_channel) = sync_channel {
+                            sync_channel.send(Message::WalletSyncCompleted(id));
+                        }
+                    }
+                    Err(WalletError::AlreadySynced) => {}
+                    Err(WalletError::AlreadySyncing) => {
+                        tracing::warn!("Policy {id} is already syncing");
+                    }
+                    Err(e) => tracing::error!("Impossible to sync policy {id}: {e}"),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Sync all policies with the timechain using Bitcoin Core RPC
+    pub(crate) async fn bitcoind_sync_all(
+        &self,
+        url: &Url,
+        auth: BitcoindAuth,
+        force: bool,
+        sync_channel: Option<SyncChannel>,
+    ) -> Result<(), Error> {
+        let client: Arc<BitcoindClient> = Arc::new(bitcoind_client(url, auth)?);
+        let wallets = self.wallets.read().await;
+        for (id, wallet) in wallets.clone().into_iter() {
+            let client = client.clone();
+            let sync_channel = sync_channel.clone();
+            thread::spawn(async move {
+                match wallet.bitcoind_sync(&client, force).await {
                     Ok(_) => {
                         if let Some(sync_channel) = sync_channel {
-                            let _ = sync_channel.send(Message::WalletSyncCompleted(id));
+                            sync_channel.send(Message::WalletSyncCompleted(id));
                         }
                     }
                     Err(WalletError::AlreadySynced) => {}
@@ -354,22 +654,23 @@ impl Manager {
     } */
 
     /// Full sync all policies with the timechain
-    pub async fn full_sync_all(
+    pub(crate) async fn full_sync_all(
         &self,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
+        stop_gap: usize,
         force: bool,
-        sync_channel: Option<Sender<Message>>,
+        sync_channel: Option<SyncChannel>,
     ) -> Result<(), Error> {
         let wallets = self.wallets.read().await;
         for (id, wallet) in wallets.clone().into_iter() {
             let endpoint = endpoint.clone();
             let sync_channel = sync_channel.clone();
             thread::spawn(async move {
-                match wallet.full_sync(endpoint, proxy, force).await {
+                match wallet.full_sync(endpoint, proxy, stop_gap, force).await {
                     Ok(_) => {
                         if let Some(sync_channel) = sync_channel {
-                            let _ = sync_channel.send(Message::WalletSyncCompleted(id));
+                            sync_channel.send(Message::WalletSyncCompleted(id));
                         }
                     }
                     Err(WalletError::AlreadySynced) => {}
@@ -383,18 +684,35 @@ impl Manager {
         Ok(())
     }
 
+    /// Sync a policy with the timechain using Bitcoin Core RPC
+    pub async fn bitcoind_sync(
+        &self,
+        policy_id: EventId,
+        url: &Url,
+        auth: BitcoindAuth,
+        force: bool,
+    ) -> Result<(), Error> {
+        let client: BitcoindClient = bitcoind_client(url, auth)?;
+        Ok(self
+            .wallet(policy_id)
+            .await?
+            .bitcoind_sync(&client, force)
+            .await?)
+    }
+
     /// Execute a **full** timechain sync.
     pub async fn full_sync(
         &self,
         policy_id: EventId,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
+        stop_gap: usize,
         force: bool,
     ) -> Result<(), Error> {
         Ok(self
             .wallet(policy_id)
             .await?
-            .full_sync(endpoint, proxy, force)
+            .full_sync(endpoint, proxy, stop_gap, force)
             .await?)
     }
 
@@ -406,11 +724,19 @@ impl Manager {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
     ) -> Result<Option<usize>, Error> {
         Ok(self
             .wallet(policy_id)
             .await?
-            .estimate_tx_vsize(address, amount, utxos, frozen_utxos, policy_path)
+            .estimate_tx_vsize(
+                address,
+                amount,
+                utxos,
+                frozen_utxos,
+                policy_path,
+                coin_selection,
+            )
             .await)
     }
 
@@ -424,6 +750,7 @@ impl Manager {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
     ) -> Result<Proposal, Error>
     where
         S: Into<String>,
@@ -439,10 +766,29 @@ impl Manager {
                 utxos,
                 frozen_utxos,
                 policy_path,
+                coin_selection,
             )
             .await?)
     }
 
+    pub async fn bump_fee<S>(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+        description: S,
+        fee_rate: FeeRate,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+    ) -> Result<Proposal, Error>
+    where
+        S: Into<String>,
+    {
+        Ok(self
+            .wallet(policy_id)
+            .await?
+            .bump_fee(txid, description, fee_rate, policy_path)
+            .await?)
+    }
+
     pub async fn proof_of_reserve<S>(
         &self,
         policy_id: EventId,
@@ -8,13 +8,17 @@ use std::ops::Deref;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
 
+use bdk_bitcoind_rpc::bitcoincore_rpc::{self, Client as BitcoindClient, RpcApi};
+use bdk_bitcoind_rpc::Emitter;
 use bdk_electrum::electrum_client::{
     Client as ElectrumClient, Config as ElectrumConfig, Socks5Config,
 };
 use bdk_electrum::{ElectrumExt, ElectrumUpdate};
 use nostr_sdk::{EventId, Timestamp};
 use smartvaults_core::bdk::chain::keychain::KeychainTxOutIndex;
-use smartvaults_core::bdk::chain::local_chain::{CannotConnectError, CheckPoint, LocalChain};
+use smartvaults_core::bdk::chain::local_chain::{
+    ApplyHeaderError, CannotConnectError, CheckPoint, LocalChain,
+};
 use smartvaults_core::bdk::chain::{
     BlockId, ConfirmationTime, ConfirmationTimeHeightAnchor, TxGraph,
 };
@@ -23,9 +27,10 @@ use smartvaults_core::bdk::wallet::{AddressIndex, AddressInfo, Balance, Update};
 use smartvaults_core::bdk::{FeeRate, KeychainKind, LocalOutput, Wallet};
 use smartvaults_core::bitcoin::address::NetworkUnchecked;
 use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
-use smartvaults_core::bitcoin::{Address, OutPoint, Script, ScriptBuf, Transaction, Txid};
+use smartvaults_core::bitcoin::{Address, OutPoint, Script, ScriptBuf, Transaction, TxOut, Txid};
 use smartvaults_core::reserves::ProofOfReserves;
-use smartvaults_core::{Amount, Policy, Proposal};
+use smartvaults_core::{Amount, CoinSelection, Policy, Proposal};
+use smartvaults_sdk_sqlite::model::Birthday;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
@@ -33,9 +38,8 @@ mod storage;
 
 pub use self::storage::{Error as StorageError, SmartVaultsWalletStorage};
 use crate::config::ElectrumEndpoint;
-use crate::constants::WALLET_SYNC_INTERVAL;
+use crate::constants::{BIRTHDAY_SAFETY_MARGIN, WALLET_SYNC_INTERVAL};
 
-const STOP_GAP: usize = 50;
 const BATCH_SIZE: usize = 5;
 
 #[derive(Debug, Error)]
@@ -49,8 +53,12 @@ pub enum Error {
     #[error(transparent)]
     Electrum(#[from] bdk_electrum::electrum_client::Error),
     #[error(transparent)]
+    Bitcoind(#[from] bitcoincore_rpc::Error),
+    #[error(transparent)]
     CannotConnect(#[from] CannotConnectError),
     #[error(transparent)]
+    ApplyHeader(#[from] ApplyHeaderError),
+    #[error(transparent)]
     BdkCreateTx(#[from] CreateTxError<StorageError>),
     #[error(transparent)]
     Storage(#[from] StorageError),
@@ -80,6 +88,15 @@ impl PartialEq for Fee {
 
 impl Eq for Fee {}
 
+/// External address with its derivation index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedAddress {
+    pub index: u32,
+    pub address: Address<NetworkUnchecked>,
+    /// Received funds (according to the last timechain sync)
+    pub used: bool,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TransactionDetails {
     pub transaction: Transaction,
@@ -131,6 +148,35 @@ impl TransactionDetails {
     }
 }
 
+/// Height of the last block with median time past before `timestamp` (the median time past is
+/// monotonic, unlike the block time)
+pub fn height_before(client: &BitcoindClient, timestamp: Timestamp) -> Result<u32, Error> {
+    let tip: u64 = client.get_block_count()?;
+    search_height_before(tip, timestamp, |height| {
+        let hash = client.get_block_hash(height)?;
+        let header = client.get_block_header_info(&hash)?;
+        Ok(header.median_time.unwrap_or(header.time) as u64)
+    })
+}
+
+/// Binary search of the last height, up to `tip`, with `median_time` before `timestamp` (`0` if none)
+fn search_height_before<F>(tip: u64, timestamp: Timestamp, mut median_time: F) -> Result<u32, Error>
+where
+    F: FnMut(u64) -> Result<u64, Error>,
+{
+    let mut low: u64 = 0;
+    let mut high: u64 = tip;
+    while low < high {
+        let mid: u64 = (low + high + 1) / 2;
+        if median_time(mid)? < timestamp.as_u64() {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low as u32)
+}
+
 #[derive(Debug, Clone)]
 pub struct SmartVaultsWallet {
     id: EventId,
@@ -138,6 +184,7 @@ pub struct SmartVaultsWallet {
     wallet: Arc<RwLock<Wallet<SmartVaultsWalletStorage>>>,
     syncing: Arc<AtomicBool>,
     last_sync: Arc<AtomicU64>,
+    birthday: Arc<RwLock<Birthday>>,
 }
 
 impl SmartVaultsWallet {
@@ -145,6 +192,7 @@ impl SmartVaultsWallet {
         policy_id: EventId,
         policy: Policy,
         wallet: Wallet<SmartVaultsWalletStorage>,
+        birthday: Birthday,
     ) -> Self {
         Self {
             id: policy_id,
@@ -152,9 +200,19 @@ impl SmartVaultsWallet {
             wallet: Arc::new(RwLock::new(wallet)),
             syncing: Arc::new(AtomicBool::new(false)),
             last_sync: Arc::new(AtomicU64::new(0)),
+            birthday: Arc::new(RwLock::new(birthday)),
         }
     }
 
+    pub async fn birthday(&self) -> Birthday {
+        *self.birthday.read().await
+    }
+
+    pub async fn set_birthday(&self, birthday: Birthday) {
+        let mut b = self.birthday.write().await;
+        *b = birthday;
+    }
+
     fn is_syncing(&self) -> bool {
         self.syncing.load(AtomicOrdering::SeqCst)
     }
@@ -221,10 +279,39 @@ impl SmartVaultsWallet {
         Ok(res)
     }
 
+    #[cfg(test)]
+    pub async fn insert_checkpoint(&self, block_id: BlockId) -> Result<bool, Error> {
+        let mut wallet = self.wallet.write().await;
+        let res = wallet
+            .insert_checkpoint(block_id)
+            .map_err(|e| Error::InsertTx(format!("{e:?}")))?;
+        wallet.commit()?;
+        Ok(res)
+    }
+
     pub async fn is_mine(&self, script: &Script) -> bool {
         self.wallet.read().await.is_mine(script)
     }
 
+    /// Get the keychain that derived the script (`None` if not ours)
+    pub async fn keychain_of(&self, script: &Script) -> Option<KeychainKind> {
+        self.wallet
+            .read()
+            .await
+            .derivation_of_spk(script)
+            .map(|(keychain, _)| keychain)
+    }
+
+    /// Get a [`TxOut`] known by the tx graph
+    pub async fn get_txout(&self, outpoint: OutPoint) -> Option<TxOut> {
+        self.wallet
+            .read()
+            .await
+            .tx_graph()
+            .get_txout(outpoint)
+            .cloned()
+    }
+
     pub async fn get_balance(&self) -> Balance {
         self.wallet.read().await.get_balance()
     }
@@ -234,8 +321,20 @@ impl SmartVaultsWallet {
         Ok(wallet.try_get_address(index)?)
     }
 
+    /// Check if the external address at `index` received funds
+    pub async fn is_address_used(&self, index: u32) -> bool {
+        let wallet = self.wallet.read().await;
+        wallet.spk_index().is_used(&(KeychainKind::External, index))
+    }
+
+    /// Index of the last external address that received funds
+    pub async fn last_used_address_index(&self) -> Option<u32> {
+        let wallet = self.wallet.read().await;
+        wallet.spk_index().last_used_index(&KeychainKind::External)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
-    pub async fn get_addresses(&self) -> Result<Vec<Address<NetworkUnchecked>>, Error> {
+    pub async fn get_addresses(&self) -> Result<Vec<IndexedAddress>, Error> {
         // Get last unused address
         let last_unused = self.get_address(AddressIndex::LastUnused).await?;
 
@@ -243,16 +342,17 @@ impl SmartVaultsWallet {
         let wallet = self.wallet.read().await;
         let network = wallet.network();
         let spks = wallet.unbounded_spk_iter(KeychainKind::External);
-        drop(wallet);
 
-        let mut addresses: Vec<Address<NetworkUnchecked>> = Vec::new();
+        let mut addresses: Vec<IndexedAddress> = Vec::new();
         let mut counter: Option<u8> = None;
 
-        for (_index, script) in spks {
+        for (index, script) in spks {
             let addr: Address = Address::from_script(&script, network)?;
-            let addr_unchecked: Address<NetworkUnchecked> =
-                Address::new(network, addr.payload.clone());
-            addresses.push(addr_unchecked);
+            addresses.push(IndexedAddress {
+                index,
+                address: Address::new(network, addr.payload.clone()),
+                used: wallet.spk_index().is_used(&(KeychainKind::External, index)),
+            });
 
             if addr == last_unused.address {
                 counter = Some(0);
@@ -270,6 +370,33 @@ impl SmartVaultsWallet {
         Ok(addresses)
     }
 
+    /// Derive `count` external addresses starting at `from_index`
+    ///
+    /// The addresses are not revealed: the ones beyond the gap limit are not scanned by the
+    /// timechain sync.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_addresses_range(
+        &self,
+        from_index: u32,
+        count: usize,
+    ) -> Result<Vec<IndexedAddress>, Error> {
+        let wallet = self.wallet.read().await;
+        let network = wallet.network();
+        wallet
+            .unbounded_spk_iter(KeychainKind::External)
+            .skip(from_index as usize)
+            .take(count)
+            .map(|(index, script)| -> Result<IndexedAddress, Error> {
+                let addr: Address = Address::from_script(&script, network)?;
+                Ok(IndexedAddress {
+                    index,
+                    address: Address::new(network, addr.payload),
+                    used: wallet.spk_index().is_used(&(KeychainKind::External, index)),
+                })
+            })
+            .collect()
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn get_addresses_balances(&self) -> HashMap<ScriptBuf, u64> {
         let mut map: HashMap<ScriptBuf, u64> = HashMap::new();
@@ -283,6 +410,20 @@ impl SmartVaultsWallet {
         map
     }
 
+    /// Get balances of the passed scripts (`0` if not funded)
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn get_scripts_balances(&self, scripts: &[ScriptBuf]) -> HashMap<ScriptBuf, u64> {
+        let mut map: HashMap<ScriptBuf, u64> = scripts.iter().map(|s| (s.clone(), 0)).collect();
+
+        for utxo in self.wallet.read().await.list_unspent() {
+            if let Some(amount) = map.get_mut(&utxo.txout.script_pubkey) {
+                *amount += utxo.txout.value;
+            }
+        }
+
+        map
+    }
+
     /// Get wallet TXs
     pub async fn txs(&self) -> BTreeSet<TransactionDetails> {
         let wallet = self.wallet.read().await;
@@ -334,6 +475,7 @@ impl SmartVaultsWallet {
         &self,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
+        stop_gap: usize,
         prev_tip: CheckPoint,
         graph: TxGraph<ConfirmationTimeHeightAnchor>,
     ) -> Result<(), Error> {
@@ -356,7 +498,7 @@ impl SmartVaultsWallet {
                 relevant_txids,
             },
             keychain_update,
-        ) = client.full_scan(prev_tip, keychain_spks, STOP_GAP, BATCH_SIZE)?;
+        ) = client.full_scan(prev_tip, keychain_spks, stop_gap, BATCH_SIZE)?;
         let missing: Vec<Txid> = relevant_txids.missing_full_txs(&graph);
         let graph_update =
             relevant_txids.into_confirmation_time_tx_graph(&client, None, missing)?;
@@ -373,10 +515,13 @@ impl SmartVaultsWallet {
     }
 
     /// Execute a full timechain sync.
+    ///
+    /// The scan stops after `stop_gap` consecutive unused addresses.
     pub async fn full_sync(
         &self,
         endpoint: ElectrumEndpoint,
         proxy: Option<SocketAddr>,
+        stop_gap: usize,
         force: bool,
     ) -> Result<(), Error> {
         if !force {
@@ -411,7 +556,7 @@ impl SmartVaultsWallet {
         };
 
         // Sync
-        self.internal_full_sync(endpoint, proxy, prev_tip, graph)
+        self.internal_full_sync(endpoint, proxy, stop_gap, prev_tip, graph)
             .await?;
 
         // Update sync timestamp and status
@@ -427,6 +572,99 @@ impl SmartVaultsWallet {
         Ok(())
     }
 
+    /// Height to start emitting the blocks from: the earliest of the user-supplied height and of
+    /// the last block mined before the policy creation (`0` if unknown)
+    async fn birthday_height(&self, client: &BitcoindClient) -> u32 {
+        let Birthday { timestamp, height } = self.birthday().await;
+        let from_timestamp: Option<u32> = match timestamp {
+            Some(timestamp) => match height_before(client, timestamp - BIRTHDAY_SAFETY_MARGIN) {
+                Ok(height) => Some(height),
+                Err(e) => {
+                    tracing::warn!(
+                        "Impossible to get birthday height of policy {}: {e}",
+                        self.id
+                    );
+                    return 0;
+                }
+            },
+            None => None,
+        };
+        match (from_timestamp, height) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(h), None) | (None, Some(h)) => h,
+            (None, None) => 0,
+        }
+    }
+
+    async fn internal_bitcoind_sync(
+        &self,
+        client: &BitcoindClient,
+        prev_tip: CheckPoint,
+        start_height: u32,
+    ) -> Result<(), Error> {
+        // Descriptors are not imported into bitcoind: blocks are fetched and filtered locally,
+        // starting from the policy birthday at first load.
+        let mut emitter = Emitter::new(client, prev_tip, start_height);
+
+        while let Some(event) = emitter.next_block()? {
+            let height: u32 = event.block_height();
+            let mut wallet = self.wallet.write().await;
+            wallet.apply_block_connected_to(&event.block, height, event.connected_to())?;
+            wallet.commit()?;
+        }
+
+        let mempool: Vec<(Transaction, u64)> = emitter.mempool()?;
+        let mut wallet = self.wallet.write().await;
+        wallet.apply_unconfirmed_txs(mempool.iter().map(|(tx, time)| (tx, *time)));
+        wallet.commit()?;
+
+        Ok(())
+    }
+
+    /// Execute a timechain sync using Bitcoin Core RPC
+    ///
+    /// Only the blocks after the last checkpoint are fetched, so the sync isn't throttled
+    /// (see [`WALLET_SYNC_INTERVAL`]).
+    pub async fn bitcoind_sync(&self, client: &BitcoindClient, force: bool) -> Result<(), Error> {
+        if !force && self.is_syncing() {
+            return Err(Error::AlreadySyncing);
+        }
+
+        self.set_syncing(true);
+
+        tracing::debug!("Syncing policy {} [bitcoind]", self.id);
+
+        // Re-emit all the blocks if forced
+        let prev_tip: CheckPoint = self.latest_checkpoint().await;
+        let prev_tip: CheckPoint = if force {
+            prev_tip.iter().last().unwrap_or(prev_tip)
+        } else {
+            prev_tip
+        };
+
+        // The blocks before the birthday can't contain policy txs
+        let start_height: u32 = if force || self.is_chain_empty().await {
+            self.birthday_height(client).await
+        } else {
+            0
+        };
+        if start_height > 0 {
+            tracing::debug!("Policy {} birthday at height {start_height}", self.id);
+        }
+
+        let res = self
+            .internal_bitcoind_sync(client, prev_tip, start_height)
+            .await;
+        self.set_syncing(false);
+        res?;
+
+        self.update_last_sync();
+
+        tracing::info!("Policy {} synced [bitcoind]", self.id);
+
+        Ok(())
+    }
+
     /* pub async fn sync(
         &self,
         endpoint: ElectrumEndpoint,
@@ -516,6 +754,7 @@ impl SmartVaultsWallet {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
     ) -> Option<usize> {
         let mut wallet = self.wallet.write().await;
         self.policy.estimate_tx_vsize(
@@ -525,6 +764,7 @@ impl SmartVaultsWallet {
             utxos,
             frozen_utxos,
             policy_path,
+            coin_selection,
         )
     }
 
@@ -537,6 +777,7 @@ impl SmartVaultsWallet {
         utxos: Option<Vec<OutPoint>>,
         frozen_utxos: Option<Vec<OutPoint>>,
         policy_path: Option<BTreeMap<String, Vec<usize>>>,
+        coin_selection: CoinSelection,
     ) -> Result<Proposal, Error>
     where
         S: Into<String>,
@@ -551,10 +792,28 @@ impl SmartVaultsWallet {
             utxos,
             frozen_utxos,
             policy_path,
+            coin_selection,
         )?;
         Ok(proposal)
     }
 
+    pub async fn bump_fee<S>(
+        &self,
+        txid: Txid,
+        description: S,
+        fee_rate: FeeRate,
+        policy_path: Option<BTreeMap<String, Vec<usize>>>,
+    ) -> Result<Proposal, Error>
+    where
+        S: Into<String>,
+    {
+        let mut wallet = self.wallet.write().await;
+        let proposal =
+            self.policy
+                .bump_fee(&mut wallet, txid, description, fee_rate, policy_path)?;
+        Ok(proposal)
+    }
+
     pub async fn proof_of_reserve<S>(&self, message: S) -> Result<Proposal, Error>
     where
         S: Into<String>,
@@ -575,3 +834,54 @@ impl SmartVaultsWallet {
         Ok(self.wallet.read().await.verify_proof(psbt, message, None)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search over a chain of median times past, counting the queried heights
+    fn search(median_times: &[u64], timestamp: u64) -> (u32, usize) {
+        let mut queries: usize = 0;
+        let height = search_height_before(
+            median_times.len() as u64 - 1,
+            Timestamp::from(timestamp),
+            |height| {
+                queries += 1;
+                Ok(median_times[height as usize])
+            },
+        )
+        .unwrap();
+        (height, queries)
+    }
+
+    #[test]
+    fn test_search_height_before() {
+        // A block every 10 minutes
+        let median_times: Vec<u64> = (0..1_000).map(|h| 1_700_000_000 + h * 600).collect();
+
+        // Last block strictly before the timestamp
+        assert_eq!(search(&median_times, 1_700_000_000 + 500 * 600).0, 499);
+        assert_eq!(search(&median_times, 1_700_000_000 + 500 * 600 + 1).0, 500);
+
+        // Before the genesis and after the tip
+        assert_eq!(search(&median_times, 1_600_000_000).0, 0);
+        assert_eq!(search(&median_times, 1_800_000_000).0, 999);
+
+        // Logarithmic number of queries
+        assert!(search(&median_times, 1_700_123_456).1 <= 10);
+
+        // Only the genesis: nothing to query
+        assert_eq!(search(&median_times[..1], 1_800_000_000), (0, 0));
+    }
+
+    #[test]
+    fn test_search_height_before_with_equal_median_times() {
+        // The median time past is monotonic, but not strictly
+        let median_times: Vec<u64> = vec![0, 10, 10, 10, 20, 20, 30];
+        assert_eq!(search(&median_times, 10).0, 0);
+        assert_eq!(search(&median_times, 11).0, 3);
+        assert_eq!(search(&median_times, 20).0, 3);
+        assert_eq!(search(&median_times, 25).0, 5);
+        assert_eq!(search(&median_times, 31).0, 6);
+    }
+}
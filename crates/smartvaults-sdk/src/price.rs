@@ -0,0 +1,242 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Fiat prices
+//!
+//! Used only to show the balances in fiat: a missing or stale price never blocks anything.
+
+use core::fmt;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use nostr_sdk::Url;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::constants::PRICE_REQUEST_TIMEOUT;
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("unknown fiat currency: {0}")]
+    UnknownCurrency(String),
+    #[error("unsupported price response")]
+    UnsupportedResponse,
+}
+
+/// Fiat currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FiatCurrency {
+    Usd,
+    Eur,
+}
+
+impl fmt::Display for FiatCurrency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usd => write!(f, "USD"),
+            Self::Eur => write!(f, "EUR"),
+        }
+    }
+}
+
+impl FromStr for FiatCurrency {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "usd" => Ok(Self::Usd),
+            "eur" => Ok(Self::Eur),
+            _ => Err(Error::UnknownCurrency(s.to_string())),
+        }
+    }
+}
+
+impl FiatCurrency {
+    pub const ALL: [Self; 2] = [Self::Usd, Self::Eur];
+}
+
+/// BTC prices
+pub type Prices = HashMap<FiatCurrency, f64>;
+
+/// Amount converted to fiat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiatAmount {
+    pub currency: FiatCurrency,
+    /// BTC price used for the conversion
+    pub price: f64,
+    pub value: f64,
+}
+
+impl fmt::Display for FiatAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} {}", self.value, self.currency)
+    }
+}
+
+impl FiatAmount {
+    pub fn from_sats(sats: u64, currency: FiatCurrency, price: f64) -> Self {
+        Self {
+            currency,
+            price,
+            value: sats as f64 / SATS_PER_BTC * price,
+        }
+    }
+}
+
+/// Price provider
+pub trait PriceProvider: fmt::Debug + Send + Sync {
+    /// Fetch the BTC price in the supported currencies
+    fn fetch(&self) -> BoxFuture<'_, Result<Prices, Error>>;
+}
+
+/// HTTP price provider
+///
+/// Supports the response formats of both mempool.space (`/api/v1/prices`) and coingecko
+/// (`/api/v3/simple/price?ids=bitcoin&vs_currencies=usd,eur`).
+#[derive(Debug, Clone)]
+pub struct HttpPriceProvider {
+    endpoint: Url,
+    proxy: Option<SocketAddr>,
+}
+
+impl HttpPriceProvider {
+    pub fn new(endpoint: Url, proxy: Option<SocketAddr>) -> Self {
+        Self { endpoint, proxy }
+    }
+
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    async fn get(&self) -> Result<Prices, Error> {
+        let mut builder = reqwest::Client::builder().timeout(PRICE_REQUEST_TIMEOUT);
+        if let Some(proxy) = self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(format!("socks5h://{proxy}"))?);
+        }
+        let value: Value = builder
+            .build()?
+            .get(self.endpoint.as_str())
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        parse_prices(&value)
+    }
+}
+
+impl PriceProvider for HttpPriceProvider {
+    fn fetch(&self) -> BoxFuture<'_, Result<Prices, Error>> {
+        Box::pin(self.get())
+    }
+}
+
+fn parse_prices(value: &Value) -> Result<Prices, Error> {
+    let mut prices = Prices::new();
+    for currency in FiatCurrency::ALL.into_iter() {
+        let code: String = currency.to_string();
+        let price: Option<f64> = value
+            .get(&code)
+            .or_else(|| value.get("bitcoin")?.get(code.to_lowercase()))
+            .and_then(Value::as_f64);
+        if let Some(price) = price.filter(|p| *p > 0.0) {
+            prices.insert(currency, price);
+        }
+    }
+
+    if prices.is_empty() {
+        return Err(Error::UnsupportedResponse);
+    }
+
+    Ok(prices)
+}
+
+/// Prices cache
+///
+/// The prices older than the TTL are not returned.
+#[derive(Debug, Clone)]
+pub(crate) struct PriceCache {
+    ttl: Duration,
+    prices: Arc<RwLock<Option<(Prices, Instant)>>>,
+}
+
+impl PriceCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            prices: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn get(&self, currency: FiatCurrency) -> Option<f64> {
+        let prices = self.prices.read();
+        let (prices, updated_at) = prices.as_ref()?;
+        if updated_at.elapsed() > self.ttl {
+            return None;
+        }
+        prices.get(&currency).copied()
+    }
+
+    pub fn update(&self, prices: Prices) {
+        let mut e = self.prices.write();
+        *e = Some((prices, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_prices() {
+        // mempool.space
+        let value: Value =
+            serde_json::from_str(r#"{"time":1703252411,"USD":43753,"EUR":40545,"GBP":37528}"#)
+                .unwrap();
+        let prices = parse_prices(&value).unwrap();
+        assert_eq!(prices.get(&FiatCurrency::Usd), Some(&43753.0));
+        assert_eq!(prices.get(&FiatCurrency::Eur), Some(&40545.0));
+
+        // coingecko
+        let value: Value =
+            serde_json::from_str(r#"{"bitcoin":{"usd":43753.5,"eur":40545.1}}"#).unwrap();
+        let prices = parse_prices(&value).unwrap();
+        assert_eq!(prices.get(&FiatCurrency::Usd), Some(&43753.5));
+
+        let value: Value = serde_json::from_str(r#"{"error":"rate limited"}"#).unwrap();
+        assert!(matches!(
+            parse_prices(&value),
+            Err(Error::UnsupportedResponse)
+        ));
+    }
+
+    #[test]
+    fn test_fiat_amount() {
+        let amount = FiatAmount::from_sats(150_000, FiatCurrency::Usd, 40_000.0);
+        assert_eq!(amount.to_string(), "60.00 USD");
+    }
+
+    #[test]
+    fn test_price_cache_ttl() {
+        let cache = PriceCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get(FiatCurrency::Usd), None);
+        cache.update(Prices::from([(FiatCurrency::Usd, 40_000.0)]));
+        assert_eq!(cache.get(FiatCurrency::Usd), Some(40_000.0));
+        assert_eq!(cache.get(FiatCurrency::Eur), None);
+
+        let cache = PriceCache::new(Duration::ZERO);
+        cache.update(Prices::from([(FiatCurrency::Usd, 40_000.0)]));
+        std::thread::sleep(Duration::from_millis(1));
+        assert_eq!(cache.get(FiatCurrency::Usd), None);
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Address and descriptor screening
+//!
+//! All the screening is local: no network calls are made by the providers of this module.
+
+use core::fmt;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::address::NetworkUnchecked;
+use smartvaults_core::bitcoin::{Address, Network, Script, ScriptBuf};
+use smartvaults_core::miniscript::{Descriptor, DescriptorPublicKey};
+
+/// Number of addresses derived from the ranged descriptors of the screening list
+const DESCRIPTOR_DERIVATION_RANGE: u32 = 1000;
+
+/// Screening result
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ScreeningResult {
+    #[default]
+    Clear,
+    Flagged {
+        reason: Option<String>,
+    },
+}
+
+impl fmt::Display for ScreeningResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Clear => write!(f, "clear"),
+            Self::Flagged {
+                reason: Some(reason),
+            } => write!(f, "flagged ({reason})"),
+            Self::Flagged { reason: None } => write!(f, "flagged"),
+        }
+    }
+}
+
+impl ScreeningResult {
+    pub fn is_flagged(&self) -> bool {
+        matches!(self, Self::Flagged { .. })
+    }
+}
+
+/// What to do when a proposal to approve pays a flagged address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreeningAction {
+    #[default]
+    Warn,
+    Block,
+}
+
+impl fmt::Display for ScreeningAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warn => write!(f, "warn"),
+            Self::Block => write!(f, "block"),
+        }
+    }
+}
+
+/// Screening provider
+pub trait ScreeningProvider: fmt::Debug + Send + Sync {
+    /// Screen an output script
+    fn screen(&self, script: &Script) -> ScreeningResult;
+}
+
+/// Default provider: nothing is flagged
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoScreening;
+
+impl ScreeningProvider for NoScreening {
+    fn screen(&self, _script: &Script) -> ScreeningResult {
+        ScreeningResult::Clear
+    }
+}
+
+#[derive(Debug, Default)]
+struct ScreeningList {
+    modified: Option<SystemTime>,
+    entries: HashMap<ScriptBuf, Option<String>>,
+}
+
+/// File-based screening provider
+///
+/// One entry per line: an address, a descriptor or an hex script, optionally followed by the reason.
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// The file is reloaded when its modification time changes.
+#[derive(Debug)]
+pub struct FileScreeningProvider {
+    path: PathBuf,
+    network: Network,
+    list: RwLock<ScreeningList>,
+}
+
+impl FileScreeningProvider {
+    pub fn new<P>(path: P, network: Network) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let provider = Self {
+            path: path.as_ref().to_path_buf(),
+            network,
+            list: RwLock::new(ScreeningList::default()),
+        };
+        provider.reload_if_changed();
+        provider
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn reload_if_changed(&self) {
+        let modified: Option<SystemTime> = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.modified().ok(),
+            Err(e) => {
+                tracing::error!(
+                    "Impossible to read screening list {}: {e}",
+                    self.path.display()
+                );
+                return;
+            }
+        };
+
+        if modified.is_some() && self.list.read().modified == modified {
+            return;
+        }
+
+        match fs::read_to_string(&self.path) {
+            Ok(content) => {
+                let entries = parse_screening_list(&content, self.network);
+                tracing::info!(
+                    "Loaded {} scripts from screening list {}",
+                    entries.len(),
+                    self.path.display()
+                );
+                let mut list = self.list.write();
+                list.modified = modified;
+                list.entries = entries;
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Impossible to read screening list {}: {e}",
+                    self.path.display()
+                )
+            }
+        }
+    }
+}
+
+impl ScreeningProvider for FileScreeningProvider {
+    fn screen(&self, script: &Script) -> ScreeningResult {
+        self.reload_if_changed();
+        match self.list.read().entries.get(script) {
+            Some(reason) => ScreeningResult::Flagged {
+                reason: reason.clone(),
+            },
+            None => ScreeningResult::Clear,
+        }
+    }
+}
+
+fn parse_screening_list(content: &str, network: Network) -> HashMap<ScriptBuf, Option<String>> {
+    let mut entries = HashMap::new();
+    for line in content.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (entry, reason) = match line.split_once(char::is_whitespace) {
+            Some((entry, reason)) => (entry, Some(reason.trim().to_string())),
+            None => (line, None),
+        };
+
+        match parse_entry(entry, network) {
+            Some(scripts) => {
+                for script in scripts.into_iter() {
+                    entries.insert(script, reason.clone());
+                }
+            }
+            None => tracing::warn!("Invalid screening list entry: {entry}"),
+        }
+    }
+    entries
+}
+
+fn parse_entry(entry: &str, network: Network) -> Option<Vec<ScriptBuf>> {
+    if let Ok(address) = Address::<NetworkUnchecked>::from_str(entry) {
+        return Some(vec![address.require_network(network).ok()?.script_pubkey()]);
+    }
+
+    if let Ok(descriptor) = Descriptor::<DescriptorPublicKey>::from_str(entry) {
+        let range: u32 = if descriptor.has_wildcard() {
+            DESCRIPTOR_DERIVATION_RANGE
+        } else {
+            1
+        };
+        return (0..range)
+            .map(|index| {
+                descriptor
+                    .at_derivation_index(index)
+                    .ok()
+                    .map(|d| d.script_pubkey())
+            })
+            .collect();
+    }
+
+    ScriptBuf::from_hex(entry).ok().map(|script| vec![script])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETWORK: Network = Network::Testnet;
+
+    #[test]
+    fn test_file_screening_provider() {
+        let path = std::env::temp_dir().join("smartvaults-screening-test.txt");
+        fs::write(
+            &path,
+            "# Blocklist\n\ntb1q7zqqsmqx5ymhd7qn73lm96w5yqdkrmx7ts8ang Sanctioned\n",
+        )
+        .unwrap();
+
+        let provider = FileScreeningProvider::new(&path, NETWORK);
+
+        let flagged = Address::from_str("tb1q7zqqsmqx5ymhd7qn73lm96w5yqdkrmx7ts8ang")
+            .unwrap()
+            .assume_checked();
+        let clear = Address::from_str("tb1q4vwy7ntfyqtcs6gxq3vwdp7ycxuc5wjxm7d9gq")
+            .unwrap()
+            .assume_checked();
+
+        assert_eq!(
+            provider.screen(&flagged.script_pubkey()),
+            ScreeningResult::Flagged {
+                reason: Some(String::from("Sanctioned"))
+            }
+        );
+        assert_eq!(
+            provider.screen(&clear.script_pubkey()),
+            ScreeningResult::Clear
+        );
+
+        // Descriptor entry
+        let desc = "tr([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*)";
+        fs::write(&path, format!("{desc}\n")).unwrap();
+        // Force reload
+        provider.list.write().modified = None;
+
+        let descriptor = Descriptor::<DescriptorPublicKey>::from_str(desc).unwrap();
+        let script = descriptor.at_derivation_index(42).unwrap().script_pubkey();
+        assert!(provider.screen(&script).is_flagged());
+        assert!(!provider.screen(&flagged.script_pubkey()).is_flagged());
+
+        fs::remove_file(path).unwrap();
+    }
+}
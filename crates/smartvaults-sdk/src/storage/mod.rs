@@ -11,14 +11,16 @@ use nostr_sdk::prelude::*;
 use smartvaults_core::bitcoin::{Network, OutPoint, ScriptBuf, Txid};
 use smartvaults_core::miniscript::{Descriptor, DescriptorPublicKey};
 use smartvaults_core::{
-    ApprovedProposal, CompletedProposal, Policy, Proposal, SharedSigner, Signer,
+    ApprovedProposal, CompletedProposal, FeeWarning, Policy, Proposal, SharedSigner, Signer,
 };
 use smartvaults_protocol::v1::constants::{
     APPROVED_PROPOSAL_KIND, COMPLETED_PROPOSAL_KIND, KEY_AGENT_VERIFIED, LABELS_KIND, POLICY_KIND,
     PROPOSAL_KIND, SHARED_KEY_KIND, SHARED_SIGNERS_KIND, SIGNERS_KIND,
     SMARTVAULTS_MAINNET_PUBLIC_KEY, SMARTVAULTS_TESTNET_PUBLIC_KEY,
 };
-use smartvaults_protocol::v1::{Encryption, Label, LabelData, LabelKind, Serde, VerifiedKeyAgents};
+use smartvaults_protocol::v1::{
+    decrypt_from, Encryption, Label, LabelData, LabelKind, Serde, VerifiedKeyAgents,
+};
 use tokio::sync::RwLock;
 
 mod model;
@@ -27,8 +29,8 @@ pub(crate) use self::model::{
     InternalApproval, InternalCompletedProposal, InternalLabel, InternalPolicy, InternalProposal,
     InternalSharedSigner,
 };
-use crate::types::GetApprovedProposals;
-use crate::{Error, EventHandled};
+use crate::types::{ApprovalOrderKey, GetApprovedProposals};
+use crate::{util, Error, EventHandled};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct WrappedEvent {
@@ -56,9 +58,14 @@ impl Ord for WrappedEvent {
 pub(crate) struct SmartVaultsStorage {
     keys: Keys,
     database: Arc<DynNostrDatabase>,
+    network: Network,
     shared_keys: Arc<RwLock<HashMap<EventId, Keys>>>,
+    /// Policies replaced by a shared key rotation
+    rotated: Arc<RwLock<HashSet<EventId>>>,
     vaults: Arc<RwLock<HashMap<EventId, InternalPolicy>>>,
     proposals: Arc<RwLock<HashMap<EventId, InternalProposal>>>,
+    fee_warnings: Arc<RwLock<HashMap<EventId, FeeWarning>>>,
+    ready_to_finalize: Arc<RwLock<HashSet<EventId>>>,
     approvals: Arc<RwLock<HashMap<EventId, InternalApproval>>>,
     completed_proposals: Arc<RwLock<HashMap<EventId, InternalCompletedProposal>>>,
     signers: Arc<RwLock<HashMap<EventId, Signer>>>,
@@ -66,24 +73,35 @@ pub(crate) struct SmartVaultsStorage {
     shared_signers: Arc<RwLock<HashMap<EventId, InternalSharedSigner>>>,
     labels: Arc<RwLock<HashMap<String, InternalLabel>>>,
     frozed_utxos: Arc<RwLock<HashMap<EventId, HashSet<OutPoint>>>>,
+    replacements: Arc<RwLock<HashMap<EventId, (EventId, EventId, Txid)>>>, /* Policy ID, Replacement proposal ID, Replacement txid */
     verified_key_agents: Arc<RwLock<VerifiedKeyAgents>>,
     pending: Arc<RwLock<BTreeSet<Event>>>,
 }
 
 impl SmartVaultsStorage {
     /// Build storage from Nostr Database
+    ///
+    /// The `quarantined` events are skipped. The other ones were already handled once by the sync,
+    /// which marks them as `processing`: no need to mark them again here.
     #[tracing::instrument(skip_all)]
     pub async fn build(
         keys: Keys,
         database: Arc<DynNostrDatabase>,
         network: Network,
+        quarantined: &HashSet<EventId>,
+        rotated_shared_keys: HashMap<EventId, Keys>,
     ) -> Result<Self, Error> {
         let this: Self = Self {
             keys,
             database,
-            shared_keys: Arc::new(RwLock::new(HashMap::new())),
+            network,
+            rotated: Arc::new(RwLock::new(rotated_shared_keys.keys().copied().collect())),
+            // The events of the rotated shared keys are deleted
+            shared_keys: Arc::new(RwLock::new(rotated_shared_keys)),
             vaults: Arc::new(RwLock::new(HashMap::new())),
             proposals: Arc::new(RwLock::new(HashMap::new())),
+            fee_warnings: Arc::new(RwLock::new(HashMap::new())),
+            ready_to_finalize: Arc::new(RwLock::new(HashSet::new())),
             approvals: Arc::new(RwLock::new(HashMap::new())),
             completed_proposals: Arc::new(RwLock::new(HashMap::new())),
             signers: Arc::new(RwLock::new(HashMap::new())),
@@ -91,6 +109,7 @@ impl SmartVaultsStorage {
             shared_signers: Arc::new(RwLock::new(HashMap::new())),
             labels: Arc::new(RwLock::new(HashMap::new())),
             frozed_utxos: Arc::new(RwLock::new(HashMap::new())),
+            replacements: Arc::new(RwLock::new(HashMap::new())),
             verified_key_agents: Arc::new(RwLock::new(VerifiedKeyAgents::empty(network))),
             pending: Arc::new(RwLock::new(BTreeSet::new())),
         };
@@ -128,6 +147,7 @@ impl SmartVaultsStorage {
             .query(vec![author_filter, pubkey_filter, smartvaults], Order::Asc)
             .await?
             .into_iter()
+            .filter(|e| !quarantined.contains(&e.id))
         {
             if let Err(e) = this.internal_handle_event(&mut pending, &event).await {
                 tracing::error!("Impossible to handle event: {e}");
@@ -169,13 +189,17 @@ impl SmartVaultsStorage {
             let mut shared_keys = self.shared_keys.write().await;
             if let HashMapEntry::Vacant(e) = shared_keys.entry(policy_id) {
                 let content =
-                    nip04::decrypt(self.keys.secret_key()?, event.author_ref(), &event.content)?;
+                    decrypt_from(self.keys.secret_key()?, event.author_ref(), &event.content)?;
                 let sk = SecretKey::from_str(&content)?;
                 let shared_key = Keys::new(sk);
                 e.insert(shared_key);
                 return Ok(Some(EventHandled::SharedKey(event.id)));
             }
         } else if event.kind == POLICY_KIND {
+            if self.rotated.read().await.contains(&event.id) {
+                return Ok(None);
+            }
+
             let shared_keys = self.shared_keys.read().await;
             let mut vaults = self.vaults.write().await;
             if let HashMapEntry::Vacant(e) = vaults.entry(event.id) {
@@ -209,6 +233,7 @@ impl SmartVaultsStorage {
                         // Decrypt proposal
                         let proposal: Proposal =
                             Proposal::decrypt_with_keys(shared_key, &event.content)?;
+                        util::network::check_proposal_network(&proposal, self.network)?;
 
                         // Froze UTXOs
                         let psbt = proposal.psbt();
@@ -221,6 +246,17 @@ impl SmartVaultsStorage {
                         )
                         .await;
 
+                        // Fee bump: the second event tag references the replaced proposal
+                        if let Some(original_id) = event.event_ids().nth(1) {
+                            self.save_replacement(
+                                *original_id,
+                                *policy_id,
+                                event.id,
+                                psbt.unsigned_tx.txid(),
+                            )
+                            .await;
+                        }
+
                         // Insert proposal
                         e.insert(InternalProposal {
                             policy_id: *policy_id,
@@ -253,7 +289,10 @@ impl SmartVaultsStorage {
                                 approval: approved_proposal,
                                 timestamp: event.created_at,
                             });
-                            return Ok(Some(EventHandled::Approval { proposal_id }));
+                            return Ok(Some(EventHandled::Approval {
+                                proposal_id,
+                                approval_id: event.id,
+                            }));
                         } else {
                             pending.insert(event.clone());
                         }
@@ -318,17 +357,41 @@ impl SmartVaultsStorage {
                 }
             } else {
                 let mut shared_signers = self.shared_signers.write().await;
-                if let HashMapEntry::Vacant(e) = shared_signers.entry(event.id) {
+                if !shared_signers.contains_key(&event.id) {
                     let shared_signer: String = nip04::decrypt(
                         self.keys.secret_key()?,
                         event.author_ref(),
                         &event.content,
                     )?;
                     let shared_signer: SharedSigner = SharedSigner::from_json(shared_signer)?;
-                    e.insert(InternalSharedSigner {
-                        owner_public_key: event.author(),
-                        shared_signer,
-                    });
+
+                    // The newest event of the same signer supersedes the others (ex. scope update)
+                    let same_signer: Vec<(EventId, Timestamp)> = shared_signers
+                        .iter()
+                        .filter(|(_, i)| {
+                            i.owner_public_key == event.author()
+                                && i.shared_signer.descriptor() == shared_signer.descriptor()
+                        })
+                        .map(|(id, i)| (*id, i.created_at))
+                        .collect();
+                    if same_signer
+                        .iter()
+                        .any(|(_, created_at)| *created_at > event.created_at)
+                    {
+                        return Ok(None);
+                    }
+                    for (id, ..) in same_signer.iter() {
+                        shared_signers.remove(id);
+                    }
+
+                    shared_signers.insert(
+                        event.id,
+                        InternalSharedSigner {
+                            owner_public_key: event.author(),
+                            shared_signer,
+                            created_at: event.created_at,
+                        },
+                    );
                     return Ok(Some(EventHandled::SharedSigner(event.id)));
                 }
             }
@@ -346,7 +409,9 @@ impl SmartVaultsStorage {
                                 label,
                             },
                         );
-                        return Ok(Some(EventHandled::Label));
+                        return Ok(Some(EventHandled::Label {
+                            policy_id: *policy_id,
+                        }));
                     } else {
                         pending.insert(event.clone());
                     }
@@ -357,10 +422,12 @@ impl SmartVaultsStorage {
                 tracing::error!("Impossible to find policy id in proposal {}", event.id);
             }
         } else if event.kind == Kind::EventDeletion {
+            let mut deleted: Vec<EventId> = Vec::new();
+
             for event_id in event.event_ids() {
                 if let Ok(true) = self.database.has_event_id_been_deleted(event_id).await {
                     self.delete_event(event_id).await;
-                    return Ok(Some(EventHandled::EventDeletion));
+                    deleted.push(*event_id);
                 } else {
                     tracing::error!("Event {event_id} not deleted");
                 }
@@ -372,6 +439,10 @@ impl SmartVaultsStorage {
                     .has_coordinate_been_deleted(coordinate, event.created_at)
                     .await
                 {
+                    if coordinate.kind == LABELS_KIND {
+                        self.delete_label(&coordinate.identifier).await;
+                    }
+
                     let filter: Filter = coordinate.into();
                     let filter: Filter = filter.until(event.created_at);
                     let event_ids = self
@@ -380,10 +451,14 @@ impl SmartVaultsStorage {
                         .await?;
                     for event_id in event_ids.into_iter() {
                         self.delete_event(&event_id).await;
+                        deleted.push(event_id);
                     }
-                    return Ok(Some(EventHandled::EventDeletion));
                 }
             }
+
+            if !deleted.is_empty() {
+                return Ok(Some(EventHandled::EventDeletion(deleted)));
+            }
         } else if event.kind == KEY_AGENT_VERIFIED {
             let new_verified_agents: VerifiedKeyAgents = VerifiedKeyAgents::from_event(event)?;
             let mut verified_key_agents = self.verified_key_agents.write().await;
@@ -398,6 +473,10 @@ impl SmartVaultsStorage {
         self.pending.read().await.clone()
     }
 
+    pub async fn is_pending(&self, event_id: &EventId) -> bool {
+        self.pending.read().await.iter().any(|e| &e.id == event_id)
+    }
+
     /// Delete event without know the kind
     pub async fn delete_event(&self, event_id: &EventId) {
         if self.delete_vault(event_id).await {
@@ -428,6 +507,21 @@ impl SmartVaultsStorage {
         shared_keys.insert(policy_id, shared_key);
     }
 
+    /// Hide the policy replaced by a shared key rotation
+    ///
+    /// The old shared key is kept, so the old proposals remain readable.
+    pub async fn mark_rotated(&self, policy_id: EventId) {
+        let mut rotated = self.rotated.write().await;
+        rotated.insert(policy_id);
+        self.delete_vault(&policy_id).await;
+    }
+
+    /// Get the public keys of the shared keys (the authors of the policies events)
+    pub async fn shared_public_keys(&self) -> Vec<PublicKey> {
+        let shared_keys = self.shared_keys.read().await;
+        shared_keys.values().map(|k| k.public_key()).collect()
+    }
+
     /// Get shared key
     pub async fn shared_key(&self, vault_id: &EventId) -> Result<Keys, Error> {
         let shared_keys = self.shared_keys.read().await;
@@ -470,6 +564,9 @@ impl SmartVaultsStorage {
         let mut proposals = self.proposals.write().await;
         match proposals.remove(proposal_id) {
             Some(p) => {
+                self.set_fee_warning(*proposal_id, None).await;
+                self.set_ready_to_finalize(*proposal_id, false).await;
+
                 // Unfreeze UTXOs
                 self.unfreeze_utxos(
                     p.policy_id,
@@ -488,6 +585,38 @@ impl SmartVaultsStorage {
         }
     }
 
+    /// Flag (or unflag) the proposal fee as suspicious
+    pub async fn set_fee_warning(&self, proposal_id: EventId, warning: Option<FeeWarning>) {
+        let mut fee_warnings = self.fee_warnings.write().await;
+        match warning {
+            Some(warning) => fee_warnings.insert(proposal_id, warning),
+            None => fee_warnings.remove(&proposal_id),
+        };
+    }
+
+    pub async fn fee_warning(&self, proposal_id: &EventId) -> Option<FeeWarning> {
+        let fee_warnings = self.fee_warnings.read().await;
+        fee_warnings.get(proposal_id).copied()
+    }
+
+    /// Flag (or unflag) the proposal as ready to be finalized
+    ///
+    /// Return `true` if the proposal was not flagged before.
+    pub async fn set_ready_to_finalize(&self, proposal_id: EventId, ready: bool) -> bool {
+        let mut ready_to_finalize = self.ready_to_finalize.write().await;
+        if ready {
+            ready_to_finalize.insert(proposal_id)
+        } else {
+            ready_to_finalize.remove(&proposal_id);
+            false
+        }
+    }
+
+    pub async fn is_ready_to_finalize(&self, proposal_id: &EventId) -> bool {
+        let ready_to_finalize = self.ready_to_finalize.read().await;
+        ready_to_finalize.contains(proposal_id)
+    }
+
     /// Get proposals
     pub async fn proposals(&self) -> HashMap<EventId, InternalProposal> {
         self.proposals
@@ -538,16 +667,25 @@ impl SmartVaultsStorage {
             proposal,
             ..
         } = self.proposal(proposal_id).await?;
+        let approvals = self.approvals.read().await;
+        let mut approved_proposals: Vec<(ApprovalOrderKey, ApprovedProposal)> = approvals
+            .iter()
+            .filter(|(_, internal)| internal.proposal_id == *proposal_id)
+            .map(|(approval_id, internal)| {
+                let key = ApprovalOrderKey {
+                    timestamp: internal.timestamp,
+                    approval_id: *approval_id,
+                };
+                (key, internal.approval.clone())
+            })
+            .collect();
+        approved_proposals.sort_by_key(|(key, _)| *key);
         Ok(GetApprovedProposals {
             policy_id,
             proposal,
-            approved_proposals: self
-                .approvals
-                .read()
-                .await
-                .values()
-                .filter(|internal| internal.proposal_id == *proposal_id)
-                .map(|internal| internal.approval.clone())
+            approved_proposals: approved_proposals
+                .into_iter()
+                .map(|(_, approval)| approval)
                 .collect(),
         })
     }
@@ -676,7 +814,7 @@ impl SmartVaultsStorage {
     /// Delete shared signer from both `shared_signers` and `my_shared_signers` collections
     pub async fn delete_shared_signer(&self, shared_signer_id: &EventId) -> bool {
         let mut my_shared_signers = self.my_shared_signers.write().await;
-        my_shared_signers.retain(|_, (id, ..)| id == shared_signer_id);
+        my_shared_signers.retain(|_, (id, ..)| id != shared_signer_id);
         let mut shared_signers = self.shared_signers.write().await;
         shared_signers.remove(shared_signer_id).is_some()
     }
@@ -732,6 +870,20 @@ impl SmartVaultsStorage {
             .ok_or(Error::NotFound)
     }
 
+    /// Get signer ID and receiver public key of my shared signer
+    pub async fn get_my_shared_signer(
+        &self,
+        shared_signer_id: EventId,
+    ) -> Result<(EventId, PublicKey), Error> {
+        self.my_shared_signers
+            .read()
+            .await
+            .iter()
+            .find(|(_, (id, _))| *id == shared_signer_id)
+            .map(|(signer_id, (_, pk))| (*signer_id, *pk))
+            .ok_or(Error::NotFound)
+    }
+
     pub async fn get_my_shared_signers_by_signer_id(
         &self,
         signer_id: &EventId,
@@ -807,6 +959,34 @@ impl SmartVaultsStorage {
             .collect()
     }
 
+    /// Get labels targeting `txid` or one of its outputs (`identifier` -> label)
+    pub async fn get_labels_by_txid(
+        &self,
+        policy_id: EventId,
+        txid: Txid,
+    ) -> BTreeMap<String, Label> {
+        self.labels
+            .read()
+            .await
+            .iter()
+            .filter(|(_, i)| i.policy_id == policy_id)
+            .filter(|(_, i)| match i.label.data() {
+                LabelData::Txid(label_txid) => label_txid == txid,
+                LabelData::Utxo(utxo) => utxo.txid == txid,
+                LabelData::Address(..) => false,
+            })
+            .map(|(identifier, i)| (identifier.clone(), i.label.clone()))
+            .collect()
+    }
+
+    pub async fn delete_label<S>(&self, identifier: S) -> bool
+    where
+        S: AsRef<str>,
+    {
+        let mut labels = self.labels.write().await;
+        labels.remove(identifier.as_ref()).is_some()
+    }
+
     pub async fn get_label_by_identifier<S>(&self, identifier: S) -> Result<Label, Error>
     where
         S: AsRef<str>,
@@ -854,7 +1034,196 @@ impl SmartVaultsStorage {
             .unwrap_or_default()
     }
 
+    pub async fn save_replacement(
+        &self,
+        original_id: EventId,
+        policy_id: EventId,
+        replacement_id: EventId,
+        txid: Txid,
+    ) {
+        let mut replacements = self.replacements.write().await;
+        replacements.insert(original_id, (policy_id, replacement_id, txid));
+    }
+
+    /// Get fee bump replacements (`original proposal ID` -> `(policy ID, replacement proposal ID, replacement txid)`)
+    pub async fn replacements(&self) -> HashMap<EventId, (EventId, EventId, Txid)> {
+        self.replacements.read().await.clone()
+    }
+
     pub async fn verified_key_agents(&self) -> VerifiedKeyAgents {
         self.verified_key_agents.read().await.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::database::{IntoNostrDatabase, MemoryDatabase, MemoryDatabaseOptions};
+    use smartvaults_core::bips::bip39::Mnemonic;
+    use smartvaults_core::bitcoin::address::NetworkUnchecked;
+    use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+    use smartvaults_core::bitcoin::{absolute, Address, Transaction};
+    use smartvaults_core::Seed;
+    use smartvaults_protocol::v1::SmartVaultsEventBuilder;
+
+    use super::*;
+
+    const NETWORK: Network = Network::Testnet;
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+
+    #[tokio::test]
+    async fn test_handle_event_variants() {
+        let keys = Keys::generate();
+        let database = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        })
+        .into_nostr_database();
+        let storage = SmartVaultsStorage::build(
+            keys.clone(),
+            database.clone(),
+            NETWORK,
+            &HashSet::new(),
+            HashMap::new(),
+        )
+        .await
+        .unwrap();
+
+        let my_pk: PublicKey = keys.public_key();
+        let shared_key = Keys::generate();
+        let policy = Policy::from_descriptor("Name", "Description", DESCRIPTOR, NETWORK).unwrap();
+
+        // Policy received before its shared key: pending
+        let policy_event = EventBuilder::policy(&shared_key, &policy, &[my_pk]).unwrap();
+        let policy_id: EventId = policy_event.id;
+        assert_eq!(storage.handle_event(&policy_event).await.unwrap(), None);
+
+        let shared_key_event =
+            EventBuilder::shared_key(&keys, &shared_key, &my_pk, policy_id).unwrap();
+        assert_eq!(
+            storage.handle_event(&shared_key_event).await.unwrap(),
+            Some(EventHandled::SharedKey(shared_key_event.id))
+        );
+        assert_eq!(
+            storage.handle_event(&policy_event).await.unwrap(),
+            Some(EventHandled::Policy(policy_id))
+        );
+        // Already handled
+        assert_eq!(storage.handle_event(&policy_event).await.unwrap(), None);
+
+        // Proposal
+        let tx = Transaction {
+            version: 2,
+            lock_time: absolute::LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        };
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(tx).unwrap();
+        let address: Address<NetworkUnchecked> =
+            Address::from_str("tb1qhk8ttr5sxpsh2uf0c7rfe5dzsxlgryrmwkg7ht").unwrap();
+        let proposal = Proposal::spending(
+            policy.descriptor(),
+            address.clone(),
+            1_000,
+            "Description",
+            psbt.clone(),
+            None,
+        );
+        let proposal_event =
+            EventBuilder::proposal(&shared_key, policy_id, &proposal, &[my_pk]).unwrap();
+        assert_eq!(
+            storage.handle_event(&proposal_event).await.unwrap(),
+            Some(EventHandled::Proposal(proposal_event.id))
+        );
+
+        // Fee bump replacement
+        let replacement = Proposal::spending(
+            policy.descriptor(),
+            address.clone(),
+            1_000,
+            "Fee bump",
+            psbt.clone(),
+            None,
+        );
+        let replacement_event = EventBuilder::replacement_proposal(
+            &shared_key,
+            policy_id,
+            proposal_event.id,
+            &replacement,
+            &[my_pk],
+        )
+        .unwrap();
+        assert_eq!(
+            storage.handle_event(&replacement_event).await.unwrap(),
+            Some(EventHandled::Proposal(replacement_event.id))
+        );
+        assert_eq!(
+            storage.replacements().await.get(&proposal_event.id),
+            Some(&(policy_id, replacement_event.id, psbt.unsigned_tx.txid()))
+        );
+
+        // Proposal paying a mainnet address: rejected
+        let mainnet_address: Address<NetworkUnchecked> =
+            Address::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").unwrap();
+        let wrong_network = Proposal::spending(
+            policy.descriptor(),
+            mainnet_address,
+            1_000,
+            "Wrong network",
+            psbt.clone(),
+            None,
+        );
+        let wrong_network_event =
+            EventBuilder::proposal(&shared_key, policy_id, &wrong_network, &[my_pk]).unwrap();
+        assert!(matches!(
+            storage.handle_event(&wrong_network_event).await,
+            Err(Error::AddressNetworkMismatch {
+                expected: NETWORK,
+                found: Network::Bitcoin,
+                ..
+            })
+        ));
+
+        // Signer
+        let mnemonic = Mnemonic::from_str(
+            "possible suffer flavor boring essay zoo collect stairs day cabbage wasp tackle",
+        )
+        .unwrap();
+        let signer =
+            Signer::from_seed("Signer", None, Seed::from_mnemonic(mnemonic), None, NETWORK)
+                .unwrap();
+        let signer_event =
+            EventBuilder::new(SIGNERS_KIND, signer.encrypt_with_keys(&keys).unwrap(), [])
+                .to_event(&keys)
+                .unwrap();
+        assert_eq!(
+            storage.handle_event(&signer_event).await.unwrap(),
+            Some(EventHandled::Signer(signer_event.id))
+        );
+
+        // Label
+        let label = Label::new(LabelData::Address(address), "Label");
+        let label_event = EventBuilder::label(&shared_key, policy_id, &label, &[my_pk]).unwrap();
+        assert_eq!(
+            storage.handle_event(&label_event).await.unwrap(),
+            Some(EventHandled::Label { policy_id })
+        );
+
+        // Deletion
+        database.save_event(&proposal_event).await.unwrap();
+        let deletion_event =
+            EventBuilder::new(Kind::EventDeletion, "", [Tag::event(proposal_event.id)])
+                .to_event(&shared_key)
+                .unwrap();
+        database.save_event(&deletion_event).await.unwrap();
+        assert_eq!(
+            storage.handle_event(&deletion_event).await.unwrap(),
+            Some(EventHandled::EventDeletion(vec![proposal_event.id]))
+        );
+
+        // Unrelated kind
+        let note = EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(storage.handle_event(&note).await.unwrap(), None);
+    }
+}
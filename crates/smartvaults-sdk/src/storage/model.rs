@@ -88,6 +88,7 @@ impl Ord for InternalCompletedProposal {
 pub(crate) struct InternalSharedSigner {
     pub owner_public_key: PublicKey,
     pub shared_signer: SharedSigner,
+    pub created_at: Timestamp,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
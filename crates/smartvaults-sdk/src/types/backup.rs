@@ -1,30 +1,44 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{Error, Read, Write};
 use std::path::Path;
 
 use nostr_sdk::PublicKey;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use smartvaults_core::miniscript::Descriptor;
 use smartvaults_protocol::v1::util::Serde;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Policy backup
+///
+/// * `v1`: unversioned, without change descriptor
+/// * `v2`: change descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PolicyBackup {
     name: Option<String>,
     description: Option<String>,
     descriptor: Descriptor<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    change_descriptor: Option<Descriptor<String>>,
     public_keys: Vec<PublicKey>,
+    /// Fields added by newer versions, preserved when the backup is saved again
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
 }
 
-impl Serde for PolicyBackup {}
+impl Serde for PolicyBackup {
+    const VERSION: u8 = 2;
+}
 
 impl PolicyBackup {
     pub fn new<S>(
         name: S,
         description: S,
         descriptor: Descriptor<String>,
+        change_descriptor: Option<Descriptor<String>>,
         public_keys: Vec<PublicKey>,
     ) -> Self
     where
@@ -34,7 +48,9 @@ impl PolicyBackup {
             name: Some(name.into()),
             description: Some(description.into()),
             descriptor,
+            change_descriptor,
             public_keys,
+            extra: BTreeMap::new(),
         }
     }
 
@@ -60,6 +76,11 @@ impl PolicyBackup {
         self.descriptor.clone()
     }
 
+    /// Change descriptor (`None` for vaults without a separate change keychain)
+    pub fn change_descriptor(&self) -> Option<Descriptor<String>> {
+        self.change_descriptor.clone()
+    }
+
     pub fn public_keys(&self) -> Vec<PublicKey> {
         self.public_keys.clone()
     }
@@ -73,3 +94,47 @@ impl PolicyBackup {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+
+    #[test]
+    fn test_policy_backup_versions() {
+        let public_key =
+            PublicKey::from_str("npub1drvpzev3syqt0kjrls50050uzf25gehpz9vgdw08hvex7e0vgfeq0eseet")
+                .unwrap();
+        let backup = PolicyBackup::new(
+            "Name",
+            "Description",
+            Descriptor::from_str(DESCRIPTOR).unwrap(),
+            None,
+            vec![public_key],
+        );
+
+        // v2 roundtrip
+        let json = backup.as_json();
+        assert!(json.contains(r#""v":2"#));
+        assert_eq!(PolicyBackup::from_json(json).unwrap(), backup);
+
+        // Unversioned (v1) backup
+        let json = format!(
+            r#"{{"name":"Name","description":"Description","descriptor":"{DESCRIPTOR}","public_keys":["{}"]}}"#,
+            public_key.to_hex()
+        );
+        assert_eq!(PolicyBackup::from_json(json).unwrap(), backup);
+
+        // Unknown fields of newer versions are preserved
+        let json = format!(
+            r#"{{"v":3,"data":{{"name":"Name","description":"Description","descriptor":"{DESCRIPTOR}","public_keys":["{}"],"threshold":2}}}}"#,
+            public_key.to_hex()
+        );
+        let restored = PolicyBackup::from_json(json).unwrap();
+        assert_eq!(restored.descriptor(), backup.descriptor());
+        assert!(restored.as_json().contains(r#""threshold":2"#));
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Forms drafts
+//!
+//! Drafts are stored in plaintext: they must never contain secrets (i.e. passwords).
+
+use nostr_sdk::PublicKey;
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::OutPoint;
+use smartvaults_protocol::v1::Serde;
+use smartvaults_sdk_sqlite::model::DraftScreen;
+
+/// Draft of a form
+pub trait Draft: Serde {
+    /// Screen of the form
+    const SCREEN: DraftScreen;
+
+    /// Check if nothing was filled
+    fn is_empty(&self) -> bool;
+}
+
+/// Draft of the spend form
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendDraft {
+    pub to_address: String,
+    pub amount: Option<u64>,
+    pub send_all: bool,
+    pub subtract_fee: bool,
+    pub description: String,
+    #[serde(default)]
+    pub selected_utxos: Vec<OutPoint>,
+    #[serde(default)]
+    pub skip_frozen_utxos: bool,
+}
+
+impl Serde for SpendDraft {}
+
+impl Draft for SpendDraft {
+    const SCREEN: DraftScreen = DraftScreen::Spend;
+
+    fn is_empty(&self) -> bool {
+        self.to_address.is_empty()
+            && self.amount.is_none()
+            && !self.send_all
+            && self.description.is_empty()
+            && self.selected_utxos.is_empty()
+    }
+}
+
+/// Draft of the add policy form
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyDraft {
+    pub name: String,
+    pub description: String,
+    /// Descriptor or policy template
+    pub descriptor: String,
+    #[serde(default)]
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl Serde for PolicyDraft {}
+
+impl Draft for PolicyDraft {
+    const SCREEN: DraftScreen = DraftScreen::AddPolicy;
+
+    fn is_empty(&self) -> bool {
+        self.name.is_empty()
+            && self.description.is_empty()
+            && self.descriptor.is_empty()
+            && self.public_keys.is_empty()
+    }
+}
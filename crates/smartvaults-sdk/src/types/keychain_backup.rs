@@ -0,0 +1,193 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Full keychain backup (to move to another machine)
+//!
+//! File format (version 1): `<magic><version><scrypt log_n><salt (16 bytes)><nonce (24 bytes)><ciphertext>`.
+//! The key is derived from the password and the salt with scrypt (memory-hard, the file contains the seed);
+//! the payload is encrypted with XChaCha20-Poly1305.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use nostr_sdk::Event;
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::crypto::{derive_key_scrypt, ScryptError, SCRYPT_LOG_N};
+use smartvaults_core::secp256k1::rand;
+use smartvaults_protocol::v1::Serde;
+
+const MAGIC: &[u8] = b"smartvaults-backup";
+/// Current file version
+pub const KEYCHAIN_BACKUP_VERSION: u8 = 1;
+/// Highest scrypt cost accepted when decrypting (avoid unbounded memory usage on crafted files)
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Scrypt(#[from] ScryptError),
+    #[error("invalid backup file")]
+    InvalidFile,
+    #[error("unsupported backup version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("wrong password or corrupted backup")]
+    Decryption,
+    #[error("impossible to encrypt backup")]
+    Encryption,
+    #[error("wrong network: expected {expected}, found {found}")]
+    WrongNetwork { expected: Network, found: Network },
+}
+
+/// Keychain backup
+///
+/// Seed, passphrases and the nostr events (signers, policies, proposals, labels, contacts, ...).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeychainBackup {
+    pub name: String,
+    pub network: Network,
+    pub mnemonic: String,
+    #[serde(default)]
+    pub passphrases: Vec<String>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+impl Serde for KeychainBackup {}
+
+impl KeychainBackup {
+    /// Encrypt backup with `password`
+    pub fn encrypt<T>(&self, password: T) -> Result<Vec<u8>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        self.encrypt_with_cost(password, SCRYPT_LOG_N)
+    }
+
+    fn encrypt_with_cost<T>(&self, password: T, log_n: u8) -> Result<Vec<u8>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let salt: [u8; SALT_LEN] = rand::random();
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let key: [u8; 32] = derive_key_scrypt(password.as_ref(), &salt, log_n)?;
+        let cipher = XChaCha20Poly1305::new(&key.into());
+        let ciphertext: Vec<u8> = cipher
+            .encrypt(XNonce::from_slice(&nonce), self.as_json().as_bytes())
+            .map_err(|_| Error::Encryption)?;
+
+        let mut payload: Vec<u8> =
+            Vec::with_capacity(MAGIC.len() + 2 + SALT_LEN + NONCE_LEN + ciphertext.len());
+        payload.extend_from_slice(MAGIC);
+        payload.push(KEYCHAIN_BACKUP_VERSION);
+        payload.push(log_n);
+        payload.extend_from_slice(&salt);
+        payload.extend_from_slice(&nonce);
+        payload.extend(ciphertext);
+        Ok(payload)
+    }
+
+    /// Decrypt backup file content
+    pub fn decrypt<P, T>(payload: P, password: T, network: Network) -> Result<Self, Error>
+    where
+        P: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        let payload: &[u8] = payload
+            .as_ref()
+            .strip_prefix(MAGIC)
+            .ok_or(Error::InvalidFile)?;
+        let (version, payload) = payload.split_first().ok_or(Error::InvalidFile)?;
+        if *version != KEYCHAIN_BACKUP_VERSION {
+            return Err(Error::UnsupportedVersion(*version));
+        }
+
+        let (log_n, payload) = payload.split_first().ok_or(Error::InvalidFile)?;
+        if *log_n > MAX_SCRYPT_LOG_N || payload.len() < SALT_LEN + NONCE_LEN {
+            return Err(Error::InvalidFile);
+        }
+        let (salt, payload) = payload.split_at(SALT_LEN);
+        let key: [u8; 32] = derive_key_scrypt(password.as_ref(), salt, *log_n)?;
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let cipher = XChaCha20Poly1305::new(&key.into());
+        let data: Vec<u8> = cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| Error::Decryption)?;
+        let backup: Self =
+            Self::from_json(String::from_utf8(data).map_err(|_| Error::InvalidFile)?)?;
+
+        if backup.network != network {
+            return Err(Error::WrongNetwork {
+                expected: network,
+                found: backup.network,
+            });
+        }
+
+        Ok(backup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup() -> KeychainBackup {
+        KeychainBackup {
+            name: String::from("main"),
+            network: Network::Testnet,
+            mnemonic: String::from(
+                "easy uncover favorite crucial ball sketch smile glare carpet uniform uncle spike",
+            ),
+            passphrases: vec![String::from("test")],
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_keychain_backup_roundtrip() {
+        let backup = backup();
+
+        // Low scrypt cost to keep the test fast: the cost is read back from the file
+        let payload = backup.encrypt_with_cost("password", 10).unwrap();
+        assert!(payload.starts_with(MAGIC));
+        assert_eq!(payload[MAGIC.len()], KEYCHAIN_BACKUP_VERSION);
+        assert_eq!(payload[MAGIC.len() + 1], 10);
+        assert_eq!(
+            KeychainBackup::decrypt(&payload, "password", Network::Testnet).unwrap(),
+            backup
+        );
+        assert!(matches!(
+            KeychainBackup::decrypt(&payload, "wrong", Network::Testnet),
+            Err(Error::Decryption)
+        ));
+        assert!(matches!(
+            KeychainBackup::decrypt(&payload, "password", Network::Bitcoin),
+            Err(Error::WrongNetwork { .. })
+        ));
+        assert!(matches!(
+            KeychainBackup::decrypt(b"not a backup", "password", Network::Testnet),
+            Err(Error::InvalidFile)
+        ));
+
+        let mut payload = payload;
+        payload[MAGIC.len()] = KEYCHAIN_BACKUP_VERSION + 1;
+        assert!(matches!(
+            KeychainBackup::decrypt(&payload, "password", Network::Testnet),
+            Err(Error::UnsupportedVersion(2))
+        ));
+    }
+
+    #[test]
+    fn test_keychain_backup_cost_limit() {
+        let mut payload = backup().encrypt_with_cost("password", 10).unwrap();
+        payload[MAGIC.len() + 1] = MAX_SCRYPT_LOG_N + 1;
+        assert!(matches!(
+            KeychainBackup::decrypt(&payload, "password", Network::Testnet),
+            Err(Error::InvalidFile)
+        ));
+    }
+}
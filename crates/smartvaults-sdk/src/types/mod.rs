@@ -2,23 +2,41 @@
 // Distributed under the MIT software license
 
 use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 use std::ops::Deref;
+use std::time::Duration;
 
-use nostr_sdk::{EventId, Profile, Timestamp};
+use nostr_sdk::{EventId, JsonUtil, Metadata, Profile, PublicKey, RelayStatus, Timestamp, Url};
+use serde_json::Value;
+use smartvaults_core::bdk::chain::ConfirmationTime;
 use smartvaults_core::bdk::wallet::Balance;
-use smartvaults_core::bdk::LocalOutput;
+use smartvaults_core::bdk::{FeeRate, LocalOutput};
 use smartvaults_core::bitcoin::address::NetworkUnchecked;
-use smartvaults_core::bitcoin::Address;
+use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+use smartvaults_core::bitcoin::{Address, OutPoint, Txid};
+use smartvaults_core::policy::{KeyOriginInfo, KeyPurpose};
 use smartvaults_core::{
-    ApprovedProposal, CompletedProposal, Policy, Proposal, SharedSigner, Signer,
+    ApprovedProposal, CompletedProposal, FeeWarning, Policy, Proposal, SharedSigner, Signer,
 };
-use smartvaults_protocol::v1::SignerOffering;
+use smartvaults_protocol::v1::{Capabilities, Label, SignerOffering, PROTOCOL_VERSION};
 pub use smartvaults_sdk_sqlite::model::*;
 
+use crate::constants::METADATA_STALE_AFTER;
+use crate::price::FiatAmount;
+use crate::screening::ScreeningResult;
+
 pub mod backup;
+pub mod draft;
+pub mod keychain_backup;
+pub mod offline_approval;
+pub mod settings;
 
 pub use self::backup::PolicyBackup;
+pub use self::draft::{Draft, PolicyDraft, SpendDraft};
+pub use self::keychain_backup::KeychainBackup;
+pub use self::offline_approval::{ApprovalRequest, ApprovalResponse};
+pub use self::settings::{DisplayPreferences, SettingsExport};
 use crate::manager::TransactionDetails;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -29,6 +47,57 @@ pub struct GetPolicy {
     pub last_sync: Timestamp,
 }
 
+/// Balance with the total converted to fiat
+#[derive(Debug, Clone)]
+pub struct BalanceWithFiat {
+    pub balance: Balance,
+    /// `None` if the price is not available
+    pub fiat: Option<FiatAmount>,
+}
+
+/// Fee rates (sat/vByte) estimated by the backend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimates {
+    /// Next block
+    pub fastest: f32,
+    /// ~30 minutes (3 blocks)
+    pub half_hour: f32,
+    /// ~1 hour (6 blocks)
+    pub hour: f32,
+    /// ~2 hours (12 blocks)
+    pub economy: f32,
+    /// The backend can't estimate (ex. fresh regtest): conservative defaults are used
+    pub fallback: bool,
+}
+
+impl FeeEstimates {
+    /// Target blocks of `fastest`, `half_hour`, `hour` and `economy`
+    pub const TARGET_BLOCKS: [u8; 4] = [1, 3, 6, 12];
+
+    pub(crate) fn from_rates(rates: [f32; 4], fallback: bool) -> Self {
+        let [fastest, half_hour, hour, economy] = rates;
+        Self {
+            fastest,
+            half_hour,
+            hour,
+            economy,
+            fallback,
+        }
+    }
+
+    /// Fee rate to confirm within `target_blocks`
+    ///
+    /// Use the estimate of the nearest target not above `target_blocks` (ex. 4 blocks -> `half_hour`).
+    pub fn fee_rate(&self, target_blocks: u8) -> f32 {
+        match target_blocks {
+            0..=2 => self.fastest,
+            3..=5 => self.half_hour,
+            6..=11 => self.hour,
+            _ => self.economy,
+        }
+    }
+}
+
 impl PartialOrd for GetPolicy {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -56,6 +125,19 @@ pub struct GetProposal {
     pub proposal: Proposal,
     pub signed: bool,
     pub timestamp: Timestamp,
+    /// Suspicious fee: approval must be acknowledged
+    pub fee_warning: Option<FeeWarning>,
+}
+
+impl GetProposal {
+    /// Time elapsed since the proposal creation
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(
+            Timestamp::now()
+                .as_u64()
+                .saturating_sub(self.timestamp.as_u64()),
+        )
+    }
 }
 
 impl PartialOrd for GetProposal {
@@ -74,12 +156,68 @@ impl Ord for GetProposal {
     }
 }
 
+/// Ordering key of approvals: event `created_at`, with the event ID as tiebreaker
+///
+/// Doesn't depend on the order in which the approvals were received, so every device lists them
+/// in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApprovalOrderKey {
+    pub timestamp: Timestamp,
+    pub approval_id: EventId,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetApproval {
     pub approval_id: EventId,
     pub user: Profile,
     pub approved_proposal: ApprovedProposal,
+    /// Raw event `created_at`
     pub timestamp: Timestamp,
+    /// `timestamp` clamped to the current time (approver with a clock in the future)
+    pub display_timestamp: Timestamp,
+    pub order_key: ApprovalOrderKey,
+}
+
+impl GetApproval {
+    pub(crate) fn new(
+        approval_id: EventId,
+        user: Profile,
+        approved_proposal: ApprovedProposal,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self::new_at(
+            approval_id,
+            user,
+            approved_proposal,
+            timestamp,
+            Timestamp::now(),
+        )
+    }
+
+    fn new_at(
+        approval_id: EventId,
+        user: Profile,
+        approved_proposal: ApprovedProposal,
+        timestamp: Timestamp,
+        now: Timestamp,
+    ) -> Self {
+        Self {
+            approval_id,
+            user,
+            approved_proposal,
+            timestamp,
+            display_timestamp: timestamp.min(now),
+            order_key: ApprovalOrderKey {
+                timestamp,
+                approval_id,
+            },
+        }
+    }
+
+    /// Check if the approval was created in the future (clock skew of the approver)
+    pub fn is_clock_skewed(&self) -> bool {
+        self.display_timestamp != self.timestamp
+    }
 }
 
 impl PartialOrd for GetApproval {
@@ -90,11 +228,7 @@ impl PartialOrd for GetApproval {
 
 impl Ord for GetApproval {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self.timestamp != other.timestamp {
-            self.timestamp.cmp(&other.timestamp).reverse()
-        } else {
-            self.approval_id.cmp(&other.approval_id)
-        }
+        self.order_key.cmp(&other.order_key)
     }
 }
 
@@ -128,6 +262,23 @@ impl Ord for GetCompletedProposal {
     }
 }
 
+/// Result of [`SmartVaults::approve_and_finalize`](crate::SmartVaults::approve_and_finalize)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApproveAndFinalize {
+    /// The approval completed the proposal: finalized and broadcasted
+    Finalized {
+        approval_id: EventId,
+        completed_proposal: CompletedProposal,
+    },
+    /// Approved but not enough approvals to finalize
+    Approved {
+        approval_id: EventId,
+        approved_proposal: ApprovedProposal,
+        /// `None` if can't be computed (i.e. hash preimages in the policy)
+        missing_signatures: Option<usize>,
+    },
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetSigner {
     pub signer_id: EventId,
@@ -200,6 +351,10 @@ pub struct GetTransaction {
     pub tx: TransactionDetails,
     pub label: Option<String>,
     pub block_explorer: Option<String>,
+    /// Incoming transactions are flagged if pay/change to a screened address
+    pub screening: ScreeningResult,
+    /// Outgoing transaction without a matching completed proposal
+    pub unexpected: bool,
 }
 
 impl PartialOrd for GetTransaction {
@@ -222,9 +377,112 @@ impl Deref for GetTransaction {
     }
 }
 
+/// Input of a [`TxDetails`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxInputDetails {
+    pub previous_output: OutPoint,
+    /// Value of the previous output (`None` if the previous tx is unknown)
+    pub value: Option<u64>,
+    pub is_mine: bool,
+}
+
+/// Output of a [`TxDetails`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxOutputDetails {
+    pub address: Option<Address<NetworkUnchecked>>,
+    pub value: u64,
+    pub is_mine: bool,
+    /// Output paying to the internal (change) keychain
+    pub is_change: bool,
+}
+
+/// Preview of a spending proposal (see [`SmartVaults::build_spend_preview`](crate::SmartVaults::build_spend_preview))
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendPreview {
+    pub policy_id: EventId,
+    /// Proposal published by [`SmartVaults::publish_spend_preview`](crate::SmartVaults::publish_spend_preview)
+    pub proposal: Proposal,
+    pub psbt: PartiallySignedTransaction,
+    /// Fee in sat
+    pub fee: u64,
+    /// Approximated fee rate (sat/vByte, see [`Proposal::fee_rate`])
+    pub fee_rate: f32,
+    pub selected_utxos: Vec<TxInputDetails>,
+    /// `None` if the transaction has no change output
+    pub change: Option<TxOutputDetails>,
+}
+
+/// Miner fees paid by a policy in a period
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeesPaid {
+    /// Total (sat)
+    pub total: u64,
+    /// Some fee is estimated (see [`TxFee::estimated`]): the total is a lower bound
+    pub estimated: bool,
+    /// Per-transaction breakdown, newest first
+    pub txs: Vec<TxFee>,
+}
+
+impl From<Vec<TxFee>> for FeesPaid {
+    fn from(txs: Vec<TxFee>) -> Self {
+        Self {
+            total: txs.iter().map(|tx| tx.fee).sum(),
+            estimated: txs.iter().any(|tx| tx.estimated),
+            txs,
+        }
+    }
+}
+
+/// Key of a policy, matched with my signers and the signers shared with me
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyKeyReport {
+    pub origin: KeyOriginInfo,
+    /// ID of my signer or of the shared signer matching the key
+    pub signer_id: Option<EventId>,
+    /// The key is of one of my signers
+    pub ours: bool,
+    /// Name of my signer or of the contact that shared it
+    pub owner: Option<String>,
+    /// Signer type, with the identifiers of the devices registered for the policy
+    pub device: Option<String>,
+    /// Purpose of the signer key matching exactly the policy key
+    ///
+    /// `None` if matched only by fingerprint (i.e. another account or purpose of the same device).
+    pub matched_purpose: Option<KeyPurpose>,
+}
+
+/// Detailed view of a policy transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxDetails {
+    pub policy_id: EventId,
+    pub txid: Txid,
+    /// Confirmed at block height/time or unconfirmed (last seen in mempool)
+    pub confirmation_time: ConfirmationTime,
+    /// Fee in sat (`None` if some previous output is unknown)
+    pub fee: Option<u64>,
+    pub fee_rate: Option<FeeRate>,
+    pub inputs: Vec<TxInputDetails>,
+    pub outputs: Vec<TxOutputDetails>,
+    /// Labels attached to the tx, to its outputs or to its addresses (`identifier` -> label)
+    pub labels: BTreeMap<String, Label>,
+    pub block_explorer: Option<String>,
+}
+
+impl TxDetails {
+    pub fn is_confirmed(&self) -> bool {
+        self.confirmation_time.is_confirmed()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GetAddress {
     pub address: Address<NetworkUnchecked>,
+    /// Derivation index (external keychain)
+    pub index: u32,
+    /// Received funds (according to the last timechain sync)
+    pub used: bool,
+    /// Balance of the unspent outputs (sats)
+    pub balance: u64,
     pub label: Option<String>,
 }
 
@@ -270,9 +528,572 @@ impl Deref for KeyAgent {
     }
 }
 
+/// Participant of a new policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyParticipant {
+    /// Profile with the metadata cached in the local database (may be empty)
+    pub user: Profile,
+    pub is_me: bool,
+    pub is_contact: bool,
+}
+
+impl Deref for PolicyParticipant {
+    type Target = Profile;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GetSignerOffering {
     pub id: EventId, // TODO: remove?
     pub signer: GetSigner,
     pub offering: SignerOffering,
 }
+
+/// Result of a batch publication
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchSendReport {
+    /// Per-event, per-relay result (`None` if the relay accepted the event)
+    pub results: BTreeMap<EventId, BTreeMap<Url, Option<String>>>,
+}
+
+impl BatchSendReport {
+    /// Number of events
+    pub fn events(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Number of events accepted by at least one relay
+    pub fn accepted_events(&self) -> usize {
+        self.results
+            .values()
+            .filter(|relays| relays.values().any(|res| res.is_none()))
+            .count()
+    }
+
+    /// Relays involved in the publication
+    pub fn relays(&self) -> HashSet<&Url> {
+        self.results
+            .values()
+            .flat_map(|relays| relays.keys())
+            .collect()
+    }
+
+    /// Relays that accepted all the events
+    pub fn accepting_relays(&self) -> HashSet<&Url> {
+        self.relays()
+            .into_iter()
+            .filter(|url| {
+                self.results
+                    .values()
+                    .all(|relays| matches!(relays.get(*url), Some(None)))
+            })
+            .collect()
+    }
+
+    /// Events rejected by relays
+    pub fn failures(&self) -> impl Iterator<Item = (&EventId, &Url, &String)> {
+        self.results.iter().flat_map(|(event_id, relays)| {
+            relays
+                .iter()
+                .filter_map(move |(url, res)| res.as_ref().map(|e| (event_id, url, e)))
+        })
+    }
+}
+
+impl fmt::Display for BatchSendReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} events accepted by {}/{} relays",
+            self.accepted_events(),
+            self.events(),
+            self.accepting_relays().len(),
+            self.relays().len()
+        )
+    }
+}
+
+/// Local data depending on a completed proposal
+///
+/// Returned before (and after) deleting a completed proposal, so the caller can decide if
+/// delete the dependent data too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionImpact {
+    pub completed_proposal_id: EventId,
+    pub policy_id: EventId,
+    /// Txid of the completed proposal (`None` for proof of reserve)
+    pub txid: Option<Txid>,
+    /// Labels targeting the txid or one of its outputs (`identifier` -> label)
+    pub labels: BTreeMap<String, Label>,
+    /// Fee bump links (superseded or replacement) referencing the completed proposal
+    pub superseded_links: usize,
+    /// Signing audit log entries of the original proposal
+    ///
+    /// The audit log is append-only: these entries are always retained.
+    pub audit_entries: usize,
+}
+
+impl DeletionImpact {
+    /// Check if there is dependent data that can be deleted
+    pub fn has_dependents(&self) -> bool {
+        !self.labels.is_empty() || self.superseded_links > 0
+    }
+}
+
+/// Approver responsiveness statistics
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApproverStats {
+    pub public_key: PublicKey,
+    /// Proposals created while the approver was a participant
+    pub proposals: usize,
+    /// Proposals approved
+    pub approved: usize,
+    /// Median time (in secs) from proposal creation to approval
+    pub median_approval_time: Option<u64>,
+    /// Completed proposals finalized without the approver's approval
+    pub finalized_without: usize,
+}
+
+impl ApproverStats {
+    /// Approval rate (`0.0` - `1.0`)
+    pub fn approval_rate(&self) -> Option<f64> {
+        if self.proposals > 0 {
+            Some(self.approved as f64 / self.proposals as f64)
+        } else {
+            None
+        }
+    }
+}
+
+/// Protocol compatibility of a policy member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberCompatibility {
+    pub public_key: PublicKey,
+    /// `None` if the member never advertised its capabilities (legacy client, v1 only)
+    pub capabilities: Option<Capabilities>,
+    /// Timestamp of the last capabilities advertisement
+    pub last_seen: Option<Timestamp>,
+}
+
+impl MemberCompatibility {
+    /// Latest protocol version supported by the member
+    pub fn latest_version(&self) -> u8 {
+        self.capabilities
+            .as_ref()
+            .and_then(|c| c.latest())
+            .unwrap_or(1)
+    }
+
+    /// Check if the member runs a client older than this one
+    pub fn is_outdated(&self) -> bool {
+        self.latest_version() < PROTOCOL_VERSION
+    }
+}
+
+/// Protocol compatibility report of a policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyCompatibility {
+    pub policy_id: EventId,
+    /// Protocol version used to encode the events of the policy
+    pub protocol_version: u8,
+    pub members: Vec<MemberCompatibility>,
+}
+
+impl PolicyCompatibility {
+    /// Members that must upgrade to allow the policy to use the latest protocol version
+    pub fn outdated_members(&self) -> Vec<&MemberCompatibility> {
+        self.members.iter().filter(|m| m.is_outdated()).collect()
+    }
+}
+
+/// Relay health
+#[derive(Debug, Clone)]
+pub struct RelayHealth {
+    pub url: Url,
+    pub status: RelayStatus,
+    /// Last successful connection
+    pub connected_at: Option<Timestamp>,
+    /// Last disconnection since startup
+    pub disconnected_at: Option<Timestamp>,
+    /// Connection attempts
+    pub attempts: usize,
+    /// Successful connections
+    pub success: usize,
+    /// Events received since startup
+    pub events_received: u64,
+    /// Failed publications since startup (outbox retries included)
+    pub failed_publishes: u64,
+    /// Average time to publish a batch of events
+    pub avg_send_latency: Option<Duration>,
+    /// Latency measured by the relay pool
+    pub latency: Option<Duration>,
+    /// Messages waiting to be sent
+    pub queue: usize,
+}
+
+impl RelayHealth {
+    pub fn is_connected(&self) -> bool {
+        self.status == RelayStatus::Connected
+    }
+}
+
+/// Dashboard counters
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub policies: usize,
+    pub pending_proposals: usize,
+    /// Pending proposals that need an approval from one of the user's signers
+    pub awaiting_my_approval: usize,
+    /// Pending proposals with enough approvals to be finalized
+    pub ready_to_finalize: usize,
+    /// Proposals completed since the start of the current month (UTC)
+    pub completed_this_month: usize,
+    /// Total balance of the policies
+    pub balance: Balance,
+}
+
+/// User role
+///
+/// Announced with the key agent signaling event: the role of other users is self-declared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UserRole {
+    #[default]
+    User,
+    KeyAgent,
+}
+
+impl fmt::Display for UserRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::User => write!(f, "user"),
+            Self::KeyAgent => write!(f, "key-agent"),
+        }
+    }
+}
+
+/// Contact with the cached metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetContact {
+    pub profile: Profile,
+    /// Timestamp of the cached metadata event (`None` if never received)
+    pub updated_at: Option<Timestamp>,
+    /// Self-declared role (informational only)
+    pub role: UserRole,
+    /// Key agent verified by Smart Vaults
+    pub verified: bool,
+}
+
+impl PartialOrd for GetContact {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GetContact {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.profile.cmp(&other.profile)
+    }
+}
+
+impl GetContact {
+    /// Check if the cached metadata is missing or outdated
+    pub fn is_stale(&self) -> bool {
+        self.is_stale_at(Timestamp::now())
+    }
+
+    pub(crate) fn is_stale_at(&self, now: Timestamp) -> bool {
+        match self.updated_at {
+            Some(updated_at) => updated_at + METADATA_STALE_AFTER < now,
+            None => true,
+        }
+    }
+}
+
+/// Changed field of the profile metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl MetadataChange {
+    /// Compare two metadata (custom fields included)
+    pub fn diff(old: &Metadata, new: &Metadata) -> Vec<Self> {
+        let to_map = |metadata: &Metadata| -> BTreeMap<String, String> {
+            match serde_json::from_str::<BTreeMap<String, Value>>(&metadata.as_json()) {
+                Ok(map) => map
+                    .into_iter()
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(k, v)| match v {
+                        Value::String(s) => (k, s),
+                        v => (k, v.to_string()),
+                    })
+                    .collect(),
+                Err(_) => BTreeMap::new(),
+            }
+        };
+
+        let mut old: BTreeMap<String, String> = to_map(old);
+        let new: BTreeMap<String, String> = to_map(new);
+
+        let mut changes: Vec<Self> = Vec::new();
+        for (field, value) in new.into_iter() {
+            let previous: Option<String> = old.remove(&field);
+            if previous.as_ref() != Some(&value) {
+                changes.push(Self {
+                    field,
+                    old: previous,
+                    new: Some(value),
+                });
+            }
+        }
+        for (field, value) in old.into_iter() {
+            changes.push(Self {
+                field,
+                old: Some(value),
+                new: None,
+            });
+        }
+        changes.sort_by(|a, b| a.field.cmp(&b.field));
+        changes
+    }
+}
+
+/// Sort order of paginated listings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+/// Pagination and filtering options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pagination {
+    /// Filter by policy
+    pub policy_id: Option<EventId>,
+    /// Max number of items (`None` means no limit)
+    pub limit: Option<usize>,
+    /// Number of items to skip
+    pub offset: usize,
+    pub sort: SortOrder,
+}
+
+impl Pagination {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn policy_id(self, policy_id: EventId) -> Self {
+        Self {
+            policy_id: Some(policy_id),
+            ..self
+        }
+    }
+
+    pub fn limit(self, limit: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    pub fn offset(self, offset: usize) -> Self {
+        Self { offset, ..self }
+    }
+
+    /// Set offset from page number (starts from `1`) and page size
+    pub fn page(self, page: usize, size: usize) -> Self {
+        Self {
+            limit: Some(size),
+            offset: page.saturating_sub(1).saturating_mul(size),
+            ..self
+        }
+    }
+
+    pub fn sort(self, sort: SortOrder) -> Self {
+        Self { sort, ..self }
+    }
+
+    pub(crate) fn match_policy(&self, policy_id: &EventId) -> bool {
+        match &self.policy_id {
+            Some(id) => id == policy_id,
+            None => true,
+        }
+    }
+
+    /// Sort (from oldest, according to [`Ord`]) and slice the items
+    pub(crate) fn apply<T>(&self, mut items: Vec<T>) -> Page<T>
+    where
+        T: Ord,
+    {
+        let total: usize = items.len();
+        match self.sort {
+            SortOrder::Newest => items.sort_by(|a, b| b.cmp(a)),
+            SortOrder::Oldest => items.sort(),
+        }
+        Page {
+            items: items
+                .into_iter()
+                .skip(self.offset)
+                .take(self.limit.unwrap_or(usize::MAX))
+                .collect(),
+            total,
+        }
+    }
+}
+
+/// Page of a paginated listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items matching the filters
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    pub fn map<U, F>(self, f: F) -> Page<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+    use smartvaults_core::bitcoin::{absolute, Transaction};
+
+    use super::*;
+
+    #[test]
+    fn test_pagination() {
+        let items: Vec<u64> = (1..=10).collect();
+
+        let page = Pagination::new().page(2, 3).apply(items.clone());
+        assert_eq!(page.items, vec![7, 6, 5]);
+        assert_eq!(page.total, 10);
+
+        let page = Pagination::new()
+            .sort(SortOrder::Oldest)
+            .offset(8)
+            .limit(5)
+            .apply(items);
+        assert_eq!(page.items, vec![9, 10]);
+    }
+
+    #[test]
+    fn test_metadata_diff() {
+        let old = Metadata::new().name("alice").about("Bitcoiner");
+        let new = Metadata::new().name("alice").display_name("Alice");
+        assert_eq!(
+            MetadataChange::diff(&old, &new),
+            vec![
+                MetadataChange {
+                    field: String::from("about"),
+                    old: Some(String::from("Bitcoiner")),
+                    new: None,
+                },
+                MetadataChange {
+                    field: String::from("display_name"),
+                    old: None,
+                    new: Some(String::from("Alice")),
+                },
+            ]
+        );
+        assert!(MetadataChange::diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_contact_staleness() {
+        let public_key =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+        let now = Timestamp::from(1_700_000_000);
+
+        let mut contact = GetContact {
+            profile: Profile::new(public_key, Metadata::default()),
+            updated_at: None,
+            role: UserRole::User,
+            verified: false,
+        };
+        assert!(contact.is_stale_at(now));
+
+        contact.updated_at = Some(now - Duration::from_secs(60));
+        assert!(!contact.is_stale_at(now));
+
+        contact.updated_at = Some(now - METADATA_STALE_AFTER - Duration::from_secs(1));
+        assert!(contact.is_stale_at(now));
+    }
+
+    #[test]
+    fn test_approvals_ordering() {
+        let public_key =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(Transaction {
+            version: 2,
+            lock_time: absolute::LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        })
+        .unwrap();
+        let now = Timestamp::from(1_700_000_000);
+        let approval = |id: u8, timestamp: Timestamp| {
+            GetApproval::new_at(
+                EventId::from_slice(&[id; 32]).unwrap(),
+                Profile::new(public_key, Metadata::default()),
+                ApprovedProposal::spending(psbt.clone()),
+                timestamp,
+                now,
+            )
+        };
+
+        // Received out of order, one from a clock in the future
+        let future = now + Duration::from_secs(3600);
+        let mut approvals = vec![
+            approval(3, future),
+            approval(2, now - Duration::from_secs(60)),
+            approval(1, now - Duration::from_secs(60)),
+        ];
+        approvals.sort();
+
+        let ids: Vec<EventId> = approvals.iter().map(|a| a.approval_id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                EventId::from_slice(&[1; 32]).unwrap(),
+                EventId::from_slice(&[2; 32]).unwrap(),
+                EventId::from_slice(&[3; 32]).unwrap(),
+            ]
+        );
+
+        // Clamped for display, raw timestamp preserved
+        let skewed = &approvals[2];
+        assert!(skewed.is_clock_skewed());
+        assert_eq!(skewed.display_timestamp, now);
+        assert_eq!(skewed.timestamp, future);
+        assert!(!approvals[0].is_clock_skewed());
+    }
+
+    #[test]
+    fn test_fee_estimates_target_blocks() {
+        let estimates = FeeEstimates::from_rates([40.0, 30.0, 20.0, 10.0], false);
+        assert_eq!(estimates.fee_rate(1), 40.0);
+        assert_eq!(estimates.fee_rate(2), 40.0);
+        assert_eq!(estimates.fee_rate(4), 30.0);
+        assert_eq!(estimates.fee_rate(6), 20.0);
+        assert_eq!(estimates.fee_rate(12), 10.0);
+        assert_eq!(estimates.fee_rate(144), 10.0);
+    }
+}
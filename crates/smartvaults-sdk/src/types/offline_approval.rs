@@ -0,0 +1,230 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Approval requests/responses exchanged with offline co-signers (ex. via USB)
+//!
+//! Same file format of the keychain backup: `<magic><version><salt (16 bytes)><nonce (24 bytes)><ciphertext>`.
+//! Request and response have different magics, so a response can be told apart from a bare PSBT.
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use nostr_sdk::EventId;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::crypto::derive_key;
+use smartvaults_core::secp256k1::rand;
+use smartvaults_core::{ApprovedProposal, Proposal};
+use smartvaults_protocol::v1::Serde;
+
+const REQUEST_MAGIC: &[u8] = b"smartvaults-approval-request";
+const RESPONSE_MAGIC: &[u8] = b"smartvaults-approval-response";
+/// Current file version
+pub const OFFLINE_APPROVAL_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("invalid approval file")]
+    InvalidFile,
+    #[error("unsupported approval file version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("wrong password or corrupted approval file")]
+    Decryption,
+    #[error("impossible to encrypt approval file")]
+    Encryption,
+    #[error("wrong network: expected {expected}, found {found}")]
+    WrongNetwork { expected: Network, found: Network },
+}
+
+/// Approval request for an offline co-signer
+///
+/// Self-contained, except for the policy: the offline instance must already know it, to check the
+/// descriptor and the inputs before signing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub proposal_id: EventId,
+    pub policy_id: EventId,
+    pub network: Network,
+    pub policy_name: String,
+    pub descriptor: String,
+    pub proposal: Proposal,
+}
+
+impl Serde for ApprovalRequest {}
+
+impl ApprovalRequest {
+    /// Encrypt request with `password`
+    pub fn encrypt<T>(&self, password: T) -> Result<Vec<u8>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        seal(REQUEST_MAGIC, password.as_ref(), self.as_json().as_bytes())
+    }
+
+    /// Decrypt request file content
+    pub fn decrypt<P, T>(payload: P, password: T, network: Network) -> Result<Self, Error>
+    where
+        P: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        let request: Self = unseal(REQUEST_MAGIC, payload.as_ref(), password.as_ref())?;
+        check_network(request.network, network)?;
+        Ok(request)
+    }
+}
+
+/// Approval signed by an offline co-signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovalResponse {
+    pub proposal_id: EventId,
+    pub network: Network,
+    pub approval: ApprovedProposal,
+}
+
+impl Serde for ApprovalResponse {}
+
+impl ApprovalResponse {
+    /// Check if `payload` looks like an approval response
+    pub fn is_response<P>(payload: P) -> bool
+    where
+        P: AsRef<[u8]>,
+    {
+        payload.as_ref().starts_with(RESPONSE_MAGIC)
+    }
+
+    /// Encrypt response with `password`
+    pub fn encrypt<T>(&self, password: T) -> Result<Vec<u8>, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        seal(RESPONSE_MAGIC, password.as_ref(), self.as_json().as_bytes())
+    }
+
+    /// Decrypt response file content
+    pub fn decrypt<P, T>(payload: P, password: T, network: Network) -> Result<Self, Error>
+    where
+        P: AsRef<[u8]>,
+        T: AsRef<[u8]>,
+    {
+        let response: Self = unseal(RESPONSE_MAGIC, payload.as_ref(), password.as_ref())?;
+        check_network(response.network, network)?;
+        Ok(response)
+    }
+}
+
+fn check_network(found: Network, expected: Network) -> Result<(), Error> {
+    if found != expected {
+        return Err(Error::WrongNetwork { expected, found });
+    }
+    Ok(())
+}
+
+fn seal(magic: &[u8], password: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+    let salt: [u8; SALT_LEN] = rand::random();
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let key: [u8; 32] = derive_key(password, &salt);
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let ciphertext: Vec<u8> = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| Error::Encryption)?;
+
+    let mut payload: Vec<u8> =
+        Vec::with_capacity(magic.len() + 1 + SALT_LEN + NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(magic);
+    payload.push(OFFLINE_APPROVAL_VERSION);
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend(ciphertext);
+    Ok(payload)
+}
+
+fn unseal<T>(magic: &[u8], payload: &[u8], password: &[u8]) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let payload: &[u8] = payload.strip_prefix(magic).ok_or(Error::InvalidFile)?;
+    let (version, payload) = payload.split_first().ok_or(Error::InvalidFile)?;
+    match version {
+        1 => {
+            if payload.len() < SALT_LEN + NONCE_LEN {
+                return Err(Error::InvalidFile);
+            }
+            let (salt, payload) = payload.split_at(SALT_LEN);
+            let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+            let key: [u8; 32] = derive_key(password, salt);
+            let cipher = XChaCha20Poly1305::new(&key.into());
+            let data: Vec<u8> = cipher
+                .decrypt(XNonce::from_slice(nonce), ciphertext)
+                .map_err(|_| Error::Decryption)?;
+            Ok(serde_json::from_slice(&data)?)
+        }
+        v => Err(Error::UnsupportedVersion(*v)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use smartvaults_core::bitcoin::address::NetworkUnchecked;
+    use smartvaults_core::bitcoin::psbt::PartiallySignedTransaction;
+    use smartvaults_core::bitcoin::{absolute, Address, Transaction};
+    use smartvaults_core::Policy;
+
+    use super::*;
+
+    const NETWORK: Network = Network::Testnet;
+    const DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+
+    #[test]
+    fn test_approval_request_roundtrip() {
+        let policy = Policy::from_descriptor("Name", "Description", DESCRIPTOR, NETWORK).unwrap();
+        let tx = Transaction {
+            version: 2,
+            lock_time: absolute::LockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        };
+        let psbt = PartiallySignedTransaction::from_unsigned_tx(tx).unwrap();
+        let address: Address<NetworkUnchecked> =
+            Address::from_str("tb1qhk8ttr5sxpsh2uf0c7rfe5dzsxlgryrmwkg7ht").unwrap();
+        let request = ApprovalRequest {
+            proposal_id: EventId::all_zeros(),
+            policy_id: EventId::all_zeros(),
+            network: NETWORK,
+            policy_name: policy.name(),
+            descriptor: policy.descriptor().to_string(),
+            proposal: Proposal::spending(
+                policy.descriptor(),
+                address,
+                1_000,
+                "Description",
+                psbt,
+                None,
+            ),
+        };
+
+        let payload = request.encrypt("password").unwrap();
+        assert!(!ApprovalResponse::is_response(&payload));
+        assert_eq!(
+            ApprovalRequest::decrypt(&payload, "password", NETWORK).unwrap(),
+            request
+        );
+        assert!(matches!(
+            ApprovalRequest::decrypt(&payload, "wrong", NETWORK),
+            Err(Error::Decryption)
+        ));
+        assert!(matches!(
+            ApprovalRequest::decrypt(&payload, "password", Network::Bitcoin),
+            Err(Error::WrongNetwork { .. })
+        ));
+        assert!(matches!(
+            ApprovalResponse::decrypt(&payload, "password", NETWORK),
+            Err(Error::InvalidFile)
+        ));
+    }
+}
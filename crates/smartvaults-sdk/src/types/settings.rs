@@ -0,0 +1,234 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Settings export (to bootstrap another device)
+//!
+//! Payload format: `smartvaults-settings:<version>:<salt>:<ciphertext>`.
+//! The keys are never included.
+
+use std::net::SocketAddr;
+
+use nostr_sdk::util::hex;
+use nostr_sdk::Url;
+use serde::{Deserialize, Serialize};
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::crypto::{aes, derive_key};
+use smartvaults_core::secp256k1::rand;
+use smartvaults_core::FeeCap;
+use smartvaults_sdk_sqlite::model::RelayRole;
+
+use crate::config::FeeTargets;
+use crate::util::TimestampStyle;
+
+const PREFIX: &str = "smartvaults-settings";
+/// Current payload version
+pub const SETTINGS_EXPORT_VERSION: u8 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Aes(#[from] aes::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("invalid settings payload")]
+    InvalidPayload,
+    #[error("unsupported settings payload version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("wrong network: expected {expected}, found {found}")]
+    WrongNetwork { expected: Network, found: Network },
+}
+
+/// Serialize the restricted relay roles as `read` or `write`
+mod relay_role {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use smartvaults_sdk_sqlite::model::RelayRole;
+
+    pub fn serialize<S>(role: &Option<RelayRole>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match role {
+            Some(role) => serializer.serialize_str(&role.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<RelayRole>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)?.as_deref() {
+            Some("read") => Ok(Some(RelayRole::read_only())),
+            Some("write") => Ok(Some(RelayRole::write_only())),
+            Some(role) => Err(D::Error::custom(format!("unknown relay role: {role}"))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelaySettings {
+    pub url: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<SocketAddr>,
+    /// `None` means both read and write
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "relay_role")]
+    pub role: Option<RelayRole>,
+}
+
+/// Display preferences (owned by the frontends)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayPreferences {
+    #[serde(default)]
+    pub hide_balances: bool,
+    #[serde(default)]
+    pub timestamp_style: TimestampStyle,
+}
+
+/// Exported settings
+///
+/// New fields must have a default value, so older exports remain importable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsExport {
+    pub network: Network,
+    #[serde(default)]
+    pub relays: Vec<RelaySettings>,
+    #[serde(default)]
+    pub electrum_server: Option<String>,
+    #[serde(default)]
+    pub proxy: Option<SocketAddr>,
+    #[serde(default)]
+    pub block_explorer: Option<Url>,
+    #[serde(default)]
+    pub fee_targets: Option<FeeTargets>,
+    #[serde(default)]
+    pub fee_cap: Option<FeeCap>,
+    #[serde(default)]
+    pub display: DisplayPreferences,
+}
+
+impl SettingsExport {
+    /// Encrypt settings with `password`
+    pub fn encrypt<T>(&self, password: T) -> Result<String, Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        let salt: [u8; 16] = rand::random();
+        let key: [u8; 32] = derive_key(password.as_ref(), &salt);
+        let json: String = serde_json::to_string(self)?;
+        Ok(format!(
+            "{PREFIX}:{SETTINGS_EXPORT_VERSION}:{}:{}",
+            hex::encode(salt),
+            aes::encrypt(key, json)
+        ))
+    }
+
+    /// Decrypt settings payload
+    pub fn decrypt<S, T>(payload: S, password: T, network: Network) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+        T: AsRef<[u8]>,
+    {
+        let mut parts = payload.as_ref().trim().splitn(4, ':');
+        if parts.next() != Some(PREFIX) {
+            return Err(Error::InvalidPayload);
+        }
+
+        let version: u8 = parts
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or(Error::InvalidPayload)?;
+        let salt: Vec<u8> = parts
+            .next()
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or(Error::InvalidPayload)?;
+        let ciphertext: &str = parts.next().ok_or(Error::InvalidPayload)?;
+
+        let settings: Self = match version {
+            1 => {
+                let key: [u8; 32] = derive_key(password.as_ref(), &salt);
+                let data: Vec<u8> = aes::decrypt(key, ciphertext)?;
+                serde_json::from_slice(&data)?
+            }
+            v => return Err(Error::UnsupportedVersion(v)),
+        };
+
+        if settings.network != network {
+            return Err(Error::WrongNetwork {
+                expected: network,
+                found: settings.network,
+            });
+        }
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_export_roundtrip() {
+        let settings = SettingsExport {
+            network: Network::Testnet,
+            relays: vec![
+                RelaySettings {
+                    url: Url::parse("wss://relay.rip").unwrap(),
+                    proxy: None,
+                    role: None,
+                },
+                RelaySettings {
+                    url: Url::parse("wss://relay.damus.io").unwrap(),
+                    proxy: None,
+                    role: Some(RelayRole::write_only()),
+                },
+            ],
+            electrum_server: Some(String::from("ssl://blockstream.info:993")),
+            proxy: None,
+            block_explorer: None,
+            fee_targets: None,
+            fee_cap: None,
+            display: DisplayPreferences {
+                hide_balances: true,
+                timestamp_style: TimestampStyle::Utc,
+            },
+        };
+
+        let payload = settings.encrypt("test").unwrap();
+        assert!(payload.starts_with("smartvaults-settings:1:"));
+        assert_eq!(
+            SettingsExport::decrypt(&payload, "test", Network::Testnet).unwrap(),
+            settings
+        );
+        assert!(SettingsExport::decrypt(&payload, "wrong", Network::Testnet).is_err());
+        assert!(matches!(
+            SettingsExport::decrypt(&payload, "test", Network::Bitcoin),
+            Err(Error::WrongNetwork { .. })
+        ));
+    }
+
+    #[test]
+    fn test_settings_export_v1_defaults() {
+        // Payload with only the required fields must be importable
+        let settings: SettingsExport = serde_json::from_str(r#"{"network":"testnet"}"#).unwrap();
+        assert!(settings.relays.is_empty());
+        assert_eq!(settings.display, DisplayPreferences::default());
+    }
+
+    #[test]
+    fn test_relay_role_serialization() {
+        let relay: RelaySettings =
+            serde_json::from_str(r#"{"url":"wss://relay.rip","role":"read"}"#).unwrap();
+        assert_eq!(relay.role, Some(RelayRole::read_only()));
+        assert_eq!(
+            serde_json::to_string(&relay).unwrap(),
+            r#"{"url":"wss://relay.rip","role":"read"}"#
+        );
+        assert!(serde_json::from_str::<RelaySettings>(
+            r#"{"url":"wss://relay.rip","role":"admin"}"#
+        )
+        .is_err());
+    }
+}
@@ -64,6 +64,19 @@ where
     Ok(path.join(format!("{public_key}.db"))) // TODO: update extension to `sqlite3` if needed a breaking change in DB migrations
 }
 
+pub(crate) fn lock_file<P>(
+    base_path: P,
+    network: Network,
+    public_key: PublicKey,
+) -> Result<PathBuf, Error>
+where
+    P: AsRef<Path>,
+{
+    let path = network_path(base_path, network)?.join("users");
+    std::fs::create_dir_all(path.as_path())?;
+    Ok(path.join(format!("{public_key}.lock")))
+}
+
 pub(crate) fn nostr_db<P>(
     base_path: P,
     public_key: PublicKey,
@@ -77,6 +90,57 @@ where
     Ok(path.join(format!("{public_key}.db")))
 }
 
+/// Remove the keychain file named `name`
+pub(crate) fn remove_keychain<P>(base_path: P, network: Network, name: &str) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let keychains_path = keychains_path(base_path, network)?;
+    for entry in std::fs::read_dir(keychains_path)? {
+        let path: PathBuf = entry?.path();
+        if path.is_file() && path.file_stem().and_then(|s| s.to_str()) == Some(name) {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rename the keychain file named `from` to `to`, atomically replacing the existing one
+pub(crate) fn replace_keychain<P>(
+    base_path: P,
+    network: Network,
+    from: &str,
+    to: &str,
+) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    let keychains_path = keychains_path(base_path, network)?;
+    for entry in std::fs::read_dir(&keychains_path)? {
+        let path: PathBuf = entry?.path();
+        if path.is_file() && path.file_stem().and_then(|s| s.to_str()) == Some(from) {
+            // Not `set_extension`: the name can contain dots
+            let file_name: String = match path.extension() {
+                Some(extension) => format!("{to}.{}", extension.to_string_lossy()),
+                None => to.to_string(),
+            };
+            std::fs::rename(path, keychains_path.join(file_name))?;
+            return Ok(());
+        }
+    }
+    Err(std::io::Error::from(std::io::ErrorKind::NotFound).into())
+}
+
+/// Check if the keychain name can be safely used as file name
+pub(crate) fn is_valid_keychain_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'))
+}
+
 pub(crate) fn get_keychains_list<P>(base_path: P, network: Network) -> Result<Vec<String>, Error>
 where
     P: AsRef<Path>,
@@ -84,3 +148,20 @@ where
     let keychains_path = keychains_path(base_path, network)?;
     dir::get_keychains_list(keychains_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keychain_name_validation() {
+        assert!(is_valid_keychain_name("my-keychain_2"));
+        assert!(is_valid_keychain_name("Main wallet"));
+        assert!(!is_valid_keychain_name(""));
+        assert!(!is_valid_keychain_name(".hidden"));
+        assert!(!is_valid_keychain_name("../other"));
+        assert!(!is_valid_keychain_name("a/b"));
+        assert!(!is_valid_keychain_name("a\\b"));
+        assert!(!is_valid_keychain_name(&"a".repeat(65)));
+    }
+}
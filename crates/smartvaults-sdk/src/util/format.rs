@@ -1,7 +1,17 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
+use core::fmt;
+
+use chrono::{Local, SecondsFormat, TimeZone, Utc};
+use nostr_sdk::Timestamp;
+
+use super::TimestampStyle;
+
 const SCALES: [(u8, &str); 4] = [(1, "K"), (2, "M"), (3, "Bn"), (4, "T")];
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S %Z";
+/// Older timestamps are rendered as absolute date by [`relative_datetime`]
+const RELATIVE_MAX_SECS: u64 = 7 * 86_400;
 
 pub fn number(num: u64) -> String {
     let mut number: String = num.to_string();
@@ -43,6 +53,95 @@ pub fn big_number(num: u64) -> String {
     number
 }
 
+/// Format seconds as a short human readable duration (ex. `1d 2h`, `5m 10s`)
+pub fn duration(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {}s", secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Format [`Timestamp`] in the `tz` time zone (ex. `2024-03-31 03:00:00 CEST`)
+pub fn datetime_in<Tz>(timestamp: Timestamp, tz: &Tz) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    match tz.timestamp_opt(timestamp.as_u64() as i64, 0).single() {
+        Some(datetime) => datetime.format(DATETIME_FORMAT).to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Format [`Timestamp`] with the global [`TimestampStyle`] preference
+pub fn datetime(timestamp: Timestamp) -> String {
+    match super::timestamp_style() {
+        TimestampStyle::Local => datetime_in(timestamp, &Local),
+        TimestampStyle::Utc => datetime_in(timestamp, &Utc),
+    }
+}
+
+/// Format [`Timestamp`] as ISO-8601 UTC (ex. `2024-03-01T12:00:00Z`), for JSON and exports
+pub fn iso_datetime(timestamp: Timestamp) -> String {
+    match Utc.timestamp_opt(timestamp.as_u64() as i64, 0).single() {
+        Some(datetime) => datetime.to_rfc3339_opts(SecondsFormat::Secs, true),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Format [`Timestamp`] relative to now (ex. `3 hours ago`, `in 2 days`)
+///
+/// Timestamps more than a week away are formatted with [`datetime`].
+pub fn relative_datetime(timestamp: Timestamp) -> String {
+    relative_datetime_at(timestamp, Timestamp::now())
+}
+
+fn relative_datetime_at(timestamp: Timestamp, now: Timestamp) -> String {
+    let (secs, future): (u64, bool) = if timestamp > now {
+        (timestamp.as_u64() - now.as_u64(), true)
+    } else {
+        (now.as_u64() - timestamp.as_u64(), false)
+    };
+
+    if secs >= RELATIVE_MAX_SECS {
+        return datetime(timestamp);
+    }
+
+    if secs < 60 {
+        return String::from("just now");
+    }
+
+    let (value, unit): (u64, &str) = if secs < 3_600 {
+        (secs / 60, "minute")
+    } else if secs < 86_400 {
+        (secs / 3_600, "hour")
+    } else {
+        (secs / 86_400, "day")
+    };
+    let plural: &str = if value == 1 { "" } else { "s" };
+
+    if future {
+        format!("in {value} {unit}{plural}")
+    } else {
+        format!("{value} {unit}{plural} ago")
+    }
+}
+
+/// Format a period (ex. proposal [`Period`](smartvaults_core::proposal::Period)) with [`datetime`]
+pub fn period(from: u64, to: u64) -> String {
+    format!(
+        "{} - {}",
+        datetime(Timestamp::from(from)),
+        datetime(Timestamp::from(to))
+    )
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -66,4 +165,79 @@ mod test {
         assert_eq!(big_number(1_000_000), "1M".to_string());
         assert_eq!(big_number(1_000_000_000), "1Bn".to_string());
     }
+
+    #[test]
+    fn format_duration() {
+        assert_eq!(duration(45), "45s".to_string());
+        assert_eq!(duration(310), "5m 10s".to_string());
+        assert_eq!(duration(7_500), "2h 5m".to_string());
+        assert_eq!(duration(93_600), "1d 2h".to_string());
+    }
+
+    #[test]
+    fn format_datetime_dst_boundaries() {
+        use chrono_tz::Europe::Rome;
+        use chrono_tz::US::Eastern;
+
+        // Europe: 2024-03-31 01:00 UTC, clocks jump from 02:00 to 03:00
+        assert_eq!(
+            datetime_in(Timestamp::from(1711846799), &Rome),
+            "2024-03-31 01:59:59 CET"
+        );
+        assert_eq!(
+            datetime_in(Timestamp::from(1711846800), &Rome),
+            "2024-03-31 03:00:00 CEST"
+        );
+
+        // Europe: 2024-10-27 01:00 UTC, clocks go back from 03:00 to 02:00
+        assert_eq!(
+            datetime_in(Timestamp::from(1729990799), &Rome),
+            "2024-10-27 02:59:59 CEST"
+        );
+        assert_eq!(
+            datetime_in(Timestamp::from(1729990800), &Rome),
+            "2024-10-27 02:00:00 CET"
+        );
+
+        // US: 2024-03-10 07:00 UTC, clocks jump from 02:00 to 03:00
+        assert_eq!(
+            datetime_in(Timestamp::from(1710053999), &Eastern),
+            "2024-03-10 01:59:59 EST"
+        );
+        assert_eq!(
+            datetime_in(Timestamp::from(1710054000), &Eastern),
+            "2024-03-10 03:00:00 EDT"
+        );
+
+        assert_eq!(
+            datetime_in(Timestamp::from(1711846800), &Utc),
+            "2024-03-31 01:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn format_iso_datetime() {
+        assert_eq!(
+            iso_datetime(Timestamp::from(1709294400)),
+            "2024-03-01T12:00:00Z"
+        );
+    }
+
+    #[test]
+    fn format_relative_datetime() {
+        let now = Timestamp::from(1709294400);
+        assert_eq!(relative_datetime_at(now, now), "just now");
+        assert_eq!(
+            relative_datetime_at(Timestamp::from(1709294400 - 60), now),
+            "1 minute ago"
+        );
+        assert_eq!(
+            relative_datetime_at(Timestamp::from(1709294400 - 3 * 3_600), now),
+            "3 hours ago"
+        );
+        assert_eq!(
+            relative_datetime_at(Timestamp::from(1709294400 + 2 * 86_400), now),
+            "in 2 days"
+        );
+    }
 }
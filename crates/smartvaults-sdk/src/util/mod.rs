@@ -1,24 +1,191 @@
 // Copyright (c) 2022-2024 Smart Vaults
 // Distributed under the MIT software license
 
-use nostr_sdk::{EventId, PublicKey};
+use core::fmt;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use nostr_sdk::nips::nip19::{FromBech32, ToBech32};
+use nostr_sdk::{EventId, PublicKey, Timestamp};
+use serde::{Deserialize, Serialize};
 use smartvaults_core::bitcoin::Txid;
+use thiserror::Error;
 
 pub(crate) mod dir;
 pub mod format;
+pub(crate) mod network;
+pub mod progress;
+pub mod ur;
+
+static PUBLIC_KEY_STYLE: AtomicU8 = AtomicU8::new(PublicKeyStyle::Hex as u8);
+static TIMESTAMP_STYLE: AtomicU8 = AtomicU8::new(TimestampStyle::Local as u8);
+
+#[derive(Debug, Error)]
+#[error("Invalid public key: {0}")]
+pub struct InvalidPublicKey(String);
+
+/// [`PublicKey`] display style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PublicKeyStyle {
+    #[default]
+    Hex = 0,
+    Npub = 1,
+}
+
+impl fmt::Display for PublicKeyStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex => write!(f, "hex"),
+            Self::Npub => write!(f, "npub"),
+        }
+    }
+}
+
+/// [`Timestamp`] display time zone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampStyle {
+    #[default]
+    Local = 0,
+    Utc = 1,
+}
+
+impl fmt::Display for TimestampStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "local time"),
+            Self::Utc => write!(f, "UTC"),
+        }
+    }
+}
+
+/// Set the global [`TimestampStyle`] preference (see [`format::datetime`])
+pub fn set_timestamp_style(style: TimestampStyle) {
+    TIMESTAMP_STYLE.store(style as u8, Ordering::SeqCst);
+}
+
+/// Get the global [`TimestampStyle`] preference
+pub fn timestamp_style() -> TimestampStyle {
+    match TIMESTAMP_STYLE.load(Ordering::SeqCst) {
+        1 => TimestampStyle::Utc,
+        _ => TimestampStyle::Local,
+    }
+}
+
+/// Set the global [`PublicKeyStyle`] preference
+pub fn set_public_key_style(style: PublicKeyStyle) {
+    PUBLIC_KEY_STYLE.store(style as u8, Ordering::SeqCst);
+}
+
+/// Get the global [`PublicKeyStyle`] preference
+pub fn public_key_style() -> PublicKeyStyle {
+    match PUBLIC_KEY_STYLE.load(Ordering::SeqCst) {
+        1 => PublicKeyStyle::Npub,
+        _ => PublicKeyStyle::Hex,
+    }
+}
+
+/// Format [`PublicKey`] as hex or npub
+pub fn format_public_key(pk: PublicKey, style: PublicKeyStyle) -> String {
+    match style {
+        PublicKeyStyle::Hex => pk.to_string(),
+        PublicKeyStyle::Npub => pk.to_bech32().unwrap_or_else(|_| pk.to_string()),
+    }
+}
+
+/// Format [`PublicKey`] with the global [`PublicKeyStyle`] preference
+pub fn display_public_key(pk: PublicKey) -> String {
+    format_public_key(pk, public_key_style())
+}
+
+/// Parse [`PublicKey`] from hex, npub or `nostr:` URI
+pub fn parse_public_key(pk: &str) -> Result<PublicKey, InvalidPublicKey> {
+    let pk: &str = pk.trim();
+    let pk: &str = pk.strip_prefix("nostr:").unwrap_or(pk);
+    PublicKey::from_hex(pk)
+        .or_else(|_| PublicKey::from_bech32(pk))
+        .map_err(|_| InvalidPublicKey(pk.to_string()))
+}
+
+/// Get the first [`PublicKey`] that appear more than once
+pub(crate) fn find_duplicated_public_key(public_keys: &[PublicKey]) -> Option<PublicKey> {
+    let mut seen: HashSet<PublicKey> = HashSet::with_capacity(public_keys.len());
+    public_keys.iter().copied().find(|pk| !seen.insert(*pk))
+}
 
 /// Get the first 8 chars of an [`EventId`]
 pub fn cut_event_id(event_id: EventId) -> String {
     event_id.to_string()[..8].to_string()
 }
 
-/// Get the first 8 chars of a [`PublicKey`]
+/// Get the first and last 8 chars of a [`PublicKey`], formatted with the global [`PublicKeyStyle`] preference
+///
+/// The `npub1` prefix is not counted.
 pub fn cut_public_key(pk: PublicKey) -> String {
-    let pk = pk.to_string();
-    format!("{}:{}", &pk[0..8], &pk[pk.len() - 8..])
+    let pk: String = display_public_key(pk);
+    let start: usize = if pk.starts_with("npub1") { 13 } else { 8 };
+    format!("{}:{}", &pk[0..start], &pk[pk.len() - 8..])
 }
 
 /// Get the first 8 chars of an [`Txid`]
 pub fn cut_txid(txid: Txid) -> String {
     txid.to_string()[..8].to_string()
 }
+
+/// Get the start of the month (UTC) of a [`Timestamp`]
+pub fn start_of_month(timestamp: Timestamp) -> Timestamp {
+    const DAY: u64 = 86_400;
+
+    // Day of month from the days since epoch (http://howardhinnant.github.io/date_algorithms.html)
+    let days: u64 = timestamp.as_u64() / DAY;
+    let z: u64 = days + 719_468;
+    let doe: u64 = z % 146_097;
+    let yoe: u64 = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy: u64 = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp: u64 = (5 * doy + 2) / 153;
+    let day_of_month: u64 = doy - (153 * mp + 2) / 5 + 1;
+
+    Timestamp::from((days + 1 - day_of_month) * DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr_sdk::Keys;
+
+    use super::*;
+
+    #[test]
+    fn test_public_key_format() {
+        let hex = "68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272";
+        let npub = "npub1drvpzev3syqt0kjrls50050uzf25gehpz9vgdw08hvex7e0vgfeq0eseet";
+
+        let pk = parse_public_key(hex).unwrap();
+        assert_eq!(parse_public_key(npub).unwrap(), pk);
+        assert_eq!(parse_public_key(&format!("nostr:{npub}")).unwrap(), pk);
+        assert!(parse_public_key("npub1invalid").is_err());
+
+        assert_eq!(format_public_key(pk, PublicKeyStyle::Hex), hex);
+        assert_eq!(format_public_key(pk, PublicKeyStyle::Npub), npub);
+    }
+
+    #[test]
+    fn test_find_duplicated_public_key() {
+        let a = Keys::generate().public_key();
+        let b = Keys::generate().public_key();
+        assert_eq!(find_duplicated_public_key(&[a, b]), None);
+        assert_eq!(find_duplicated_public_key(&[a, b, a]), Some(a));
+        assert_eq!(find_duplicated_public_key(&[]), None);
+    }
+
+    #[test]
+    fn test_start_of_month() {
+        let month = Timestamp::from(1709251200); // 2024-03-01 00:00:00
+        assert_eq!(start_of_month(Timestamp::from(1710504000)), month);
+        assert_eq!(start_of_month(month), month);
+        // Leap day
+        assert_eq!(
+            start_of_month(Timestamp::from(1709251199)),
+            Timestamp::from(1706745600)
+        );
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Network checks
+//!
+//! Done as early as possible, so a mainnet address or key never reaches BDK on a test network
+//! (and vice versa).
+
+use std::str::FromStr;
+
+use smartvaults_core::bitcoin::address::NetworkUnchecked;
+use smartvaults_core::bitcoin::{Address, Network};
+use smartvaults_core::miniscript::{Descriptor, DescriptorPublicKey};
+use smartvaults_core::util::search_network_for_descriptor;
+use smartvaults_core::Proposal;
+
+use crate::Error;
+
+const NETWORKS: [Network; 4] = [
+    Network::Bitcoin,
+    Network::Testnet,
+    Network::Signet,
+    Network::Regtest,
+];
+
+/// Check if the address is valid for `network`
+pub(crate) fn check_address_network(
+    address: &Address<NetworkUnchecked>,
+    network: Network,
+) -> Result<Address, Error> {
+    if address.is_valid_for_network(network) {
+        return Ok(address.clone().assume_checked());
+    }
+
+    match NETWORKS
+        .into_iter()
+        .find(|n| address.is_valid_for_network(*n))
+    {
+        Some(found) => Err(Error::AddressNetworkMismatch {
+            address: address.clone().assume_checked().to_string(),
+            expected: network,
+            found,
+        }),
+        None => Ok(address.clone().require_network(network)?),
+    }
+}
+
+/// Check if the keys of the descriptor are for `network`
+///
+/// Testnet, signet and regtest share the same keys: only mainnet vs test networks are checked.
+pub(crate) fn check_descriptor_network(
+    descriptor: &Descriptor<String>,
+    network: Network,
+) -> Result<(), Error> {
+    if let Some(found) = search_network_for_descriptor(descriptor) {
+        if (found == Network::Bitcoin) != (network == Network::Bitcoin) {
+            return Err(Error::NetworkMismatch {
+                expected: network,
+                found,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check if the extended key is for `network`
+///
+/// Single keys have no network: always valid.
+pub(crate) fn check_key_network(key: &DescriptorPublicKey, network: Network) -> Result<(), Error> {
+    let found: Network = match key {
+        DescriptorPublicKey::XPub(xpub) => xpub.xkey.network,
+        DescriptorPublicKey::MultiXPub(xpub) => xpub.xkey.network,
+        DescriptorPublicKey::Single(..) => return Ok(()),
+    };
+    if (found == Network::Bitcoin) != (network == Network::Bitcoin) {
+        return Err(Error::NetworkMismatch {
+            expected: network,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Check the keys of a descriptor string for `network`
+///
+/// Miniscript policies are skipped: they are checked once compiled.
+pub(crate) fn check_descriptor_str_network(
+    descriptor: &str,
+    network: Network,
+) -> Result<(), Error> {
+    match Descriptor::from_str(descriptor) {
+        Ok(descriptor) => check_descriptor_network(&descriptor, network),
+        Err(_) => Ok(()),
+    }
+}
+
+/// Check descriptor and recipient of a received proposal
+pub(crate) fn check_proposal_network(proposal: &Proposal, network: Network) -> Result<(), Error> {
+    check_descriptor_network(&proposal.descriptor(), network)?;
+    if let Proposal::Spending { to_address, .. } = proposal {
+        check_address_network(to_address, network)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TESTNET_DESCRIPTOR: &str = "tr([9bf4354b/86'/1'/784923']tpubDCT8uwnkZj7woaY71Xr5hU7Wvjr7B1BXJEpwMzzDLd1H6HLnKTiaLPtt6ZfEizDMwdQ8PT8JCmKbB4ESVXTkCzv51oxhJhX5FLBvkeN9nJ3/0/*,pk([7356e457/86'/1'/784923']tpubDCvLwbJPseNux9EtPbrbA2tgDayzptK4HNkky14Cw6msjHuqyZCE88miedZD86TZUb29Rof3sgtREU4wtzofte7QDSWDiw8ZU6ZYHmAxY9d/0/*))#rs0udsfg";
+    const MAINNET_DESCRIPTOR: &str = "tr([7c997e72/86'/0'/784923']xpub6DGQCZUmD4kdGDj8ttgba5Jc6pUSkFWaMwB1jedmzer1BtKDdef18k3cWwC9k7HfJGci7Q9S5KTRD9bBn4JZm3xPcDvidkSXvZ6pg4now57/0/*)";
+    const TESTNET_ADDRESS: &str = "tb1qhk8ttr5sxpsh2uf0c7rfe5dzsxlgryrmwkg7ht";
+    const MAINNET_ADDRESS: &str = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+
+    #[test]
+    fn test_address_network() {
+        let address = Address::from_str(TESTNET_ADDRESS).unwrap();
+        assert!(check_address_network(&address, Network::Testnet).is_ok());
+        assert!(check_address_network(&address, Network::Signet).is_ok());
+
+        let address = Address::from_str(MAINNET_ADDRESS).unwrap();
+        match check_address_network(&address, Network::Testnet) {
+            Err(e @ Error::AddressNetworkMismatch { .. }) => {
+                assert!(matches!(
+                    e,
+                    Error::AddressNetworkMismatch {
+                        expected: Network::Testnet,
+                        found: Network::Bitcoin,
+                        ..
+                    }
+                ));
+                let msg: String = e.to_string();
+                assert!(msg.contains("testnet") && msg.contains("bitcoin"));
+            }
+            res => panic!("unexpected result: {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_descriptor_network() {
+        let descriptor = Descriptor::from_str(TESTNET_DESCRIPTOR).unwrap();
+        assert!(check_descriptor_network(&descriptor, Network::Testnet).is_ok());
+        assert!(check_descriptor_network(&descriptor, Network::Regtest).is_ok());
+        assert!(matches!(
+            check_descriptor_network(&descriptor, Network::Bitcoin),
+            Err(Error::NetworkMismatch {
+                expected: Network::Bitcoin,
+                found: Network::Testnet
+            })
+        ));
+
+        assert!(matches!(
+            check_descriptor_str_network(MAINNET_DESCRIPTOR, Network::Signet),
+            Err(Error::NetworkMismatch {
+                expected: Network::Signet,
+                found: Network::Bitcoin
+            })
+        ));
+        // Miniscript policy
+        assert!(check_descriptor_str_network("thresh(2,pk(A),pk(B))", Network::Bitcoin).is_ok());
+
+        let descriptor = Descriptor::from_str(MAINNET_DESCRIPTOR).unwrap();
+        assert!(check_descriptor_network(&descriptor, Network::Bitcoin).is_ok());
+        let err = check_descriptor_network(&descriptor, Network::Testnet).unwrap_err();
+        let msg: String = err.to_string();
+        assert!(msg.contains("testnet") && msg.contains("bitcoin"));
+    }
+
+    #[test]
+    fn test_key_network() {
+        let key = DescriptorPublicKey::from_str("[7c997e72/86'/0'/784923']xpub6DGQCZUmD4kdGDj8ttgba5Jc6pUSkFWaMwB1jedmzer1BtKDdef18k3cWwC9k7HfJGci7Q9S5KTRD9bBn4JZm3xPcDvidkSXvZ6pg4now57/0/*").unwrap();
+        assert!(check_key_network(&key, Network::Bitcoin).is_ok());
+        assert!(matches!(
+            check_key_network(&key, Network::Testnet),
+            Err(Error::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Bitcoin
+            })
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! Progress reporting and cancellation of long-running operations
+
+use core::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Progress of a long-running operation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Processed items
+    pub done: usize,
+    /// Total items, `None` if not known yet
+    pub total: Option<usize>,
+}
+
+impl Progress {
+    pub fn new(done: usize, total: usize) -> Self {
+        Self {
+            done,
+            total: Some(total),
+        }
+    }
+
+    /// Completion percentage (`0.0` - `100.0`), `None` if the total is not known
+    pub fn percentage(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(100.0),
+            Some(total) => Some(self.done.min(total) as f64 / total as f64 * 100.0),
+            None => None,
+        }
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.total, self.percentage()) {
+            (Some(total), Some(percentage)) => {
+                write!(f, "{}/{total} ({percentage:.0}%)", self.done)
+            }
+            _ => write!(f, "{}", self.done),
+        }
+    }
+}
+
+/// Callback called every time a long-running operation makes progress
+pub type ProgressCallback = Arc<dyn Fn(Progress) + Send + Sync>;
+
+/// Cooperative cancellation of a long-running operation
+///
+/// The operation stops at the next checkpoint and return [`Error::Cancelled`](crate::Error::Cancelled).
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Progress callback and cancellation token of an operation
+#[derive(Clone, Default)]
+pub(crate) struct Task {
+    progress: Option<ProgressCallback>,
+    token: CancellationToken,
+}
+
+impl Task {
+    pub fn new(progress: Option<ProgressCallback>, token: CancellationToken) -> Self {
+        Self { progress, token }
+    }
+
+    pub fn report(&self, progress: Progress) {
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+
+    /// Return [`Error::Cancelled`](crate::Error::Cancelled) if the operation was cancelled
+    pub fn checkpoint(&self) -> Result<(), crate::Error> {
+        if self.token.is_cancelled() {
+            Err(crate::Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(Progress::new(1, 4).percentage(), Some(25.0));
+        assert_eq!(Progress::new(0, 0).percentage(), Some(100.0));
+        assert_eq!(Progress::default().percentage(), None);
+        assert_eq!(Progress::new(3, 4).to_string(), "3/4 (75%)");
+    }
+
+    #[test]
+    fn test_task() {
+        let reported = Arc::new(AtomicUsize::new(0));
+        let r = reported.clone();
+        let callback: ProgressCallback = Arc::new(move |p: Progress| {
+            r.store(p.done, Ordering::SeqCst);
+        });
+        let token = CancellationToken::new();
+        let task = Task::new(Some(callback), token.clone());
+
+        task.report(Progress::new(2, 5));
+        assert_eq!(reported.load(Ordering::SeqCst), 2);
+        assert!(task.checkpoint().is_ok());
+
+        token.cancel();
+        assert!(matches!(task.checkpoint(), Err(crate::Error::Cancelled)));
+    }
+}
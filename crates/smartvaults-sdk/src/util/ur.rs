@@ -0,0 +1,750 @@
+// Copyright (c) 2022-2024 Smart Vaults
+// Distributed under the MIT software license
+
+//! UR `crypto-account` decoder (BCR-2020-015) and `crypto-psbt` encoder/decoder (BCR-2020-006)
+//!
+//! Used by air-gapped devices (i.e. SeedSigner, Specter DIY, Keystone, Passport) to export the
+//! account xpubs and to exchange PSBTs through animated QR codes.
+
+use smartvaults_core::bitcoin::bip32::{
+    self, ChildNumber, DerivationPath, ExtendedPubKey, Fingerprint,
+};
+use smartvaults_core::bitcoin::psbt::{self, PartiallySignedTransaction};
+use smartvaults_core::bitcoin::Network;
+use smartvaults_core::miniscript::descriptor::{DescriptorXKey, Wildcard};
+use smartvaults_core::miniscript::{self, Descriptor, DescriptorPublicKey};
+use smartvaults_core::signer::{self, Signer};
+use thiserror::Error;
+
+const CRYPTO_ACCOUNT: &str = "crypto-account";
+const CRYPTO_PSBT: &str = "crypto-psbt";
+
+/// Default max fragment length of a multi-part UR (fit a QR code readable by most cameras)
+pub const DEFAULT_MAX_FRAGMENT_LEN: usize = 200;
+
+// CBOR tags
+const TAG_SCRIPT_HASH: u64 = 400;
+const TAG_WITNESS_SCRIPT_HASH: u64 = 401;
+const TAG_PUBLIC_KEY_HASH: u64 = 403;
+const TAG_WITNESS_PUBLIC_KEY_HASH: u64 = 404;
+const TAG_TAPROOT: u64 = 409;
+const TAG_COSIGNER: u64 = 410;
+const TAG_HDKEY: u64 = 303;
+const TAG_KEYPATH: u64 = 304;
+const TAG_COININFO: u64 = 305;
+const TAG_PSBT: u64 = 310;
+
+// BIP48 script types
+const BIP48_P2WSH: u32 = 2;
+const BIP48_P2TR: u32 = 3;
+
+const XPUB_VERSION_MAINNET: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+const XPUB_VERSION_TESTNET: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid UR: {0}")]
+    Ur(String),
+    #[error(transparent)]
+    BIP32(#[from] bip32::Error),
+    #[error(transparent)]
+    Miniscript(#[from] miniscript::Error),
+    #[error(transparent)]
+    Signer(#[from] signer::Error),
+    #[error(transparent)]
+    Psbt(#[from] psbt::Error),
+    #[error("empty UR")]
+    Empty,
+    #[error("expected a `{CRYPTO_ACCOUNT}` UR but found `{0}`: export the account xpubs from the device")]
+    UnexpectedType(String),
+    #[error(
+        "expected a `{CRYPTO_PSBT}` UR but found `{0}`: export the signed PSBT from the device"
+    )]
+    UnexpectedPsbtType(String),
+    #[error("incomplete multi-part UR: scan all the QR codes")]
+    Incomplete,
+    #[error("invalid CBOR: {0}")]
+    Cbor(&'static str),
+    #[error("the UR contains {found} keys but the wallet is on {expected}: export the xpubs for the right network from the device")]
+    WrongNetwork {
+        expected: &'static str,
+        found: &'static str,
+    },
+    #[error("the UR doesn't contain a BIP-48 account: export the multisig xpubs (BIP-48) from the device")]
+    MissingBip48Account,
+}
+
+/// Script expression wrapping an account key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptExpression {
+    Pkh,
+    ShWpkh,
+    Wpkh,
+    Tr,
+    ShWsh,
+    Wsh,
+    Other,
+}
+
+impl ScriptExpression {
+    fn from_tags(tags: &[u64]) -> Self {
+        match tags {
+            [TAG_PUBLIC_KEY_HASH] => Self::Pkh,
+            [TAG_SCRIPT_HASH, TAG_WITNESS_PUBLIC_KEY_HASH] => Self::ShWpkh,
+            [TAG_WITNESS_PUBLIC_KEY_HASH] => Self::Wpkh,
+            [TAG_TAPROOT] => Self::Tr,
+            [TAG_SCRIPT_HASH, TAG_WITNESS_SCRIPT_HASH, TAG_COSIGNER] => Self::ShWsh,
+            [TAG_WITNESS_SCRIPT_HASH, TAG_COSIGNER] => Self::Wsh,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Account exported in a `crypto-account`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrAccount {
+    pub script: ScriptExpression,
+    /// Account derivation path (i.e. `m/48'/0'/0'/2'`)
+    pub path: DerivationPath,
+    pub xpub: ExtendedPubKey,
+}
+
+impl UrAccount {
+    /// BIP purpose (first component of the derivation path)
+    pub fn purpose(&self) -> Option<u32> {
+        match self.path.as_ref().first() {
+            Some(ChildNumber::Hardened { index }) => Some(*index),
+            _ => None,
+        }
+    }
+
+    fn bip48_script_type(&self) -> Option<u32> {
+        match (self.purpose(), self.path.as_ref().get(3)) {
+            (Some(48), Some(ChildNumber::Hardened { index })) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Account key with origin (`[fingerprint/path]xpub/0/*`)
+    pub fn descriptor_public_key(&self, fingerprint: Fingerprint) -> DescriptorPublicKey {
+        DescriptorPublicKey::XPub(DescriptorXKey {
+            origin: Some((fingerprint, self.path.clone())),
+            xkey: self.xpub,
+            derivation_path: DerivationPath::from(vec![ChildNumber::Normal { index: 0 }]),
+            wildcard: Wildcard::Unhardened,
+        })
+    }
+}
+
+/// Decoded `crypto-account`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CryptoAccount {
+    pub fingerprint: Fingerprint,
+    pub accounts: Vec<UrAccount>,
+}
+
+impl CryptoAccount {
+    /// Decode a `crypto-account` from one or more UR parts (`ur:crypto-account/...`)
+    pub fn from_ur<S>(parts: &[S]) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let (ur_type, cbor) = decode_parts(parts)?;
+        if ur_type != CRYPTO_ACCOUNT {
+            return Err(Error::UnexpectedType(ur_type));
+        }
+        Self::from_cbor(&cbor)
+    }
+
+    /// Decode a `crypto-account` from CBOR
+    pub fn from_cbor(data: &[u8]) -> Result<Self, Error> {
+        let mut cbor = Cbor::new(data);
+        let mut fingerprint: Option<Fingerprint> = None;
+        let mut accounts: Vec<UrAccount> = Vec::new();
+
+        for _ in 0..cbor.map()? {
+            match cbor.uint()? {
+                1 => {
+                    let fp = u32::try_from(cbor.uint()?)
+                        .map_err(|_| Error::Cbor("invalid master fingerprint"))?;
+                    fingerprint = Some(Fingerprint::from(fp.to_be_bytes()));
+                }
+                2 => {
+                    for _ in 0..cbor.array()? {
+                        if let Some(account) = decode_output(&mut cbor)? {
+                            accounts.push(account);
+                        }
+                    }
+                }
+                _ => cbor.skip()?,
+            }
+        }
+
+        Ok(Self {
+            fingerprint: fingerprint.ok_or(Error::Cbor("missing master fingerprint"))?,
+            accounts,
+        })
+    }
+
+    /// Check that all the accounts are for `network`
+    pub fn check_network(&self, network: Network) -> Result<(), Error> {
+        let expected: &'static str = network_name(network);
+        for account in self.accounts.iter() {
+            // Check both the xpub version and the BIP44 coin type
+            let coin_type: Option<Network> = match account.path.as_ref().get(1) {
+                Some(ChildNumber::Hardened { index: 0 }) => Some(Network::Bitcoin),
+                Some(ChildNumber::Hardened { .. }) => Some(Network::Testnet),
+                _ => None,
+            };
+            for found in [Some(account.xpub.network), coin_type]
+                .into_iter()
+                .flatten()
+            {
+                let found: &'static str = network_name(found);
+                if found != expected {
+                    return Err(Error::WrongNetwork { expected, found });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the BIP-48 account to use for the vaults
+    ///
+    /// Prefer the taproot one (`3'`), fallback to P2WSH (`2'`).
+    pub fn bip48_account(&self) -> Result<&UrAccount, Error> {
+        [BIP48_P2TR, BIP48_P2WSH]
+            .into_iter()
+            .find_map(|script_type| {
+                self.accounts
+                    .iter()
+                    .find(|a| a.bip48_script_type() == Some(script_type))
+            })
+            .ok_or(Error::MissingBip48Account)
+    }
+
+    /// Taproot descriptor of the BIP-48 account
+    pub fn descriptor(&self) -> Result<Descriptor<DescriptorPublicKey>, Error> {
+        let account = self.bip48_account()?;
+        Ok(Descriptor::new_tr(
+            account.descriptor_public_key(self.fingerprint),
+            None,
+        )?)
+    }
+
+    /// Build AirGap [`Signer`]
+    pub fn to_signer<S>(&self, name: S, network: Network) -> Result<Signer, Error>
+    where
+        S: Into<String>,
+    {
+        self.check_network(network)?;
+        Ok(Signer::airgap(
+            name.into(),
+            None,
+            self.fingerprint,
+            self.descriptor()?,
+            network,
+        )?)
+    }
+}
+
+/// Encode a PSBT as `crypto-psbt` UR parts
+///
+/// If the PSBT doesn't fit in `max_fragment_len` bytes, the parts must be shown as an animated QR code.
+pub fn encode_psbt(
+    psbt: &PartiallySignedTransaction,
+    max_fragment_len: usize,
+) -> Result<Vec<String>, Error> {
+    let data: Vec<u8> = psbt.serialize();
+    let mut cbor: Vec<u8> = Vec::with_capacity(data.len() + 9);
+    write_header(&mut cbor, MAJOR_BYTES, data.len() as u64);
+    cbor.extend(data);
+
+    let mut encoder = ::ur::Encoder::new(&cbor, max_fragment_len.max(10), CRYPTO_PSBT)
+        .map_err(|e| Error::Ur(e.to_string()))?;
+    (0..encoder.fragment_count())
+        .map(|_| encoder.next_part().map_err(|e| Error::Ur(e.to_string())))
+        .collect()
+}
+
+/// Decode a PSBT from one or more `crypto-psbt` UR parts (`ur:crypto-psbt/...`)
+pub fn decode_psbt<S>(parts: &[S]) -> Result<PartiallySignedTransaction, Error>
+where
+    S: AsRef<str>,
+{
+    let (ur_type, cbor) = decode_parts(parts)?;
+    if ur_type != CRYPTO_PSBT {
+        return Err(Error::UnexpectedPsbtType(ur_type));
+    }
+
+    let mut cbor = Cbor::new(&cbor);
+    if cbor.peek_major()? == MAJOR_TAG {
+        cbor.expect_tag(TAG_PSBT)?;
+    }
+    Ok(PartiallySignedTransaction::deserialize(cbor.bytes()?)?)
+}
+
+/// Decode single or multi-part UR, returning the UR type and the CBOR payload
+fn decode_parts<S>(parts: &[S]) -> Result<(String, Vec<u8>), Error>
+where
+    S: AsRef<str>,
+{
+    let parts: Vec<String> = parts
+        .iter()
+        .flat_map(|p| p.as_ref().split_whitespace())
+        .map(|p| p.to_lowercase())
+        .collect();
+    let first: &String = parts.first().ok_or(Error::Empty)?;
+
+    let ur_type: String = first
+        .strip_prefix("ur:")
+        .and_then(|s| s.split('/').next())
+        .ok_or_else(|| Error::Ur(String::from("missing `ur:` prefix")))?
+        .to_string();
+
+    let cbor: Vec<u8> = if parts.len() == 1 && first.matches('/').count() == 1 {
+        let (_, data) = ::ur::decode(first).map_err(|e| Error::Ur(e.to_string()))?;
+        data
+    } else {
+        let mut decoder = ::ur::Decoder::default();
+        for part in parts.iter() {
+            decoder
+                .receive(part)
+                .map_err(|e| Error::Ur(e.to_string()))?;
+            if decoder.complete() {
+                break;
+            }
+        }
+        decoder
+            .message()
+            .map_err(|e| Error::Ur(e.to_string()))?
+            .ok_or(Error::Incomplete)?
+    };
+
+    Ok((ur_type, cbor))
+}
+
+fn network_name(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "mainnet",
+        _ => "testnet",
+    }
+}
+
+/// Decode `crypto-output`
+///
+/// Return `None` if the key expression is not a `crypto-hdkey`.
+fn decode_output(cbor: &mut Cbor) -> Result<Option<UrAccount>, Error> {
+    let mut tags: Vec<u64> = Vec::new();
+    loop {
+        match cbor.peek_major()? {
+            MAJOR_TAG => {
+                let tag = cbor.tag()?;
+                if tag == TAG_HDKEY {
+                    break;
+                }
+                tags.push(tag);
+            }
+            MAJOR_MAP => break,
+            _ => {
+                cbor.skip()?;
+                return Ok(None);
+            }
+        }
+    }
+
+    let mut key_data: Option<Vec<u8>> = None;
+    let mut chain_code: Option<Vec<u8>> = None;
+    let mut path: Option<DerivationPath> = None;
+    let mut parent_fingerprint: [u8; 4] = [0u8; 4];
+    let mut is_testnet: bool = false;
+
+    for _ in 0..cbor.map()? {
+        match cbor.uint()? {
+            3 => key_data = Some(cbor.bytes()?.to_vec()),
+            4 => chain_code = Some(cbor.bytes()?.to_vec()),
+            5 => {
+                cbor.expect_tag(TAG_COININFO)?;
+                for _ in 0..cbor.map()? {
+                    match cbor.uint()? {
+                        2 => is_testnet = cbor.uint()? == 1,
+                        _ => cbor.skip()?,
+                    }
+                }
+            }
+            6 => {
+                cbor.expect_tag(TAG_KEYPATH)?;
+                let mut components: Vec<ChildNumber> = Vec::new();
+                for _ in 0..cbor.map()? {
+                    match cbor.uint()? {
+                        1 => {
+                            let len = cbor.array()?;
+                            if len % 2 != 0 {
+                                return Err(Error::Cbor("invalid keypath components"));
+                            }
+                            for _ in 0..len / 2 {
+                                let index = u32::try_from(cbor.uint()?)
+                                    .map_err(|_| Error::Cbor("invalid keypath index"))?;
+                                components.push(if cbor.bool()? {
+                                    ChildNumber::from_hardened_idx(index)?
+                                } else {
+                                    ChildNumber::from_normal_idx(index)?
+                                });
+                            }
+                        }
+                        _ => cbor.skip()?,
+                    }
+                }
+                path = Some(DerivationPath::from(components));
+            }
+            8 => {
+                let fp = u32::try_from(cbor.uint()?)
+                    .map_err(|_| Error::Cbor("invalid parent fingerprint"))?;
+                parent_fingerprint = fp.to_be_bytes();
+            }
+            _ => cbor.skip()?,
+        }
+    }
+
+    let key_data = key_data.ok_or(Error::Cbor("missing key data"))?;
+    let chain_code = chain_code.ok_or(Error::Cbor("missing chain code"))?;
+    let path = path.ok_or(Error::Cbor("missing origin"))?;
+    let child_number: ChildNumber = *path
+        .as_ref()
+        .last()
+        .ok_or(Error::Cbor("empty origin path"))?;
+
+    if key_data.len() != 33 || chain_code.len() != 32 {
+        return Err(Error::Cbor("invalid key"));
+    }
+
+    // Serialize as BIP32 extended public key
+    let mut xpub: Vec<u8> = Vec::with_capacity(78);
+    xpub.extend_from_slice(if is_testnet {
+        &XPUB_VERSION_TESTNET
+    } else {
+        &XPUB_VERSION_MAINNET
+    });
+    xpub.push(path.as_ref().len() as u8);
+    xpub.extend_from_slice(&parent_fingerprint);
+    xpub.extend_from_slice(&u32::from(child_number).to_be_bytes());
+    xpub.extend_from_slice(&chain_code);
+    xpub.extend_from_slice(&key_data);
+
+    Ok(Some(UrAccount {
+        script: ScriptExpression::from_tags(&tags),
+        path,
+        xpub: ExtendedPubKey::decode(&xpub)?,
+    }))
+}
+
+const MAJOR_UINT: u8 = 0;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+fn write_header(buf: &mut Vec<u8>, major: u8, arg: u64) {
+    if arg < 24 {
+        buf.push((major << 5) | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        buf.push((major << 5) | 24);
+        buf.push(arg as u8);
+    } else if arg <= u16::MAX as u64 {
+        buf.push((major << 5) | 25);
+        buf.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        buf.push((major << 5) | 26);
+        buf.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        buf.push((major << 5) | 27);
+        buf.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+/// Minimal CBOR reader (definite lengths only)
+struct Cbor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cbor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end: usize = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or(Error::Cbor("unexpected end of data"))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn peek_major(&self) -> Result<u8, Error> {
+        self.data
+            .get(self.pos)
+            .map(|b| b >> 5)
+            .ok_or(Error::Cbor("unexpected end of data"))
+    }
+
+    fn header(&mut self) -> Result<(u8, u64), Error> {
+        let byte: u8 = self.read(1)?[0];
+        let major: u8 = byte >> 5;
+        let arg: u64 = match byte & 0x1f {
+            n @ 0..=23 => n as u64,
+            24 => self.read(1)?[0] as u64,
+            25 => u16::from_be_bytes([self.read(1)?[0], self.read(1)?[0]]) as u64,
+            26 => {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(self.read(4)?);
+                u32::from_be_bytes(buf) as u64
+            }
+            27 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(self.read(8)?);
+                u64::from_be_bytes(buf)
+            }
+            _ => return Err(Error::Cbor("indefinite length not supported")),
+        };
+        Ok((major, arg))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<u64, Error> {
+        let (major, arg) = self.header()?;
+        if major == expected {
+            Ok(arg)
+        } else {
+            Err(Error::Cbor("unexpected data type"))
+        }
+    }
+
+    fn uint(&mut self) -> Result<u64, Error> {
+        self.expect(MAJOR_UINT)
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.expect(MAJOR_BYTES)?;
+        self.read(len as usize)
+    }
+
+    fn array(&mut self) -> Result<u64, Error> {
+        self.expect(MAJOR_ARRAY)
+    }
+
+    fn map(&mut self) -> Result<u64, Error> {
+        self.expect(MAJOR_MAP)
+    }
+
+    fn tag(&mut self) -> Result<u64, Error> {
+        self.expect(MAJOR_TAG)
+    }
+
+    fn expect_tag(&mut self, tag: u64) -> Result<(), Error> {
+        if self.tag()? == tag {
+            Ok(())
+        } else {
+            Err(Error::Cbor("unexpected tag"))
+        }
+    }
+
+    fn bool(&mut self) -> Result<bool, Error> {
+        match self.expect(MAJOR_SIMPLE)? {
+            20 => Ok(false),
+            21 => Ok(true),
+            _ => Err(Error::Cbor("expected boolean")),
+        }
+    }
+
+    fn skip(&mut self) -> Result<(), Error> {
+        let (major, arg) = self.header()?;
+        match major {
+            MAJOR_BYTES | MAJOR_TEXT => {
+                self.read(arg as usize)?;
+            }
+            MAJOR_ARRAY => {
+                for _ in 0..arg {
+                    self.skip()?;
+                }
+            }
+            MAJOR_MAP => {
+                for _ in 0..arg * 2 {
+                    self.skip()?;
+                }
+            }
+            MAJOR_TAG => self.skip()?,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use smartvaults_core::bitcoin::bip32::ExtendedPrivKey;
+    use smartvaults_core::SECP256K1;
+
+    use super::*;
+
+    const MULTISIG_PSBT: &str = "cHNidP8BAH0CAAAAATkMna6SNbx6SUrU/Aa9k6eESYG376CB9ziYVapeQqYoAAAAAAD9////AkCcAAAAAAAAFgAUkl1AKIgL0MnWj7x/x9/ul2aYYpx45gAAAAAAACIAIEZLsUGVC+KIi8wdHLOQphaJrVCx7EE58qwnJz9Q7K+kAAAAAAABASughgEAAAAAACIAIEZLsUGVC+KIi8wdHLOQphaJrVCx7EE58qwnJz9Q7K+kAQVpUiECdZKqtdQ2GN2hP7px45k811F6cS09pJZkwG7hvT0fcK8hAuV0DmO60oCB7Xz2VN1sGQKcoDOC/AWrX13agfLFW4RbIQLsbUma79VA6QNX8QBKE2BJ0fffWtmcRMRuPtQWnkCstlOuIgYCdZKqtdQ2GN2hP7px45k811F6cS09pJZkwG7hvT0fcK8cm/Q1SzAAAIABAACAAAAAgAIAAIAAAAAAAAAAACIGAuV0DmO60oCB7Xz2VN1sGQKcoDOC/AWrX13agfLFW4RbHHNW5FcwAACAAQAAgAAAAIACAACAAAAAAAAAAAAiBgLsbUma79VA6QNX8QBKE2BJ0fffWtmcRMRuPtQWnkCsthz1emuZMAAAgAEAAIAAAACAAgAAgAAAAAAAAAAAAAABAGlSIQJ1kqq11DYY3aE/unHjmTzXUXpxLT2klmTAbuG9PR9wryEC5XQOY7rSgIHtfPZU3WwZApygM4L8BatfXdqB8sVbhFshAuxtSZrv1UDpA1fxAEoTYEnR999a2ZxExG4+1BaeQKy2U64A";
+
+    fn hdkey(buf: &mut Vec<u8>, root: &ExtendedPrivKey, path: &str, testnet: bool) {
+        let path = DerivationPath::from_str(path).unwrap();
+        let xprv = root.derive_priv(&SECP256K1, &path).unwrap();
+        let xpub = ExtendedPubKey::from_priv(&SECP256K1, &xprv);
+        let encoded: [u8; 78] = xpub.encode();
+
+        write_header(buf, MAJOR_TAG, TAG_HDKEY);
+        write_header(buf, MAJOR_MAP, 5);
+        // key-data
+        write_header(buf, MAJOR_UINT, 3);
+        write_header(buf, MAJOR_BYTES, 33);
+        buf.extend_from_slice(&encoded[45..78]);
+        // chain-code
+        write_header(buf, MAJOR_UINT, 4);
+        write_header(buf, MAJOR_BYTES, 32);
+        buf.extend_from_slice(&encoded[13..45]);
+        // use-info
+        write_header(buf, MAJOR_UINT, 5);
+        write_header(buf, MAJOR_TAG, TAG_COININFO);
+        write_header(buf, MAJOR_MAP, 1);
+        write_header(buf, MAJOR_UINT, 2);
+        write_header(buf, MAJOR_UINT, testnet as u64);
+        // origin
+        write_header(buf, MAJOR_UINT, 6);
+        write_header(buf, MAJOR_TAG, TAG_KEYPATH);
+        write_header(buf, MAJOR_MAP, 1);
+        write_header(buf, MAJOR_UINT, 1);
+        write_header(buf, MAJOR_ARRAY, path.as_ref().len() as u64 * 2);
+        for child in path.as_ref() {
+            let (index, hardened) = match child {
+                ChildNumber::Normal { index } => (*index, false),
+                ChildNumber::Hardened { index } => (*index, true),
+            };
+            write_header(buf, MAJOR_UINT, index as u64);
+            write_header(buf, MAJOR_SIMPLE, if hardened { 21 } else { 20 });
+        }
+        // parent fingerprint
+        write_header(buf, MAJOR_UINT, 8);
+        write_header(
+            buf,
+            MAJOR_UINT,
+            u32::from_be_bytes(xpub.parent_fingerprint.to_bytes()) as u64,
+        );
+    }
+
+    fn crypto_account(
+        root: &ExtendedPrivKey,
+        outputs: &[(&[u64], &str)],
+        testnet: bool,
+    ) -> Vec<u8> {
+        let fingerprint = root.fingerprint(&SECP256K1);
+        let mut buf = Vec::new();
+        write_header(&mut buf, MAJOR_MAP, 2);
+        write_header(&mut buf, MAJOR_UINT, 1);
+        write_header(
+            &mut buf,
+            MAJOR_UINT,
+            u32::from_be_bytes(fingerprint.to_bytes()) as u64,
+        );
+        write_header(&mut buf, MAJOR_UINT, 2);
+        write_header(&mut buf, MAJOR_ARRAY, outputs.len() as u64);
+        for (tags, path) in outputs.iter() {
+            for tag in tags.iter() {
+                write_header(&mut buf, MAJOR_TAG, *tag);
+            }
+            hdkey(&mut buf, root, path, testnet);
+        }
+        buf
+    }
+
+    #[test]
+    fn test_decode_crypto_account() {
+        let root = ExtendedPrivKey::new_master(Network::Testnet, &[1u8; 32]).unwrap();
+        let data = crypto_account(
+            &root,
+            &[
+                (&[TAG_WITNESS_PUBLIC_KEY_HASH], "m/84'/1'/0'"),
+                (
+                    &[TAG_SCRIPT_HASH, TAG_WITNESS_SCRIPT_HASH, TAG_COSIGNER],
+                    "m/48'/1'/0'/1'",
+                ),
+                (&[TAG_WITNESS_SCRIPT_HASH, TAG_COSIGNER], "m/48'/1'/0'/2'"),
+            ],
+            true,
+        );
+
+        let account = CryptoAccount::from_cbor(&data).unwrap();
+        assert_eq!(account.fingerprint, root.fingerprint(&SECP256K1));
+        assert_eq!(account.accounts.len(), 3);
+        assert_eq!(account.accounts[0].script, ScriptExpression::Wpkh);
+        assert_eq!(account.accounts[2].script, ScriptExpression::Wsh);
+
+        let bip48 = account.bip48_account().unwrap();
+        assert_eq!(
+            bip48.path,
+            DerivationPath::from_str("m/48'/1'/0'/2'").unwrap()
+        );
+        let xprv = root.derive_priv(&SECP256K1, &bip48.path).unwrap();
+        assert_eq!(bip48.xpub, ExtendedPubKey::from_priv(&SECP256K1, &xprv));
+
+        let signer = account.to_signer("SeedSigner", Network::Testnet).unwrap();
+        assert_eq!(signer.fingerprint(), account.fingerprint);
+
+        // Wrong network
+        assert!(matches!(
+            account.to_signer("SeedSigner", Network::Bitcoin),
+            Err(Error::WrongNetwork { .. })
+        ));
+
+        // Missing BIP-48 accounts
+        let data = crypto_account(
+            &root,
+            &[(&[TAG_WITNESS_PUBLIC_KEY_HASH], "m/84'/1'/0'")],
+            true,
+        );
+        let account = CryptoAccount::from_cbor(&data).unwrap();
+        assert!(matches!(
+            account.to_signer("SeedSigner", Network::Testnet),
+            Err(Error::MissingBip48Account)
+        ));
+    }
+
+    #[test]
+    fn test_crypto_psbt_round_trip() {
+        // 2-of-3 P2WSH
+        let psbt = PartiallySignedTransaction::from_str(MULTISIG_PSBT).unwrap();
+
+        // Multi-part (animated QR)
+        let parts = encode_psbt(&psbt, 100).unwrap();
+        assert!(parts.len() > 1);
+        assert!(parts.iter().all(|p| p.starts_with("ur:crypto-psbt/")));
+        assert_eq!(decode_psbt(&parts).unwrap(), psbt);
+
+        // Scanned as uppercase QR codes
+        let upper: Vec<String> = parts.iter().map(|p| p.to_uppercase()).collect();
+        assert_eq!(decode_psbt(&upper).unwrap(), psbt);
+
+        // Single part
+        let parts = encode_psbt(&psbt, 10_000).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(decode_psbt(&parts).unwrap(), psbt);
+
+        // Missing parts
+        let parts = encode_psbt(&psbt, 100).unwrap();
+        assert!(matches!(decode_psbt(&parts[..1]), Err(Error::Incomplete)));
+
+        // Wrong UR type
+        assert!(matches!(
+            CryptoAccount::from_ur(&parts),
+            Err(Error::UnexpectedType(..))
+        ));
+    }
+}